@@ -8,11 +8,11 @@ use chain_abci::app::check_validators;
 use chain_abci::app::*;
 use chain_abci::enclave_bridge::mock::MockClient;
 use chain_core::common::MerkleTree;
-use chain_core::compute_app_hash;
 use chain_core::init::coin::Coin;
 use chain_core::init::config::InitConfig;
 use chain_core::init::config::NetworkParameters;
 use chain_core::state::tendermint::{TendermintValidatorPubKey, TendermintVotePower};
+use chain_core::{compute_app_hash, genesis_filter_header};
 use chain_storage::{Storage, NUM_COLUMNS};
 use kvdb::KeyValueDB;
 use kvdb_memorydb::create;
@@ -183,6 +183,7 @@ fuzz_target!(|data: &[u8]| {
                                         &new_account_root,
                                         &state.rewards_pool,
                                         &network_params,
+                                        genesis_filter_header().as_ref(),
                                     );
                                     if req.chain_id.len() > 3 {
                                         if let Ok(netid) =
@@ -8,12 +8,12 @@ use parity_scale_codec::{Decode, Encode};
 use protobuf::Message;
 use serde::{Deserialize, Serialize};
 
+use crate::app::indexer::EventIndexer;
 use crate::enclave_bridge::EnclaveProxy;
 use crate::staking::StakingTable;
 use chain_core::common::MerkleTree;
 use chain_core::common::Timespec;
 use chain_core::common::{H256, HASH_SIZE_256};
-use chain_core::compute_app_hash;
 use chain_core::init::address::RedeemAddress;
 use chain_core::init::coin::Coin;
 use chain_core::init::config::InitConfig;
@@ -24,6 +24,7 @@ use chain_core::state::tendermint::{BlockHeight, TendermintVotePower};
 use chain_core::state::{ChainState, RewardsPoolState};
 use chain_core::tx::TxAux;
 use chain_core::ChainInfo;
+use chain_core::{compute_app_hash, genesis_filter_header};
 use chain_storage::buffer::{
     flush_storage, GetStaking, KVBuffer, StakingBuffer, StoreKV, StoreStaking,
 };
@@ -105,6 +106,7 @@ impl ChainNodeState {
                 account_root,
                 rewards_pool,
                 network_params,
+                filter_header: genesis_filter_header(),
             },
         }
     }
@@ -151,6 +153,8 @@ pub struct ChainNodeApp<T: EnclaveProxy> {
     pub kv_buffer: KVBuffer,
     /// mempool buffer of key-value storage
     pub mempool_kv_buffer: KVBuffer,
+    /// staking event indexer (if enabled)
+    pub indexer: Option<EventIndexer>,
 }
 
 pub fn get_validator_key(node: &CouncilNodeMeta) -> PubKey {
@@ -224,6 +228,8 @@ fn get_voting_power(
 ) -> TendermintVotePower {
     match node_address {
         StakedStateAddress::BasicRedeem(a) => TendermintVotePower::from(distribution[a].1),
+        // genesis distribution only contains redeem addresses
+        StakedStateAddress::OrTree(_) => TendermintVotePower::zero(),
     }
 }
 
@@ -237,6 +243,7 @@ pub fn init_app_hash(conf: &InitConfig, genesis_time: Timespec) -> H256 {
         &compute_staking_root(&state.accounts),
         &state.rewards_pool,
         &NetworkParameters::Genesis(conf.network_params.clone()),
+        genesis_filter_header().as_ref(),
     )
 }
 
@@ -285,6 +292,7 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
             mempool_staking_buffer: HashMap::new(),
             kv_buffer: HashMap::new(),
             mempool_kv_buffer: HashMap::new(),
+            indexer: None,
         }
     }
 
@@ -390,10 +398,17 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
                 mempool_staking_buffer: HashMap::new(),
                 kv_buffer: HashMap::new(),
                 mempool_kv_buffer: HashMap::new(),
+                indexer: None,
             }
         }
     }
 
+    /// Enables the staking event indexer (events of the following committed blocks are indexed)
+    pub fn with_indexer(mut self) -> Self {
+        self.indexer = Some(EventIndexer::default());
+        self
+    }
+
     /// Handles InitChain requests:
     /// should validate initial genesis distribution, initialize everything in the key-value DB and check it matches the expected values
     /// provided as arguments.
@@ -440,6 +455,7 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
             &new_account_root,
             &state.rewards_pool,
             &network_params,
+            genesis_filter_header().as_ref(),
         );
 
         if self.genesis_app_hash != genesis_app_hash {
@@ -115,9 +115,20 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
             &top_level.account_root,
             &top_level.rewards_pool,
             &top_level.network_params,
+            top_level.filter_header.as_ref(),
         );
         new_state.last_apphash = app_hash;
 
+        if let Some(indexer) = self.indexer.as_mut() {
+            // the index isn't a part of the consensus state, so the block is committed anyway
+            if let Err(e) = indexer.flush(&mut kv_store!(self), new_state.last_block_height) {
+                log::error!(
+                    "failed to index staking events at height {}: {}",
+                    new_state.last_block_height,
+                    e
+                );
+            }
+        }
         chain_storage::store_txs_merkle_tree(&mut kv_store!(self), &app_hash, &tree.encode());
         chain_storage::store_chain_state(
             &mut kv_store!(self),
@@ -3,12 +3,13 @@ use std::convert::TryInto;
 use crate::app::app_init::ChainNodeApp;
 use crate::enclave_bridge::EnclaveProxy;
 use abci::{Event, Pair as KVPair, PubKey, RequestEndBlock, ResponseEndBlock, ValidatorUpdate};
-use chain_core::common::TendermintEventType;
+use chain_core::common::{TendermintEventKey, TendermintEventType};
+use chain_core::compute_filter_header;
 use chain_tx_filter::BlockFilter;
 use enclave_protocol::{IntraEnclaveRequest, IntraEnclaveResponseOk};
 
 impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
-    /// tags the block with the transaction filter + computes validator set changes
+    /// tags the block with the transaction filter + updates the filter header + computes validator set changes
     pub fn end_block_handler(&mut self, req: &RequestEndBlock) -> ResponseEndBlock {
         let mut resp = ResponseEndBlock::new();
         if !self.delivered_txs.is_empty() {
@@ -17,16 +18,27 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
                 .process_request(IntraEnclaveRequest::EndBlock);
             if let Ok(IntraEnclaveResponseOk::EndBlock(maybe_filter)) = end_block_resp {
                 if let Some(raw_filter) = maybe_filter {
-                    let filter = BlockFilter::from(&*raw_filter);
+                    let filter = BlockFilter::from(&raw_filter.bloom);
 
+                    let mut event = Event::new();
+                    event.field_type = TendermintEventType::BlockFilter.to_string();
+                    // the legacy bloom filter is kept for older clients
                     let (key, value) = filter.get_tendermint_kv();
                     let mut kvpair = KVPair::new();
                     kvpair.key = key;
                     kvpair.value = value;
-                    let mut event = Event::new();
-                    event.field_type = TendermintEventType::BlockFilter.to_string();
                     event.attributes.push(kvpair);
+                    let mut gcs_kvpair = KVPair::new();
+                    gcs_kvpair.key = TendermintEventKey::GolombCodedSet.into();
+                    gcs_kvpair.value = raw_filter.gcs.clone();
+                    event.attributes.push(gcs_kvpair);
                     resp.events.push(event);
+
+                    // chains started before the filter header app version don't have the filter header chain
+                    let state = self.last_state.as_mut().expect("executing end block, but no app state stored (i.e. no initchain or recovery was executed)");
+                    if let Some(filter_header) = state.top_level.filter_header.as_mut() {
+                        *filter_header = compute_filter_header(filter_header, &raw_filter.gcs);
+                    }
                 }
             } else {
                 panic!("end block request to obtain the block filter failed");
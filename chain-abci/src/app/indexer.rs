@@ -0,0 +1,342 @@
+//! # Staking event indexer
+//! Optional secondary indexes of staking events (enabled in the node configuration).
+//!
+//! The events of a block are collected during `BeginBlock` and `DeliverTx`
+//! and written to `COL_INDEX` at `Commit`, under the following keys:
+//! `index kind (1 byte) || SCALE-encoded staking address || entry number (8 bytes BE)`
+//! and the number of entries is stored under `index kind (1 byte) || SCALE-encoded staking address`.
+//!
+//! So entries of one staking address are numbered in the order of blocks and a page
+//! ("staking-events", "rewards" and "punishments" query paths) is looked up directly by the entry numbers.
+use chain_core::common::Timespec;
+use chain_core::init::coin::Coin;
+use chain_core::state::account::{CouncilNodeMeta, PunishmentKind, StakedStateAddress};
+use chain_core::state::tendermint::BlockHeight;
+use chain_core::tx::fee::Fee;
+use chain_storage::buffer::{GetKV, StoreKV};
+use chain_storage::{get_index_entry, insert_index_entry};
+use parity_scale_codec::{Decode, Encode, Error};
+use serde::{Deserialize, Serialize};
+
+use super::staking_event::StakingEvent;
+
+/// maximal number of events in one page
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+/// Secondary index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexKind {
+    /// all staking events of a staking address
+    StakingEvents = 0,
+    /// rewards distributed to a staking address
+    Rewards = 1,
+    /// slashing and jailing of a staking address
+    Punishments = 2,
+}
+
+impl IndexKind {
+    /// the index kind served by the query path
+    pub fn from_query_path(path: &str) -> Option<Self> {
+        match path {
+            "staking-events" => Some(IndexKind::StakingEvents),
+            "rewards" => Some(IndexKind::Rewards),
+            "punishments" => Some(IndexKind::Punishments),
+            _ => None,
+        }
+    }
+
+    fn prefix(self, address: &StakedStateAddress) -> Vec<u8> {
+        let mut prefix = vec![self as u8];
+        address.encode_to(&mut prefix);
+        prefix
+    }
+
+    fn entry_key(self, address: &StakedStateAddress, number: u64) -> Vec<u8> {
+        let mut key = self.prefix(address);
+        key.extend_from_slice(&number.to_be_bytes());
+        key
+    }
+
+    /// the number of entries of the staking address
+    fn len(self, db: &impl GetKV, address: &StakedStateAddress) -> Result<u64, Error> {
+        match get_index_entry(db, &self.prefix(address)) {
+            Some(len) => u64::decode(&mut len.as_slice()),
+            None => Ok(0),
+        }
+    }
+
+    fn push(
+        self,
+        db: &mut impl StoreKV,
+        address: &StakedStateAddress,
+        value: Vec<u8>,
+    ) -> Result<(), Error> {
+        let len = self.len(db, address)?;
+        insert_index_entry(db, self.entry_key(address, len), value);
+        insert_index_entry(db, self.prefix(address), (len + 1).encode());
+        Ok(())
+    }
+}
+
+/// Staking event (owned version of the emitted Tendermint events)
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+pub enum StakingEventRecord {
+    Deposit {
+        amount: Coin,
+    },
+    Unbond {
+        amount: Coin,
+        unbonded_from: Timespec,
+        fee: Fee,
+    },
+    Withdraw {
+        amount: Coin,
+    },
+    NodeJoin {
+        council_node: CouncilNodeMeta,
+    },
+    Reward {
+        amount: Coin,
+    },
+    Jail {
+        jailed_until: Timespec,
+        punishment_kind: PunishmentKind,
+    },
+    Slash {
+        bonded: Coin,
+        unbonded: Coin,
+        punishment_kind: PunishmentKind,
+    },
+    Unjail,
+}
+
+impl StakingEventRecord {
+    /// the additional (besides `StakingEvents`) index the event belongs to
+    fn secondary_index(&self) -> Option<IndexKind> {
+        match self {
+            StakingEventRecord::Reward { .. } => Some(IndexKind::Rewards),
+            StakingEventRecord::Jail { .. } | StakingEventRecord::Slash { .. } => {
+                Some(IndexKind::Punishments)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// owned copy of the emitted event
+fn to_record(event: &StakingEvent) -> (StakedStateAddress, StakingEventRecord) {
+    match event {
+        StakingEvent::Deposit(address, amount) => {
+            (**address, StakingEventRecord::Deposit { amount: *amount })
+        }
+        StakingEvent::Unbond(address, amount, unbonded_from, fee) => (
+            **address,
+            StakingEventRecord::Unbond {
+                amount: *amount,
+                unbonded_from: *unbonded_from,
+                fee: *fee,
+            },
+        ),
+        StakingEvent::Withdraw(address, amount) => {
+            (**address, StakingEventRecord::Withdraw { amount: *amount })
+        }
+        StakingEvent::NodeJoin(address, council_node) => (
+            **address,
+            StakingEventRecord::NodeJoin {
+                council_node: council_node.clone(),
+            },
+        ),
+        StakingEvent::Reward(address, amount) => {
+            (**address, StakingEventRecord::Reward { amount: *amount })
+        }
+        StakingEvent::Jail(address, jailed_until, punishment_kind) => (
+            **address,
+            StakingEventRecord::Jail {
+                jailed_until: *jailed_until,
+                punishment_kind: *punishment_kind,
+            },
+        ),
+        StakingEvent::Slash(address, bonded, unbonded, punishment_kind) => (
+            **address,
+            StakingEventRecord::Slash {
+                bonded: *bonded,
+                unbonded: *unbonded,
+                punishment_kind: *punishment_kind,
+            },
+        ),
+        StakingEvent::Unjail(address) => (**address, StakingEventRecord::Unjail),
+    }
+}
+
+/// Indexed staking event
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+pub struct IndexedStakingEvent {
+    /// height of the block which emitted the event
+    pub block_height: BlockHeight,
+    /// staking address the event relates to
+    pub staking_address: StakedStateAddress,
+    /// the event
+    pub event: StakingEventRecord,
+}
+
+/// Query data (SCALE-encoded) of the index query paths
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct IndexQuery {
+    /// staking address to look up
+    pub staking_address: StakedStateAddress,
+    /// page number (starting from 0)
+    pub page: u32,
+    /// number of events per page (at most `MAX_PAGE_SIZE`)
+    pub page_size: u32,
+}
+
+/// Query response (JSON-serialized) of the index query paths
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexPage {
+    /// events in the page (sorted by block height)
+    pub events: Vec<IndexedStakingEvent>,
+    /// page number
+    pub page: u32,
+    /// number of events per page
+    pub page_size: u32,
+    /// if there are more events on the following pages
+    pub has_more: bool,
+}
+
+/// Collects the staking events of the current block
+#[derive(Debug, Default)]
+pub struct EventIndexer {
+    pending: Vec<(StakedStateAddress, StakingEventRecord)>,
+}
+
+impl EventIndexer {
+    /// records the emitted event
+    pub(crate) fn add(&mut self, event: &StakingEvent) {
+        self.pending.push(to_record(event));
+    }
+
+    /// writes the collected events of the committed block to the index column
+    /// (fails if the stored number of entries can't be decoded)
+    pub fn flush(&mut self, db: &mut impl StoreKV, block_height: BlockHeight) -> Result<(), Error> {
+        for (staking_address, event) in self.pending.drain(..) {
+            let secondary_index = event.secondary_index();
+            let value = IndexedStakingEvent {
+                block_height,
+                staking_address,
+                event,
+            }
+            .encode();
+            IndexKind::StakingEvents.push(db, &staking_address, value.clone())?;
+            if let Some(kind) = secondary_index {
+                kind.push(db, &staking_address, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// looks up a page of the index (only the entries in the page are read)
+pub fn query_index(
+    db: &impl GetKV,
+    kind: IndexKind,
+    query: &IndexQuery,
+) -> Result<IndexPage, Error> {
+    let page_size = query.page_size.min(MAX_PAGE_SIZE).max(1);
+    let len = kind.len(db, &query.staking_address)?;
+    let start = u64::from(query.page) * u64::from(page_size);
+    let end = len.min(start + u64::from(page_size));
+    let events = (start..end)
+        .map(|number| {
+            let value = get_index_entry(db, &kind.entry_key(&query.staking_address, number))
+                .ok_or_else(|| Error::from("missing index entry"))?;
+            IndexedStakingEvent::decode(&mut value.as_slice())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(IndexPage {
+        events,
+        page: query.page,
+        page_size,
+        has_more: end < len,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chain_storage::buffer::{flush_storage, BufferStore, KVBuffer};
+    use chain_storage::NUM_COLUMNS;
+    use kvdb_memorydb::create;
+    use std::sync::Arc;
+
+    fn address(byte: u8) -> StakedStateAddress {
+        StakedStateAddress::BasicRedeem([byte; 20].into())
+    }
+
+    #[test]
+    fn check_index_pagination() {
+        let mut storage = Storage::new_db(Arc::new(create(NUM_COLUMNS)));
+        let mut indexer = EventIndexer::default();
+        let (alice, bob) = (address(1), address(2));
+        for height in 1..=5u64 {
+            let mut buffer = KVBuffer::new();
+            indexer.add(&StakingEvent::Reward(&alice, Coin::unit()));
+            indexer.add(&StakingEvent::Deposit(&bob, Coin::one()));
+            if height == 3 {
+                indexer.add(&StakingEvent::Slash(
+                    &alice,
+                    Coin::one(),
+                    Coin::zero(),
+                    PunishmentKind::NonLive,
+                ));
+            }
+            indexer
+                .flush(
+                    &mut BufferStore::new(&storage, &mut buffer),
+                    BlockHeight::new(height),
+                )
+                .unwrap();
+            flush_storage(&mut storage, buffer).unwrap();
+        }
+
+        let query = |kind, staking_address, page, page_size| {
+            query_index(
+                &storage,
+                kind,
+                &IndexQuery {
+                    staking_address,
+                    page,
+                    page_size,
+                },
+            )
+            .unwrap()
+        };
+
+        let first = query(IndexKind::StakingEvents, alice, 0, 4);
+        assert!(first.has_more);
+        assert_eq!(
+            first
+                .events
+                .iter()
+                .map(|e| e.block_height.value())
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3, 3]
+        );
+        let second = query(IndexKind::StakingEvents, alice, 1, 4);
+        assert!(!second.has_more);
+        assert_eq!(second.events.len(), 2);
+
+        let rewards = query(IndexKind::Rewards, alice, 0, 10);
+        assert_eq!(rewards.events.len(), 5);
+        let punishments = query(IndexKind::Punishments, alice, 0, 10);
+        assert_eq!(punishments.events.len(), 1);
+        assert_eq!(punishments.events[0].block_height, BlockHeight::new(3));
+        assert!(query(IndexKind::Punishments, bob, 0, 10).events.is_empty());
+        assert!(query(IndexKind::Rewards, bob, 0, 10).events.is_empty());
+        assert_eq!(
+            query(IndexKind::StakingEvents, bob, 0, 10).events[0].event,
+            StakingEventRecord::Deposit {
+                amount: Coin::one()
+            }
+        );
+    }
+}
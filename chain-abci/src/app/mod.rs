@@ -4,6 +4,7 @@ mod macros;
 mod app_init;
 mod commit;
 mod end_block;
+pub mod indexer;
 mod query;
 mod rewards;
 mod staking_event;
@@ -20,6 +21,7 @@ pub use self::app_init::check_validators;
 pub use self::app_init::{
     get_validator_key, init_app_hash, BufferType, ChainNodeApp, ChainNodeState,
 };
+use crate::app::indexer::EventIndexer;
 use crate::app::staking_event::StakingEvent;
 use crate::app::validate_tx::ResponseWithCodeAndLog;
 use crate::enclave_bridge::EnclaveProxy;
@@ -205,7 +207,9 @@ impl<T: EnclaveProxy + 'static> abci::Application for ChainNodeApp<T> {
                 punishment_outcome.slashed_coin.unbonded,
                 punishment_outcome.punishment_kind,
             );
-            response.events.push(event.into());
+            response
+                .events
+                .push(index_staking_event(&mut self.indexer, event));
 
             if punishment_outcome.punishment_kind == PunishmentKind::ByzantineFault {
                 let jailed_until = punishment_outcome
@@ -216,7 +220,9 @@ impl<T: EnclaveProxy + 'static> abci::Application for ChainNodeApp<T> {
                     jailed_until,
                     punishment_outcome.punishment_kind,
                 );
-                response.events.push(event.into());
+                response
+                    .events
+                    .push(index_staking_event(&mut self.indexer, event));
             }
         }
 
@@ -237,7 +243,7 @@ impl<T: EnclaveProxy + 'static> abci::Application for ChainNodeApp<T> {
         }
 
         if let Some((distributed, minted)) = self.rewards_try_distribute() {
-            let events = generate_reward_events(distributed, minted);
+            let events = generate_reward_events(distributed, minted, &mut self.indexer);
             for event in events.iter() {
                 response.events.push(event.to_owned());
             }
@@ -255,7 +261,7 @@ impl<T: EnclaveProxy + 'static> abci::Application for ChainNodeApp<T> {
         match result {
             Ok((txaux, tx_action)) => {
                 let fee_amount = tx_action.fee().to_coin();
-                let tx_events = generate_tx_events(&txaux, tx_action);
+                let tx_events = generate_tx_events(&txaux, tx_action, &mut self.indexer);
 
                 resp.set_code(0);
 
@@ -337,11 +343,23 @@ fn abci_block_height(i: i64) -> Option<BlockHeight> {
     result
 }
 
-fn generate_reward_events(distribution: RewardsDistribution, minted: Coin) -> Vec<Event> {
+/// converts the staking event to Tendermint event (and records it if the indexer is enabled)
+fn index_staking_event(indexer: &mut Option<EventIndexer>, event: StakingEvent) -> Event {
+    if let Some(indexer) = indexer.as_mut() {
+        indexer.add(&event);
+    }
+    event.into()
+}
+
+fn generate_reward_events(
+    distribution: RewardsDistribution,
+    minted: Coin,
+    indexer: &mut Option<EventIndexer>,
+) -> Vec<Event> {
     let mut events: Vec<Event> = Vec::new();
 
     for reward in distribution.iter() {
-        let event = StakingEvent::Reward(&reward.0, reward.1).into();
+        let event = index_staking_event(indexer, StakingEvent::Reward(&reward.0, reward.1));
 
         events.push(event);
     }
@@ -362,7 +380,11 @@ fn generate_reward_events(distribution: RewardsDistribution, minted: Coin) -> Ve
     events
 }
 
-fn generate_tx_events(txaux: &TxAux, tx_action: TxAction) -> Vec<abci::Event> {
+fn generate_tx_events(
+    txaux: &TxAux,
+    tx_action: TxAction,
+    indexer: &mut Option<EventIndexer>,
+) -> Vec<abci::Event> {
     let mut events = Vec::new();
 
     let mut valid_txs_event = Event::new();
@@ -381,23 +403,23 @@ fn generate_tx_events(txaux: &TxAux, tx_action: TxAction) -> Vec<abci::Event> {
 
     events.push(valid_txs_event);
 
-    let maybe_tx_staking_event = generate_tx_staking_change_event(tx_action);
+    let maybe_tx_staking_event = generate_tx_staking_change_event(&tx_action);
     if let Some(tx_staking_event) = maybe_tx_staking_event {
-        events.push(tx_staking_event);
+        events.push(index_staking_event(indexer, tx_staking_event));
     }
 
     events
 }
 
-fn generate_tx_staking_change_event(tx_action: TxAction) -> Option<abci::Event> {
+fn generate_tx_staking_change_event(tx_action: &TxAction) -> Option<StakingEvent> {
     match tx_action {
         TxAction::Enclave(tx_enclave_action) => match tx_enclave_action {
             TxEnclaveAction::Transfer { .. } => None,
             TxEnclaveAction::Deposit { deposit, .. } => {
-                Some(StakingEvent::Deposit(&deposit.0, deposit.1).into())
+                Some(StakingEvent::Deposit(&deposit.0, deposit.1))
             }
             TxEnclaveAction::Withdraw { withdraw, .. } => {
-                Some(StakingEvent::Withdraw(&withdraw.0, withdraw.1).into())
+                Some(StakingEvent::Withdraw(&withdraw.0, withdraw.1))
             }
         },
         TxAction::Public(tx_public_action) => match tx_public_action {
@@ -406,15 +428,18 @@ fn generate_tx_staking_change_event(tx_action: TxAction) -> Option<abci::Event>
                 unbonded_from,
                 fee,
                 ..
-            } => Some(StakingEvent::Unbond(&unbond.0, unbond.1, unbonded_from, fee).into()),
+            } => Some(StakingEvent::Unbond(
+                &unbond.0,
+                unbond.1,
+                *unbonded_from,
+                *fee,
+            )),
             TxPublicAction::NodeJoin {
                 address,
                 council_node,
                 ..
-            } => Some(StakingEvent::NodeJoin(&address, council_node).into()),
-            TxPublicAction::Unjail(staking_address) => {
-                Some(StakingEvent::Unjail(&staking_address).into())
-            }
+            } => Some(StakingEvent::NodeJoin(address, council_node.clone())),
+            TxPublicAction::Unjail(staking_address) => Some(StakingEvent::Unjail(staking_address)),
         },
     }
 }
@@ -1,5 +1,6 @@
-use std::convert::{TryFrom, TryInto};
+use std::convert::TryInto;
 
+use super::indexer::{query_index, IndexKind, IndexQuery};
 use super::ChainNodeApp;
 use crate::enclave_bridge::EnclaveProxy;
 use abci::*;
@@ -175,7 +176,7 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
                 );
             }
             "account" => {
-                let account_address = StakedStateAddress::try_from(_req.data.as_slice());
+                let account_address = StakedStateAddress::from_query_data(&_req.data);
                 if let (Some(state), Ok(address)) = (&self.last_state, account_address) {
                     let (account, _proof) =
                         get_with_proof(&self.storage, state.staking_version, &address);
@@ -200,7 +201,7 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
                 } else {
                     self.last_state.as_ref().map(|state| state.staking_version)
                 };
-                let account_address = StakedStateAddress::try_from(_req.data.as_slice());
+                let account_address = StakedStateAddress::from_query_data(&_req.data);
                 if let (Some(version), Ok(address)) = (mversion, account_address) {
                     let (mstaking, proof) = get_with_proof(&self.storage, version, &address);
                     resp.value = mstaking.encode();
@@ -265,6 +266,31 @@ impl<T: EnclaveProxy + 'static> ChainNodeApp<T> {
                     "sealed log not found",
                 );
             }
+            "staking-events" | "rewards" | "punishments" => {
+                let kind = IndexKind::from_query_path(&_req.path).expect("index query path");
+                if self.indexer.is_none() {
+                    resp.log += "indexer is not enabled";
+                    resp.code = 1;
+                } else {
+                    match IndexQuery::decode(&mut _req.data.as_slice()) {
+                        Ok(query) => match query_index(&self.storage, kind, &query) {
+                            Ok(page) => {
+                                resp.value = serde_json::to_string(&page)
+                                    .expect("Unable to serialize indexed staking events into json")
+                                    .into_bytes();
+                            }
+                            Err(e) => {
+                                resp.log += &format!("index lookup failed: {}", e);
+                                resp.code = 2;
+                            }
+                        },
+                        Err(_) => {
+                            resp.log += "invalid index query";
+                            resp.code = 4;
+                        }
+                    }
+                }
+            }
             _ => {
                 resp.log += "invalid path";
                 resp.code = 1;
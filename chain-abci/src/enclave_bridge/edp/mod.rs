@@ -2,16 +2,14 @@ mod server;
 
 use crate::enclave_bridge::EnclaveProxy;
 use aesm_client::AesmClient;
-use chain_core::tx::TX_AUX_SIZE;
-use chain_storage::ReadOnlyStorage;
+use chain_storage::{EnclaveDataStorage, ReadOnlyStorage};
+use enclave_protocol::codec::{StreamRead, StreamWrite};
 use enclave_protocol::{IntraEnclaveRequest, IntraEnclaveResponse};
 use enclave_runner::{
     usercalls::{AsyncListener, AsyncStream, UsercallExtension},
     EnclaveBuilder,
 };
-use parity_scale_codec::{Decode, Encode};
 use sgxs_loaders::isgx::Device;
-use std::io::{Read, Write};
 use std::sync::{mpsc::channel, Arc, Mutex};
 use std::thread::{self};
 use std::{future::Future, io, pin::Pin};
@@ -89,20 +87,15 @@ impl EnclaveProxy for TxValidationApp {
             .runner_stream
             .lock()
             .expect("lock for tx-validation request-reply");
-        stream
-            .write_all(&request.encode())
+        // requests and responses are length-prefixed, so that they can be larger than what
+        // a single read returns
+        request
+            .write_to(&mut *stream)
             .expect("write enclave request");
-        let mut request_buf = vec![0u8; 2 * TX_AUX_SIZE];
-        match stream.read(&mut request_buf) {
-            Ok(c) => match IntraEnclaveResponse::decode(&mut request_buf[..c].as_ref()) {
-                Ok(response) => response,
-                Err(e) => {
-                    log::error!("enclave response decode error {:?}", e);
-                    Err(chain_tx_validation::Error::EnclaveRejected)
-                }
-            },
+        match IntraEnclaveResponse::read_from(&mut *stream) {
+            Ok(response) => response,
             Err(e) => {
-                log::error!("enclave response decode error {:?}", e);
+                log::error!("enclave response read / decode error {:?}", e);
                 Err(chain_tx_validation::Error::EnclaveRejected)
             }
         }
@@ -203,6 +196,7 @@ pub fn temp_start_up_ra_tx_query<T: EnclaveProxy + 'static>(
     proxy: T,
     network_id: u8,
     storage: ReadOnlyStorage,
+    enclave_data: EnclaveDataStorage,
     socket_to_enclave: UnixStream,
 ) {
     if let Some(ra_config) = ra_config {
@@ -233,8 +227,14 @@ pub fn temp_start_up_ra_tx_query<T: EnclaveProxy + 'static>(
     });
 
     let (sender, receiver) = channel();
-    let mut server =
-        server::TxValidationServer::new(socket_to_enclave, proxy, storage, network_id, sender);
+    let mut server = server::TxValidationServer::new(
+        socket_to_enclave,
+        proxy,
+        storage,
+        enclave_data,
+        network_id,
+        sender,
+    );
     log::info!("starting tx-query data handling server");
     let _child_t = thread::spawn(move || server.execute());
     receiver.recv().unwrap();
@@ -1,18 +1,20 @@
 use crate::app::ChainNodeState;
 use crate::enclave_bridge::EnclaveProxy;
+use chain_core::common::MerkleTree;
 use chain_core::state::account::StakedState;
 use chain_core::state::account::StakedStateOpWitness;
+use chain_core::state::tendermint::BlockHeight;
 use chain_core::tx::data::TxId;
 use chain_storage::buffer::Get;
 use chain_storage::jellyfish::StakingGetter;
-use chain_storage::ReadOnlyStorage;
+use chain_storage::{EnclaveDataStorage, LookupItem, ReadOnlyStorage};
 use chain_tx_validation::witness::verify_tx_recover_address;
 use chain_tx_validation::ChainInfo;
 use enclave_protocol::codec::StreamWrite;
 use enclave_protocol::IntraEnclaveRequest;
 use enclave_protocol::{
-    EnclaveRequest, EnclaveResponse, IntraEnclaveResponseOk, IntraEncryptRequest,
-    ENCRYPTION_REQUEST_SIZE,
+    BlockTxIds, EnclaveRequest, EnclaveResponse, IntraEnclaveResponseOk, IntraEncryptRequest,
+    ENCRYPTION_REQUEST_SIZE, MAX_BLOCK_TXIDS_RANGE,
 };
 use parity_scale_codec::Decode;
 use std::io::Read;
@@ -23,6 +25,7 @@ pub struct TxValidationServer<T: EnclaveProxy> {
     socket_to_enclave: UnixStream,
     enclave: T,
     storage: ReadOnlyStorage,
+    enclave_data: EnclaveDataStorage,
     network_id: u8,
     start_signal: Sender<()>,
 }
@@ -32,6 +35,7 @@ impl<T: EnclaveProxy> TxValidationServer<T> {
         socket_to_enclave: UnixStream,
         enclave: T,
         storage: ReadOnlyStorage,
+        enclave_data: EnclaveDataStorage,
         network_id: u8,
         start_signal: Sender<()>,
     ) -> Self {
@@ -39,6 +43,7 @@ impl<T: EnclaveProxy> TxValidationServer<T> {
             socket_to_enclave,
             enclave,
             storage,
+            enclave_data,
             network_id,
             start_signal,
         }
@@ -59,6 +64,52 @@ impl<T: EnclaveProxy> TxValidationServer<T> {
         Some(result)
     }
 
+    fn lookup_block_txids(
+        &self,
+        start_height: BlockHeight,
+        end_height: BlockHeight,
+    ) -> Option<Vec<BlockTxIds>> {
+        if start_height > end_height
+            || end_height.value() - start_height.value() >= MAX_BLOCK_TXIDS_RANGE
+        {
+            return None;
+        }
+        let mut result = Vec::new();
+        for height in start_height.value()..=end_height.value() {
+            let block_height = BlockHeight::new(height);
+            let tree_payload =
+                chain_storage::get_historical_txs_merkle_tree(&self.storage, block_height)?;
+            let tree = match MerkleTree::<TxId>::decode(&mut tree_payload.as_slice()) {
+                Ok(tree) => tree,
+                Err(e) => {
+                    log::error!(
+                        "failed to decode transaction merkle tree at {}: {}",
+                        block_height,
+                        e
+                    );
+                    return None;
+                }
+            };
+            let txids: Vec<TxId> = tree.values().into_iter().copied().collect();
+            // only transactions with outputs have their spent metadata stored
+            let output_txids = txids
+                .iter()
+                .filter(|txid| {
+                    chain_storage::lookup_item(&self.storage, LookupItem::TxMetaSpent, txid)
+                        .is_some()
+                })
+                .copied()
+                .collect();
+            result.push(BlockTxIds {
+                block_height,
+                txs_root: tree.root_hash(),
+                txids,
+                output_txids,
+            });
+        }
+        Some(result)
+    }
+
     fn lookup_state(
         &self,
         txid: &TxId,
@@ -82,6 +133,28 @@ impl<T: EnclaveProxy> TxValidationServer<T> {
                     Ok(EnclaveRequest::GetSealedTxData { txids }) => {
                         EnclaveResponse::GetSealedTxData(self.lookup_txids(txids.iter().copied()))
                     }
+                    Ok(EnclaveRequest::GetBlockTxIds {
+                        start_height,
+                        end_height,
+                    }) => EnclaveResponse::GetBlockTxIds(
+                        self.lookup_block_txids(start_height, end_height),
+                    ),
+                    Ok(EnclaveRequest::StoreSealedTxData {
+                        sealed_logs,
+                        sync_cursor,
+                    }) => match self
+                        .enclave_data
+                        .store_tdbe_sealed_logs(&sealed_logs, sync_cursor)
+                    {
+                        Ok(()) => EnclaveResponse::StoreSealedTxData(true),
+                        Err(e) => {
+                            log::error!("failed to store sealed logs fetched by TDBE: {}", e);
+                            EnclaveResponse::StoreSealedTxData(false)
+                        }
+                    },
+                    Ok(EnclaveRequest::GetTdbeSyncCursor) => {
+                        EnclaveResponse::GetTdbeSyncCursor(self.storage.get_tdbe_sync_cursor())
+                    }
                     Ok(EnclaveRequest::EncryptTx(req)) => {
                         let result = {
                             let tx_inputs = match req.tx_inputs {
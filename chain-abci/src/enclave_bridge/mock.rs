@@ -4,7 +4,7 @@ use chain_tx_filter::BlockFilter;
 use chain_tx_validation::{
     verify_bonded_deposit_core, verify_transfer, verify_unbonded_withdraw, Error,
 };
-use enclave_protocol::IntraEnclaveResponseOk;
+use enclave_protocol::{IntraEnclaveResponseOk, RawBlockFilter};
 use mock_utils::{decrypt, seal, unseal};
 
 use super::*;
@@ -65,7 +65,10 @@ impl EnclaveProxy for MockClient {
                 .map_err(|_| Error::WrongChainHexId),
             IntraEnclaveRequest::EndBlock => {
                 let maybe_filter = if self.filter.is_modified() {
-                    Some(Box::new(self.filter.get_raw()))
+                    Some(Box::new(RawBlockFilter {
+                        bloom: self.filter.get_raw(),
+                        gcs: self.filter.get_gcs().to_bytes(),
+                    }))
                 } else {
                     None
                 };
@@ -76,6 +79,10 @@ impl EnclaveProxy for MockClient {
                 // In mock mode, client will do the encryption on their own.
                 Err(chain_tx_validation::Error::EnclaveRejected)
             }
+            IntraEnclaveRequest::NewObfuscationKey { .. } => {
+                // In mock mode, payloads are not obfuscated.
+                Ok(IntraEnclaveResponseOk::NewObfuscationKey)
+            }
             IntraEnclaveRequest::ValidateTx { request, tx_inputs } => {
                 let (tx, account, info) =
                     (request.tx.clone(), request.account.clone(), request.info);
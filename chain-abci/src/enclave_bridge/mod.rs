@@ -3,7 +3,15 @@ use enclave_protocol::{IntraEnclaveRequest, IntraEnclaveResponse};
 /// TODO: feature-guard when workspaces can be built with --features flag: https://github.com/rust-lang/cargo/issues/5015
 pub mod mock;
 
-#[cfg(all(not(feature = "mock-enclave"), feature = "edp", target_os = "linux"))]
+/// software "simulation" of the tx-validation enclave (real obfuscation, software-held keys)
+pub mod simulation;
+
+#[cfg(all(
+    not(feature = "mock-enclave"),
+    not(feature = "simulation-enclave"),
+    feature = "edp",
+    target_os = "linux"
+))]
 pub mod edp;
 
 /// Abstracts over communication with an external part that does enclave calls
@@ -0,0 +1,558 @@
+use std::sync::{Arc, Mutex};
+
+use chain_core::state::tendermint::BlockHeight;
+use chain_core::tx::data::TxId;
+use chain_core::tx::{PlainTxAux, TransactionId, TxObfuscated, TxToObfuscate, TxWithOutputs};
+use chain_tx_filter::BlockFilter;
+use chain_tx_validation::witness::verify_tx_recover_address;
+use chain_tx_validation::{
+    verify_bonded_deposit_core, verify_enclave_tx, verify_transfer, verify_unbonded_withdraw_core,
+    Error, VerifiedEnclaveTx,
+};
+use enclave_protocol::obfuscation_keys::{obfuscation_key_seal_id, ObfuscationKeyring};
+use enclave_protocol::{
+    is_basic_valid_tx_request, EncryptionRequest, IntraEnclaveResponseOk, IntraEncryptRequest,
+    RawBlockFilter, SealedLog, VerifyTxRequest,
+};
+use mock_utils::simulation::{deobfuscate, obfuscate, seal_with_key, unseal_with_key, KEY_LEN};
+use parity_scale_codec::{Decode, Encode};
+
+use super::*;
+
+/// Keys which are held in software by the simulation backend
+/// (in the real enclaves, the obfuscation key comes from TDBE and the sealing key from the CPU)
+#[derive(Clone)]
+pub struct SimulationKeys {
+    /// key used for transaction payload obfuscation until the first key rotation
+    pub obfuscation_key: [u8; KEY_LEN],
+    /// key used for sealing transaction data stored in chain-abci
+    pub sealing_key: [u8; KEY_LEN],
+}
+
+impl SimulationKeys {
+    /// parses hex-encoded keys (both are required, as there are no built-in keys)
+    pub fn from_hex(
+        obfuscation_key: Option<&str>,
+        sealing_key: Option<&str>,
+    ) -> Result<Self, String> {
+        Ok(SimulationKeys {
+            obfuscation_key: key_from_hex("simulation.obfuscation_key", obfuscation_key)?,
+            sealing_key: key_from_hex("simulation.sealing_key", sealing_key)?,
+        })
+    }
+}
+
+fn key_from_hex(name: &str, hex_key: Option<&str>) -> Result<[u8; KEY_LEN], String> {
+    let hex_key = hex_key.ok_or_else(|| {
+        format!(
+            "{} is not set (hex-encoded {}-byte key is required)",
+            name, KEY_LEN
+        )
+    })?;
+    let bytes = hex::decode(hex_key.trim()).map_err(|e| format!("{}: {}", name, e))?;
+    if bytes.len() != KEY_LEN {
+        return Err(format!("{}: expected {} bytes", name, KEY_LEN));
+    }
+    let mut key = [0u8; KEY_LEN];
+    key.copy_from_slice(&bytes);
+    Ok(key)
+}
+
+/// Runs the transaction validation and obfuscation logic of `tx-validation-next`
+/// as a normal process with software-held keys.
+/// Unlike `MockClient`, payloads are actually obfuscated with AEAD and sealed logs are encrypted,
+/// so it can be used for exercising these code paths without SGX (e.g. in CI or on dev machines).
+#[derive(Clone)]
+pub struct SimulationClient {
+    chain_hex_id: u8,
+    keys: SimulationKeys,
+    keyring: Arc<Mutex<ObfuscationKeyring>>,
+    filter: Arc<Mutex<BlockFilter>>,
+}
+
+impl SimulationClient {
+    pub fn new(chain_hex_id: u8, keys: SimulationKeys) -> Self {
+        let keyring = ObfuscationKeyring::new(BlockHeight::genesis(), keys.obfuscation_key);
+        SimulationClient {
+            chain_hex_id,
+            keys,
+            keyring: Arc::new(Mutex::new(keyring)),
+            filter: Arc::new(Mutex::new(BlockFilter::default())),
+        }
+    }
+
+    /// obfuscates the transaction payload the same way as the enclave would
+    /// (with the key active at given height)
+    pub fn encrypt(&self, block_height: BlockHeight, tx: &TxToObfuscate) -> TxObfuscated {
+        let keyring = self.keyring.lock().unwrap();
+        let (key_from, key) = keyring
+            .active(block_height)
+            .expect("keyring has the initial key");
+        obfuscate(key, key_from, tx)
+    }
+
+    /// deobfuscates a new payload validated at given height
+    /// (it has to be obfuscated with the key active at that height)
+    fn decrypt(
+        &self,
+        payload: &TxObfuscated,
+        block_height: BlockHeight,
+    ) -> Result<PlainTxAux, Error> {
+        let keyring = self.keyring.lock().unwrap();
+        let key = keyring
+            .get_for_new_payload(payload.key_from, block_height)
+            .ok_or_else(|| {
+                log::error!(
+                    "obfuscation key from height {} is not active at {}",
+                    payload.key_from,
+                    block_height
+                );
+                Error::EnclaveRejected
+            })?;
+        deobfuscate(key, payload)
+    }
+
+    /// seals the obfuscation key of a new epoch (in the real setup, this is done by TDBE)
+    pub fn seal_obfuscation_key(&self, key_from: BlockHeight, key: &[u8; KEY_LEN]) -> SealedLog {
+        seal_with_key(
+            &self.keys.sealing_key,
+            key,
+            &obfuscation_key_seal_id(key_from),
+        )
+    }
+
+    fn handle_new_obfuscation_key(
+        &self,
+        key_from: BlockHeight,
+        sealed_key: SealedLog,
+    ) -> IntraEnclaveResponse {
+        let unsealed = unseal_with_key(
+            &self.keys.sealing_key,
+            &sealed_key,
+            &obfuscation_key_seal_id(key_from),
+        )
+        .filter(|unsealed| unsealed.len() == KEY_LEN)
+        .ok_or(Error::EnclaveRejected)?;
+        let mut key = [0u8; KEY_LEN];
+        key.copy_from_slice(&unsealed);
+        self.keyring
+            .lock()
+            .unwrap()
+            .insert(key_from, key)
+            .map_err(|e| {
+                log::error!("new obfuscation key rejected: {}", e);
+                Error::EnclaveRejected
+            })?;
+        Ok(IntraEnclaveResponseOk::NewObfuscationKey)
+    }
+
+    /// seals the encryption request (in the real setup, this is done by the tx-query enclave)
+    pub fn seal_encryption_request(&self, request: &EncryptionRequest, txid: &TxId) -> SealedLog {
+        seal_with_key(&self.keys.sealing_key, &request.encode(), txid)
+    }
+
+    fn seal(&self, tx: &TxWithOutputs) -> SealedLog {
+        seal_with_key(&self.keys.sealing_key, &tx.encode(), &tx.id())
+    }
+
+    fn check_unseal<I>(&self, txids: I, sealed_logs: Vec<SealedLog>) -> Option<Vec<TxWithOutputs>>
+    where
+        I: IntoIterator<Item = TxId> + ExactSizeIterator,
+    {
+        if txids.len() != sealed_logs.len() {
+            return None;
+        }
+        txids
+            .into_iter()
+            .zip(sealed_logs.iter())
+            .map(|(txid, sealed_log)| {
+                let unsealed = unseal_with_key(&self.keys.sealing_key, sealed_log, &txid)?;
+                TxWithOutputs::decode(&mut unsealed.as_slice()).ok()
+            })
+            .collect()
+    }
+
+    fn add_view_keys(&self, tx: &TxWithOutputs) {
+        let mut filter = self.filter.lock().unwrap();
+        let allowed_view = match tx {
+            TxWithOutputs::Transfer(tx) => &tx.attributes.allowed_view,
+            TxWithOutputs::StakeWithdraw(tx) => &tx.attributes.allowed_view,
+        };
+        for view in allowed_view.iter() {
+            filter.add_view_key(&view.view_key);
+        }
+    }
+
+    fn handle_validate_tx(
+        &self,
+        request: VerifyTxRequest,
+        tx_inputs: Option<Vec<SealedLog>>,
+    ) -> IntraEnclaveResponse {
+        if let Err(e) = is_basic_valid_tx_request(&request, &tx_inputs, self.chain_hex_id) {
+            log::error!("check request failed: {}", e);
+            return Err(Error::EnclaveRejected);
+        }
+        let block_height = request.info.block_height;
+        let verified = verify_enclave_tx(
+            request.tx,
+            request.account,
+            &request.info,
+            tx_inputs,
+            |payload| self.decrypt(payload, block_height).ok(),
+            |txids, sealed_inputs| self.check_unseal(txids.into_iter(), sealed_inputs),
+        )?;
+        match verified {
+            VerifiedEnclaveTx::WithOutputs { paid_fee, tx } => {
+                let sealed_tx = self.seal(&tx);
+                self.add_view_keys(&tx);
+                Ok(IntraEnclaveResponseOk::TxWithOutputs {
+                    paid_fee,
+                    sealed_tx,
+                })
+            }
+            VerifiedEnclaveTx::DepositStake { input_coins } => {
+                Ok(IntraEnclaveResponseOk::DepositStakeTx { input_coins })
+            }
+        }
+    }
+
+    fn handle_encrypt_request(&self, request: IntraEncryptRequest) -> IntraEnclaveResponse {
+        let enc_request = unseal_with_key(
+            &self.keys.sealing_key,
+            &request.sealed_enc_request,
+            &request.txid,
+        )
+        .and_then(|unsealed| EncryptionRequest::decode(&mut unsealed.as_slice()).ok());
+        let to_obfuscate = match (enc_request, request.tx_inputs) {
+            (Some(EncryptionRequest::TransferTx(tx, witness)), Some(sealed_inputs)) => {
+                let inputs = self
+                    .check_unseal(tx.inputs.iter().map(|x| x.id), sealed_inputs)
+                    .ok_or(Error::EnclaveRejected)?;
+                verify_transfer(&tx, &witness, &request.info, inputs)?;
+                let txid = tx.id();
+                TxToObfuscate::from(PlainTxAux::TransferTx(tx, witness), txid)
+            }
+            (Some(EncryptionRequest::DepositStake(tx, witness)), Some(sealed_inputs)) => {
+                let inputs = self
+                    .check_unseal(tx.inputs.iter().map(|x| x.id), sealed_inputs)
+                    .ok_or(Error::EnclaveRejected)?;
+                verify_bonded_deposit_core(&tx, &witness, &request.info, inputs)?;
+                TxToObfuscate::from(PlainTxAux::DepositStakeTx(witness), tx.id())
+            }
+            (Some(EncryptionRequest::WithdrawStake(tx, witness)), None) => {
+                let account = request.account.ok_or(Error::EnclaveRejected)?;
+                let txid = tx.id();
+                match verify_tx_recover_address(&witness, &txid) {
+                    Ok(address) if address == account.address => {
+                        verify_unbonded_withdraw_core(&tx, &request.info, &account)?;
+                        TxToObfuscate::from(PlainTxAux::WithdrawUnbondedStakeTx(tx), txid)
+                    }
+                    _ => {
+                        log::debug!("invalid address");
+                        return Err(Error::EnclaveRejected);
+                    }
+                }
+            }
+            (_, _) => {
+                log::debug!("invalid request");
+                return Err(Error::EnclaveRejected);
+            }
+        };
+        let to_obfuscate = to_obfuscate.ok_or(Error::EnclaveRejected)?;
+        Ok(IntraEnclaveResponseOk::Encrypt(
+            self.encrypt(request.info.block_height, &to_obfuscate),
+        ))
+    }
+}
+
+impl EnclaveProxy for SimulationClient {
+    fn check_chain(&mut self, network_id: u8) -> Result<(), ()> {
+        if self.chain_hex_id == network_id {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    fn process_request(&mut self, request: IntraEnclaveRequest) -> IntraEnclaveResponse {
+        match request {
+            IntraEnclaveRequest::InitChainCheck(network_id) => self
+                .check_chain(network_id)
+                .map(|_| IntraEnclaveResponseOk::InitChainCheck)
+                .map_err(|_| Error::WrongChainHexId),
+            IntraEnclaveRequest::EndBlock => {
+                let mut filter = self.filter.lock().unwrap();
+                let maybe_filter = if filter.is_modified() {
+                    Some(Box::new(RawBlockFilter {
+                        bloom: filter.get_raw(),
+                        gcs: filter.get_gcs().to_bytes(),
+                    }))
+                } else {
+                    None
+                };
+                filter.reset();
+                Ok(IntraEnclaveResponseOk::EndBlock(maybe_filter))
+            }
+            IntraEnclaveRequest::Encrypt(request) => self.handle_encrypt_request(*request),
+            IntraEnclaveRequest::ValidateTx { request, tx_inputs } => {
+                self.handle_validate_tx(*request, tx_inputs)
+            }
+            IntraEnclaveRequest::NewObfuscationKey {
+                key_from,
+                sealed_key,
+            } => self.handle_new_obfuscation_key(key_from, sealed_key),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chain_core::common::MerkleTree;
+    use chain_core::init::address::RedeemAddress;
+    use chain_core::init::coin::Coin;
+    use chain_core::state::account::{
+        StakedState, StakedStateAddress, StakedStateOpWitness, WithdrawUnbondedTx,
+    };
+    use chain_core::tx::data::{
+        address::ExtendedAddr, attribute::TxAttributes, input::TxoPointer, output::TxOut, Tx,
+    };
+    use chain_core::tx::fee::Fee;
+    use chain_core::tx::witness::tree::RawXOnlyPubkey;
+    use chain_core::tx::witness::{TxInWitness, TxWitness};
+    use chain_core::tx::TxEnclaveAux;
+    use chain_core::ChainInfo;
+    use secp256k1::schnorrsig::schnorr_sign;
+    use secp256k1::{key::PublicKey, key::SecretKey, key::XOnlyPublicKey, Message};
+
+    const TEST_NETWORK_ID: u8 = 0xab;
+    const TEST_OBFUSCATION_KEY: [u8; KEY_LEN] = [0x0f; KEY_LEN];
+
+    fn test_keys() -> SimulationKeys {
+        SimulationKeys {
+            obfuscation_key: TEST_OBFUSCATION_KEY,
+            sealing_key: [0xf0; KEY_LEN],
+        }
+    }
+
+    fn chain_info() -> ChainInfo {
+        chain_info_at(BlockHeight::genesis())
+    }
+
+    fn chain_info_at(block_height: BlockHeight) -> ChainInfo {
+        ChainInfo {
+            min_fee_computed: Fee::new(Coin::zero()),
+            chain_hex_id: TEST_NETWORK_ID,
+            block_time: 1,
+            block_height,
+            max_evidence_age: 0,
+        }
+    }
+
+    #[test]
+    fn test_simulation_keys_from_hex() {
+        let obfuscation_key = hex::encode(TEST_OBFUSCATION_KEY);
+        let keys = SimulationKeys::from_hex(Some(&obfuscation_key), Some(&"f0".repeat(KEY_LEN)))
+            .expect("valid keys");
+        assert_eq!(keys.obfuscation_key, TEST_OBFUSCATION_KEY);
+        assert_eq!(keys.sealing_key, [0xf0; KEY_LEN]);
+
+        assert!(SimulationKeys::from_hex(Some(&obfuscation_key), None).is_err());
+        assert!(SimulationKeys::from_hex(Some(&obfuscation_key), Some("f0f0")).is_err());
+        assert!(SimulationKeys::from_hex(Some("not hex"), Some(&obfuscation_key)).is_err());
+    }
+
+    #[test]
+    fn test_simulation_obfuscation_and_sealing() {
+        let mut client = SimulationClient::new(TEST_NETWORK_ID, test_keys());
+        let secp = secp256k1::SECP256K1;
+        let secret_key = SecretKey::from_slice(&[0xcd; 32]).unwrap();
+        let public_key = PublicKey::from_secret_key(&secp, &secret_key);
+        let x_public_key = XOnlyPublicKey::from_secret_key(&secp, &secret_key);
+        let merkle_tree = MerkleTree::new(vec![RawXOnlyPubkey::from(x_public_key.serialize())]);
+        let eaddr = ExtendedAddr::OrTree(merkle_tree.root_hash());
+
+        // withdraw unbonded stake -> sealed output
+        let mut account =
+            StakedState::default(StakedStateAddress::from(RedeemAddress::from(&public_key)));
+        account.unbonded = Coin::one();
+        let tx0 = WithdrawUnbondedTx::new(
+            0,
+            vec![TxOut::new_with_timelock(eaddr.clone(), Coin::one(), 0)],
+            TxAttributes::new(TEST_NETWORK_ID),
+        );
+        let txid0 = tx0.id();
+        let witness0 = StakedStateOpWitness::new(
+            secp.sign_recoverable(&Message::from_slice(&txid0).unwrap(), &secret_key),
+        );
+        let payload0 = client.encrypt(
+            BlockHeight::genesis(),
+            &TxToObfuscate::from(PlainTxAux::WithdrawUnbondedStakeTx(tx0.clone()), txid0).unwrap(),
+        );
+        assert!(mock_utils::decrypt(&payload0).is_err());
+        let response = client.process_request(IntraEnclaveRequest::ValidateTx {
+            request: Box::new(VerifyTxRequest {
+                tx: TxEnclaveAux::WithdrawUnbondedStakeTx {
+                    no_of_outputs: 1,
+                    witness: witness0,
+                    payload: payload0,
+                },
+                account: Some(account),
+                info: chain_info(),
+            }),
+            tx_inputs: None,
+        });
+        let sealed_tx = match response {
+            Ok(IntraEnclaveResponseOk::TxWithOutputs { sealed_tx, .. }) => sealed_tx,
+            _ => panic!("valid withdraw tx rejected"),
+        };
+        assert!(mock_utils::unseal(&sealed_tx).is_err());
+
+        // transfer spending the sealed output
+        let mut tx1 = Tx::new();
+        tx1.attributes = TxAttributes::new(TEST_NETWORK_ID);
+        tx1.add_input(TxoPointer::new(txid0, 0));
+        tx1.add_output(TxOut::new(eaddr, Coin::one()));
+        let txid1 = tx1.id();
+        let witness1: TxWitness = vec![TxInWitness::TreeSig(
+            schnorr_sign(
+                &secp,
+                &Message::from_slice(&txid1).unwrap(),
+                &secret_key,
+                &mut rand::thread_rng(),
+            ),
+            merkle_tree
+                .generate_proof(RawXOnlyPubkey::from(x_public_key.serialize()))
+                .unwrap(),
+        )]
+        .into();
+        let mut payload1 = client.encrypt(
+            BlockHeight::genesis(),
+            &TxToObfuscate::from(PlainTxAux::TransferTx(tx1.clone(), witness1.clone()), txid1)
+                .unwrap(),
+        );
+        let request = |payload: TxObfuscated| IntraEnclaveRequest::ValidateTx {
+            request: Box::new(VerifyTxRequest {
+                tx: TxEnclaveAux::TransferTx {
+                    inputs: tx1.inputs.clone(),
+                    no_of_outputs: 1,
+                    payload,
+                },
+                account: None,
+                info: chain_info(),
+            }),
+            tx_inputs: Some(vec![sealed_tx.clone()]),
+        };
+        assert!(matches!(
+            client.process_request(request(payload1.clone())),
+            Ok(IntraEnclaveResponseOk::TxWithOutputs { .. })
+        ));
+
+        // tampered payload fails AEAD authentication
+        payload1.txpayload[0] ^= 1;
+        assert!(matches!(
+            client.process_request(request(payload1)),
+            Err(Error::EnclaveRejected)
+        ));
+
+        // the same transaction is rejected if inputs can't be unsealed with the sealing key
+        let mut other_client = SimulationClient::new(
+            TEST_NETWORK_ID,
+            SimulationKeys {
+                sealing_key: [1; KEY_LEN],
+                ..test_keys()
+            },
+        );
+        let payload1 = client.encrypt(
+            BlockHeight::genesis(),
+            &TxToObfuscate::from(PlainTxAux::TransferTx(tx1.clone(), witness1), txid1).unwrap(),
+        );
+        assert!(other_client.process_request(request(payload1)).is_err());
+    }
+
+    #[test]
+    fn test_simulation_key_rotation() {
+        let mut client = SimulationClient::new(TEST_NETWORK_ID, test_keys());
+        let secp = secp256k1::SECP256K1;
+        let secret_key = SecretKey::from_slice(&[0xcd; 32]).unwrap();
+        let public_key = PublicKey::from_secret_key(&secp, &secret_key);
+        let mut account =
+            StakedState::default(StakedStateAddress::from(RedeemAddress::from(&public_key)));
+        account.unbonded = Coin::one();
+        let tx = WithdrawUnbondedTx::new(
+            0,
+            vec![TxOut::new(ExtendedAddr::OrTree([0; 32]), Coin::one())],
+            TxAttributes::new(TEST_NETWORK_ID),
+        );
+        let txid = tx.id();
+        let witness = StakedStateOpWitness::new(
+            secp.sign_recoverable(&Message::from_slice(&txid).unwrap(), &secret_key),
+        );
+        let to_obfuscate =
+            TxToObfuscate::from(PlainTxAux::WithdrawUnbondedStakeTx(tx), txid).unwrap();
+        let request = |payload: TxObfuscated, block_height: u64| IntraEnclaveRequest::ValidateTx {
+            request: Box::new(VerifyTxRequest {
+                tx: TxEnclaveAux::WithdrawUnbondedStakeTx {
+                    no_of_outputs: 1,
+                    witness: witness.clone(),
+                    payload,
+                },
+                account: Some(account.clone()),
+                info: chain_info_at(block_height.into()),
+            }),
+            tx_inputs: None,
+        };
+
+        let genesis_payload = client.encrypt(BlockHeight::genesis(), &to_obfuscate);
+        assert_eq!(genesis_payload.key_from, BlockHeight::genesis());
+
+        // a new epoch is committed at height 10
+        let key_from = BlockHeight::from(10);
+        let sealed_key = client.seal_obfuscation_key(key_from, &[0x10; KEY_LEN]);
+        assert!(client
+            .process_request(IntraEnclaveRequest::NewObfuscationKey {
+                key_from,
+                sealed_key: sealed_key.clone(),
+            })
+            .is_ok());
+        // keys can't be replaced or go back in time
+        assert!(client
+            .process_request(IntraEnclaveRequest::NewObfuscationKey {
+                key_from,
+                sealed_key,
+            })
+            .is_err());
+        // the sealed key is bound to its height
+        let sealed_key = client.seal_obfuscation_key(20.into(), &[0x20; KEY_LEN]);
+        assert!(client
+            .process_request(IntraEnclaveRequest::NewObfuscationKey {
+                key_from: 30.into(),
+                sealed_key,
+            })
+            .is_err());
+
+        // the new key is only used from its height
+        assert_eq!(
+            client.encrypt(9.into(), &to_obfuscate).key_from,
+            BlockHeight::genesis()
+        );
+        let payload = client.encrypt(key_from, &to_obfuscate);
+        assert_eq!(payload.key_from, key_from);
+        assert!(deobfuscate(&TEST_OBFUSCATION_KEY, &payload).is_err());
+
+        // new transactions have to use the key active at the validated height
+        assert!(client
+            .process_request(request(genesis_payload.clone(), 9))
+            .is_ok());
+        assert!(client
+            .process_request(request(genesis_payload, 10))
+            .is_err());
+        assert!(client.process_request(request(payload.clone(), 9)).is_err());
+        assert!(client.process_request(request(payload.clone(), 10)).is_ok());
+
+        // the key is selected by `key_from`
+        let mut unknown_epoch = payload;
+        unknown_epoch.key_from = 5.into();
+        assert!(client.process_request(request(unknown_epoch, 10)).is_err());
+    }
+}
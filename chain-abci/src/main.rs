@@ -1,22 +1,37 @@
 use chain_abci::app::{sanity_check_enabled, ChainNodeApp};
-#[cfg(all(not(feature = "mock-enclave"), feature = "edp", target_os = "linux"))]
+#[cfg(all(
+    not(feature = "mock-enclave"),
+    not(feature = "simulation-enclave"),
+    feature = "edp",
+    target_os = "linux"
+))]
 use chain_abci::enclave_bridge::edp::{
     launch_tx_validation, temp_start_up_ra_tx_query, TempTxQueryOptions, TxValidationApp,
 };
-#[cfg(any(feature = "mock-enclave", not(target_os = "linux")))]
+#[cfg(any(
+    feature = "mock-enclave",
+    all(not(feature = "simulation-enclave"), not(target_os = "linux"))
+))]
 use chain_abci::enclave_bridge::mock::MockClient;
+#[cfg(all(feature = "simulation-enclave", not(feature = "mock-enclave")))]
+use chain_abci::enclave_bridge::simulation::{SimulationClient, SimulationKeys};
 use chain_abci::enclave_bridge::EnclaveProxy;
 use chain_core::init::network::{get_network, get_network_id, init_chain_id};
-use chain_storage::ReadOnlyStorage;
+use chain_storage::{EnclaveDataStorage, ReadOnlyStorage};
 use chain_storage::{Storage, StorageConfig, StorageType};
 use log::{error, info, warn};
-use ra_sp_server::config::SpRaConfig;
+use ra_sp_server::config::{AttestationProviderKind, SpRaConfig};
 use serde::{Deserialize, Serialize};
 use std::env::var;
 use std::fs::{create_dir_all, write, File};
 use std::io::BufReader;
 use std::net::SocketAddr;
-#[cfg(all(not(feature = "mock-enclave"), feature = "edp", target_os = "linux"))]
+#[cfg(all(
+    not(feature = "mock-enclave"),
+    not(feature = "simulation-enclave"),
+    feature = "edp",
+    target_os = "linux"
+))]
 use std::os::unix::net::UnixStream;
 use std::path::{Path, PathBuf};
 use structopt::StructOpt;
@@ -35,6 +50,12 @@ pub struct Config {
     launch_ra_proxy: bool,
     remote_attestation: SpRaConfig,
     data_bootstrap: TdbeConfig,
+    /// keys of the simulation backend (only used when built with `simulation-enclave`)
+    #[serde(default)]
+    simulation: SimulationConfig,
+    /// index staking events (served by "staking-events", "rewards" and "punishments" query paths)
+    #[serde(default)]
+    indexer: bool,
 }
 
 /// TODO: more concrete when ready
@@ -51,6 +72,15 @@ pub struct TdbeConfig {
     pub external_listen_address: String,
 }
 
+/// Keys held in software by the simulation backend (instead of TDBE and the CPU)
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SimulationConfig {
+    /// Hex-encoded 16-byte key used for transaction payload obfuscation until the first key rotation
+    pub obfuscation_key: Option<String>,
+    /// Hex-encoded 16-byte key used for sealing transaction data stored in chain-abci
+    pub sealing_key: Option<String>,
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -77,8 +107,18 @@ impl Default for Config {
                 ias_sig_rl_path: "/attestation/v4/sigrl/".into(),
                 // TODO: compile-time? only needed to change with version upgrades
                 ias_report_path: "/attestation/v4/report".into(),
+                attestation_provider: AttestationProviderKind::Ias,
+                cache_dir: None,
+                sig_rl_cache_ttl_secs: 3600,
+                report_cache_ttl_secs: 600,
+                report_max_stale_secs: 3600,
+                metrics_address: None,
+                record_dir: None,
+                replay_dir: None,
             },
             data_bootstrap: TdbeConfig::default(),
+            simulation: SimulationConfig::default(),
+            indexer: false,
         }
     }
 }
@@ -109,6 +149,9 @@ impl Config {
         if opt.tx_query.is_some() {
             self.tx_query = opt.tx_query.clone();
         }
+        if opt.indexer {
+            self.indexer = true;
+        }
     }
     pub fn is_valid(&self) -> bool {
         let mut valid = true;
@@ -187,27 +230,58 @@ pub struct AbciOpt {
         help = "Optional transaction query support for clients (tx query enclave listening address, e.g. mydomain.com:4444)"
     )]
     tx_query: Option<String>,
+    #[structopt(
+        long = "indexer",
+        help = "Index staking events (for \"staking-events\", \"rewards\" and \"punishments\" queries)"
+    )]
+    indexer: bool,
 }
 
 /// edp
-#[cfg(all(not(feature = "mock-enclave"), feature = "edp", target_os = "linux"))]
-fn get_enclave_proxy() -> TxValidationApp {
+#[cfg(all(
+    not(feature = "mock-enclave"),
+    not(feature = "simulation-enclave"),
+    feature = "edp",
+    target_os = "linux"
+))]
+fn get_enclave_proxy(_config: &Config) -> TxValidationApp {
     launch_tx_validation()
 }
 
 /// for development
-#[cfg(any(feature = "mock-enclave", not(target_os = "linux")))]
-fn get_enclave_proxy() -> MockClient {
+#[cfg(any(
+    feature = "mock-enclave",
+    all(not(feature = "simulation-enclave"), not(target_os = "linux"))
+))]
+fn get_enclave_proxy(_config: &Config) -> MockClient {
     warn!("Using mock (non-enclave) infrastructure");
     MockClient::new(get_network_id())
 }
 
+/// for development (real obfuscation without SGX)
+#[cfg(all(feature = "simulation-enclave", not(feature = "mock-enclave")))]
+fn get_enclave_proxy(config: &Config) -> SimulationClient {
+    warn!("Using simulation (non-enclave) infrastructure with software-held keys");
+    let keys = SimulationKeys::from_hex(
+        config.simulation.obfuscation_key.as_deref(),
+        config.simulation.sealing_key.as_deref(),
+    )
+    .expect("invalid simulation keys in config");
+    SimulationClient::new(get_network_id(), keys)
+}
+
 /// edp
-#[cfg(all(not(feature = "mock-enclave"), feature = "edp", target_os = "linux"))]
+#[cfg(all(
+    not(feature = "mock-enclave"),
+    not(feature = "simulation-enclave"),
+    feature = "edp",
+    target_os = "linux"
+))]
 fn start_up_ra_tx_query<T: EnclaveProxy + 'static>(
     config: &Config,
     proxy: T,
     storage: ReadOnlyStorage,
+    enclave_data: EnclaveDataStorage,
 ) {
     if let Some(tx_query_address) = config.tx_query.as_ref() {
         let (sender, receiver) = UnixStream::pair().expect("init tx query socket");
@@ -232,17 +306,23 @@ fn start_up_ra_tx_query<T: EnclaveProxy + 'static>(
             proxy,
             network_id,
             storage,
+            enclave_data,
             receiver,
         );
     }
 }
 
 /// for development
-#[cfg(any(feature = "mock-enclave", not(target_os = "linux")))]
+#[cfg(any(
+    feature = "mock-enclave",
+    feature = "simulation-enclave",
+    not(target_os = "linux")
+))]
 fn start_up_ra_tx_query<T: EnclaveProxy + 'static>(
     _config: &Config,
     _proxy: T,
     _storage: ReadOnlyStorage,
+    _enclave_data: EnclaveDataStorage,
 ) {
     // nothing
 }
@@ -292,7 +372,7 @@ fn main() {
                 get_network(),
                 get_network_id()
             );
-            let tx_validator = get_enclave_proxy();
+            let tx_validator = get_enclave_proxy(&config);
             if sanity_check_enabled() {
                 warn!("Enabled sanity checks");
             }
@@ -300,19 +380,28 @@ fn main() {
             let host = config.host.parse().expect("invalid host");
             let addr = SocketAddr::new(host, config.port);
             let storage = Storage::new(&StorageConfig::new(&opt.data, StorageType::Node));
-            start_up_ra_tx_query(&config, tx_validator.clone(), storage.get_read_only());
+            start_up_ra_tx_query(
+                &config,
+                tx_validator.clone(),
+                storage.get_read_only(),
+                storage.get_enclave_data_storage(),
+            );
             info!("starting up");
-            abci::run(
-                addr,
-                ChainNodeApp::new_with_storage(
-                    tx_validator,
-                    &config.genesis_app_hash,
-                    &config.chain_id,
-                    storage,
-                    config.tx_query,
-                    config.data_bootstrap.external_listen_address,
-                ),
+            let app = ChainNodeApp::new_with_storage(
+                tx_validator,
+                &config.genesis_app_hash,
+                &config.chain_id,
+                storage,
+                config.tx_query,
+                config.data_bootstrap.external_listen_address,
             );
+            let app = if config.indexer {
+                info!("staking event indexer enabled");
+                app.with_indexer()
+            } else {
+                app
+            };
+            abci::run(addr, app);
         }
     }
 }
@@ -36,7 +36,10 @@ impl StakingTable {
             return Err(NodeJoinError::BondedNotEnough.into());
         }
 
-        let isv_svn = if cfg!(feature = "mock-enclave") {
+        let isv_svn = if cfg!(any(
+            feature = "mock-enclave",
+            feature = "simulation-enclave"
+        )) {
             0
         } else {
             // FIXME: more tdbe-related checks that may be observable by abci -- e.g. key not in the mls tree already
@@ -292,6 +292,17 @@ fn check_staking_attributes(
     Ok(())
 }
 
+/// checks that the staking address (and so the witness kind) is supported by the app version of the transaction
+fn check_staking_address_version(
+    address: &StakedStateAddress,
+    attrs: &StakedStateOpAttributes,
+) -> Result<(), PublicTxError> {
+    if address.min_app_version() > attrs.app_version {
+        return Err(PublicTxError::UnsupportedVersion);
+    }
+    Ok(())
+}
+
 /// Execute public transactions against uncommitted db.
 /// If OK, returns the paid fee + affected staking address
 pub fn process_public_tx(
@@ -306,6 +317,7 @@ pub fn process_public_tx(
         // TODO: delay checking witness, as address is contained in Tx?
         TxPublicAux::UnbondStakeTx(maintx, witness) => {
             let address = verify_tx_recover_address(&witness, &maintx.id())?;
+            check_staking_address_version(&address, &maintx.attributes)?;
             if address != maintx.from_staked_account {
                 return Err(PublicTxError::StakingWitnessNotMatch);
             }
@@ -327,6 +339,7 @@ pub fn process_public_tx(
         // TODO: delay checking witness, as address is contained in Tx?
         TxPublicAux::UnjailTx(maintx, witness) => {
             let address = verify_tx_recover_address(&witness, &maintx.id())?;
+            check_staking_address_version(&address, &maintx.attributes)?;
             if address != maintx.address {
                 return Err(PublicTxError::StakingWitnessNotMatch);
             }
@@ -338,6 +351,7 @@ pub fn process_public_tx(
         // TODO: delay checking witness, as address is contained in Tx?
         TxPublicAux::NodeJoinTx(maintx, witness) => {
             let address = verify_tx_recover_address(&witness, &maintx.id())?;
+            check_staking_address_version(&address, &maintx.attributes)?;
             if address != maintx.address {
                 return Err(PublicTxError::StakingWitnessNotMatch);
             }
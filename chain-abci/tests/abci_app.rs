@@ -1,10 +1,10 @@
 use abci::*;
 use bit_vec::BitVec;
+use chain_abci::app::indexer::{IndexPage, IndexQuery, StakingEventRecord};
 use chain_abci::app::*;
 use chain_abci::enclave_bridge::mock::MockClient;
 use chain_abci::staking::StakingTable;
-use chain_core::common::{MerkleTree, Proof, H256, HASH_SIZE_256};
-use chain_core::compute_app_hash;
+use chain_core::common::{MerkleTree, Proof, TendermintEventKey, H256, HASH_SIZE_256};
 use chain_core::init::address::RedeemAddress;
 use chain_core::init::coin::Coin;
 use chain_core::init::config::InitConfig;
@@ -37,13 +37,14 @@ use chain_core::tx::{
     witness::{TxInWitness, TxWitness},
     PlainTxAux, TransactionId, TxAux, TxEnclaveAux, TxPublicAux,
 };
+use chain_core::{compute_app_hash, compute_filter_header, genesis_filter_header};
 use chain_storage::buffer::Get;
 use chain_storage::jellyfish::SparseMerkleProof;
 use chain_storage::{
     LookupItem, Storage, CHAIN_ID_KEY, COL_EXTRA, COL_NODE_INFO, GENESIS_APP_HASH_KEY,
     LAST_STATE_KEY, NUM_COLUMNS,
 };
-use chain_tx_filter::BlockFilter;
+use chain_tx_filter::{BlockFilter, GcsFilter};
 use hex::decode;
 use kvdb::KeyValueDB;
 use kvdb_memorydb::create;
@@ -227,10 +228,28 @@ fn get_dummy_app_state(app_hash: H256) -> ChainNodeState {
             account_root: [0u8; 32],
             rewards_pool: RewardsPoolState::new(0, params.get_rewards_monetary_expansion_tau()),
             network_params: params,
+            filter_header: genesis_filter_header(),
         },
     }
 }
 
+#[test]
+fn state_stored_before_filter_header_should_decode() {
+    let example_hash = [1u8; 32];
+    let mut state = get_dummy_app_state(example_hash);
+    state.top_level.filter_header = None;
+    let encoded = state.encode();
+    // the old layout didn't have the (optional) filter header at the end
+    let legacy = &encoded[..encoded.len() - 1];
+    let decoded = ChainNodeState::decode(&mut &legacy[..]).expect("decode legacy app state");
+    assert_eq!(example_hash, decoded.last_apphash);
+    assert_eq!(state.top_level, decoded.top_level);
+
+    state.top_level.filter_header = Some([2u8; 32]);
+    let decoded = ChainNodeState::decode(&mut state.encode().as_slice()).expect("decode app state");
+    assert_eq!(state.top_level, decoded.top_level);
+}
+
 #[test]
 #[should_panic]
 fn previously_stored_hash_should_match() {
@@ -303,6 +322,7 @@ fn init_chain_for(address: RedeemAddress) -> ChainNodeApp<MockClient> {
             &new_account_root,
             &genesis_state.rewards_pool,
             &get_dummy_network_params(),
+            genesis_filter_header().as_ref(),
         );
 
         let example_hash = hex::encode_upper(genesis_app_hash);
@@ -515,7 +535,7 @@ fn two_beginblocks_should_panic() {
 }
 
 fn get_block_proposer(app: &ChainNodeApp<MockClient>) -> TendermintValidatorAddress {
-    let StakedStateAddress::BasicRedeem(staking_address) = app
+    let staking_address = app
         .last_state
         .as_ref()
         .unwrap()
@@ -526,7 +546,9 @@ fn get_block_proposer(app: &ChainNodeApp<MockClient>) -> TendermintValidatorAddr
         .unwrap()
         .0;
 
-    match get_account(staking_address, app)
+    match app
+        .staking_getter(BufferType::Consensus)
+        .get(staking_address)
         .unwrap()
         .node_meta
         .unwrap()
@@ -700,6 +722,11 @@ fn endblock_should_change_block_height() {
         app.last_state.as_ref().unwrap().last_block_height
     );
     assert_eq!(0, cresp.events.len());
+    // blocks without a filter don't change the filter header
+    assert_eq!(
+        genesis_filter_header(),
+        app.last_state.as_ref().unwrap().top_level.filter_header
+    );
 }
 
 #[test]
@@ -725,18 +752,37 @@ fn valid_commit_should_persist() {
     endreq.set_height(10);
     let cresp = app.end_block(&endreq);
     assert_eq!(1, cresp.events.len());
-    assert_eq!(1, cresp.events[0].attributes.len());
+    assert_eq!(2, cresp.events[0].attributes.len());
     assert_eq!(1, app.delivered_txs.len());
     let filter = BlockFilter::try_from(cresp.events[0].attributes[0].value.as_slice())
         .expect("there should be a block filter");
+    assert_eq!(
+        cresp.events[0].attributes[1].key,
+        TendermintEventKey::GolombCodedSet
+    );
+    let gcs_filter = BlockFilter::from(
+        GcsFilter::try_from(cresp.events[0].attributes[1].value.as_slice())
+            .expect("there should be a Golomb-coded set"),
+    );
 
     assert!(filter.check_view_key(&tx.attributes.allowed_view[0].view_key));
+    assert!(gcs_filter.check_view_key(&tx.attributes.allowed_view[0].view_key));
     let sample = PublicKey::from_slice(&[
         3, 23, 183, 225, 206, 31, 159, 148, 195, 42, 67, 115, 146, 41, 248, 140, 11, 3, 51, 41,
         111, 180, 110, 143, 114, 134, 88, 73, 198, 174, 52, 184, 78,
     ])
     .expect("sample pk");
     assert!(!filter.check_view_key(&sample));
+    assert!(!gcs_filter.check_view_key(&sample));
+    // the filter is chained into the filter header committed in the app hash
+    // (if the chain has the filter header chain)
+    assert_eq!(
+        app.last_state.as_ref().unwrap().top_level.filter_header,
+        genesis_filter_header().map(|genesis_filter_header| compute_filter_header(
+            &genesis_filter_header,
+            &cresp.events[0].attributes[1].value
+        ))
+    );
 
     assert!(app
         .storage
@@ -812,6 +858,67 @@ fn no_delivered_tx_commit_should_keep_apphash() {
     assert_eq!(&old_app_hash[..], &cresp.data[..]);
 }
 
+#[test]
+fn indexer_should_serve_staking_events() {
+    let (app, txaux, _) = prepare_app_valid_tx();
+    let mut app = app.with_indexer();
+    begin_block(&mut app);
+    let mut dreq = RequestDeliverTx::default();
+    dreq.set_tx(txaux.encode());
+    assert_eq!(0, app.deliver_tx(&dreq).code);
+    let mut endreq = RequestEndBlock::default();
+    endreq.set_height(10);
+    app.end_block(&endreq);
+    app.commit(&RequestCommit::default());
+
+    // the withdrawing staking address
+    let staking_address =
+        StakedStateAddress::from_str("0x89aef553a06ab0c3173e79de1ce241a9ed3b992c").unwrap();
+    let query = |app: &mut ChainNodeApp<MockClient>, path: &str| {
+        let mut qreq = RequestQuery::new();
+        qreq.path = path.into();
+        qreq.data = IndexQuery {
+            staking_address,
+            page: 0,
+            page_size: 10,
+        }
+        .encode();
+        let qresp = app.query(&qreq);
+        assert_eq!(0, qresp.code);
+        serde_json::from_slice::<IndexPage>(&qresp.value).unwrap()
+    };
+
+    let events = query(&mut app, "staking-events");
+    assert!(!events.has_more);
+    let withdrawals = events
+        .events
+        .iter()
+        .filter(|e| matches!(e.event, StakingEventRecord::Withdraw { .. }))
+        .collect::<Vec<_>>();
+    assert_eq!(1, withdrawals.len());
+    assert_eq!(BlockHeight::new(10), withdrawals[0].block_height);
+    assert!(query(&mut app, "punishments").events.is_empty());
+    assert!(query(&mut app, "rewards")
+        .events
+        .iter()
+        .all(|e| matches!(e.event, StakingEventRecord::Reward { .. })));
+}
+
+#[test]
+fn index_query_should_fail_without_indexer() {
+    let addr = "fe7c045110b8dbf29765047380898919c5cb56f9";
+    let mut app = init_chain_for(addr.parse().unwrap());
+    let mut qreq = RequestQuery::new();
+    qreq.path = "staking-events".into();
+    qreq.data = IndexQuery {
+        staking_address: StakedStateAddress::from_str(addr).unwrap(),
+        page: 0,
+        page_size: 10,
+    }
+    .encode();
+    assert_eq!(1, app.query(&qreq).code);
+}
+
 #[test]
 fn query_should_return_an_account() {
     let addr = "fe7c045110b8dbf29765047380898919c5cb56f9";
@@ -1185,7 +1292,8 @@ fn query_should_return_proof_for_committed_tx() {
             &merkle,
             &last_state.top_level.account_root,
             &last_state.top_level.rewards_pool,
-            &last_state.top_level.network_params
+            &last_state.top_level.network_params,
+            last_state.top_level.filter_header.as_ref()
         )
         .to_vec(),
         cresp.data
@@ -296,6 +296,52 @@ fn existing_account_unbond_tx_should_verify() {
     assert!(result.is_ok());
 }
 
+#[test]
+fn tree_account_unbond_tx_should_verify() {
+    let mut storage = Storage::new_db(create_db());
+    let secp = secp256k1::SECP256K1;
+    let secret_key = SecretKey::from_slice(&[0xcd; 32]).expect("32 bytes, within curve order");
+    let (addr, merkle_tree) = get_address(&secp, &secret_key);
+    let root_hash = match addr {
+        ExtendedAddr::OrTree(root_hash) => root_hash,
+    };
+
+    let address = StakedStateAddress::OrTree(root_hash);
+    let account = StakedState::new(1, Coin::one(), Coin::zero(), 0, address, None);
+    storage.put_stakings(0, &[account]);
+    let tx = UnbondTx::new(
+        address,
+        1,
+        Coin::new(9).unwrap(),
+        StakedStateOpAttributes::new(DEFAULT_CHAIN_ID),
+    );
+    let witness = get_tx_witness(secp, &tx.id(), &secret_key, &merkle_tree);
+    let txaux = TxPublicAux::UnbondStakeTx(tx.clone(), witness.into());
+    let extra_info = get_chain_info_pub(&txaux);
+    let result = verify_public_tx(&txaux, &extra_info, NodeInfoWrap::default(), 0, &storage);
+    if chain_core::APP_VERSION < chain_core::TREE_STAKING_APP_VERSION {
+        expect_error_public(&result, PublicTxError::UnsupportedVersion);
+        return;
+    }
+    assert!(result.is_ok());
+
+    // UnsupportedVersion
+    let mut old_tx = tx.clone();
+    old_tx.attributes.app_version = chain_core::TREE_STAKING_APP_VERSION - 1;
+    let witness = get_tx_witness(secp, &old_tx.id(), &secret_key, &merkle_tree);
+    let txaux = TxPublicAux::UnbondStakeTx(old_tx, witness.into());
+    let result = verify_public_tx(&txaux, &extra_info, NodeInfoWrap::default(), 0, &storage);
+    expect_error_public(&result, PublicTxError::UnsupportedVersion);
+
+    // StakingWitnessNotMatch
+    let sk2 = SecretKey::from_slice(&[0x11; 32]).expect("32 bytes, within curve order");
+    let merkle_tree2 = get_address(&secp, &sk2).1;
+    let witness = get_tx_witness(secp, &tx.id(), &sk2, &merkle_tree2);
+    let txaux = TxPublicAux::UnbondStakeTx(tx, witness.into());
+    let result = verify_public_tx(&txaux, &extra_info, NodeInfoWrap::default(), 0, &storage);
+    expect_error_public(&result, PublicTxError::StakingWitnessNotMatch);
+}
+
 #[test]
 fn test_account_unbond_verify_fail() {
     let (txaux, tx, secret_key, storage) = prepare_app_valid_unbond_tx();
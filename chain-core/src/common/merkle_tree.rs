@@ -65,6 +65,18 @@ impl<T> Tree<T> {
         }
     }
 
+    /// Appends values of all the leaf nodes (from left to right) to given vector
+    fn collect_values<'a>(&'a self, values: &mut Vec<&'a T>) {
+        match self {
+            Tree::Empty => {}
+            Tree::Leaf { value, .. } => values.push(value),
+            Tree::Node { left, right, .. } => {
+                left.collect_values(values);
+                right.collect_values(values);
+            }
+        }
+    }
+
     /// Generates merkle path for given value. Returns `None` if given value is not present in tree.
     /// Uses depth first search (DFS) to find value in tree
     fn generate_path(&self, value: &T) -> Option<Path>
@@ -421,6 +433,13 @@ impl<T> MerkleTree<T> {
         0 == self.len
     }
 
+    /// Returns the values of leaf nodes in the order they were inserted
+    pub fn values(&self) -> Vec<&T> {
+        let mut values = Vec::with_capacity(self.len());
+        self.tree.collect_values(&mut values);
+        values
+    }
+
     /// Generates inclusion proof for given value. Returns `None` if given value is not present in merkle tree
     pub fn generate_proof(&self, value: T) -> Option<Proof<T>>
     where
@@ -497,6 +516,7 @@ mod tests {
         let tree = MerkleTree::new(values);
 
         assert!(tree.is_empty());
+        assert!(tree.values().is_empty());
         assert_eq!(EMPTY_HASH, tree.root_hash());
         assert_eq!(0, tree.height());
         assert_eq!(0, tree.len());
@@ -561,6 +581,7 @@ mod tests {
         assert_eq!(root_hash, tree.root_hash());
         assert_eq!(2, tree.height());
         assert_eq!(3, tree.len());
+        assert_eq!(vec![&"one", &"two", &"three"], tree.values());
         assert_eq!(None, tree.generate_proof("ten"));
 
         assert!(tree
@@ -65,6 +65,8 @@ pub enum TendermintEventKey {
     TxId,
     /// bloom filter of view keys
     EthBloom,
+    /// Golomb-coded set of view keys (compact replacement of `EthBloom`)
+    GolombCodedSet,
     /// affected staking address
     StakingAddress,
     /// staking operation type
@@ -103,6 +105,7 @@ impl fmt::Display for TendermintEventKey {
             TendermintEventKey::Fee => write!(f, "fee"),
             TendermintEventKey::TxId => write!(f, "txid"),
             TendermintEventKey::EthBloom => write!(f, "ethbloom"),
+            TendermintEventKey::GolombCodedSet => write!(f, "gcs"),
             TendermintEventKey::StakingAddress => write!(f, "staking_address"),
             TendermintEventKey::StakingOpType => write!(f, "staking_optype"),
             TendermintEventKey::StakingDiff => write!(f, "staking_diff"),
@@ -127,6 +130,7 @@ impl TendermintEventKey {
             TendermintEventKey::Fee => String::from("ZmVl"),
             TendermintEventKey::TxId => String::from("dHhpZA=="),
             TendermintEventKey::EthBloom => String::from("ZXRoYmxvb20="),
+            TendermintEventKey::GolombCodedSet => String::from("Z2Nz"),
             TendermintEventKey::StakingAddress => String::from("c3Rha2luZ19hZGRyZXNz"),
             TendermintEventKey::StakingOpType => String::from("c3Rha2luZ19vcHR5cGU="),
             TendermintEventKey::StakingDiff => String::from("c3Rha2luZ19kaWZm"),
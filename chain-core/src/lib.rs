@@ -33,18 +33,32 @@ use tx::fee::Fee;
 #[cfg(not(feature = "new-txid"))]
 pub const APP_VERSION: u64 = 1;
 #[cfg(feature = "new-txid")]
-/// version 2 -- 0.6.0 (not yet released --> transaction data bootstrapping, new TX types, genesis changes, TXID calculation change, app hash calculation change);
-pub const APP_VERSION: u64 = 2;
+/// version 2 -- 0.6.0 (not yet released --> transaction data bootstrapping, new TX types, genesis changes, TXID calculation change,
+/// app hash calculation change incl. the block filter header chain);
+/// version 3 -- multi-sig tree staking addresses and tree signature witnesses of staking operations
+pub const APP_VERSION: u64 = 3;
+
+/// The first app version whose chains commit the block filter header chain in the app hash.
+/// Chains started by an older version keep their state layout and app hash calculation without it
+/// (see `ChainState::filter_header`).
+pub const FILTER_HEADER_APP_VERSION: u64 = 2;
+
+/// The first app version accepting multi-sig tree staking addresses (`StakedStateAddress::OrTree`)
+/// and tree signature witnesses of staking operations (`StakedStateOpWitness::TreeSig`).
+/// Transactions declaring an older app version can only use redeem staking addresses.
+pub const TREE_STAKING_APP_VERSION: u64 = 3;
 
 /// computes the "global" application hash (used by Tendermint to check consistency + block replaying)
 /// currently: app_hash = blake3(b"app_hash" || root of valid TX merkle tree
-/// || root of account/staked state trie || blake3(scale bytes(rewards pool state)) || blake3(scale bytes(network params)))
+/// || root of account/staked state trie || blake3(scale bytes(rewards pool state)) || blake3(scale bytes(network params))
+/// || block filter header -- only if the chain has one, see `FILTER_HEADER_APP_VERSION`)
 /// TODO: cache (as many parts remain static)
 pub fn compute_app_hash(
     valid_tx_id_tree: &MerkleTree<H256>,
     account_state_root: &H256,
     reward_pool: &RewardsPoolState,
     params: &NetworkParameters,
+    filter_header: Option<&H256>,
 ) -> H256 {
     let valid_tx_part = valid_tx_id_tree.root_hash();
     let rewards_pool_part = reward_pool.hash();
@@ -55,9 +69,36 @@ pub fn compute_app_hash(
     hasher.update(&account_state_root[..]);
     hasher.update(&rewards_pool_part);
     hasher.update(&network_params_part);
+    if let Some(filter_header) = filter_header {
+        hasher.update(filter_header);
+    }
     hasher.finalize().into()
 }
 
+/// computes the block filter header, which chains the block filters (similar to BIP-157 filter headers)
+/// currently: filter_header = blake3(b"filter_header" || previous filter header || blake3(serialized block filter))
+/// (it's only updated in blocks with a filter, i.e. the ones with valid transactions)
+///
+/// As it's committed in the app hash, light clients can check block filters served by untrusted nodes
+/// against the trusted (light client-verified) headers.
+pub fn compute_filter_header(prev_filter_header: &H256, filter: &[u8]) -> H256 {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(b"filter_header");
+    hasher.update(prev_filter_header);
+    hasher.update(blake3::hash(filter).as_bytes());
+    hasher.finalize().into()
+}
+
+/// the block filter header of a chain started at the current `APP_VERSION`
+/// (the zero hash if the filter header chain is committed in the app hash, otherwise none)
+pub fn genesis_filter_header() -> Option<H256> {
+    if APP_VERSION >= FILTER_HEADER_APP_VERSION {
+        Some(H256::default())
+    } else {
+        None
+    }
+}
+
 /// External information needed for TX validation
 #[derive(Clone, Copy, Encode, Decode)]
 pub struct ChainInfo {
@@ -500,7 +500,8 @@ pub struct StakedState {
 
 /// the tree used in StakedState storage db has a hardcoded 32-byte keys,
 /// this computes a key as blake3(0 || StakedState.address) where
-/// the StakedState address itself is ETH-style address (20 bytes from keccak hash of public key)
+/// the StakedState address itself is ETH-style address (20 bytes from keccak hash of public key),
+/// or as blake3(1 || StakedState.address) where the address is a multi-sig tree root hash
 pub fn to_stake_key(address: &StakedStateAddress) -> [u8; HASH_SIZE_256] {
     match address {
         StakedStateAddress::BasicRedeem(a) => {
@@ -509,6 +510,12 @@ pub fn to_stake_key(address: &StakedStateAddress) -> [u8; HASH_SIZE_256] {
             hasher.update(a);
             hasher.finalize()
         }
+        StakedStateAddress::OrTree(root_hash) => {
+            let mut hasher = blake3::Hasher::new();
+            hasher.update(&[1u8]);
+            hasher.update(root_hash);
+            hasher.finalize()
+        }
     }
     .into()
 }
@@ -1,5 +1,6 @@
+use crate::common::{H256, HASH_SIZE_256};
 use crate::init::address::ErrorAddress;
-use crate::init::address::RedeemAddress;
+use crate::init::address::{RedeemAddress, REDEEM_ADDRESS_BYTES};
 use parity_scale_codec::{Decode, Encode, Error, Input, Output};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::convert::From;
@@ -12,6 +13,8 @@ use std::str::FromStr;
 pub enum StakedStateAddress {
     /// needs ecdsa witness
     BasicRedeem(RedeemAddress),
+    /// needs schnorr witness + merkle proof (root hash of a multi-sig tree, same as `ExtendedAddr::OrTree`)
+    OrTree(H256),
 }
 
 impl Encode for StakedStateAddress {
@@ -21,12 +24,17 @@ impl Encode for StakedStateAddress {
                 dest.push_byte(0);
                 dest.push(ra);
             }
+            StakedStateAddress::OrTree(ref root_hash) => {
+                dest.push_byte(1);
+                dest.push(root_hash);
+            }
         }
     }
 
     fn size_hint(&self) -> usize {
         match self {
             StakedStateAddress::BasicRedeem(ref addr) => addr.size_hint() + 1,
+            StakedStateAddress::OrTree(ref root_hash) => root_hash.size_hint() + 1,
         }
     }
 }
@@ -39,6 +47,10 @@ impl Decode for StakedStateAddress {
                 let addr = RedeemAddress::decode(input)?;
                 Ok(StakedStateAddress::BasicRedeem(addr))
             }
+            1 => {
+                let root_hash = H256::decode(input)?;
+                Ok(StakedStateAddress::OrTree(root_hash))
+            }
             _ => Err("No such variant in enum StakedStateAddress".into()),
         }
     }
@@ -84,12 +96,54 @@ impl<'de> Deserialize<'de> for StakedStateAddress {
 impl TryFrom<&[u8]> for StakedStateAddress {
     type Error = ErrorAddress;
 
+    /// parses a redeem address (tree addresses need `StakedStateAddress::try_from_root_hash`)
     fn try_from(c: &[u8]) -> Result<Self, Self::Error> {
         let addr = RedeemAddress::try_from(c)?;
         Ok(StakedStateAddress::BasicRedeem(addr))
     }
 }
 
+impl StakedStateAddress {
+    /// parses a multi-sig tree address from the root hash bytes
+    pub fn try_from_root_hash(c: &[u8]) -> Result<Self, ErrorAddress> {
+        if c.len() != HASH_SIZE_256 {
+            return Err(ErrorAddress::InvalidLength(c.len()));
+        }
+        let mut root_hash = [0; HASH_SIZE_256];
+        root_hash.copy_from_slice(c);
+        Ok(StakedStateAddress::OrTree(root_hash))
+    }
+
+    /// the first app version accepting this kind of address (see `TREE_STAKING_APP_VERSION`)
+    pub fn min_app_version(&self) -> u64 {
+        match self {
+            StakedStateAddress::BasicRedeem(_) => 0,
+            StakedStateAddress::OrTree(_) => crate::TREE_STAKING_APP_VERSION,
+        }
+    }
+
+    /// the data of "account" / "staking" ABCI queries:
+    /// raw bytes of redeem addresses (as before) and SCALE bytes of other addresses (so that the variant is explicit)
+    pub fn to_query_data(&self) -> Vec<u8> {
+        match self {
+            StakedStateAddress::BasicRedeem(a) => a.to_vec(),
+            StakedStateAddress::OrTree(_) => self.encode(),
+        }
+    }
+
+    /// parses the data of "account" / "staking" ABCI queries (see `to_query_data`)
+    pub fn from_query_data(data: &[u8]) -> Result<Self, ErrorAddress> {
+        if data.len() == REDEEM_ADDRESS_BYTES {
+            return StakedStateAddress::try_from(data);
+        }
+        let mut input = data;
+        match StakedStateAddress::decode(&mut input) {
+            Ok(address @ StakedStateAddress::OrTree(_)) if input.is_empty() => Ok(address),
+            _ => Err(ErrorAddress::InvalidLength(data.len())),
+        }
+    }
+}
+
 impl From<RedeemAddress> for StakedStateAddress {
     fn from(addr: RedeemAddress) -> Self {
         StakedStateAddress::BasicRedeem(addr)
@@ -100,6 +154,7 @@ impl fmt::Display for StakedStateAddress {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             StakedStateAddress::BasicRedeem(a) => write!(f, "{}", a),
+            StakedStateAddress::OrTree(root_hash) => write!(f, "0x{}", hex::encode(root_hash)),
         }
     }
 }
@@ -108,7 +163,17 @@ impl FromStr for StakedStateAddress {
     type Err = ErrorAddress;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(StakedStateAddress::BasicRedeem(RedeemAddress::from_str(s)?))
+        let value = if s.starts_with("0x") {
+            s.split_at(2).1
+        } else {
+            s
+        };
+
+        if value.len() == HASH_SIZE_256 * 2 {
+            StakedStateAddress::try_from_root_hash(&hex::decode(value)?)
+        } else {
+            Ok(StakedStateAddress::BasicRedeem(RedeemAddress::from_str(s)?))
+        }
     }
 }
 
@@ -116,6 +181,43 @@ impl AsRef<[u8]> for StakedStateAddress {
     fn as_ref(&self) -> &[u8] {
         match self {
             StakedStateAddress::BasicRedeem(a) => &a,
+            StakedStateAddress::OrTree(root_hash) => root_hash,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_or_tree_address_roundtrip() {
+        let address = StakedStateAddress::OrTree([0xab; 32]);
+
+        assert_eq!(
+            address,
+            StakedStateAddress::decode(&mut address.encode().as_slice()).unwrap()
+        );
+        assert_eq!(
+            address,
+            StakedStateAddress::from_str(&address.to_string()).unwrap()
+        );
+
+        let redeem = StakedStateAddress::BasicRedeem(RedeemAddress::from([0xcd; 20]));
+        assert_eq!(
+            redeem,
+            StakedStateAddress::from_str(&redeem.to_string()).unwrap()
+        );
+
+        for address in [address, redeem].iter() {
+            assert_eq!(
+                *address,
+                StakedStateAddress::from_query_data(&address.to_query_data()).unwrap()
+            );
         }
+        // the variant is given by the caller, not guessed from the length
+        assert!(StakedStateAddress::try_from(&[0xab; 32][..]).is_err());
+        assert!(StakedStateAddress::try_from_root_hash(&[0xcd; 20][..]).is_err());
+        assert!(StakedStateAddress::from_query_data(&[0xab; 32][..]).is_err());
     }
 }
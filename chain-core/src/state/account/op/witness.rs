@@ -1,13 +1,24 @@
-use crate::tx::witness::{tree::RawSignature, EcdsaSignature};
+use crate::common::Proof;
+use crate::tx::witness::tree::{RawSignature, RawXOnlyPubkey};
+use crate::tx::witness::{EcdsaSignature, TxInWitness};
 use parity_scale_codec::{Decode, Encode, Error, Input, Output};
 use secp256k1::recovery::{RecoverableSignature, RecoveryId};
-use serde::{Deserialize, Serialize};
+use secp256k1::schnorrsig::SchnorrSignature;
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+use std::prelude::v1::Vec;
 
 /// A witness for StakedState operations
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum StakedStateOpWitness {
     /// Eth-style recoverable signature
     BasicRedeem(EcdsaSignature),
+    /// BIP340-compatible Schnorr signature
+    /// + Merkle proof from the pubkey leaf to the `OrTree` address root
+    #[serde(serialize_with = "serialize_tree_sig")]
+    #[serde(deserialize_with = "deserialize_tree_sig")]
+    TreeSig(SchnorrSignature, Proof<RawXOnlyPubkey>),
 }
 
 impl StakedStateOpWitness {
@@ -28,12 +39,18 @@ impl Encode for StakedStateOpWitness {
                 dest.push_byte(rid);
                 serialized_sig.encode_to(dest);
             }
+            StakedStateOpWitness::TreeSig(ref schnorrsig, ref proof) => {
+                dest.push_byte(1);
+                schnorrsig.serialize_default().encode_to(dest);
+                proof.encode_to(dest);
+            }
         }
     }
 
     fn size_hint(&self) -> usize {
         match self {
             StakedStateOpWitness::BasicRedeem(_) => 66,
+            StakedStateOpWitness::TreeSig(_, ref proof) => 65 + proof.size_hint(),
         }
     }
 }
@@ -51,7 +68,74 @@ impl Decode for StakedStateOpWitness {
                     .map_err(|_| Error::from("Unable to create recoverable signature"))?;
                 Ok(StakedStateOpWitness::BasicRedeem(sig))
             }
+            1 => {
+                let raw_sig = RawSignature::decode(input)?;
+                let schnorrsig = SchnorrSignature::from_default(&raw_sig)
+                    .map_err(|_| Error::from("Unable to parse schnorr signature"))?;
+                let proof = Proof::decode(input)?;
+                Ok(StakedStateOpWitness::TreeSig(schnorrsig, proof))
+            }
             _ => Err(Error::from("Invalid tag")),
         }
     }
 }
+
+impl From<TxInWitness> for StakedStateOpWitness {
+    fn from(witness: TxInWitness) -> Self {
+        match witness {
+            TxInWitness::TreeSig(schnorrsig, proof) => {
+                StakedStateOpWitness::TreeSig(schnorrsig, proof)
+            }
+        }
+    }
+}
+
+/// serializes the signature and the proof as hex-encoded SCALE bytes
+fn serialize_tree_sig<S>(
+    schnorrsig: &SchnorrSignature,
+    proof: &Proof<RawXOnlyPubkey>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut bytes = schnorrsig.serialize_default().encode();
+    proof.encode_to(&mut bytes);
+    serializer.serialize_str(&hex::encode(bytes))
+}
+
+fn deserialize_tree_sig<'de, D>(
+    deserializer: D,
+) -> std::result::Result<(SchnorrSignature, Proof<RawXOnlyPubkey>), D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct StrVisitor;
+
+    impl<'de> de::Visitor<'de> for StrVisitor {
+        type Value = (SchnorrSignature, Proof<RawXOnlyPubkey>);
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("schnorr signature and merkle proof in hexadecimal string")
+        }
+
+        #[inline]
+        fn visit_str<E>(self, value: &str) -> std::result::Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            let bytes: Vec<u8> =
+                hex::decode(value).map_err(|err| de::Error::custom(err.to_string()))?;
+            let mut input = bytes.as_slice();
+            let raw_sig = RawSignature::decode(&mut input)
+                .map_err(|_| de::Error::custom("Unable to decode schnorr signature"))?;
+            let schnorrsig = SchnorrSignature::from_default(&raw_sig)
+                .map_err(|err| de::Error::custom(err.to_string()))?;
+            let proof = Proof::decode(&mut input)
+                .map_err(|_| de::Error::custom("Unable to decode merkle proof"))?;
+            Ok((schnorrsig, proof))
+        }
+    }
+
+    deserializer.deserialize_str(StrVisitor)
+}
@@ -5,7 +5,7 @@ pub mod tendermint;
 /// data types related to council node operations in staked state (nodejoin and unjail)
 pub mod validator;
 
-use parity_scale_codec::{Decode, Encode};
+use parity_scale_codec::{Decode, Encode, Error, Input};
 use serde::{Deserialize, Serialize};
 use std::prelude::v1::Vec;
 
@@ -17,7 +17,7 @@ use crate::init::params::NetworkParameters;
 use crate::tx::data::TxId;
 
 /// ABCI chain state
-#[derive(PartialEq, Debug, Clone, Encode, Decode, Serialize, Deserialize)]
+#[derive(PartialEq, Debug, Clone, Encode, Serialize, Deserialize)]
 pub struct ChainState {
     /// root hash of the sparse merkle patricia trie of staking account states
     pub account_root: H256,
@@ -25,6 +25,32 @@ pub struct ChainState {
     pub rewards_pool: RewardsPoolState,
     /// network parameters (fee policy, staking configuration etc.)
     pub network_params: NetworkParameters,
+    /// the latest block filter header (see `compute_filter_header`);
+    /// none if the chain was started before `FILTER_HEADER_APP_VERSION`
+    #[serde(default)]
+    pub filter_header: Option<H256>,
+}
+
+impl Decode for ChainState {
+    /// states stored before `FILTER_HEADER_APP_VERSION` end after the network parameters
+    /// (the chain state is always the last part of the stored node state),
+    /// they're decoded as states without the filter header
+    fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+        let account_root = H256::decode(input)?;
+        let rewards_pool = RewardsPoolState::decode(input)?;
+        let network_params = NetworkParameters::decode(input)?;
+        let filter_header = if input.remaining_len()? == Some(0) {
+            None
+        } else {
+            Option::<H256>::decode(input)?
+        };
+        Ok(ChainState {
+            account_root,
+            rewards_pool,
+            network_params,
+            filter_header,
+        })
+    }
 }
 
 impl ChainState {
@@ -36,6 +62,7 @@ impl ChainState {
             &self.account_root,
             &self.rewards_pool,
             &self.network_params,
+            self.filter_header.as_ref(),
         )
     }
 }
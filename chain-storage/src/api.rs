@@ -14,7 +14,7 @@ use chain_core::tx::data::{
 use super::buffer::{GetKV, StoreKV};
 use super::{
     LookupItem, StoredChainState, CHAIN_ID_KEY, COL_APP_HASHS, COL_APP_STATES, COL_EXTRA,
-    COL_NODE_INFO, COL_STAKING_VERSIONS, GENESIS_APP_HASH_KEY, LAST_STATE_KEY,
+    COL_INDEX, COL_NODE_INFO, COL_STAKING_VERSIONS, GENESIS_APP_HASH_KEY, LAST_STATE_KEY,
 };
 
 pub fn get_last_app_state(db: &impl GetKV) -> Option<Vec<u8>> {
@@ -44,6 +44,14 @@ pub fn insert_item(
     db.set((col, txid_or_app_hash.to_vec()), data)
 }
 
+pub fn insert_index_entry(db: &mut impl StoreKV, key: Vec<u8>, value: Vec<u8>) {
+    db.set((COL_INDEX, key), value)
+}
+
+pub fn get_index_entry(db: &impl GetKV, key: &[u8]) -> Option<Vec<u8>> {
+    db.get(&(COL_INDEX, key.to_vec()))
+}
+
 pub fn get_genesis_app_hash(db: &impl GetKV) -> Option<H256> {
     let value = db.get(&(COL_NODE_INFO, GENESIS_APP_HASH_KEY.to_vec()))?;
     let mut app_hash = H256::default();
@@ -66,6 +74,12 @@ pub fn get_historical_app_hash(db: &impl GetKV, height: BlockHeight) -> Option<H
     Some(stored_ah)
 }
 
+/// Returns the encoded merkle tree of transaction IDs committed at the given height
+pub fn get_historical_txs_merkle_tree(db: &impl GetKV, height: BlockHeight) -> Option<Vec<u8>> {
+    let app_hash = get_historical_app_hash(db, height)?;
+    lookup_item(db, LookupItem::TxsMerkle, &app_hash)
+}
+
 pub fn get_historical_staking_version(db: &impl GetKV, height: BlockHeight) -> Option<Version> {
     let sah = db.get(&(COL_STAKING_VERSIONS, height.encode()))?;
     Version::decode(&mut sah.as_slice()).ok()
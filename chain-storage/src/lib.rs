@@ -9,6 +9,8 @@ use chain_core::state::account::StakedState;
 use chain_core::state::tendermint::BlockHeight;
 use chain_core::tx::data::TxId;
 use kvdb::{DBTransaction, KeyValueDB};
+use parity_scale_codec::{Decode, Encode};
+use std::collections::BTreeSet;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -39,12 +41,18 @@ pub const COL_TRIE_NODE: u32 = 9;
 pub const COL_TRIE_STALED: u32 = 10;
 /// Column to store block height -> staking version
 pub const COL_STAKING_VERSIONS: u32 = 11;
+/// Column for the optional event indexer: index key => indexed event
+pub const COL_INDEX: u32 = 12;
 /// Number of columns in DB
-pub const NUM_COLUMNS: u32 = 12;
+pub const NUM_COLUMNS: u32 = 13;
+/// Number of columns in DBs created before `COL_INDEX` was added (they're upgraded when opened)
+pub const NUM_COLUMNS_WITHOUT_INDEX: u32 = 12;
 
 pub const CHAIN_ID_KEY: &[u8] = b"chain_id";
 pub const GENESIS_APP_HASH_KEY: &[u8] = b"genesis_app_hash";
 pub const LAST_STATE_KEY: &[u8] = b"last_state";
+/// the next block height TDBE needs to fetch transaction data for (stored in `COL_EXTRA`)
+pub const TDBE_SYNC_CURSOR_KEY: &[u8] = b"tdbe_sync_cursor";
 
 pub enum StorageType {
     Node,
@@ -127,6 +135,57 @@ impl ReadOnlyStorage {
             .expect("IO fail")
             .map(|x| x.to_vec())
     }
+
+    pub fn get_tdbe_sync_cursor(&self) -> Option<BlockHeight> {
+        let cursor = self
+            .db
+            .get(COL_EXTRA, TDBE_SYNC_CURSOR_KEY)
+            .expect("IO fail")?;
+        BlockHeight::decode(&mut cursor.as_ref()).ok()
+    }
+}
+
+/// committed storage which can only be extended with the local data of the enclaves
+/// (it's not a part of the consensus state, so it's written outside of block processing)
+pub struct EnclaveDataStorage {
+    read_only: ReadOnlyStorage,
+}
+
+impl EnclaveDataStorage {
+    /// stores sealed transaction payloads fetched by TDBE from other nodes, together with its sync cursor
+    /// (written in one DB transaction). Only payloads of committed transactions with outputs
+    /// which don't have any payload stored yet are accepted -- otherwise, nothing is written.
+    pub fn store_tdbe_sealed_logs(
+        &self,
+        sealed_logs: &[(TxId, Vec<u8>)],
+        sync_cursor: Option<BlockHeight>,
+    ) -> std::io::Result<()> {
+        let mut txids = BTreeSet::new();
+        for (txid, _) in sealed_logs {
+            if lookup_item(&self.read_only, LookupItem::TxMetaSpent, txid).is_none() {
+                return Err(invalid_input(
+                    "sealed payload of a transaction which wasn't committed with outputs",
+                ));
+            }
+            if !txids.insert(*txid) || self.read_only.get_sealed_log(txid).is_some() {
+                return Err(invalid_input(
+                    "sealed payload of a transaction which is already stored",
+                ));
+            }
+        }
+        let mut dbtx = self.read_only.db.transaction();
+        for (txid, sealed_log) in sealed_logs {
+            dbtx.put(COL_ENCLAVE_TX, txid, sealed_log);
+        }
+        if let Some(sync_cursor) = sync_cursor {
+            dbtx.put(COL_EXTRA, TDBE_SYNC_CURSOR_KEY, &sync_cursor.encode());
+        }
+        self.read_only.db.write(dbtx)
+    }
+}
+
+fn invalid_input(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, message)
 }
 
 pub trait StoredChainState {
@@ -150,6 +209,24 @@ pub enum LookupItem {
     TxSealed = COL_ENCLAVE_TX,
 }
 
+/// opens the RocksDB database, adding the columns missing in databases created by older versions
+#[cfg(feature = "kvdb-rocksdb")]
+fn open_rocksdb(path: &str) -> std::io::Result<kvdb_rocksdb::Database> {
+    use kvdb_rocksdb::{Database, DatabaseConfig};
+
+    if let Ok(db) = Database::open(&DatabaseConfig::with_columns(NUM_COLUMNS), path) {
+        return Ok(db);
+    }
+    let db = Database::open(
+        &DatabaseConfig::with_columns(NUM_COLUMNS_WITHOUT_INDEX),
+        path,
+    )?;
+    while db.num_columns() < NUM_COLUMNS {
+        db.add_column()?;
+    }
+    Ok(db)
+}
+
 impl Storage {
     pub fn get_read_only(&self) -> ReadOnlyStorage {
         ReadOnlyStorage {
@@ -157,6 +234,12 @@ impl Storage {
         }
     }
 
+    pub fn get_enclave_data_storage(&self) -> EnclaveDataStorage {
+        EnclaveDataStorage {
+            read_only: self.get_read_only(),
+        }
+    }
+
     pub fn lookup_item(&self, item_type: LookupItem, txid_or_app_hash: &H256) -> Option<Vec<u8>> {
         lookup_item(self, item_type, txid_or_app_hash)
     }
@@ -173,13 +256,7 @@ impl Storage {
     /// inititalizes Storage based on the provided config
     #[cfg(feature = "kvdb-rocksdb")]
     pub fn new(config: &StorageConfig<'_>) -> Self {
-        let db = Arc::new(
-            kvdb_rocksdb::Database::open(
-                &kvdb_rocksdb::DatabaseConfig::with_columns(NUM_COLUMNS),
-                &config.db_path(),
-            )
-            .expect("failed to open db"),
-        );
+        let db = Arc::new(open_rocksdb(&config.db_path()).expect("failed to open db"));
         Storage {
             db,
             current_tx: None,
@@ -265,3 +342,43 @@ impl Storage {
         root_hash
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_tdbe_sealed_logs_are_only_stored_for_missing_payloads() {
+        let mut storage = Storage::new_db(Arc::new(kvdb_memorydb::create(NUM_COLUMNS)));
+        let (committed, stored, unknown) = ([1u8; 32], [2u8; 32], [3u8; 32]);
+        let mut kv_buffer = KVBuffer::new();
+        let mut kv_store = BufferStore::new(&storage, &mut kv_buffer);
+        create_utxo(&mut kv_store, 1, &committed);
+        create_utxo(&mut kv_store, 1, &stored);
+        store_sealed_log(&mut kv_store, &stored, &[2]);
+        flush_storage(&mut storage, kv_buffer).unwrap();
+        storage.persist_write().unwrap();
+
+        let enclave_data = storage.get_enclave_data_storage();
+        let cursor = Some(BlockHeight::new(5));
+        assert!(enclave_data
+            .store_tdbe_sealed_logs(&[(committed, vec![1]), (unknown, vec![3])], cursor)
+            .is_err());
+        assert!(enclave_data
+            .store_tdbe_sealed_logs(&[(committed, vec![1]), (stored, vec![3])], cursor)
+            .is_err());
+        assert!(enclave_data
+            .store_tdbe_sealed_logs(&[(committed, vec![1]), (committed, vec![3])], cursor)
+            .is_err());
+        let read_only = storage.get_read_only();
+        assert_eq!(read_only.get_sealed_log(&committed), None);
+        assert_eq!(read_only.get_sealed_log(&stored), Some(vec![2]));
+        assert_eq!(read_only.get_tdbe_sync_cursor(), None);
+
+        enclave_data
+            .store_tdbe_sealed_logs(&[(committed, vec![1])], cursor)
+            .unwrap();
+        assert_eq!(read_only.get_sealed_log(&committed), Some(vec![1]));
+        assert_eq!(read_only.get_tdbe_sync_cursor(), cursor);
+    }
+}
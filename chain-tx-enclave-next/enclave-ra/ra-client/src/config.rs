@@ -1,5 +1,6 @@
 use std::borrow::Cow;
 
+use ra_common::AttestationProviderKind;
 use sgx_isa::Report;
 
 const IAS_CERT: &[u8] =
@@ -21,6 +22,9 @@ pub struct EnclaveCertVerifierConfig<'a> {
     pub report_validity_secs: u32,
     /// Information about the enclave that'll be verifier if present -- TODO: make non-optional?
     pub enclave_info: Option<EnclaveInfo>,
+    /// Attestation providers whose reports are trusted (reports generated by other providers are
+    /// rejected)
+    pub trusted_attestation_providers: Cow<'a, [AttestationProviderKind]>,
 }
 
 impl<'a> EnclaveCertVerifierConfig<'a> {
@@ -32,6 +36,7 @@ impl<'a> EnclaveCertVerifierConfig<'a> {
             valid_enclave_quote_statuses: vec!["OK".into(), "SW_HARDENING_NEEDED".into()].into(),
             report_validity_secs: DEFAULT_VALIDITY_SECS,
             enclave_info: None,
+            trusted_attestation_providers: vec![AttestationProviderKind::Ias].into(),
         }
     }
 
@@ -5,12 +5,14 @@
 //! ```rust,no_run
 //! use std::sync::Arc;
 //!
-//! use ra_client::{EnclaveCertVerifier, EnclaveCertVerifierConfig};
+//! use ra_client::{AttestationProviderKind, EnclaveCertVerifier, EnclaveCertVerifierConfig};
 //! use rustls::ClientConfig;
 //!
 //! let verifier_config = EnclaveCertVerifierConfig {
 //!     signing_ca_cert_path: "./path/to/Intel_SGX_Attestation_RootCA.pem".into(),
 //!     valid_enclave_quote_statuses: vec!["OK".into(), "GROUP_OUT_OF_DATE".into()].into(),
+//!     trusted_attestation_providers: vec![AttestationProviderKind::Ias].into(),
+//!     ..Default::default()
 //! };
 //! let verifier = EnclaveCertVerifier::new(verifier_config).unwrap();
 //!
@@ -28,4 +30,4 @@ pub use self::{
         ENCLAVE_CERT_VERIFIER,
     },
 };
-pub use ra_common::DEFAULT_EXPIRATION_SECS;
+pub use ra_common::{AttestationProviderKind, DEFAULT_EXPIRATION_SECS};
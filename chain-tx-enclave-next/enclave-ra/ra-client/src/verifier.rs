@@ -4,8 +4,8 @@ use chrono::{DateTime, Duration, Utc};
 use der_parser::oid::Oid;
 use lazy_static::lazy_static;
 use ra_common::{
-    AttestationReport, AttestationReportBody, EnclaveQuoteStatus, Quote,
-    OID_EXTENSION_ATTESTATION_REPORT,
+    AttestationProviderKind, AttestationReport, AttestationReportBody, EnclaveQuoteStatus,
+    MockAttestationReport, Quote, QuoteVerificationReport, OID_EXTENSION_ATTESTATION_REPORT,
 };
 use rustls::{
     internal::pemfile::certs, Certificate, ClientCertVerified, ClientCertVerifier, ClientConfig,
@@ -55,6 +55,7 @@ pub struct EnclaveCertVerifier {
     report_validity_duration: Duration,
     // TODO: make non-optional?
     enclave_info: Option<EnclaveInfo>,
+    trusted_attestation_providers: HashSet<AttestationProviderKind>,
 }
 
 impl Default for EnclaveCertVerifier {
@@ -90,11 +91,18 @@ impl EnclaveCertVerifier {
 
         let report_validity_duration = Duration::seconds(config.report_validity_secs.into());
 
+        let trusted_attestation_providers: HashSet<AttestationProviderKind> = config
+            .trusted_attestation_providers
+            .iter()
+            .copied()
+            .collect();
+
         Ok(Self {
             root_cert_store,
             valid_enclave_quote_statuses,
             report_validity_duration,
             enclave_info: config.enclave_info,
+            trusted_attestation_providers,
         })
     }
 
@@ -192,8 +200,36 @@ impl EnclaveCertVerifier {
         public_key: &[u8],
         now: DateTime<Utc>,
     ) -> Result<Quote, EnclaveCertVerifierError> {
-        let attestation_report: AttestationReport = serde_json::from_slice(attestation_report)
-            .map_err(EnclaveCertVerifierError::AttestationReportParsingError)?;
+        let attestation_report: QuoteVerificationReport =
+            serde_json::from_slice(attestation_report)
+                .map_err(EnclaveCertVerifierError::AttestationReportParsingError)?;
+
+        let provider_kind = attestation_report.provider_kind();
+        if !self.trusted_attestation_providers.contains(&provider_kind) {
+            return Err(EnclaveCertVerifierError::UntrustedAttestationProvider(
+                provider_kind,
+            ));
+        }
+
+        let quote = match attestation_report {
+            QuoteVerificationReport::Ias(ref attestation_report) => {
+                self.verify_ias_attestation_report(attestation_report, now)?
+            }
+            QuoteVerificationReport::Mock(MockAttestationReport { ref mock_quote }) => {
+                Quote::try_copy_from(mock_quote)
+                    .ok_or_else(|| EnclaveCertVerifierError::InvalidQuote)?
+            }
+        };
+
+        self.verify_quote(quote, public_key)
+    }
+
+    /// Verifies attestation report signed by IAS and returns the quote in it
+    fn verify_ias_attestation_report(
+        &self,
+        attestation_report: &AttestationReport,
+        now: DateTime<Utc>,
+    ) -> Result<Quote, EnclaveCertVerifierError> {
         let signing_certificate_chain = certs(&mut attestation_report.signing_cert.as_ref())
             .map_err(|_| {
                 EnclaveCertVerifierError::AttestationReportSigningCertificateChainParsingError
@@ -211,13 +247,12 @@ impl EnclaveCertVerifier {
             &attestation_report.body,
             &attestation_report.signature,
         )?;
-        self.verify_attestation_report_body(&attestation_report.body, public_key, now)
+        self.verify_attestation_report_body(&attestation_report.body, now)
     }
 
     fn verify_attestation_report_body(
         &self,
         attestation_report_body: &[u8],
-        public_key: &[u8],
         now: DateTime<Utc>,
     ) -> Result<Quote, EnclaveCertVerifierError> {
         let attestation_report_body: AttestationReportBody =
@@ -242,7 +277,15 @@ impl EnclaveCertVerifier {
             ));
         }*/
 
-        let quote = attestation_report_body.get_quote()?;
+        attestation_report_body.get_quote().map_err(Into::into)
+    }
+
+    /// Verifies that quote contains given public key and matches configured enclave info
+    fn verify_quote(
+        &self,
+        quote: Quote,
+        public_key: &[u8],
+    ) -> Result<Quote, EnclaveCertVerifierError> {
         let has_correct_len = public_key.len() == 65;
         let is_uncompressed = public_key[0] == 4;
         let pubkey_matches = public_key[1..] == quote.report_body.report_data[..];
@@ -402,6 +445,8 @@ pub enum EnclaveCertVerifierError {
     EnclaveQuoteStatusParsingError(#[from] ra_common::EnclaveQuoteStatusParsingError),
     #[error("Invalid enclave quote status: {0}")]
     InvalidEnclaveQuoteStatus(String),
+    #[error("Invalid enclave quote structure")]
+    InvalidQuote,
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
     #[error("JSON error: {0}")]
@@ -424,6 +469,8 @@ pub enum EnclaveCertVerifierError {
     QuoteParsingError(#[from] ra_common::QuoteParsingError),
     #[error("Unable to get current time")]
     TimeError,
+    #[error("Attestation report generated by untrusted attestation provider: {0:?}")]
+    UntrustedAttestationProvider(AttestationProviderKind),
     #[error("Webpki error: {0}")]
     WebpkiError(#[from] webpki::Error),
 }
@@ -466,6 +513,7 @@ mod tests {
             .into(),
             report_validity_secs: 86400,
             enclave_info: None,
+            trusted_attestation_providers: vec![AttestationProviderKind::Ias].into(),
         };
         let verifier = EnclaveCertVerifier::new(verifier_config).unwrap();
         let now = Utc.timestamp(1594612800, 0);
@@ -492,6 +540,7 @@ mod tests {
             .into(),
             report_validity_secs: 86400,
             enclave_info: None,
+            trusted_attestation_providers: vec![AttestationProviderKind::Ias].into(),
         };
         let verifier = EnclaveCertVerifier::new(verifier_config).unwrap();
         let now = Utc.timestamp(1594612800, 0);
@@ -529,6 +578,7 @@ mod tests {
             .into(),
             report_validity_secs: 86400,
             enclave_info: None,
+            trusted_attestation_providers: vec![AttestationProviderKind::Ias].into(),
         };
         let verifier = EnclaveCertVerifier::new(verifier_config).unwrap();
         let now = Utc.timestamp(1594612800, 0);
@@ -567,6 +617,7 @@ mod tests {
             .into(),
             report_validity_secs: 86400,
             enclave_info: None,
+            trusted_attestation_providers: vec![AttestationProviderKind::Ias].into(),
         };
         let verifier = EnclaveCertVerifier::new(verifier_config).unwrap();
         let now = Utc.timestamp(1594612800, 0);
@@ -604,6 +655,7 @@ mod tests {
             .into(),
             report_validity_secs: 86400,
             enclave_info: None,
+            trusted_attestation_providers: vec![AttestationProviderKind::Ias].into(),
         };
         let verifier = EnclaveCertVerifier::new(verifier_config).unwrap();
         let now = Utc.timestamp(1594612800, 0);
@@ -640,6 +692,7 @@ mod tests {
             .into(),
             report_validity_secs: 86400,
             enclave_info: None,
+            trusted_attestation_providers: vec![AttestationProviderKind::Ias].into(),
         };
         let verifier = EnclaveCertVerifier::new(verifier_config).unwrap();
         let now = Utc.timestamp(1594612800, 0);
@@ -670,6 +723,7 @@ mod tests {
             .into(),
             report_validity_secs: 86400,
             enclave_info: None,
+            trusted_attestation_providers: vec![AttestationProviderKind::Ias].into(),
         };
         let verifier = EnclaveCertVerifier::new(verifier_config).unwrap();
         let now = Utc.timestamp(1594612800, 0);
@@ -680,4 +734,62 @@ mod tests {
             EnclaveCertVerifierError::PublicKeyMismatch
         ));
     }
+
+    fn mock_attestation_report(public_key: &[u8]) -> Vec<u8> {
+        let mut mock_quote = vec![0u8; 432];
+        mock_quote[368..].copy_from_slice(&public_key[1..]);
+
+        serde_json::to_vec(&MockAttestationReport { mock_quote }).unwrap()
+    }
+
+    fn mock_verifier_config<'a>(
+        trusted_attestation_providers: Vec<AttestationProviderKind>,
+    ) -> EnclaveCertVerifierConfig<'a> {
+        let ias_ca = include_bytes!(
+            "../../../../client-common/src/cipher/AttestationReportSigningCACert.pem"
+        );
+
+        EnclaveCertVerifierConfig {
+            signing_ca_cert_pem: ias_ca.to_vec().into(),
+            valid_enclave_quote_statuses: vec!["OK".into()].into(),
+            report_validity_secs: 86400,
+            enclave_info: None,
+            trusted_attestation_providers: trusted_attestation_providers.into(),
+        }
+    }
+
+    #[test]
+    fn test_verify_mock_attestation_report() {
+        let public_key = &[&[4], &[1u8; 64][..]].concat();
+        let attestation_report = mock_attestation_report(public_key);
+
+        let verifier = EnclaveCertVerifier::new(mock_verifier_config(vec![
+            AttestationProviderKind::Ias,
+            AttestationProviderKind::Mock,
+        ]))
+        .unwrap();
+        let now = Utc.timestamp(1594612800, 0);
+        let quote = verifier
+            .verify_attestation_report(&attestation_report, public_key, now)
+            .unwrap();
+
+        assert_eq!([1u8; 64], quote.report_body.report_data);
+    }
+
+    #[test]
+    fn test_verify_mock_attestation_report_untrusted_attestation_provider() {
+        let public_key = &[&[4], &[1u8; 64][..]].concat();
+        let attestation_report = mock_attestation_report(public_key);
+
+        let verifier =
+            EnclaveCertVerifier::new(mock_verifier_config(vec![AttestationProviderKind::Ias]))
+                .unwrap();
+        let now = Utc.timestamp(1594612800, 0);
+        let result = verifier.verify_attestation_report(&attestation_report, public_key, now);
+
+        assert!(matches!(
+            result.unwrap_err(),
+            EnclaveCertVerifierError::UntrustedAttestationProvider(AttestationProviderKind::Mock)
+        ));
+    }
 }
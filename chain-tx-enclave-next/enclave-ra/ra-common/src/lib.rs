@@ -6,8 +6,10 @@ pub mod sp;
 pub use self::{
     quote::{Measurement, Quote, QuoteBody, ReportBody},
     report::{
-        AttestationReport, AttestationReportBody, EnclaveQuoteStatus,
-        EnclaveQuoteStatusParsingError, QuoteParsingError, OID_EXTENSION_ATTESTATION_REPORT,
+        AttestationProviderKind, AttestationProviderKindParsingError, AttestationReport,
+        AttestationReportBody, EnclaveQuoteStatus, EnclaveQuoteStatusParsingError,
+        MockAttestationReport, QuoteParsingError, QuoteVerificationReport,
+        OID_EXTENSION_ATTESTATION_REPORT,
     },
 };
 
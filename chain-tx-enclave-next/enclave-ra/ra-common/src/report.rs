@@ -85,7 +85,7 @@ pub enum QuoteParsingError {
 }
 
 /// Attestation verification report (containing report body, signature and signing certificate)
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AttestationReport {
     /// Report body (This can be converted into `AttestationReportBody` using `serde_json::from_slice`)
     pub body: Vec<u8>,
@@ -95,6 +95,70 @@ pub struct AttestationReport {
     pub signing_cert: Vec<u8>,
 }
 
+/// Unsigned quote which is only accepted by verifiers explicitly configured to trust mock
+/// attestation (only for tests)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MockAttestationReport {
+    /// Quote (without signature)
+    pub mock_quote: Vec<u8>,
+}
+
+/// Report proving that an enclave quote was verified (this is embedded in enclave certificates)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum QuoteVerificationReport {
+    /// Attestation verification report signed by IAS (EPID quotes)
+    Ias(AttestationReport),
+    /// Unsigned quote (only for tests)
+    Mock(MockAttestationReport),
+}
+
+impl QuoteVerificationReport {
+    /// Returns the kind of attestation provider which generated this report
+    pub fn provider_kind(&self) -> AttestationProviderKind {
+        match self {
+            Self::Ias(_) => AttestationProviderKind::Ias,
+            Self::Mock(_) => AttestationProviderKind::Mock,
+        }
+    }
+}
+
+impl From<AttestationReport> for QuoteVerificationReport {
+    fn from(report: AttestationReport) -> Self {
+        Self::Ias(report)
+    }
+}
+
+/// Kinds of attestation providers which can verify enclave quotes
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttestationProviderKind {
+    /// Intel Attestation Service (EPID)
+    Ias,
+    /// Mock provider which does not verify anything (only for tests)
+    Mock,
+}
+
+impl FromStr for AttestationProviderKind {
+    type Err = AttestationProviderKindParsingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ias" => Ok(Self::Ias),
+            "mock" => Ok(Self::Mock),
+            _ => Err(AttestationProviderKindParsingError::InvalidKind(
+                s.to_owned(),
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AttestationProviderKindParsingError {
+    #[error("Invalid attestation provider (possible values: `ias` or `mock`): {0}")]
+    InvalidKind(String),
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -147,4 +211,24 @@ mod tests {
             attestation_report_body.advisory_ids
         );
     }
+
+    #[test]
+    fn test_quote_verification_report_deserialization() {
+        let ias_report = serde_json::to_vec(&AttestationReport {
+            body: vec![1],
+            signature: vec![2],
+            signing_cert: vec![3],
+        })
+        .unwrap();
+        let report: QuoteVerificationReport = serde_json::from_slice(&ias_report).unwrap();
+        assert_eq!(AttestationProviderKind::Ias, report.provider_kind());
+
+        let mock_report =
+            serde_json::to_vec(&QuoteVerificationReport::Mock(MockAttestationReport {
+                mock_quote: vec![1],
+            }))
+            .unwrap();
+        let report: QuoteVerificationReport = serde_json::from_slice(&mock_report).unwrap();
+        assert_eq!(AttestationProviderKind::Mock, report.provider_kind());
+    }
 }
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{sp::QuoteResult, AttestationReport};
+use crate::{sp::QuoteResult, QuoteVerificationReport};
 
 /// Requests sent to SP by enclave
 #[derive(Debug, Serialize, Deserialize)]
@@ -9,7 +9,7 @@ pub enum Request {
     GetTargetInfo,
     /// Generates a new quote from QE using AESM
     GetQuote { report: Vec<u8>, nonce: [u8; 16] },
-    /// Generate attestation report using IAS
+    /// Generate attestation report using configured attestation provider
     GetAttestationReport { quote: Vec<u8> },
 }
 
@@ -22,6 +22,6 @@ pub enum Response {
     GetQuote { quote_result: QuoteResult },
     /// Response of attestation report request
     GetAttestationReport {
-        attestation_report: AttestationReport,
+        attestation_report: QuoteVerificationReport,
     },
 }
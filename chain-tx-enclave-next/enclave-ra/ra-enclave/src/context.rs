@@ -1,7 +1,7 @@
 use std::sync::{Arc, Mutex};
 
 use chrono::{Duration, Utc};
-use ra_common::{AttestationReport, OID_EXTENSION_ATTESTATION_REPORT};
+use ra_common::{QuoteVerificationReport, OID_EXTENSION_ATTESTATION_REPORT};
 use ra_sp_client::{SpRaClient, SpRaClientError};
 use rcgen::{
     Certificate as RcGenCertificate, CertificateParams, CustomExtension, DistinguishedName, DnType,
@@ -82,7 +82,7 @@ impl EnclaveRaContext {
     fn get_attestation_report(
         &self,
         public_key: &[u8],
-    ) -> Result<AttestationReport, EnclaveRaContextError> {
+    ) -> Result<QuoteVerificationReport, EnclaveRaContextError> {
         // Get target info from SP server
         let target_info_bytes = self.sp_ra_client.get_target_info()?;
         let target_info = Targetinfo::try_copy_from(&target_info_bytes)
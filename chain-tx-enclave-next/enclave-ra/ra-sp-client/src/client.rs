@@ -7,7 +7,7 @@ use ra_common::{
         protocol::{Request, Response},
         QuoteResult,
     },
-    AttestationReport,
+    QuoteVerificationReport,
 };
 
 /// Client to connect and send requests to SP server for remote attestation
@@ -61,11 +61,11 @@ impl SpRaClient {
         }
     }
 
-    /// Generate attestation report using IAS
+    /// Generate attestation report using configured attestation provider
     pub fn get_attestation_report(
         &self,
         quote: Vec<u8>,
-    ) -> Result<AttestationReport, SpRaClientError> {
+    ) -> Result<QuoteVerificationReport, SpRaClientError> {
         let request = Request::GetAttestationReport { quote };
         serde_json::to_writer(&self.stream, &request)?;
 
@@ -0,0 +1,45 @@
+use ra_common::QuoteVerificationReport;
+use thiserror::Error;
+
+use crate::ias_client::{IasClient, IasClientError};
+
+/// Service which verifies enclave quotes and generates reports which can be embedded in enclave
+/// certificates
+pub trait AttestationProvider: Send + Sync {
+    /// Gets SigRL (Signature revocation list) for given EPID group (returns `None` if there is no
+    /// revocation list or if the provider does not use EPID)
+    fn get_sig_rl(&self, gid: [u8; 4]) -> Result<Option<Vec<u8>>, AttestationProviderError>;
+
+    /// Verifies given quote and generates a new quote verification report
+    fn get_attestation_report(
+        &self,
+        quote: &[u8],
+    ) -> Result<QuoteVerificationReport, AttestationProviderError>;
+}
+
+impl AttestationProvider for IasClient {
+    fn get_sig_rl(&self, gid: [u8; 4]) -> Result<Option<Vec<u8>>, AttestationProviderError> {
+        IasClient::get_sig_rl(self, gid).map_err(Into::into)
+    }
+
+    fn get_attestation_report(
+        &self,
+        quote: &[u8],
+    ) -> Result<QuoteVerificationReport, AttestationProviderError> {
+        self.verify_attestation_evidence(quote)
+            .map(Into::into)
+            .map_err(Into::into)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AttestationProviderError {
+    #[error("IAS client error: {0}")]
+    IasError(#[from] IasClientError),
+    #[error("Invalid quote")]
+    InvalidQuote,
+    #[error("No recorded response found: {0}")]
+    MissingRecording(String),
+    #[error("Unable to record response: {0}")]
+    RecordingError(#[source] std::io::Error),
+}
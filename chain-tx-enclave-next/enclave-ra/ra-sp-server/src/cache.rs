@@ -0,0 +1,531 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use ra_common::QuoteVerificationReport;
+
+use crate::{
+    attestation_provider::{AttestationProvider, AttestationProviderError},
+    metrics::Metrics,
+};
+
+/// Length of quote header and report body (attestation reports only depend on this part of a
+/// quote, so, reports can be reused for quotes with same body)
+const QUOTE_BODY_LEN: usize = 432;
+
+/// Length of the part of quote header preceding basename (basename is random for unlinkable
+/// quotes, so, it is not a part of enclave identity)
+const QUOTE_HEADER_IDENTITY_LEN: usize = 16;
+
+/// Offset of quote header and report body at which report data (public key of enclave
+/// certificate) starts
+const REPORT_DATA_OFFSET: usize = 368;
+
+/// Offset of report body in quote
+const REPORT_BODY_OFFSET: usize = 48;
+
+/// Entry in attestation cache
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry<T> {
+    /// Seconds since unix epoch when this entry was created
+    created_at: u64,
+    /// Cached value
+    value: T,
+}
+
+impl<T> CacheEntry<T> {
+    fn new(value: T) -> Self {
+        Self {
+            created_at: now_secs(),
+            value,
+        }
+    }
+
+    fn age_secs(&self) -> u64 {
+        now_secs().saturating_sub(self.created_at)
+    }
+}
+
+/// Attestation report cached for an enclave along with the report data (public key of enclave
+/// certificate) of the quote it was generated for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedReport {
+    /// Hex encoded report data of the quote
+    report_data: String,
+    /// Attestation report of the quote
+    report: QuoteVerificationReport,
+}
+
+/// Cache of values of type `T` (stored in memory and, optionally, on disk so that the cache
+/// survives restarts)
+struct Cache<T> {
+    kind: &'static str,
+    ttl: Duration,
+    max_stale: Duration,
+    cache_dir: Option<PathBuf>,
+    entries: Mutex<HashMap<String, CacheEntry<T>>>,
+}
+
+impl<T> Cache<T>
+where
+    T: Clone + Serialize + DeserializeOwned,
+{
+    fn new(
+        kind: &'static str,
+        ttl: Duration,
+        max_stale: Duration,
+        cache_dir: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            kind,
+            ttl,
+            max_stale,
+            cache_dir,
+            entries: Default::default(),
+        }
+    }
+
+    /// Returns true if given entry can be served without contacting attestation provider
+    fn is_fresh(&self, entry: &CacheEntry<T>) -> bool {
+        entry.age_secs() < self.ttl.as_secs()
+    }
+
+    /// Returns true if given entry can be served when attestation provider fails
+    fn is_usable_when_stale(&self, entry: &CacheEntry<T>) -> bool {
+        entry.age_secs() < self.ttl.as_secs().saturating_add(self.max_stale.as_secs())
+    }
+
+    fn file_path(&self, key: &str) -> Option<PathBuf> {
+        self.cache_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{}-{}.json", self.kind, key)))
+    }
+
+    fn get(&self, key: &str) -> Option<CacheEntry<T>> {
+        let mut entries = self.entries.lock().unwrap();
+
+        if let Some(entry) = entries.get(key) {
+            return Some(entry.clone());
+        }
+
+        let entry: CacheEntry<T> = read_json_file(&self.file_path(key)?)?;
+        entries.insert(key.to_owned(), entry.clone());
+        Some(entry)
+    }
+
+    fn insert(&self, key: &str, value: T) {
+        let entry = CacheEntry::new(value);
+
+        if let Some(file_path) = self.file_path(key) {
+            if let Err(e) = write_json_file(&file_path, &entry) {
+                log::warn!(
+                    "Unable to persist {} cache entry ({}): {}",
+                    self.kind,
+                    file_path.display(),
+                    e
+                );
+            }
+        }
+
+        self.entries.lock().unwrap().insert(key.to_owned(), entry);
+    }
+}
+
+/// Attestation provider which caches sig-rls and attestation reports generated by another
+/// provider
+///
+/// Sig-rls are cached per EPID group. Attestation reports are cached per enclave identity (quote
+/// header and report body without basename and report data), i.e. only the latest report of each
+/// enclave is kept, and it is only served for quotes with the same report data. Enclaves generate a
+/// new key pair for each certificate, so, a cached report is only reused when the same
+/// certificate key is attested again (e.g. when an enclave retries after failing to receive the
+/// report).
+///
+/// Cached values are served until their TTL expires. If the underlying provider fails, expired
+/// reports are served (if present) for at most `report_max_stale` after their TTL, so that remote
+/// attestation keeps working while the provider is briefly unreachable. Note that verifiers reject
+/// attestation reports older than their configured report validity (one day by default), so, the
+/// TTL and stale window of reports must stay well below it. Expired sig-rls are never served, as
+/// they may miss newly revoked signatures.
+pub struct CachingAttestationProvider {
+    inner: Box<dyn AttestationProvider>,
+    sig_rls: Cache<Option<Vec<u8>>>,
+    reports: Cache<CachedReport>,
+    metrics: Arc<Metrics>,
+}
+
+impl CachingAttestationProvider {
+    /// Creates a new caching attestation provider
+    pub fn new(
+        inner: Box<dyn AttestationProvider>,
+        cache_dir: Option<PathBuf>,
+        sig_rl_ttl: Duration,
+        report_ttl: Duration,
+        report_max_stale: Duration,
+        metrics: Arc<Metrics>,
+    ) -> Self {
+        Self {
+            inner,
+            sig_rls: Cache::new(
+                "sig-rl",
+                sig_rl_ttl,
+                Duration::from_secs(0),
+                cache_dir.clone(),
+            ),
+            reports: Cache::new("report", report_ttl, report_max_stale, cache_dir),
+            metrics,
+        }
+    }
+
+    /// Returns cached value for given key if it matches the request (fetches a new value from
+    /// attestation provider if there is no fresh matching value)
+    fn get_or_fetch<T>(
+        &self,
+        cache: &Cache<T>,
+        key: &str,
+        matches: impl Fn(&T) -> bool,
+        fetch: impl FnOnce() -> Result<T, AttestationProviderError>,
+    ) -> Result<T, AttestationProviderError>
+    where
+        T: Clone + Serialize + DeserializeOwned,
+    {
+        let label = ("kind", cache.kind);
+        let cached = cache.get(key).filter(|entry| matches(&entry.value));
+
+        if let Some(ref entry) = cached {
+            if cache.is_fresh(entry) {
+                self.metrics.inc("ra_sp_cache_hits_total", label);
+                return Ok(entry.value.clone());
+            }
+        }
+
+        self.metrics.inc("ra_sp_cache_misses_total", label);
+        self.metrics.inc("ra_sp_provider_requests_total", label);
+
+        let start = Instant::now();
+        let result = fetch();
+
+        self.metrics.observe(
+            "ra_sp_provider_request_duration_seconds",
+            label,
+            start.elapsed().as_secs_f64(),
+        );
+
+        match result {
+            Ok(value) => {
+                cache.insert(key, value.clone());
+                Ok(value)
+            }
+            Err(e) => {
+                self.metrics.inc("ra_sp_provider_errors_total", label);
+
+                match cached {
+                    Some(entry) if cache.is_usable_when_stale(&entry) => {
+                        log::warn!(
+                            "Attestation provider error: {}, serving expired {} from cache",
+                            e,
+                            cache.kind
+                        );
+                        self.metrics.inc("ra_sp_cache_stale_fallbacks_total", label);
+                        Ok(entry.value)
+                    }
+                    _ => Err(e),
+                }
+            }
+        }
+    }
+}
+
+impl AttestationProvider for CachingAttestationProvider {
+    fn get_sig_rl(&self, gid: [u8; 4]) -> Result<Option<Vec<u8>>, AttestationProviderError> {
+        self.get_or_fetch(
+            &self.sig_rls,
+            &hex::encode(gid),
+            |_| true,
+            || self.inner.get_sig_rl(gid),
+        )
+    }
+
+    fn get_attestation_report(
+        &self,
+        quote: &[u8],
+    ) -> Result<QuoteVerificationReport, AttestationProviderError> {
+        let report_data = hex::encode(
+            quote
+                .get(REPORT_DATA_OFFSET..QUOTE_BODY_LEN)
+                .ok_or_else(|| AttestationProviderError::InvalidQuote)?,
+        );
+
+        self.get_or_fetch(
+            &self.reports,
+            &enclave_identity_hash(quote)?,
+            |cached| cached.report_data == report_data,
+            || {
+                Ok(CachedReport {
+                    report_data: report_data.clone(),
+                    report: self.inner.get_attestation_report(quote)?,
+                })
+            },
+        )
+        .map(|cached| cached.report)
+    }
+}
+
+/// Returns hex encoded SHA-256 hash of the parts of quote header and report body which identify
+/// the enclave (and its platform), i.e. excluding basename and report data
+fn enclave_identity_hash(quote: &[u8]) -> Result<String, AttestationProviderError> {
+    let header = quote
+        .get(..QUOTE_HEADER_IDENTITY_LEN)
+        .ok_or_else(|| AttestationProviderError::InvalidQuote)?;
+    let report_body = quote
+        .get(REPORT_BODY_OFFSET..REPORT_DATA_OFFSET)
+        .ok_or_else(|| AttestationProviderError::InvalidQuote)?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(header);
+    hasher.update(report_body);
+
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Returns hex encoded SHA-256 hash of header and report body of given quote
+pub(crate) fn quote_body_hash(quote: &[u8]) -> Result<String, AttestationProviderError> {
+    let quote_body = quote
+        .get(..QUOTE_BODY_LEN)
+        .ok_or_else(|| AttestationProviderError::InvalidQuote)?;
+
+    Ok(hex::encode(Sha256::digest(quote_body)))
+}
+
+pub(crate) fn read_json_file<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let bytes = fs::read(path).ok()?;
+
+    match serde_json::from_slice(&bytes) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            log::warn!("Unable to parse {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+pub(crate) fn write_json_file<T: Serialize>(path: &Path, value: &T) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, serde_json::to_vec(value)?)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::mock_provider::MockAttestationProvider;
+
+    /// Provider which counts calls and fails after `fail_after` calls
+    struct FlakyProvider {
+        calls: Arc<AtomicUsize>,
+        fail_after: usize,
+    }
+
+    impl AttestationProvider for FlakyProvider {
+        fn get_sig_rl(&self, _gid: [u8; 4]) -> Result<Option<Vec<u8>>, AttestationProviderError> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) >= self.fail_after {
+                Err(AttestationProviderError::InvalidQuote)
+            } else {
+                Ok(Some(vec![1, 2, 3]))
+            }
+        }
+
+        fn get_attestation_report(
+            &self,
+            quote: &[u8],
+        ) -> Result<QuoteVerificationReport, AttestationProviderError> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) >= self.fail_after {
+                Err(AttestationProviderError::InvalidQuote)
+            } else {
+                MockAttestationProvider.get_attestation_report(quote)
+            }
+        }
+    }
+
+    fn provider(
+        fail_after: usize,
+        ttl: Duration,
+        max_stale: Duration,
+    ) -> (CachingAttestationProvider, Arc<AtomicUsize>, Arc<Metrics>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let metrics = Arc::new(Metrics::default());
+        let inner = FlakyProvider {
+            calls: calls.clone(),
+            fail_after,
+        };
+
+        (
+            CachingAttestationProvider::new(
+                Box::new(inner),
+                None,
+                ttl,
+                ttl,
+                max_stale,
+                metrics.clone(),
+            ),
+            calls,
+            metrics,
+        )
+    }
+
+    #[test]
+    fn test_cache_hit() {
+        let (provider, calls, metrics) =
+            provider(usize::MAX, Duration::from_secs(60), Duration::from_secs(60));
+        let mut quote = vec![0u8; 1116];
+
+        assert_eq!(Some(vec![1, 2, 3]), provider.get_sig_rl([0; 4]).unwrap());
+        assert_eq!(Some(vec![1, 2, 3]), provider.get_sig_rl([0; 4]).unwrap());
+        assert!(provider.get_attestation_report(&quote).is_ok());
+
+        // Different basename (unlinkable quote) of same enclave for same report data
+        quote[20] = 1;
+        assert!(provider.get_attestation_report(&quote).is_ok());
+
+        assert_eq!(2, calls.load(Ordering::SeqCst));
+        assert_eq!(
+            1.0,
+            metrics.get("ra_sp_cache_hits_total", ("kind", "sig-rl"))
+        );
+        assert_eq!(
+            1.0,
+            metrics.get("ra_sp_cache_hits_total", ("kind", "report"))
+        );
+    }
+
+    #[test]
+    fn test_cached_report_not_served_for_different_report_data() {
+        let (provider, calls, metrics) =
+            provider(usize::MAX, Duration::from_secs(60), Duration::from_secs(60));
+        let mut quote = vec![0u8; 1116];
+
+        assert!(provider.get_attestation_report(&quote).is_ok());
+
+        // New certificate key of same enclave
+        quote[REPORT_DATA_OFFSET] = 1;
+        let report = provider.get_attestation_report(&quote).unwrap();
+
+        match report {
+            QuoteVerificationReport::Mock(ref mock_report) => {
+                assert_eq!(&quote[..QUOTE_BODY_LEN], &mock_report.mock_quote[..])
+            }
+            _ => panic!("Unexpected report"),
+        }
+
+        assert_eq!(2, calls.load(Ordering::SeqCst));
+        assert_eq!(
+            0.0,
+            metrics.get("ra_sp_cache_hits_total", ("kind", "report"))
+        );
+    }
+
+    #[test]
+    fn test_expired_report_served_on_provider_error() {
+        let (provider, calls, metrics) =
+            provider(1, Duration::from_secs(0), Duration::from_secs(60));
+        let mut quote = vec![0u8; 1116];
+
+        assert!(provider.get_attestation_report(&quote).is_ok());
+        assert!(provider.get_attestation_report(&quote).is_ok());
+
+        // Different enclave
+        quote[REPORT_BODY_OFFSET] = 1;
+        assert!(provider.get_attestation_report(&quote).is_err());
+
+        assert_eq!(3, calls.load(Ordering::SeqCst));
+        assert_eq!(
+            1.0,
+            metrics.get("ra_sp_cache_stale_fallbacks_total", ("kind", "report"))
+        );
+        assert_eq!(
+            2.0,
+            metrics.get("ra_sp_provider_errors_total", ("kind", "report"))
+        );
+    }
+
+    #[test]
+    fn test_stale_report_not_served_after_max_stale() {
+        let (provider, calls, metrics) =
+            provider(1, Duration::from_secs(0), Duration::from_secs(0));
+        let quote = vec![0u8; 1116];
+
+        assert!(provider.get_attestation_report(&quote).is_ok());
+        assert!(provider.get_attestation_report(&quote).is_err());
+
+        assert_eq!(2, calls.load(Ordering::SeqCst));
+        assert_eq!(
+            0.0,
+            metrics.get("ra_sp_cache_stale_fallbacks_total", ("kind", "report"))
+        );
+    }
+
+    #[test]
+    fn test_expired_sig_rl_not_served_on_provider_error() {
+        let (provider, calls, metrics) =
+            provider(1, Duration::from_secs(0), Duration::from_secs(60));
+
+        assert!(provider.get_sig_rl([0; 4]).is_ok());
+        assert!(provider.get_sig_rl([0; 4]).is_err());
+
+        assert_eq!(2, calls.load(Ordering::SeqCst));
+        assert_eq!(
+            0.0,
+            metrics.get("ra_sp_cache_stale_fallbacks_total", ("kind", "sig-rl"))
+        );
+    }
+
+    #[test]
+    fn test_enclave_identity_hash() {
+        let mut quote = vec![0u8; 1116];
+        let hash = enclave_identity_hash(&quote).unwrap();
+
+        // Basename and report data are not a part of enclave identity
+        quote[20] = 1;
+        quote[REPORT_DATA_OFFSET] = 1;
+        assert_eq!(hash, enclave_identity_hash(&quote).unwrap());
+
+        // MRENCLAVE is
+        quote[REPORT_BODY_OFFSET + 64] = 1;
+        assert_ne!(hash, enclave_identity_hash(&quote).unwrap());
+
+        assert!(enclave_identity_hash(&quote[..100]).is_err());
+    }
+
+    #[test]
+    fn test_quote_body_hash() {
+        let mut quote = vec![0u8; 1116];
+        let hash = quote_body_hash(&quote).unwrap();
+
+        // Signature part of quote does not affect the hash
+        quote[500] = 1;
+        assert_eq!(hash, quote_body_hash(&quote).unwrap());
+
+        quote[400] = 1;
+        assert_ne!(hash, quote_body_hash(&quote).unwrap());
+
+        assert!(quote_body_hash(&quote[..100]).is_err());
+    }
+}
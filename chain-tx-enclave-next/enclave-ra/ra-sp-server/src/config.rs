@@ -1,3 +1,6 @@
+use std::path::PathBuf;
+
+pub use ra_common::AttestationProviderKind;
 use serde::{Deserialize, Serialize};
 use structopt::StructOpt;
 
@@ -60,4 +63,91 @@ pub struct SpRaConfig {
         default_value = "/attestation/v4/report"
     )]
     pub ias_report_path: String,
+    /// Attestation provider used for verifying quotes (possible values: `ias` or `mock`)
+    #[structopt(
+        long = "attestation-provider",
+        help = "Attestation provider used for verifying quotes (default: `ias`)",
+        possible_values = &["ias", "mock"],
+        default_value = "ias"
+    )]
+    #[serde(default = "default_attestation_provider")]
+    pub attestation_provider: AttestationProviderKind,
+    /// Directory for persisting cached sig-rls and attestation reports (cache is only kept in
+    /// memory if this is not provided)
+    #[structopt(
+        long = "cache-dir",
+        help = "Directory for persisting cached sig-rls and attestation reports",
+        parse(from_os_str)
+    )]
+    #[serde(default)]
+    pub cache_dir: Option<PathBuf>,
+    /// Number of seconds for which a sig-rl is served from cache
+    #[structopt(
+        long = "sig-rl-cache-ttl",
+        help = "Number of seconds for which a sig-rl is served from cache (default: `3600`)",
+        default_value = "3600"
+    )]
+    #[serde(default = "default_sig_rl_cache_ttl_secs")]
+    pub sig_rl_cache_ttl_secs: u64,
+    /// Number of seconds for which an attestation report is served from cache
+    #[structopt(
+        long = "report-cache-ttl",
+        help = "Number of seconds for which an attestation report is served from cache (default: `600`)",
+        default_value = "600"
+    )]
+    #[serde(default = "default_report_cache_ttl_secs")]
+    pub report_cache_ttl_secs: u64,
+    /// Number of seconds after TTL for which an expired attestation report is served from cache
+    /// when attestation provider fails (TTL and this window together must stay well below the
+    /// report validity of verifiers, as older reports are rejected); expired sig-rls are never
+    /// served, as they may miss newly revoked signatures
+    #[structopt(
+        long = "report-max-stale",
+        help = "Number of seconds after TTL for which expired attestation reports are served when attestation provider fails (default: `3600`)",
+        default_value = "3600"
+    )]
+    #[serde(default = "default_report_max_stale_secs")]
+    pub report_max_stale_secs: u64,
+    /// TCP address for serving prometheus metrics over HTTP
+    #[structopt(
+        long = "metrics-address",
+        help = "TCP address for serving prometheus metrics over HTTP (metrics are not served if not provided)"
+    )]
+    #[serde(default)]
+    pub metrics_address: Option<String>,
+    /// Directory for recording responses of attestation provider
+    #[structopt(
+        long = "record-dir",
+        help = "Directory for recording responses of attestation provider (for replaying them in tests)",
+        parse(from_os_str)
+    )]
+    #[serde(default)]
+    pub record_dir: Option<PathBuf>,
+    /// Directory containing recorded responses of attestation provider (if provided, recorded
+    /// responses are served instead of contacting attestation provider; when `attestation_provider`
+    /// is `mock`, quotes without a recording get mock attestation reports)
+    #[structopt(
+        long = "replay-dir",
+        help = "Directory containing recorded responses of attestation provider (only for tests)",
+        parse(from_os_str),
+        conflicts_with = "record-dir"
+    )]
+    #[serde(default)]
+    pub replay_dir: Option<PathBuf>,
+}
+
+fn default_attestation_provider() -> AttestationProviderKind {
+    AttestationProviderKind::Ias
+}
+
+fn default_sig_rl_cache_ttl_secs() -> u64 {
+    3600
+}
+
+fn default_report_cache_ttl_secs() -> u64 {
+    600
+}
+
+fn default_report_max_stale_secs() -> u64 {
+    3600
 }
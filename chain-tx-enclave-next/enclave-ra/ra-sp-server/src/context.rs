@@ -1,22 +1,28 @@
-use std::convert::TryInto;
+use std::{convert::TryInto, sync::Arc, time::Duration};
 
 use aesm_client::{AesmClient, QuoteInfo, QuoteType};
 use hex::FromHex;
-use ra_common::{sp::QuoteResult, AttestationReport};
+use ra_common::{sp::QuoteResult, AttestationProviderKind, QuoteVerificationReport};
 use thiserror::Error;
 
 use crate::{
+    attestation_provider::{AttestationProvider, AttestationProviderError},
+    cache::CachingAttestationProvider,
     config::SpRaConfig,
-    ias_client::{IasClient, IasClientError},
+    ias_client::IasClient,
+    metrics::Metrics,
+    mock_provider::MockAttestationProvider,
+    replay::{RecordingAttestationProvider, ReplayAttestationProvider},
 };
 
 /// Wraps all the SP operations required for remote attestation
 pub struct SpRaContext {
     aesm_client: AesmClient,
-    ias_client: IasClient,
+    attestation_provider: Box<dyn AttestationProvider>,
     spid: [u8; 16],
     quote_info: QuoteInfo,
     quote_type: String,
+    metrics: Arc<Metrics>,
 }
 
 impl SpRaContext {
@@ -28,30 +34,66 @@ impl SpRaContext {
             .map_err(SpRaContextError::AesmError)?;
         let quote_type = config.quote_type;
 
-        let ias_client = IasClient::new(
-            config.ias_key,
-            config.ias_base_uri,
-            config.ias_sig_rl_path,
-            config.ias_report_path,
-        );
+        let mut attestation_provider: Box<dyn AttestationProvider> =
+            match (config.replay_dir, config.attestation_provider) {
+                (Some(replay_dir), AttestationProviderKind::Ias) => {
+                    Box::new(ReplayAttestationProvider::new(replay_dir, None))
+                }
+                (Some(replay_dir), AttestationProviderKind::Mock) => {
+                    Box::new(ReplayAttestationProvider::new(
+                        replay_dir,
+                        Some(Box::new(MockAttestationProvider)),
+                    ))
+                }
+                (None, AttestationProviderKind::Ias) => Box::new(IasClient::new(
+                    config.ias_key,
+                    config.ias_base_uri,
+                    config.ias_sig_rl_path,
+                    config.ias_report_path,
+                )),
+                (None, AttestationProviderKind::Mock) => Box::new(MockAttestationProvider),
+            };
+
+        if let Some(record_dir) = config.record_dir {
+            attestation_provider = Box::new(RecordingAttestationProvider::new(
+                attestation_provider,
+                record_dir,
+            ));
+        }
+
+        let metrics = Arc::new(Metrics::default());
+        let attestation_provider = Box::new(CachingAttestationProvider::new(
+            attestation_provider,
+            config.cache_dir,
+            Duration::from_secs(config.sig_rl_cache_ttl_secs),
+            Duration::from_secs(config.report_cache_ttl_secs),
+            Duration::from_secs(config.report_max_stale_secs),
+            metrics.clone(),
+        ));
 
         let spid = <[u8; 16]>::from_hex(config.spid).map_err(SpRaContextError::InvalidSpid)?;
 
         Ok(Self {
             aesm_client,
-            ias_client,
+            attestation_provider,
             spid,
             quote_info,
             quote_type,
+            metrics,
         })
     }
 
+    /// Returns metrics of SP operations
+    pub fn metrics(&self) -> &Arc<Metrics> {
+        &self.metrics
+    }
+
     /// Returns target info obtained from AESM
     pub fn get_target_info(&self) -> &[u8] {
         self.quote_info.target_info()
     }
 
-    /// Gets SigRL (Signature revocation list) from IAS
+    /// Gets SigRL (Signature revocation list) from attestation provider
     pub fn get_sig_rl(&self) -> Result<Option<Vec<u8>>, SpRaContextError> {
         let gid = self
             .quote_info
@@ -60,7 +102,9 @@ impl SpRaContext {
             .try_into()
             .map_err(SpRaContextError::InvalidGid)?;
 
-        self.ias_client.get_sig_rl(gid).map_err(Into::into)
+        self.attestation_provider
+            .get_sig_rl(gid)
+            .map_err(Into::into)
     }
 
     /// Generates a new quote from QE using AESM
@@ -87,10 +131,10 @@ impl SpRaContext {
         Ok(QuoteResult { quote, qe_report })
     }
 
-    /// Verifies quote using IAS
-    pub fn verify_quote(&self, quote: &[u8]) -> Result<AttestationReport, SpRaContextError> {
-        self.ias_client
-            .verify_attestation_evidence(quote)
+    /// Verifies quote using attestation provider (or returns a cached report for same quote body)
+    pub fn verify_quote(&self, quote: &[u8]) -> Result<QuoteVerificationReport, SpRaContextError> {
+        self.attestation_provider
+            .get_attestation_report(quote)
             .map_err(Into::into)
     }
 }
@@ -107,8 +151,8 @@ fn parse_quote_type(quote_type: &str) -> Result<QuoteType, SpRaContextError> {
 pub enum SpRaContextError {
     #[error("AESM error: {0}")]
     AesmError(aesm_client::Error),
-    #[error("IAS client error: {0}")]
-    IasError(#[from] IasClientError),
+    #[error("Attestation provider error: {0}")]
+    AttestationProviderError(#[from] AttestationProviderError),
     #[error("Invalid GID from AESM client: {0}")]
     InvalidGid(#[source] std::array::TryFromSliceError),
     #[error("Invalid quote type provided in configuration (possible values: `Linkable` or `Unlinkable`)")]
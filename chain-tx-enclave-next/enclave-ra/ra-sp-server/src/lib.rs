@@ -1,4 +1,9 @@
+mod attestation_provider;
+mod cache;
 pub mod config;
 mod context;
 mod ias_client;
+mod metrics;
+mod mock_provider;
+mod replay;
 pub mod server;
@@ -1,6 +1,11 @@
+mod attestation_provider;
+mod cache;
 mod config;
 mod context;
 mod ias_client;
+mod metrics;
+mod mock_provider;
+mod replay;
 mod server;
 
 use structopt::StructOpt;
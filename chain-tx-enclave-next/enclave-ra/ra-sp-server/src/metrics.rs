@@ -0,0 +1,191 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::Mutex,
+};
+
+/// Help text and type of all the metrics exported by SP server
+const METRICS: &[(&str, &str, &str)] = &[
+    (
+        "ra_sp_requests_total",
+        "counter",
+        "Number of requests received from enclaves",
+    ),
+    (
+        "ra_sp_request_errors_total",
+        "counter",
+        "Number of requests from enclaves which failed",
+    ),
+    (
+        "ra_sp_cache_hits_total",
+        "counter",
+        "Number of lookups served from attestation cache",
+    ),
+    (
+        "ra_sp_cache_misses_total",
+        "counter",
+        "Number of lookups not found (or expired) in attestation cache",
+    ),
+    (
+        "ra_sp_cache_stale_fallbacks_total",
+        "counter",
+        "Number of expired cache entries served because attestation provider failed",
+    ),
+    (
+        "ra_sp_provider_requests_total",
+        "counter",
+        "Number of requests sent to attestation provider",
+    ),
+    (
+        "ra_sp_provider_errors_total",
+        "counter",
+        "Number of failed requests sent to attestation provider",
+    ),
+    (
+        "ra_sp_provider_request_duration_seconds",
+        "summary",
+        "Time spent waiting for attestation provider",
+    ),
+];
+
+/// Prometheus style metrics of SP server (all the metrics have a single label)
+///
+/// Values are keyed by metric name, series suffix (empty for counters), label name and label value
+#[derive(Debug, Default)]
+pub struct Metrics {
+    values: Mutex<BTreeMap<(&'static str, &'static str, &'static str, &'static str), f64>>,
+}
+
+impl Metrics {
+    /// Increments the value of given counter by one
+    pub fn inc(&self, name: &'static str, label: (&'static str, &'static str)) {
+        self.add(name, label, 1.0)
+    }
+
+    /// Adds given value to the value of given counter
+    pub fn add(&self, name: &'static str, label: (&'static str, &'static str), value: f64) {
+        let mut values = self.values.lock().unwrap();
+        *values.entry((name, "", label.0, label.1)).or_default() += value;
+    }
+
+    /// Records an observation of given summary (updates both its sum and count)
+    pub fn observe(&self, name: &'static str, label: (&'static str, &'static str), value: f64) {
+        let mut values = self.values.lock().unwrap();
+        *values.entry((name, "_sum", label.0, label.1)).or_default() += value;
+        *values
+            .entry((name, "_count", label.0, label.1))
+            .or_default() += 1.0;
+    }
+
+    /// Returns current value of given counter
+    #[cfg(test)]
+    pub fn get(&self, name: &'static str, label: (&'static str, &'static str)) -> f64 {
+        let values = self.values.lock().unwrap();
+        values
+            .get(&(name, "", label.0, label.1))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Renders all the metrics in prometheus text exposition format
+    pub fn render(&self) -> String {
+        let values = self.values.lock().unwrap();
+        let mut output = String::new();
+
+        for (name, metric_type, help) in METRICS {
+            let _ = writeln!(output, "# HELP {} {}", name, help);
+            let _ = writeln!(output, "# TYPE {} {}", name, metric_type);
+
+            for ((_, suffix, label_name, label_value), value) in values
+                .iter()
+                .filter(|((metric_name, _, _, _), _)| metric_name == name)
+            {
+                let _ = writeln!(
+                    output,
+                    "{}{}{{{}=\"{}\"}} {}",
+                    name, suffix, label_name, label_value, value
+                );
+            }
+        }
+
+        output
+    }
+
+    /// Serves metrics over HTTP on given address (this blocks current thread)
+    pub fn serve(&self, addrs: impl ToSocketAddrs) -> std::io::Result<()> {
+        let listener = TcpListener::bind(addrs)?;
+
+        for stream in listener.incoming() {
+            if let Err(e) = stream.and_then(|stream| self.handle_http_connection(stream)) {
+                log::warn!("Error while serving metrics: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_http_connection(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        // Read request headers (request itself is ignored as there is only one endpoint)
+        let mut reader = BufReader::new(&stream);
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+
+            if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+        }
+
+        let body = self.render();
+
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )?;
+        stream.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_render() {
+        let metrics = Metrics::default();
+
+        metrics.inc("ra_sp_requests_total", ("request", "get_quote"));
+        metrics.inc("ra_sp_requests_total", ("request", "get_quote"));
+        metrics.observe(
+            "ra_sp_provider_request_duration_seconds",
+            ("kind", "report"),
+            0.5,
+        );
+        metrics.observe(
+            "ra_sp_provider_request_duration_seconds",
+            ("kind", "report"),
+            0.25,
+        );
+
+        assert_eq!(
+            2.0,
+            metrics.get("ra_sp_requests_total", ("request", "get_quote"))
+        );
+
+        let rendered = metrics.render();
+
+        assert!(rendered.contains("# TYPE ra_sp_requests_total counter\n"));
+        assert!(rendered.contains("ra_sp_requests_total{request=\"get_quote\"} 2\n"));
+        assert!(rendered.contains("# TYPE ra_sp_provider_request_duration_seconds summary\n"));
+        assert!(rendered
+            .contains("ra_sp_provider_request_duration_seconds_sum{kind=\"report\"} 0.75\n"));
+        assert!(
+            rendered.contains("ra_sp_provider_request_duration_seconds_count{kind=\"report\"} 2\n")
+        );
+    }
+}
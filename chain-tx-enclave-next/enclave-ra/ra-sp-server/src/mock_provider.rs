@@ -0,0 +1,59 @@
+use ra_common::{MockAttestationReport, Quote, QuoteVerificationReport};
+
+use crate::attestation_provider::{AttestationProvider, AttestationProviderError};
+
+/// Length of quote header and report body
+const QUOTE_BODY_LEN: usize = 432;
+
+/// Attestation provider which does not contact any external service (only for tests)
+///
+/// Generated reports are not signed and are only accepted by verifiers which explicitly trust
+/// mock attestation.
+#[derive(Debug, Default)]
+pub struct MockAttestationProvider;
+
+impl AttestationProvider for MockAttestationProvider {
+    fn get_sig_rl(&self, _gid: [u8; 4]) -> Result<Option<Vec<u8>>, AttestationProviderError> {
+        Ok(None)
+    }
+
+    fn get_attestation_report(
+        &self,
+        quote: &[u8],
+    ) -> Result<QuoteVerificationReport, AttestationProviderError> {
+        let mock_quote = quote
+            .get(..QUOTE_BODY_LEN)
+            .ok_or_else(|| AttestationProviderError::InvalidQuote)?;
+
+        if Quote::try_copy_from(mock_quote).is_none() {
+            return Err(AttestationProviderError::InvalidQuote);
+        }
+
+        Ok(QuoteVerificationReport::Mock(MockAttestationReport {
+            mock_quote: mock_quote.to_vec(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_attestation_report() {
+        let provider = MockAttestationProvider;
+        let mut quote = vec![0u8; 1116];
+        quote[368..432].copy_from_slice(&[1u8; 64]);
+
+        assert!(provider.get_sig_rl([0; 4]).unwrap().is_none());
+
+        match provider.get_attestation_report(&quote).unwrap() {
+            QuoteVerificationReport::Mock(report) => {
+                assert_eq!(&quote[..432], &report.mock_quote[..])
+            }
+            _ => panic!("Mock provider generated non-mock report"),
+        }
+
+        assert!(provider.get_attestation_report(&quote[..100]).is_err());
+    }
+}
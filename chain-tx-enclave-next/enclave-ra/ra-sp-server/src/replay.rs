@@ -0,0 +1,134 @@
+use std::path::{Path, PathBuf};
+
+use ra_common::QuoteVerificationReport;
+
+use crate::{
+    attestation_provider::{AttestationProvider, AttestationProviderError},
+    cache::{quote_body_hash, read_json_file, write_json_file},
+};
+
+fn sig_rl_file(dir: &Path, gid: [u8; 4]) -> PathBuf {
+    dir.join(format!("sig-rl-{}.json", hex::encode(gid)))
+}
+
+fn report_file(dir: &Path, quote: &[u8]) -> Result<PathBuf, AttestationProviderError> {
+    Ok(dir.join(format!("report-{}.json", quote_body_hash(quote)?)))
+}
+
+/// Attestation provider which records all the responses of another provider to disk (these
+/// recordings can later be served by [`ReplayAttestationProvider`])
+pub struct RecordingAttestationProvider {
+    inner: Box<dyn AttestationProvider>,
+    record_dir: PathBuf,
+}
+
+impl RecordingAttestationProvider {
+    /// Creates a new recording attestation provider
+    pub fn new(inner: Box<dyn AttestationProvider>, record_dir: PathBuf) -> Self {
+        Self { inner, record_dir }
+    }
+}
+
+impl AttestationProvider for RecordingAttestationProvider {
+    fn get_sig_rl(&self, gid: [u8; 4]) -> Result<Option<Vec<u8>>, AttestationProviderError> {
+        let sig_rl = self.inner.get_sig_rl(gid)?;
+        write_json_file(&sig_rl_file(&self.record_dir, gid), &sig_rl)
+            .map_err(AttestationProviderError::RecordingError)?;
+        Ok(sig_rl)
+    }
+
+    fn get_attestation_report(
+        &self,
+        quote: &[u8],
+    ) -> Result<QuoteVerificationReport, AttestationProviderError> {
+        let report = self.inner.get_attestation_report(quote)?;
+        write_json_file(&report_file(&self.record_dir, quote)?, &report)
+            .map_err(AttestationProviderError::RecordingError)?;
+        Ok(report)
+    }
+}
+
+/// Attestation provider which serves responses recorded by [`RecordingAttestationProvider`]
+/// without any network access (only for tests)
+///
+/// Attestation reports are looked up by header and report body of the quote, so, a recorded
+/// report is only served for quotes generated by the same enclave for the same report data.
+/// Quotes for freshly generated keys (e.g., renewed enclave certificates) can never have a
+/// recording, so, these are forwarded to `fallback` (if provided) instead of failing.
+pub struct ReplayAttestationProvider {
+    replay_dir: PathBuf,
+    fallback: Option<Box<dyn AttestationProvider>>,
+}
+
+impl ReplayAttestationProvider {
+    /// Creates a new replay attestation provider
+    pub fn new(replay_dir: PathBuf, fallback: Option<Box<dyn AttestationProvider>>) -> Self {
+        Self {
+            replay_dir,
+            fallback,
+        }
+    }
+}
+
+impl AttestationProvider for ReplayAttestationProvider {
+    fn get_sig_rl(&self, gid: [u8; 4]) -> Result<Option<Vec<u8>>, AttestationProviderError> {
+        let path = sig_rl_file(&self.replay_dir, gid);
+
+        // A missing sig-rl recording is treated as an empty revocation list
+        Ok(read_json_file(&path).unwrap_or_default())
+    }
+
+    fn get_attestation_report(
+        &self,
+        quote: &[u8],
+    ) -> Result<QuoteVerificationReport, AttestationProviderError> {
+        let path = report_file(&self.replay_dir, quote)?;
+
+        match (read_json_file(&path), &self.fallback) {
+            (Some(report), _) => Ok(report),
+            (None, Some(fallback)) => fallback.get_attestation_report(quote),
+            (None, None) => Err(AttestationProviderError::MissingRecording(
+                path.display().to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_provider::MockAttestationProvider;
+
+    #[test]
+    fn test_record_and_replay() {
+        let dir = std::env::temp_dir().join(format!("ra-sp-replay-{}", std::process::id()));
+        let quote = vec![1u8; 1116];
+
+        let recorder =
+            RecordingAttestationProvider::new(Box::new(MockAttestationProvider), dir.clone());
+        assert!(recorder.get_sig_rl([0; 4]).unwrap().is_none());
+        let recorded = recorder.get_attestation_report(&quote).unwrap();
+
+        let replayer = ReplayAttestationProvider::new(dir.clone(), None);
+        assert!(replayer.get_sig_rl([0; 4]).unwrap().is_none());
+        let replayed = replayer.get_attestation_report(&quote).unwrap();
+        assert_eq!(
+            serde_json::to_vec(&recorded).unwrap(),
+            serde_json::to_vec(&replayed).unwrap()
+        );
+
+        assert!(matches!(
+            replayer.get_attestation_report(&[2u8; 1116]).unwrap_err(),
+            AttestationProviderError::MissingRecording(_)
+        ));
+
+        let replayer =
+            ReplayAttestationProvider::new(dir.clone(), Some(Box::new(MockAttestationProvider)));
+        assert!(matches!(
+            replayer.get_attestation_report(&[2u8; 1116]).unwrap(),
+            QuoteVerificationReport::Mock(_)
+        ));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
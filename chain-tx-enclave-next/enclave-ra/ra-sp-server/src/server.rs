@@ -15,19 +15,35 @@ use crate::{
 /// SP TCP server for enclave remote attestation
 pub struct SpRaServer {
     context: Arc<SpRaContext>,
+    metrics_address: Option<String>,
 }
 
 impl SpRaServer {
     /// Creates a new instance of SP TCP server for remote attestation
     pub fn new(config: SpRaConfig) -> Result<Self, SpRaServerError> {
+        let metrics_address = config.metrics_address.clone();
         let context = Arc::new(SpRaContext::new(config)?);
 
-        Ok(Self { context })
+        Ok(Self {
+            context,
+            metrics_address,
+        })
     }
 
     pub fn run(&self, addrs: impl ToSocketAddrs) -> Result<(), SpRaServerError> {
         let listener = TcpListener::bind(addrs)?;
 
+        if let Some(ref metrics_address) = self.metrics_address {
+            let metrics = self.context.metrics().clone();
+            let metrics_address = metrics_address.clone();
+
+            thread::spawn(move || {
+                if let Err(e) = metrics.serve(&metrics_address) {
+                    log::error!("Unable to serve SP RA metrics: {}", e);
+                }
+            });
+        }
+
         for stream in listener.incoming() {
             let stream = stream?;
             let context = self.context.clone();
@@ -56,24 +72,15 @@ fn handle_connection(context: &SpRaContext, stream: TcpStream) -> Result<(), SpR
 
         log::debug!("Received request: {:?}", request);
 
-        let response = match request {
-            Request::GetTargetInfo => {
-                let target_info = context.get_target_info().to_vec();
+        let request_label = ("request", request_name(&request));
+        context.metrics().inc("ra_sp_requests_total", request_label);
 
-                Response::GetTargetInfo { target_info }
-            }
-            Request::GetQuote { report, nonce } => {
-                let sig_rl = context.get_sig_rl()?.unwrap_or_default();
-                let quote_result = context.get_quote(report, sig_rl, nonce)?;
-
-                Response::GetQuote { quote_result }
-            }
-            Request::GetAttestationReport { ref quote } => {
-                let attestation_report = context.verify_quote(quote)?;
-
-                Response::GetAttestationReport { attestation_report }
-            }
-        };
+        let response = handle_request(context, request).map_err(|e| {
+            context
+                .metrics()
+                .inc("ra_sp_request_errors_total", request_label);
+            e
+        })?;
 
         log::debug!("Sending response: {:?}", response);
 
@@ -81,6 +88,37 @@ fn handle_connection(context: &SpRaContext, stream: TcpStream) -> Result<(), SpR
     }
 }
 
+fn request_name(request: &Request) -> &'static str {
+    match request {
+        Request::GetTargetInfo => "get_target_info",
+        Request::GetQuote { .. } => "get_quote",
+        Request::GetAttestationReport { .. } => "get_attestation_report",
+    }
+}
+
+fn handle_request(context: &SpRaContext, request: Request) -> Result<Response, SpRaServerError> {
+    let response = match request {
+        Request::GetTargetInfo => {
+            let target_info = context.get_target_info().to_vec();
+
+            Response::GetTargetInfo { target_info }
+        }
+        Request::GetQuote { report, nonce } => {
+            let sig_rl = context.get_sig_rl()?.unwrap_or_default();
+            let quote_result = context.get_quote(report, sig_rl, nonce)?;
+
+            Response::GetQuote { quote_result }
+        }
+        Request::GetAttestationReport { ref quote } => {
+            let attestation_report = context.verify_quote(quote)?;
+
+            Response::GetAttestationReport { attestation_report }
+        }
+    };
+
+    Ok(response)
+}
+
 #[derive(Debug, Error)]
 pub enum SpRaServerError {
     #[error("IO error: {0}")]
@@ -92,6 +92,14 @@ pub trait CipherSuite: Debug + Clone + Default + Sized {
         Secret::new(okm)
     }
 
+    /// spec: draft-ietf-mls-protocol.md#exporters
+    fn derive_exporter_secret(exporter_secret: &KeySecret<Self>, label: &str, okm: &mut [u8]) {
+        let prk = Self::derive_group_secret(exporter_secret, label);
+        let hkdf =
+            Hkdf::<Self>::from_prk(prk.expose_secret().as_ref()).expect("size of prk == Kdf.Nk");
+        expand_with_label::<Self>(&hkdf, "exporter", okm).expect("exported secret too long");
+    }
+
     /// spec: draft-ietf-mls-protocol.md#welcoming-new-members
     /// returns (welcome_key, welcome_nonce)
     fn derive_welcome_secret(
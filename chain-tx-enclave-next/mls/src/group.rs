@@ -21,7 +21,7 @@ use crate::message::{
     MLSPlaintext, MLSPlaintextCommon, MLSPlaintextTBS, PathSecret, Proposal, ProposalId, Remove,
     Sender, SenderType, Update, Welcome,
 };
-use crate::secrets::EpochSecrets;
+use crate::secrets::{EpochSecrets, OBFUSCATION_KEY_LEN};
 use crate::tree::{Node, RatchetTreeExt, TreeEvolveResult, TreePublicKey, TreeSecret};
 use crate::tree_math::{LeafSize, NodeSize, NodeType, ParentSize};
 use crate::utils::{encode_vec_u8_u16, encode_vec_u8_u8, read_vec_u8_u16, read_vec_u8_u8};
@@ -91,6 +91,12 @@ impl<CS: CipherSuite + Ord> GroupAux<CS> {
         })
     }
 
+    /// the transaction obfuscation key exported from the current epoch
+    /// (all the members agree on it after processing the same commit)
+    pub fn get_obfuscation_key(&self) -> Secret<[u8; OBFUSCATION_KEY_LEN]> {
+        self.secrets.get_obfuscation_key()
+    }
+
     fn get_sender(&self) -> Sender {
         Sender {
             sender_type: SenderType::Member,
@@ -1253,6 +1259,39 @@ pub mod test {
         assert_eq!(member2_group.tree.get_package(member3_group.my_pos), None);
     }
 
+    #[test]
+    fn test_obfuscation_key_rotation() {
+        let ra_verifier = MockVerifier {};
+        let (mut member1_group, mut member2_group, member3_group) = three_member_setup();
+        let key = member1_group.get_obfuscation_key();
+        assert_eq!(
+            key.expose_secret(),
+            member2_group.get_obfuscation_key().expose_secret()
+        );
+        assert_eq!(
+            key.expose_secret(),
+            member3_group.get_obfuscation_key().expose_secret()
+        );
+
+        // a new epoch (remove member3) derives a new key
+        let proposals = vec![member1_group
+            .get_signed_remove(member3_group.my_pos)
+            .unwrap()];
+        let (commit, _welcome) = member1_group.commit_proposals(&proposals).unwrap();
+        member1_group
+            .process_commit(commit.clone(), &proposals, &ra_verifier, 0)
+            .expect("commit ok");
+        member2_group
+            .process_commit(commit, &proposals, &ra_verifier, 0)
+            .expect("commit ok");
+        let new_key = member1_group.get_obfuscation_key();
+        assert_eq!(
+            new_key.expose_secret(),
+            member2_group.get_obfuscation_key().expose_secret()
+        );
+        assert_ne!(key.expose_secret(), new_key.expose_secret());
+    }
+
     #[test]
     fn test_invalid_commit() {
         // process invalid commit don't end up partial mutated state.
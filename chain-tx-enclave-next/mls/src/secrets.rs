@@ -6,11 +6,17 @@ use crate::ciphersuite::{
     AeadKeySize, AeadNonceSize, CipherSuite, HashValue, KeySecret, NodeSecret,
 };
 
+/// length of the transaction obfuscation key (AES-128-GCM-SIV)
+pub const OBFUSCATION_KEY_LEN: usize = 16;
+/// label used for exporting the transaction obfuscation key
+const OBFUSCATION_KEY_LABEL: &str = "tx obfuscation key";
+
 pub struct EpochSecrets<CS: CipherSuite> {
     pub init_secret: Secret<KeySecret<CS>>,
     pub confirmation_key: Secret<KeySecret<CS>>,
     pub joiner_secret: Secret<KeySecret<CS>>,
     pub epoch_secret: Secret<KeySecret<CS>>,
+    pub exporter_secret: Secret<KeySecret<CS>>,
 }
 
 impl<CS: CipherSuite> EpochSecrets<CS> {
@@ -42,7 +48,7 @@ impl<CS: CipherSuite> EpochSecrets<CS> {
         let _sender_data_secret =
             CS::derive_group_secret(epoch_secret.expose_secret(), "sender data");
         let _handshake_secret = CS::derive_group_secret(epoch_secret.expose_secret(), "handshake");
-        let _exporter_secret = CS::derive_group_secret(epoch_secret.expose_secret(), "exporter");
+        let exporter_secret = CS::derive_group_secret(epoch_secret.expose_secret(), "exporter");
 
         let confirmation_key = CS::derive_group_secret(epoch_secret.expose_secret(), "confirm");
         let init_secret = CS::derive_group_secret(epoch_secret.expose_secret(), "init");
@@ -52,6 +58,7 @@ impl<CS: CipherSuite> EpochSecrets<CS> {
             confirmation_key,
             joiner_secret,
             epoch_secret,
+            exporter_secret,
         }
     }
 
@@ -75,6 +82,19 @@ impl<CS: CipherSuite> EpochSecrets<CS> {
         CS::derive_welcome_secret(self.joiner_secret.expose_secret())
     }
 
+    /// spec: draft-ietf-mls-protocol.md#exporters
+    /// (the context is always empty)
+    pub fn export_secret(&self, label: &str, okm: &mut [u8]) {
+        CS::derive_exporter_secret(self.exporter_secret.expose_secret(), label, okm)
+    }
+
+    /// the transaction obfuscation key of this epoch
+    pub fn get_obfuscation_key(&self) -> Secret<[u8; OBFUSCATION_KEY_LEN]> {
+        let mut key = [0u8; OBFUSCATION_KEY_LEN];
+        self.export_secret(OBFUSCATION_KEY_LABEL, &mut key);
+        Secret::new(key)
+    }
+
     pub fn compute_confirmation(&self, confirmed_transcript: &HashValue<CS>) -> HashValue<CS> {
         // HMAC(confirmation_key, GroupContext.confirmed_transcript_hash)
         HashValue(CS::extract(
@@ -14,16 +14,18 @@ use sgx_isa::Report;
 use thread_pool::ThreadPool;
 use webpki::DNSNameRef;
 
-use chain_core::tx::data::TxId;
+use chain_core::state::tendermint::BlockHeight;
 use enclave_protocol::{
     codec::{StreamRead, StreamWrite},
-    tdbe_protocol::{TrustedTdbeRequest, TrustedTdbeResponse},
+    tdbe_protocol::{TransactionsPage, TrustedTdbeRequest, TrustedTdbeResponse},
 };
 use ra_client::{EnclaveCertVerifier, EnclaveCertVerifierConfig, EnclaveInfo};
 use ra_enclave::{EnclaveRaConfig, EnclaveRaContext, DEFAULT_EXPIRATION_SECS};
-use tdbe_common::TdbeConfig;
+use tdbe_common::{TdbeConfig, TdbePeer};
 
 const THREAD_POOL_SIZE: usize = 4;
+/// Number of times each peer is tried for a single page before giving up on fetching initial data
+const MAX_ATTEMPTS_PER_PEER: usize = 3;
 
 pub fn entry() -> std::io::Result<()> {
     // Initialize logger
@@ -39,23 +41,23 @@ pub fn entry() -> std::io::Result<()> {
     // Create remote attestation context
     let context = create_ra_context();
 
-    // Fetch initial transaction data if TDBE is configured to connect to another TDBE server
-    if let Some(ref tdbe_dns_name) = tdbe_config.tdbe_dns_name {
+    // Connect to ZeroMQ
+    log::info!("Connecting to ZeroMQ");
+    let zmq_stream = Arc::new(Mutex::new(TcpStream::connect("zmq")?));
+
+    // Fetch initial transaction data if TDBE is configured to connect to other TDBE servers
+    if !tdbe_config.tdbe_peers.is_empty() {
         if let Err(err) = fetch_initial_data(
             &context,
             verifier.clone(),
-            tdbe_dns_name,
-            &tdbe_config.transaction_ids,
+            &tdbe_config,
+            &mut zmq_stream.lock().unwrap(),
         ) {
-            log::error!("Unable to fetch initial data from another TDBE server");
+            log::error!("Unable to fetch initial data from other TDBE servers");
             return Err(err);
         }
     }
 
-    // Connect to ZeroMQ
-    log::info!("Connecting to ZeroMQ");
-    let zmq_stream = Arc::new(Mutex::new(TcpStream::connect("zmq")?));
-
     // Start TDBE server
     log::info!("Starting TBDE Server");
     let listener = TcpListener::bind("tdbe")?;
@@ -108,6 +110,21 @@ fn handle_connection<T: Read + Write>(mut stream: T, zmq_stream: Arc<Mutex<TcpSt
                             },
                         }
                     }
+                    TrustedTdbeRequest::GetTransactionsInBlockRange {
+                        start_height,
+                        end_height,
+                        page_size,
+                    } => match handler::get_transactions_in_block_range(
+                        start_height,
+                        end_height,
+                        page_size,
+                        &mut zmq_stream.lock().unwrap(),
+                    ) {
+                        Ok(page) => TrustedTdbeResponse::GetTransactionsInBlockRange(page),
+                        Err(message) => TrustedTdbeResponse::Error {
+                            message: message.into(),
+                        },
+                    },
                 };
 
                 if let Err(err) = tdbe_response.write_to(&mut stream) {
@@ -177,39 +194,166 @@ fn create_ra_context() -> Arc<EnclaveRaContext> {
 fn fetch_initial_data(
     context: &EnclaveRaContext,
     verifier: EnclaveCertVerifier,
-    tdbe_dns_name: &str,
-    transaction_ids: &[TxId],
+    tdbe_config: &TdbeConfig,
+    zmq_stream: &mut TcpStream,
 ) -> std::io::Result<()> {
-    log::info!("Fetching initial data from another TDBE server");
+    // resume from the sync cursor stored after the last fully stored page (if any)
+    let start_height = match handler::get_sync_cursor(zmq_stream)
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?
+    {
+        Some(sync_cursor) if sync_cursor > tdbe_config.start_height => sync_cursor,
+        _ => tdbe_config.start_height,
+    };
+
+    if start_height > tdbe_config.end_height {
+        log::info!(
+            "Initial data for blocks up to {} was already fetched",
+            tdbe_config.end_height
+        );
+        return Ok(());
+    }
+
+    log::info!(
+        "Fetching initial data for blocks {}..={} from other TDBE servers",
+        start_height,
+        tdbe_config.end_height
+    );
+
+    let mut cursor = Some(start_height);
+    let mut peer_index = 0;
+    let mut tls_stream = None;
+    let mut failed_attempts = 0;
 
-    // Create attested TLS stream
-    let mut tls_stream = create_tls_client_stream(context, verifier, tdbe_dns_name, "tdbe")?;
+    while let Some(start_height) = cursor {
+        let peer = &tdbe_config.tdbe_peers[peer_index];
+
+        let result = fetch_page(
+            context,
+            verifier.clone(),
+            peer,
+            &mut tls_stream,
+            start_height,
+            tdbe_config,
+            zmq_stream,
+        )
+        .and_then(|page| {
+            handler::store_transactions(&page, zmq_stream)
+                .map_err(|err| format!("Unable to store page: {}", err))?;
+            Ok(page)
+        });
+
+        match result {
+            Ok(page) => {
+                let num_transactions: usize = page
+                    .blocks
+                    .iter()
+                    .map(|block| block.transactions.len())
+                    .sum();
+                log::info!(
+                    "{} transactions received for {} blocks starting at {}",
+                    num_transactions,
+                    page.blocks.len(),
+                    start_height
+                );
+                cursor = page.next_height;
+                failed_attempts = 0;
+
+                if let Some(next_height) = cursor {
+                    log::info!("Sync cursor: {}", next_height);
+                }
+            }
+            Err(err) => {
+                log::warn!(
+                    "Unable to fetch page starting at {} from {}: {}",
+                    start_height,
+                    peer.dns_name,
+                    err
+                );
+
+                failed_attempts += 1;
+                if failed_attempts >= tdbe_config.tdbe_peers.len() * MAX_ATTEMPTS_PER_PEER {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::Other,
+                        format!(
+                            "Unable to fetch initial data from any TDBE server (sync cursor: {})",
+                            start_height
+                        ),
+                    ));
+                }
+
+                // Retry from the same cursor against the next peer
+                tls_stream = None;
+                peer_index = (peer_index + 1) % tdbe_config.tdbe_peers.len();
+            }
+        }
+    }
+
+    log::info!("Finished fetching initial data from other TDBE servers");
+
+    Ok(())
+}
+
+/// Fetches one page of transaction data from given peer (reusing the connection if there is one)
+/// and verifies it against transaction merkle roots stored in local chain-abci
+fn fetch_page(
+    context: &EnclaveRaContext,
+    verifier: EnclaveCertVerifier,
+    peer: &TdbePeer,
+    tls_stream: &mut Option<StreamOwned<ClientSession, TcpStream>>,
+    start_height: BlockHeight,
+    tdbe_config: &TdbeConfig,
+    zmq_stream: &mut TcpStream,
+) -> Result<TransactionsPage, String> {
+    if tls_stream.is_none() {
+        *tls_stream = Some(
+            create_tls_client_stream(context, verifier, &peer.dns_name, &peer.address)
+                .map_err(|err| format!("Unable to connect: {}", err))?,
+        );
+    }
+    let stream = tls_stream.as_mut().unwrap();
 
     // Create request to send to TDBE server
-    let request = TrustedTdbeRequest::GetTransactionsWithOutputs {
-        transaction_ids: transaction_ids.into(),
+    let request = TrustedTdbeRequest::GetTransactionsInBlockRange {
+        start_height,
+        end_height: tdbe_config.end_height,
+        page_size: tdbe_config.page_size,
     };
 
     // Write request to stream
-    request.write_to(&mut tls_stream)?;
+    request
+        .write_to(&mut *stream)
+        .map_err(|err| format!("Error while writing request: {}", err))?;
 
     // Read response from stream
-    let response = TrustedTdbeResponse::read_from(&mut tls_stream)?;
+    let response = TrustedTdbeResponse::read_from(&mut *stream)
+        .map_err(|err| format!("Error while reading response: {}", err))?;
 
-    match response {
-        TrustedTdbeResponse::GetTransactionsWithOutputs { transactions } => {
-            log::info!("{} transactions received", transactions.len())
-        }
+    let page = match response {
+        TrustedTdbeResponse::GetTransactionsInBlockRange(page) => page,
         TrustedTdbeResponse::Error { message } => {
-            log::error!("Received error: {}", message);
-            panic!("Cannot fetch initial data from another TDBE server");
+            return Err(format!("Received error: {}", message))
         }
-    }
-    // TODO: Persist response
+        _ => return Err("Unexpected response".to_owned()),
+    };
 
-    log::info!("Finished fetching initial data from another TDBE server");
+    let last_height = match page.blocks.last() {
+        Some(block) => block.block_height,
+        None => return Err("Received empty page".to_owned()),
+    };
 
-    Ok(())
+    let local_blocks = if last_height < start_height {
+        Vec::new()
+    } else {
+        handler::get_block_txids(start_height, last_height, zmq_stream)?
+    };
+
+    page.verify(start_height, tdbe_config.end_height, |height| {
+        local_blocks
+            .iter()
+            .find(|block| block.block_height == height)
+    })?;
+
+    Ok(page)
 }
 
 fn create_tls_client_stream(
@@ -7,10 +7,18 @@ use std::{
 use parity_scale_codec::{Decode, Encode};
 use zeroize::Zeroize;
 
+use chain_core::state::tendermint::BlockHeight;
 use chain_core::tx::{data::TxId, TxWithOutputs};
-use enclave_protocol::{EnclaveRequest, EnclaveResponse};
+use enclave_protocol::{
+    tdbe_protocol::{BlockTransactions, TransactionsPage},
+    BlockTxIds, EnclaveRequest, EnclaveResponse, SealedLog, ENCRYPTION_REQUEST_SIZE,
+    MAX_BLOCK_TXIDS_RANGE,
+};
 use enclave_utils::SealedData;
 
+/// Maximum number of transactions served in one page (requested page sizes are capped to this)
+const MAX_PAGE_SIZE: u32 = 5000;
+
 /// Retrieves all the transactions with outputs with given transaction IDs
 pub fn get_transactions_with_outputs(
     transaction_ids: Vec<TxId>,
@@ -19,32 +27,10 @@ pub fn get_transactions_with_outputs(
     // Prepare enclave request
     let enclave_request = EnclaveRequest::GetSealedTxData {
         txids: transaction_ids.clone(),
-    }
-    .encode();
-
-    // Send request to ZeroMQ
-    zmq_stream
-        .write_all(&enclave_request)
-        .map_err(|err| format!("Error while writing request to ZeroMQ: {}", err))?;
-
-    // Read reponse length from ZeroMQ (little endian u32 bytes)
-    let mut response_len = [0u8; 4];
-    zmq_stream
-        .read(&mut response_len)
-        .map_err(|err| format!("Error while reading reponse length from ZeroMQ: {}", err))?;
-
-    let response_len: usize = u32::from_le_bytes(response_len)
-        .try_into()
-        .expect("Response length exceeds `usize` bounds");
+    };
 
-    // Read result from ZeroMQ
-    let mut result_buf = vec![0u8; response_len];
-    zmq_stream
-        .read(&mut result_buf)
-        .map_err(|err| format!("Error while reading response from ZeroMQ: {}", err))?;
-
-    match EnclaveResponse::decode(&mut result_buf.as_ref()) {
-        Ok(EnclaveResponse::GetSealedTxData(Some(sealed_logs))) => {
+    match send_enclave_request(&enclave_request, zmq_stream)? {
+        EnclaveResponse::GetSealedTxData(Some(sealed_logs)) => {
             let mut transactions_with_outputs = Vec::with_capacity(sealed_logs.len());
 
             for (txid, sealed_log) in transaction_ids.into_iter().zip(sealed_logs.into_iter()) {
@@ -71,11 +57,204 @@ pub fn get_transactions_with_outputs(
 
             Ok(transactions_with_outputs)
         }
-        Ok(EnclaveResponse::GetSealedTxData(None)) => Err("Transactions not found".to_owned()),
-        Ok(_) => Err("Unexpected response from ZeroMQ".to_owned()),
-        Err(err) => Err(format!(
-            "Error while decoding response from ZeroMQ: {}",
-            err
+        EnclaveResponse::GetSealedTxData(None) => Err("Transactions not found".to_owned()),
+        _ => Err("Unexpected response from ZeroMQ".to_owned()),
+    }
+}
+
+/// Retrieves committed transaction IDs (and transaction merkle roots) of blocks in given
+/// inclusive height range
+pub fn get_block_txids(
+    start_height: BlockHeight,
+    end_height: BlockHeight,
+    zmq_stream: &mut TcpStream,
+) -> Result<Vec<BlockTxIds>, String> {
+    let enclave_request = EnclaveRequest::GetBlockTxIds {
+        start_height,
+        end_height,
+    };
+
+    match send_enclave_request(&enclave_request, zmq_stream)? {
+        EnclaveResponse::GetBlockTxIds(Some(blocks)) => Ok(blocks),
+        EnclaveResponse::GetBlockTxIds(None) => Err(format!(
+            "Blocks not found in range: {}..={}",
+            start_height, end_height
         )),
+        _ => Err("Unexpected response from ZeroMQ".to_owned()),
+    }
+}
+
+/// Retrieves one page of transactions with outputs committed in blocks starting at
+/// `start_height`. The page contains whole blocks and stops after `page_size` transactions
+/// (or `MAX_BLOCK_TXIDS_RANGE` blocks) are collected.
+pub fn get_transactions_in_block_range(
+    start_height: BlockHeight,
+    end_height: BlockHeight,
+    page_size: u32,
+    zmq_stream: &mut TcpStream,
+) -> Result<TransactionsPage, String> {
+    if start_height > end_height {
+        return Err("Invalid block range".to_owned());
+    }
+
+    let page_size = page_size.min(MAX_PAGE_SIZE) as usize;
+    let last_height = start_height
+        .saturating_add(MAX_BLOCK_TXIDS_RANGE - 1)
+        .min(end_height);
+
+    let mut blocks = Vec::new();
+    let mut num_transactions = 0;
+
+    for block_txids in get_block_txids(start_height, last_height, zmq_stream)? {
+        // fails if any of the transactions is not available, so that pages are always complete
+        let transactions = if block_txids.output_txids.is_empty() {
+            Vec::new()
+        } else {
+            get_transactions_with_outputs(block_txids.output_txids, zmq_stream)?
+        };
+
+        num_transactions += transactions.len();
+
+        blocks.push(BlockTransactions {
+            block_height: block_txids.block_height,
+            transaction_ids: block_txids.txids,
+            transactions,
+        });
+
+        if num_transactions >= page_size {
+            break;
+        }
+    }
+
+    let next_height = match blocks.last() {
+        Some(block) if block.block_height < end_height => {
+            Some(block.block_height.saturating_add(1))
+        }
+        Some(_) => None,
+        None => return Err("No blocks found in range".to_owned()),
+    };
+
+    Ok(TransactionsPage {
+        blocks,
+        next_height,
+    })
+}
+
+/// Seals the transactions of a verified page and stores them in chain-abci, followed by the sync
+/// cursor (so that an interrupted sync is resumed from the first page which wasn't fully stored)
+pub fn store_transactions(
+    page: &TransactionsPage,
+    zmq_stream: &mut TcpStream,
+) -> Result<(), String> {
+    // requests are read by chain-abci into a buffer of `ENCRYPTION_REQUEST_SIZE`, so the sealed
+    // logs are sent in chunks fitting into it
+    let max_chunk_size = ENCRYPTION_REQUEST_SIZE - 64;
+    let mut chunk: Vec<(TxId, SealedLog)> = Vec::new();
+    let mut chunk_size = 0;
+
+    for transaction in page
+        .blocks
+        .iter()
+        .flat_map(|block| block.transactions.iter())
+    {
+        let txid = transaction.id();
+        // chain-abci rejects payloads which are already stored (e.g. by an interrupted sync)
+        if is_sealed_log_stored(txid, zmq_stream)? {
+            continue;
+        }
+        let sealed_log = SealedData::seal(&transaction.encode(), txid)
+            .map_err(|e| format!("Unable to seal transaction: {:?}", e))?;
+        let size = (txid, &sealed_log).encoded_size();
+
+        if size > max_chunk_size {
+            return Err("Sealed transaction is too large to be stored".to_owned());
+        }
+
+        if chunk_size + size > max_chunk_size {
+            store_sealed_logs(std::mem::take(&mut chunk), None, zmq_stream)?;
+            chunk_size = 0;
+        }
+
+        chunk_size += size;
+        chunk.push((txid, sealed_log));
+    }
+
+    let sync_cursor = match page.next_height {
+        Some(next_height) => next_height,
+        None => page
+            .blocks
+            .last()
+            .ok_or_else(|| "Page does not contain any block".to_owned())?
+            .block_height
+            .saturating_add(1),
+    };
+
+    store_sealed_logs(chunk, Some(sync_cursor), zmq_stream)
+}
+
+/// Checks if the sealed payload of given transaction is stored in chain-abci
+fn is_sealed_log_stored(txid: TxId, zmq_stream: &mut TcpStream) -> Result<bool, String> {
+    let enclave_request = EnclaveRequest::GetSealedTxData { txids: vec![txid] };
+
+    match send_enclave_request(&enclave_request, zmq_stream)? {
+        EnclaveResponse::GetSealedTxData(sealed_logs) => Ok(sealed_logs.is_some()),
+        _ => Err("Unexpected response from ZeroMQ".to_owned()),
+    }
+}
+
+/// Retrieves the last sync cursor stored in chain-abci (if any)
+pub fn get_sync_cursor(zmq_stream: &mut TcpStream) -> Result<Option<BlockHeight>, String> {
+    match send_enclave_request(&EnclaveRequest::GetTdbeSyncCursor, zmq_stream)? {
+        EnclaveResponse::GetTdbeSyncCursor(sync_cursor) => Ok(sync_cursor),
+        _ => Err("Unexpected response from ZeroMQ".to_owned()),
+    }
+}
+
+fn store_sealed_logs(
+    sealed_logs: Vec<(TxId, SealedLog)>,
+    sync_cursor: Option<BlockHeight>,
+    zmq_stream: &mut TcpStream,
+) -> Result<(), String> {
+    let enclave_request = EnclaveRequest::StoreSealedTxData {
+        sealed_logs,
+        sync_cursor,
+    };
+
+    match send_enclave_request(&enclave_request, zmq_stream)? {
+        EnclaveResponse::StoreSealedTxData(true) => Ok(()),
+        EnclaveResponse::StoreSealedTxData(false) => {
+            Err("Unable to store sealed transactions".to_owned())
+        }
+        _ => Err("Unexpected response from ZeroMQ".to_owned()),
     }
 }
+
+/// Sends a request to chain-abci over ZeroMQ and reads its response
+fn send_enclave_request(
+    enclave_request: &EnclaveRequest,
+    zmq_stream: &mut TcpStream,
+) -> Result<EnclaveResponse, String> {
+    // Send request to ZeroMQ
+    zmq_stream
+        .write_all(&enclave_request.encode())
+        .map_err(|err| format!("Error while writing request to ZeroMQ: {}", err))?;
+
+    // Read reponse length from ZeroMQ (little endian u32 bytes)
+    let mut response_len = [0u8; 4];
+    zmq_stream
+        .read_exact(&mut response_len)
+        .map_err(|err| format!("Error while reading reponse length from ZeroMQ: {}", err))?;
+
+    let response_len: usize = u32::from_le_bytes(response_len)
+        .try_into()
+        .expect("Response length exceeds `usize` bounds");
+
+    // Read result from ZeroMQ
+    let mut result_buf = vec![0u8; response_len];
+    zmq_stream
+        .read_exact(&mut result_buf)
+        .map_err(|err| format!("Error while reading response from ZeroMQ: {}", err))?;
+
+    EnclaveResponse::decode(&mut result_buf.as_ref())
+        .map_err(|err| format!("Error while decoding response from ZeroMQ: {}", err))
+}
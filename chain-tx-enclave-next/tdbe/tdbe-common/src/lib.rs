@@ -1,12 +1,27 @@
 use parity_scale_codec::{Decode, Encode};
 
-use chain_core::tx::data::TxId;
+use chain_core::state::tendermint::BlockHeight;
+
+/// Remote TDBE server from which initial data can be fetched
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct TdbePeer {
+    /// DNS name of TDBE server (used for verifying its TLS certificate)
+    pub dns_name: String,
+    /// Address of TDBE server to connect to
+    pub address: String,
+}
 
 /// Configuration options passed to TDBE on startup
 #[derive(Debug, Encode, Decode)]
 pub struct TdbeConfig {
-    /// DNS name of TDBE server for fetching initial data
-    pub tdbe_dns_name: Option<String>,
-    /// Transaction IDs to fetch from another TDBE server
-    pub transaction_ids: Vec<TxId>,
+    /// TDBE servers for fetching initial data (tried in order; a failed page is retried against
+    /// the next one)
+    pub tdbe_peers: Vec<TdbePeer>,
+    /// Height of the first block to fetch transaction data for (set to the last reported sync
+    /// cursor when resuming an interrupted sync)
+    pub start_height: BlockHeight,
+    /// Height of the last block to fetch transaction data for (inclusive)
+    pub end_height: BlockHeight,
+    /// Soft limit on number of transactions fetched in one page
+    pub page_size: u32,
 }
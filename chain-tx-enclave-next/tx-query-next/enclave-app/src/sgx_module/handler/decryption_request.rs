@@ -29,6 +29,12 @@ pub fn verify_decryption_request(decryption_request: &DecryptionRequest, challen
     decryption_request.verify(&secp, challenge).is_ok()
 }
 
+/// Looks up the requested transactions and returns those the view key is allowed to see.
+///
+/// The transactions are unsealed from the logs stored by the tx-validation enclave
+/// after it deobfuscated them with the key of their epoch (`TxObfuscated::key_from`).
+/// The sealed logs don't depend on the obfuscation key, so a request may span
+/// any number of key rotations.
 pub fn handle_decryption_request(
     decryption_request: &DecryptionRequest,
     chain_data_stream: Arc<Mutex<TcpStream>>,
@@ -9,9 +9,11 @@ use chain_core::tx::TX_AUX_SIZE;
 use chain_tx_filter::BlockFilter;
 use chain_tx_validation::Error;
 use enclave_macro::get_network_id;
-use enclave_protocol::{IntraEnclaveRequest, IntraEnclaveResponse, IntraEnclaveResponseOk};
-use parity_scale_codec::{Decode, Encode};
-use std::io::{Read, Write};
+use enclave_protocol::codec::{StreamRead, StreamWrite};
+use enclave_protocol::{
+    IntraEnclaveRequest, IntraEnclaveResponse, IntraEnclaveResponseOk, RawBlockFilter,
+};
+use std::io::{ErrorKind, Read, Write};
 use std::net::TcpStream;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::Sender;
@@ -20,8 +22,12 @@ use std::sync::Arc;
 /// FIXME: genesis app hash etc.?
 pub const NETWORK_HEX_ID: u8 = get_network_id!();
 
+/// maximum length of a request from chain-abci
+/// (requests are length-prefixed; longer ones are skipped and rejected)
+const MAX_REQUEST_SIZE: usize = 2 * TX_AUX_SIZE;
+
 pub(crate) fn write_response<I: Write>(response: IntraEnclaveResponse, output: &mut I) {
-    if let Err(e) = output.write_all(&response.encode()) {
+    if let Err(e) = response.write_to(output) {
         log::error!("writing response failed: {:?}", e);
     }
 }
@@ -34,7 +40,7 @@ fn handling_loop<I: Read + Write>(
     process_signal: Option<(Arc<AtomicBool>, Sender<()>)>,
 ) {
     let mut filter = BlockFilter::default();
-    let mut request_buf = vec![0u8; 2 * TX_AUX_SIZE];
+    let mut keyring = obfuscate::genesis_keyring();
     log::debug!("waiting for chain-abci requests");
     loop {
         if let Some((ref b, _)) = process_signal {
@@ -43,60 +49,77 @@ fn handling_loop<I: Read + Write>(
             }
         }
         log::trace!("waiting for chain-abci request");
-        match chain_abci.read(&mut request_buf) {
-            Ok(n) if n > 0 => match IntraEnclaveRequest::decode(&mut &request_buf.as_slice()[0..n])
-            {
-                Ok(IntraEnclaveRequest::InitChainCheck(network_id)) => {
-                    let response: IntraEnclaveResponse = if network_id == NETWORK_HEX_ID {
-                        Ok(IntraEnclaveResponseOk::InitChainCheck)
-                    } else {
-                        Err(Error::WrongChainHexId)
-                    };
-                    write_response(response, &mut chain_abci);
-                    if let Some((_, ref s)) = process_signal {
-                        let _ = s.send(());
-                    }
+        match IntraEnclaveRequest::read_from_bounded(&mut chain_abci, MAX_REQUEST_SIZE) {
+            Ok(IntraEnclaveRequest::InitChainCheck(network_id)) => {
+                let response: IntraEnclaveResponse = if network_id == NETWORK_HEX_ID {
+                    Ok(IntraEnclaveResponseOk::InitChainCheck)
+                } else {
+                    Err(Error::WrongChainHexId)
+                };
+                write_response(response, &mut chain_abci);
+                if let Some((_, ref s)) = process_signal {
+                    let _ = s.send(());
                 }
-                Ok(IntraEnclaveRequest::ValidateTx { request, tx_inputs }) => {
-                    log::debug!("validate tx request");
-                    validate::handle_validate_tx(request, tx_inputs, &mut filter, &mut chain_abci);
-                    if let Some((_, ref s)) = process_signal {
-                        let _ = s.send(());
-                    }
+            }
+            Ok(IntraEnclaveRequest::ValidateTx { request, tx_inputs }) => {
+                log::debug!("validate tx request");
+                validate::handle_validate_tx(
+                    request,
+                    tx_inputs,
+                    &keyring,
+                    &mut filter,
+                    &mut chain_abci,
+                );
+                if let Some((_, ref s)) = process_signal {
+                    let _ = s.send(());
                 }
-                Ok(IntraEnclaveRequest::EndBlock) => {
-                    log::debug!("end block request");
-
-                    let maybe_filter = if filter.is_modified() {
-                        Some(Box::new(filter.get_raw()))
-                    } else {
-                        None
-                    };
-                    filter.reset();
-                    let response: IntraEnclaveResponse =
-                        Ok(IntraEnclaveResponseOk::EndBlock(maybe_filter));
-                    write_response(response, &mut chain_abci);
-                    if let Some((_, ref s)) = process_signal {
-                        let _ = s.send(());
-                    }
+            }
+            Ok(IntraEnclaveRequest::EndBlock) => {
+                log::debug!("end block request");
+
+                let maybe_filter = if filter.is_modified() {
+                    Some(Box::new(RawBlockFilter {
+                        bloom: filter.get_raw(),
+                        gcs: filter.get_gcs().to_bytes(),
+                    }))
+                } else {
+                    None
+                };
+                filter.reset();
+                let response: IntraEnclaveResponse =
+                    Ok(IntraEnclaveResponseOk::EndBlock(maybe_filter));
+                write_response(response, &mut chain_abci);
+                if let Some((_, ref s)) = process_signal {
+                    let _ = s.send(());
                 }
-                Ok(IntraEnclaveRequest::Encrypt(request)) => {
-                    obfuscate::handle_encrypt_request(request, &mut chain_abci);
-                    if let Some((_, ref s)) = process_signal {
-                        let _ = s.send(());
-                    }
+            }
+            Ok(IntraEnclaveRequest::Encrypt(request)) => {
+                obfuscate::handle_encrypt_request(request, &keyring, &mut chain_abci);
+                if let Some((_, ref s)) = process_signal {
+                    let _ = s.send(());
                 }
-                Err(e) => {
-                    log::error!("check tx failed: {:?}", e);
-                    write_response(Err(Error::EnclaveRejected), &mut chain_abci);
+            }
+            Ok(IntraEnclaveRequest::NewObfuscationKey {
+                key_from,
+                sealed_key,
+            }) => {
+                log::debug!("new obfuscation key request");
+                obfuscate::handle_new_obfuscation_key(
+                    key_from,
+                    sealed_key,
+                    &mut keyring,
+                    &mut chain_abci,
+                );
+                if let Some((_, ref s)) = process_signal {
+                    let _ = s.send(());
                 }
-            },
-            Ok(_) => {
-                // n == 0
+            }
+            Err(ref e) if e.kind() == ErrorKind::UnexpectedEof => {
                 log::trace!("end of stream?");
             }
             Err(e) => {
-                log::error!("error reading from chain-abci: {:?}", e);
+                log::error!("error reading request from chain-abci: {:?}", e);
+                write_response(Err(Error::EnclaveRejected), &mut chain_abci);
             }
         }
     }
@@ -146,7 +169,6 @@ mod tests {
     use chain_tx_validation::Error;
     use enclave_protocol::{IntraEnclaveRequest, IntraEnclaveResponseOk, VerifyTxRequest};
     use log::debug;
-    use parity_scale_codec::{Decode, Encode};
     use secp256k1::{
         key::PublicKey, key::SecretKey, key::XOnlyPublicKey, schnorrsig::schnorr_sign, Message,
         Secp256k1, Signing,
@@ -218,6 +240,12 @@ mod tests {
         stream.lock().unwrap().push_bytes(bytes)
     }
 
+    pub fn push_request(stream: Arc<Mutex<FakeStream>>, request: &IntraEnclaveRequest) {
+        let mut bytes = Vec::new();
+        request.write_to(&mut bytes).unwrap();
+        push_bytes(stream, &bytes)
+    }
+
     pub fn pop_written_bytes(stream: Arc<Mutex<FakeStream>>) -> Vec<u8> {
         stream.lock().unwrap().pop_written_bytes()
     }
@@ -256,15 +284,14 @@ mod tests {
         let stop2 = stop.clone();
         let stream = SyncStream::default();
         let stream2 = stream.stream.clone();
-        push_bytes(stream2.clone(), &IntraEnclaveRequest::EndBlock.encode());
+        push_request(stream2.clone(), &IntraEnclaveRequest::EndBlock);
 
         let _handler = std::thread::spawn(move || {
             handling_loop(stream, Some((stop2, sender)));
         });
 
         let _ = receiver.recv().unwrap();
-        let end_b =
-            IntraEnclaveResponse::decode(&mut pop_written_bytes(stream2.clone()).as_slice());
+        let end_b = IntraEnclaveResponse::read_from(pop_written_bytes(stream2.clone()).as_slice());
 
         match end_b {
             Ok(Ok(IntraEnclaveResponseOk::EndBlock(b))) => {
@@ -276,6 +303,7 @@ mod tests {
             }
         };
 
+        let keyring = obfuscate::genesis_keyring();
         let secp = secp256k1::SECP256K1;
         let secret_key = SecretKey::from_slice(&[0xcd; 32]).expect("32 bytes, within curve order");
         let public_key = PublicKey::from_secret_key(&secp, &secret_key);
@@ -301,6 +329,8 @@ mod tests {
             no_of_outputs: tx0.outputs.len() as TxoSize,
             witness: witness0.clone(),
             payload: crate::sgx_module::obfuscate::encrypt(
+                &keyring,
+                BlockHeight::genesis(),
                 TxToObfuscate::from(PlainTxAux::WithdrawUnbondedStakeTx(tx0.clone()), *txid)
                     .expect("tx"),
             ),
@@ -322,26 +352,25 @@ mod tests {
             }),
             tx_inputs: None,
         };
-        push_bytes(stream2.clone(), &request0.encode());
+        push_request(stream2.clone(), &request0);
         let _ = receiver.recv().unwrap();
-        let r = IntraEnclaveResponse::decode(&mut pop_written_bytes(stream2.clone()).as_slice());
+        let r = IntraEnclaveResponse::read_from(pop_written_bytes(stream2.clone()).as_slice());
 
         let sealedtx = match r {
             Ok(Ok(IntraEnclaveResponseOk::TxWithOutputs { sealed_tx, .. })) => sealed_tx,
             _ => vec![],
         };
 
-        push_bytes(stream2.clone(), &IntraEnclaveRequest::EndBlock.encode());
+        push_request(stream2.clone(), &IntraEnclaveRequest::EndBlock);
         let _ = receiver.recv().unwrap();
-        let end_b =
-            IntraEnclaveResponse::decode(&mut pop_written_bytes(stream2.clone()).as_slice());
+        let end_b = IntraEnclaveResponse::read_from(pop_written_bytes(stream2.clone()).as_slice());
 
         match end_b {
             Ok(Ok(IntraEnclaveResponseOk::EndBlock(b))) => {
                 debug!("request filter after one tx");
                 assert_stop_thread(
                     stop.clone(),
-                    b.unwrap().iter().any(|x| *x != 0u8),
+                    b.unwrap().bloom.iter().any(|x| *x != 0u8),
                     "non-empty filter",
                 );
             }
@@ -372,6 +401,8 @@ mod tests {
             inputs: tx1.inputs.clone(),
             no_of_outputs: tx1.outputs.len() as TxoSize,
             payload: crate::sgx_module::obfuscate::encrypt(
+                &keyring,
+                BlockHeight::genesis(),
                 TxToObfuscate::from(PlainTxAux::TransferTx(tx1.clone(), witness1.clone()), txid1)
                     .expect("tx"),
             ),
@@ -385,9 +416,9 @@ mod tests {
             }),
             tx_inputs: Some(vec![sealedtx.clone()]),
         };
-        push_bytes(stream2.clone(), &request1.encode());
+        push_request(stream2.clone(), &request1);
         let _ = receiver.recv().unwrap();
-        let r2 = IntraEnclaveResponse::decode(&mut pop_written_bytes(stream2.clone()).as_slice());
+        let r2 = IntraEnclaveResponse::read_from(pop_written_bytes(stream2.clone()).as_slice());
 
         match r2 {
             Ok(Ok(IntraEnclaveResponseOk::TxWithOutputs { .. })) => {}
@@ -417,6 +448,8 @@ mod tests {
             inputs: tx2.inputs.clone(),
             no_of_outputs: tx2.outputs.len() as TxoSize,
             payload: crate::sgx_module::obfuscate::encrypt(
+                &keyring,
+                BlockHeight::genesis(),
                 TxToObfuscate::from(PlainTxAux::TransferTx(tx2.clone(), witness2.clone()), txid2)
                     .expect("tx"),
             ),
@@ -429,9 +462,9 @@ mod tests {
             }),
             tx_inputs: Some(vec![sealedtx]),
         };
-        push_bytes(stream2.clone(), &request2.encode());
+        push_request(stream2.clone(), &request2);
         let _ = receiver.recv().unwrap();
-        let r3 = IntraEnclaveResponse::decode(&mut pop_written_bytes(stream2.clone()).as_slice());
+        let r3 = IntraEnclaveResponse::read_from(pop_written_bytes(stream2.clone()).as_slice());
 
         match r3 {
             Ok(Err(Error::ZeroCoin)) => {
@@ -13,7 +13,10 @@ use chain_tx_validation::{
     witness::verify_tx_recover_address,
 };
 use enclave_macro::mock_key;
-use enclave_protocol::{EncryptionRequest, IntraEncryptRequest};
+use enclave_protocol::obfuscation_keys::{
+    obfuscation_key_seal_id, ObfuscationKey, ObfuscationKeyring, OBFUSCATION_KEY_LEN,
+};
+use enclave_protocol::{EncryptionRequest, IntraEncryptRequest, SealedLog};
 use enclave_protocol::{IntraEnclaveResponse, IntraEnclaveResponseOk};
 use enclave_utils::SealedData;
 use parity_scale_codec::Decode;
@@ -21,25 +24,54 @@ use std::io::Write;
 use std::prelude::v1::Box;
 use zeroize::Zeroize;
 
-/// this will be injected by TDBE connection
-const MOCK_KEY: [u8; 16] = mock_key!();
+/// used until the first epoch key is injected by TDBE connection
+const MOCK_KEY: ObfuscationKey = mock_key!();
+
+/// keyring with the initial key
+pub(crate) fn genesis_keyring() -> ObfuscationKeyring {
+    ObfuscationKeyring::new(BlockHeight::genesis(), MOCK_KEY)
+}
 
-pub(crate) fn encrypt(tx: TxToObfuscate) -> TxObfuscated {
+/// obfuscates with the key active at given height
+pub(crate) fn encrypt(
+    keyring: &ObfuscationKeyring,
+    block_height: BlockHeight,
+    tx: TxToObfuscate,
+) -> TxObfuscated {
+    let (key_from, key) = keyring
+        .active(block_height)
+        .expect("keyring has the initial key");
     let init_vector: [u8; 12] = rand::random();
-    let key = GenericArray::clone_from_slice(&MOCK_KEY);
+    let key = GenericArray::clone_from_slice(key);
     let aead = Aes128GcmSiv::new(&key);
     let nonce = GenericArray::from_slice(&init_vector);
     let ciphertext = aead.encrypt(nonce, &tx).expect("encryption failure!");
     TxObfuscated {
-        key_from: BlockHeight::genesis(),
+        key_from,
         init_vector,
         txpayload: ciphertext,
         txid: tx.txid,
     }
 }
 
-pub(crate) fn decrypt(tx: &TxObfuscated) -> Result<PlainTxAux, ()> {
-    let key = GenericArray::clone_from_slice(&MOCK_KEY);
+/// deobfuscates a new payload validated at given height
+/// (it has to be obfuscated with the key active at that height)
+pub(crate) fn decrypt(
+    keyring: &ObfuscationKeyring,
+    tx: &TxObfuscated,
+    block_height: BlockHeight,
+) -> Result<PlainTxAux, ()> {
+    let key = match keyring.get_for_new_payload(tx.key_from, block_height) {
+        Some(key) => GenericArray::clone_from_slice(key),
+        None => {
+            log::debug!(
+                "obfuscation key from height {} is not active at {}",
+                tx.key_from,
+                block_height
+            );
+            return Err(());
+        }
+    };
     let aead = Aes128GcmSiv::new(&key);
     let nonce = GenericArray::from_slice(&tx.init_vector);
     let plaintext = aead.decrypt(nonce, tx).map_err(|_| ())?;
@@ -100,7 +132,11 @@ where
 }
 
 #[inline]
-pub(crate) fn handle_encrypt_request<I: Write>(request: Box<IntraEncryptRequest>, output: &mut I) {
+pub(crate) fn handle_encrypt_request<I: Write>(
+    request: Box<IntraEncryptRequest>,
+    keyring: &ObfuscationKeyring,
+    output: &mut I,
+) {
     match (unseal_request(&request), request.tx_inputs) {
         (Some(EncryptionRequest::TransferTx(tx, witness)), Some(sealed_inputs)) => {
             let unsealed_inputs = check_unseal(tx.inputs.iter().map(|x| x.id), sealed_inputs);
@@ -109,6 +145,8 @@ pub(crate) fn handle_encrypt_request<I: Write>(request: Box<IntraEncryptRequest>
                 let txid = tx.id();
                 let response: IntraEnclaveResponse = result.map(|_| {
                     IntraEnclaveResponseOk::Encrypt(encrypt(
+                        keyring,
+                        request.info.block_height,
                         TxToObfuscate::from(PlainTxAux::TransferTx(tx, witness), txid)
                             .expect("construct plain payload"),
                     ))
@@ -126,6 +164,8 @@ pub(crate) fn handle_encrypt_request<I: Write>(request: Box<IntraEncryptRequest>
                 let txid = tx.id();
                 let response: IntraEnclaveResponse = result.map(|_| {
                     IntraEnclaveResponseOk::Encrypt(encrypt(
+                        keyring,
+                        request.info.block_height,
                         TxToObfuscate::from(PlainTxAux::DepositStakeTx(witness), txid)
                             .expect("construct plain payload"),
                     ))
@@ -145,6 +185,8 @@ pub(crate) fn handle_encrypt_request<I: Write>(request: Box<IntraEncryptRequest>
                         let result = verify_unbonded_withdraw_core(&tx, &request.info, &account);
                         let response: IntraEnclaveResponse = result.map(|_| {
                             IntraEnclaveResponseOk::Encrypt(encrypt(
+                                keyring,
+                                request.info.block_height,
                                 TxToObfuscate::from(PlainTxAux::WithdrawUnbondedStakeTx(tx), txid)
                                     .expect("construct plain payload"),
                             ))
@@ -167,3 +209,55 @@ pub(crate) fn handle_encrypt_request<I: Write>(request: Box<IntraEncryptRequest>
         }
     }
 }
+
+/// unseals the key of a newly committed TDBE epoch and adds it to the keyring
+/// (the old keys are kept for deobfuscating historical payloads)
+#[inline]
+pub(crate) fn handle_new_obfuscation_key<I: Write>(
+    key_from: BlockHeight,
+    sealed_key: SealedLog,
+    keyring: &mut ObfuscationKeyring,
+    output: &mut I,
+) {
+    let sealed_data = match SealedData::try_copy_from(&sealed_key) {
+        Some(sealed_data)
+            if sealed_data.aes_data.additional_txt == obfuscation_key_seal_id(key_from) =>
+        {
+            sealed_data
+        }
+        _ => {
+            log::error!("invalid sealed obfuscation key");
+            write_response(Err(Error::EnclaveRejected), output);
+            return;
+        }
+    };
+    let mut unsealed = match sealed_data.unseal() {
+        Ok(unsealed) if unsealed.len() == OBFUSCATION_KEY_LEN => unsealed,
+        Ok(mut unsealed) => {
+            unsealed.zeroize();
+            log::error!("invalid obfuscation key length");
+            write_response(Err(Error::EnclaveRejected), output);
+            return;
+        }
+        Err(e) => {
+            log::error!("Error while unsealing obfuscation key: {:?}", e);
+            write_response(Err(Error::EnclaveRejected), output);
+            return;
+        }
+    };
+    let mut key = [0u8; OBFUSCATION_KEY_LEN];
+    key.copy_from_slice(&unsealed);
+    unsealed.zeroize();
+    let response: IntraEnclaveResponse = match keyring.insert(key_from, key) {
+        Ok(()) => {
+            log::info!("new obfuscation key from height: {}", key_from);
+            Ok(IntraEnclaveResponseOk::NewObfuscationKey)
+        }
+        Err(e) => {
+            log::error!("new obfuscation key rejected: {}", e);
+            Err(Error::EnclaveRejected)
+        }
+    };
+    key.zeroize();
+    write_response(response, output);
+}
@@ -1,3 +1,5 @@
+pub mod simulation;
+
 use parity_scale_codec::{Decode, Encode};
 
 use chain_core::state::tendermint::BlockHeight;
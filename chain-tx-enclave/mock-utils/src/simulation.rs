@@ -0,0 +1,85 @@
+//! Software implementation of enclave obfuscation and sealing (used by the simulation backend)
+//!
+//! Obfuscation is the same AEAD (AES-128-GCM-SIV with transaction ID as additional data) as the
+//! one used in `tx-validation-next`, but the keys are held in normal process memory, so, this
+//! must never be used in production.
+use aead::{generic_array::GenericArray, Aead, NewAead, Payload};
+use aes_gcm_siv::Aes128GcmSiv;
+use parity_scale_codec::Decode;
+
+use chain_core::state::tendermint::BlockHeight;
+use chain_core::tx::{data::TxId, PlainTxAux, TxObfuscated, TxToObfuscate};
+use chain_tx_validation::Error;
+
+/// Length of AES-128 keys used for obfuscation and sealing
+pub const KEY_LEN: usize = 16;
+/// Length of AES-GCM-SIV nonce
+const NONCE_LEN: usize = 12;
+/// Length of transaction ID prepended to sealed data
+const TXID_LEN: usize = 32;
+
+/// Obfuscates transaction payload with given key (`key_from` denotes the height from which the key
+/// is used)
+pub fn obfuscate(key: &[u8; KEY_LEN], key_from: BlockHeight, tx: &TxToObfuscate) -> TxObfuscated {
+    let init_vector: [u8; NONCE_LEN] = rand::random();
+    let aead = Aes128GcmSiv::new(GenericArray::from_slice(key));
+    let nonce = GenericArray::from_slice(&init_vector);
+    let txpayload = aead.encrypt(nonce, tx).expect("encryption failure!");
+
+    TxObfuscated {
+        key_from,
+        init_vector,
+        txpayload,
+        txid: tx.txid,
+    }
+}
+
+/// Deobfuscates transaction payload with given key
+pub fn deobfuscate(key: &[u8; KEY_LEN], tx: &TxObfuscated) -> Result<PlainTxAux, Error> {
+    let aead = Aes128GcmSiv::new(GenericArray::from_slice(key));
+    let nonce = GenericArray::from_slice(&tx.init_vector);
+    let plaintext = aead
+        .decrypt(nonce, tx)
+        .map_err(|_| Error::EnclaveRejected)?;
+    PlainTxAux::decode(&mut plaintext.as_slice()).map_err(|_| Error::EnclaveRejected)
+}
+
+/// Seals data for given transaction ID (format: `txid || nonce || ciphertext`, where transaction
+/// ID is authenticated as additional data -- similar to `additional_txt` in enclave sealed data)
+pub fn seal_with_key(key: &[u8; KEY_LEN], data: &[u8], txid: &TxId) -> Vec<u8> {
+    let nonce: [u8; NONCE_LEN] = rand::random();
+    let aead = Aes128GcmSiv::new(GenericArray::from_slice(key));
+    let ciphertext = aead
+        .encrypt(
+            GenericArray::from_slice(&nonce),
+            Payload {
+                msg: data,
+                aad: txid,
+            },
+        )
+        .expect("sealing failure!");
+
+    let mut sealed = Vec::with_capacity(TXID_LEN + NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(txid);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    sealed
+}
+
+/// Unseals data sealed with `seal_with_key` (returns `None` if data was not sealed for given
+/// transaction ID or with given key)
+pub fn unseal_with_key(key: &[u8; KEY_LEN], sealed: &[u8], txid: &TxId) -> Option<Vec<u8>> {
+    if sealed.len() < TXID_LEN + NONCE_LEN || &sealed[..TXID_LEN] != txid {
+        return None;
+    }
+
+    let aead = Aes128GcmSiv::new(GenericArray::from_slice(key));
+    aead.decrypt(
+        GenericArray::from_slice(&sealed[TXID_LEN..TXID_LEN + NONCE_LEN]),
+        Payload {
+            msg: &sealed[TXID_LEN + NONCE_LEN..],
+            aad: txid,
+        },
+    )
+    .ok()
+}
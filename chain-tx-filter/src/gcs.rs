@@ -0,0 +1,325 @@
+//! # Golomb-coded set
+//! Compact probabilistic set as described in BIP-158
+//! (https://github.com/bitcoin/bips/blob/master/bip-0158.mediawiki)
+//!
+//! Items are hashed with SipHash-2-4 to a uniform range `[0, N * M)`,
+//! sorted, and differences between them are Golomb-Rice coded with parameter `P`.
+//! The false positive rate is approximately `1 / M` (independently of the number of items),
+//! while the size of the filter grows with the number of items (around `N * (P + 2)` bits).
+//!
+//! Unlike BIP-158, the SipHash key is fixed (the enclave producing the filter doesn't know
+//! the block hash at the end of the block).
+use parity_scale_codec::{Compact, Decode, Encode};
+use std::convert::TryFrom;
+use std::prelude::v1::Vec;
+
+/// Golomb-Rice coding parameter (BIP-158 "basic" filter)
+pub const GCS_P: u8 = 19;
+/// inverse of the target false positive rate (BIP-158 "basic" filter)
+pub const GCS_M: u64 = 784_931;
+/// SipHash key used for hashing items
+const GCS_KEY: [u8; 16] = *b"chain-gcs-filter";
+
+/// A Golomb-coded set
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GcsFilter {
+    /// number of items
+    n: u32,
+    /// Golomb-Rice coded differences of sorted hashed items
+    data: Vec<u8>,
+}
+
+impl GcsFilter {
+    /// Constructs the filter from a set of (unique) items
+    pub fn build<T: AsRef<[u8]>>(items: &[T]) -> Self {
+        let n = items.len() as u32;
+        let range = u64::from(n) * GCS_M;
+        let mut hashed: Vec<u64> = items
+            .iter()
+            .map(|item| hash_to_range(item.as_ref(), range))
+            .collect();
+        hashed.sort_unstable();
+
+        let mut writer = BitWriter::default();
+        let mut last = 0;
+        for value in hashed {
+            writer.write_golomb(value - last);
+            last = value;
+        }
+        GcsFilter {
+            n,
+            data: writer.finish(),
+        }
+    }
+
+    /// Tests if an item is in the set
+    /// true = maybe present
+    /// false = not present
+    pub fn contains(&self, item: &[u8]) -> bool {
+        if self.n == 0 {
+            return false;
+        }
+        let target = hash_to_range(item, u64::from(self.n) * GCS_M);
+        let mut reader = BitReader::new(&self.data);
+        let mut value = 0u64;
+        for _ in 0..self.n {
+            match reader.read_golomb() {
+                Some(delta) => value += delta,
+                None => return false,
+            }
+            if value == target {
+                return true;
+            }
+            if value > target {
+                return false;
+            }
+        }
+        false
+    }
+
+    /// number of items in the set
+    pub fn len(&self) -> usize {
+        self.n as usize
+    }
+
+    /// if the set is empty
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Gets the serialized filter (compact-encoded number of items followed by coded data)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut result = Compact(self.n).encode();
+        result.extend_from_slice(&self.data);
+        result
+    }
+}
+
+impl TryFrom<&[u8]> for GcsFilter {
+    type Error = &'static str;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let mut input = value;
+        let n = Compact::<u32>::decode(&mut input)
+            .map_err(|_| "Invalid Golomb-coded set: unable to decode the number of items")?
+            .0;
+        let filter = GcsFilter {
+            n,
+            data: input.to_vec(),
+        };
+        // check all the items can be decoded
+        let mut reader = BitReader::new(&filter.data);
+        for _ in 0..n {
+            if reader.read_golomb().is_none() {
+                return Err("Invalid Golomb-coded set: unexpected end of data");
+            }
+        }
+        Ok(filter)
+    }
+}
+
+/// maps the item's hash uniformly to `[0, range)`
+fn hash_to_range(item: &[u8], range: u64) -> u64 {
+    ((u128::from(siphash24(&GCS_KEY, item)) * u128::from(range)) >> 64) as u64
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    /// number of bits used in the last byte (0 = the last byte is full / no bytes)
+    used: u8,
+}
+
+impl BitWriter {
+    fn write_bit(&mut self, bit: bool) {
+        if self.used == 0 {
+            self.bytes.push(0);
+        }
+        if bit {
+            let last = self.bytes.len() - 1;
+            self.bytes[last] |= 0x80 >> self.used;
+        }
+        self.used = (self.used + 1) % 8;
+    }
+
+    fn write_golomb(&mut self, value: u64) {
+        // quotient in unary
+        for _ in 0..(value >> GCS_P) {
+            self.write_bit(true);
+        }
+        self.write_bit(false);
+        // remainder in P bits (big-endian)
+        for i in (0..GCS_P).rev() {
+            self.write_bit((value >> i) & 1 == 1);
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        BitReader { bytes, position: 0 }
+    }
+
+    fn read_bit(&mut self) -> Option<bool> {
+        let byte = self.bytes.get(self.position / 8)?;
+        let bit = byte & (0x80 >> (self.position % 8)) != 0;
+        self.position += 1;
+        Some(bit)
+    }
+
+    fn read_golomb(&mut self) -> Option<u64> {
+        let mut quotient = 0u64;
+        while self.read_bit()? {
+            quotient += 1;
+        }
+        let mut remainder = 0u64;
+        for _ in 0..GCS_P {
+            remainder = (remainder << 1) | u64::from(self.read_bit()?);
+        }
+        Some((quotient << GCS_P) | remainder)
+    }
+}
+
+/// SipHash-2-4
+fn siphash24(key: &[u8; 16], data: &[u8]) -> u64 {
+    let mut k0 = [0u8; 8];
+    let mut k1 = [0u8; 8];
+    k0.copy_from_slice(&key[..8]);
+    k1.copy_from_slice(&key[8..]);
+    let k0 = u64::from_le_bytes(k0);
+    let k1 = u64::from_le_bytes(k1);
+    let mut v = [
+        k0 ^ 0x736f_6d65_7073_6575,
+        k1 ^ 0x646f_7261_6e64_6f6d,
+        k0 ^ 0x6c79_6765_6e65_7261,
+        k1 ^ 0x7465_6462_7974_6573,
+    ];
+
+    let mut chunks = data.chunks_exact(8);
+    for chunk in &mut chunks {
+        let mut m = [0u8; 8];
+        m.copy_from_slice(chunk);
+        let m = u64::from_le_bytes(m);
+        v[3] ^= m;
+        sip_round(&mut v);
+        sip_round(&mut v);
+        v[0] ^= m;
+    }
+
+    let mut last = [0u8; 8];
+    let remainder = chunks.remainder();
+    last[..remainder.len()].copy_from_slice(remainder);
+    let b = ((data.len() as u64) << 56) | u64::from_le_bytes(last);
+    v[3] ^= b;
+    sip_round(&mut v);
+    sip_round(&mut v);
+    v[0] ^= b;
+
+    v[2] ^= 0xff;
+    for _ in 0..4 {
+        sip_round(&mut v);
+    }
+    v[0] ^ v[1] ^ v[2] ^ v[3]
+}
+
+#[inline]
+fn sip_round(v: &mut [u64; 4]) {
+    v[0] = v[0].wrapping_add(v[1]);
+    v[1] = v[1].rotate_left(13);
+    v[1] ^= v[0];
+    v[0] = v[0].rotate_left(32);
+    v[2] = v[2].wrapping_add(v[3]);
+    v[3] = v[3].rotate_left(16);
+    v[3] ^= v[2];
+    v[0] = v[0].wrapping_add(v[3]);
+    v[3] = v[3].rotate_left(21);
+    v[3] ^= v[0];
+    v[2] = v[2].wrapping_add(v[1]);
+    v[1] = v[1].rotate_left(17);
+    v[1] ^= v[2];
+    v[2] = v[2].rotate_left(32);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::Bloom;
+    use quickcheck::quickcheck;
+
+    fn item(i: u32) -> Vec<u8> {
+        let mut item = vec![0x02u8; 33];
+        item[1..5].copy_from_slice(&i.to_le_bytes());
+        item
+    }
+
+    #[test]
+    fn test_siphash_reference_vectors() {
+        let mut key = [0u8; 16];
+        for (i, k) in key.iter_mut().enumerate() {
+            *k = i as u8;
+        }
+        assert_eq!(siphash24(&key, &[]), 0x726f_db47_dd0e_0e31);
+        let data: Vec<u8> = (0u8..15).collect();
+        assert_eq!(siphash24(&key, &data), 0xa129_ca61_49be_45e5);
+    }
+
+    #[test]
+    fn test_empty_filter() {
+        let filter = GcsFilter::build::<Vec<u8>>(&[]);
+        assert!(filter.is_empty());
+        assert!(!filter.contains(&item(0)));
+        let decoded = GcsFilter::try_from(filter.to_bytes().as_slice()).unwrap();
+        assert_eq!(filter, decoded);
+    }
+
+    #[test]
+    fn test_truncated_filter_rejected() {
+        let items: Vec<Vec<u8>> = (0..10).map(item).collect();
+        let bytes = GcsFilter::build(&items).to_bytes();
+        assert!(GcsFilter::try_from(&bytes[..bytes.len() - 3]).is_err());
+        assert!(GcsFilter::try_from(&[][..]).is_err());
+    }
+
+    #[test]
+    fn test_false_positive_rate() {
+        const ITEMS: u32 = 1000;
+        const QUERIES: u32 = 100_000;
+        let items: Vec<Vec<u8>> = (0..ITEMS).map(item).collect();
+        let filter = GcsFilter::build(&items);
+        let mut bloom = Bloom::default();
+        for item in items.iter() {
+            bloom.set(item);
+            assert!(filter.contains(item));
+        }
+        // around N * (P + 2) bits
+        assert!(filter.to_bytes().len() < (ITEMS as usize) * 22 / 8 + 8);
+
+        let gcs_false_positives = (ITEMS..ITEMS + QUERIES)
+            .filter(|i| filter.contains(&item(*i)))
+            .count();
+        let bloom_false_positives = (ITEMS..ITEMS + QUERIES)
+            .filter(|i| bloom.check(&item(*i)))
+            .count();
+        // expected rate is 1 / M (i.e. ~0.13 false positives in 100k queries)
+        assert!(gcs_false_positives <= 3);
+        // the 2048-bit bloom filter is mostly saturated with 1000 view keys
+        assert!(bloom_false_positives > QUERIES as usize / 4);
+    }
+
+    quickcheck! {
+        fn gcs_contains_all_items(items: Vec<Vec<u8>>) -> bool {
+            let filter = GcsFilter::build(&items);
+            let decoded = GcsFilter::try_from(filter.to_bytes().as_slice()).unwrap();
+            filter == decoded && items.iter().all(|item| decoded.contains(item))
+        }
+    }
+}
@@ -1,16 +1,24 @@
 mod filter;
+mod gcs;
 use chain_core::common::TendermintEventKey;
 use filter::Bloom;
-use filter::H2048;
+pub use filter::H2048;
+pub use gcs::GcsFilter;
 use secp256k1::key::PublicKey;
+use std::collections::BTreeSet;
 use std::convert::TryFrom;
 use std::prelude::v1::Vec;
 
-/// Probabilistic fixed-size filter wrapper
+/// Probabilistic filter wrapper
+///
+/// The filter is built from a fixed-size (2048-bit) bloom filter and the set of added view keys.
+/// Filters received from the chain are either Golomb-coded sets (if present)
+/// or the legacy bloom filters.
 #[derive(Default, Debug)]
 pub struct BlockFilter {
-    // may be replaced with GCS, e.g. https://github.com/dac-gmbh/golomb-set
     bloom: Bloom,
+    view_keys: BTreeSet<Vec<u8>>,
+    gcs: Option<GcsFilter>,
     modified: bool,
 }
 
@@ -19,30 +27,55 @@ impl BlockFilter {
     pub fn reset(&mut self) {
         self.modified = false;
         self.bloom.reset();
+        self.view_keys.clear();
+        self.gcs = None;
     }
 
     /// joins with another filter
     pub fn add_filter(&mut self, other: &BlockFilter) {
         self.modified = true;
         self.bloom.add(&other.bloom);
+        self.view_keys.extend(other.view_keys.iter().cloned());
     }
 
     /// adds a view key to the filter
     pub fn add_view_key(&mut self, view_key: &PublicKey) {
         self.modified = true;
-        self.bloom.set(&view_key.serialize()[..]);
+        let serialized = view_key.serialize();
+        self.bloom.set(&serialized[..]);
+        self.view_keys.insert(serialized.to_vec());
     }
 
-    /// gets a Key-Value payload for tendermint events
+    /// gets a Key-Value payload for tendermint events (legacy bloom filter)
     pub fn get_tendermint_kv(&self) -> (Vec<u8>, Vec<u8>) {
         (TendermintEventKey::EthBloom.into(), self.bloom.data())
     }
 
+    /// gets a Key-Value payload for tendermint events (Golomb-coded set)
+    pub fn get_gcs_tendermint_kv(&self) -> (Vec<u8>, Vec<u8>) {
+        (
+            TendermintEventKey::GolombCodedSet.into(),
+            self.get_gcs().to_bytes(),
+        )
+    }
+
     /// tests if a view key is in the filter
     /// true = maybe present
     /// false = not present
     pub fn check_view_key(&self, view_key: &PublicKey) -> bool {
-        self.bloom.check(&view_key.serialize())
+        let serialized = view_key.serialize();
+        match &self.gcs {
+            Some(gcs) => gcs.contains(&serialized),
+            None => self.bloom.check(&serialized),
+        }
+    }
+
+    /// gets the Golomb-coded set of added view keys
+    pub fn get_gcs(&self) -> GcsFilter {
+        match &self.gcs {
+            Some(gcs) => gcs.clone(),
+            None => GcsFilter::build(&self.view_keys.iter().collect::<Vec<_>>()),
+        }
     }
 
     /// check if view keys were added since its creation
@@ -63,7 +96,7 @@ impl TryFrom<&[u8]> for BlockFilter {
         let bloom = Bloom::try_from(value)?;
         Ok(BlockFilter {
             bloom,
-            modified: false,
+            ..Default::default()
         })
     }
 }
@@ -73,7 +106,44 @@ impl From<&H2048> for BlockFilter {
         let bloom = Bloom::from(val);
         BlockFilter {
             bloom,
-            modified: false,
+            ..Default::default()
         }
     }
 }
+
+impl From<GcsFilter> for BlockFilter {
+    fn from(gcs: GcsFilter) -> BlockFilter {
+        BlockFilter {
+            gcs: Some(gcs),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::key::SecretKey;
+
+    fn view_key(i: u8) -> PublicKey {
+        let secret_key = SecretKey::from_slice(&[i; 32]).unwrap();
+        PublicKey::from_secret_key(secp256k1::SECP256K1, &secret_key)
+    }
+
+    #[test]
+    fn check_gcs_from_view_keys() {
+        let mut filter = BlockFilter::default();
+        filter.add_view_key(&view_key(1));
+        filter.add_view_key(&view_key(2));
+        let (key, value) = filter.get_gcs_tendermint_kv();
+        assert_eq!(key, TendermintEventKey::GolombCodedSet);
+
+        let received = BlockFilter::from(GcsFilter::try_from(value.as_slice()).unwrap());
+        assert!(received.check_view_key(&view_key(1)));
+        assert!(received.check_view_key(&view_key(2)));
+        assert!(!received.check_view_key(&view_key(3)));
+
+        filter.reset();
+        assert!(filter.get_gcs().is_empty());
+    }
+}
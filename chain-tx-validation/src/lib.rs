@@ -7,20 +7,20 @@ pub mod witness;
 
 use chain_core::init::coin::Coin;
 use chain_core::state::account::{DepositBondTx, StakedState, WithdrawUnbondedTx};
-use chain_core::tx::data::input::TxoPointer;
+use chain_core::tx::data::input::{TxoPointer, TxoSize};
 use chain_core::tx::data::output::TxOut;
 use chain_core::tx::data::Tx;
 use chain_core::tx::data::TxId;
 use chain_core::tx::fee::Fee;
 use chain_core::tx::witness::TxWitness;
-use chain_core::tx::TransactionId;
 pub use chain_core::tx::TxWithOutputs;
+use chain_core::tx::{PlainTxAux, TransactionId, TxEnclaveAux, TxObfuscated};
 pub use chain_core::ChainInfo;
 use parity_scale_codec::{Decode, Encode};
 use std::collections::BTreeSet;
 use std::fmt;
 use std::prelude::v1::Vec;
-use witness::verify_tx_address;
+use witness::{verify_tx_address, verify_tx_recover_address};
 
 /// All possible TX validation errors
 #[derive(Debug, Encode, Decode)]
@@ -294,6 +294,9 @@ pub fn verify_bonded_deposit_core(
         maintx.attributes.app_version,
         extra_info,
     )?;
+    if maintx.to_staked_account.min_app_version() > maintx.attributes.app_version {
+        return Err(Error::UnsupportedVersion);
+    }
     check_inputs_basic(&maintx.inputs, witness)?;
     let incoins = check_inputs(
         &maintx.id(),
@@ -322,6 +325,9 @@ pub fn verify_unbonded_withdraw_core(
         maintx.attributes.app_version,
         &extra_info,
     )?;
+    if account.address.min_app_version() > maintx.attributes.app_version {
+        return Err(Error::UnsupportedVersion);
+    }
     check_outputs_basic(&maintx.outputs)?;
     // checks that account transaction count matches to the one in transaction
     if maintx.nonce != account.nonce {
@@ -367,3 +373,101 @@ pub fn verify_unjailed(account: &StakedState) -> Result<(), Error> {
         Ok(())
     }
 }
+
+/// Result of verifying an obfuscated transaction
+#[derive(Debug)]
+pub enum VerifiedEnclaveTx {
+    /// transaction creating outputs (to be sealed) and the fee it paid
+    WithOutputs {
+        /// paid fee
+        paid_fee: Fee,
+        /// transaction with its outputs
+        tx: TxWithOutputs,
+    },
+    /// deposit to a staked state and the sum of its inputs
+    DepositStake {
+        /// sum of input amounts
+        input_coins: Coin,
+    },
+}
+
+/// checks an obfuscated transaction (as done by the transaction validation enclave)
+/// -- `decrypt` deobfuscates the payload and `unseal` unseals the transactions
+/// of given input ids; any payload or input that can't be opened or doesn't match is `Error::EnclaveRejected`
+/// WARNING: it assumes double-spending BitVec of inputs and basic validity of the request are checked in chain-abci
+pub fn verify_enclave_tx<D, U>(
+    tx: TxEnclaveAux,
+    account: Option<StakedState>,
+    extra_info: &ChainInfo,
+    sealed_inputs: Option<Vec<Vec<u8>>>,
+    decrypt: D,
+    unseal: U,
+) -> Result<VerifiedEnclaveTx, Error>
+where
+    D: FnOnce(&TxObfuscated) -> Option<PlainTxAux>,
+    U: FnOnce(Vec<TxId>, Vec<Vec<u8>>) -> Option<Vec<TxWithOutputs>>,
+{
+    match (sealed_inputs, tx) {
+        (
+            Some(sealed_inputs),
+            TxEnclaveAux::TransferTx {
+                payload,
+                no_of_outputs,
+                inputs,
+            },
+        ) => {
+            let inputs = unseal(inputs.iter().map(|x| x.id).collect(), sealed_inputs)
+                .ok_or(Error::EnclaveRejected)?;
+            match decrypt(&payload) {
+                Some(PlainTxAux::TransferTx(tx, witness))
+                    if tx.id() == payload.txid && tx.outputs.len() as TxoSize == no_of_outputs =>
+                {
+                    let paid_fee = verify_transfer(&tx, &witness, extra_info, inputs)?;
+                    Ok(VerifiedEnclaveTx::WithOutputs {
+                        paid_fee,
+                        tx: TxWithOutputs::Transfer(tx),
+                    })
+                }
+                _ => Err(Error::EnclaveRejected),
+            }
+        }
+        (Some(sealed_inputs), TxEnclaveAux::DepositStakeTx { tx, payload }) => {
+            let inputs = unseal(tx.inputs.iter().map(|x| x.id).collect(), sealed_inputs)
+                .ok_or(Error::EnclaveRejected)?;
+            match decrypt(&payload) {
+                Some(PlainTxAux::DepositStakeTx(witness)) => {
+                    let input_coins =
+                        verify_bonded_deposit_core(&tx, &witness, extra_info, inputs)?;
+                    Ok(VerifiedEnclaveTx::DepositStake { input_coins })
+                }
+                _ => Err(Error::EnclaveRejected),
+            }
+        }
+        (
+            None,
+            TxEnclaveAux::WithdrawUnbondedStakeTx {
+                no_of_outputs,
+                payload,
+                witness,
+            },
+        ) => {
+            let address = verify_tx_recover_address(&witness, &payload.txid)
+                .map_err(|_| Error::EnclaveRejected)?;
+            match (decrypt(&payload), account) {
+                (Some(PlainTxAux::WithdrawUnbondedStakeTx(tx)), Some(account))
+                    if tx.id() == payload.txid
+                        && no_of_outputs == tx.outputs.len() as TxoSize
+                        && account.address == address =>
+                {
+                    let paid_fee = verify_unbonded_withdraw_core(&tx, extra_info, &account)?;
+                    Ok(VerifiedEnclaveTx::WithOutputs {
+                        paid_fee,
+                        tx: TxWithOutputs::StakeWithdraw(tx),
+                    })
+                }
+                _ => Err(Error::EnclaveRejected),
+            }
+        }
+        (_, _) => Err(Error::EnclaveRejected),
+    }
+}
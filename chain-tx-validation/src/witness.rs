@@ -35,6 +35,7 @@ pub fn verify_tx_address(
 
 /// verify the signature against the given transation `Tx`
 /// and recovers the address from it
+/// (for tree signatures, the address is the root hash of the merkle proof)
 ///
 pub fn verify_tx_recover_address(
     witness: &StakedStateOpWitness,
@@ -48,6 +49,22 @@ pub fn verify_tx_recover_address(
             secp.verify(&message, &sig.to_standard(), &pk)?;
             Ok(StakedStateAddress::BasicRedeem(RedeemAddress::from(&pk)))
         }
+        StakedStateOpWitness::TreeSig(sig, proof) => {
+            // the root hash is taken from the path, so the path has to be checked against it
+            let root_hash = proof.root_hash();
+            if !proof.verify(&root_hash) {
+                return Err(secp256k1::Error::InvalidPublicKey);
+            }
+            let secp = secp256k1::SECP256K1;
+            let message = Message::from_slice(txid)?;
+            schnorr_verify(
+                &secp,
+                &message,
+                &sig,
+                &XOnlyPublicKey::from_slice(proof.value().as_bytes())?,
+            )?;
+            Ok(StakedStateAddress::OrTree(proof.root_hash()))
+        }
     }
 }
 
@@ -58,10 +75,11 @@ pub mod tests {
     use secp256k1::schnorrsig::schnorr_sign;
     use secp256k1::{PublicKey, SecretKey};
 
-    use chain_core::common::MerkleTree;
+    use chain_core::common::{MerkleTree, Proof};
     use chain_core::tx::data::Tx;
     use chain_core::tx::witness::tree::RawXOnlyPubkey;
     use chain_core::tx::TransactionId;
+    use parity_scale_codec::{Decode, Encode};
 
     #[test]
     fn check_1_of_1_verify() {
@@ -183,4 +201,64 @@ pub mod tests {
 
         assert_eq!(address, recovered_address);
     }
+
+    #[test]
+    fn check_staked_tree_verify() {
+        let transation = Tx::new();
+
+        let secp = secp256k1::SECP256K1;
+
+        let secret_keys = [
+            SecretKey::from_slice(&[0xcd; 32]).expect("Unable to create secret key"),
+            SecretKey::from_slice(&[0xde; 32]).expect("Unable to create secret key"),
+        ];
+        let public_keys = [
+            XOnlyPublicKey::from_secret_key(&secp, &secret_keys[0]),
+            XOnlyPublicKey::from_secret_key(&secp, &secret_keys[1]),
+        ];
+
+        let merkle_tree = MerkleTree::new(vec![
+            RawXOnlyPubkey::from(public_keys[0].serialize()),
+            RawXOnlyPubkey::from(public_keys[1].serialize()),
+        ]);
+        let address = StakedStateAddress::OrTree(merkle_tree.root_hash());
+
+        let message = Message::from_slice(&transation.id()).unwrap();
+
+        let witness = StakedStateOpWitness::TreeSig(
+            schnorr_sign(&secp, &message, &secret_keys[1], &mut rand::thread_rng()),
+            merkle_tree
+                .generate_proof(RawXOnlyPubkey::from(public_keys[1].serialize()))
+                .unwrap(),
+        );
+        let recovered_address = verify_tx_recover_address(&witness, &transation.id())
+            .expect("Unable to verify signature");
+        assert_eq!(address, recovered_address);
+
+        let witness = StakedStateOpWitness::TreeSig(
+            schnorr_sign(&secp, &message, &secret_keys[1], &mut rand::thread_rng()),
+            merkle_tree
+                .generate_proof(RawXOnlyPubkey::from(public_keys[0].serialize()))
+                .unwrap(),
+        );
+        assert!(verify_tx_recover_address(&witness, &transation.id()).is_err());
+
+        // forged path: proof of the tree, but with the leaf replaced by a key outside of it
+        let outsider_key = SecretKey::from_slice(&[0xef; 32]).expect("Unable to create secret key");
+        let outsider_public_key = XOnlyPublicKey::from_secret_key(&secp, &outsider_key);
+        let mut proof_bytes = merkle_tree
+            .generate_proof(RawXOnlyPubkey::from(public_keys[0].serialize()))
+            .unwrap()
+            .encode();
+        let leaf_start = proof_bytes.len() - 32;
+        proof_bytes[leaf_start..].copy_from_slice(&outsider_public_key.serialize());
+        let forged_proof = Proof::<RawXOnlyPubkey>::decode(&mut proof_bytes.as_slice()).unwrap();
+        assert_eq!(merkle_tree.root_hash(), forged_proof.root_hash());
+
+        let witness = StakedStateOpWitness::TreeSig(
+            schnorr_sign(&secp, &message, &outsider_key, &mut rand::thread_rng()),
+            forged_proof,
+        );
+        assert!(verify_tx_recover_address(&witness, &transation.id()).is_err());
+    }
 }
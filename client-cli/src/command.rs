@@ -1,9 +1,13 @@
 mod address_command;
+mod label_command;
 mod multisig_command;
+mod storage_command;
 mod transaction_command;
+mod utxo_command;
 mod wallet_command;
 
 use std::convert::TryInto;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
 use chrono::{DateTime, Local, NaiveDateTime, Utc};
@@ -18,7 +22,8 @@ use structopt::StructOpt;
 
 use chain_core::init::coin::Coin;
 use chain_core::state::account::{NodeState, StakedStateAddress};
-use client_common::storage::SledStorage;
+#[cfg(unix)]
+use client_common::secret_service::SecretService;
 #[cfg(not(feature = "mock-enclave"))]
 use client_common::tendermint::types::AbciQueryExt;
 use client_common::tendermint::types::GenesisExt;
@@ -27,25 +32,32 @@ use client_common::TransactionObfuscation;
 use client_common::{ErrorKind, Result, ResultExt, SecKey, Storage};
 use client_core::signer::WalletSignerManager;
 use client_core::transaction_builder::DefaultWalletTransactionBuilder;
-use client_core::types::BalanceChange;
+use client_core::types::staking_history::staking_history_to_csv;
+use client_core::types::{BalanceChange, StakingHistoryEntry};
 use client_core::wallet::syncer::{
     spawn_light_client_supervisor, Handle, ObfuscationSyncerConfig, ProgressReport, SyncerOptions,
     WalletSyncer,
 };
 use client_core::wallet::{DefaultWalletClient, WalletClient};
+use client_network::address_discovery::{AddressDiscovery, DiscoveryProgress};
 use client_network::network_ops::{DefaultNetworkOpsClient, NetworkOpsClient};
 
 use self::address_command::AddressCommand;
+use self::label_command::LabelCommand;
 use self::multisig_command::MultiSigCommand;
+use self::storage_command::StorageCommand;
 use self::transaction_command::TransactionCommand;
+use self::utxo_command::UtxoCommand;
 use self::wallet_command::WalletCommand;
 use crate::logo::{get_jok, get_logo};
-use crate::{ask_seckey, storage_path, tendermint_url};
+use crate::{
+    ask_seckey, open_storage, open_storage_unmigrated, storage_path, tendermint_url, AppStorage,
+};
 use chain_core::tx::fee::LinearFee;
 use client_core::hd_wallet::HardwareKind;
 #[cfg(feature = "mock-hardware-wallet")]
 use client_core::service::MockHardwareService;
-use client_core::service::{HwKeyService, LedgerService};
+use client_core::service::{HwKeyService, LedgerService, RemoteSignerService};
 use once_cell::sync::Lazy;
 use std::env;
 
@@ -59,8 +71,8 @@ type AppTransactionCipher = DefaultTransactionObfuscation;
 #[cfg(feature = "mock-enclave")]
 type AppTransactionCipher = MockAbciTransactionObfuscation<WebsocketRpcClient>;
 
-type AppTxBuilder = DefaultWalletTransactionBuilder<SledStorage, LinearFee, AppTransactionCipher>;
-type AppWalletClient = DefaultWalletClient<SledStorage, WebsocketRpcClient, AppTxBuilder>;
+type AppTxBuilder = DefaultWalletTransactionBuilder<AppStorage, LinearFee, AppTransactionCipher>;
+type AppWalletClient = DefaultWalletClient<AppStorage, WebsocketRpcClient, AppTxBuilder>;
 
 static VERSION: Lazy<String> = Lazy::new(|| {
     format!(
@@ -74,10 +86,10 @@ static VERSION: Lazy<String> = Lazy::new(|| {
 });
 
 #[cfg(feature = "mock-hardware-wallet")]
-const HARDWARE_WALLET_KIND: [&str; 3] = ["ledger", "trezor", "mock"];
+const HARDWARE_WALLET_KIND: [&str; 4] = ["ledger", "trezor", "remote", "mock"];
 
 #[cfg(not(feature = "mock-hardware-wallet"))]
-const HARDWARE_WALLET_KIND: [&str; 2] = ["ledger", "trezor"];
+const HARDWARE_WALLET_KIND: [&str; 3] = ["ledger", "trezor", "remote"];
 
 #[derive(Debug, StructOpt)]
 #[structopt(
@@ -88,7 +100,11 @@ ENVIRONMENT VARIABLES:
     CRYPTO_CLIENT_DEBUG             Set to `true` for detailed error messages (Default: `false`)
     CRYPTO_CHAIN_ID                 Chain ID of Crypto.com Chain
     CRYPTO_CLIENT_STORAGE           Storage directory (Default: `.storage`)
+    CRYPTO_CLIENT_STORAGE_BACKEND   Storage backend: `sled` or `sqlite` (Default: `sled`)
+    CRYPTO_CLIENT_SECRET_SERVICE    Unix socket of the secret service keeping the enckeys of unlocked wallets (Optional)
     CRYPTO_CLIENT_TENDERMINT        Websocket endpoint for tendermint (Default: `ws://localhost:26657/websocket`)
+    CRYPTO_REMOTE_SIGNER            Endpoint of the remote signer: `tcp://<address>` or `unix://<path>` (Optional)
+    CRYPTO_REMOTE_SIGNER_KEY        Hex encoded authentication key shared with the remote signer (Optional)
     CRYPTO_GENESIS_FINGERPRINT             Set the genesis fingerprint(Optional)
 "#
 )]
@@ -146,6 +162,54 @@ pub enum Command {
         )]
         reversed: bool,
     },
+    #[structopt(
+        name = "staking-history",
+        about = "Get staking history (rewards, slashing and other changes) of staking addresses of a wallet"
+    )]
+    StakingHistory {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "staking address",
+            short = "a",
+            long = "address",
+            help = "Staking address (all staking addresses of wallet if not given)"
+        )]
+        address: Option<StakedStateAddress>,
+        #[structopt(name = "offset", short, long, help = "Offset", default_value = "0")]
+        offset: usize,
+        #[structopt(name = "limit", short, long, help = "Limit", default_value = "100")]
+        limit: usize,
+        #[structopt(
+            name = "reversed",
+            short,
+            long,
+            help = "Reverse order (default is from old to new)"
+        )]
+        reversed: bool,
+        #[structopt(
+            name = "csv",
+            long = "csv",
+            parse(from_os_str),
+            help = "Export whole history to a CSV file instead of printing it"
+        )]
+        csv: Option<PathBuf>,
+    },
+    #[structopt(name = "label", about = "Address book and label operations")]
+    Label {
+        #[structopt(subcommand)]
+        label_command: LabelCommand,
+    },
+    #[structopt(name = "utxo", about = "Unspent output (coin control) operations")]
+    Utxo {
+        #[structopt(subcommand)]
+        utxo_command: UtxoCommand,
+    },
     #[structopt(name = "transaction", about = "Transaction operations")]
     Transaction {
         #[structopt(subcommand)]
@@ -179,6 +243,35 @@ pub enum Command {
         )]
         hardware: Option<HardwareKind>,
     },
+    #[structopt(
+        name = "discover",
+        about = "Discover used addresses of a restored HD wallet (BIP-44 gap limit scan)"
+    )]
+    Discover {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "gap-limit",
+            short,
+            long,
+            default_value = "20",
+            help = "Number of consecutive unused addresses which ends the scan of an address chain"
+        )]
+        gap_limit: u32,
+        #[structopt(
+            name = "batch-size",
+            short,
+            long,
+            default_value = "20",
+            help = "Number of requests per batch in RPC calls to tendermint"
+        )]
+        batch_size: usize,
+    },
     #[structopt(name = "sync", about = "Synchronize client with Crypto.com Chain")]
     Sync {
         #[structopt(
@@ -245,6 +338,13 @@ pub enum Command {
         )]
         light_client_trusting_blockhash: Option<String>,
 
+        #[structopt(
+            name = "filter peers",
+            long = "filter-peers",
+            help = "Additional tendermint rpc urls (comma separated) to fetch block filters from if the node serves invalid ones"
+        )]
+        filter_peers: Option<String>,
+
         #[structopt(
             name = "disable-address-recovery",
             long,
@@ -264,6 +364,26 @@ pub enum Command {
         #[structopt(subcommand)]
         multisig_command: MultiSigCommand,
     },
+    #[structopt(name = "storage", about = "Storage operations")]
+    Storage {
+        #[structopt(subcommand)]
+        storage_command: StorageCommand,
+    },
+    #[cfg(unix)]
+    #[structopt(
+        name = "secret-service",
+        about = "Run a secret service keeping the enckeys of unlocked wallets"
+    )]
+    SecretService {
+        #[structopt(
+            name = "socket",
+            short,
+            long,
+            parse(from_os_str),
+            help = "Unix socket to listen on"
+        )]
+        socket: PathBuf,
+    },
 }
 
 /// normal
@@ -304,23 +424,23 @@ impl Command {
     pub fn execute(&self) -> Result<()> {
         match self {
             Command::Wallet { wallet_command } => {
-                let storage = SledStorage::new(storage_path())?;
+                let storage = open_storage()?;
                 let wallet_client = DefaultWalletClient::new_read_only(storage);
                 wallet_command.execute(wallet_client)
             }
             Command::Address { address_command } => {
-                let storage = SledStorage::new(storage_path())?;
+                let storage = open_storage()?;
                 let wallet_client = DefaultWalletClient::new_read_only(storage);
                 address_command.execute(wallet_client)
             }
             Command::ViewKey { name, private } => {
-                let storage = SledStorage::new(storage_path())?;
+                let storage = open_storage()?;
                 let wallet_client = DefaultWalletClient::new_read_only(storage);
 
                 Self::get_view_key(wallet_client, name, *private)
             }
             Command::Balance { name } => {
-                let storage = SledStorage::new(storage_path())?;
+                let storage = open_storage()?;
                 let wallet_client = DefaultWalletClient::new_read_only(storage);
                 Self::get_balance(wallet_client, name)
             }
@@ -330,21 +450,58 @@ impl Command {
                 limit,
                 reversed,
             } => {
-                let storage = SledStorage::new(storage_path())?;
+                let storage = open_storage()?;
                 let wallet_client = DefaultWalletClient::new_read_only(storage);
                 Self::get_history(wallet_client, name, *offset, *limit, *reversed)
             }
+            Command::StakingHistory {
+                name,
+                address,
+                offset,
+                limit,
+                reversed,
+                csv,
+            } => {
+                let storage = open_storage()?;
+                let wallet_client = DefaultWalletClient::new_read_only(storage);
+                match csv {
+                    Some(file) => {
+                        Self::export_staking_history(wallet_client, name, address.as_ref(), file)
+                    }
+                    None => Self::get_staking_history(
+                        wallet_client,
+                        name,
+                        address.as_ref(),
+                        *offset,
+                        *limit,
+                        *reversed,
+                    ),
+                }
+            }
+            Command::Label { label_command } => {
+                let storage = open_storage()?;
+                let wallet_client = DefaultWalletClient::new_read_only(storage);
+                label_command.execute(wallet_client)
+            }
+            Command::Utxo { utxo_command } => {
+                let storage = open_storage()?;
+                let wallet_client = DefaultWalletClient::new_read_only(storage);
+                utxo_command.execute(wallet_client)
+            }
             Command::Transaction {
                 transaction_command,
                 hardware,
             } => {
-                let storage = SledStorage::new(storage_path())?;
+                let storage = open_storage()?;
                 let tendermint_client = WebsocketRpcClient::new(&tendermint_url())?;
                 let hw_key_service = match hardware {
                     None => HwKeyService::default(),
                     #[cfg(feature = "mock-hardware-wallet")]
                     Some(HardwareKind::Mock) => HwKeyService::Mock(MockHardwareService::new()),
                     Some(HardwareKind::Trezor) => HwKeyService::default(),
+                    Some(HardwareKind::Remote) => {
+                        HwKeyService::RemoteSigner(RemoteSignerService::new()?)
+                    }
                     Some(HardwareKind::Ledger) => {
                         let ledger_service = LedgerService::new(true)?;
                         HwKeyService::Ledger(ledger_service)
@@ -387,12 +544,15 @@ impl Command {
                     #[cfg(feature = "mock-hardware-wallet")]
                     Some(HardwareKind::Mock) => HwKeyService::Mock(MockHardwareService::new()),
                     Some(HardwareKind::Trezor) => HwKeyService::default(),
+                    Some(HardwareKind::Remote) => {
+                        HwKeyService::RemoteSigner(RemoteSignerService::new()?)
+                    }
                     Some(HardwareKind::Ledger) => {
                         let ledger_service = LedgerService::new(true)?;
                         HwKeyService::Ledger(ledger_service)
                     }
                 };
-                let storage = SledStorage::new(storage_path())?;
+                let storage = open_storage()?;
                 let tendermint_client = WebsocketRpcClient::new(&tendermint_url())?;
                 let signer_manager =
                     WalletSignerManager::new(storage.clone(), hw_key_service.clone());
@@ -420,6 +580,47 @@ impl Command {
                 );
                 Self::get_staked_stake(&network_ops_client, &name, address)
             }
+            Command::Discover {
+                name,
+                gap_limit,
+                batch_size,
+            } => {
+                let storage = open_storage()?;
+                let tendermint_client = WebsocketRpcClient::new(&tendermint_url())?;
+                let hw_key_service = HwKeyService::default();
+                let signer_manager =
+                    WalletSignerManager::new(storage.clone(), hw_key_service.clone());
+                let fee_algorithm = tendermint_client.genesis()?.fee_policy();
+                let transaction_obfuscation = get_tx_query(tendermint_client.clone())?;
+                let transaction_builder = DefaultWalletTransactionBuilder::new(
+                    signer_manager.clone(),
+                    fee_algorithm,
+                    transaction_obfuscation.clone(),
+                );
+                let wallet_client = DefaultWalletClient::new(
+                    storage,
+                    tendermint_client.clone(),
+                    transaction_builder,
+                    None,
+                    hw_key_service,
+                );
+                let network_ops_client = DefaultNetworkOpsClient::new(
+                    wallet_client,
+                    signer_manager,
+                    tendermint_client.clone(),
+                    fee_algorithm,
+                    transaction_obfuscation.clone(),
+                );
+                let discovery = AddressDiscovery::new(
+                    network_ops_client.get_wallet_client(),
+                    &network_ops_client,
+                    &tendermint_client,
+                    &transaction_obfuscation,
+                )
+                .with_gap_limit(*gap_limit)
+                .with_batch_size(*batch_size);
+                Self::discover(&discovery, name)
+            }
             Command::Sync {
                 name,
                 batch_size,
@@ -432,12 +633,13 @@ impl Command {
                 light_client_trusting_period_seconds,
                 light_client_trusting_height,
                 light_client_trusting_blockhash,
+                filter_peers,
             } => {
                 let rpc_url = tendermint_url();
                 let tendermint_client = WebsocketRpcClient::new(&rpc_url)?;
                 let tx_obfuscation = get_tx_query(tendermint_client.clone())?;
                 let db_path = storage_path();
-                let storage = SledStorage::new(&db_path)?;
+                let storage = open_storage()?;
                 let max_trusting_period = tendermint_client.genesis()?.trusting_period() / 2;
 
                 let mut light_client_peers_user: String = "".into();
@@ -579,6 +781,14 @@ impl Command {
                         light_client_trusting_blockhash: light_client_trusting_blockhash_user,
                     },
                     handle.clone(),
+                )
+                .with_filter_peers(
+                    filter_peers
+                        .iter()
+                        .flat_map(|peers| peers.split(','))
+                        .filter(|url| !url.trim().is_empty())
+                        .map(|url| WebsocketRpcClient::new(url.trim()))
+                        .collect::<Result<Vec<_>>>()?,
                 );
                 Self::resync(config, name.clone(), enckey, *force, storage)?;
                 if let Some(this_handle) = handle.as_ref() {
@@ -589,13 +799,92 @@ impl Command {
                 Ok(())
             }
             Command::MultiSig { multisig_command } => {
-                let storage = SledStorage::new(storage_path())?;
-                let wallet_client = DefaultWalletClient::new_read_only(storage);
-                multisig_command.execute(wallet_client)
+                let storage = open_storage()?;
+
+                if multisig_command.requires_network() {
+                    let tendermint_client = WebsocketRpcClient::new(&tendermint_url())?;
+                    let signer_manager =
+                        WalletSignerManager::new(storage.clone(), HwKeyService::default());
+                    let fee_algorithm = tendermint_client.genesis()?.fee_policy();
+                    let transaction_builder = DefaultWalletTransactionBuilder::new(
+                        signer_manager,
+                        fee_algorithm,
+                        get_tx_query(tendermint_client.clone())?,
+                    );
+                    let wallet_client = DefaultWalletClient::new(
+                        storage,
+                        tendermint_client,
+                        transaction_builder,
+                        None,
+                        HwKeyService::default(),
+                    );
+                    multisig_command.execute(wallet_client)
+                } else {
+                    let wallet_client = DefaultWalletClient::new_read_only(storage);
+                    multisig_command.execute(wallet_client)
+                }
+            }
+            Command::Storage { storage_command } => {
+                storage_command.execute(open_storage_unmigrated()?)
+            }
+            #[cfg(unix)]
+            Command::SecretService { socket } => {
+                success(&format!("Secret service listening on {}", socket.display()));
+                SecretService::default().serve(socket)
             }
         }
     }
 
+    fn discover<W, N, C, O>(discovery: &AddressDiscovery<W, N, C, O>, name: &str) -> Result<()>
+    where
+        W: WalletClient,
+        N: NetworkOpsClient,
+        C: Client,
+        O: TransactionObfuscation,
+    {
+        let enckey = ask_seckey(None)?;
+        let mut progress_bar: Option<ProgressBar<_>> = None;
+        let result = discovery.discover(name, &enckey, |report| match report {
+            DiscoveryProgress::StakingAddress { index, used: true }
+            | DiscoveryProgress::TransferAddress { index, used: true } => {
+                log::info!("used address found at index {}", index)
+            }
+            DiscoveryProgress::BlockFilters {
+                current_block_height,
+                finish_block_height,
+                ..
+            } => {
+                let pb = progress_bar.get_or_insert_with(|| {
+                    let mut pb = ProgressBar::new(finish_block_height);
+                    pb.message("Scanning block filters: ");
+                    pb
+                });
+                pb.set(current_block_height);
+                if current_block_height == finish_block_height {
+                    pb.finish_println("");
+                }
+            }
+            _ => {}
+        })?;
+
+        success(&format!(
+            "Found {} used staking address(es) and {} used transfer address(es) in {} block(s)",
+            result.staking_addresses.len(),
+            result.transfer_addresses.len(),
+            result.matched_blocks.len()
+        ));
+        for address in result.staking_addresses.iter() {
+            ask("Staking address: ");
+            success(&address.to_string());
+        }
+        for address in result.transfer_addresses.iter() {
+            ask("Transfer address: ");
+            success(&address.to_string());
+        }
+        success("Run `sync --force` to synchronize the funds of the discovered addresses");
+        Ok(())
+    }
+
     fn get_staked_stake<N: NetworkOpsClient>(
         network_ops_client: &N,
         name: &str,
@@ -771,9 +1060,18 @@ impl Command {
                 Cell::new("Transaction Type", bold),
                 Cell::new("Block Height", bold),
                 Cell::new("Block Time", bold),
+                Cell::new("Label", bold),
             ]));
 
-            for change in history {
+            for labeled_change in history {
+                let label = labeled_change
+                    .label
+                    .iter()
+                    .chain(labeled_change.contacts.iter())
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let change = labeled_change.change;
                 let green = CellFormat::builder()
                     .foreground_color(Some(Color::Green))
                     .build();
@@ -806,6 +1104,7 @@ impl Command {
                     Cell::new(&change.transaction_type, Default::default()),
                     Cell::new(&change.block_height, right_justify),
                     Cell::new(&change.block_time, Default::default()),
+                    Cell::new(&label, Default::default()),
                 ]));
             }
 
@@ -822,12 +1121,86 @@ impl Command {
         Ok(())
     }
 
+    fn get_staking_history<T: WalletClient>(
+        wallet_client: T,
+        name: &str,
+        address: Option<&StakedStateAddress>,
+        offset: usize,
+        limit: usize,
+        reversed: bool,
+    ) -> Result<()> {
+        let enckey = ask_seckey(None)?;
+        print_sync_warning();
+
+        let history =
+            wallet_client.staking_history(name, &enckey, address, offset, limit, reversed)?;
+
+        if history.is_empty() {
+            success("No staking history found!");
+            return Ok(());
+        }
+
+        let bold = CellFormat::builder().bold(true).build();
+        let right_justify = CellFormat::builder().justify(Justify::Right).build();
+
+        let mut rows = vec![Row::new(vec![
+            Cell::new("Staking Address", bold),
+            Cell::new("Kind", bold),
+            Cell::new("Bonded", bold),
+            Cell::new("Unbonded", bold),
+            Cell::new("Details", bold),
+            Cell::new("Block Height", bold),
+            Cell::new("Block Time", bold),
+        ])];
+
+        for entry in history {
+            rows.push(Row::new(vec![
+                Cell::new(&entry.staking_address, Default::default()),
+                Cell::new(&entry.kind, Default::default()),
+                staking_change_cell(entry.bonded_change),
+                staking_change_cell(entry.unbonded_change),
+                Cell::new(&staking_entry_details(&entry), Default::default()),
+                Cell::new(&entry.block_height, right_justify),
+                Cell::new(&entry.block_time, Default::default()),
+            ]));
+        }
+
+        let table = Table::new(rows, Default::default())
+            .chain(|| (ErrorKind::InternalError, "Unable to create new table"))?;
+
+        table
+            .print_stdout()
+            .chain(|| (ErrorKind::IoError, "Unable to print table"))
+    }
+
+    fn export_staking_history<T: WalletClient>(
+        wallet_client: T,
+        name: &str,
+        address: Option<&StakedStateAddress>,
+        file: &PathBuf,
+    ) -> Result<()> {
+        let enckey = ask_seckey(None)?;
+        print_sync_warning();
+
+        let history =
+            wallet_client.staking_history(name, &enckey, address, 0, std::usize::MAX, false)?;
+        std::fs::write(file, staking_history_to_csv(&history))
+            .chain(|| (ErrorKind::IoError, "Unable to write to file"))?;
+
+        success(&format!(
+            "{} staking history entries exported to {}",
+            history.len(),
+            file.display()
+        ));
+        Ok(())
+    }
+
     fn resync<S: Storage, C: Client, O: TransactionObfuscation, L: Handle + Send + Sync + Clone>(
         config: ObfuscationSyncerConfig<S, C, O, L>,
         name: String,
         enckey: SecKey,
         force: bool,
-        storage: SledStorage,
+        storage: AppStorage,
     ) -> Result<()> {
         let wallet_client = get_wallet_client(storage)?;
 
@@ -879,7 +1252,53 @@ fn print_sync_warning() {
     println!();
 }
 
-fn get_wallet_client(storage: SledStorage) -> Result<AppWalletClient> {
+fn staking_change_cell(change: BalanceChange) -> Cell {
+    let right_justify = CellFormat::builder().justify(Justify::Right).build();
+    let green = CellFormat::builder()
+        .foreground_color(Some(Color::Green))
+        .justify(Justify::Right)
+        .build();
+    let red = CellFormat::builder()
+        .foreground_color(Some(Color::Red))
+        .justify(Justify::Right)
+        .build();
+
+    match change {
+        BalanceChange::Incoming { value } => Cell::new(&format!("+{}", value), green),
+        BalanceChange::Outgoing { value } => Cell::new(&format!("-{}", value), red),
+        BalanceChange::NoChange => Cell::new("", right_justify),
+    }
+}
+
+fn staking_entry_details(entry: &StakingHistoryEntry) -> String {
+    let mut details = Vec::new();
+    if let Some(reason) = &entry.reason {
+        details.push(format!("reason: {}", reason));
+    }
+    if let Some(jailed_until) = entry.jailed_until {
+        details.push(format!(
+            "jailed until: {}",
+            timespec_to_string(jailed_until)
+        ));
+    }
+    if let Some(unbonded_from) = entry.unbonded_from {
+        details.push(format!(
+            "withdrawable from: {}",
+            timespec_to_string(unbonded_from)
+        ));
+    }
+    details.join(", ")
+}
+
+fn timespec_to_string(timespec: u64) -> String {
+    <DateTime<Local>>::from(DateTime::<Utc>::from_utc(
+        NaiveDateTime::from_timestamp(timespec.try_into().unwrap_or(std::i64::MAX), 0),
+        Utc,
+    ))
+    .to_string()
+}
+
+fn get_wallet_client(storage: AppStorage) -> Result<AppWalletClient> {
     let tendermint_client = WebsocketRpcClient::new(&tendermint_url())?;
 
     let hw_key_service = HwKeyService::default();
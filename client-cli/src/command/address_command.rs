@@ -5,6 +5,7 @@ use structopt::StructOpt;
 use unicase::eq_ascii;
 
 use client_common::{error::ResultExt, Error, ErrorKind, PublicKey, Result};
+use client_core::service::account_wallet_name;
 use client_core::WalletClient;
 
 use crate::{ask_hardware_kind, ask_seckey};
@@ -59,6 +60,14 @@ pub enum AddressCommand {
             case_insensitive = true
         )]
         address_type: AddressType,
+        #[structopt(
+            name = "account",
+            short = "a",
+            long = "account",
+            default_value = "0",
+            help = "Account of HD wallet"
+        )]
+        account: u32,
     },
     #[structopt(name = "list", about = "List all addresses for a wallet")]
     List {
@@ -89,6 +98,14 @@ pub enum AddressCommand {
             help = "Reverse order (default is from old to new)"
         )]
         reversed: bool,
+        #[structopt(
+            name = "account",
+            short = "a",
+            long = "account",
+            default_value = "0",
+            help = "Account of HD wallet"
+        )]
+        account: u32,
     },
     #[structopt(name = "list-pub-key", about = "Shows the public keys of a wallet")]
     ListPubKey {
@@ -108,32 +125,53 @@ pub enum AddressCommand {
             case_insensitive = true
         )]
         address_type: AddressType,
+        #[structopt(
+            name = "account",
+            short = "a",
+            long = "account",
+            default_value = "0",
+            help = "Account of HD wallet"
+        )]
+        account: u32,
     },
 }
 
 impl AddressCommand {
     pub fn execute<T: WalletClient>(&self, wallet_client: T) -> Result<()> {
         match self {
-            AddressCommand::New { name, address_type } => {
-                Self::new_address(wallet_client, name, address_type)
-            }
+            AddressCommand::New {
+                name,
+                address_type,
+                account,
+            } => Self::new_address(
+                wallet_client,
+                &account_wallet_name(name, *account),
+                address_type,
+            ),
             AddressCommand::List {
                 name,
                 address_type,
                 offset,
                 limit,
                 reversed,
+                account,
             } => Self::list_addresses(
                 wallet_client,
-                name,
+                &account_wallet_name(name, *account),
                 address_type,
                 *offset,
                 *limit,
                 *reversed,
             ),
-            AddressCommand::ListPubKey { name, address_type } => {
-                Self::list_pubkeys(wallet_client, name, address_type)
-            }
+            AddressCommand::ListPubKey {
+                name,
+                address_type,
+                account,
+            } => Self::list_pubkeys(
+                wallet_client,
+                &account_wallet_name(name, *account),
+                address_type,
+            ),
         }
     }
 
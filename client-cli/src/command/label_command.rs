@@ -0,0 +1,235 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+
+use cli_table::format::CellFormat;
+use cli_table::{Cell, Row, Table};
+use quest::success;
+use structopt::StructOpt;
+
+use client_common::{ErrorKind, Result, ResultExt};
+use client_core::types::label::{labels_from_jsonl, labels_to_jsonl};
+use client_core::types::{Label, LabelType};
+use client_core::WalletClient;
+
+use crate::ask_seckey;
+
+const LABEL_TYPE_VARIANTS: [&str; 3] = ["tx", "addr", "output"];
+
+#[derive(Debug, StructOpt)]
+pub enum LabelCommand {
+    #[structopt(
+        name = "set",
+        about = "Labels a transaction, an output or an address (address book contact)"
+    )]
+    Set {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "label type",
+            short = "t",
+            long = "type",
+            help = "Type of labelled reference",
+            possible_values = &LABEL_TYPE_VARIANTS
+        )]
+        label_type: LabelType,
+        #[structopt(
+            name = "reference",
+            short = "r",
+            long = "ref",
+            help = "Transaction id, <transaction id>:<output index> or address"
+        )]
+        reference: String,
+        #[structopt(
+            name = "label",
+            short = "l",
+            long = "label",
+            help = "Label (name of contact for addresses)"
+        )]
+        label: String,
+        #[structopt(name = "notes", long = "notes", help = "Notes")]
+        notes: Option<String>,
+    },
+    #[structopt(name = "remove", about = "Removes a label")]
+    Remove {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "label type",
+            short = "t",
+            long = "type",
+            help = "Type of labelled reference",
+            possible_values = &LABEL_TYPE_VARIANTS
+        )]
+        label_type: LabelType,
+        #[structopt(
+            name = "reference",
+            short = "r",
+            long = "ref",
+            help = "Transaction id, <transaction id>:<output index> or address"
+        )]
+        reference: String,
+    },
+    #[structopt(name = "list", about = "Lists labels (`--type addr` for address book)")]
+    List {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "label type",
+            short = "t",
+            long = "type",
+            help = "Only list labels of this type",
+            possible_values = &LABEL_TYPE_VARIANTS
+        )]
+        label_type: Option<LabelType>,
+    },
+    #[structopt(name = "export", about = "Exports labels as BIP-329 JSON lines")]
+    Export {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "file",
+            short = "f",
+            long = "file",
+            parse(from_os_str),
+            help = "File to write the labels to"
+        )]
+        file: PathBuf,
+    },
+    #[structopt(name = "import", about = "Imports labels from BIP-329 JSON lines")]
+    Import {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "file",
+            short = "f",
+            long = "file",
+            parse(from_os_str),
+            help = "File to read the labels from"
+        )]
+        file: PathBuf,
+    },
+}
+
+impl LabelCommand {
+    pub fn execute<T: WalletClient>(&self, wallet_client: T) -> Result<()> {
+        match self {
+            LabelCommand::Set {
+                name,
+                label_type,
+                reference,
+                label,
+                notes,
+            } => {
+                let label = Label::new(*label_type, reference, label.clone(), notes.clone())?;
+                let enckey = ask_seckey(None)?;
+                wallet_client.set_labels(name, &enckey, vec![label])?;
+                success("Label set");
+                Ok(())
+            }
+            LabelCommand::Remove {
+                name,
+                label_type,
+                reference,
+            } => {
+                let enckey = ask_seckey(None)?;
+                if wallet_client.remove_label(name, &enckey, *label_type, reference)? {
+                    success("Label removed");
+                } else {
+                    success("No label found!");
+                }
+                Ok(())
+            }
+            LabelCommand::List { name, label_type } => list(wallet_client, name, *label_type),
+            LabelCommand::Export { name, file } => {
+                let enckey = ask_seckey(None)?;
+                let labels = wallet_client.labels(name, &enckey)?;
+                let mut file_handle =
+                    File::create(file).chain(|| (ErrorKind::IoError, "Unable to create file"))?;
+                file_handle
+                    .write_all(labels_to_jsonl(&labels)?.as_bytes())
+                    .chain(|| (ErrorKind::IoError, "Unable to write to file"))?;
+                success(&format!("{} labels written to {:?}", labels.len(), file));
+                Ok(())
+            }
+            LabelCommand::Import { name, file } => {
+                let jsonl = std::fs::read_to_string(file)
+                    .chain(|| (ErrorKind::IoError, "Unable to read from file"))?;
+                let labels = labels_from_jsonl(&jsonl)?;
+                let count = labels.len();
+                let enckey = ask_seckey(None)?;
+                wallet_client.set_labels(name, &enckey, labels)?;
+                success(&format!("{} labels imported", count));
+                Ok(())
+            }
+        }
+    }
+}
+
+fn list<T: WalletClient>(
+    wallet_client: T,
+    name: &str,
+    label_type: Option<LabelType>,
+) -> Result<()> {
+    let enckey = ask_seckey(None)?;
+    let labels = wallet_client
+        .labels(name, &enckey)?
+        .into_iter()
+        .filter(|label| label_type.map_or(true, |label_type| label.label_type == label_type))
+        .collect::<Vec<_>>();
+
+    if labels.is_empty() {
+        success("No labels found!");
+        return Ok(());
+    }
+
+    let bold = CellFormat::builder().bold(true).build();
+    let mut rows = vec![Row::new(vec![
+        Cell::new("Type", bold),
+        Cell::new("Reference", bold),
+        Cell::new("Label", bold),
+        Cell::new("Notes", bold),
+    ])];
+    for label in labels {
+        rows.push(Row::new(vec![
+            Cell::new(&label.label_type, Default::default()),
+            Cell::new(&label.reference, Default::default()),
+            Cell::new(&label.label, Default::default()),
+            Cell::new(
+                label.notes.as_ref().unwrap_or(&String::new()),
+                Default::default(),
+            ),
+        ]));
+    }
+
+    let table = Table::new(rows, Default::default())
+        .chain(|| (ErrorKind::InternalError, "Unable to create new table"))?;
+    table
+        .print_stdout()
+        .chain(|| (ErrorKind::IoError, "Unable to print table"))
+}
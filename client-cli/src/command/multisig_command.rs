@@ -1,11 +1,17 @@
+use parity_scale_codec::{Decode, Encode};
 use quest::{ask, success, text};
+use rand::rngs::OsRng;
+use rand::RngCore;
 use std::str::FromStr;
 use structopt::StructOpt;
 
 use super::address_command::ask_public_key;
-use client_common::{ErrorKind, PublicKey, Result, ResultExt};
+use chain_core::tx::data::address::ExtendedAddr;
+use chain_core::tx::data::Tx;
+use chain_core::tx::TransactionId;
+use client_common::{Error, ErrorKind, PublicKey, Result, ResultExt};
 use client_core::types::AddressType;
-use client_core::WalletClient;
+use client_core::{ThresholdWalletClient, WalletClient};
 
 use crate::ask_seckey;
 
@@ -70,10 +76,88 @@ pub enum MultiSigCommand {
         )]
         required_signatures: Option<usize>,
     },
+
+    #[structopt(
+        name = "new-threshold-address",
+        about = "Create a new threshold MultiSig address with distributed key generation (interactive)"
+    )]
+    CreateThresholdAddress {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "public keys",
+            short = "p",
+            long = "public_keys",
+            help = "public keys of all the participants, included self public key, separated by commas"
+        )]
+        public_keys: Option<String>,
+        #[structopt(
+            name = "self public key",
+            short = "s",
+            long = "self_public_key",
+            help = "self public key"
+        )]
+        self_public_key: Option<String>,
+        #[structopt(
+            name = "threshold",
+            short = "t",
+            long = "threshold",
+            help = "the number of participants required to sign"
+        )]
+        threshold: Option<u16>,
+        #[structopt(
+            name = "context",
+            short = "c",
+            long = "context",
+            help = "hex encoded identifier of key generation shared by all the participants (a new one is generated if omitted)"
+        )]
+        context: Option<String>,
+    },
+
+    #[structopt(
+        name = "threshold-sign",
+        about = "Sign and broadcast a transaction spending from a threshold MultiSig address (interactive)"
+    )]
+    ThresholdSign {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "address",
+            short = "a",
+            long = "address",
+            help = "threshold MultiSig address"
+        )]
+        address: String,
+        #[structopt(
+            name = "transaction",
+            short = "t",
+            long = "transaction",
+            help = "JSON encoded unsigned transaction"
+        )]
+        transaction: Option<String>,
+    },
 }
 
 impl MultiSigCommand {
-    pub fn execute<T: WalletClient>(&self, wallet_client: T) -> Result<()> {
+    /// Returns true if this command needs to connect to tendermint (e.g. to broadcast transactions)
+    pub fn requires_network(&self) -> bool {
+        match self {
+            MultiSigCommand::ThresholdSign { .. } => true,
+            _ => false,
+        }
+    }
+
+    pub fn execute<T: ThresholdWalletClient>(&self, wallet_client: T) -> Result<()> {
         match self {
             MultiSigCommand::NewAddressPublicKey { name } => {
                 new_address_public_key(wallet_client, name)
@@ -93,6 +177,25 @@ impl MultiSigCommand {
                 self_public_key,
                 required_signatures,
             ),
+            MultiSigCommand::CreateThresholdAddress {
+                name,
+                public_keys,
+                self_public_key,
+                threshold,
+                context,
+            } => new_threshold_address(
+                wallet_client,
+                name,
+                public_keys,
+                self_public_key,
+                threshold,
+                context,
+            ),
+            MultiSigCommand::ThresholdSign {
+                name,
+                address,
+                transaction,
+            } => threshold_sign(wallet_client, name, address, transaction),
         }
     }
 }
@@ -169,6 +272,175 @@ fn new_multisign_address<T: WalletClient>(
     Ok(())
 }
 
+fn new_threshold_address<T: ThresholdWalletClient>(
+    wallet_client: T,
+    name: &str,
+    public_keys: &Option<String>,
+    self_public_key: &Option<String>,
+    threshold: &Option<u16>,
+    context: &Option<String>,
+) -> Result<()> {
+    let enckey = ask_seckey(None)?;
+    let public_keys_str = match public_keys {
+        None => ask_public_keys(None)?,
+        Some(s) => s.clone(),
+    };
+    let pubkeys = public_keys_str
+        .split(',')
+        .map(|s| PublicKey::from_str(s.trim()))
+        .collect::<Result<Vec<_>>>()
+        .chain(|| (ErrorKind::InvalidInput, "Invalid public key"))?;
+
+    let self_public_key = match self_public_key {
+        None => ask_public_key(Some("input self public key: "))?,
+        Some(p) => PublicKey::from_str(p)?,
+    };
+    let threshold = match threshold {
+        None => ask_required_signature()? as u16,
+        Some(t) => *t,
+    };
+    let context = match context {
+        None => {
+            let mut context = [0u8; 32];
+            OsRng.fill_bytes(&mut context);
+            success(&format!(
+                "Key generation context (share it with all the participants): {}",
+                hex::encode(&context)
+            ));
+            context
+        }
+        Some(c) => parse_hash(c)?,
+    };
+
+    let round1 = wallet_client.new_threshold_key_generation(
+        name,
+        &enckey,
+        context,
+        pubkeys,
+        self_public_key,
+        threshold,
+    )?;
+    success(&format!(
+        "Commitments (send to all the participants): {}",
+        hex::encode(round1.encode())
+    ));
+
+    let mut all_round1 =
+        ask_packages("Enter commitments of other participants (separated by commas): ")?;
+    all_round1.push(round1);
+    let shares =
+        wallet_client.threshold_key_generation_shares(name, &enckey, &context, all_round1)?;
+    for share in shares {
+        success(&format!(
+            "Share for participant {} (send to this participant only): {}",
+            share.receiver,
+            hex::encode(share.encode())
+        ));
+    }
+
+    let shares =
+        ask_packages("Enter shares received from other participants (separated by commas): ")?;
+    let extended_address =
+        wallet_client.finish_threshold_key_generation(name, &enckey, &context, shares)?;
+
+    success(&format!(
+        "Threshold MultiSign address: {}",
+        extended_address
+    ));
+    Ok(())
+}
+
+fn threshold_sign<T: ThresholdWalletClient>(
+    wallet_client: T,
+    name: &str,
+    address: &str,
+    transaction: &Option<String>,
+) -> Result<()> {
+    let enckey = ask_seckey(None)?;
+    let address = address
+        .parse::<ExtendedAddr>()
+        .chain(|| (ErrorKind::InvalidInput, "Invalid address"))?;
+    let transaction = match transaction {
+        None => {
+            ask("Enter unsigned transaction (JSON): ");
+            text().chain(|| (ErrorKind::InvalidInput, "Invalid input"))?
+        }
+        Some(t) => t.clone(),
+    };
+    let transaction: Tx = serde_json::from_str(&transaction).chain(|| {
+        (
+            ErrorKind::DeserializationError,
+            "Invalid unsigned transaction",
+        )
+    })?;
+
+    let (session_id, commitment) =
+        wallet_client.new_threshold_session(name, &enckey, &address, transaction.id())?;
+    success(&format!(
+        "Nonce commitment (send to all the co-signers): {}",
+        hex::encode(commitment.encode())
+    ));
+
+    let commitments =
+        ask_packages("Enter nonce commitments of co-signers (separated by commas): ")?;
+    for commitment in commitments {
+        wallet_client.add_threshold_commitment(name, &enckey, &session_id, commitment)?;
+    }
+
+    let share = wallet_client.threshold_partial_signature(name, &enckey, &session_id)?;
+    success(&format!(
+        "Partial signature (send to all the co-signers): {}",
+        hex::encode(share.encode())
+    ));
+
+    let shares = ask_packages("Enter partial signatures of co-signers (separated by commas): ")?;
+    for share in shares {
+        wallet_client.add_threshold_partial_signature(name, &enckey, &session_id, share)?;
+    }
+
+    let tx_aux = wallet_client.threshold_transaction(name, &enckey, &session_id, transaction)?;
+    wallet_client.broadcast_transaction(&tx_aux)?;
+
+    success(&format!(
+        "Transaction successfully broadcasted: {}",
+        hex::encode(tx_aux.tx_id())
+    ));
+    Ok(())
+}
+
+fn ask_packages<P: Decode>(message: &str) -> Result<Vec<P>> {
+    ask(message);
+    let packages = text().chain(|| (ErrorKind::InvalidInput, "Invalid input"))?;
+
+    packages
+        .split(',')
+        .map(str::trim)
+        .filter(|package| !package.is_empty())
+        .map(|package| {
+            let bytes = hex::decode(package)
+                .chain(|| (ErrorKind::InvalidInput, "Invalid hex encoded package"))?;
+            P::decode(&mut bytes.as_slice())
+                .chain(|| (ErrorKind::DeserializationError, "Invalid package"))
+        })
+        .collect()
+}
+
+fn parse_hash(hash: &str) -> Result<[u8; 32]> {
+    let bytes = hex::decode(hash.trim())
+        .chain(|| (ErrorKind::InvalidInput, "Invalid hex encoded context"))?;
+
+    if bytes.len() != 32 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Context should be a hex string of 32 bytes",
+        ));
+    }
+
+    let mut context = [0u8; 32];
+    context.copy_from_slice(&bytes);
+    Ok(context)
+}
+
 fn ask_required_signature() -> Result<usize> {
     ask("how many signatures required: ");
     let n = text().err_kind(ErrorKind::InvalidInput, || {
@@ -0,0 +1,100 @@
+use cli_table::format::{CellFormat, Justify};
+use cli_table::{Cell, Row, Table};
+use quest::success;
+use structopt::StructOpt;
+
+use client_common::storage::{MigrationAction, MigrationReport};
+use client_common::{ErrorKind, Result, ResultExt, Storage};
+use client_core::service::MigrationService;
+
+use crate::ask_seckey;
+
+#[derive(Debug, StructOpt)]
+pub enum StorageCommand {
+    #[structopt(
+        name = "migrate",
+        about = "Upgrades the stored data to the current schema"
+    )]
+    Migrate {
+        #[structopt(
+            name = "dry-run",
+            long,
+            help = "Only report the changes without writing them"
+        )]
+        dry_run: bool,
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet to upgrade the encrypted data of"
+        )]
+        name: Option<String>,
+    },
+}
+
+impl StorageCommand {
+    pub fn execute<S: Storage>(&self, storage: S) -> Result<()> {
+        match self {
+            StorageCommand::Migrate { dry_run, name } => migrate(storage, *dry_run, name),
+        }
+    }
+}
+
+fn migrate<S: Storage>(storage: S, dry_run: bool, name: &Option<String>) -> Result<()> {
+    let service = MigrationService::new(storage);
+    let report = service.migrate(dry_run)?;
+    display_report(&report)?;
+
+    if let Some(name) = name {
+        let enckey = ask_seckey(None)?;
+        let report = service.migrate_wallet(name, &enckey, dry_run)?;
+        display_report(&report)?;
+    }
+
+    if dry_run {
+        success("Dry run, no change written");
+    } else {
+        success("Storage migrated");
+    }
+    Ok(())
+}
+
+fn display_report(report: &MigrationReport) -> Result<()> {
+    if report.is_empty() {
+        success("No data to migrate");
+        return Ok(());
+    }
+
+    let bold = CellFormat::builder().bold(true).build();
+    let justify_right = CellFormat::builder().justify(Justify::Right).build();
+
+    let mut rows = vec![Row::new(vec![
+        Cell::new("Keyspace", bold),
+        Cell::new("Key", bold),
+        Cell::new("Version", bold),
+        Cell::new("Action", bold),
+        Cell::new("Changes", bold),
+    ])];
+    for change in report.changes.iter() {
+        let action = match change.action {
+            MigrationAction::Upgraded => "upgraded",
+            MigrationAction::Deferred => "deferred (needs wallet enckey)",
+        };
+        rows.push(Row::new(vec![
+            Cell::new(&change.keyspace, Default::default()),
+            Cell::new(&String::from_utf8_lossy(&change.key), Default::default()),
+            Cell::new(
+                &format!("{} -> {}", change.from_version, change.to_version),
+                justify_right,
+            ),
+            Cell::new(action, Default::default()),
+            Cell::new(&change.descriptions.join("; "), Default::default()),
+        ]));
+    }
+
+    let table = Table::new(rows, Default::default())
+        .chain(|| (ErrorKind::InternalError, "Unable to create new table"))?;
+    table
+        .print_stdout()
+        .chain(|| (ErrorKind::IoError, "Unable to print table"))
+}
@@ -21,8 +21,9 @@ use chain_core::tx::data::output::TxOut;
 use chain_core::tx::TxAux;
 use client_common::{Error, ErrorKind, PublicKey, Result, ResultExt, SecKey, Transaction};
 use client_core::transaction_builder::SignedTransferTransaction;
+use client_core::types::label::parse_output_reference;
 use client_core::types::{BalanceChange, TransactionPending};
-use client_core::WalletClient;
+use client_core::{InputSelectionStrategy, WalletClient};
 use client_network::NetworkOpsClient;
 use mls::{Codec, DefaultCipherSuite, KeyPackage};
 
@@ -118,6 +119,14 @@ pub enum TransactionCommand {
             case_insensitive = true
         )]
         keypackage: Option<PathBuf>,
+        #[structopt(
+            name = "inputs",
+            long = "inputs",
+            use_delimiter = true,
+            parse(try_from_str = parse_output_reference),
+            help = "Inputs (comma separated <transaction id>:<output index>) to spend when transaction type is transfer (selected automatically if not given)"
+        )]
+        inputs: Vec<TxoPointer>,
     },
     #[structopt(name = "show", about = "Display details of a transaction")]
     Show {
@@ -255,6 +264,7 @@ impl TransactionCommand {
                 transaction_type,
                 advanced,
                 keypackage,
+                inputs,
             } => new_transaction(
                 wallet_client,
                 network_ops_client,
@@ -262,6 +272,7 @@ impl TransactionCommand {
                 transaction_type,
                 *advanced,
                 keypackage.clone(),
+                inputs,
             ),
             TransactionCommand::Show {
                 name,
@@ -531,6 +542,7 @@ fn new_transaction<T: WalletClient, N: NetworkOpsClient>(
     transaction_type: &TransactionType,
     advanced: bool,
     keypackage: Option<PathBuf>,
+    inputs: &[TxoPointer],
 ) -> Result<()> {
     let can_use_advanced = vec![TransactionType::Deposit];
     if advanced && !can_use_advanced.contains(transaction_type) {
@@ -540,11 +552,18 @@ fn new_transaction<T: WalletClient, N: NetworkOpsClient>(
         );
         return Err(error);
     }
+    if !inputs.is_empty() && *transaction_type != TransactionType::Transfer {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "inputs can only be given when transfer",
+        ));
+    }
     let enckey = ask_seckey(None)?;
 
     match transaction_type {
         TransactionType::Transfer => {
-            let (tx_aux, tx_pending) = new_transfer_transaction(wallet_client, name, &enckey)?;
+            let (tx_aux, tx_pending) =
+                new_transfer_transaction(wallet_client, name, &enckey, inputs)?;
             wallet_client.broadcast_transaction(&tx_aux)?;
             wallet_client.update_tx_pending_state(&name, &enckey, tx_aux.tx_id(), tx_pending)?;
         }
@@ -645,21 +664,22 @@ fn check_staking_address_for_deposit<T: WalletClient, N: NetworkOpsClient>(
     address: &StakedStateAddress,
 ) -> Result<()> {
     // if the to_address belongs to current wallet, we do not check the state
-    match address {
-        StakedStateAddress::BasicRedeem(ref redeem_address) => {
-            // if to_address doesn't belong to current wallet, we check the state
-            if wallet_client
-                .find_staking_key(name, enckey, redeem_address)?
-                .is_none()
-            {
-                let staking = network_ops.get_staked_state(name, address, true).err_kind(ErrorKind::ValidationError,|| "Address not found in the current wallet and is not yet initialized on the blockchain")?;
-                if staking.is_jailed() {
-                    return Err(Error::new(
-                        ErrorKind::ValidationError,
-                        "staking address is jailed",
-                    ));
-                }
-            }
+    let is_own_address = match address {
+        StakedStateAddress::BasicRedeem(ref redeem_address) => wallet_client
+            .find_staking_key(name, enckey, redeem_address)?
+            .is_some(),
+        StakedStateAddress::OrTree(ref root_hash) => wallet_client
+            .find_root_hash(name, enckey, &ExtendedAddr::OrTree(*root_hash))?
+            .is_some(),
+    };
+    // if to_address doesn't belong to current wallet, we check the state
+    if !is_own_address {
+        let staking = network_ops.get_staked_state(name, address, true).err_kind(ErrorKind::ValidationError,|| "Address not found in the current wallet and is not yet initialized on the blockchain")?;
+        if staking.is_jailed() {
+            return Err(Error::new(
+                ErrorKind::ValidationError,
+                "staking address is jailed",
+            ));
         }
     }
     Ok(())
@@ -699,10 +719,10 @@ fn new_deposit_transaction<T: WalletClient, N: NetworkOpsClient>(
     let inputs = ask_inputs()?;
     let to_address = ask_staking_address()?;
     double_confirm_staking_address(wallet_client, network_ops_client, name, enckey, &to_address)?;
-    if !wallet_client.has_unspent_transactions(name, enckey, &inputs)? {
+    if !wallet_client.has_spendable_transactions(name, enckey, &inputs)? {
         return Err(Error::new(
             ErrorKind::InvalidInput,
-            "Given transaction inputs are not present in unspent transactions (synchronizing your wallet may help)",
+            "Given transaction inputs are not present in spendable transactions (they may be frozen, or synchronizing your wallet may help)",
         ));
     }
     let transactions = inputs
@@ -799,6 +819,7 @@ fn new_transfer_transaction<T: WalletClient>(
     wallet_client: &T,
     name: &str,
     enckey: &SecKey,
+    inputs: &[TxoPointer],
 ) -> Result<(TxAux, TransactionPending)> {
     let outputs = ask_outputs()?;
     let mut view_keys = ask_view_keys()?;
@@ -817,12 +838,18 @@ fn new_transfer_transaction<T: WalletClient>(
 
     let return_address = wallet_client.new_transfer_address(name, &enckey)?;
 
+    let input_selection_strategy = if inputs.is_empty() {
+        None
+    } else {
+        Some(InputSelectionStrategy::Manual(inputs.to_vec()))
+    };
+
     let (transaction, used_inputs, return_amount) = wallet_client.create_transaction(
         name,
         &enckey,
         outputs,
         attributes,
-        None,
+        input_selection_strategy,
         return_address,
     )?;
     let tx_pending = TransactionPending {
@@ -0,0 +1,133 @@
+use cli_table::format::{CellFormat, Color, Justify};
+use cli_table::{Cell, Row, Table};
+use quest::success;
+use structopt::StructOpt;
+
+use chain_core::tx::data::input::TxoPointer;
+use client_common::{ErrorKind, Result, ResultExt};
+use client_core::types::label::{output_reference, parse_output_reference};
+use client_core::WalletClient;
+
+use crate::ask_seckey;
+
+#[derive(Debug, StructOpt)]
+pub enum UtxoCommand {
+    #[structopt(
+        name = "list",
+        about = "Lists unspent outputs of wallet (with frozen flags)"
+    )]
+    List {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+    },
+    #[structopt(
+        name = "freeze",
+        about = "Freezes unspent outputs so that they are never selected as inputs"
+    )]
+    Freeze {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "inputs",
+            short = "i",
+            long = "inputs",
+            required = true,
+            use_delimiter = true,
+            parse(try_from_str = parse_output_reference),
+            help = "Outputs to freeze (comma separated <transaction id>:<output index>)"
+        )]
+        inputs: Vec<TxoPointer>,
+    },
+    #[structopt(name = "unfreeze", about = "Unfreezes unspent outputs")]
+    Unfreeze {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "inputs",
+            short = "i",
+            long = "inputs",
+            required = true,
+            use_delimiter = true,
+            parse(try_from_str = parse_output_reference),
+            help = "Outputs to unfreeze (comma separated <transaction id>:<output index>)"
+        )]
+        inputs: Vec<TxoPointer>,
+    },
+}
+
+impl UtxoCommand {
+    pub fn execute<T: WalletClient>(&self, wallet_client: T) -> Result<()> {
+        match self {
+            UtxoCommand::List { name } => list(wallet_client, name),
+            UtxoCommand::Freeze { name, inputs } => {
+                let enckey = ask_seckey(None)?;
+                wallet_client.set_frozen_outputs(name, &enckey, inputs, true)?;
+                success(&format!("{} outputs frozen", inputs.len()));
+                Ok(())
+            }
+            UtxoCommand::Unfreeze { name, inputs } => {
+                let enckey = ask_seckey(None)?;
+                wallet_client.set_frozen_outputs(name, &enckey, inputs, false)?;
+                success(&format!("{} outputs unfrozen", inputs.len()));
+                Ok(())
+            }
+        }
+    }
+}
+
+fn list<T: WalletClient>(wallet_client: T, name: &str) -> Result<()> {
+    let enckey = ask_seckey(None)?;
+    let unspent_transactions = wallet_client.unspent_transactions(name, &enckey)?;
+    let frozen_outputs = wallet_client.frozen_outputs(name, &enckey)?;
+
+    if unspent_transactions.is_empty() {
+        success("No unspent outputs found!");
+        return Ok(());
+    }
+
+    let bold = CellFormat::builder().bold(true).build();
+    let right_justify = CellFormat::builder().justify(Justify::Right).build();
+    let red = CellFormat::builder()
+        .foreground_color(Some(Color::Red))
+        .build();
+
+    let mut rows = vec![Row::new(vec![
+        Cell::new("Output", bold),
+        Cell::new("Address", bold),
+        Cell::new("Amount", bold),
+        Cell::new("Frozen", bold),
+    ])];
+    for (input, output) in unspent_transactions.iter() {
+        let frozen = frozen_outputs.contains(input);
+        rows.push(Row::new(vec![
+            Cell::new(&output_reference(input), Default::default()),
+            Cell::new(&output.address, Default::default()),
+            Cell::new(&output.value, right_justify),
+            Cell::new(
+                if frozen { "yes" } else { "no" },
+                if frozen { red } else { Default::default() },
+            ),
+        ]));
+    }
+
+    let table = Table::new(rows, Default::default())
+        .chain(|| (ErrorKind::InternalError, "Unable to create new table"))?;
+    table
+        .print_stdout()
+        .chain(|| (ErrorKind::IoError, "Unable to print table"))
+}
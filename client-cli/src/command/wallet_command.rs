@@ -6,8 +6,11 @@ use client_common::{Error, ErrorKind, PrivateKey, Result, ResultExt};
 use client_core::types::WalletKind;
 use client_core::{Mnemonic, WalletClient};
 
-use crate::{ask_passphrase, ask_seckey};
-use client_core::service::WalletInfo;
+use crate::{ask_passphrase, ask_seckey, secret_service};
+use client_core::mnemonic::shamir::{
+    recover_mnemonic, split_mnemonic, validate_shares, MnemonicShare,
+};
+use client_core::service::{account_wallet_name, AccountXpubs, WalletInfo};
 use client_core::wallet::WalletRequest;
 use std::fs::File;
 use std::io::Write;
@@ -93,6 +96,39 @@ pub enum WalletCommand {
         )]
         name: String,
     },
+    #[structopt(
+        name = "restore-shamir",
+        about = "Restore HD Wallet from Shamir backup shares"
+    )]
+    RestoreShamir {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+    },
+    #[structopt(
+        name = "shamir-split",
+        about = "Split mnemonic into Shamir backup shares"
+    )]
+    ShamirSplit {
+        #[structopt(
+            name = "threshold",
+            short = "t",
+            long = "threshold",
+            help = "Number of shares needed for recovery"
+        )]
+        threshold: u8,
+        #[structopt(name = "count", short = "c", long = "count", help = "Number of shares")]
+        count: u8,
+    },
+    #[structopt(
+        name = "shamir-validate",
+        about = "Validate Shamir backup shares without recovering the mnemonic"
+    )]
+    ShamirValidate,
     #[structopt(name = "restore-basic", about = "Restore watch-only Wallet")]
     RestoreBasic {
         #[structopt(
@@ -103,6 +139,112 @@ pub enum WalletCommand {
         )]
         name: String,
     },
+    #[structopt(
+        name = "restore-xpub",
+        about = "Restore watch-only Wallet from account extended public keys"
+    )]
+    RestoreXpub {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "transfer xpub",
+            long = "transfer-xpub",
+            help = "Extended public key of the transfer account"
+        )]
+        transfer: String,
+        #[structopt(
+            name = "staking xpub",
+            long = "staking-xpub",
+            help = "Extended public key of the staking account"
+        )]
+        staking: String,
+    },
+    #[structopt(
+        name = "export-xpub",
+        about = "Export account extended public keys of HD wallet"
+    )]
+    ExportXpub {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "account",
+            short = "a",
+            long = "account",
+            default_value = "0",
+            help = "Account of HD wallet"
+        )]
+        account: u32,
+    },
+    #[structopt(
+        name = "backup",
+        about = "Backup all the data of a wallet to an encrypted file"
+    )]
+    Backup {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "file",
+            short = "f",
+            long = "file",
+            parse(from_os_str),
+            help = "File to write the backup to"
+        )]
+        file: PathBuf,
+    },
+    #[structopt(
+        name = "restore-backup",
+        about = "Restore a wallet from an encrypted backup file"
+    )]
+    RestoreBackup {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+        #[structopt(
+            name = "file",
+            short = "f",
+            long = "file",
+            parse(from_os_str),
+            help = "Backup file"
+        )]
+        file: PathBuf,
+    },
+    #[structopt(
+        name = "unlock",
+        about = "Store the enckey of a wallet in the secret service and get a handle to use as authentication token"
+    )]
+    Unlock {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+    },
+    #[structopt(
+        name = "lock",
+        about = "Remove an authentication token handle from the secret service"
+    )]
+    Lock,
     #[structopt(name = "auth-token", about = "Get authentication token")]
     AuthToken {
         #[structopt(
@@ -113,6 +255,26 @@ pub enum WalletCommand {
         )]
         name: String,
     },
+    #[structopt(name = "new-account", about = "Create a new account in HD wallet")]
+    NewAccount {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+    },
+    #[structopt(name = "list-accounts", about = "List all accounts of HD wallet")]
+    ListAccounts {
+        #[structopt(
+            name = "wallet name",
+            short = "n",
+            long = "name",
+            help = "Name of wallet"
+        )]
+        name: String,
+    },
     #[structopt(name = "delete", about = "Delete wallet")]
     Delete {
         #[structopt(
@@ -135,8 +297,31 @@ impl WalletCommand {
             } => Self::new_wallet(wallet_client, name, *wallet_type, *mnemonics_word_count),
             WalletCommand::List => Self::list_wallets(wallet_client),
             WalletCommand::Restore { name } => Self::restore_wallet(wallet_client, name),
+            WalletCommand::RestoreShamir { name } => {
+                Self::restore_shamir_wallet(wallet_client, name)
+            }
+            WalletCommand::ShamirSplit { threshold, count } => {
+                Self::shamir_split(*threshold, *count)
+            }
+            WalletCommand::ShamirValidate => Self::shamir_validate(),
             WalletCommand::RestoreBasic { name } => Self::restore_basic_wallet(wallet_client, name),
+            WalletCommand::RestoreXpub {
+                name,
+                transfer,
+                staking,
+            } => Self::restore_xpub_wallet(wallet_client, name, transfer, staking),
+            WalletCommand::ExportXpub { name, account } => {
+                Self::export_xpub(wallet_client, name, *account)
+            }
+            WalletCommand::Backup { name, file } => Self::backup(wallet_client, name, file),
+            WalletCommand::RestoreBackup { name, file } => {
+                Self::restore_backup(wallet_client, name, file)
+            }
+            WalletCommand::Unlock { name } => Self::unlock(wallet_client, name),
+            WalletCommand::Lock => Self::lock(),
             WalletCommand::AuthToken { name } => Self::auth_token(wallet_client, name),
+            WalletCommand::NewAccount { name } => Self::new_account(wallet_client, name),
+            WalletCommand::ListAccounts { name } => Self::list_accounts(wallet_client, name),
             WalletCommand::Delete { name } => Self::delete(wallet_client, name),
             WalletCommand::Export {
                 name,
@@ -321,6 +506,74 @@ impl WalletCommand {
         Ok(())
     }
 
+    fn restore_shamir_wallet<T: WalletClient>(wallet_client: T, name: &str) -> Result<()> {
+        let passphrase = ask_passphrase(None)?;
+        let confirmed_passphrase = ask_passphrase(Some("Confirm passphrase: "))?;
+
+        if passphrase != confirmed_passphrase {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Passphrases do not match",
+            ));
+        }
+
+        let shares = ask_shares()?;
+        let mnemonic = recover_mnemonic(&shares)?;
+        let enckey = wallet_client.restore_wallet(name, &passphrase, &mnemonic)?;
+
+        mnemonic.zeroize();
+
+        success(&format!(
+            "Authentication token: {}",
+            &hex::encode(enckey.unsecure())
+        ));
+        Ok(())
+    }
+
+    fn shamir_split(threshold: u8, count: u8) -> Result<()> {
+        let mnemonic = ask_mnemonic(None)?;
+        let confirmed_mnemonic = ask_mnemonic(Some("Confirm mnemonic: "))?;
+
+        if mnemonic.as_ref() != confirmed_mnemonic.as_ref() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Mnemonics do not match",
+            ));
+        }
+
+        let shares = split_mnemonic(&mnemonic, threshold, count)?;
+        mnemonic.zeroize();
+
+        ask(&format!(
+            "Any {} of the following {} shares recover the mnemonic, store them at different locations:",
+            threshold, count
+        ));
+        println!();
+        for share in shares.iter() {
+            ask(&format!("Share {}: ", share.member));
+            success(share.phrase().unsecure());
+        }
+        Ok(())
+    }
+
+    fn shamir_validate() -> Result<()> {
+        let shares = ask_shares()?;
+        let info = validate_shares(&shares)?;
+        success(&format!(
+            "{} shares of backup {:04x} ({} needed for recovery)",
+            info.share_count, info.identifier, info.threshold
+        ));
+        if info.recoverable {
+            success("Shares are enough to recover the mnemonic");
+        } else {
+            success(&format!(
+                "{} more shares are needed to recover the mnemonic",
+                info.threshold - info.share_count
+            ));
+        }
+        Ok(())
+    }
+
     fn restore_basic_wallet<T: WalletClient>(wallet_client: T, name: &str) -> Result<()> {
         let passphrase = ask_passphrase(None)?;
         let confirmed_passphrase = ask_passphrase(Some("Confirm passphrase: "))?;
@@ -343,6 +596,74 @@ impl WalletCommand {
         Ok(())
     }
 
+    fn restore_xpub_wallet<T: WalletClient>(
+        wallet_client: T,
+        name: &str,
+        transfer: &str,
+        staking: &str,
+    ) -> Result<()> {
+        let passphrase = ask_passphrase(None)?;
+        let confirmed_passphrase = ask_passphrase(Some("Confirm passphrase: "))?;
+
+        if passphrase != confirmed_passphrase {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Passphrases do not match",
+            ));
+        }
+
+        let xpubs = AccountXpubs {
+            transfer: transfer.to_owned(),
+            staking: staking.to_owned(),
+        };
+        let private_view_key = ask_private_view_key()?;
+
+        let enckey =
+            wallet_client.restore_xpub_wallet(name, &passphrase, &xpubs, &private_view_key)?;
+
+        success(&format!(
+            "Authentication token: {}",
+            &hex::encode(enckey.unsecure())
+        ));
+        Ok(())
+    }
+
+    fn export_xpub<T: WalletClient>(wallet_client: T, name: &str, account: u32) -> Result<()> {
+        let enckey = ask_seckey(None)?;
+        let xpubs = wallet_client.export_xpubs(&account_wallet_name(name, account), &enckey)?;
+        ask("Transfer xpub: ");
+        success(&xpubs.transfer);
+        ask("Staking xpub: ");
+        success(&xpubs.staking);
+        Ok(())
+    }
+
+    fn backup<T: WalletClient>(wallet_client: T, name: &str, file: &PathBuf) -> Result<()> {
+        let enckey = ask_seckey(None)?;
+        let backup = wallet_client.backup_wallet(name, &enckey)?;
+        let mut file_handle =
+            File::create(file).chain(|| (ErrorKind::IoError, "Unable to create file"))?;
+        file_handle
+            .write_all(&backup)
+            .chain(|| (ErrorKind::IoError, "Unable to write to file"))?;
+        success(&format!("Backup of wallet {} written to {:?}", name, file));
+        Ok(())
+    }
+
+    fn restore_backup<T: WalletClient>(wallet_client: T, name: &str, file: &PathBuf) -> Result<()> {
+        let backup =
+            std::fs::read(file).chain(|| (ErrorKind::IoError, "Unable to read from file"))?;
+        let passphrase = ask_passphrase(None)?;
+        let enckey = wallet_client.restore_wallet_backup(name, &passphrase, &backup)?;
+
+        success(&format!("Wallet {} restored from backup", name));
+        success(&format!(
+            "Authentication token: {}",
+            &hex::encode(enckey.unsecure())
+        ));
+        Ok(())
+    }
+
     fn list_wallets<T: WalletClient>(wallet_client: T) -> Result<()> {
         let wallets = wallet_client.wallets()?;
 
@@ -368,6 +689,63 @@ impl WalletCommand {
         Ok(())
     }
 
+    fn unlock<T: WalletClient>(wallet_client: T, name: &str) -> Result<()> {
+        let secret_service = secret_service().chain(|| {
+            (
+                ErrorKind::InvalidInput,
+                "CRYPTO_CLIENT_SECRET_SERVICE environment variable is not set",
+            )
+        })?;
+        let passphrase = ask_passphrase(None)?;
+        let enckey = wallet_client.auth_token(name, &passphrase)?;
+        let handle = secret_service.store(&enckey)?;
+        success(&format!(
+            "Authentication token: {}",
+            &hex::encode(handle.unsecure())
+        ));
+        Ok(())
+    }
+
+    fn lock() -> Result<()> {
+        let secret_service = secret_service().chain(|| {
+            (
+                ErrorKind::InvalidInput,
+                "CRYPTO_CLIENT_SECRET_SERVICE environment variable is not set",
+            )
+        })?;
+        let handle = ask_seckey(None)?;
+        if secret_service.remove(&handle)? {
+            success("Authentication token removed from secret service");
+        } else {
+            success("Authentication token not found in secret service");
+        }
+        Ok(())
+    }
+
+    fn new_account<T: WalletClient>(wallet_client: T, name: &str) -> Result<()> {
+        let enckey = ask_seckey(None)?;
+        let account = wallet_client.new_account(name, &enckey)?;
+        success(&format!(
+            "Account {} created, it can be used as wallet {}",
+            account,
+            account_wallet_name(name, account)
+        ));
+        Ok(())
+    }
+
+    fn list_accounts<T: WalletClient>(wallet_client: T, name: &str) -> Result<()> {
+        let enckey = ask_seckey(None)?;
+        for account in wallet_client.accounts(name, &enckey)? {
+            ask("Account: ");
+            success(&format!(
+                "{} (wallet {})",
+                account,
+                account_wallet_name(name, account)
+            ));
+        }
+        Ok(())
+    }
+
     fn delete<T: WalletClient>(wallet_client: T, name: &str) -> Result<()> {
         let passphrase = ask_passphrase(None)?;
         wallet_client.delete_wallet(name, &passphrase)?;
@@ -382,6 +760,21 @@ fn ask_mnemonic(message: Option<&str>) -> Result<Mnemonic> {
     Mnemonic::from_secstr(&mnemonic)
 }
 
+fn ask_shares() -> Result<Vec<MnemonicShare>> {
+    let mut shares = Vec::new();
+    loop {
+        ask(&format!(
+            "Enter share {} (empty to finish): ",
+            shares.len() + 1
+        ));
+        let share = SecUtf8::from(text().chain(|| (ErrorKind::IoError, "Unable to read share"))?);
+        if share.unsecure().trim().is_empty() {
+            return Ok(shares);
+        }
+        shares.push(MnemonicShare::from_secstr(&share)?);
+    }
+}
+
 fn ask_private_view_key() -> Result<PrivateKey> {
     ask("Enter private view key: ");
 
@@ -9,10 +9,13 @@ use secstr::SecUtf8;
 use structopt::StructOpt;
 
 use chain_core::init::{coin::Coin, network::init_chain_id};
+use client_common::secret_service::SecretServiceClient;
+use client_common::storage::{ClientStorage, SecretServiceStorage, StorageBackend};
 use client_common::{seckey::parse_hex_enckey, Error, ErrorKind, Result, ResultExt, SecKey};
 
 use crate::command::Command;
 use client_core::hd_wallet::HardwareKind;
+use client_core::service::MigrationService;
 
 fn main() {
     env_logger::init();
@@ -50,6 +53,49 @@ pub(crate) fn storage_path() -> String {
     std::env::var("CRYPTO_CLIENT_STORAGE").unwrap_or_else(|_| ".storage".to_owned())
 }
 
+#[inline]
+pub(crate) fn storage_backend() -> Result<StorageBackend> {
+    std::env::var("CRYPTO_CLIENT_STORAGE_BACKEND")
+        .map(|backend| backend.parse())
+        .unwrap_or_else(|_| Ok(StorageBackend::default()))
+}
+
+#[inline]
+pub(crate) fn secret_service() -> Option<SecretServiceClient> {
+    std::env::var("CRYPTO_CLIENT_SECRET_SERVICE")
+        .ok()
+        .map(SecretServiceClient::new)
+}
+
+/// Storage of the client (enckeys are resolved by the secret service if configured)
+pub(crate) type AppStorage = SecretServiceStorage<ClientStorage>;
+
+/// Opens the storage and upgrades its unencrypted data to the current schema
+pub(crate) fn open_storage() -> Result<AppStorage> {
+    let storage = open_storage_unmigrated()?;
+    let report = MigrationService::new(storage.clone()).migrate(false)?;
+    for change in report.changes {
+        log::info!(
+            "{:?} {} from version {} to {}",
+            change.action,
+            change.keyspace,
+            change.from_version,
+            change.to_version
+        );
+    }
+    Ok(storage)
+}
+
+/// Opens the storage without upgrading its schema
+pub(crate) fn open_storage_unmigrated() -> Result<AppStorage> {
+    let storage =
+        SecretServiceStorage::new(ClientStorage::open(storage_backend()?, storage_path())?);
+    match secret_service() {
+        Some(secret_service) => Ok(storage.with_secret_service(secret_service)),
+        None => Ok(storage),
+    }
+}
+
 #[inline]
 pub(crate) fn tendermint_url() -> String {
     std::env::var("CRYPTO_CLIENT_TENDERMINT")
@@ -76,9 +122,9 @@ pub(crate) fn ask_seckey(message: Option<&str>) -> Result<SecKey> {
 
 pub(crate) fn ask_hardware_kind(message: Option<&str>) -> Result<HardwareKind> {
     let s = if cfg!(feature = "mock-hardware-wallet") {
-        "Which hardware wallet type do you have: ledger|trezor|mock: "
+        "Which hardware wallet type do you have: ledger|trezor|remote|mock: "
     } else {
-        "Which hardware wallet type do you have: ledger|trezor: "
+        "Which hardware wallet type do you have: ledger|trezor|remote: "
     };
     ask(message.unwrap_or(s));
     let key = text().err_kind(ErrorKind::InvalidInput, || {
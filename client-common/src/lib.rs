@@ -12,8 +12,10 @@ pub mod error;
 pub mod key;
 pub mod multi_sig_address;
 pub mod seckey;
+pub mod secret_service;
 pub mod storage;
 pub mod tendermint;
+pub mod threshold;
 
 #[doc(inline)]
 pub use crate::cipher::TransactionObfuscation;
@@ -3,6 +3,8 @@ use std::str::FromStr;
 
 use aes::{Aes256, NewBlockCipher};
 use aes_gcm_siv::aead::generic_array::GenericArray;
+use rand::rngs::OsRng;
+use rand::Rng;
 use secstr::{SecBox, SecUtf8};
 use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 use zeroize::Zeroize;
@@ -23,6 +25,14 @@ impl SecKey {
     pub fn unsecure(&self) -> &GenericArray<u8, SecKeySize> {
         self.0.unsecure()
     }
+
+    /// generates a random key starting with given prefix (e.g. a handle to a key kept elsewhere)
+    pub fn random_with_prefix(prefix: &[u8]) -> Self {
+        let mut arr = GenericArray::clone_from_slice(&[0; 32]);
+        arr[..prefix.len()].copy_from_slice(prefix);
+        OsRng.fill(&mut arr.as_mut_slice()[prefix.len()..]);
+        SecKey(SecBox::new(Box::new(arr)))
+    }
 }
 
 impl FromStr for SecKey {
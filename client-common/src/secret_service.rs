@@ -0,0 +1,388 @@
+//! Local secret service keeping wallet enckeys out of the wallet process
+//!
+//! The service runs as a separate process listening on a Unix socket (only accessible by its
+//! owner). Enckeys are stored in the memory of the service, and callers get a random handle in
+//! exchange which they can use as authentication token instead of the enckey. The handle is
+//! resolved by [`SecretServiceStorage`](crate::storage::SecretServiceStorage) and revoked by
+//! removing it from the service.
+//!
+//! Handles start with a fixed tag, so that they are told apart from enckeys (a derived enckey
+//! starts with the tag with a probability of 2^-64). Clients cache the resolved enckeys for
+//! [`CACHE_TTL`], so a handle revoked through another client stays usable by a client at most
+//! that long.
+//!
+//! # Protocol
+//!
+//! One JSON request per connection, terminated by a new line, answered by one JSON response:
+//!
+//! - `{"method":"store","params":{"enckey":"<hex>"}}` -> `{"result":"handle","value":"<hex>"}`
+//! - `{"method":"get","params":{"handle":"<hex>"}}` -> `{"result":"enckey","value":"<hex>"}` or
+//!   `{"result":"not_found"}`
+//! - `{"method":"remove","params":{"handle":"<hex>"}}` -> `{"result":"removed","value":<bool>}`
+//!
+//! The service is only available on Unix platforms.
+use std::collections::HashMap;
+#[cfg(unix)]
+use std::fs;
+#[cfg(unix)]
+use std::io::{BufRead, BufReader, Write};
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+#[cfg(unix)]
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(unix)]
+use crate::ResultExt;
+use crate::{Error, ErrorKind, Result, SecKey};
+
+/// Tag at the start of the handles
+const HANDLE_TAG: &[u8; 8] = b"CROHNDL\x01";
+
+/// How long clients keep the resolved enckeys
+pub const CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Returns `true` if the key is a handle of a secret service (rather than an enckey)
+#[inline]
+pub fn is_handle(key: &SecKey) -> bool {
+    key.unsecure().starts_with(HANDLE_TAG)
+}
+
+/// Request to the secret service
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+enum SecretRequest {
+    Store { enckey: SecKey },
+    Get { handle: SecKey },
+    Remove { handle: SecKey },
+}
+
+/// Response of the secret service
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "result", content = "value", rename_all = "snake_case")]
+enum SecretResponse {
+    Handle(SecKey),
+    Enckey(SecKey),
+    NotFound,
+    Removed(bool),
+    Error(String),
+}
+
+/// resolved enckeys of handles (with the time they were resolved)
+type EnckeyCache = HashMap<Vec<u8>, (SecKey, Instant)>;
+
+/// Client of a local secret service (clones share the cache of resolved enckeys)
+#[derive(Debug, Clone)]
+pub struct SecretServiceClient {
+    socket_path: PathBuf,
+    cache: Arc<Mutex<EnckeyCache>>,
+}
+
+impl SecretServiceClient {
+    /// Creates a new client of the secret service listening on given socket
+    pub fn new<P: AsRef<Path>>(socket_path: P) -> Self {
+        Self {
+            socket_path: socket_path.as_ref().to_owned(),
+            cache: Default::default(),
+        }
+    }
+
+    /// Stores an enckey in the service and returns its handle
+    pub fn store(&self, enckey: &SecKey) -> Result<SecKey> {
+        match self.request(&SecretRequest::Store {
+            enckey: enckey.clone(),
+        })? {
+            SecretResponse::Handle(handle) => {
+                self.cache_enckey(&handle, enckey)?;
+                Ok(handle)
+            }
+            response => Err(unexpected_response(response)),
+        }
+    }
+
+    /// Returns the enckey of a handle (if the handle is known to the service)
+    pub fn get(&self, handle: &SecKey) -> Result<Option<SecKey>> {
+        if let Some((enckey, cached_at)) = self.lock_cache()?.get(handle.unsecure().as_slice()) {
+            if cached_at.elapsed() < CACHE_TTL {
+                return Ok(Some(enckey.clone()));
+            }
+        }
+        match self.request(&SecretRequest::Get {
+            handle: handle.clone(),
+        })? {
+            SecretResponse::Enckey(enckey) => {
+                self.cache_enckey(handle, &enckey)?;
+                Ok(Some(enckey))
+            }
+            SecretResponse::NotFound => {
+                self.lock_cache()?.remove(handle.unsecure().as_slice());
+                Ok(None)
+            }
+            response => Err(unexpected_response(response)),
+        }
+    }
+
+    /// Revokes a handle, returns `false` if the handle was unknown
+    pub fn remove(&self, handle: &SecKey) -> Result<bool> {
+        self.lock_cache()?.remove(handle.unsecure().as_slice());
+        match self.request(&SecretRequest::Remove {
+            handle: handle.clone(),
+        })? {
+            SecretResponse::Removed(removed) => Ok(removed),
+            response => Err(unexpected_response(response)),
+        }
+    }
+
+    fn cache_enckey(&self, handle: &SecKey, enckey: &SecKey) -> Result<()> {
+        let mut cache = self.lock_cache()?;
+        cache.retain(|_, (_, cached_at)| cached_at.elapsed() < CACHE_TTL);
+        cache.insert(handle.unsecure().to_vec(), (enckey.clone(), Instant::now()));
+        Ok(())
+    }
+
+    fn lock_cache(&self) -> Result<MutexGuard<EnckeyCache>> {
+        self.cache.lock().map_err(|_| {
+            Error::new(
+                ErrorKind::InternalError,
+                "Unable to acquire lock on secret service cache",
+            )
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn request(&self, _request: &SecretRequest) -> Result<SecretResponse> {
+        Err(Error::new(
+            ErrorKind::InitializationError,
+            "Secret service is only supported on Unix platforms",
+        ))
+    }
+
+    #[cfg(unix)]
+    fn request(&self, request: &SecretRequest) -> Result<SecretResponse> {
+        let mut stream = UnixStream::connect(&self.socket_path).chain(|| {
+            (
+                ErrorKind::ConnectionError,
+                format!(
+                    "Unable to connect to secret service at {}",
+                    self.socket_path.display()
+                ),
+            )
+        })?;
+        let mut line = serde_json::to_string(request).chain(|| {
+            (
+                ErrorKind::SerializationError,
+                "Unable to serialize secret service request",
+            )
+        })?;
+        line.push('\n');
+        stream.write_all(line.as_bytes()).chain(|| {
+            (
+                ErrorKind::IoError,
+                "Unable to send request to secret service",
+            )
+        })?;
+
+        let mut response = String::new();
+        BufReader::new(stream)
+            .read_line(&mut response)
+            .chain(|| (ErrorKind::IoError, "Unable to read secret service response"))?;
+        serde_json::from_str(&response).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Unable to deserialize secret service response",
+            )
+        })
+    }
+}
+
+/// Binds the socket in a private directory, restricts its permissions and moves it to the
+/// final path (replacing an existing socket file)
+#[cfg(unix)]
+fn bind_private(private_path: &Path, socket_path: &Path) -> Result<UnixListener> {
+    let listener = UnixListener::bind(private_path).chain(|| {
+        (
+            ErrorKind::InitializationError,
+            format!("Unable to listen on socket {}", socket_path.display()),
+        )
+    })?;
+    fs::set_permissions(private_path, fs::Permissions::from_mode(0o600)).chain(|| {
+        (
+            ErrorKind::InitializationError,
+            format!(
+                "Unable to restrict permissions of socket {}",
+                socket_path.display()
+            ),
+        )
+    })?;
+    fs::rename(private_path, socket_path).chain(|| {
+        (
+            ErrorKind::InitializationError,
+            format!("Unable to move socket to {}", socket_path.display()),
+        )
+    })?;
+    Ok(listener)
+}
+
+fn unexpected_response(response: SecretResponse) -> Error {
+    match response {
+        SecretResponse::Error(message) => Error::new(
+            ErrorKind::InternalError,
+            format!("Secret service error: {}", message),
+        ),
+        _ => Error::new(
+            ErrorKind::InternalError,
+            "Unexpected response from secret service",
+        ),
+    }
+}
+
+/// Secret service keeping enckeys in memory
+#[cfg(unix)]
+#[derive(Debug, Default, Clone)]
+pub struct SecretService {
+    enckeys: Arc<Mutex<HashMap<Vec<u8>, SecKey>>>,
+}
+
+#[cfg(unix)]
+impl SecretService {
+    /// Listens on given Unix socket and serves requests until the process is stopped (an existing
+    /// socket file at the path is replaced)
+    pub fn serve<P: AsRef<Path>>(&self, socket_path: P) -> Result<()> {
+        let listener = self.bind(socket_path)?;
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let service = self.clone();
+                    thread::spawn(move || {
+                        if let Err(e) = service.handle_connection(stream) {
+                            log::warn!("secret service connection failed: {}", e);
+                        }
+                    });
+                }
+                Err(e) => log::warn!("secret service connection failed: {}", e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Binds the socket of the service (only accessible by the owner of the process)
+    ///
+    /// The socket is created in a new directory only accessible by the owner and moved to the
+    /// given path after its permissions are restricted, so others can't connect in the meantime.
+    pub fn bind<P: AsRef<Path>>(&self, socket_path: P) -> Result<UnixListener> {
+        let socket_path = socket_path.as_ref();
+        let private_dir = socket_path
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join(format!(".secret-service-{}", uuid::Uuid::new_v4()));
+        fs::DirBuilder::new()
+            .mode(0o700)
+            .create(&private_dir)
+            .chain(|| {
+                (
+                    ErrorKind::InitializationError,
+                    format!("Unable to create directory {}", private_dir.display()),
+                )
+            })?;
+        let result = bind_private(&private_dir.join("socket"), socket_path);
+        let _ = fs::remove_dir_all(&private_dir);
+        result
+    }
+
+    fn handle_connection(&self, stream: UnixStream) -> Result<()> {
+        let mut reader = BufReader::new(
+            stream
+                .try_clone()
+                .chain(|| (ErrorKind::IoError, "Unable to clone socket"))?,
+        );
+        let mut line = String::new();
+        reader
+            .read_line(&mut line)
+            .chain(|| (ErrorKind::IoError, "Unable to read secret service request"))?;
+
+        let response = match serde_json::from_str(&line) {
+            Ok(request) => self
+                .handle(request)
+                .unwrap_or_else(|e| SecretResponse::Error(e.to_string())),
+            Err(_) => SecretResponse::Error("invalid request".to_owned()),
+        };
+        let mut response = serde_json::to_string(&response).chain(|| {
+            (
+                ErrorKind::SerializationError,
+                "Unable to serialize secret service response",
+            )
+        })?;
+        response.push('\n');
+        (&stream)
+            .write_all(response.as_bytes())
+            .chain(|| (ErrorKind::IoError, "Unable to send secret service response"))
+    }
+
+    fn handle(&self, request: SecretRequest) -> Result<SecretResponse> {
+        let mut enckeys = self.enckeys.lock().map_err(|_| {
+            Error::new(
+                ErrorKind::InternalError,
+                "Unable to acquire lock on secret service",
+            )
+        })?;
+        let response = match request {
+            SecretRequest::Store { enckey } => {
+                let handle = SecKey::random_with_prefix(HANDLE_TAG);
+                enckeys.insert(handle.unsecure().to_vec(), enckey);
+                SecretResponse::Handle(handle)
+            }
+            SecretRequest::Get { handle } => match enckeys.get(handle.unsecure().as_slice()) {
+                Some(enckey) => SecretResponse::Enckey(enckey.clone()),
+                None => SecretResponse::NotFound,
+            },
+            SecretRequest::Remove { handle } => {
+                SecretResponse::Removed(enckeys.remove(handle.unsecure().as_slice()).is_some())
+            }
+        };
+        Ok(response)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::seckey::derive_enckey;
+    use secstr::SecUtf8;
+
+    #[test]
+    fn check_secret_service() {
+        let socket_path =
+            std::env::temp_dir().join(format!("secret-service-{}.sock", uuid::Uuid::new_v4()));
+        let service = SecretService::default();
+        let listener = service.bind(&socket_path).unwrap();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                service.handle_connection(stream.unwrap()).unwrap();
+            }
+        });
+
+        let client = SecretServiceClient::new(&socket_path);
+        let enckey = derive_enckey(&SecUtf8::from("passphrase"), "name").unwrap();
+        let handle = client.store(&enckey).unwrap();
+        assert_ne!(handle, enckey);
+        assert!(is_handle(&handle));
+        assert!(!is_handle(&enckey));
+        assert_eq!(client.get(&handle).unwrap(), Some(enckey.clone()));
+        assert_eq!(client.get(&enckey).unwrap(), None);
+        // resolved by the service for other clients
+        let other = SecretServiceClient::new(&socket_path);
+        assert_eq!(other.get(&handle).unwrap(), Some(enckey.clone()));
+        assert!(client.remove(&handle).unwrap());
+        assert!(!client.remove(&handle).unwrap());
+        assert_eq!(client.get(&handle).unwrap(), None);
+        let mode = fs::metadata(&socket_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        std::fs::remove_file(socket_path).unwrap();
+    }
+}
@@ -1,13 +1,28 @@
 //! Data storage layer
+mod client_storage;
 mod memory_storage;
+mod migration;
+mod secret_service_storage;
 #[cfg(feature = "sled")]
 mod sled_storage;
+#[cfg(feature = "sqlite")]
+mod sqlite_storage;
 mod unauthorized_storage;
+use std::borrow::Cow;
+
 use parity_scale_codec::{Decode, Encode};
 
+pub use client_storage::{ClientStorage, StorageBackend};
 pub use memory_storage::MemoryStorage;
+pub use migration::{
+    value_record_key, Migration, MigrationAction, MigrationChange, MigrationRegistry,
+    MigrationReport, UpgradeFn, SCHEMA_KEYSPACE,
+};
+pub use secret_service_storage::SecretServiceStorage;
 #[cfg(feature = "sled")]
 pub use sled_storage::SledStorage;
+#[cfg(feature = "sqlite")]
+pub use sqlite_storage::SqliteStorage;
 pub use unauthorized_storage::UnauthorizedStorage;
 
 use crate::SecKey;
@@ -82,6 +97,12 @@ pub trait Storage: Send + Sync + Clone {
 
     /// flush db
     fn flush(&self) -> Result<()>;
+
+    /// Returns the enckey used to encrypt values for given caller provided enckey (storages keeping
+    /// the enckeys out of process resolve the handle given to callers here)
+    fn resolve_enckey<'a>(&self, enckey: &'a SecKey) -> Result<Cow<'a, SecKey>> {
+        Ok(Cow::Borrowed(enckey))
+    }
 }
 
 /// Interface for a generic key-value storage (with encryption)
@@ -158,8 +179,9 @@ where
         key: K,
         enckey: &SecKey,
     ) -> Result<Option<Vec<u8>>> {
+        let enckey = self.resolve_enckey(enckey)?;
         self.get(keyspace, &key)?
-            .map(|value| decrypt_bytes(&key, enckey, &value))
+            .map(|value| decrypt_bytes(&key, &enckey, &value))
             .transpose()
     }
 
@@ -170,9 +192,13 @@ where
         value: Vec<u8>,
         enckey: &SecKey,
     ) -> Result<Option<Vec<u8>>> {
-        let old_value = self.get_secure(&keyspace, &key, enckey)?;
+        let enckey = self.resolve_enckey(enckey)?;
+        let old_value = self
+            .get(&keyspace, &key)?
+            .map(|old_value| decrypt_bytes(&key, &enckey, &old_value))
+            .transpose()?;
 
-        let cipher = encrypt_bytes(&key, enckey, &value)?;
+        let cipher = encrypt_bytes(&key, &enckey, &value)?;
         self.set(keyspace, &key, cipher)?;
 
         Ok(old_value)
@@ -190,9 +216,10 @@ where
         K: AsRef<[u8]>,
         F: Fn(Option<&[u8]>) -> Result<Option<Vec<u8>>>,
     {
+        let enckey = self.resolve_enckey(enckey)?;
         self.fetch_and_update(keyspace, &key, |current| {
             let opened = current
-                .map(|current| decrypt_bytes(&key, enckey, current))
+                .map(|current| decrypt_bytes(&key, &enckey, current))
                 .transpose()
                 .chain(|| {
                     (
@@ -204,7 +231,7 @@ where
             let next = f(opened.as_ref().map(AsRef::as_ref))?;
 
             next.as_ref()
-                .map(|next| encrypt_bytes(&key, enckey, next))
+                .map(|next| encrypt_bytes(&key, &enckey, next))
                 .transpose()
         })
     }
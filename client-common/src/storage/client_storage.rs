@@ -0,0 +1,164 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+#[cfg(feature = "sled")]
+use crate::storage::SledStorage;
+#[cfg(feature = "sqlite")]
+use crate::storage::SqliteStorage;
+use crate::storage::Storage;
+#[cfg(feature = "sqlite")]
+use crate::ResultExt;
+use crate::{Error, ErrorKind, Result, SecKey};
+
+/// File name of the database in the storage directory of SQLite backend
+#[cfg(feature = "sqlite")]
+const SQLITE_FILE_NAME: &str = "wallet.sqlite";
+
+/// Storage backend selected at runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageBackend {
+    /// sled database
+    Sled,
+    /// SQLite database
+    Sqlite,
+}
+
+impl Default for StorageBackend {
+    fn default() -> Self {
+        StorageBackend::Sled
+    }
+}
+
+impl fmt::Display for StorageBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageBackend::Sled => write!(f, "sled"),
+            StorageBackend::Sqlite => write!(f, "sqlite"),
+        }
+    }
+}
+
+impl FromStr for StorageBackend {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "sled" => Ok(StorageBackend::Sled),
+            "sqlite" => Ok(StorageBackend::Sqlite),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Unknown storage backend: {} (sled|sqlite)", s),
+            )),
+        }
+    }
+}
+
+/// Storage of the backend selected at runtime
+#[derive(Clone)]
+pub enum ClientStorage {
+    /// sled database
+    #[cfg(feature = "sled")]
+    Sled(SledStorage),
+    /// SQLite database
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqliteStorage),
+}
+
+impl ClientStorage {
+    /// Opens the storage of given backend in a storage directory
+    pub fn open<P: AsRef<Path>>(backend: StorageBackend, storage_dir: P) -> Result<Self> {
+        match backend {
+            #[cfg(feature = "sled")]
+            StorageBackend::Sled => Ok(ClientStorage::Sled(SledStorage::new(storage_dir)?)),
+            #[cfg(feature = "sqlite")]
+            StorageBackend::Sqlite => {
+                std::fs::create_dir_all(&storage_dir).chain(|| {
+                    (
+                        ErrorKind::InitializationError,
+                        format!(
+                            "Unable to create storage directory: {}",
+                            storage_dir.as_ref().display()
+                        ),
+                    )
+                })?;
+                Ok(ClientStorage::Sqlite(SqliteStorage::new(
+                    storage_dir.as_ref().join(SQLITE_FILE_NAME),
+                )?))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::new(
+                ErrorKind::InitializationError,
+                format!("Storage backend {} is not enabled in this build", backend),
+            )),
+        }
+    }
+}
+
+macro_rules! delegate {
+    ($self:ident, $storage:ident => $call:expr) => {
+        match $self {
+            #[cfg(feature = "sled")]
+            ClientStorage::Sled($storage) => $call,
+            #[cfg(feature = "sqlite")]
+            ClientStorage::Sqlite($storage) => $call,
+        }
+    };
+}
+
+impl Storage for ClientStorage {
+    fn clear<S: AsRef<[u8]>>(&self, keyspace: S) -> Result<()> {
+        delegate!(self, storage => storage.clear(keyspace))
+    }
+
+    fn get<S: AsRef<[u8]>, K: AsRef<[u8]>>(&self, keyspace: S, key: K) -> Result<Option<Vec<u8>>> {
+        delegate!(self, storage => storage.get(keyspace, key))
+    }
+
+    fn set<S: AsRef<[u8]>, K: AsRef<[u8]>>(
+        &self,
+        keyspace: S,
+        key: K,
+        value: Vec<u8>,
+    ) -> Result<Option<Vec<u8>>> {
+        delegate!(self, storage => storage.set(keyspace, key, value))
+    }
+
+    fn delete<S: AsRef<[u8]>, K: AsRef<[u8]>>(
+        &self,
+        keyspace: S,
+        key: K,
+    ) -> Result<Option<Vec<u8>>> {
+        delegate!(self, storage => storage.delete(keyspace, key))
+    }
+
+    fn fetch_and_update<S, K, F>(&self, keyspace: S, key: K, f: F) -> Result<Option<Vec<u8>>>
+    where
+        S: AsRef<[u8]>,
+        K: AsRef<[u8]>,
+        F: Fn(Option<&[u8]>) -> Result<Option<Vec<u8>>>,
+    {
+        delegate!(self, storage => storage.fetch_and_update(keyspace, key, f))
+    }
+
+    fn keys<S: AsRef<[u8]>>(&self, keyspace: S) -> Result<Vec<Vec<u8>>> {
+        delegate!(self, storage => storage.keys(keyspace))
+    }
+
+    fn contains_key<S: AsRef<[u8]>, K: AsRef<[u8]>>(&self, keyspace: S, key: K) -> Result<bool> {
+        delegate!(self, storage => storage.contains_key(keyspace, key))
+    }
+
+    fn keyspaces(&self) -> Result<Vec<Vec<u8>>> {
+        delegate!(self, storage => storage.keyspaces())
+    }
+
+    fn flush(&self) -> Result<()> {
+        delegate!(self, storage => storage.flush())
+    }
+
+    fn resolve_enckey<'a>(&self, enckey: &'a SecKey) -> Result<Cow<'a, SecKey>> {
+        delegate!(self, storage => storage.resolve_enckey(enckey))
+    }
+}
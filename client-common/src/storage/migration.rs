@@ -0,0 +1,533 @@
+use parity_scale_codec::{Decode, Encode};
+
+use crate::storage::{SecureStorage, Storage};
+use crate::{Error, ErrorKind, Result, ResultExt, SecKey};
+
+/// Keyspace of schema version records
+///
+/// - `<keyspace>`: version of the values in the keyspace
+/// - `<keyspace>/<key>`: version of an encrypted value which couldn't be upgraded yet (it is
+///   upgraded when its enckey is available)
+pub const SCHEMA_KEYSPACE: &str = "core_schema_version";
+
+/// Upgrades a (decrypted) value to the version of the migration
+pub type UpgradeFn = fn(&[u8]) -> Result<Vec<u8>>;
+
+/// Migration of the values of a keyspace to a new schema version
+#[derive(Clone)]
+pub struct Migration {
+    /// migrated keyspace
+    pub keyspace: &'static str,
+    /// schema version after the migration (versions of a keyspace start at `1`)
+    pub version: u32,
+    /// description of the change
+    pub description: &'static str,
+    /// whether values of the keyspace are encrypted with the enckey of the wallet
+    pub encrypted: bool,
+    /// upgrade of a value from the previous version
+    pub upgrade: UpgradeFn,
+}
+
+/// What happened to a migrated value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationAction {
+    /// value upgraded to the latest version
+    Upgraded,
+    /// encrypted value recorded to be upgraded when its enckey is available
+    Deferred,
+}
+
+/// Change of a value by the migrations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationChange {
+    /// keyspace of the value
+    pub keyspace: String,
+    /// key of the value
+    pub key: Vec<u8>,
+    /// version of the stored value
+    pub from_version: u32,
+    /// version after migration
+    pub to_version: u32,
+    /// what happened to the value
+    pub action: MigrationAction,
+    /// descriptions of the applied migrations
+    pub descriptions: Vec<&'static str>,
+}
+
+/// Changes made (or to be made in a dry run) by the migrations
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// changed values
+    pub changes: Vec<MigrationChange>,
+}
+
+impl MigrationReport {
+    /// Returns `true` if no value needs to be migrated
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Ordered registry of storage migrations
+#[derive(Default, Clone)]
+pub struct MigrationRegistry {
+    migrations: Vec<Migration>,
+}
+
+impl MigrationRegistry {
+    /// Creates an empty registry
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the next migration of a keyspace
+    ///
+    /// # Panics
+    ///
+    /// Panics if the version doesn't directly follow the latest registered version of the
+    /// keyspace or if the migrations of a keyspace disagree on encryption.
+    pub fn register(mut self, migration: Migration) -> Self {
+        assert_eq!(
+            self.latest_version(migration.keyspace) + 1,
+            migration.version,
+            "migrations of keyspace {} must be registered in order",
+            migration.keyspace
+        );
+        assert!(
+            self.migrations
+                .iter()
+                .filter(|m| m.keyspace == migration.keyspace)
+                .all(|m| m.encrypted == migration.encrypted),
+            "migrations of keyspace {} disagree on encryption",
+            migration.keyspace
+        );
+        self.migrations.push(migration);
+        self
+    }
+
+    /// Returns the latest schema version of a keyspace (`0` for keyspaces without migrations)
+    pub fn latest_version(&self, keyspace: &str) -> u32 {
+        self.migrations
+            .iter()
+            .filter(|m| m.keyspace == keyspace)
+            .map(|m| m.version)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Returns the schema version of the values in a keyspace
+    ///
+    /// Keyspaces without version record are either empty (and get the latest version) or store
+    /// values written before the keyspace was versioned (version `0`).
+    pub fn version<S: Storage>(&self, storage: &S, keyspace: &str) -> Result<u32> {
+        match storage.load::<u32>(SCHEMA_KEYSPACE, keyspace)? {
+            Some(version) => Ok(version),
+            None if storage.keys(keyspace)?.is_empty() => Ok(self.latest_version(keyspace)),
+            None => Ok(0),
+        }
+    }
+
+    /// Returns the schema version of a value (values recorded as outdated keep their version)
+    pub fn value_version<S: Storage>(
+        &self,
+        storage: &S,
+        keyspace: &str,
+        key: &[u8],
+    ) -> Result<u32> {
+        match storage.get(SCHEMA_KEYSPACE, value_record_key(keyspace, key))? {
+            Some(bytes) => decode_version(&bytes),
+            None => self.version(storage, keyspace),
+        }
+    }
+
+    /// Writes a value of the given schema version (e.g. from a backup) to a migrated storage
+    ///
+    /// Outdated unencrypted values are upgraded and outdated encrypted values are recorded to be
+    /// upgraded by [`migrate_secure`]. Fails on values newer than the keyspace.
+    ///
+    /// [`migrate_secure`]: #method.migrate_secure
+    pub fn import_value<S: Storage>(
+        &self,
+        storage: &S,
+        keyspace: &str,
+        key: &[u8],
+        value: Vec<u8>,
+        version: u32,
+    ) -> Result<()> {
+        let keyspace_version = self.version(storage, keyspace)?;
+        if version > keyspace_version {
+            return Err(Error::new(
+                ErrorKind::StorageError,
+                format!(
+                    "Value of keyspace {} has schema version {} which is newer than version {} of the keyspace",
+                    keyspace, version, keyspace_version
+                ),
+            ));
+        }
+        // keeps the version of the keyspace once it stores the value
+        if !storage.contains_key(SCHEMA_KEYSPACE, keyspace)? {
+            storage.save(SCHEMA_KEYSPACE, keyspace, &keyspace_version)?;
+        }
+
+        let encrypted = self.is_encrypted(keyspace);
+        if version < keyspace_version && encrypted {
+            storage.set(
+                SCHEMA_KEYSPACE,
+                value_record_key(keyspace, key),
+                version.encode(),
+            )?;
+            storage.set(keyspace, key, value)?;
+        } else if version < keyspace_version {
+            storage.set(keyspace, key, self.upgrade(keyspace, version, value)?)?;
+        } else {
+            storage.set(keyspace, key, value)?;
+        }
+        Ok(())
+    }
+
+    /// Migrates all the registered keyspaces to their latest version (changes are only reported
+    /// in a dry run)
+    ///
+    /// Encrypted values are recorded to be upgraded by [`migrate_secure`] when their enckey is
+    /// available.
+    ///
+    /// [`migrate_secure`]: #method.migrate_secure
+    pub fn migrate<S: Storage>(&self, storage: &S, dry_run: bool) -> Result<MigrationReport> {
+        let mut report = MigrationReport::default();
+
+        for keyspace in self.keyspaces() {
+            let version = self.version(storage, keyspace)?;
+            let latest_version = self.latest_version(keyspace);
+            if version > latest_version {
+                return Err(Error::new(
+                    ErrorKind::StorageError,
+                    format!(
+                        "Keyspace {} has schema version {} which is newer than supported version {}",
+                        keyspace, version, latest_version
+                    ),
+                ));
+            }
+
+            if version < latest_version {
+                for key in storage.keys(keyspace)? {
+                    report
+                        .changes
+                        .push(self.migrate_value(storage, keyspace, key, version, dry_run)?);
+                }
+            }
+
+            let recorded = storage.contains_key(SCHEMA_KEYSPACE, keyspace)?;
+            if !dry_run && (!recorded || version < latest_version) {
+                storage.save(SCHEMA_KEYSPACE, keyspace, &latest_version)?;
+            }
+        }
+
+        if !dry_run {
+            storage.flush()?;
+        }
+        Ok(report)
+    }
+
+    /// Upgrades the encrypted values stored under `key` which are recorded as outdated (changes
+    /// are only reported in a dry run)
+    pub fn migrate_secure<S: SecureStorage>(
+        &self,
+        storage: &S,
+        key: &[u8],
+        enckey: &SecKey,
+        dry_run: bool,
+    ) -> Result<MigrationReport> {
+        let mut report = MigrationReport::default();
+
+        for keyspace in self.keyspaces() {
+            let record_key = value_record_key(keyspace, key);
+            let version = match storage.get(SCHEMA_KEYSPACE, &record_key)? {
+                Some(bytes) => decode_version(&bytes)?,
+                None => continue,
+            };
+            let latest_version = self.latest_version(keyspace);
+
+            if let Some(value) = storage.get_secure(keyspace, key, enckey)? {
+                let upgraded = self.upgrade(keyspace, version, value)?;
+                if !dry_run {
+                    storage.set_secure(keyspace, key, upgraded, enckey)?;
+                }
+                report.changes.push(MigrationChange {
+                    keyspace: keyspace.to_owned(),
+                    key: key.to_vec(),
+                    from_version: version,
+                    to_version: latest_version,
+                    action: MigrationAction::Upgraded,
+                    descriptions: self.descriptions(keyspace, version),
+                });
+            }
+            if !dry_run {
+                storage.delete(SCHEMA_KEYSPACE, &record_key)?;
+            }
+        }
+
+        if !dry_run {
+            storage.flush()?;
+        }
+        Ok(report)
+    }
+
+    fn migrate_value<S: Storage>(
+        &self,
+        storage: &S,
+        keyspace: &str,
+        key: Vec<u8>,
+        version: u32,
+        dry_run: bool,
+    ) -> Result<MigrationChange> {
+        let action = if self.is_encrypted(keyspace) {
+            let record_key = value_record_key(keyspace, &key);
+            // values already waiting for an upgrade keep their (older) version
+            if !dry_run && !storage.contains_key(SCHEMA_KEYSPACE, &record_key)? {
+                storage.set(SCHEMA_KEYSPACE, &record_key, version.encode())?;
+            }
+            MigrationAction::Deferred
+        } else {
+            let value = storage
+                .get(keyspace, &key)?
+                .chain(|| (ErrorKind::StorageError, "Storage entry disappeared"))?;
+            let upgraded = self.upgrade(keyspace, version, value)?;
+            if !dry_run {
+                storage.set(keyspace, &key, upgraded)?;
+            }
+            MigrationAction::Upgraded
+        };
+
+        Ok(MigrationChange {
+            keyspace: keyspace.to_owned(),
+            key,
+            from_version: version,
+            to_version: self.latest_version(keyspace),
+            action,
+            descriptions: self.descriptions(keyspace, version),
+        })
+    }
+
+    fn upgrade(&self, keyspace: &str, version: u32, mut value: Vec<u8>) -> Result<Vec<u8>> {
+        for migration in self.pending(keyspace, version) {
+            value = (migration.upgrade)(&value).chain(|| {
+                (
+                    ErrorKind::DeserializationError,
+                    format!(
+                        "Unable to migrate value of keyspace {} to version {}",
+                        keyspace, migration.version
+                    ),
+                )
+            })?;
+        }
+        Ok(value)
+    }
+
+    fn is_encrypted(&self, keyspace: &str) -> bool {
+        self.migrations
+            .iter()
+            .any(|m| m.keyspace == keyspace && m.encrypted)
+    }
+
+    fn pending<'a>(
+        &'a self,
+        keyspace: &'a str,
+        version: u32,
+    ) -> impl Iterator<Item = &'a Migration> {
+        self.migrations
+            .iter()
+            .filter(move |m| m.keyspace == keyspace && m.version > version)
+    }
+
+    fn descriptions(&self, keyspace: &str, version: u32) -> Vec<&'static str> {
+        self.pending(keyspace, version)
+            .map(|m| m.description)
+            .collect()
+    }
+
+    fn keyspaces(&self) -> Vec<&'static str> {
+        let mut keyspaces: Vec<&'static str> = Vec::new();
+        for migration in self.migrations.iter() {
+            if !keyspaces.contains(&migration.keyspace) {
+                keyspaces.push(migration.keyspace);
+            }
+        }
+        keyspaces
+    }
+}
+
+/// Returns the key of the version record of a value in [`SCHEMA_KEYSPACE`]
+///
+/// [`SCHEMA_KEYSPACE`]: constant.SCHEMA_KEYSPACE.html
+pub fn value_record_key(keyspace: &str, key: &[u8]) -> Vec<u8> {
+    let mut record_key = keyspace.as_bytes().to_vec();
+    record_key.push(b'/');
+    record_key.extend_from_slice(key);
+    record_key
+}
+
+fn decode_version(bytes: &[u8]) -> Result<u32> {
+    u32::decode(&mut &bytes[..]).chain(|| {
+        (
+            ErrorKind::DeserializationError,
+            "Unable to decode schema version",
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seckey::derive_enckey;
+    use crate::storage::MemoryStorage;
+    use secstr::SecUtf8;
+
+    fn append_zero(value: &[u8]) -> Result<Vec<u8>> {
+        let mut value = value.to_vec();
+        value.push(0);
+        Ok(value)
+    }
+
+    fn append_one(value: &[u8]) -> Result<Vec<u8>> {
+        let mut value = value.to_vec();
+        value.push(1);
+        Ok(value)
+    }
+
+    fn registry() -> MigrationRegistry {
+        MigrationRegistry::new()
+            .register(Migration {
+                keyspace: "plain",
+                version: 1,
+                description: "append 0",
+                encrypted: false,
+                upgrade: append_zero,
+            })
+            .register(Migration {
+                keyspace: "secure",
+                version: 1,
+                description: "append 0",
+                encrypted: true,
+                upgrade: append_zero,
+            })
+            .register(Migration {
+                keyspace: "plain",
+                version: 2,
+                description: "append 1",
+                encrypted: false,
+                upgrade: append_one,
+            })
+    }
+
+    #[test]
+    fn check_migration() {
+        let storage = MemoryStorage::default();
+        let enckey = derive_enckey(&SecUtf8::from("passphrase"), "name").unwrap();
+        storage.set("plain", "key", vec![7]).unwrap();
+        storage
+            .set_secure("secure", "name", vec![7], &enckey)
+            .unwrap();
+        let registry = registry();
+        assert_eq!(registry.version(&storage, "plain").unwrap(), 0);
+
+        let report = registry.migrate(&storage, true).unwrap();
+        assert_eq!(report.changes.len(), 2);
+        assert_eq!(report.changes[0].action, MigrationAction::Upgraded);
+        assert_eq!(report.changes[0].descriptions, vec!["append 0", "append 1"]);
+        assert_eq!(report.changes[1].action, MigrationAction::Deferred);
+        // dry run doesn't change anything
+        assert_eq!(storage.get("plain", "key").unwrap(), Some(vec![7]));
+        assert_eq!(registry.version(&storage, "plain").unwrap(), 0);
+
+        registry.migrate(&storage, false).unwrap();
+        assert_eq!(storage.get("plain", "key").unwrap(), Some(vec![7, 0, 1]));
+        assert_eq!(registry.version(&storage, "plain").unwrap(), 2);
+        assert_eq!(registry.version(&storage, "secure").unwrap(), 1);
+        assert!(registry.migrate(&storage, false).unwrap().is_empty());
+
+        // values written after the migration are not upgraded again
+        storage
+            .set_secure("secure", "other", vec![8], &enckey)
+            .unwrap();
+        assert!(registry
+            .migrate_secure(&storage, b"other", &enckey, false)
+            .unwrap()
+            .is_empty());
+
+        let report = registry
+            .migrate_secure(&storage, b"name", &enckey, false)
+            .unwrap();
+        assert_eq!(report.changes.len(), 1);
+        assert_eq!(
+            storage.get_secure("secure", "name", &enckey).unwrap(),
+            Some(vec![7, 0])
+        );
+        assert!(registry
+            .migrate_secure(&storage, b"name", &enckey, false)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            storage.get_secure("secure", "other", &enckey).unwrap(),
+            Some(vec![8])
+        );
+    }
+
+    #[test]
+    fn check_import_value() {
+        let storage = MemoryStorage::default();
+        let enckey = derive_enckey(&SecUtf8::from("passphrase"), "name").unwrap();
+        let registry = registry();
+        registry.migrate(&storage, false).unwrap();
+
+        registry
+            .import_value(&storage, "plain", b"key", vec![7], 1)
+            .unwrap();
+        assert_eq!(storage.get("plain", "key").unwrap(), Some(vec![7, 1]));
+        assert_eq!(registry.version(&storage, "plain").unwrap(), 2);
+        assert_eq!(
+            registry.value_version(&storage, "plain", b"key").unwrap(),
+            2
+        );
+        assert!(registry
+            .import_value(&storage, "plain", b"other", vec![7], 3)
+            .is_err());
+
+        // encrypted value of version 0 from another storage
+        let source = MemoryStorage::default();
+        source
+            .set_secure("secure", "name", vec![7], &enckey)
+            .unwrap();
+        let encrypted = source.get("secure", "name").unwrap().unwrap();
+        registry
+            .import_value(&storage, "secure", b"name", encrypted, 0)
+            .unwrap();
+        assert_eq!(
+            registry.value_version(&storage, "secure", b"name").unwrap(),
+            0
+        );
+        registry
+            .migrate_secure(&storage, b"name", &enckey, false)
+            .unwrap();
+        assert_eq!(
+            storage.get_secure("secure", "name", &enckey).unwrap(),
+            Some(vec![7, 0])
+        );
+        assert_eq!(
+            registry.value_version(&storage, "secure", b"name").unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn check_new_storage_is_stamped() {
+        let storage = MemoryStorage::default();
+        let registry = registry();
+        assert!(registry.migrate(&storage, false).unwrap().is_empty());
+        storage.set("plain", "key", vec![7]).unwrap();
+        assert_eq!(registry.version(&storage, "plain").unwrap(), 2);
+        assert!(registry.migrate(&storage, false).unwrap().is_empty());
+        assert_eq!(storage.get("plain", "key").unwrap(), Some(vec![7]));
+    }
+}
@@ -0,0 +1,184 @@
+use std::borrow::Cow;
+
+use crate::secret_service::{is_handle, SecretServiceClient};
+use crate::storage::Storage;
+use crate::{Error, ErrorKind, Result, ResultExt, SecKey};
+
+/// Storage keeping the enckeys in a local secret service
+///
+/// Callers pass the handles returned by the secret service instead of the enckeys; values are
+/// encrypted with the enckey the service holds for a handle. Unknown (or revoked) handles are
+/// rejected, while keys which aren't handles are used as enckeys, so wallets can still be used
+/// with their plain authentication token.
+#[derive(Debug, Clone)]
+pub struct SecretServiceStorage<S: Storage> {
+    storage: S,
+    secret_service: Option<SecretServiceClient>,
+}
+
+impl<S> SecretServiceStorage<S>
+where
+    S: Storage,
+{
+    /// Wraps a storage (enckeys are used as given until a secret service is set)
+    #[inline]
+    pub fn new(storage: S) -> Self {
+        Self {
+            storage,
+            secret_service: None,
+        }
+    }
+
+    /// Resolves the enckeys with given secret service
+    #[inline]
+    pub fn with_secret_service(mut self, secret_service: SecretServiceClient) -> Self {
+        self.secret_service = Some(secret_service);
+        self
+    }
+
+    /// Returns the secret service of the storage (if any)
+    #[inline]
+    pub fn secret_service(&self) -> Option<&SecretServiceClient> {
+        self.secret_service.as_ref()
+    }
+}
+
+impl<S> Storage for SecretServiceStorage<S>
+where
+    S: Storage,
+{
+    #[inline]
+    fn clear<K: AsRef<[u8]>>(&self, keyspace: K) -> Result<()> {
+        self.storage.clear(keyspace)
+    }
+
+    #[inline]
+    fn get<K: AsRef<[u8]>, V: AsRef<[u8]>>(&self, keyspace: K, key: V) -> Result<Option<Vec<u8>>> {
+        self.storage.get(keyspace, key)
+    }
+
+    #[inline]
+    fn set<K: AsRef<[u8]>, V: AsRef<[u8]>>(
+        &self,
+        keyspace: K,
+        key: V,
+        value: Vec<u8>,
+    ) -> Result<Option<Vec<u8>>> {
+        self.storage.set(keyspace, key, value)
+    }
+
+    #[inline]
+    fn delete<K: AsRef<[u8]>, V: AsRef<[u8]>>(
+        &self,
+        keyspace: K,
+        key: V,
+    ) -> Result<Option<Vec<u8>>> {
+        self.storage.delete(keyspace, key)
+    }
+
+    #[inline]
+    fn fetch_and_update<K, V, F>(&self, keyspace: K, key: V, f: F) -> Result<Option<Vec<u8>>>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+        F: Fn(Option<&[u8]>) -> Result<Option<Vec<u8>>>,
+    {
+        self.storage.fetch_and_update(keyspace, key, f)
+    }
+
+    #[inline]
+    fn keys<K: AsRef<[u8]>>(&self, keyspace: K) -> Result<Vec<Vec<u8>>> {
+        self.storage.keys(keyspace)
+    }
+
+    #[inline]
+    fn contains_key<K: AsRef<[u8]>, V: AsRef<[u8]>>(&self, keyspace: K, key: V) -> Result<bool> {
+        self.storage.contains_key(keyspace, key)
+    }
+
+    #[inline]
+    fn keyspaces(&self) -> Result<Vec<Vec<u8>>> {
+        self.storage.keyspaces()
+    }
+
+    #[inline]
+    fn flush(&self) -> Result<()> {
+        self.storage.flush()
+    }
+
+    fn resolve_enckey<'a>(&self, enckey: &'a SecKey) -> Result<Cow<'a, SecKey>> {
+        let enckey = self.storage.resolve_enckey(enckey)?;
+        if !is_handle(&enckey) {
+            return Ok(enckey);
+        }
+        let secret_service = self.secret_service.as_ref().chain(|| {
+            (
+                ErrorKind::PermissionDenied,
+                "Authentication token is a handle but no secret service is configured",
+            )
+        })?;
+        match secret_service.get(&enckey)? {
+            Some(resolved) => Ok(Cow::Owned(resolved)),
+            None => Err(Error::new(
+                ErrorKind::PermissionDenied,
+                "Authentication token is unknown to the secret service (or was revoked)",
+            )),
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::seckey::derive_enckey;
+    use crate::secret_service::SecretService;
+    use crate::storage::{MemoryStorage, SecureStorage};
+    use secstr::SecUtf8;
+
+    #[test]
+    fn check_enckey_resolution() {
+        let socket_path =
+            std::env::temp_dir().join(format!("secret-storage-{}.sock", uuid::Uuid::new_v4()));
+        let serve_path = socket_path.clone();
+        std::thread::spawn(move || SecretService::default().serve(serve_path).unwrap());
+        let client = SecretServiceClient::new(&socket_path);
+        let enckey = derive_enckey(&SecUtf8::from("passphrase"), "name").unwrap();
+        // waits for the service to listen
+        let handle = loop {
+            if let Ok(handle) = client.store(&enckey) {
+                break handle;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+
+        let memory = MemoryStorage::default();
+        let storage = SecretServiceStorage::new(memory.clone()).with_secret_service(client.clone());
+        storage
+            .set_secure("keyspace", "key", b"value".to_vec(), &handle)
+            .unwrap();
+        // values are encrypted with the enckey behind the handle
+        assert_eq!(
+            memory.get_secure("keyspace", "key", &enckey).unwrap(),
+            Some(b"value".to_vec())
+        );
+        // plain enckeys still work
+        assert_eq!(
+            storage.get_secure("keyspace", "key", &enckey).unwrap(),
+            Some(b"value".to_vec())
+        );
+
+        client.remove(&handle).unwrap();
+        assert_eq!(
+            storage
+                .get_secure("keyspace", "key", &handle)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::PermissionDenied
+        );
+        // handles aren't used as enckeys without secret service
+        assert!(SecretServiceStorage::new(memory)
+            .resolve_enckey(&handle)
+            .is_err());
+        std::fs::remove_file(socket_path).unwrap();
+    }
+}
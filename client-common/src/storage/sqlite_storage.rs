@@ -0,0 +1,519 @@
+#![cfg(feature = "sqlite")]
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use rusqlite::{params, Connection, OptionalExtension, Transaction, TransactionBehavior};
+
+use crate::storage::Storage;
+use crate::{Error, ErrorKind, Result, ResultExt};
+
+/// Schema migration of SQLite storage
+struct SqliteMigration {
+    /// schema version after the migration
+    version: u32,
+    /// description of the change (recorded in the database)
+    description: &'static str,
+    /// statements applying the change
+    sql: &'static str,
+}
+
+/// Schema migrations of SQLite storage, in order
+///
+/// Applied migrations are recorded in `schema_migrations` (and `PRAGMA user_version` is the
+/// latest applied version). A database whose recorded migrations differ from these is rejected.
+const MIGRATIONS: &[SqliteMigration] = &[SqliteMigration {
+    version: 1,
+    description: "key-value entries grouped by keyspace",
+    sql: "CREATE TABLE entries (
+        keyspace BLOB NOT NULL,
+        key BLOB NOT NULL,
+        value BLOB NOT NULL,
+        PRIMARY KEY (keyspace, key)
+    ) WITHOUT ROWID;",
+}];
+
+/// Record of the applied migrations
+const MIGRATIONS_TABLE: &str = "CREATE TABLE IF NOT EXISTS schema_migrations (
+    version INTEGER PRIMARY KEY,
+    description TEXT NOT NULL,
+    applied_at INTEGER NOT NULL
+);";
+
+/// Storage backed by SQLite
+#[derive(Clone)]
+pub struct SqliteStorage(Arc<Mutex<Connection>>);
+
+impl SqliteStorage {
+    /// Creates a new instance with specified path of the database file (applies pending schema
+    /// migrations)
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let connection = Connection::open(&path).chain(|| {
+            (
+                ErrorKind::InitializationError,
+                format!(
+                    "Unable to initialize sqlite storage at path: {}",
+                    path.as_ref().display()
+                ),
+            )
+        })?;
+        Self::from_connection(connection)
+    }
+
+    /// Creates a new instance backed by an in-memory database
+    pub fn new_in_memory() -> Result<Self> {
+        let connection = Connection::open_in_memory().chain(|| {
+            (
+                ErrorKind::InitializationError,
+                "Unable to initialize in-memory sqlite storage",
+            )
+        })?;
+        Self::from_connection(connection)
+    }
+
+    fn from_connection(mut connection: Connection) -> Result<Self> {
+        migrate(&mut connection)?;
+        Ok(Self(Arc::new(Mutex::new(connection))))
+    }
+
+    /// Returns the schema version of the database
+    pub fn schema_version(&self) -> Result<usize> {
+        schema_version(&self.connection()?)
+    }
+
+    fn connection(&self) -> Result<MutexGuard<'_, Connection>> {
+        self.0.lock().map_err(|_| {
+            Error::new(
+                ErrorKind::StorageError,
+                "Unable to acquire lock on sqlite storage",
+            )
+        })
+    }
+}
+
+fn schema_version(connection: &Connection) -> Result<usize> {
+    let version: i64 = connection
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .chain(|| {
+            (
+                ErrorKind::StorageError,
+                "Unable to read schema version of sqlite storage",
+            )
+        })?;
+    Ok(version as usize)
+}
+
+/// Returns the recorded migrations (version and description) in order
+fn applied_migrations(connection: &Connection) -> Result<Vec<(u32, String)>> {
+    let error = || {
+        (
+            ErrorKind::StorageError,
+            "Unable to read applied migrations of sqlite storage",
+        )
+    };
+    let mut statement = connection
+        .prepare("SELECT version, description FROM schema_migrations ORDER BY version")
+        .chain(error)?;
+    let applied = statement
+        .query_map(params![], |row| Ok((row.get(0)?, row.get(1)?)))
+        .chain(error)?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .chain(error)?;
+    Ok(applied)
+}
+
+fn record_migration(connection: &Connection, migration: &SqliteMigration) -> Result<()> {
+    let applied_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default();
+    connection
+        .execute(
+            "INSERT INTO schema_migrations (version, description, applied_at) VALUES (?1, ?2, ?3)",
+            params![migration.version, migration.description, applied_at],
+        )
+        .chain(|| {
+            (
+                ErrorKind::StorageError,
+                format!(
+                    "Unable to record migration {} of sqlite storage",
+                    migration.version
+                ),
+            )
+        })?;
+    connection
+        .pragma_update(None, "user_version", &i64::from(migration.version))
+        .chain(|| {
+            (
+                ErrorKind::StorageError,
+                "Unable to update schema version of sqlite storage",
+            )
+        })
+}
+
+fn begin(connection: &mut Connection) -> Result<Transaction<'_>> {
+    Transaction::new(connection, TransactionBehavior::Exclusive).chain(|| {
+        (
+            ErrorKind::StorageError,
+            "Unable to start sqlite transaction",
+        )
+    })
+}
+
+/// Checks the recorded migrations and applies the newer ones
+fn migrate(connection: &mut Connection) -> Result<()> {
+    let version = schema_version(connection)?;
+    if version > MIGRATIONS.len() {
+        return Err(Error::new(
+            ErrorKind::InitializationError,
+            format!(
+                "Sqlite storage has schema version {} which is newer than supported version {}",
+                version,
+                MIGRATIONS.len()
+            ),
+        ));
+    }
+
+    let transaction = begin(connection)?;
+    transaction.execute_batch(MIGRATIONS_TABLE).chain(|| {
+        (
+            ErrorKind::InitializationError,
+            "Unable to create migrations table of sqlite storage",
+        )
+    })?;
+    let applied = applied_migrations(&transaction)?;
+    transaction.commit().chain(|| {
+        (
+            ErrorKind::StorageError,
+            "Unable to commit migrations table of sqlite storage",
+        )
+    })?;
+
+    let known = MIGRATIONS
+        .iter()
+        .map(|migration| (migration.version, migration.description.to_owned()));
+    if applied.len() != version || !applied.iter().cloned().eq(known.take(version)) {
+        return Err(Error::new(
+            ErrorKind::InitializationError,
+            format!(
+                "Sqlite storage (schema version {}) was migrated by an incompatible version",
+                version
+            ),
+        ));
+    }
+
+    for migration in MIGRATIONS.iter().skip(version) {
+        let transaction = begin(connection)?;
+        transaction.execute_batch(migration.sql).chain(|| {
+            (
+                ErrorKind::InitializationError,
+                format!(
+                    "Unable to apply migration {} to sqlite storage",
+                    migration.version
+                ),
+            )
+        })?;
+        record_migration(&transaction, migration)?;
+        transaction.commit().chain(|| {
+            (
+                ErrorKind::StorageError,
+                format!(
+                    "Unable to commit migration {} to sqlite storage",
+                    migration.version
+                ),
+            )
+        })?;
+    }
+    Ok(())
+}
+
+fn get_value(connection: &Connection, keyspace: &[u8], key: &[u8]) -> Result<Option<Vec<u8>>> {
+    connection
+        .query_row(
+            "SELECT value FROM entries WHERE keyspace = ?1 AND key = ?2",
+            params![keyspace, key],
+            |row| row.get(0),
+        )
+        .optional()
+        .chain(|| {
+            (
+                ErrorKind::StorageError,
+                format!(
+                    "Unable to find value for {} in keyspace: {}",
+                    String::from_utf8_lossy(key),
+                    String::from_utf8_lossy(keyspace)
+                ),
+            )
+        })
+}
+
+fn set_value(connection: &Connection, keyspace: &[u8], key: &[u8], value: &[u8]) -> Result<()> {
+    connection
+        .execute(
+            "INSERT OR REPLACE INTO entries (keyspace, key, value) VALUES (?1, ?2, ?3)",
+            params![keyspace, key, value],
+        )
+        .chain(|| {
+            (
+                ErrorKind::StorageError,
+                format!(
+                    "Unable to insert value for {} in keyspace: {}",
+                    String::from_utf8_lossy(key),
+                    String::from_utf8_lossy(keyspace)
+                ),
+            )
+        })?;
+    Ok(())
+}
+
+fn delete_value(connection: &Connection, keyspace: &[u8], key: &[u8]) -> Result<()> {
+    connection
+        .execute(
+            "DELETE FROM entries WHERE keyspace = ?1 AND key = ?2",
+            params![keyspace, key],
+        )
+        .chain(|| {
+            (
+                ErrorKind::StorageError,
+                format!(
+                    "Unable to delete {} in keyspace: {}",
+                    String::from_utf8_lossy(key),
+                    String::from_utf8_lossy(keyspace)
+                ),
+            )
+        })?;
+    Ok(())
+}
+
+impl Storage for SqliteStorage {
+    fn flush(&self) -> Result<()> {
+        // every statement is committed when it completes
+        Ok(())
+    }
+
+    fn clear<S: AsRef<[u8]>>(&self, keyspace: S) -> Result<()> {
+        self.connection()?
+            .execute(
+                "DELETE FROM entries WHERE keyspace = ?1",
+                params![keyspace.as_ref()],
+            )
+            .chain(|| {
+                (
+                    ErrorKind::StorageError,
+                    format!(
+                        "Unable to clear keyspace: {}",
+                        String::from_utf8_lossy(keyspace.as_ref())
+                    ),
+                )
+            })?;
+        Ok(())
+    }
+
+    fn get<S: AsRef<[u8]>, K: AsRef<[u8]>>(&self, keyspace: S, key: K) -> Result<Option<Vec<u8>>> {
+        get_value(&self.connection()?, keyspace.as_ref(), key.as_ref())
+    }
+
+    fn set<S: AsRef<[u8]>, K: AsRef<[u8]>>(
+        &self,
+        keyspace: S,
+        key: K,
+        value: Vec<u8>,
+    ) -> Result<Option<Vec<u8>>> {
+        self.fetch_and_update(keyspace, key, |_| Ok(Some(value.clone())))
+    }
+
+    fn delete<S: AsRef<[u8]>, K: AsRef<[u8]>>(
+        &self,
+        keyspace: S,
+        key: K,
+    ) -> Result<Option<Vec<u8>>> {
+        self.fetch_and_update(keyspace, key, |_| Ok(None))
+    }
+
+    fn fetch_and_update<S, K, F>(&self, keyspace: S, key: K, f: F) -> Result<Option<Vec<u8>>>
+    where
+        S: AsRef<[u8]>,
+        K: AsRef<[u8]>,
+        F: Fn(Option<&[u8]>) -> Result<Option<Vec<u8>>>,
+    {
+        let mut connection = self.connection()?;
+        let transaction =
+            Transaction::new(&mut connection, TransactionBehavior::Immediate).chain(|| {
+                (
+                    ErrorKind::StorageError,
+                    "Unable to start sqlite transaction",
+                )
+            })?;
+
+        let current = get_value(&transaction, keyspace.as_ref(), key.as_ref())?;
+        match f(current.as_ref().map(AsRef::as_ref))? {
+            Some(next) => set_value(&transaction, keyspace.as_ref(), key.as_ref(), &next)?,
+            None => delete_value(&transaction, keyspace.as_ref(), key.as_ref())?,
+        }
+
+        transaction.commit().chain(|| {
+            (
+                ErrorKind::StorageError,
+                format!(
+                    "Unable to commit value for {} in keyspace: {}",
+                    String::from_utf8_lossy(key.as_ref()),
+                    String::from_utf8_lossy(keyspace.as_ref())
+                ),
+            )
+        })?;
+        Ok(current)
+    }
+
+    fn keys<S: AsRef<[u8]>>(&self, keyspace: S) -> Result<Vec<Vec<u8>>> {
+        let connection = self.connection()?;
+        let error = || {
+            (
+                ErrorKind::StorageError,
+                format!(
+                    "Unable to retrieve keys for keyspace: {}",
+                    String::from_utf8_lossy(keyspace.as_ref())
+                ),
+            )
+        };
+
+        let mut statement = connection
+            .prepare("SELECT key FROM entries WHERE keyspace = ?1 ORDER BY key")
+            .chain(error)?;
+        let keys = statement
+            .query_map(params![keyspace.as_ref()], |row| row.get(0))
+            .chain(error)?
+            .collect::<std::result::Result<Vec<Vec<u8>>, _>>()
+            .chain(error)?;
+        Ok(keys)
+    }
+
+    fn contains_key<S: AsRef<[u8]>, K: AsRef<[u8]>>(&self, keyspace: S, key: K) -> Result<bool> {
+        let found = self
+            .connection()?
+            .query_row(
+                "SELECT 1 FROM entries WHERE keyspace = ?1 AND key = ?2",
+                params![keyspace.as_ref(), key.as_ref()],
+                |_| Ok(()),
+            )
+            .optional()
+            .chain(|| {
+                (
+                    ErrorKind::StorageError,
+                    format!(
+                        "Unable to check if {} exists in keyspace: {}",
+                        String::from_utf8_lossy(key.as_ref()),
+                        String::from_utf8_lossy(keyspace.as_ref())
+                    ),
+                )
+            })?;
+        Ok(found.is_some())
+    }
+
+    fn keyspaces(&self) -> Result<Vec<Vec<u8>>> {
+        let connection = self.connection()?;
+        let error = || (ErrorKind::StorageError, "Unable to retrieve keyspaces");
+
+        let mut statement = connection
+            .prepare("SELECT DISTINCT keyspace FROM entries ORDER BY keyspace")
+            .chain(error)?;
+        let keyspaces = statement
+            .query_map(params![], |row| row.get(0))
+            .chain(error)?
+            .collect::<std::result::Result<Vec<Vec<u8>>, _>>()
+            .chain(error)?;
+        Ok(keyspaces)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_flow() {
+        let storage = SqliteStorage::new_in_memory().expect("Unable to start sqlite storage");
+        assert_eq!(MIGRATIONS.len(), storage.schema_version().unwrap());
+        assert_eq!(
+            MIGRATIONS.len(),
+            applied_migrations(&storage.connection().unwrap())
+                .unwrap()
+                .len()
+        );
+
+        assert!(!storage.contains_key("keyspace", "key").unwrap());
+        assert_eq!(None, storage.get("keyspace", "key").unwrap());
+        assert_eq!(
+            None,
+            storage
+                .set("keyspace", "key", b"value1".to_vec())
+                .expect("Unable to set value")
+        );
+        assert_eq!(
+            Some(b"value1".to_vec()),
+            storage
+                .fetch_and_update("keyspace", "key", |_| Ok(Some(b"value".to_vec())))
+                .unwrap()
+        );
+        assert!(storage
+            .fetch_and_update("keyspace", "key", |_| Err(ErrorKind::InternalError.into()))
+            .is_err());
+        assert_eq!(
+            Some(b"value".to_vec()),
+            storage.get("keyspace", "key").unwrap()
+        );
+        storage.set("keyspace2", "key", b"value".to_vec()).unwrap();
+        assert_eq!(1, storage.keys("keyspace").unwrap().len());
+        assert_eq!(2, storage.keyspaces().unwrap().len());
+
+        assert_eq!(
+            Some(b"value".to_vec()),
+            storage.delete("keyspace2", "key").unwrap()
+        );
+        storage.clear("keyspace").unwrap();
+        assert_eq!(0, storage.keys("keyspace").unwrap().len());
+        assert!(storage.keyspaces().unwrap().is_empty());
+    }
+
+    #[test]
+    fn check_migration_of_existing_database() {
+        let path = std::env::temp_dir().join(format!("sqlite-storage-{}", uuid::Uuid::new_v4()));
+        {
+            let storage = SqliteStorage::new(&path).unwrap();
+            storage.set("keyspace", "key", b"value".to_vec()).unwrap();
+        }
+        let storage = SqliteStorage::new(&path).unwrap();
+        assert_eq!(
+            Some(b"value".to_vec()),
+            storage.get("keyspace", "key").unwrap()
+        );
+
+        storage
+            .connection()
+            .unwrap()
+            .pragma_update(None, "user_version", &((MIGRATIONS.len() + 1) as i64))
+            .unwrap();
+        drop(storage);
+        assert!(SqliteStorage::new(&path).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn check_recorded_migrations() {
+        let mut connection = Connection::open_in_memory().unwrap();
+        migrate(&mut connection).unwrap();
+        assert_eq!(
+            applied_migrations(&connection).unwrap(),
+            vec![(1, MIGRATIONS[0].description.to_owned())]
+        );
+        // migrations are applied once
+        migrate(&mut connection).unwrap();
+        assert_eq!(applied_migrations(&connection).unwrap().len(), 1);
+
+        connection
+            .execute(
+                "UPDATE schema_migrations SET description = 'other' WHERE version = 1",
+                params![],
+            )
+            .unwrap();
+        assert!(migrate(&mut connection).is_err());
+    }
+}
@@ -11,7 +11,7 @@ use chain_core::tx::data::TxId;
 use chain_core::tx::fee::LinearFee;
 use chain_core::tx::{TxAux, TxEnclaveAux, TxPublicAux};
 
-pub use self::block_results::BlockResults;
+pub use self::block_results::{BlockResults, StakingChange, StakingCoinDiff, StakingOpType};
 pub use tendermint::{
     abci, abci::transaction::Data, abci::Code, block::Header, block::Height, Block,
     Genesis as GenericGenesis, Hash, Time,
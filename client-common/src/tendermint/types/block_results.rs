@@ -3,17 +3,79 @@ use indexmap::IndexMap;
 use std::convert::TryFrom;
 use std::str::{from_utf8, FromStr};
 
-use chain_core::common::{TendermintEventKey, TendermintEventType};
+use serde_json::Value;
+
+use chain_core::common::{TendermintEventKey, TendermintEventType, Timespec};
 use chain_core::init::{coin::Coin, MAX_COIN_DECIMALS};
 use chain_core::state::account::StakedStateAddress;
 use chain_core::tx::data::TxId;
 use chain_core::tx::fee::Fee;
-use chain_tx_filter::BlockFilter;
+use chain_tx_filter::{BlockFilter, GcsFilter};
 
 use crate::tendermint::types::BlockResultsResponse;
 use crate::{Error, ErrorKind, Result, ResultExt};
 use tendermint::abci::tag::Tag as Attribute;
 
+/// Staking operation of a `staking_change` event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StakingOpType {
+    Deposit,
+    Unbond,
+    Withdraw,
+    NodeJoin,
+    Reward,
+    Jail,
+    Slash,
+    Unjail,
+}
+
+impl FromStr for StakingOpType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "deposit" => Ok(StakingOpType::Deposit),
+            "unbond" => Ok(StakingOpType::Unbond),
+            "withdraw" => Ok(StakingOpType::Withdraw),
+            "nodejoin" => Ok(StakingOpType::NodeJoin),
+            "reward" => Ok(StakingOpType::Reward),
+            "jail" => Ok(StakingOpType::Jail),
+            "slash" => Ok(StakingOpType::Slash),
+            "unjail" => Ok(StakingOpType::Unjail),
+            _ => Err(Error::new(
+                ErrorKind::DeserializationError,
+                format!("Unknown staking operation type: {}", s),
+            )),
+        }
+    }
+}
+
+/// Difference of bonded or unbonded amount in a `staking_change` event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StakingCoinDiff {
+    Increase(Coin),
+    Decrease(Coin),
+}
+
+/// Change of a staked state emitted as `staking_change` event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StakingChange {
+    /// affected staking address
+    pub staking_address: StakedStateAddress,
+    /// staking operation
+    pub op_type: StakingOpType,
+    /// difference of bonded amount
+    pub bonded: Option<StakingCoinDiff>,
+    /// difference of unbonded amount
+    pub unbonded: Option<StakingCoinDiff>,
+    /// new time from which unbonded amount can be withdrawn
+    pub unbonded_from: Option<Timespec>,
+    /// time until which the staked state is jailed
+    pub jailed_until: Option<Timespec>,
+    /// reason of punishment (for jail and slash)
+    pub reason: Option<String>,
+}
+
 pub trait BlockResults {
     /// Returns transaction ids and the corresponding fees in block results
     fn fees(&self) -> Result<IndexMap<TxId, Fee>>;
@@ -31,6 +93,17 @@ pub trait BlockResults {
 
     /// Returns block filter in block results
     fn block_filter(&self) -> Result<BlockFilter>;
+
+    /// Returns staking changes (begin block, transaction and end block `staking_change` events, in that order)
+    /// of staking addresses accepted by given callback
+    fn staking_changes(
+        &self,
+        contain_account_callback: &dyn Fn(&StakedStateAddress) -> bool,
+    ) -> Result<Vec<StakingChange>>;
+
+    /// Returns the serialized Golomb-coded set in block results (if the block has one),
+    /// i.e. the bytes chained in the filter header
+    fn raw_gcs_filter(&self) -> Result<Option<Vec<u8>>>;
 }
 
 impl BlockResults for BlockResultsResponse {
@@ -105,7 +178,15 @@ impl BlockResults for BlockResultsResponse {
             Some(events) => {
                 for event in events.iter() {
                     if event.type_str == TendermintEventType::BlockFilter.to_string() {
-                        let attribute = &event.attributes[0];
+                        // Golomb-coded set is preferred, bloom filter is used for older blocks
+                        let maybe_gcs = find_event_attribute_by_key(
+                            &event.attributes,
+                            TendermintEventKey::GolombCodedSet,
+                        )?;
+                        let attribute = match maybe_gcs {
+                            Some(attribute) => attribute,
+                            None => &event.attributes[0],
+                        };
                         let decoded = base64::decode(attribute.value.as_ref()).chain(|| {
                             (
                                 ErrorKind::DeserializationError,
@@ -113,15 +194,78 @@ impl BlockResults for BlockResultsResponse {
                             )
                         })?;
 
-                        return Ok(BlockFilter::try_from(decoded.as_slice()).map_err(
-                            |message| Error::new(ErrorKind::DeserializationError, message),
-                        )?);
+                        let block_filter = if maybe_gcs.is_some() {
+                            GcsFilter::try_from(decoded.as_slice()).map(BlockFilter::from)
+                        } else {
+                            BlockFilter::try_from(decoded.as_slice())
+                        };
+                        return Ok(block_filter.map_err(|message| {
+                            Error::new(ErrorKind::DeserializationError, message)
+                        })?);
                     }
                 }
                 Ok(BlockFilter::default())
             }
         }
     }
+
+    fn staking_changes(
+        &self,
+        contain_account_callback: &dyn Fn(&StakedStateAddress) -> bool,
+    ) -> Result<Vec<StakingChange>> {
+        let begin_block_events = self.begin_block_events.iter().flatten();
+        let tx_events = self
+            .txs_results
+            .iter()
+            .flatten()
+            .flat_map(|deliver_tx| deliver_tx.events.iter());
+        let end_block_events = self.end_block_events.iter().flatten();
+
+        let mut changes = Vec::new();
+        for event in begin_block_events.chain(tx_events).chain(end_block_events) {
+            if event.type_str != TendermintEventType::StakingChange.to_string() {
+                continue;
+            }
+            match find_staking_address_from_event_attributes(&event.attributes)? {
+                Some(address) if contain_account_callback(&address) => {
+                    changes.push(staking_change_from_event_attributes(
+                        address,
+                        &event.attributes,
+                    )?);
+                }
+                _ => continue,
+            }
+        }
+        Ok(changes)
+    }
+
+    fn raw_gcs_filter(&self) -> Result<Option<Vec<u8>>> {
+        match &self.end_block_events {
+            None => Ok(None),
+            Some(events) => {
+                for event in events.iter() {
+                    if event.type_str == TendermintEventType::BlockFilter.to_string() {
+                        let maybe_gcs = find_event_attribute_by_key(
+                            &event.attributes,
+                            TendermintEventKey::GolombCodedSet,
+                        )?;
+                        return match maybe_gcs {
+                            None => Ok(None),
+                            Some(attribute) => base64::decode(attribute.value.as_ref())
+                                .map(Some)
+                                .chain(|| {
+                                    (
+                                        ErrorKind::DeserializationError,
+                                        "Unable to decode base64 bytes of block filter in block results",
+                                    )
+                                }),
+                        };
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
 }
 
 fn find_event_attribute_by_key(
@@ -205,6 +349,108 @@ fn find_tx_id_from_event_attributes(attributes: &[Attribute]) -> Result<Option<[
     }
 }
 
+fn find_string_from_event_attributes(
+    attributes: &[Attribute],
+    target_key: TendermintEventKey,
+) -> Result<Option<String>> {
+    match find_event_attribute_by_key(attributes, target_key)? {
+        None => Ok(None),
+        Some(attribute) => {
+            let value = base64::decode(attribute.value.as_ref()).chain(|| {
+                (
+                    ErrorKind::DeserializationError,
+                    format!(
+                        "Unable to decode base64 bytes of {} in block results",
+                        target_key
+                    ),
+                )
+            })?;
+            String::from_utf8(value).map(Some).chain(|| {
+                (
+                    ErrorKind::DeserializationError,
+                    format!("Unable to decode string of {} in block results", target_key),
+                )
+            })
+        }
+    }
+}
+
+fn staking_change_from_event_attributes(
+    staking_address: StakedStateAddress,
+    attributes: &[Attribute],
+) -> Result<StakingChange> {
+    let op_type = find_string_from_event_attributes(attributes, TendermintEventKey::StakingOpType)?
+        .chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Staking operation type not found in staking change event",
+            )
+        })?
+        .parse::<StakingOpType>()?;
+
+    let mut change = StakingChange {
+        staking_address,
+        op_type,
+        bonded: None,
+        unbonded: None,
+        unbonded_from: None,
+        jailed_until: None,
+        reason: find_string_from_event_attributes(attributes, TendermintEventKey::StakingOpReason)?,
+    };
+
+    let diffs =
+        match find_string_from_event_attributes(attributes, TendermintEventKey::StakingDiff)? {
+            None => return Ok(change),
+            Some(diffs) => serde_json::from_str::<Vec<Value>>(&diffs).chain(|| {
+                (
+                    ErrorKind::DeserializationError,
+                    "Unable to decode staking diff in block results",
+                )
+            })?,
+        };
+    for diff in diffs {
+        let value = &diff["value"];
+        match diff["key"].as_str() {
+            Some("Bonded") => change.bonded = Some(parse_staking_coin_diff(value)?),
+            Some("Unbonded") => change.unbonded = Some(parse_staking_coin_diff(value)?),
+            Some("UnbondedFrom") => change.unbonded_from = Some(parse_timespec(value)?),
+            Some("JailedUntil") => change.jailed_until = Some(parse_timespec(value)?),
+            _ => continue,
+        }
+    }
+    Ok(change)
+}
+
+fn parse_staking_coin_diff(value: &Value) -> Result<StakingCoinDiff> {
+    let value = value.as_str().unwrap_or_default();
+    let decrease = value.starts_with('-');
+    let amount = value
+        .trim_start_matches('-')
+        .parse::<u64>()
+        .ok()
+        .and_then(|amount| Coin::new(amount).ok())
+        .chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Invalid amount in staking diff",
+            )
+        })?;
+    if decrease {
+        Ok(StakingCoinDiff::Decrease(amount))
+    } else {
+        Ok(StakingCoinDiff::Increase(amount))
+    }
+}
+
+fn parse_timespec(value: &Value) -> Result<Timespec> {
+    value.as_u64().chain(|| {
+        (
+            ErrorKind::DeserializationError,
+            "Invalid time in staking diff",
+        )
+    })
+}
+
 fn find_staking_address_from_event_attributes(
     attributes: &[Attribute],
 ) -> Result<Option<StakedStateAddress>> {
@@ -336,6 +582,129 @@ mod tests {
         assert!(block_results.block_filter().is_ok());
     }
 
+    #[test]
+    fn check_block_filter_prefers_gcs() {
+        let secret_key = secp256k1::key::SecretKey::from_slice(&[0xcd; 32]).unwrap();
+        let view_key =
+            secp256k1::key::PublicKey::from_secret_key(secp256k1::SECP256K1, &secret_key);
+        let mut filter = BlockFilter::default();
+        filter.add_view_key(&view_key);
+        let (_, gcs) = filter.get_gcs_tendermint_kv();
+
+        // the (empty) bloom filter is ignored if Golomb-coded set is present
+        let response_str = format!(
+            r#"{{"height": "37", "txs_results": null, "begin_block_events": null, "end_block_events": [{{"type": "block_filter", "attributes": [{{"key": "{}", "value": "{}"}}, {{"key": "{}", "value": "{}"}}]}}], "validator_updates": null, "consensus_param_updates": null}}"#,
+            TendermintEventKey::EthBloom.to_base64_string(),
+            base64::encode(&[0u8; 256][..]),
+            TendermintEventKey::GolombCodedSet.to_base64_string(),
+            base64::encode(&gcs),
+        );
+        let block_results: BlockResultsResponse =
+            serde_json::from_str(&response_str).expect("invalid response str");
+        let block_filter = block_results.block_filter().unwrap();
+        assert!(block_filter.check_view_key(&view_key));
+        assert_eq!(Some(gcs), block_results.raw_gcs_filter().unwrap());
+    }
+
+    #[test]
+    fn check_staking_changes() {
+        let attribute = |key: TendermintEventKey, value: &str| {
+            format!(
+                r#"{{"key": "{}", "value": "{}"}}"#,
+                key.to_base64_string(),
+                base64::encode(value)
+            )
+        };
+        let event = |address: &str, op_type: &str, extra: Vec<String>| {
+            let mut attributes = vec![
+                attribute(TendermintEventKey::StakingAddress, address),
+                attribute(TendermintEventKey::StakingOpType, op_type),
+            ];
+            attributes.extend(extra);
+            format!(
+                r#"{{"type": "staking_change", "attributes": [{}]}}"#,
+                attributes.join(", ")
+            )
+        };
+        let alice = "0x0e7c045110b8dbf29765047380898919c5cb56f4";
+        let bob = "0x33502ed39d0c4e2044fb37fdcd5161493f5900c3";
+
+        let begin_block_events = vec![
+            event(
+                alice,
+                "slash",
+                vec![
+                    attribute(
+                        TendermintEventKey::StakingDiff,
+                        r#"[{"key":"Bonded","value":"-10"},{"key":"Unbonded","value":"-0"}]"#,
+                    ),
+                    attribute(TendermintEventKey::StakingOpReason, "ByzantineFault"),
+                ],
+            ),
+            event(
+                alice,
+                "jail",
+                vec![attribute(
+                    TendermintEventKey::StakingDiff,
+                    r#"[{"key":"JailedUntil","value":1587071014}]"#,
+                )],
+            ),
+            event(
+                bob,
+                "reward",
+                vec![attribute(
+                    TendermintEventKey::StakingDiff,
+                    r#"[{"key":"Bonded","value":"5"}]"#,
+                )],
+            ),
+        ];
+        let tx_events = vec![event(alice, "unjail", vec![])];
+        let response_str = format!(
+            r#"{{"height": "40", "txs_results": [{{"code": 0, "data": null, "log": "", "info": "", "gasWanted": "0", "gasUsed": "0", "events": [{}], "codespace": ""}}], "begin_block_events": [{}], "end_block_events": null, "validator_updates": null, "consensus_param_updates": null}}"#,
+            tx_events.join(", "),
+            begin_block_events.join(", ")
+        );
+        let block_results: BlockResultsResponse =
+            serde_json::from_str(&response_str).expect("invalid response str");
+
+        let alice = StakedStateAddress::from_str(alice).unwrap();
+        let changes = block_results
+            .staking_changes(&|address| *address == alice)
+            .unwrap();
+        assert_eq!(
+            vec![
+                StakingChange {
+                    staking_address: alice,
+                    op_type: StakingOpType::Slash,
+                    bonded: Some(StakingCoinDiff::Decrease(Coin::new(10).unwrap())),
+                    unbonded: Some(StakingCoinDiff::Decrease(Coin::zero())),
+                    unbonded_from: None,
+                    jailed_until: None,
+                    reason: Some("ByzantineFault".to_owned()),
+                },
+                StakingChange {
+                    staking_address: alice,
+                    op_type: StakingOpType::Jail,
+                    bonded: None,
+                    unbonded: None,
+                    unbonded_from: None,
+                    jailed_until: Some(1_587_071_014),
+                    reason: None,
+                },
+                StakingChange {
+                    staking_address: alice,
+                    op_type: StakingOpType::Unjail,
+                    bonded: None,
+                    unbonded: None,
+                    unbonded_from: None,
+                    jailed_until: None,
+                    reason: None,
+                },
+            ],
+            changes
+        );
+    }
+
     #[test]
     fn check_wrong_id() {
         let response_str = r#"{"height": "38", "txs_results": [{"code": 0, "data": null, "log": "", "info": "", "gasWanted": "0", "gasUsed": "0", "events": [{"type": "valid_txs", "attributes": [{"key": "dHhpZA==", "value": "kOzcmhZgAAaw5riwRjjKNe+foJEiDAOObTDQ="}]}], "codespace": ""}], "begin_block_events": null, "end_block_events": [{"type": "block_filter", "attributes": [{"key": "ZXRoYmxvb20=", "value": "AAAAAAAAAAAAAAAAAgAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAEAAAAAAAAAAAAAAAAAAAAAAAAAAAAACAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAgAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAQAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABAAAAAA=="}]}], "validator_updates": null, "consensus_param_updates": null}"#;
@@ -355,6 +724,7 @@ mod tests {
             consensus_param_updates: None,
         };
         assert_eq!(0, block_results.fees().unwrap().len());
+        assert_eq!(None, block_results.raw_gcs_filter().unwrap());
     }
 
     mod find_event_attribute_by_key {
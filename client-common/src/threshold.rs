@@ -0,0 +1,1248 @@
+//! Threshold (FROST-style) Schnorr signatures for large m-of-n multi-sig addresses
+//!
+//! A [`MultiSigAddress`](crate::MultiSigAddress) commits to every combination of `m` public keys, which limits it to
+//! small committees. Here the `n` participants instead run a distributed key generation and the single aggregated
+//! x-only public key is the only leaf of the `OrTree`. Any `m` participants can then produce a BIP340 signature for
+//! this key:
+//!
+//! 1. Key generation (once): every participant broadcasts a [`KeyGenRound1`] (commitments to its secret polynomial
+//!    with a proof of knowledge of its secret) and then sends an encrypted [`KeyGenShare`] to every other
+//!    participant. Every participant finishes with its own [`ThresholdKey`].
+//! 2. Signing (for each transaction): every signer generates [`SigningNonces`] and broadcasts the corresponding
+//!    [`SigningCommitment`]. Given the commitments of the signing set, every signer produces a [`SignatureShare`].
+//!    Shares are verified on arrival and aggregated into the final signature.
+//!
+//! Participants are identified by their public keys: the index of a participant is its position (starting from 1) in
+//! the sorted list of participant public keys.
+//!
+//! Secret scalars (polynomial coefficients, secret shares and nonces) are only combined with the constant-time tweak
+//! operations of secp256k1 and are zeroized on drop. Big integer arithmetic, which is not constant-time, is only used
+//! for public values (indexes, lagrange coefficients, challenges and binding factors).
+use std::fmt;
+
+use num_bigint::BigUint;
+use parity_scale_codec::{Decode, Encode};
+use secp256k1::key::XOnlyPublicKey;
+use secp256k1::schnorrsig::{schnorr_verify, SchnorrSignature};
+use secp256k1::{constants::CURVE_ORDER, Message, PublicKey as SecpPublicKey, SecretKey};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+use chain_core::common::{MerkleTree, Proof, H256};
+use chain_core::tx::data::address::ExtendedAddr;
+use chain_core::tx::witness::tree::RawXOnlyPubkey;
+
+use crate::{Error, ErrorKind, PrivateKey, PublicKey, Result, ResultExt};
+
+/// Index of a participant (starting from 1)
+pub type ParticipantIndex = u16;
+
+/// Parameters of a threshold key
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct ThresholdParameters {
+    /// Number of required signers
+    pub threshold: u16,
+    /// Public keys of all the participants (sorted)
+    pub participants: Vec<PublicKey>,
+}
+
+impl ThresholdParameters {
+    /// Creates new threshold parameters (`threshold`-of-`participants.len()`)
+    pub fn new(threshold: u16, mut participants: Vec<PublicKey>) -> Result<Self> {
+        participants.sort();
+        participants.dedup();
+
+        if participants.len() < 2 || participants.len() > ParticipantIndex::max_value() as usize {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Number of distinct participants should be between 2 and 65535",
+            ));
+        }
+        if threshold < 1 || threshold as usize > participants.len() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Threshold should be between 1 and the number of participants",
+            ));
+        }
+
+        Ok(Self {
+            threshold,
+            participants,
+        })
+    }
+
+    /// Returns index of participant with given public key
+    pub fn index_of(&self, public_key: &PublicKey) -> Result<ParticipantIndex> {
+        self.participants
+            .binary_search(public_key)
+            .map(|position| (position + 1) as ParticipantIndex)
+            .map_err(|_| {
+                Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Participant with public key ({}) not found", public_key),
+                )
+            })
+    }
+
+    /// Returns public key of participant with given index
+    pub fn participant(&self, index: ParticipantIndex) -> Result<&PublicKey> {
+        if index == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Participant index starts from 1",
+            ));
+        }
+        self.participants.get(index as usize - 1).chain(|| {
+            (
+                ErrorKind::InvalidInput,
+                format!("Participant with index ({}) not found", index),
+            )
+        })
+    }
+
+    fn indices(&self) -> impl Iterator<Item = ParticipantIndex> {
+        1..=(self.participants.len() as ParticipantIndex)
+    }
+}
+
+/// A (non-zero) secret scalar which is zeroized on drop
+#[derive(Clone, Encode, Decode)]
+struct SecretScalar(H256);
+
+impl SecretScalar {
+    fn random() -> Result<Self> {
+        Ok(Self::from_private_key(&PrivateKey::new()?))
+    }
+
+    fn from_private_key(private_key: &PrivateKey) -> Self {
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&private_key.serialize());
+        SecretScalar(bytes)
+    }
+
+    /// Applies a (constant-time) tweak operation of secp256k1 and returns the result
+    fn tweak<F>(&self, operation: F) -> Result<Self>
+    where
+        F: FnOnce(&mut SecretKey) -> std::result::Result<(), secp256k1::Error>,
+    {
+        let mut secret_key = SecretKey::from_slice(&self.0)
+            .chain(|| (ErrorKind::MultiSigError, "Invalid secret scalar"))?;
+        let result = operation(&mut secret_key).map(|_| {
+            let mut bytes = [0u8; 32];
+            bytes.copy_from_slice(&secret_key[..]);
+            SecretScalar(bytes)
+        });
+        secret_key.zeroize();
+        result.chain(|| (ErrorKind::MultiSigError, "Invalid secret scalar operation"))
+    }
+
+    /// Returns `self + other`
+    fn add(&self, other: &SecretScalar) -> Result<Self> {
+        self.tweak(|secret_key| secret_key.add_assign(&other.0))
+    }
+
+    /// Returns `self * factor` (for a public factor)
+    fn mul(&self, factor: &BigUint) -> Result<Self> {
+        self.tweak(|secret_key| secret_key.mul_assign(&scalar_to_bytes(factor)))
+    }
+
+    /// Returns `-self`
+    fn negate(&self) -> Result<Self> {
+        self.mul(&(curve_order() - BigUint::from(1u8)))
+    }
+
+    /// Returns the public point of this scalar
+    fn public(&self) -> Result<SecpPublicKey> {
+        let mut secret_key = SecretKey::from_slice(&self.0)
+            .chain(|| (ErrorKind::MultiSigError, "Invalid secret scalar"))?;
+        let public_key = SecpPublicKey::from_secret_key(secp256k1::SECP256K1, &secret_key);
+        secret_key.zeroize();
+        Ok(public_key)
+    }
+}
+
+impl fmt::Debug for SecretScalar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretScalar(***)")
+    }
+}
+
+impl Drop for SecretScalar {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// First round message of distributed key generation (broadcast to all the participants)
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct KeyGenRound1 {
+    /// Index of sender
+    pub sender: ParticipantIndex,
+    /// Commitments to the coefficients of the secret polynomial of sender
+    pub commitments: Vec<PublicKey>,
+    /// Nonce of the proof of knowledge of the secret of sender
+    pub proof_nonce: PublicKey,
+    /// Response of the proof of knowledge of the secret of sender
+    pub proof_response: H256,
+}
+
+/// Second round message of distributed key generation (sent to `receiver` only; the share is encrypted with a key
+/// shared by sender and receiver)
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct KeyGenShare {
+    /// Index of sender
+    pub sender: ParticipantIndex,
+    /// Index of receiver
+    pub receiver: ParticipantIndex,
+    /// Encrypted secret share
+    pub encrypted_share: H256,
+}
+
+/// State of a participant during distributed key generation. It holds secrets and is never persisted: if it is lost,
+/// the key generation has to be restarted.
+pub struct KeyGenParticipant {
+    context: H256,
+    parameters: ThresholdParameters,
+    index: ParticipantIndex,
+    identity_key: PrivateKey,
+    coefficients: Vec<SecretScalar>,
+}
+
+impl fmt::Debug for KeyGenParticipant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyGenParticipant")
+            .field("context", &hex::encode(&self.context))
+            .field("parameters", &self.parameters)
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+impl KeyGenParticipant {
+    /// Starts distributed key generation and returns the first round message to broadcast
+    ///
+    /// # Arguments
+    ///
+    /// - `context`: Unique identifier of this key generation (shared by all the participants)
+    /// - `parameters`: Threshold parameters
+    /// - `identity_key`: Private key of current participant (corresponding to one of the participant public keys)
+    pub fn new(
+        context: H256,
+        parameters: ThresholdParameters,
+        identity_key: PrivateKey,
+    ) -> Result<(Self, KeyGenRound1)> {
+        let index = parameters.index_of(&PublicKey::from(&identity_key))?;
+
+        let coefficients = (0..parameters.threshold)
+            .map(|_| SecretScalar::random())
+            .collect::<Result<Vec<SecretScalar>>>()?;
+        let commitments = coefficients
+            .iter()
+            .map(|coefficient| coefficient.public().map(PublicKey::from))
+            .collect::<Result<Vec<PublicKey>>>()?;
+
+        let nonce = SecretScalar::random()?;
+        let proof_nonce = PublicKey::from(nonce.public()?);
+        let challenge = keygen_challenge(&context, index, &commitments[0], &proof_nonce);
+        let proof_response = coefficients[0].mul(&challenge)?.add(&nonce)?;
+
+        let round1 = KeyGenRound1 {
+            sender: index,
+            commitments,
+            proof_nonce,
+            proof_response: proof_response.0,
+        };
+
+        let participant = KeyGenParticipant {
+            context,
+            parameters,
+            index,
+            identity_key,
+            coefficients,
+        };
+
+        Ok((participant, round1))
+    }
+
+    /// Returns index of current participant
+    pub fn index(&self) -> ParticipantIndex {
+        self.index
+    }
+
+    /// Returns threshold parameters
+    pub fn parameters(&self) -> &ThresholdParameters {
+        &self.parameters
+    }
+
+    /// Verifies first round messages of all the participants and returns the encrypted shares to send to every other
+    /// participant.
+    pub fn shares(&self, round1: &[KeyGenRound1]) -> Result<Vec<KeyGenShare>> {
+        let round1 = self.verify_round1(round1)?;
+        let own_commitment = &round1[self.index as usize - 1].commitments[0];
+
+        self.parameters
+            .indices()
+            .filter(|receiver| *receiver != self.index)
+            .map(|receiver| {
+                let share = self.evaluate(receiver)?;
+                let pad = self.share_pad(self.index, receiver, own_commitment)?;
+
+                Ok(KeyGenShare {
+                    sender: self.index,
+                    receiver,
+                    encrypted_share: xor(&share.0, &pad),
+                })
+            })
+            .collect()
+    }
+
+    /// Verifies the shares received from all the other participants and returns the threshold key of current
+    /// participant.
+    pub fn finish(self, round1: &[KeyGenRound1], shares: &[KeyGenShare]) -> Result<ThresholdKey> {
+        let round1 = self.verify_round1(round1)?;
+
+        let mut secret_share = self.evaluate(self.index)?;
+
+        for sender in self.parameters.indices().filter(|i| *i != self.index) {
+            let sender_public_key = self.parameters.participant(sender)?;
+            let mut received = shares
+                .iter()
+                .filter(|share| share.sender == sender && share.receiver == self.index);
+            let share = match (received.next(), received.next()) {
+                (Some(share), None) => share,
+                (None, _) => {
+                    return Err(Error::new(
+                        ErrorKind::MultiSigError,
+                        format!(
+                            "Missing key generation share from participant with public key ({})",
+                            sender_public_key
+                        ),
+                    ))
+                }
+                (Some(_), Some(_)) => {
+                    return Err(Error::new(
+                        ErrorKind::MultiSigError,
+                        format!(
+                            "Duplicate key generation shares from participant with public key ({})",
+                            sender_public_key
+                        ),
+                    ))
+                }
+            };
+
+            let commitments = &round1[sender as usize - 1].commitments;
+            let pad = self.share_pad(sender, self.index, &commitments[0])?;
+            let value = SecretScalar(xor(&share.encrypted_share, &pad));
+
+            if !points_equal(
+                value.public(),
+                evaluate_commitments(commitments, self.index),
+            ) {
+                return Err(Error::new(
+                    ErrorKind::MultiSigError,
+                    format!(
+                        "Invalid key generation share from participant with public key ({})",
+                        sender_public_key
+                    ),
+                ));
+            }
+
+            secret_share = secret_share.add(&value)?;
+        }
+
+        let mut group_public_key: SecpPublicKey = round1[0].commitments[0].clone().into();
+        for package in round1.iter().skip(1) {
+            group_public_key =
+                point_add(&group_public_key, &package.commitments[0].clone().into())?;
+        }
+
+        let mut verification_shares = self
+            .parameters
+            .indices()
+            .map(|index| {
+                let mut share = evaluate_commitments(&round1[0].commitments, index)?;
+                for package in round1.iter().skip(1) {
+                    share = point_add(&share, &evaluate_commitments(&package.commitments, index)?)?;
+                }
+                Ok(share)
+            })
+            .collect::<Result<Vec<SecpPublicKey>>>()?;
+
+        // BIP340 keys have an even y coordinate: negating all the shares negates the group key
+        if !has_even_y(&group_public_key) {
+            group_public_key = point_neg(&group_public_key)?;
+            secret_share = secret_share.negate()?;
+            verification_shares = verification_shares
+                .iter()
+                .map(point_neg)
+                .collect::<Result<Vec<SecpPublicKey>>>()?;
+        }
+
+        let key = ThresholdKey {
+            parameters: self.parameters.clone(),
+            index: self.index,
+            secret_share: secret_share.clone(),
+            group_public_key: group_public_key.into(),
+            verification_shares: verification_shares.into_iter().map(Into::into).collect(),
+        };
+
+        if secret_share.public()? != key.verification_share(self.index)? {
+            return Err(Error::new(
+                ErrorKind::MultiSigError,
+                "Secret share does not match its verification share",
+            ));
+        }
+
+        Ok(key)
+    }
+
+    /// Verifies first round messages and returns them ordered by sender
+    fn verify_round1<'a>(&self, round1: &'a [KeyGenRound1]) -> Result<Vec<&'a KeyGenRound1>> {
+        let order = curve_order();
+
+        self.parameters
+            .indices()
+            .map(|sender| {
+                let sender_public_key = self.parameters.participant(sender)?;
+                let mut packages = round1.iter().filter(|package| package.sender == sender);
+                let package = match (packages.next(), packages.next()) {
+                    (Some(package), None) => package,
+                    (None, _) => {
+                        return Err(Error::new(
+                            ErrorKind::MultiSigError,
+                            format!(
+                                "Missing key generation commitments from participant with public key ({})",
+                                sender_public_key
+                            ),
+                        ))
+                    }
+                    (Some(_), Some(_)) => {
+                        return Err(Error::new(
+                            ErrorKind::MultiSigError,
+                            format!(
+                                "Duplicate key generation commitments from participant with public key ({})",
+                                sender_public_key
+                            ),
+                        ))
+                    }
+                };
+
+                if package.commitments.len() != self.parameters.threshold as usize {
+                    return Err(Error::new(
+                        ErrorKind::MultiSigError,
+                        format!(
+                            "Invalid number of key generation commitments from participant with public key ({})",
+                            sender_public_key
+                        ),
+                    ));
+                }
+
+                let challenge = keygen_challenge(
+                    &self.context,
+                    sender,
+                    &package.commitments[0],
+                    &package.proof_nonce,
+                );
+                let response = BigUint::from_bytes_be(&package.proof_response);
+                let expected = point_add(
+                    &package.proof_nonce.clone().into(),
+                    &point_mul(&package.commitments[0].clone().into(), &challenge)?,
+                );
+                if response >= order || !points_equal(base_mul(&response), expected) {
+                    return Err(Error::new(
+                        ErrorKind::MultiSigError,
+                        format!(
+                            "Invalid proof of knowledge from participant with public key ({})",
+                            sender_public_key
+                        ),
+                    ));
+                }
+
+                Ok(package)
+            })
+            .collect()
+    }
+
+    /// Evaluates the secret polynomial of current participant at given index
+    fn evaluate(&self, index: ParticipantIndex) -> Result<SecretScalar> {
+        let x = BigUint::from(index);
+        let mut coefficients = self.coefficients.iter().rev();
+        let mut result = coefficients
+            .next()
+            .chain(|| (ErrorKind::InternalError, "Empty secret polynomial"))?
+            .clone();
+        for coefficient in coefficients {
+            result = result.mul(&x)?.add(coefficient)?;
+        }
+        Ok(result)
+    }
+
+    /// Returns the one-time pad used to encrypt the share sent by `sender` to `receiver`
+    fn share_pad(
+        &self,
+        sender: ParticipantIndex,
+        receiver: ParticipantIndex,
+        sender_commitment: &PublicKey,
+    ) -> Result<H256> {
+        let other = if sender == self.index {
+            receiver
+        } else {
+            sender
+        };
+        let mut shared_point: SecpPublicKey = self.parameters.participant(other)?.clone().into();
+        shared_point
+            .mul_assign(
+                secp256k1::SECP256K1,
+                &SecretScalar::from_private_key(&self.identity_key).0,
+            )
+            .chain(|| (ErrorKind::MultiSigError, "Invalid scalar multiplication"))?;
+
+        let mut data = Vec::with_capacity(32 + 4 + 33 + 33);
+        data.extend_from_slice(&self.context);
+        data.extend_from_slice(&sender.to_be_bytes());
+        data.extend_from_slice(&receiver.to_be_bytes());
+        data.extend_from_slice(&sender_commitment.serialize());
+        data.extend_from_slice(&shared_point.serialize());
+
+        Ok(tagged_hash(b"CRO/threshold/share", &data))
+    }
+}
+
+/// Threshold key share of a participant
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct ThresholdKey {
+    /// Threshold parameters
+    pub parameters: ThresholdParameters,
+    /// Index of current participant
+    pub index: ParticipantIndex,
+    secret_share: SecretScalar,
+    /// Aggregated public key (with an even y coordinate)
+    pub group_public_key: PublicKey,
+    /// Public verification shares of all the participants (ordered by index)
+    pub verification_shares: Vec<PublicKey>,
+}
+
+impl ThresholdKey {
+    /// Returns the aggregated x-only public key
+    pub fn raw_public_key(&self) -> RawXOnlyPubkey {
+        RawXOnlyPubkey::from(x_only(&self.group_public_key.clone().into()))
+    }
+
+    /// Returns the `OrTree` with the aggregated public key as its only leaf
+    pub fn merkle_tree(&self) -> MerkleTree<RawXOnlyPubkey> {
+        MerkleTree::new(vec![self.raw_public_key()])
+    }
+
+    /// Returns root hash of the underlying MerkleTree
+    pub fn root_hash(&self) -> H256 {
+        self.merkle_tree().root_hash()
+    }
+
+    /// Generates inclusion proof of the aggregated public key
+    pub fn generate_proof(&self) -> Result<Proof<RawXOnlyPubkey>> {
+        self.merkle_tree()
+            .generate_proof(self.raw_public_key())
+            .chain(|| {
+                (
+                    ErrorKind::InternalError,
+                    "Unable to generate proof of threshold public key",
+                )
+            })
+    }
+
+    /// Returns transfer address of this threshold key
+    pub fn to_extended_addr(&self) -> ExtendedAddr {
+        ExtendedAddr::OrTree(self.root_hash())
+    }
+
+    /// Returns public key of current participant
+    pub fn public_key(&self) -> &PublicKey {
+        &self.parameters.participants[self.index as usize - 1]
+    }
+
+    /// Generates a partial signature of given message with the nonces of current signer. The nonces are consumed so
+    /// that they are never used twice.
+    pub fn sign(
+        &self,
+        nonces: SigningNonces,
+        message: &H256,
+        commitments: &[SigningCommitment],
+    ) -> Result<SignatureShare> {
+        if nonces.signer != self.index {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Signing nonces do not belong to current participant",
+            ));
+        }
+
+        let own_commitment = nonces.commitment()?;
+        if !commitments.contains(&own_commitment) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Signing commitments do not contain the commitment of current participant",
+            ));
+        }
+
+        let signing = self.signing_package(message, commitments)?;
+        let binding_factor = signing.binding_factor(self.index)?;
+
+        let mut nonce = nonces.binding.mul(binding_factor)?.add(&nonces.hiding)?;
+        if signing.negated {
+            nonce = nonce.negate()?;
+        }
+
+        let lambda = lagrange_coefficient(self.index, &signing.signers())?;
+        let share = self
+            .secret_share
+            .mul(&((lambda * &signing.challenge) % curve_order()))?
+            .add(&nonce)?;
+
+        Ok(SignatureShare {
+            signer: self.index,
+            share: share.0,
+        })
+    }
+
+    /// Verifies a partial signature of given message
+    pub fn verify_share(
+        &self,
+        message: &H256,
+        commitments: &[SigningCommitment],
+        share: &SignatureShare,
+    ) -> Result<()> {
+        let signing = self.signing_package(message, commitments)?;
+        self.verify_share_with(&signing, share)
+    }
+
+    /// Verifies all the partial signatures and aggregates them into the final signature
+    pub fn aggregate(
+        &self,
+        message: &H256,
+        commitments: &[SigningCommitment],
+        shares: &[SignatureShare],
+    ) -> Result<SchnorrSignature> {
+        let signing = self.signing_package(message, commitments)?;
+        let order = curve_order();
+
+        let mut signature = BigUint::from(0u8);
+        for signer in signing.signers() {
+            let mut received = shares.iter().filter(|share| share.signer == signer);
+            let share = match (received.next(), received.next()) {
+                (Some(share), None) => share,
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::MultiSigError,
+                        format!(
+                        "Expected exactly one partial signature from signer with public key ({})",
+                        self.parameters.participant(signer)?
+                    ),
+                    ))
+                }
+            };
+
+            self.verify_share_with(&signing, share)?;
+            signature = (signature + scalar_from_bytes(&share.share)) % &order;
+        }
+        if shares.len() != signing.commitments.len() {
+            return Err(Error::new(
+                ErrorKind::MultiSigError,
+                "Received partial signatures from signers outside of the signing set",
+            ));
+        }
+
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&x_only(&signing.group_commitment));
+        bytes[32..].copy_from_slice(&scalar_to_bytes(&signature));
+
+        let signature = SchnorrSignature::from_default(&bytes).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Unable to deserialize aggregated signature",
+            )
+        })?;
+
+        let public_key =
+            XOnlyPublicKey::from_slice(self.raw_public_key().as_bytes()).chain(|| {
+                (
+                    ErrorKind::DeserializationError,
+                    "Unable to deserialize threshold public key",
+                )
+            })?;
+        let message = Message::from_slice(message).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Unable to deserialize message to sign",
+            )
+        })?;
+        schnorr_verify(secp256k1::SECP256K1, &message, &signature, &public_key).chain(|| {
+            (
+                ErrorKind::MultiSigError,
+                "Aggregated threshold signature is invalid",
+            )
+        })?;
+
+        Ok(signature)
+    }
+
+    fn verify_share_with(&self, signing: &SigningPackage, share: &SignatureShare) -> Result<()> {
+        let signer_public_key = self.parameters.participant(share.signer)?;
+        let commitment = signing.commitment(share.signer).chain(|| {
+            (
+                ErrorKind::MultiSigError,
+                format!(
+                    "Signer with public key ({}) is not part of the signing set",
+                    signer_public_key
+                ),
+            )
+        })?;
+
+        let value = BigUint::from_bytes_be(&share.share);
+        let binding_factor = signing.binding_factor(share.signer)?;
+        let lambda = lagrange_coefficient(share.signer, &signing.signers())?;
+
+        let mut nonce_commitment = point_add(
+            &commitment.hiding.clone().into(),
+            &point_mul(&commitment.binding.clone().into(), &binding_factor)?,
+        )?;
+        if signing.negated {
+            nonce_commitment = point_neg(&nonce_commitment)?;
+        }
+        let expected = point_add(
+            &nonce_commitment,
+            &point_mul(
+                &self.verification_share(share.signer)?,
+                &((lambda * &signing.challenge) % curve_order()),
+            )?,
+        );
+
+        if value >= curve_order() || !points_equal(base_mul(&value), expected) {
+            return Err(Error::new(
+                ErrorKind::MultiSigError,
+                format!(
+                    "Invalid partial signature from signer with public key ({})",
+                    signer_public_key
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn verification_share(&self, index: ParticipantIndex) -> Result<SecpPublicKey> {
+        self.parameters.participant(index)?;
+        self.verification_shares
+            .get(index as usize - 1)
+            .map(|share| share.clone().into())
+            .chain(|| (ErrorKind::InternalError, "Missing verification share"))
+    }
+
+    fn signing_package<'a>(
+        &self,
+        message: &H256,
+        commitments: &'a [SigningCommitment],
+    ) -> Result<SigningPackage<'a>> {
+        let mut sorted = commitments.iter().collect::<Vec<&SigningCommitment>>();
+        sorted.sort_by_key(|commitment| commitment.signer);
+
+        for window in sorted.windows(2) {
+            if window[0].signer == window[1].signer {
+                return Err(Error::new(
+                    ErrorKind::MultiSigError,
+                    format!(
+                        "Duplicate signing commitments from signer with public key ({})",
+                        self.parameters.participant(window[0].signer)?
+                    ),
+                ));
+            }
+        }
+        for commitment in sorted.iter() {
+            self.parameters.participant(commitment.signer)?;
+        }
+        if sorted.len() < self.parameters.threshold as usize {
+            return Err(Error::new(
+                ErrorKind::MultiSigError,
+                format!(
+                    "At least {} signers are required",
+                    self.parameters.threshold
+                ),
+            ));
+        }
+
+        let mut encoded = Vec::new();
+        encoded.extend_from_slice(message);
+        encoded.extend_from_slice(&self.group_public_key.serialize());
+        for commitment in sorted.iter() {
+            encoded.extend_from_slice(&commitment.encode());
+        }
+
+        let binding_factors = sorted
+            .iter()
+            .map(|commitment| {
+                let mut data = encoded.clone();
+                data.extend_from_slice(&commitment.signer.to_be_bytes());
+                hash_to_scalar(b"CRO/threshold/binding", &data)
+            })
+            .collect::<Vec<BigUint>>();
+
+        let mut group_commitment: Option<SecpPublicKey> = None;
+        for (commitment, binding_factor) in sorted.iter().zip(binding_factors.iter()) {
+            let signer_commitment = point_add(
+                &commitment.hiding.clone().into(),
+                &point_mul(&commitment.binding.clone().into(), binding_factor)?,
+            )?;
+            group_commitment = Some(match group_commitment {
+                None => signer_commitment,
+                Some(point) => point_add(&point, &signer_commitment)?,
+            });
+        }
+        let mut group_commitment =
+            group_commitment.chain(|| (ErrorKind::MultiSigError, "Empty signing set"))?;
+
+        // BIP340 nonces have an even y coordinate: signers negate their nonces otherwise
+        let negated = !has_even_y(&group_commitment);
+        if negated {
+            group_commitment = point_neg(&group_commitment)?;
+        }
+
+        let mut data = Vec::with_capacity(32 + 32 + 32);
+        data.extend_from_slice(&x_only(&group_commitment));
+        data.extend_from_slice(self.raw_public_key().as_bytes());
+        data.extend_from_slice(message);
+        let challenge = hash_to_scalar(b"BIP0340/challenge", &data);
+
+        Ok(SigningPackage {
+            commitments: sorted,
+            binding_factors,
+            group_commitment,
+            negated,
+            challenge,
+        })
+    }
+}
+
+/// Secret nonces of a signer for one signing session. They are never encoded or persisted and are consumed by
+/// [`ThresholdKey::sign`].
+pub struct SigningNonces {
+    signer: ParticipantIndex,
+    hiding: SecretScalar,
+    binding: SecretScalar,
+}
+
+impl fmt::Debug for SigningNonces {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SigningNonces({}, ***)", self.signer)
+    }
+}
+
+impl SigningNonces {
+    /// Generates new random nonces for given signer and returns them along with their public commitment
+    pub fn new(signer: ParticipantIndex) -> Result<(Self, SigningCommitment)> {
+        let nonces = SigningNonces {
+            signer,
+            hiding: SecretScalar::random()?,
+            binding: SecretScalar::random()?,
+        };
+        let commitment = nonces.commitment()?;
+        Ok((nonces, commitment))
+    }
+
+    /// Returns public commitment of these nonces
+    pub fn commitment(&self) -> Result<SigningCommitment> {
+        Ok(SigningCommitment {
+            signer: self.signer,
+            hiding: self.hiding.public()?.into(),
+            binding: self.binding.public()?.into(),
+        })
+    }
+}
+
+/// Public commitment to the nonces of a signer (broadcast to all the signers)
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct SigningCommitment {
+    /// Index of signer
+    pub signer: ParticipantIndex,
+    /// Commitment to the hiding nonce
+    pub hiding: PublicKey,
+    /// Commitment to the binding nonce
+    pub binding: PublicKey,
+}
+
+/// Partial signature of a signer
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct SignatureShare {
+    /// Index of signer
+    pub signer: ParticipantIndex,
+    /// Partial signature
+    pub share: H256,
+}
+
+struct SigningPackage<'a> {
+    commitments: Vec<&'a SigningCommitment>,
+    binding_factors: Vec<BigUint>,
+    group_commitment: SecpPublicKey,
+    negated: bool,
+    challenge: BigUint,
+}
+
+impl<'a> SigningPackage<'a> {
+    fn signers(&self) -> Vec<ParticipantIndex> {
+        self.commitments
+            .iter()
+            .map(|commitment| commitment.signer)
+            .collect()
+    }
+
+    fn position(&self, signer: ParticipantIndex) -> Option<usize> {
+        self.commitments
+            .iter()
+            .position(|commitment| commitment.signer == signer)
+    }
+
+    fn commitment(&self, signer: ParticipantIndex) -> Option<&'a SigningCommitment> {
+        self.position(signer)
+            .map(|position| self.commitments[position])
+    }
+
+    fn binding_factor(&self, signer: ParticipantIndex) -> Result<&BigUint> {
+        self.position(signer)
+            .map(|position| &self.binding_factors[position])
+            .chain(|| {
+                (
+                    ErrorKind::MultiSigError,
+                    format!(
+                        "Signer with index ({}) is not part of the signing set",
+                        signer
+                    ),
+                )
+            })
+    }
+}
+
+/// Returns lagrange coefficient of given signer in the signing set (at `x = 0`)
+fn lagrange_coefficient(signer: ParticipantIndex, signers: &[ParticipantIndex]) -> Result<BigUint> {
+    let order = curve_order();
+    let x_i = BigUint::from(signer);
+
+    let mut numerator = BigUint::from(1u8);
+    let mut denominator = BigUint::from(1u8);
+    for x_j in signers
+        .iter()
+        .filter(|index| **index != signer)
+        .map(|index| BigUint::from(*index))
+    {
+        numerator = (numerator * &x_j) % &order;
+        denominator = (denominator * ((&x_j + &order - &x_i) % &order)) % &order;
+    }
+
+    if denominator == BigUint::from(0u8) {
+        return Err(Error::new(
+            ErrorKind::MultiSigError,
+            "Duplicate signers in signing set",
+        ));
+    }
+
+    let inverse = denominator.modpow(&(&order - BigUint::from(2u8)), &order);
+    Ok((numerator * inverse) % order)
+}
+
+/// Evaluates committed polynomial at given index: `sum(C_k * index^k)`
+fn evaluate_commitments(
+    commitments: &[PublicKey],
+    index: ParticipantIndex,
+) -> Result<SecpPublicKey> {
+    let order = curve_order();
+    let x = BigUint::from(index);
+
+    let mut power = BigUint::from(1u8);
+    let mut result: Option<SecpPublicKey> = None;
+    for commitment in commitments {
+        let term = point_mul(&commitment.clone().into(), &power)?;
+        result = Some(match result {
+            None => term,
+            Some(point) => point_add(&point, &term)?,
+        });
+        power = (power * &x) % &order;
+    }
+
+    result.chain(|| (ErrorKind::InvalidInput, "Empty polynomial commitments"))
+}
+
+fn keygen_challenge(
+    context: &H256,
+    index: ParticipantIndex,
+    commitment: &PublicKey,
+    proof_nonce: &PublicKey,
+) -> BigUint {
+    let mut data = Vec::with_capacity(32 + 2 + 33 + 33);
+    data.extend_from_slice(context);
+    data.extend_from_slice(&index.to_be_bytes());
+    data.extend_from_slice(&commitment.serialize());
+    data.extend_from_slice(&proof_nonce.serialize());
+    hash_to_scalar(b"CRO/threshold/keygen", &data)
+}
+
+/// BIP340 tagged hash: `sha256(sha256(tag) || sha256(tag) || data)`
+fn tagged_hash(tag: &[u8], data: &[u8]) -> H256 {
+    let tag_hash = Sha256::digest(tag);
+    let mut hasher = Sha256::new();
+    hasher.update(&tag_hash);
+    hasher.update(&tag_hash);
+    hasher.update(data);
+
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&hasher.finalize());
+    hash
+}
+
+fn hash_to_scalar(tag: &[u8], data: &[u8]) -> BigUint {
+    scalar_from_bytes(&tagged_hash(tag, data))
+}
+
+fn curve_order() -> BigUint {
+    BigUint::from_bytes_be(&CURVE_ORDER)
+}
+
+fn scalar_from_bytes(bytes: &[u8]) -> BigUint {
+    BigUint::from_bytes_be(bytes) % curve_order()
+}
+
+fn scalar_to_bytes(scalar: &BigUint) -> H256 {
+    let bytes = scalar.to_bytes_be();
+    let mut result = [0u8; 32];
+    result[32 - bytes.len()..].copy_from_slice(&bytes);
+    result
+}
+
+fn xor(a: &H256, b: &H256) -> H256 {
+    let mut result = [0u8; 32];
+    for (i, byte) in result.iter_mut().enumerate() {
+        *byte = a[i] ^ b[i];
+    }
+    result
+}
+
+fn base_mul(scalar: &BigUint) -> Result<SecpPublicKey> {
+    let secret_key = SecretKey::from_slice(&scalar_to_bytes(scalar))
+        .chain(|| (ErrorKind::MultiSigError, "Invalid scalar"))?;
+    Ok(SecpPublicKey::from_secret_key(
+        secp256k1::SECP256K1,
+        &secret_key,
+    ))
+}
+
+fn point_mul(point: &SecpPublicKey, scalar: &BigUint) -> Result<SecpPublicKey> {
+    let mut result = *point;
+    result
+        .mul_assign(secp256k1::SECP256K1, &scalar_to_bytes(scalar))
+        .chain(|| (ErrorKind::MultiSigError, "Invalid scalar multiplication"))?;
+    Ok(result)
+}
+
+fn point_add(a: &SecpPublicKey, b: &SecpPublicKey) -> Result<SecpPublicKey> {
+    a.combine(b)
+        .chain(|| (ErrorKind::MultiSigError, "Invalid point addition"))
+}
+
+fn point_neg(point: &SecpPublicKey) -> Result<SecpPublicKey> {
+    point_mul(point, &(curve_order() - BigUint::from(1u8)))
+}
+
+/// Returns true if both points are valid and equal
+fn points_equal(a: Result<SecpPublicKey>, b: Result<SecpPublicKey>) -> bool {
+    match (a, b) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn has_even_y(point: &SecpPublicKey) -> bool {
+    point.serialize()[0] == 0x02
+}
+
+fn x_only(point: &SecpPublicKey) -> H256 {
+    let mut result = [0u8; 32];
+    result.copy_from_slice(&point.serialize()[1..]);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keygen(threshold: u16, n: usize) -> (Vec<PrivateKey>, Vec<ThresholdKey>) {
+        let identity_keys = (0..n)
+            .map(|_| PrivateKey::new().unwrap())
+            .collect::<Vec<PrivateKey>>();
+        let parameters = ThresholdParameters::new(
+            threshold,
+            identity_keys.iter().map(PublicKey::from).collect(),
+        )
+        .unwrap();
+
+        let (participants, round1): (Vec<KeyGenParticipant>, Vec<KeyGenRound1>) = identity_keys
+            .iter()
+            .map(|key| KeyGenParticipant::new([7u8; 32], parameters.clone(), key.clone()).unwrap())
+            .unzip();
+        let shares = participants
+            .iter()
+            .flat_map(|participant| participant.shares(&round1).unwrap())
+            .collect::<Vec<KeyGenShare>>();
+        let mut keys = participants
+            .into_iter()
+            .map(|participant| participant.finish(&round1, &shares).unwrap())
+            .collect::<Vec<ThresholdKey>>();
+        keys.sort_by_key(|key| key.index);
+
+        (identity_keys, keys)
+    }
+
+    fn sign(keys: &[&ThresholdKey], message: &H256) -> Result<SchnorrSignature> {
+        let (nonces, commitments): (Vec<SigningNonces>, Vec<SigningCommitment>) = keys
+            .iter()
+            .map(|key| SigningNonces::new(key.index).unwrap())
+            .unzip();
+        let shares = keys
+            .iter()
+            .zip(nonces.into_iter())
+            .map(|(key, nonces)| key.sign(nonces, message, &commitments))
+            .collect::<Result<Vec<SignatureShare>>>()?;
+        keys[0].aggregate(message, &commitments, &shares)
+    }
+
+    #[test]
+    fn check_threshold_key_generation_and_signing() {
+        let (_, keys) = keygen(7, 15);
+
+        for key in keys.iter() {
+            assert_eq!(keys[0].group_public_key, key.group_public_key);
+            assert_eq!(keys[0].verification_shares, key.verification_shares);
+            assert_eq!(keys[0].to_extended_addr(), key.to_extended_addr());
+        }
+        assert!(has_even_y(&keys[0].group_public_key.clone().into()));
+        assert!(keys[0]
+            .generate_proof()
+            .unwrap()
+            .verify(&keys[0].root_hash()));
+
+        let message = [3u8; 32];
+        let signers = vec![
+            &keys[1], &keys[3], &keys[4], &keys[8], &keys[10], &keys[11], &keys[14],
+        ];
+        sign(&signers, &message).expect("Should be able to sign with threshold signers");
+
+        let signers = keys.iter().collect::<Vec<&ThresholdKey>>();
+        sign(&signers, &message).expect("Should be able to sign with all the participants");
+
+        let error = sign(&signers[..6], &message).unwrap_err();
+        assert_eq!(ErrorKind::MultiSigError, error.kind());
+    }
+
+    #[test]
+    fn check_invalid_signature_share_names_signer() {
+        let (_, keys) = keygen(2, 3);
+        let message = [3u8; 32];
+
+        let (nonces_1, commitment_1) = SigningNonces::new(keys[0].index).unwrap();
+        let (nonces_2, commitment_2) = SigningNonces::new(keys[1].index).unwrap();
+        let commitments = vec![commitment_1, commitment_2];
+
+        let share_1 = keys[0].sign(nonces_1, &message, &commitments).unwrap();
+        let mut share_2 = keys[1].sign(nonces_2, &message, &commitments).unwrap();
+        keys[2]
+            .verify_share(&message, &commitments, &share_2)
+            .expect("Valid partial signature");
+
+        share_2.share[31] ^= 1;
+        let error = keys[2]
+            .aggregate(&message, &commitments, &[share_1, share_2])
+            .unwrap_err();
+        assert_eq!(ErrorKind::MultiSigError, error.kind());
+        assert!(error
+            .to_string()
+            .contains(&keys[1].public_key().to_string()));
+    }
+
+    #[test]
+    fn check_invalid_key_generation_share_names_sender() {
+        let identity_keys = (0..3)
+            .map(|_| PrivateKey::new().unwrap())
+            .collect::<Vec<PrivateKey>>();
+        let parameters =
+            ThresholdParameters::new(2, identity_keys.iter().map(PublicKey::from).collect())
+                .unwrap();
+
+        let (participants, round1): (Vec<KeyGenParticipant>, Vec<KeyGenRound1>) = identity_keys
+            .iter()
+            .map(|key| KeyGenParticipant::new([7u8; 32], parameters.clone(), key.clone()).unwrap())
+            .unzip();
+        let mut shares = participants
+            .iter()
+            .flat_map(|participant| participant.shares(&round1).unwrap())
+            .collect::<Vec<KeyGenShare>>();
+
+        let tampered = shares
+            .iter_mut()
+            .find(|share| share.sender == 1 && share.receiver == 2)
+            .unwrap();
+        tampered.encrypted_share[0] ^= 1;
+
+        let receiver = participants
+            .into_iter()
+            .find(|participant| participant.index() == 2)
+            .unwrap();
+        let error = receiver.finish(&round1, &shares).unwrap_err();
+        assert_eq!(ErrorKind::MultiSigError, error.kind());
+        assert!(error
+            .to_string()
+            .contains(&parameters.participant(1).unwrap().to_string()));
+
+        let mut invalid_round1 = round1.clone();
+        invalid_round1[0].proof_response[31] ^= 1;
+        let (participant, _) =
+            KeyGenParticipant::new([7u8; 32], parameters.clone(), identity_keys[0].clone())
+                .unwrap();
+        assert!(participant.shares(&invalid_round1).is_err());
+    }
+
+    #[test]
+    fn check_threshold_parameters() {
+        let public_keys = (0..3)
+            .map(|_| PublicKey::from(&PrivateKey::new().unwrap()))
+            .collect::<Vec<PublicKey>>();
+
+        assert!(ThresholdParameters::new(0, public_keys.clone()).is_err());
+        assert!(ThresholdParameters::new(4, public_keys.clone()).is_err());
+        assert!(ThresholdParameters::new(1, vec![public_keys[0].clone()]).is_err());
+
+        let parameters = ThresholdParameters::new(2, public_keys).unwrap();
+        for index in parameters.indices() {
+            let public_key = parameters.participant(index).unwrap();
+            assert_eq!(index, parameters.index_of(public_key).unwrap());
+        }
+        assert!(parameters.participant(0).is_err());
+        assert!(parameters.participant(4).is_err());
+    }
+
+    #[test]
+    fn check_secret_scalar_arithmetic() {
+        let a = SecretScalar::random().unwrap();
+        let b = SecretScalar::random().unwrap();
+        let order = curve_order();
+
+        let sum = a.add(&b).unwrap();
+        let expected = (scalar_from_bytes(&a.0) + scalar_from_bytes(&b.0)) % &order;
+        assert_eq!(sum.0, scalar_to_bytes(&expected));
+        assert_eq!(
+            sum.public().unwrap(),
+            point_add(&a.public().unwrap(), &b.public().unwrap()).unwrap()
+        );
+
+        let factor = BigUint::from(12345u32);
+        let product = a.mul(&factor).unwrap();
+        let expected = (scalar_from_bytes(&a.0) * &factor) % &order;
+        assert_eq!(product.0, scalar_to_bytes(&expected));
+
+        // a + (-a) is zero, which is not a valid secret scalar
+        assert!(a.add(&a.negate().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use std::net::TcpStream;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+use chain_core::common::H256;
+use chain_core::tx::data::Tx;
+use client_common::{Error, ErrorKind, PrivateKey, PublicKey, Result, ResultExt};
+
+use crate::protocol::{
+    check_signers, read_message, write_message, CoordinatorRequest, CoordinatorResponse,
+    SessionView, SignedMessage,
+};
+
+/// timeout of a request to the coordinator
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Client of a multi-sig coordinator
+#[derive(Debug, Clone)]
+pub struct CoordinatorClient {
+    address: String,
+}
+
+impl CoordinatorClient {
+    /// Creates a new client of the coordinator listening on given address (`<host>:<port>`)
+    pub fn new(address: &str) -> Self {
+        Self {
+            address: address.to_owned(),
+        }
+    }
+
+    /// Creates a new session for given unsigned transaction and returns its ID
+    ///
+    /// # Arguments
+    ///
+    /// - `private_key`: Private key of creator (corresponding to one of the signers)
+    /// - `transaction`: Unsigned transaction to sign
+    /// - `signers`: Public keys of all the signers (including creator)
+    pub fn create_session(
+        &self,
+        private_key: &PrivateKey,
+        transaction: Tx,
+        mut signers: Vec<PublicKey>,
+    ) -> Result<H256> {
+        signers.sort();
+        signers.dedup();
+        check_signers(&signers, &PublicKey::from(private_key))?;
+
+        let mut session_id = [0u8; 32];
+        OsRng.fill_bytes(&mut session_id);
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .chain(|| (ErrorKind::InternalError, "System time is before UNIX epoch"))?
+            .as_secs();
+
+        self.submit(
+            private_key,
+            CoordinatorRequest::CreateSession {
+                session_id,
+                transaction,
+                signers,
+                created_at,
+            },
+        )?;
+
+        Ok(session_id)
+    }
+
+    /// Fetches a session and verifies all its messages
+    pub fn session(&self, private_key: &PrivateKey, session_id: &H256) -> Result<SessionView> {
+        let request = CoordinatorRequest::GetSession {
+            session_id: *session_id,
+        };
+
+        match self.send(&SignedMessage::new(&request, private_key)?)? {
+            CoordinatorResponse::Session(state) => state.verify(session_id),
+            response => Err(unexpected_response(response)),
+        }
+    }
+
+    /// Sends a request which does not return anything (creation or submission)
+    pub fn submit(&self, private_key: &PrivateKey, request: CoordinatorRequest) -> Result<()> {
+        match self.send(&SignedMessage::new(&request, private_key)?)? {
+            CoordinatorResponse::Accepted => Ok(()),
+            response => Err(unexpected_response(response)),
+        }
+    }
+
+    fn send(&self, message: &SignedMessage) -> Result<CoordinatorResponse> {
+        let mut stream = TcpStream::connect(&self.address).chain(|| {
+            (
+                ErrorKind::ConnectionError,
+                format!("Unable to connect to coordinator at {}", self.address),
+            )
+        })?;
+        stream
+            .set_read_timeout(Some(TIMEOUT))
+            .and_then(|_| stream.set_write_timeout(Some(TIMEOUT)))
+            .chain(|| (ErrorKind::IoError, "Unable to set coordinator timeout"))?;
+
+        write_message(&mut stream, message)?;
+        read_message(&mut stream)
+    }
+}
+
+fn unexpected_response(response: CoordinatorResponse) -> Error {
+    match response {
+        CoordinatorResponse::Error(message) => Error::new(
+            ErrorKind::MultiSigError,
+            format!("Coordinator error: {}", message),
+        ),
+        _ => Error::new(
+            ErrorKind::MultiSigError,
+            "Unexpected response from coordinator",
+        ),
+    }
+}
@@ -0,0 +1,57 @@
+#![deny(missing_docs, unsafe_code, unstable_features)]
+//! Coordination server for multi-sig signing sessions
+//!
+//! Instead of shuttling nonce commitments, nonces and partial signatures between co-signers by
+//! hand, the creator of a session posts the unsigned transaction to a coordinator. Co-signers
+//! fetch the session, review the transaction and submit their messages of every round. Once all
+//! the partial signatures are available, any co-signer assembles the final signature, broadcasts
+//! the transaction and reports it to the coordinator.
+//!
+//! The coordinator only relays messages: it never holds any secret and it can not forge any
+//! message, as every message is signed by the key of the co-signer who sent it and is verified
+//! again by every other co-signer.
+//!
+//! # Transport
+//!
+//! The coordinator listens on a TCP socket. Every connection carries one request answered by one
+//! response. Messages are JSON objects, each one prefixed with its length (`u32`, big endian, at
+//! most 1 MiB). Connections are closed after 10 seconds without progress, and at most 64 are
+//! served at the same time.
+//!
+//! # Authentication
+//!
+//! Requests are sent as `{"payload":"<request>","public_key":"<hex>","signature":"<hex>"}` where
+//! `payload` is the JSON request as a string and `signature` is the schnorr signature of
+//! `SHA256("CRO/coordinator/message" || payload)` with the key of the co-signer. A co-signer can
+//! only create or access a session if its public key is one of the signers of the session.
+//!
+//! The coordinator can be restricted to the sessions created by some keys, and it limits the
+//! number of sessions per creator. A session expires after the time to live of the coordinator
+//! counted from the `created_at` time of its creation request, which is rejected if it is in the
+//! future (beyond a small clock drift) or already expired, so that it can not be replayed.
+//!
+//! # Requests
+//!
+//! - `{"method":"create_session","params":{"session_id":"<hex>","transaction":<Tx>,"signers":[...],"created_at":<seconds>}}`
+//! - `{"method":"get_session","params":{"session_id":"<hex>"}}`
+//! - `{"method":"submit_nonce_commitment","params":{"session_id":"<hex>","nonce_commitment":"<hex>"}}`
+//! - `{"method":"submit_nonce","params":{"session_id":"<hex>","nonce":"<hex>"}}`
+//! - `{"method":"submit_partial_signature","params":{"session_id":"<hex>","partial_signature":"<hex>"}}`
+//! - `{"method":"submit_broadcast","params":{"session_id":"<hex>","transaction_id":"<hex>"}}`
+//!
+//! # Responses
+//!
+//! - `{"result":"session","value":{"creation":<signed message>,"submissions":[<signed message>]}}`
+//! - `{"result":"accepted"}`
+//! - `{"result":"error","value":"<message>"}`
+mod client;
+mod protocol;
+mod server;
+
+pub use self::client::CoordinatorClient;
+pub use self::protocol::{
+    CoordinatorRequest, CoordinatorResponse, SessionState, SessionView, SignedMessage,
+};
+pub use self::server::{
+    CoordinatorServer, DEFAULT_SESSION_TTL, MAX_CONNECTIONS, MAX_SESSIONS, MAX_SESSIONS_PER_CREATOR,
+};
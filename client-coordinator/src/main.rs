@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use structopt::StructOpt;
+
+use client_common::PublicKey;
+use client_coordinator::CoordinatorServer;
+
+#[derive(StructOpt, Debug)]
+#[structopt(
+    name = "client-coordinator",
+    about = "Coordination server relaying the messages of multi-sig signing sessions"
+)]
+struct Options {
+    #[structopt(
+        name = "host",
+        short,
+        long,
+        default_value = "0.0.0.0",
+        help = "Coordinator hostname"
+    )]
+    host: String,
+
+    #[structopt(
+        name = "port",
+        short,
+        long,
+        default_value = "9982",
+        help = "Coordinator port"
+    )]
+    port: u16,
+
+    #[structopt(
+        name = "session-ttl",
+        long,
+        default_value = "3600",
+        help = "Time to live of sessions (in seconds)"
+    )]
+    session_ttl: u64,
+
+    #[structopt(
+        name = "allowed-creator",
+        long,
+        help = "Public key allowed to create sessions (any key if none is given, can be repeated)"
+    )]
+    allowed_creators: Vec<PublicKey>,
+}
+
+fn main() {
+    env_logger::init();
+    let options = Options::from_args();
+    let address = format!("{}:{}", options.host, options.port);
+
+    let server = options.allowed_creators.into_iter().fold(
+        CoordinatorServer::default().with_session_ttl(Duration::from_secs(options.session_ttl)),
+        CoordinatorServer::with_allowed_creator,
+    );
+    let result = CoordinatorServer::bind(&address).and_then(|listener| {
+        log::info!("coordinator listening on {}", address);
+        server.serve(listener)
+    });
+
+    if let Err(e) = result {
+        log::error!("coordinator stopped: {}", e);
+        std::process::exit(1);
+    }
+}
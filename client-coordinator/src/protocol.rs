@@ -0,0 +1,575 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+use rand::rngs::OsRng;
+use secp256k1::key::XOnlyPublicKey;
+use secp256k1::schnorrsig::{schnorr_sign, schnorr_verify, SchnorrSignature};
+use secp256k1::{Message, PublicKey as SecpPublicKey, SecretKey};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+
+use chain_core::common::{H256, HASH_SIZE_256};
+use chain_core::tx::data::{Tx, TxId};
+use chain_core::tx::TransactionId;
+use client_common::{Error, ErrorKind, PrivateKey, PublicKey, Result, ResultExt};
+
+/// maximum length of a message
+const MAX_MESSAGE_LENGTH: usize = 1024 * 1024;
+/// domain separation tag of signed messages
+const MESSAGE_TAG: &[u8] = b"CRO/coordinator/message";
+
+/// Request of a co-signer (always sent as the payload of a [`SignedMessage`])
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum CoordinatorRequest {
+    /// Creates a new session for given unsigned transaction
+    CreateSession {
+        /// ID of session (chosen randomly by its creator)
+        #[serde(
+            serialize_with = "serialize_h256",
+            deserialize_with = "deserialize_h256"
+        )]
+        session_id: H256,
+        /// Unsigned transaction to sign
+        transaction: Tx,
+        /// Public keys of all the signers (including creator)
+        signers: Vec<PublicKey>,
+        /// Creation time (seconds since UNIX epoch), the session expires after the time to live of the coordinator
+        /// from this time so that the request can not be replayed
+        created_at: u64,
+    },
+    /// Returns current state of a session
+    GetSession {
+        /// ID of session
+        #[serde(
+            serialize_with = "serialize_h256",
+            deserialize_with = "deserialize_h256"
+        )]
+        session_id: H256,
+    },
+    /// Submits the nonce commitment of sender
+    SubmitNonceCommitment {
+        /// ID of session
+        #[serde(
+            serialize_with = "serialize_h256",
+            deserialize_with = "deserialize_h256"
+        )]
+        session_id: H256,
+        /// Nonce commitment
+        #[serde(
+            serialize_with = "serialize_h256",
+            deserialize_with = "deserialize_h256"
+        )]
+        nonce_commitment: H256,
+    },
+    /// Submits the nonce of sender
+    SubmitNonce {
+        /// ID of session
+        #[serde(
+            serialize_with = "serialize_h256",
+            deserialize_with = "deserialize_h256"
+        )]
+        session_id: H256,
+        /// Nonce
+        #[serde(
+            serialize_with = "serialize_h256",
+            deserialize_with = "deserialize_h256"
+        )]
+        nonce: H256,
+    },
+    /// Submits the partial signature of sender
+    SubmitPartialSignature {
+        /// ID of session
+        #[serde(
+            serialize_with = "serialize_h256",
+            deserialize_with = "deserialize_h256"
+        )]
+        session_id: H256,
+        /// Partial signature
+        #[serde(
+            serialize_with = "serialize_h256",
+            deserialize_with = "deserialize_h256"
+        )]
+        partial_signature: H256,
+    },
+    /// Reports that the signed transaction is broadcasted
+    SubmitBroadcast {
+        /// ID of session
+        #[serde(
+            serialize_with = "serialize_h256",
+            deserialize_with = "deserialize_h256"
+        )]
+        session_id: H256,
+        /// ID of broadcasted transaction
+        #[serde(
+            serialize_with = "serialize_h256",
+            deserialize_with = "deserialize_h256"
+        )]
+        transaction_id: TxId,
+    },
+}
+
+impl CoordinatorRequest {
+    /// Returns ID of the session of this request
+    pub fn session_id(&self) -> &H256 {
+        match self {
+            CoordinatorRequest::CreateSession { session_id, .. }
+            | CoordinatorRequest::GetSession { session_id }
+            | CoordinatorRequest::SubmitNonceCommitment { session_id, .. }
+            | CoordinatorRequest::SubmitNonce { session_id, .. }
+            | CoordinatorRequest::SubmitPartialSignature { session_id, .. }
+            | CoordinatorRequest::SubmitBroadcast { session_id, .. } => session_id,
+        }
+    }
+}
+
+/// Response of the coordinator
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "result", content = "value", rename_all = "snake_case")]
+pub enum CoordinatorResponse {
+    /// Current state of a session
+    Session(SessionState),
+    /// Request is accepted
+    Accepted,
+    /// Request failed
+    Error(String),
+}
+
+/// Request signed by the key of its sender
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedMessage {
+    /// JSON encoded request
+    pub payload: String,
+    /// Public key of sender
+    pub public_key: PublicKey,
+    /// Hex encoded schnorr signature of payload
+    pub signature: String,
+}
+
+impl SignedMessage {
+    /// Signs given request with given private key
+    pub fn new(request: &CoordinatorRequest, private_key: &PrivateKey) -> Result<Self> {
+        let payload = serde_json::to_string(request).chain(|| {
+            (
+                ErrorKind::SerializationError,
+                "Unable to serialize coordinator request",
+            )
+        })?;
+        let signature = schnorr_sign(
+            secp256k1::SECP256K1,
+            &message_digest(&payload)?,
+            &SecretKey::from(private_key),
+            &mut OsRng,
+        );
+
+        Ok(SignedMessage {
+            payload,
+            public_key: PublicKey::from(private_key),
+            signature: hex::encode(&signature.serialize_default()[..]),
+        })
+    }
+
+    /// Verifies the signature of this message and returns its request
+    pub fn verify(&self) -> Result<CoordinatorRequest> {
+        let signature = hex::decode(&self.signature)
+            .ok()
+            .filter(|signature| signature.len() == 64)
+            .and_then(|signature| {
+                let mut bytes = [0u8; 64];
+                bytes.copy_from_slice(&signature);
+                SchnorrSignature::from_default(&bytes).ok()
+            })
+            .chain(|| {
+                (
+                    ErrorKind::VerifyError,
+                    format!(
+                        "Invalid signature of message from public key ({})",
+                        self.public_key
+                    ),
+                )
+            })?;
+        let public_key = XOnlyPublicKey::from_pubkey(&SecpPublicKey::from(&self.public_key));
+
+        schnorr_verify(
+            secp256k1::SECP256K1,
+            &message_digest(&self.payload)?,
+            &signature,
+            &public_key,
+        )
+        .chain(|| {
+            (
+                ErrorKind::VerifyError,
+                format!(
+                    "Invalid signature of message from public key ({})",
+                    self.public_key
+                ),
+            )
+        })?;
+
+        serde_json::from_str(&self.payload).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Unable to deserialize coordinator request",
+            )
+        })
+    }
+}
+
+/// State of a session kept by the coordinator
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionState {
+    /// Signed request which created the session
+    pub creation: SignedMessage,
+    /// Signed messages submitted by the signers (in order of arrival)
+    pub submissions: Vec<SignedMessage>,
+}
+
+/// Verified view of a session
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionView {
+    /// ID of session
+    pub session_id: H256,
+    /// Unsigned transaction to sign
+    pub transaction: Tx,
+    /// Public key of the creator of session
+    pub creator: PublicKey,
+    /// Public keys of all the signers
+    pub signers: Vec<PublicKey>,
+    /// Nonce commitments received so far
+    pub nonce_commitments: BTreeMap<PublicKey, H256>,
+    /// Nonces received so far
+    pub nonces: BTreeMap<PublicKey, H256>,
+    /// Partial signatures received so far
+    pub partial_signatures: BTreeMap<PublicKey, H256>,
+    /// ID of broadcasted transaction (if any)
+    pub broadcast: Option<TxId>,
+}
+
+impl SessionState {
+    /// Verifies all the messages of this session (so that the coordinator can not forge any of them) and returns
+    /// their content
+    pub fn verify(&self, session_id: &H256) -> Result<SessionView> {
+        let (id, transaction, signers) = match self.creation.verify()? {
+            CoordinatorRequest::CreateSession {
+                session_id,
+                transaction,
+                signers,
+                ..
+            } => (session_id, transaction, signers),
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::VerifyError,
+                    "Session is not created by a create session request",
+                ))
+            }
+        };
+        if id != *session_id {
+            return Err(Error::new(
+                ErrorKind::VerifyError,
+                "Session is created with another session ID",
+            ));
+        }
+        check_signers(&signers, &self.creation.public_key)?;
+
+        let mut view = SessionView {
+            session_id: *session_id,
+            transaction,
+            creator: self.creation.public_key.clone(),
+            signers,
+            nonce_commitments: BTreeMap::new(),
+            nonces: BTreeMap::new(),
+            partial_signatures: BTreeMap::new(),
+            broadcast: None,
+        };
+
+        for submission in self.submissions.iter() {
+            view.apply(submission)?;
+        }
+
+        Ok(view)
+    }
+}
+
+impl SessionView {
+    /// Returns true if given map contains a value of every signer
+    pub fn is_complete(&self, values: &BTreeMap<PublicKey, H256>) -> bool {
+        self.signers
+            .iter()
+            .all(|signer| values.contains_key(signer))
+    }
+
+    /// Verifies a submitted message and adds it to this view
+    pub(crate) fn apply(&mut self, submission: &SignedMessage) -> Result<()> {
+        let request = submission.verify()?;
+        let sender = &submission.public_key;
+
+        if request.session_id() != &self.session_id {
+            return Err(Error::new(
+                ErrorKind::VerifyError,
+                "Message does not belong to current session",
+            ));
+        }
+
+        if !self.signers.contains(sender) {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                format!("Public key ({}) is not a signer of session", sender),
+            ));
+        }
+
+        match request {
+            CoordinatorRequest::SubmitNonceCommitment {
+                nonce_commitment, ..
+            } => insert_value(
+                &mut self.nonce_commitments,
+                sender,
+                nonce_commitment,
+                "nonce commitment",
+            ),
+            CoordinatorRequest::SubmitNonce { nonce, .. } => {
+                insert_value(&mut self.nonces, sender, nonce, "nonce")
+            }
+            CoordinatorRequest::SubmitPartialSignature {
+                partial_signature, ..
+            } => insert_value(
+                &mut self.partial_signatures,
+                sender,
+                partial_signature,
+                "partial signature",
+            ),
+            CoordinatorRequest::SubmitBroadcast { transaction_id, .. } => {
+                if transaction_id != self.transaction.id() {
+                    return Err(Error::new(
+                        ErrorKind::VerifyError,
+                        "Broadcasted transaction does not match the transaction of session",
+                    ));
+                }
+                self.broadcast = Some(transaction_id);
+                Ok(())
+            }
+            CoordinatorRequest::CreateSession { .. } | CoordinatorRequest::GetSession { .. } => {
+                Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Message is not a submission",
+                ))
+            }
+        }
+    }
+}
+
+/// Checks that signers of a session are sorted, unique and contain given public key
+pub(crate) fn check_signers(signers: &[PublicKey], public_key: &PublicKey) -> Result<()> {
+    if signers.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Signers of session should be sorted and unique",
+        ));
+    }
+
+    if !signers.contains(public_key) {
+        return Err(Error::new(
+            ErrorKind::PermissionDenied,
+            format!("Public key ({}) is not a signer of session", public_key),
+        ));
+    }
+
+    Ok(())
+}
+
+fn insert_value(
+    values: &mut BTreeMap<PublicKey, H256>,
+    sender: &PublicKey,
+    value: H256,
+    what: &str,
+) -> Result<()> {
+    match values.get(sender) {
+        Some(existing) if *existing != value => Err(Error::new(
+            ErrorKind::MultiSigError,
+            format!(
+                "Conflicting {} from signer with public key ({})",
+                what, sender
+            ),
+        )),
+        Some(_) => Ok(()),
+        None => {
+            values.insert(sender.clone(), value);
+            Ok(())
+        }
+    }
+}
+
+fn message_digest(payload: &str) -> Result<Message> {
+    let mut hasher = Sha256::new();
+    hasher.update(MESSAGE_TAG);
+    hasher.update(payload.as_bytes());
+
+    Message::from_slice(&hasher.finalize()).chain(|| {
+        (
+            ErrorKind::InternalError,
+            "Unable to create message from digest",
+        )
+    })
+}
+
+fn serialize_h256<S>(value: &H256, serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&hex::encode(value))
+}
+
+fn deserialize_h256<'de, D>(deserializer: D) -> std::result::Result<H256, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    let bytes = hex::decode(&value).map_err(serde::de::Error::custom)?;
+
+    if bytes.len() != HASH_SIZE_256 {
+        return Err(serde::de::Error::custom(format!(
+            "({}) should be a hex string of 32 bytes",
+            value
+        )));
+    }
+
+    let mut hash = [0u8; HASH_SIZE_256];
+    hash.copy_from_slice(&bytes);
+    Ok(hash)
+}
+
+pub(crate) fn write_message<W: Write, T: Serialize>(stream: &mut W, message: &T) -> Result<()> {
+    let data = serde_json::to_vec(message).chain(|| {
+        (
+            ErrorKind::SerializationError,
+            "Unable to serialize coordinator message",
+        )
+    })?;
+    stream
+        .write_all(&(data.len() as u32).to_be_bytes())
+        .and_then(|_| stream.write_all(&data))
+        .and_then(|_| stream.flush())
+        .chain(|| (ErrorKind::IoError, "Unable to send coordinator message"))
+}
+
+pub(crate) fn read_message<R: Read, T: for<'de> Deserialize<'de>>(stream: &mut R) -> Result<T> {
+    let mut length = [0u8; 4];
+    stream
+        .read_exact(&mut length)
+        .chain(|| (ErrorKind::IoError, "Unable to read coordinator message"))?;
+    let length = u32::from_be_bytes(length) as usize;
+    if length > MAX_MESSAGE_LENGTH {
+        return Err(Error::new(
+            ErrorKind::IoError,
+            "Coordinator message too long",
+        ));
+    }
+    let mut data = vec![0u8; length];
+    stream
+        .read_exact(&mut data)
+        .chain(|| (ErrorKind::IoError, "Unable to read coordinator message"))?;
+    serde_json::from_slice(&data).chain(|| {
+        (
+            ErrorKind::DeserializationError,
+            "Unable to deserialize coordinator message",
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signers(private_keys: &[PrivateKey]) -> Vec<PublicKey> {
+        let mut signers = private_keys.iter().map(PublicKey::from).collect::<Vec<_>>();
+        signers.sort();
+        signers
+    }
+
+    #[test]
+    fn check_signed_message() {
+        let private_key = PrivateKey::new().unwrap();
+        let request = CoordinatorRequest::GetSession {
+            session_id: [1u8; 32],
+        };
+
+        let mut message = SignedMessage::new(&request, &private_key).unwrap();
+        assert_eq!(request, message.verify().unwrap());
+
+        message.payload = message.payload.replace("01", "02");
+        assert_eq!(
+            ErrorKind::VerifyError,
+            message
+                .verify()
+                .expect_err("Verified forged message")
+                .kind()
+        );
+
+        let mut message = SignedMessage::new(&request, &private_key).unwrap();
+        message.public_key = PublicKey::from(&PrivateKey::new().unwrap());
+        assert_eq!(
+            ErrorKind::VerifyError,
+            message
+                .verify()
+                .expect_err("Verified message with other public key")
+                .kind()
+        );
+    }
+
+    #[test]
+    fn check_session_view() {
+        let private_keys = vec![PrivateKey::new().unwrap(), PrivateKey::new().unwrap()];
+        let session_id = [3u8; 32];
+        let creation = CoordinatorRequest::CreateSession {
+            session_id,
+            transaction: Tx::default(),
+            signers: signers(&private_keys),
+            created_at: 0,
+        };
+        let commitment = |private_key: &PrivateKey, nonce_commitment: H256| {
+            SignedMessage::new(
+                &CoordinatorRequest::SubmitNonceCommitment {
+                    session_id,
+                    nonce_commitment,
+                },
+                private_key,
+            )
+            .unwrap()
+        };
+
+        let mut state = SessionState {
+            creation: SignedMessage::new(&creation, &private_keys[0]).unwrap(),
+            submissions: vec![
+                commitment(&private_keys[0], [4u8; 32]),
+                commitment(&private_keys[1], [5u8; 32]),
+            ],
+        };
+
+        let view = state.verify(&session_id).unwrap();
+        assert_eq!(PublicKey::from(&private_keys[0]), view.creator);
+        assert!(view.is_complete(&view.nonce_commitments));
+        assert!(!view.is_complete(&view.nonces));
+
+        state
+            .submissions
+            .push(commitment(&private_keys[1], [6u8; 32]));
+        assert_eq!(
+            ErrorKind::MultiSigError,
+            state
+                .verify(&session_id)
+                .expect_err("Accepted conflicting nonce commitments")
+                .kind()
+        );
+
+        state.submissions.pop();
+        state
+            .submissions
+            .push(commitment(&PrivateKey::new().unwrap(), [6u8; 32]));
+        assert_eq!(
+            ErrorKind::PermissionDenied,
+            state
+                .verify(&session_id)
+                .expect_err("Accepted nonce commitment from other key")
+                .kind()
+        );
+    }
+}
@@ -0,0 +1,419 @@
+use std::collections::{BTreeSet, HashMap};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chain_core::common::H256;
+use client_common::{Error, ErrorKind, PublicKey, Result, ResultExt};
+
+use crate::protocol::{
+    read_message, write_message, CoordinatorRequest, CoordinatorResponse, SessionState,
+    SessionView, SignedMessage,
+};
+
+/// Default time to live of a session
+pub const DEFAULT_SESSION_TTL: Duration = Duration::from_secs(60 * 60);
+/// Maximum number of sessions kept by the coordinator
+pub const MAX_SESSIONS: usize = 1024;
+/// Maximum number of sessions created by the same key kept by the coordinator
+pub const MAX_SESSIONS_PER_CREATOR: usize = 16;
+/// Maximum number of connections served at the same time
+pub const MAX_CONNECTIONS: usize = 64;
+/// Time after which a connection without progress is closed
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
+/// Maximum difference accepted between the creation time of a session and the clock of the coordinator
+const MAX_CLOCK_DRIFT: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug)]
+struct StoredSession {
+    state: SessionState,
+    view: SessionView,
+    /// expiry time (seconds since UNIX epoch)
+    expires_at: u64,
+}
+
+/// Coordinator relaying the messages of multi-sig signing sessions (sessions are only kept in memory)
+#[derive(Debug, Clone)]
+pub struct CoordinatorServer {
+    sessions: Arc<Mutex<HashMap<H256, StoredSession>>>,
+    session_ttl: Duration,
+    allowed_creators: Option<BTreeSet<PublicKey>>,
+    connections: Arc<AtomicUsize>,
+}
+
+impl Default for CoordinatorServer {
+    fn default() -> Self {
+        Self {
+            sessions: Default::default(),
+            session_ttl: DEFAULT_SESSION_TTL,
+            allowed_creators: None,
+            connections: Default::default(),
+        }
+    }
+}
+
+/// Counts a connection being served until it is dropped
+struct ConnectionGuard(Arc<AtomicUsize>);
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl CoordinatorServer {
+    /// Sets the time to live of new sessions
+    pub fn with_session_ttl(mut self, session_ttl: Duration) -> Self {
+        self.session_ttl = session_ttl;
+        self
+    }
+
+    /// Allows given key to create sessions (any key can create sessions if no key is allowed)
+    pub fn with_allowed_creator(mut self, public_key: PublicKey) -> Self {
+        self.allowed_creators
+            .get_or_insert_with(BTreeSet::new)
+            .insert(public_key);
+        self
+    }
+
+    /// Binds the TCP socket of the coordinator
+    pub fn bind(address: &str) -> Result<TcpListener> {
+        TcpListener::bind(address).chain(|| {
+            (
+                ErrorKind::InitializationError,
+                format!("Unable to listen on {}", address),
+            )
+        })
+    }
+
+    /// Serves requests on given socket until the process is stopped
+    pub fn serve(&self, listener: TcpListener) -> Result<()> {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if self.connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                        self.connections.fetch_sub(1, Ordering::SeqCst);
+                        log::warn!("coordinator connection refused: too many connections");
+                        continue;
+                    }
+                    let guard = ConnectionGuard(self.connections.clone());
+                    let server = self.clone();
+                    thread::spawn(move || {
+                        if let Err(e) = server.handle_connection(stream) {
+                            log::warn!("coordinator connection failed: {}", e);
+                        }
+                        drop(guard);
+                    });
+                }
+                Err(e) => log::warn!("coordinator connection failed: {}", e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Handles a signed request
+    pub fn handle(&self, message: SignedMessage) -> CoordinatorResponse {
+        self.handle_message(message)
+            .unwrap_or_else(|e| CoordinatorResponse::Error(e.to_string()))
+    }
+
+    fn handle_connection(&self, mut stream: TcpStream) -> Result<()> {
+        stream
+            .set_read_timeout(Some(CONNECTION_TIMEOUT))
+            .and_then(|_| stream.set_write_timeout(Some(CONNECTION_TIMEOUT)))
+            .chain(|| (ErrorKind::IoError, "Unable to set connection timeout"))?;
+        let response = match read_message(&mut stream) {
+            Ok(message) => self.handle(message),
+            Err(_) => CoordinatorResponse::Error("invalid request".to_owned()),
+        };
+        write_message(&mut stream, &response)
+    }
+
+    fn handle_message(&self, message: SignedMessage) -> Result<CoordinatorResponse> {
+        let request = message.verify()?;
+
+        let mut sessions = self.sessions.lock().map_err(|_| {
+            Error::new(
+                ErrorKind::InternalError,
+                "Unable to acquire lock on coordinator sessions",
+            )
+        })?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .chain(|| (ErrorKind::InternalError, "System time is before UNIX epoch"))?
+            .as_secs();
+        sessions.retain(|_, session| session.expires_at > now);
+
+        match request {
+            CoordinatorRequest::CreateSession {
+                session_id,
+                created_at,
+                ..
+            } => {
+                if let Some(ref allowed_creators) = self.allowed_creators {
+                    if !allowed_creators.contains(&message.public_key) {
+                        return Err(Error::new(
+                            ErrorKind::PermissionDenied,
+                            format!(
+                                "Public key ({}) is not allowed to create sessions",
+                                message.public_key
+                            ),
+                        ));
+                    }
+                }
+
+                // the session expires from its creation time, so that an expired creation can not be replayed
+                let expires_at = created_at.saturating_add(self.session_ttl.as_secs());
+                if created_at > now + MAX_CLOCK_DRIFT.as_secs() || expires_at <= now {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        "Creation time of session is in the future or expired",
+                    ));
+                }
+
+                if sessions.contains_key(&session_id) {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!(
+                            "Session with ID ({}) already exists",
+                            hex::encode(&session_id)
+                        ),
+                    ));
+                }
+
+                if sessions.len() >= MAX_SESSIONS {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        "Too many sessions in progress; try again later",
+                    ));
+                }
+                let created_sessions = sessions
+                    .values()
+                    .filter(|session| session.view.creator == message.public_key)
+                    .count();
+                if created_sessions >= MAX_SESSIONS_PER_CREATOR {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!(
+                            "Too many sessions created by public key ({}) in progress; try again later",
+                            message.public_key
+                        ),
+                    ));
+                }
+
+                let state = SessionState {
+                    creation: message,
+                    submissions: Vec::new(),
+                };
+                let view = state.verify(&session_id)?;
+
+                sessions.insert(
+                    session_id,
+                    StoredSession {
+                        state,
+                        view,
+                        expires_at,
+                    },
+                );
+
+                Ok(CoordinatorResponse::Accepted)
+            }
+            CoordinatorRequest::GetSession { session_id } => {
+                let session = get_session(&mut sessions, &session_id)?;
+
+                if !session.view.signers.contains(&message.public_key) {
+                    return Err(Error::new(
+                        ErrorKind::PermissionDenied,
+                        format!(
+                            "Public key ({}) is not a signer of session",
+                            message.public_key
+                        ),
+                    ));
+                }
+
+                Ok(CoordinatorResponse::Session(session.state.clone()))
+            }
+            request => {
+                let session = get_session(&mut sessions, request.session_id())?;
+                session.view.apply(&message)?;
+
+                let is_new = !session.state.submissions.iter().any(|submission| {
+                    submission.public_key == message.public_key
+                        && submission.payload == message.payload
+                });
+                if is_new {
+                    session.state.submissions.push(message);
+                }
+
+                Ok(CoordinatorResponse::Accepted)
+            }
+        }
+    }
+}
+
+fn get_session<'a>(
+    sessions: &'a mut HashMap<H256, StoredSession>,
+    session_id: &H256,
+) -> Result<&'a mut StoredSession> {
+    sessions.get_mut(session_id).chain(|| {
+        (
+            ErrorKind::InvalidInput,
+            format!(
+                "Session with ID ({}) not found or expired",
+                hex::encode(session_id)
+            ),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chain_core::tx::data::Tx;
+    use client_common::PrivateKey;
+
+    use crate::CoordinatorClient;
+
+    #[test]
+    fn check_coordinator_flow() {
+        let listener = CoordinatorServer::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        thread::spawn(move || CoordinatorServer::default().serve(listener).unwrap());
+
+        let client = CoordinatorClient::new(&address);
+        let private_keys = vec![PrivateKey::new().unwrap(), PrivateKey::new().unwrap()];
+        let signers = private_keys.iter().map(PublicKey::from).collect::<Vec<_>>();
+
+        let session_id = client
+            .create_session(&private_keys[0], Tx::default(), signers)
+            .unwrap();
+
+        for (i, private_key) in private_keys.iter().enumerate() {
+            client
+                .submit(
+                    private_key,
+                    CoordinatorRequest::SubmitNonceCommitment {
+                        session_id,
+                        nonce_commitment: [i as u8; 32],
+                    },
+                )
+                .unwrap();
+        }
+
+        let view = client.session(&private_keys[1], &session_id).unwrap();
+        assert_eq!(PublicKey::from(&private_keys[0]), view.creator);
+        assert!(view.is_complete(&view.nonce_commitments));
+        assert_eq!(
+            Some(&[1u8; 32]),
+            view.nonce_commitments
+                .get(&PublicKey::from(&private_keys[1]))
+        );
+
+        let outsider = PrivateKey::new().unwrap();
+        assert_eq!(
+            ErrorKind::MultiSigError,
+            client
+                .session(&outsider, &session_id)
+                .expect_err("Outsider fetched session")
+                .kind()
+        );
+        assert!(client
+            .submit(
+                &private_keys[1],
+                CoordinatorRequest::SubmitNonceCommitment {
+                    session_id,
+                    nonce_commitment: [9u8; 32],
+                },
+            )
+            .is_err());
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    fn create_session(
+        server: &CoordinatorServer,
+        private_key: &PrivateKey,
+        session_id: H256,
+        created_at: u64,
+    ) -> CoordinatorResponse {
+        let request = CoordinatorRequest::CreateSession {
+            session_id,
+            transaction: Tx::default(),
+            signers: vec![PublicKey::from(private_key)],
+            created_at,
+        };
+        server.handle(SignedMessage::new(&request, private_key).unwrap())
+    }
+
+    #[test]
+    fn check_session_expiry() {
+        let server = CoordinatorServer::default().with_session_ttl(Duration::from_secs(1));
+        let private_key = PrivateKey::new().unwrap();
+
+        assert_eq!(
+            CoordinatorResponse::Accepted,
+            create_session(&server, &private_key, [1u8; 32], now())
+        );
+        thread::sleep(Duration::from_secs(2));
+
+        let request = CoordinatorRequest::GetSession {
+            session_id: [1u8; 32],
+        };
+        match server.handle(SignedMessage::new(&request, &private_key).unwrap()) {
+            CoordinatorResponse::Error(_) => {}
+            response => panic!("Expired session is returned: {:?}", response),
+        }
+    }
+
+    #[test]
+    fn check_session_creation_limits() {
+        let private_key = PrivateKey::new().unwrap();
+        let server = CoordinatorServer::default();
+
+        // replayed (expired) or post-dated creations
+        let expired = now() - DEFAULT_SESSION_TTL.as_secs();
+        let future = now() + 2 * MAX_CLOCK_DRIFT.as_secs();
+        for created_at in [expired, future].iter() {
+            match create_session(&server, &private_key, [1u8; 32], *created_at) {
+                CoordinatorResponse::Error(_) => {}
+                response => panic!("Invalid creation time is accepted: {:?}", response),
+            }
+        }
+
+        // sessions per creator
+        for i in 0..MAX_SESSIONS_PER_CREATOR {
+            assert_eq!(
+                CoordinatorResponse::Accepted,
+                create_session(&server, &private_key, [i as u8; 32], now())
+            );
+        }
+        match create_session(&server, &private_key, [0xff; 32], now()) {
+            CoordinatorResponse::Error(_) => {}
+            response => panic!("Too many sessions are accepted: {:?}", response),
+        }
+        let other_key = PrivateKey::new().unwrap();
+        assert_eq!(
+            CoordinatorResponse::Accepted,
+            create_session(&server, &other_key, [0xff; 32], now())
+        );
+
+        // allowed creators
+        let server = CoordinatorServer::default().with_allowed_creator(PublicKey::from(&other_key));
+        match create_session(&server, &private_key, [1u8; 32], now()) {
+            CoordinatorResponse::Error(_) => {}
+            response => panic!("Session of other creator is accepted: {:?}", response),
+        }
+        assert_eq!(
+            CoordinatorResponse::Accepted,
+            create_session(&server, &other_key, [1u8; 32], now())
+        );
+    }
+}
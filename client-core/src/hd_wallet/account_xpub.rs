@@ -0,0 +1,206 @@
+//! # Account-level extended public key
+//! BIP-32 serialization of the extended public key of a BIP-44 account
+//! (`m / 44' / coin_type' / account'`):
+//!
+//! `base58(version || depth || parent fingerprint || child number || chain code || public key || checksum)`
+//!
+//! - `version` (4 bytes): `0x0488B21E` (`xpub...`) on mainnet and `0x043587CF` (`tpub...`) on
+//!   testnet and devnet
+//! - `depth` (1 byte): `3`
+//! - `parent fingerprint` (4 bytes): first 4 bytes of `RIPEMD160(SHA256(coin type public key))`
+//! - `child number` (4 bytes, BE): hardened account index (`0x80000000 + account'`)
+//! - `chain code` (32 bytes)
+//! - `public key` (33 bytes): compressed public key
+//! - `checksum` (4 bytes): first 4 bytes of `SHA256(SHA256(payload))`
+//!
+//! Addresses are derived from the account key at `0 / address_index` (non-hardened),
+//! so watch-only wallets get the same keys as the HD wallet holding the seed.
+use std::fmt;
+use std::str::FromStr;
+
+use base58::{FromBase58, ToBase58};
+use ring::digest;
+use ripemd160::{Digest, Ripemd160};
+use secp256k1::PublicKey as SecpPublicKey;
+
+use chain_core::init::network::{get_bip44_coin_type_from_network, get_network, Network};
+use client_common::{Error, ErrorKind, PublicKey, Result, ResultExt};
+
+use crate::hd_wallet::{
+    ChainPath, DefaultKeyChain, ExtendedPrivKey, ExtendedPubKey, KeyChain, KeyIndex,
+};
+use crate::HDSeed;
+
+const MAINNET_VERSION: [u8; 4] = [0x04, 0x88, 0xB2, 0x1E];
+const TESTNET_VERSION: [u8; 4] = [0x04, 0x35, 0x87, 0xCF];
+const ACCOUNT_DEPTH: u8 = 3;
+const PAYLOAD_LENGTH: usize = 78;
+
+/// Extended public key of a BIP-44 account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountXpub {
+    /// first 4 bytes of the hash of the parent (coin type) public key
+    pub parent_fingerprint: [u8; 4],
+    /// BIP-44 account index (not hardened)
+    pub account_index: u32,
+    /// the extended public key
+    pub key: ExtendedPubKey,
+}
+
+impl AccountXpub {
+    /// Derives the extended public key of given BIP-44 account from a HD seed
+    pub fn from_seed(seed: &HDSeed, network: Network, account_index: u32) -> Result<Self> {
+        let coin_type = get_bip44_coin_type_from_network(network);
+        let chain_path = ChainPath::from(format!("m/44'/{}'/{}'", coin_type, account_index));
+        let key_chain = DefaultKeyChain::new(
+            ExtendedPrivKey::with_seed(seed.as_bytes())
+                .chain(|| (ErrorKind::InternalError, "Invalid seed bytes"))?,
+        );
+        let (account_key, derivation) = key_chain.derive_private_key(chain_path).chain(|| {
+            (
+                ErrorKind::InternalError,
+                "Failed to derive HD wallet account key",
+            )
+        })?;
+        let parent_key = derivation
+            .parent_key
+            .chain(|| (ErrorKind::InternalError, "Account key without parent key"))?;
+
+        Ok(AccountXpub {
+            parent_fingerprint: fingerprint(&ExtendedPubKey::from_private_key(&parent_key)),
+            account_index,
+            key: ExtendedPubKey::from_private_key(&account_key),
+        })
+    }
+
+    /// Derives the public key at `0 / index` of the account
+    pub fn derive_public_key(&self, index: u32) -> Result<PublicKey> {
+        let public_key = self
+            .key
+            .derive_public_key(KeyIndex::Normal(0))
+            .and_then(|chain_key| chain_key.derive_public_key(KeyIndex::Normal(index)))
+            .chain(|| (ErrorKind::InternalError, "Invalid extended pubkey"))?
+            .public_key;
+        Ok(PublicKey::from(public_key))
+    }
+}
+
+fn version(network: Network) -> [u8; 4] {
+    match network {
+        Network::Mainnet => MAINNET_VERSION,
+        Network::Testnet | Network::Devnet => TESTNET_VERSION,
+    }
+}
+
+fn fingerprint(key: &ExtendedPubKey) -> [u8; 4] {
+    let sha256 = digest::digest(&digest::SHA256, &key.public_key.serialize());
+    let mut hasher = Ripemd160::new();
+    hasher.update(sha256.as_ref());
+    let mut fingerprint = [0u8; 4];
+    fingerprint.copy_from_slice(&hasher.finalize()[..4]);
+    fingerprint
+}
+
+fn checksum(payload: &[u8]) -> [u8; 4] {
+    let hash = digest::digest(&digest::SHA256, payload);
+    let hash = digest::digest(&digest::SHA256, hash.as_ref());
+    let mut checksum = [0u8; 4];
+    checksum.copy_from_slice(&hash.as_ref()[..4]);
+    checksum
+}
+
+impl fmt::Display for AccountXpub {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buf = Vec::with_capacity(PAYLOAD_LENGTH + 4);
+        buf.extend_from_slice(&version(get_network()));
+        buf.push(ACCOUNT_DEPTH);
+        buf.extend_from_slice(&self.parent_fingerprint);
+        let child_number = KeyIndex::hardened_from_normalize_index(self.account_index)
+            .map_err(|_| fmt::Error)?
+            .raw_index();
+        buf.extend_from_slice(&child_number.to_be_bytes());
+        buf.extend_from_slice(&self.key.chain_code);
+        buf.extend_from_slice(&self.key.public_key.serialize());
+        let checksum = checksum(&buf);
+        buf.extend_from_slice(&checksum);
+        write!(f, "{}", buf.to_base58())
+    }
+}
+
+impl FromStr for AccountXpub {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = |message: &str| Error::new(ErrorKind::DeserializationError, message);
+
+        let buf = s
+            .from_base58()
+            .map_err(|_| invalid("Extended public key is not base58 encoded"))?;
+        if buf.len() != PAYLOAD_LENGTH + 4 {
+            return Err(invalid("Invalid length of extended public key"));
+        }
+        let (payload, check) = buf.split_at(PAYLOAD_LENGTH);
+        if checksum(payload) != check {
+            return Err(invalid("Invalid checksum of extended public key"));
+        }
+        if payload[..4] != version(get_network()) {
+            return Err(invalid("Extended public key of another network"));
+        }
+        if payload[4] != ACCOUNT_DEPTH {
+            return Err(invalid("Extended public key is not an account key"));
+        }
+        let mut parent_fingerprint = [0u8; 4];
+        parent_fingerprint.copy_from_slice(&payload[5..9]);
+        let mut child_number = [0u8; 4];
+        child_number.copy_from_slice(&payload[9..13]);
+        let account_index = match KeyIndex::from_index(u32::from_be_bytes(child_number)) {
+            Ok(key_index @ KeyIndex::Hardened(_)) => key_index.normalize_index(),
+            _ => return Err(invalid("Extended public key of non-hardened account")),
+        };
+        let public_key = SecpPublicKey::from_slice(&payload[45..])
+            .map_err(|_| invalid("Invalid public key in extended public key"))?;
+
+        Ok(AccountXpub {
+            parent_fingerprint,
+            account_index,
+            key: ExtendedPubKey {
+                public_key,
+                chain_code: payload[13..45].to_vec(),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::HDAccountType;
+    use crate::Mnemonic;
+    use secstr::SecUtf8;
+
+    #[test]
+    fn check_account_xpub_serialization() {
+        let mnemonic = Mnemonic::from_secstr(&SecUtf8::from("speed tortoise kiwi forward extend baby acoustic foil coach castle ship purchase unlock base hip erode tag keen present vibrant oyster cotton write fetch")).unwrap();
+        let seed = HDSeed::from(&mnemonic);
+        let account_index = HDAccountType::Transfer.account_index(1);
+        let xpub = AccountXpub::from_seed(&seed, get_network(), account_index).unwrap();
+
+        let encoded = xpub.to_string();
+        assert!(encoded.starts_with("tpub"));
+        let decoded = AccountXpub::from_str(&encoded).unwrap();
+        assert_eq!(decoded, xpub);
+        assert_eq!(decoded.account_index, account_index);
+
+        for index in 1..4 {
+            let (public_key, _) = seed
+                .derive_key_pair(get_network(), account_index, index)
+                .unwrap();
+            assert_eq!(xpub.derive_public_key(index).unwrap(), public_key);
+        }
+
+        let mut tampered = encoded.into_bytes();
+        let last = tampered.len() - 1;
+        tampered[last] = if tampered[last] == b'1' { b'2' } else { b'1' };
+        assert!(AccountXpub::from_str(&String::from_utf8(tampered).unwrap()).is_err());
+    }
+}
@@ -3,6 +3,8 @@
 //! Copyright (c) 2018 - 2020, Jiang Jinyang (licensed under the MIT License)
 //! Modifications Copyright (c) 2018 - 2020, Foris Limited (licensed under the Apache License, Version 2.0)
 //!
+/// account-level extended public key
+pub mod account_xpub;
 /// error code for hdwallet
 pub mod error;
 /// entended key for hdwallet
@@ -16,6 +18,7 @@ mod wallet_kind;
 
 pub use wallet_kind::HardwareKind;
 
+pub use crate::hd_wallet::account_xpub::AccountXpub;
 pub use crate::hd_wallet::extended_key::{
     key_index::KeyIndex, ExtendedPrivKey, ExtendedPubKey, KeySeed,
 };
@@ -10,6 +10,8 @@ pub enum HardwareKind {
     Ledger,
     /// trezor wallet
     Trezor,
+    /// remote signer (HSM or signing service)
+    Remote,
     /// mock wallet
     #[cfg(feature = "mock-hardware-wallet")]
     Mock,
@@ -24,6 +26,8 @@ impl FromStr for HardwareKind {
             Ok(HardwareKind::Ledger)
         } else if s == "trezor" {
             Ok(HardwareKind::Trezor)
+        } else if s == "remote" {
+            Ok(HardwareKind::Remote)
         } else if s == "mock" {
             Ok(HardwareKind::Mock)
         } else {
@@ -37,6 +41,8 @@ impl FromStr for HardwareKind {
             Ok(HardwareKind::Ledger)
         } else if s == "trezor" {
             Ok(HardwareKind::Trezor)
+        } else if s == "remote" {
+            Ok(HardwareKind::Remote)
         } else {
             Err(ErrorKind::DeserializationError.into())
         }
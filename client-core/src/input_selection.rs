@@ -1,4 +1,6 @@
 //! Input selection operations
+use chain_core::tx::data::input::TxoPointer;
+
 use crate::unspent_transactions::{Operation, Sorter};
 
 /// Different strategies for input selection
@@ -10,6 +12,8 @@ pub enum InputSelectionStrategy {
     LowestValueFirst,
     /// Selects unspent transactions randomly
     Random,
+    /// Selects only given unspent transactions (at least one, in given order, as many as needed)
+    Manual(Vec<TxoPointer>),
 }
 
 impl Default for InputSelectionStrategy {
@@ -28,7 +32,7 @@ impl AsRef<[Operation]> for InputSelectionStrategy {
             InputSelectionStrategy::LowestValueFirst => {
                 &[Operation::Sort(Sorter::LowestValueFirst)]
             }
-            InputSelectionStrategy::Random => &[],
+            InputSelectionStrategy::Random | InputSelectionStrategy::Manual(_) => &[],
         }
     }
 }
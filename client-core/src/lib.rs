@@ -39,6 +39,8 @@ pub use crate::transaction_builder::WalletTransactionBuilder;
 #[doc(inline)]
 pub use crate::unspent_transactions::{SelectedUnspentTransactions, UnspentTransactions};
 #[doc(inline)]
+pub use crate::wallet::ThresholdWalletClient;
+#[doc(inline)]
 pub use crate::wallet::WalletClient;
 
 #[cfg(feature = "experimental")]
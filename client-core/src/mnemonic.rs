@@ -1,4 +1,6 @@
 //! Mnemonic wrapper
+pub mod shamir;
+
 use std::fmt;
 use zeroize::Zeroize;
 
@@ -71,6 +73,21 @@ impl Mnemonic {
         Ok(Mnemonic(mnemonic))
     }
 
+    /// Create Mnemonic from its entropy
+    #[inline]
+    pub fn from_entropy(entropy: &[u8]) -> Result<Self> {
+        let mnemonic = bip39::Mnemonic::from_entropy(entropy, MNEMONIC_LANGUAGE)
+            .chain(|| (ErrorKind::DeserializationError, "Invalid mnemonic entropy"))?;
+
+        Ok(Mnemonic(mnemonic))
+    }
+
+    /// Returns the entropy encoded by the mnemonic words
+    #[inline]
+    pub fn entropy(&self) -> &[u8] {
+        self.0.entropy()
+    }
+
     /// Returns mnemonic phrase as secure string
     #[inline]
     pub fn phrase(&self) -> SecUtf8 {
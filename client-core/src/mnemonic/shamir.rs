@@ -0,0 +1,393 @@
+//! # Shamir backup of mnemonic
+//! SLIP-39 style `threshold`-of-`count` secret sharing of the entropy of a BIP-39 mnemonic.
+//!
+//! The entropy is split with the SLIP-39 scheme over GF(256) (polynomial `x^8 + x^4 + x^3 + x + 1`):
+//!
+//! - with `threshold = 1`, every share is the entropy itself
+//! - otherwise, the shares are the evaluations at `x = 0 .. count - 1` of the polynomial of degree
+//!   `threshold - 1` defined by `threshold - 2` random shares, the digest share at `x = 254`
+//!   (`HMAC-SHA256(R, entropy)[..4] || R`, `R` random) and the entropy at `x = 255`
+//!
+//! The digest share lets recovery detect wrong or corrupted shares.
+//!
+//! Each share is written as `<identifier>-<threshold>-<member> <words>`:
+//!
+//! - `identifier`: 4 hex digits, random and the same for all shares of one backup
+//! - `threshold`: number of shares needed for recovery
+//! - `member`: number of the share (`1` to `count`)
+//! - `words`: BIP-39 mnemonic phrase encoding the share value (with its own checksum)
+//!
+//! The share values have the length of the entropy, so they have as many words as the mnemonic.
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+use ring::hmac;
+use secstr::SecUtf8;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use client_common::{Error, ErrorKind, Result, ResultExt};
+
+use super::{Mnemonic, MNEMONIC_LANGUAGE};
+
+/// Maximum number of shares of one backup
+pub const MAX_SHARE_COUNT: u8 = 16;
+
+const DIGEST_INDEX: u8 = 254;
+const SECRET_INDEX: u8 = 255;
+const DIGEST_LENGTH: usize = 4;
+
+/// One share of a Shamir backup of a mnemonic
+#[derive(Clone, PartialEq, Eq)]
+pub struct MnemonicShare {
+    /// random identifier of the backup
+    pub identifier: u16,
+    /// number of shares needed for recovery
+    pub threshold: u8,
+    /// number of the share in the backup (starting from `1`)
+    pub member: u8,
+    value: Vec<u8>,
+}
+
+/// Summary of a set of shares of one backup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShareSetInfo {
+    /// random identifier of the backup
+    pub identifier: u16,
+    /// number of shares needed for recovery
+    pub threshold: u8,
+    /// number of distinct shares in the set
+    pub share_count: u8,
+    /// if the set has enough shares (with a valid digest) to recover the mnemonic
+    pub recoverable: bool,
+}
+
+impl MnemonicShare {
+    /// Returns the share as `<identifier>-<threshold>-<member> <words>` in secure string
+    pub fn phrase(&self) -> SecUtf8 {
+        let words = bip39::Mnemonic::from_entropy(&self.value, MNEMONIC_LANGUAGE)
+            .expect("share value has the length of a mnemonic entropy")
+            .into_phrase();
+        SecUtf8::from(format!(
+            "{:04x}-{}-{} {}",
+            self.identifier, self.threshold, self.member, words
+        ))
+    }
+
+    /// Parses a share from secure string
+    pub fn from_secstr(share: &SecUtf8) -> Result<Self> {
+        MnemonicShare::from_str(share.unsecure())
+    }
+}
+
+impl FromStr for MnemonicShare {
+    type Err = Error;
+
+    fn from_str(share: &str) -> Result<Self> {
+        let invalid = |message: &str| Error::new(ErrorKind::DeserializationError, message);
+
+        let share = share.trim();
+        let (header, words) = share
+            .find(char::is_whitespace)
+            .map(|position| share.split_at(position))
+            .chain(|| (ErrorKind::DeserializationError, "Share has no words"))?;
+        let header = header.split('-').collect::<Vec<_>>();
+        if header.len() != 3 {
+            return Err(invalid("Invalid header of share"));
+        }
+        let identifier = u16::from_str_radix(header[0], 16)
+            .map_err(|_| invalid("Invalid identifier of share"))?;
+        let threshold =
+            u8::from_str(header[1]).map_err(|_| invalid("Invalid threshold of share"))?;
+        let member = u8::from_str(header[2]).map_err(|_| invalid("Invalid member of share"))?;
+        if threshold == 0 || member == 0 || threshold > MAX_SHARE_COUNT || member > MAX_SHARE_COUNT
+        {
+            return Err(invalid("Threshold or member of share out of range"));
+        }
+        let words = words.split_whitespace().collect::<Vec<_>>().join(" ");
+        let value = bip39::Mnemonic::from_phrase(&words, MNEMONIC_LANGUAGE)
+            .map_err(|_| invalid("Invalid words of share"))?
+            .entropy()
+            .to_vec();
+
+        Ok(MnemonicShare {
+            identifier,
+            threshold,
+            member,
+            value,
+        })
+    }
+}
+
+impl Drop for MnemonicShare {
+    fn drop(&mut self) {
+        self.value.zeroize();
+    }
+}
+
+impl fmt::Debug for MnemonicShare {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:04x}-{}-{} ***SECRET***",
+            self.identifier, self.threshold, self.member
+        )
+    }
+}
+
+/// Splits the entropy of the mnemonic into `count` shares, any `threshold` of which recover it
+pub fn split_mnemonic(mnemonic: &Mnemonic, threshold: u8, count: u8) -> Result<Vec<MnemonicShare>> {
+    if threshold == 0 || threshold > count || count > MAX_SHARE_COUNT {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Threshold should be between 1 and share count, which is at most {}",
+                MAX_SHARE_COUNT
+            ),
+        ));
+    }
+    let secret = mnemonic.entropy();
+    let mut rng = OsRng;
+    let identifier = rng.next_u32() as u16;
+    let share = |member: u8, value: Vec<u8>| MnemonicShare {
+        identifier,
+        threshold,
+        member,
+        value,
+    };
+
+    if threshold == 1 {
+        return Ok((1..=count)
+            .map(|member| share(member, secret.to_vec()))
+            .collect());
+    }
+
+    let mut base_shares = Vec::with_capacity(threshold as usize);
+    for x in 0..threshold - 2 {
+        let mut value = vec![0u8; secret.len()];
+        rng.fill_bytes(&mut value);
+        base_shares.push((x, value));
+    }
+    let mut random = vec![0u8; secret.len() - DIGEST_LENGTH];
+    rng.fill_bytes(&mut random);
+    let mut digest_share = digest(&random, secret);
+    digest_share.extend_from_slice(&random);
+    base_shares.push((DIGEST_INDEX, digest_share));
+    base_shares.push((SECRET_INDEX, secret.to_vec()));
+
+    let shares = (0..count)
+        .map(|x| share(x + 1, interpolate(&base_shares, x)))
+        .collect();
+    for (_, value) in base_shares.iter_mut() {
+        value.zeroize();
+    }
+    Ok(shares)
+}
+
+/// Checks that the shares belong to one backup and if they are enough to recover the mnemonic
+pub fn validate_shares(shares: &[MnemonicShare]) -> Result<ShareSetInfo> {
+    let first = shares
+        .first()
+        .chain(|| (ErrorKind::InvalidInput, "No shares given"))?;
+    let mut members = BTreeSet::new();
+    for share in shares {
+        if share.identifier != first.identifier
+            || share.threshold != first.threshold
+            || share.value.len() != first.value.len()
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Shares do not belong to the same backup",
+            ));
+        }
+        members.insert(share.member);
+    }
+
+    let mut info = ShareSetInfo {
+        identifier: first.identifier,
+        threshold: first.threshold,
+        share_count: members.len() as u8,
+        recoverable: false,
+    };
+    if info.share_count >= info.threshold {
+        combine(shares)?.zeroize();
+        info.recoverable = true;
+    }
+    Ok(info)
+}
+
+/// Recovers the mnemonic from at least `threshold` shares of one backup
+pub fn recover_mnemonic(shares: &[MnemonicShare]) -> Result<Mnemonic> {
+    let info = validate_shares(shares)?;
+    if !info.recoverable {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "{} shares are needed for recovery, only {} given",
+                info.threshold, info.share_count
+            ),
+        ));
+    }
+    let mut secret = combine(shares)?;
+    let mnemonic = Mnemonic::from_entropy(&secret);
+    secret.zeroize();
+    mnemonic
+}
+
+/// returns the secret of (already consistent) shares after checking the digest
+fn combine(shares: &[MnemonicShare]) -> Result<Vec<u8>> {
+    let threshold = shares[0].threshold;
+    let mut points: Vec<(u8, Vec<u8>)> = Vec::with_capacity(threshold as usize);
+    for share in shares {
+        if points.len() == threshold as usize {
+            break;
+        }
+        let x = share.member - 1;
+        match points.iter().find(|(other, _)| *other == x) {
+            Some((_, value)) if *value != share.value => {
+                return Err(Error::new(
+                    ErrorKind::VerifyError,
+                    format!("Different values of share {}", share.member),
+                ))
+            }
+            Some(_) => {}
+            None => points.push((x, share.value.clone())),
+        }
+    }
+
+    let result = if threshold == 1 {
+        Ok(points[0].1.clone())
+    } else {
+        let secret = interpolate(&points, SECRET_INDEX);
+        let mut digest_share = interpolate(&points, DIGEST_INDEX);
+        let valid =
+            digest(&digest_share[DIGEST_LENGTH..], &secret)[..] == digest_share[..DIGEST_LENGTH];
+        digest_share.zeroize();
+        if valid {
+            Ok(secret)
+        } else {
+            Err(Error::new(
+                ErrorKind::VerifyError,
+                "Invalid digest of shares, some shares are wrong",
+            ))
+        }
+    };
+    for (_, value) in points.iter_mut() {
+        value.zeroize();
+    }
+    result
+}
+
+fn digest(random: &[u8], secret: &[u8]) -> Vec<u8> {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, random);
+    hmac::sign(&key, secret).as_ref()[..DIGEST_LENGTH].to_vec()
+}
+
+/// exponent and logarithm tables of GF(256) with generator `3`
+fn tables() -> ([u8; 255], [u8; 256]) {
+    let mut exp = [0u8; 255];
+    let mut log = [0u8; 256];
+    let mut poly: u16 = 1;
+    for (i, item) in exp.iter_mut().enumerate() {
+        *item = poly as u8;
+        log[poly as usize] = i as u8;
+        poly = (poly << 1) ^ poly;
+        if poly & 0x100 != 0 {
+            poly ^= 0x11b;
+        }
+    }
+    (exp, log)
+}
+
+/// Lagrange interpolation at `x` of the polynomial through the given points
+fn interpolate(points: &[(u8, Vec<u8>)], x: u8) -> Vec<u8> {
+    if let Some((_, value)) = points.iter().find(|(index, _)| *index == x) {
+        return value.clone();
+    }
+    let (exp, log_table) = tables();
+    let log = |value: u8| i32::from(log_table[value as usize]);
+
+    let log_product: i32 = points.iter().map(|(index, _)| log(index ^ x)).sum();
+    let mut result = vec![0u8; points[0].1.len()];
+    for (index, value) in points {
+        let log_basis = log_product
+            - log(index ^ x)
+            - points
+                .iter()
+                .filter(|(other, _)| other != index)
+                .map(|(other, _)| log(index ^ other))
+                .sum::<i32>();
+        let log_basis = log_basis.rem_euclid(255);
+        for (item, byte) in result.iter_mut().zip(value) {
+            if *byte != 0 {
+                *item ^= exp[((log(*byte) + log_basis) % 255) as usize];
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(shares: &[MnemonicShare]) -> Vec<MnemonicShare> {
+        shares
+            .iter()
+            .map(|share| MnemonicShare::from_secstr(&share.phrase()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn check_split_and_recover_mnemonic() {
+        let mnemonic = Mnemonic::new(24).unwrap();
+        let shares = parse(&split_mnemonic(&mnemonic, 3, 5).unwrap());
+        assert_eq!(shares.len(), 5);
+        assert_eq!(shares[0].phrase().unsecure().split_whitespace().count(), 25);
+
+        for subset in &[[0, 1, 2], [4, 2, 0], [1, 3, 4]] {
+            let subset = subset
+                .iter()
+                .map(|index| shares[*index].clone())
+                .collect::<Vec<_>>();
+            let recovered = recover_mnemonic(&subset).unwrap();
+            assert_eq!(recovered.unsecure_phrase(), mnemonic.unsecure_phrase());
+        }
+
+        let info = validate_shares(&shares[..2]).unwrap();
+        assert_eq!(info.threshold, 3);
+        assert_eq!(info.share_count, 2);
+        assert!(!info.recoverable);
+        assert!(
+            recover_mnemonic(&[shares[0].clone(), shares[0].clone(), shares[1].clone()]).is_err()
+        );
+        assert!(validate_shares(&shares).unwrap().recoverable);
+
+        let mut corrupted = shares[..3].to_vec();
+        corrupted[1].value[0] ^= 1;
+        assert!(recover_mnemonic(&corrupted).is_err());
+
+        let other = split_mnemonic(&mnemonic, 3, 5).unwrap();
+        let mixed = vec![shares[0].clone(), shares[1].clone(), other[2].clone()];
+        assert!(recover_mnemonic(&mixed).is_err());
+    }
+
+    #[test]
+    fn check_single_threshold_and_invalid_parameters() {
+        let mnemonic = Mnemonic::new(12).unwrap();
+        let shares = split_mnemonic(&mnemonic, 1, 2).unwrap();
+        assert_eq!(
+            recover_mnemonic(&shares[1..]).unwrap().unsecure_phrase(),
+            mnemonic.unsecure_phrase()
+        );
+
+        assert!(split_mnemonic(&mnemonic, 0, 2).is_err());
+        assert!(split_mnemonic(&mnemonic, 3, 2).is_err());
+        assert!(split_mnemonic(&mnemonic, 2, MAX_SHARE_COUNT + 1).is_err());
+        assert!(MnemonicShare::from_str("12ab-2-1").is_err());
+        assert!(MnemonicShare::from_str("12ab-2 abandon abandon").is_err());
+    }
+}
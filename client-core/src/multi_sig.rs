@@ -4,5 +4,5 @@ mod session;
 mod signer;
 
 pub use builder::MultiSigBuilder;
-pub use session::MultiSigSession;
+pub use session::{MultiSigSession, SecretNonce};
 use signer::Signer;
@@ -2,19 +2,19 @@ use parity_scale_codec::{Decode, Encode};
 use secp256k1::schnorrsig::SchnorrSignature;
 
 use chain_core::common::H256;
-use client_common::{ErrorKind, PrivateKey, PublicKey, Result, ResultExt};
+use client_common::{Error, ErrorKind, PrivateKey, PublicKey, Result, ResultExt};
 
-use super::MultiSigSession;
+use super::{MultiSigSession, SecretNonce, Signer};
 
 /// MultiSig session builder tailored for Crypto.com chain flow
 ///
-/// WARNING: Never restore MultiSig builder from an untrusted source or
-/// from a source not created by you.
-/// Restoring from such a source can result in complete lost of your funds.
-/// If you are in doubt, always create a new session builder and restart
-/// the whole process from scratch.
+/// The builder holds the secret nonce seed of current signer in memory only. It is consumed once the partial
+/// signature of current signer is generated, and it is never part of the encoded (incomplete) session. A builder
+/// restored from bytes can therefore still collect data of co-signers and combine the final signature, but it can
+/// never generate a nonce or a partial signature which was not generated before: such a session has to be restarted.
 pub struct MultiSigBuilder {
     session: MultiSigSession,
+    secret_nonce: Option<SecretNonce>,
 }
 
 impl MultiSigBuilder {
@@ -22,24 +22,30 @@ impl MultiSigBuilder {
     ///
     /// # Arguments
     ///
-    /// - `message`: Message to be signed,
+    /// - `message`: Message to be signed (transaction id),
     /// - `signer_public_keys`: Public keys of all the signers (including current signer)
     /// - `self_public_key`: Public key of current signer
-    /// - `self_private_key`: Private key of current signer
+    /// - `wallet_name`: Name of the wallet holding the private key of current signer
+    /// - `expires_at`: Unix timestamp (in seconds) after which the session can no longer be used
     pub fn new(
         message: H256,
         signer_public_keys: Vec<PublicKey>,
         self_public_key: PublicKey,
-        self_private_key: PrivateKey,
+        wallet_name: String,
+        expires_at: u64,
     ) -> Result<Self> {
-        let session = MultiSigSession::new(
+        let (session, secret_nonce) = MultiSigSession::new(
             message,
             signer_public_keys,
             self_public_key,
-            self_private_key,
+            wallet_name,
+            expires_at,
         )?;
 
-        Ok(MultiSigBuilder { session })
+        Ok(MultiSigBuilder {
+            session,
+            secret_nonce: Some(secret_nonce),
+        })
     }
 
     /// Returns the session Id
@@ -47,17 +53,51 @@ impl MultiSigBuilder {
         self.session.id
     }
 
+    /// Returns the message to be signed (transaction id the session is bound to)
+    pub fn message(&self) -> H256 {
+        self.session.message
+    }
+
+    /// Returns public key of current signer
+    pub fn public_key(&self) -> &PublicKey {
+        &self.session.public_key
+    }
+
+    /// Returns name of the wallet holding the private key of current signer
+    pub fn wallet_name(&self) -> &str {
+        &self.session.wallet_name
+    }
+
+    /// Returns an error if the session has expired at given unix timestamp (in seconds)
+    pub fn check_expiry(&self, now: u64) -> Result<()> {
+        self.session.check_expiry(now)
+    }
+
+    /// Attaches the in-memory secret nonce seed of current signer (e.g. kept aside while the session was stored)
+    pub fn with_secret_nonce(mut self, secret_nonce: SecretNonce) -> Self {
+        self.secret_nonce = Some(secret_nonce);
+        self
+    }
+
+    /// Takes the secret nonce seed of current signer out of the builder (if it is not consumed yet)
+    pub fn take_secret_nonce(&mut self) -> Option<SecretNonce> {
+        self.secret_nonce.take()
+    }
+
     /// Returns nonce commitment of current signer. Add the nonce commitment to
     /// the session if the current signer has no nonce commitment added before.
-    pub fn nonce_commitment(&mut self) -> Result<H256> {
-        let nonce_commitment = self.session.nonce_commitment()?;
-
-        let public_key = &self.session.public_key.clone();
-        if !self.session.has_nonce_commitment(public_key)? {
-            self.session
-                .add_nonce_commitment(public_key, nonce_commitment)?;
+    pub fn nonce_commitment(&mut self, private_key: &PrivateKey) -> Result<H256> {
+        let public_key = self.session.public_key.clone();
+        if self.session.has_nonce_commitment(&public_key)? {
+            return self.own_signer_data(|signer| signer.nonce_commitment);
         }
 
+        let nonce_commitment = self
+            .session
+            .nonce_commitment(self.secret_nonce()?, private_key)?;
+        self.session
+            .add_nonce_commitment(&public_key, nonce_commitment)?;
+
         Ok(nonce_commitment)
     }
 
@@ -68,9 +108,7 @@ impl MultiSigBuilder {
         nonce_commitment: H256,
     ) -> Result<()> {
         self.session
-            .add_nonce_commitment(&public_key, nonce_commitment)?;
-
-        Ok(())
+            .add_nonce_commitment(&public_key, nonce_commitment)
     }
 
     /// Returns nonce of current signer. Add the nonce to the session if the
@@ -78,51 +116,73 @@ impl MultiSigBuilder {
     ///
     /// This function will fail if nonce commitments from all co-signers are
     /// not received.
-    pub fn nonce(&mut self) -> Result<H256> {
-        let nonce = self.session.nonce()?;
-
-        let public_key = &self.session.public_key.clone();
-        if !self.session.has_nonce(public_key)? {
-            self.session.add_nonce(public_key, nonce)?;
+    pub fn nonce(&mut self, private_key: &PrivateKey) -> Result<H256> {
+        let public_key = self.session.public_key.clone();
+        if self.session.has_nonce(&public_key)? {
+            return self.own_signer_data(|signer| signer.nonce);
         }
 
+        let nonce = self.session.nonce(self.secret_nonce()?, private_key)?;
+        self.session.add_nonce(&public_key, nonce)?;
+
         Ok(nonce)
     }
 
     /// Adds a nonce from a public key to session.
     pub fn add_nonce(&mut self, public_key: &PublicKey, nonce: &H256) -> Result<()> {
-        self.session.add_nonce(public_key, *nonce)?;
-
-        Ok(())
+        self.session.add_nonce(public_key, *nonce)
     }
 
     /// Returns partial signature of current signer. Add the partial signature
     /// to the session if the current signer has no partial signature added
     /// before.
     ///
+    /// The secret nonce seed is consumed by this function (even on failure),
+    /// so that a partial signature is never generated twice with the same nonce.
+    ///
     /// This function will fail if nonces from all co-signers are not received.
-    pub fn partial_signature(&mut self) -> Result<H256> {
-        let partial_signature = self.session.partial_signature()?;
-        let public_key = &self.session.public_key.clone();
+    pub fn partial_signature(&mut self, private_key: &PrivateKey) -> Result<H256> {
+        let public_key = self.session.public_key.clone();
+        if self.session.has_partial_signature(&public_key)? {
+            return self.own_signer_data(|signer| signer.partial_signature);
+        }
 
-        if !self.session.has_partial_signature(public_key)? {
-            self.session
-                .add_partial_signature(public_key, partial_signature)?;
+        // Fails early (without consuming the secret nonce) when data of some co-signers is still missing
+        if let Some(signer) = self
+            .session
+            .signers
+            .iter()
+            .find(|signer| signer.nonce.is_none())
+        {
+            return Err(Error::new(
+                ErrorKind::MultiSigError,
+                format!(
+                    "Missing nonce for signer with public key: {}",
+                    signer.public_key
+                ),
+            ));
         }
 
+        let secret_nonce = self
+            .secret_nonce
+            .take()
+            .chain(|| lost_secret_nonce(&self.session))?;
+        let partial_signature = self.session.partial_signature(&secret_nonce, private_key)?;
+        self.session
+            .add_partial_signature(&public_key, partial_signature)?;
+
         Ok(partial_signature)
     }
 
-    /// Adds a partial signature from a public key to session
+    /// Adds a partial signature from a public key to session. The partial
+    /// signature is verified on arrival.
     pub fn add_partial_signature(
         &mut self,
         public_key: &PublicKey,
         partial_signature: H256,
     ) -> Result<()> {
         self.session
-            .add_partial_signature(public_key, partial_signature)?;
-
-        Ok(())
+            .add_partial_signature(public_key, partial_signature)
     }
 
     /// Returns final signature.
@@ -151,23 +211,22 @@ impl MultiSigBuilder {
 
     /// Returns true if partial signature for given public key is already set,
     /// false otherwise.
-    pub fn has_partial_signature(&mut self, public_key: &PublicKey) -> Result<bool> {
+    pub fn has_partial_signature(&self, public_key: &PublicKey) -> Result<bool> {
         self.session.has_partial_signature(public_key)
     }
 
-    /// Returns incompleted MultiSig session in bytes
+    /// Returns incompleted MultiSig session in bytes. The secret nonce seed is
+    /// never part of it.
     pub fn to_incomplete(&self) -> Vec<u8> {
         self.session.encode()
     }
 
     /// Restore MultiSig session builder from encoded incompleted bytes
     ///
-    /// WARNING: Never restore MultiSig builder from an untrusted source or
-    /// from a source not created by you.
-    /// Restoring from such a source can result in complete lost of your funds.
-    /// If you are in doubt, always create a new session builder and restart
-    /// the whole process from scratch.
-    pub fn from_incomplete_insecure(bytes: Vec<u8>) -> Result<Self> {
+    /// The restored builder has no secret nonce seed: unless it is attached
+    /// with [`MultiSigBuilder::with_secret_nonce`], only data already generated
+    /// by current signer can be returned.
+    pub fn from_incomplete(bytes: Vec<u8>) -> Result<Self> {
         let session = MultiSigSession::decode(&mut bytes.as_slice()).chain(|| {
             (
                 ErrorKind::DeserializationError,
@@ -175,10 +234,47 @@ impl MultiSigBuilder {
             )
         })?;
 
-        Ok(MultiSigBuilder { session })
+        Ok(MultiSigBuilder {
+            session,
+            secret_nonce: None,
+        })
+    }
+
+    fn secret_nonce(&self) -> Result<&SecretNonce> {
+        self.secret_nonce
+            .as_ref()
+            .chain(|| lost_secret_nonce(&self.session))
+    }
+
+    fn own_signer_data<F>(&self, f: F) -> Result<H256>
+    where
+        F: Fn(&Signer) -> Option<H256>,
+    {
+        let public_key = &self.session.public_key;
+        self.session
+            .signers
+            .iter()
+            .find(|signer| &signer.public_key == public_key)
+            .and_then(f)
+            .chain(|| {
+                (
+                    ErrorKind::InternalError,
+                    "Data of current signer not found in session",
+                )
+            })
     }
 }
 
+fn lost_secret_nonce(session: &MultiSigSession) -> (ErrorKind, String) {
+    (
+        ErrorKind::MultiSigError,
+        format!(
+            "Secret nonce of session with ID ({}) is not available (already used or lost on restart); start a new session",
+            hex::encode(&session.id)
+        ),
+    )
+}
+
 #[cfg(test)]
 mod multi_sig_builder_tests {
     use super::*;
@@ -186,7 +282,16 @@ mod multi_sig_builder_tests {
     use secp256k1::schnorrsig::schnorr_verify;
     use secp256k1::Message;
 
-    use client_common::SECP;
+    fn new_builder(public_keys: &[PublicKey], public_key: &PublicKey) -> MultiSigBuilder {
+        MultiSigBuilder::new(
+            [1u8; 32],
+            public_keys.to_vec(),
+            public_key.clone(),
+            "name".to_owned(),
+            u64::max_value(),
+        )
+        .unwrap()
+    }
 
     #[test]
     fn check_multi_sig_transaction_flow() {
@@ -197,29 +302,19 @@ mod multi_sig_builder_tests {
 
         let public_key_1 = PublicKey::from(&private_key_1);
         let public_key_2 = PublicKey::from(&private_key_2);
+        let public_keys = vec![public_key_1.clone(), public_key_2.clone()];
 
-        let mut session_1 = MultiSigBuilder::new(
-            message,
-            vec![public_key_1.clone(), public_key_2.clone()],
-            public_key_1.clone(),
-            private_key_1.clone(),
-        )
-        .unwrap();
-        let mut session_2 = MultiSigBuilder::new(
-            message,
-            vec![public_key_1.clone(), public_key_2.clone()],
-            public_key_2.clone(),
-            private_key_2.clone(),
-        )
-        .unwrap();
+        let mut session_1 = new_builder(&public_keys, &public_key_1);
+        let mut session_2 = new_builder(&public_keys, &public_key_2);
 
-        let nonce_commitment_1 = session_1.nonce_commitment().unwrap();
-        assert!(
-            session_1.nonce_commitment().is_ok(),
+        let nonce_commitment_1 = session_1.nonce_commitment(&private_key_1).unwrap();
+        assert_eq!(
+            nonce_commitment_1,
+            session_1.nonce_commitment(&private_key_1).unwrap(),
             "Should be able to retrieve nonce commitment multiple times"
         );
 
-        let nonce_commitment_2 = session_2.nonce_commitment().unwrap();
+        let nonce_commitment_2 = session_2.nonce_commitment(&private_key_2).unwrap();
 
         session_1
             .add_nonce_commitment(&public_key_2, nonce_commitment_2)
@@ -228,13 +323,14 @@ mod multi_sig_builder_tests {
             .add_nonce_commitment(&public_key_1, nonce_commitment_1)
             .expect("Should be able to add nonce commitment to session 2");
 
-        let nonce_1 = session_1.nonce().unwrap();
-        assert!(
-            session_1.nonce().is_ok(),
+        let nonce_1 = session_1.nonce(&private_key_1).unwrap();
+        assert_eq!(
+            nonce_1,
+            session_1.nonce(&private_key_1).unwrap(),
             "Should be able to retrieve nonce multiple times"
         );
 
-        let nonce_2 = session_2.nonce().unwrap();
+        let nonce_2 = session_2.nonce(&private_key_2).unwrap();
 
         session_1
             .add_nonce(&public_key_2, &nonce_2)
@@ -247,17 +343,22 @@ mod multi_sig_builder_tests {
             .expect("Should be able to add nonce to session 2");
 
         let partial_signature_1 = session_1
-            .partial_signature()
+            .partial_signature(&private_key_1)
             .expect("Should be able to generate partial signature for session 1");
-        assert!(
-            session_1.partial_signature().is_ok(),
+        assert!(session_1.take_secret_nonce().is_none());
+        assert_eq!(
+            partial_signature_1,
+            session_1.partial_signature(&private_key_1).unwrap(),
             "Should be able to retrieve partial signatures multiple times"
         );
 
         let partial_signature_2 = session_2
-            .partial_signature()
+            .partial_signature(&private_key_2)
             .expect("Should be able to generate partial signature for session 2");
 
+        session_1
+            .add_partial_signature(&public_key_2, partial_signature_1)
+            .expect_err("Should not be able to add a partial signature of another signer");
         session_1
             .add_partial_signature(&public_key_2, partial_signature_2)
             .expect("Should be able to add partial signature to session 1");
@@ -267,7 +368,7 @@ mod multi_sig_builder_tests {
 
         let encoded = session_1.to_incomplete();
 
-        let restored_session_1 = MultiSigBuilder::from_incomplete_insecure(encoded)
+        let restored_session_1 = MultiSigBuilder::from_incomplete(encoded)
             .expect("Should be able to restore from encoded incompleted bytes");
 
         let signature_1 = session_1.signature().unwrap();
@@ -279,7 +380,7 @@ mod multi_sig_builder_tests {
         assert_eq!(signature_1, signature_2);
         assert_eq!(restored_signature_1, signature_2);
 
-        let mut public_keys = vec![public_key_1, public_key_2];
+        let mut public_keys = public_keys;
         public_keys.sort();
 
         let combined_public_key = PublicKey::combine(&public_keys).unwrap().0;
@@ -293,4 +394,44 @@ mod multi_sig_builder_tests {
         )
         .expect("Invalid signature");
     }
+
+    #[test]
+    fn check_restored_session_cannot_reuse_nonce() {
+        let private_key_1 = PrivateKey::new().unwrap();
+        let private_key_2 = PrivateKey::new().unwrap();
+
+        let public_key_1 = PublicKey::from(&private_key_1);
+        let public_key_2 = PublicKey::from(&private_key_2);
+        let public_keys = vec![public_key_1.clone(), public_key_2.clone()];
+
+        let mut session_1 = new_builder(&public_keys, &public_key_1);
+        let mut session_2 = new_builder(&public_keys, &public_key_2);
+
+        let nonce_commitment_1 = session_1.nonce_commitment(&private_key_1).unwrap();
+        let nonce_commitment_2 = session_2.nonce_commitment(&private_key_2).unwrap();
+        session_1
+            .add_nonce_commitment(&public_key_2, nonce_commitment_2)
+            .unwrap();
+
+        // Simulates a crash: only the encoded session survives
+        let mut restored = MultiSigBuilder::from_incomplete(session_1.to_incomplete()).unwrap();
+
+        assert_eq!(
+            nonce_commitment_1,
+            restored.nonce_commitment(&private_key_1).unwrap()
+        );
+        let error = restored
+            .nonce(&private_key_1)
+            .expect_err("Should not be able to generate a nonce without secret nonce");
+        assert_eq!(ErrorKind::MultiSigError, error.kind());
+
+        // A fresh secret nonce does not match the already published nonce commitment
+        restored
+            .with_secret_nonce(SecretNonce::new())
+            .nonce(&private_key_1)
+            .expect_err("Should not be able to use another secret nonce");
+
+        // The original (in-memory) builder can still proceed
+        assert!(session_1.nonce(&private_key_1).is_ok());
+    }
 }
@@ -1,8 +1,9 @@
-use std::convert::TryFrom;
+use std::fmt;
 
 use parity_scale_codec::{Decode, Encode};
 use rand::rngs::OsRng;
-use secp256k1::key::pubkey_combine;
+use rand::RngCore;
+use zeroize::Zeroize;
 
 use secp256k1experimental::key::MuSigPreSession;
 use secp256k1experimental::musig::{
@@ -22,26 +23,66 @@ use client_common::{Error, ErrorKind, PrivateKey, PublicKey, Result, ResultExt};
 
 use super::Signer;
 
+/// Secret nonce seed of current signer in a MultiSig session.
+///
+/// The MuSig nonce of current signer is derived from this seed, so it is only ever kept in memory: it is neither
+/// encoded nor persisted, and it is zeroized when dropped. Losing it (e.g. on a crash) means that the session has to
+/// be restarted from scratch with a fresh seed, which is exactly what prevents a nonce from being reused.
+pub struct SecretNonce([u8; 32]);
+
+impl SecretNonce {
+    /// Generates a new random secret nonce seed
+    pub fn new() -> Self {
+        let mut rng = OsRng;
+        let mut seed = [0u8; 32];
+        seed.copy_from_slice(&MuSigSessionID::new(&mut rng)[..]);
+        SecretNonce(seed)
+    }
+
+    fn session_id(&self) -> MuSigSessionID {
+        MuSigSessionID::from_slice(&self.0).expect("32 bytes multi-sig session ID")
+    }
+}
+
+impl Default for SecretNonce {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for SecretNonce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretNonce(***)")
+    }
+}
+
+impl Drop for SecretNonce {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
 /// A MultiSig session as a basic building block
+///
+/// A session only contains public data: the private key of current signer has to be provided on every step which
+/// needs it and its secret nonce seed is kept separately (see [`SecretNonce`]).
 #[derive(Debug, Encode, Decode)]
 pub struct MultiSigSession {
-    /// Session id
+    /// Session id (public, independent of the secret nonce seed)
     pub id: H256,
-    /// The message to be signed
+    /// The message to be signed (transaction id the session is bound to)
     pub message: H256,
     /// Data of all the signers (also includes data of current signer). This is sorted by public key of signer.
     pub signers: Vec<Signer>,
     /// Public key of current signer
     pub public_key: PublicKey,
-    /// Private key of current signer
-    pub private_key: PrivateKey,
+    /// Name of the wallet holding the private key of current signer
+    pub wallet_name: String,
+    /// Unix timestamp (in seconds) after which the session can no longer be used
+    pub expires_at: u64,
 }
 
 impl MultiSigSession {
-    /// for some reason, the old implementation exposed all internals + kept reconstructing the session?
-    /// this is a minimal change to get it working
-    /// TODO: change all these client-* to something safe, but not so naive and super-inefficient way, as it was before
-    /// (perhaps after MuSig API stabilizes more?)
     fn get_preinit(&self) -> Result<(XOnlyPublicKey, MuSigPreSession)> {
         let pks: Vec<PublicKey> = self
             .signers
@@ -51,29 +92,31 @@ impl MultiSigSession {
         PublicKey::combine(&pks)
     }
 
-    /// Create a new instance of MultiSigSession
+    /// Create a new instance of MultiSigSession along with the secret nonce seed of current signer
     pub fn new(
         message: H256,
         mut signer_public_keys: Vec<PublicKey>,
         self_public_key: PublicKey,
-        self_private_key: PrivateKey,
-    ) -> Result<Self> {
-        if PublicKey::from(&self_private_key) != self_public_key {
+        wallet_name: String,
+        expires_at: u64,
+    ) -> Result<(Self, SecretNonce)> {
+        if signer_public_keys.len() <= 1 {
             return Err(Error::new(
                 ErrorKind::InvalidInput,
-                "Invalid self public/private keypair",
+                "Cannot create a session with less than 2 signers",
             ));
         }
 
+        signer_public_keys.sort();
+        signer_public_keys.dedup();
+
         if signer_public_keys.len() <= 1 {
             return Err(Error::new(
                 ErrorKind::InvalidInput,
-                "Cannot create a session with less than 2 signers",
+                "Cannot create a session with less than 2 distinct signers",
             ));
         }
 
-        signer_public_keys.sort();
-
         if signer_public_keys.binary_search(&self_public_key).is_err() {
             return Err(Error::new(
                 ErrorKind::InvalidInput,
@@ -91,49 +134,43 @@ impl MultiSigSession {
             })
             .collect::<Vec<Signer>>();
 
-        let mut rng = OsRng;
-        let session_id = H256::try_from(&MuSigSessionID::new(&mut rng)[..]).chain(|| {
-            (
-                ErrorKind::DeserializationError,
-                "Unable to deserialize session ID from bytes",
-            )
-        })?;
+        let mut id = [0u8; 32];
+        OsRng.fill_bytes(&mut id);
 
-        Ok(MultiSigSession {
-            id: session_id,
+        let session = MultiSigSession {
+            id,
             message,
             signers,
             public_key: self_public_key,
-            private_key: self_private_key,
-        })
+            wallet_name,
+            expires_at,
+        };
+
+        Ok((session, SecretNonce::new()))
     }
 
-    /// Returns nonce commitment of current signer
-    pub fn nonce_commitment(&self) -> Result<H256> {
-        SECP.with(|secp| -> Result<H256> {
-            let (pk, pre_init) = self.get_preinit()?;
-            let session = MuSigSession::new(
-                &secp,
-                MuSigSessionID::from_slice(&self.id).chain(|| {
-                    (
-                        ErrorKind::DeserializationError,
-                        "Unable to deserialize multi-sig session ID from bytes",
-                    )
-                })?,
-                &Message::from_slice(&self.message).chain(|| {
-                    (
-                        ErrorKind::DeserializationError,
-                        "Unable to deserialize message to sign from bytes",
-                    )
-                })?,
-                &pk,
-                &pre_init,
-                self.signers.len(),
-                self.signer_index(&self.public_key)?,
-                &SecretKey::from(&self.private_key),
-            )
-            .chain(|| (ErrorKind::MultiSigError, "Unable to create session"))?;
+    /// Returns an error if the session has expired at given unix timestamp (in seconds)
+    pub fn check_expiry(&self, now: u64) -> Result<()> {
+        if now >= self.expires_at {
+            Err(Error::new(
+                ErrorKind::MultiSigError,
+                format!(
+                    "Session with ID ({}) has expired; start a new session",
+                    hex::encode(&self.id)
+                ),
+            ))
+        } else {
+            Ok(())
+        }
+    }
 
+    /// Returns nonce commitment of current signer
+    pub fn nonce_commitment(
+        &self,
+        secret_nonce: &SecretNonce,
+        private_key: &PrivateKey,
+    ) -> Result<H256> {
+        self.with_signer_session(secret_nonce, private_key, |session| {
             Ok(session.get_my_nonce_commitment().serialize())
         })
     }
@@ -150,43 +187,11 @@ impl MultiSigSession {
 
     /// Returns nonce of current signer. This function will fail if nonce commitments from all co-signers are not
     /// received.
-    pub fn nonce(&self) -> Result<H256> {
+    pub fn nonce(&self, secret_nonce: &SecretNonce, private_key: &PrivateKey) -> Result<H256> {
         let nonce_commitments = self.nonce_commitments()?;
 
-        SECP.with(|secp| -> Result<H256> {
-            let (pk, pre_init) = self.get_preinit()?;
-            let mut session = MuSigSession::new(
-                &secp,
-                MuSigSessionID::from_slice(&self.id).chain(|| {
-                    (
-                        ErrorKind::DeserializationError,
-                        "Unable to deserialize multi-sig session ID from bytes",
-                    )
-                })?,
-                &Message::from_slice(&self.message).chain(|| {
-                    (
-                        ErrorKind::DeserializationError,
-                        "Unable to deserialize message to sign from bytes",
-                    )
-                })?,
-                &pk,
-                &pre_init,
-                self.signers.len(),
-                self.signer_index(&self.public_key)?,
-                &SecretKey::from(&self.private_key),
-            )
-            .chain(|| (ErrorKind::MultiSigError, "Unable to create session"))?;
-
-            nonce_commitments
-                .into_iter()
-                .map(|(public_key, nonce_commitment)| -> Result<()> {
-                    session.set_nonce_commitment(
-                        MuSigNonceCommitment::deserialize_from(nonce_commitment),
-                        self.signer_index(&public_key)?,
-                    );
-                    Ok(())
-                })
-                .collect::<Result<Vec<()>>>()?;
+        self.with_signer_session(secret_nonce, private_key, |session| {
+            self.set_nonce_commitments(session, nonce_commitments)?;
 
             let public_nonce = session.get_public_nonce().chain(|| {
                 (
@@ -199,52 +204,47 @@ impl MultiSigSession {
         })
     }
 
-    /// Adds nonce for signer corresponding to given public key
+    /// Adds nonce for signer corresponding to given public key. The nonce is checked against the nonce commitment
+    /// of the signer, so this function will fail if nonce commitments from all co-signers are not received.
     pub fn add_nonce(&mut self, public_key: &PublicKey, nonce: H256) -> Result<()> {
         let signer_index = self.signer_index(public_key)?;
+
+        if self.signers[signer_index].nonce.is_some() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Cannot add nonce twice for same signer",
+            ));
+        }
+
+        self.with_verifier_session(|session| {
+            session
+                .set_nonce(signer_index, MuSigNonce::deserialize_from(nonce))
+                .chain(|| {
+                    (
+                        ErrorKind::MultiSigError,
+                        format!(
+                            "Nonce of signer with public key ({}) does not match its nonce commitment",
+                            public_key
+                        ),
+                    )
+                })
+        })?;
+
         self.signers[signer_index].add_nonce(nonce)
     }
 
     /// Returns partial signature of current signer. This function will fail if nonces from all co-signers are not
     /// received.
-    pub fn partial_signature(&self) -> Result<H256> {
+    pub fn partial_signature(
+        &self,
+        secret_nonce: &SecretNonce,
+        private_key: &PrivateKey,
+    ) -> Result<H256> {
         let nonce_commitments = self.nonce_commitments()?;
         let nonces = self.nonces()?;
 
-        SECP.with(|secp| -> Result<H256> {
-            let (pk, pre_init) = self.get_preinit()?;
-            let mut session = MuSigSession::new(
-                &secp,
-                MuSigSessionID::from_slice(&self.id).chain(|| {
-                    (
-                        ErrorKind::DeserializationError,
-                        "Unable to deserialize multi-sig session ID from bytes",
-                    )
-                })?,
-                &Message::from_slice(&self.message).chain(|| {
-                    (
-                        ErrorKind::DeserializationError,
-                        "Unable to deserialize message to sign from bytes",
-                    )
-                })?,
-                &pk,
-                &pre_init,
-                self.signers.len(),
-                self.signer_index(&self.public_key)?,
-                &SecretKey::from(&self.private_key),
-            )
-            .chain(|| (ErrorKind::MultiSigError, "Unable to create session"))?;
-
-            nonce_commitments
-                .into_iter()
-                .map(|(public_key, nonce_commitment)| -> Result<()> {
-                    session.set_nonce_commitment(
-                        MuSigNonceCommitment::deserialize_from(nonce_commitment),
-                        self.signer_index(&public_key)?,
-                    );
-                    Ok(())
-                })
-                .collect::<Result<Vec<()>>>()?;
+        self.with_signer_session(secret_nonce, private_key, |session| {
+            self.set_nonce_commitments(session, nonce_commitments)?;
 
             session.get_public_nonce().chain(|| {
                 (
@@ -253,26 +253,7 @@ impl MultiSigSession {
                 )
             })?;
 
-            nonces
-                .into_iter()
-                .map(|(public_key, nonce)| {
-                    session
-                        .set_nonce(
-                            self.signer_index(&public_key)?,
-                            MuSigNonce::deserialize_from(nonce),
-                        )
-                        .chain(|| {
-                            (
-                                ErrorKind::MultiSigError,
-                                format!("Missing nonce of signer with public key: {}", public_key),
-                            )
-                        })
-                })
-                .collect::<Result<Vec<()>>>()?;
-
-            session
-                .combine_nonces()
-                .chain(|| (ErrorKind::MultiSigError, "Unable to combine nonces"))?;
+            self.set_nonces(session, nonces)?;
 
             Ok(session
                 .partial_sign()
@@ -286,91 +267,62 @@ impl MultiSigSession {
         })
     }
 
-    /// Adds partial signature for signer corresponding to given public key
+    /// Adds partial signature for signer corresponding to given public key. The partial signature is verified
+    /// against the public key and nonce of the signer, so this function will fail if nonces from all co-signers are
+    /// not received.
     pub fn add_partial_signature(
         &mut self,
         public_key: &PublicKey,
         partial_signature: H256,
     ) -> Result<()> {
         let signer_index = self.signer_index(public_key)?;
-        self.signers[signer_index].add_partial_signature(partial_signature)
-    }
 
-    /// Returns combined signature. This function will fail if partial signatures from all co-signers are not received.
-    pub fn signature(&self) -> Result<SchnorrSignature> {
-        let nonce_commitments = self.nonce_commitments()?;
+        if self.signers[signer_index].partial_signature.is_some() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Cannot add partial signature twice for same signer",
+            ));
+        }
+
         let nonces = self.nonces()?;
-        let partial_signatures = self.partial_signatures()?;
 
-        SECP.with(|secp| -> Result<SchnorrSignature> {
-            let (pk, pre_init) = self.get_preinit()?;
-            let mut session = MuSigSession::new(
-                &secp,
-                MuSigSessionID::from_slice(&self.id).chain(|| {
-                    (
-                        ErrorKind::DeserializationError,
-                        "Unable to deserialize multi-sig session ID from bytes",
-                    )
-                })?,
-                &Message::from_slice(&self.message).chain(|| {
+        self.with_verifier_session(|session| {
+            self.set_nonces(session, nonces)?;
+
+            let signature =
+                MuSigPartialSignature::deserialize_from(partial_signature).chain(|| {
                     (
                         ErrorKind::DeserializationError,
-                        "Unable to deserialize message to sign from bytes",
+                        format!(
+                        "Unable to deserialize partial signature of signer with public key ({})",
+                        public_key
+                    ),
                     )
-                })?,
-                &pk,
-                &pre_init,
-                self.signers.len(),
-                self.signer_index(&self.public_key)?,
-                &SecretKey::from(&self.private_key),
-            )
-            .chain(|| (ErrorKind::MultiSigError, "Unable to create session"))?;
-
-            nonce_commitments
-                .into_iter()
-                .map(|(public_key, nonce_commitment)| -> Result<()> {
-                    session.set_nonce_commitment(
-                        MuSigNonceCommitment::deserialize_from(nonce_commitment),
-                        self.signer_index(&public_key)?,
-                    );
-                    Ok(())
-                })
-                .collect::<Result<Vec<()>>>()?;
+                })?;
 
-            session.get_public_nonce().chain(|| {
-                (
+            if session.partial_sig_verify(&signature, signer_index, &public_key.into()) {
+                Ok(())
+            } else {
+                Err(Error::new(
                     ErrorKind::MultiSigError,
-                    "Missing nonce commitment of at least one signer",
-                )
-            })?;
+                    format!(
+                        "Invalid partial signature from signer with public key ({})",
+                        public_key
+                    ),
+                ))
+            }
+        })?;
 
-            nonces
-                .into_iter()
-                .map(|(public_key, nonce)| {
-                    session
-                        .set_nonce(
-                            self.signer_index(&public_key)?,
-                            MuSigNonce::deserialize_from(nonce),
-                        )
-                        .chain(|| {
-                            (
-                                ErrorKind::MultiSigError,
-                                format!("Missing nonce of signer with public key: {}", public_key),
-                            )
-                        })
-                })
-                .collect::<Result<Vec<()>>>()?;
+        self.signers[signer_index].add_partial_signature(partial_signature)
+    }
 
-            session
-                .combine_nonces()
-                .chain(|| (ErrorKind::MultiSigError, "Unable to combine nonces"))?;
+    /// Returns combined signature. This function will fail if partial signatures from all co-signers are not received.
+    pub fn signature(&self) -> Result<SchnorrSignature> {
+        let nonces = self.nonces()?;
+        let partial_signatures = self.partial_signatures()?;
 
-            session.partial_sign().chain(|| {
-                (
-                    ErrorKind::MultiSigError,
-                    "Unable to generate partial signature",
-                )
-            })?;
+        self.with_verifier_session(|session| {
+            self.set_nonces(session, nonces)?;
 
             Ok(session
                 .partial_sig_combine(
@@ -417,11 +369,129 @@ impl MultiSigSession {
     }
 
     /// Returns true if partial signature for given public key is already set, false otherwise
-    pub fn has_partial_signature(&mut self, public_key: &PublicKey) -> Result<bool> {
+    pub fn has_partial_signature(&self, public_key: &PublicKey) -> Result<bool> {
         let signer_index = self.signer_index(public_key)?;
         Ok(self.signers[signer_index].partial_signature.is_some())
     }
 
+    /// Runs given function with a MuSig session of current signer
+    fn with_signer_session<T, F>(
+        &self,
+        secret_nonce: &SecretNonce,
+        private_key: &PrivateKey,
+        f: F,
+    ) -> Result<T>
+    where
+        F: FnOnce(&mut MuSigSession) -> Result<T>,
+    {
+        if PublicKey::from(private_key) != self.public_key {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Private key does not belong to current signer",
+            ));
+        }
+
+        let message = self.musig_message()?;
+        let my_index = self.signer_index(&self.public_key)?;
+
+        SECP.with(|secp| -> Result<T> {
+            let (pk, pre_init) = self.get_preinit()?;
+            let mut session = MuSigSession::new(
+                &secp,
+                secret_nonce.session_id(),
+                &message,
+                &pk,
+                &pre_init,
+                self.signers.len(),
+                my_index,
+                &SecretKey::from(private_key),
+            )
+            .chain(|| (ErrorKind::MultiSigError, "Unable to create session"))?;
+
+            f(&mut session)
+        })
+    }
+
+    /// Runs given function with a MuSig verifier session (which does not need any secret). This function will fail
+    /// if nonce commitments from all co-signers are not received.
+    fn with_verifier_session<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut MuSigSession) -> Result<T>,
+    {
+        let nonce_commitments = self.nonce_commitments()?;
+        let message = self.musig_message()?;
+
+        SECP.with(|secp| -> Result<T> {
+            let (pk, pre_init) = self.get_preinit()?;
+            let commitments = nonce_commitments
+                .into_iter()
+                .map(|(_, nonce_commitment)| {
+                    MuSigNonceCommitment::deserialize_from(nonce_commitment)
+                })
+                .collect::<Vec<MuSigNonceCommitment>>();
+            let mut session =
+                MuSigSession::new_verifier(&secp, &message, &pk, &pre_init, &commitments).chain(
+                    || {
+                        (
+                            ErrorKind::MultiSigError,
+                            "Unable to create verifier session",
+                        )
+                    },
+                )?;
+
+            f(&mut session)
+        })
+    }
+
+    /// Sets nonce commitments of all the signers in given MuSig session
+    fn set_nonce_commitments(
+        &self,
+        session: &mut MuSigSession,
+        nonce_commitments: Vec<(PublicKey, H256)>,
+    ) -> Result<()> {
+        for (public_key, nonce_commitment) in nonce_commitments {
+            session.set_nonce_commitment(
+                MuSigNonceCommitment::deserialize_from(nonce_commitment),
+                self.signer_index(&public_key)?,
+            );
+        }
+        Ok(())
+    }
+
+    /// Sets nonces of all the signers in given MuSig session and combines them
+    fn set_nonces(&self, session: &mut MuSigSession, nonces: Vec<(PublicKey, H256)>) -> Result<()> {
+        for (public_key, nonce) in nonces {
+            session
+                .set_nonce(
+                    self.signer_index(&public_key)?,
+                    MuSigNonce::deserialize_from(nonce),
+                )
+                .chain(|| {
+                    (
+                        ErrorKind::MultiSigError,
+                        format!(
+                            "Nonce of signer with public key ({}) does not match its nonce commitment",
+                            public_key
+                        ),
+                    )
+                })?;
+        }
+
+        session
+            .combine_nonces()
+            .chain(|| (ErrorKind::MultiSigError, "Unable to combine nonces"))
+    }
+
+    /// Returns the message to be signed
+    fn musig_message(&self) -> Result<Message> {
+        Message::from_slice(&self.message).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Unable to deserialize message to sign from bytes",
+            )
+        })
+    }
+
     /// Returns index of signer with given public key
     fn signer_index(&self, public_key: &PublicKey) -> Result<usize> {
         self.signers
@@ -497,6 +567,22 @@ mod multi_sig_session_tests {
 
     use client_common::SECP;
 
+    const EXPIRES_AT: u64 = 1000;
+
+    fn new_session(
+        public_keys: &[PublicKey],
+        public_key: &PublicKey,
+    ) -> (MultiSigSession, SecretNonce) {
+        MultiSigSession::new(
+            [1u8; 32],
+            public_keys.to_vec(),
+            public_key.clone(),
+            "name".to_owned(),
+            EXPIRES_AT,
+        )
+        .unwrap()
+    }
+
     #[test]
     fn check_multi_sig_transaction_flow() {
         let message = [1u8; 32];
@@ -506,98 +592,73 @@ mod multi_sig_session_tests {
 
         let public_key_1 = PublicKey::from(&private_key_1);
         let public_key_2 = PublicKey::from(&private_key_2);
+        let public_keys = vec![public_key_1.clone(), public_key_2.clone()];
 
-        let mut session_1 = MultiSigSession::new(
-            message,
-            vec![public_key_1.clone(), public_key_2.clone()],
-            public_key_1.clone(),
-            private_key_1.clone(),
-        )
-        .unwrap();
-        let mut session_2 = MultiSigSession::new(
-            message,
-            vec![public_key_1.clone(), public_key_2.clone()],
-            public_key_2.clone(),
-            private_key_2.clone(),
-        )
-        .unwrap();
+        let (mut session_1, secret_nonce_1) = new_session(&public_keys, &public_key_1);
+        let (mut session_2, secret_nonce_2) = new_session(&public_keys, &public_key_2);
 
-        let nonce_commitment_1 = session_1.nonce_commitment().unwrap();
-        assert!(
-            session_1.nonce_commitment().is_ok(),
-            "Should be able to retrieve nonce commitment multiple times"
-        );
+        assert_ne!(session_1.id, session_2.id);
+        session_1
+            .nonce_commitment(&secret_nonce_1, &private_key_2)
+            .expect_err("Should not be able to use private key of another signer");
 
-        let nonce_commitment_2 = session_2.nonce_commitment().unwrap();
+        let nonce_commitment_1 = session_1
+            .nonce_commitment(&secret_nonce_1, &private_key_1)
+            .unwrap();
+        let nonce_commitment_2 = session_2
+            .nonce_commitment(&secret_nonce_2, &private_key_2)
+            .unwrap();
 
-        session_1
-            .add_nonce_commitment(&public_key_1, nonce_commitment_1)
-            .expect("Should be able to add nonce commitment to session 1");
-        session_1
-            .add_nonce_commitment(&public_key_2, nonce_commitment_2)
-            .expect("Should be able to add nonce commitment to session 1");
-        session_2
-            .add_nonce_commitment(&public_key_1, nonce_commitment_1)
-            .expect("Should be able to add nonce commitment to session 2");
-        session_2
-            .add_nonce_commitment(&public_key_2, nonce_commitment_2)
-            .expect("Should be able to add nonce commitment to session 2");
-
-        let nonce_1 = session_1.nonce().unwrap();
-        assert!(
-            session_1.nonce().is_ok(),
-            "Should be able to retrieve nonce multiple times"
-        );
+        for session in [&mut session_1, &mut session_2].iter_mut() {
+            session
+                .add_nonce_commitment(&public_key_1, nonce_commitment_1)
+                .expect("Should be able to add nonce commitment");
+            session
+                .add_nonce_commitment(&public_key_2, nonce_commitment_2)
+                .expect("Should be able to add nonce commitment");
+        }
 
-        let nonce_2 = session_2.nonce().unwrap();
+        let nonce_1 = session_1.nonce(&secret_nonce_1, &private_key_1).unwrap();
+        let nonce_2 = session_2.nonce(&secret_nonce_2, &private_key_2).unwrap();
 
         session_1
-            .add_nonce(&public_key_1, nonce_1.clone())
-            .expect("Should be able to add nonce to session 1");
-        session_1
-            .add_nonce(&public_key_2, nonce_2.clone())
-            .expect("Should be able to add nonce to session 1");
+            .add_nonce(&public_key_2, nonce_1)
+            .expect_err("Should not be able to add a nonce not matching its commitment");
+
+        for session in [&mut session_1, &mut session_2].iter_mut() {
+            session
+                .add_nonce(&public_key_1, nonce_1)
+                .expect("Should be able to add nonce");
+            session
+                .add_nonce(&public_key_2, nonce_2)
+                .expect("Should be able to add nonce");
+        }
         session_1
-            .add_nonce(&public_key_2, nonce_1.clone())
+            .add_nonce(&public_key_2, nonce_1)
             .expect_err("Should not be able to modify an already existing nonce");
-        session_2
-            .add_nonce(&public_key_1, nonce_1.clone())
-            .expect("Should be able to add nonce to session 2");
-        session_2
-            .add_nonce(&public_key_2, nonce_2.clone())
-            .expect("Should be able to add nonce to session 2");
 
         let partial_signature_1 = session_1
-            .partial_signature()
+            .partial_signature(&secret_nonce_1, &private_key_1)
             .expect("Should be able to generate partial signature for session 1");
-        assert!(
-            session_1.partial_signature().is_ok(),
-            "Should be able to retrieve partial signatures multiple times"
-        );
-
         let partial_signature_2 = session_2
-            .partial_signature()
+            .partial_signature(&secret_nonce_2, &private_key_2)
             .expect("Should be able to generate partial signature for session 2");
 
-        session_1
-            .add_partial_signature(&public_key_1, partial_signature_1)
-            .expect("Should be able to add partial signature to session 1");
-        session_1
-            .add_partial_signature(&public_key_2, partial_signature_2)
-            .expect("Should be able to add partial signature to session 1");
-        session_2
-            .add_partial_signature(&public_key_1, partial_signature_1)
-            .expect("Should be able to add partial signature to session 2");
-        session_2
-            .add_partial_signature(&public_key_2, partial_signature_2)
-            .expect("Should be able to add partial signature to session 2");
+        for session in [&mut session_1, &mut session_2].iter_mut() {
+            session
+                .add_partial_signature(&public_key_1, partial_signature_1)
+                .expect("Should be able to add partial signature");
+            session
+                .add_partial_signature(&public_key_2, partial_signature_2)
+                .expect("Should be able to add partial signature");
+        }
 
         let signature_1 = session_1.signature().unwrap();
         let signature_2 = session_2.signature().unwrap();
 
         assert_eq!(signature_1, signature_2);
 
-        let mut public_keys = vec![public_key_1, public_key_2];
+        let mut public_keys = public_keys;
         public_keys.sort();
 
         let combined_public_key = PublicKey::combine(&public_keys).unwrap().0;
@@ -608,4 +669,87 @@ mod multi_sig_session_tests {
                 .expect("Invalid signature");
         })
     }
+
+    #[test]
+    fn check_invalid_partial_signature_names_signer() {
+        let private_key_1 = PrivateKey::new().unwrap();
+        let private_key_2 = PrivateKey::new().unwrap();
+
+        let public_key_1 = PublicKey::from(&private_key_1);
+        let public_key_2 = PublicKey::from(&private_key_2);
+        let public_keys = vec![public_key_1.clone(), public_key_2.clone()];
+
+        let (mut session_1, secret_nonce_1) = new_session(&public_keys, &public_key_1);
+        let (mut session_2, secret_nonce_2) = new_session(&public_keys, &public_key_2);
+
+        let nonce_commitment_1 = session_1
+            .nonce_commitment(&secret_nonce_1, &private_key_1)
+            .unwrap();
+        let nonce_commitment_2 = session_2
+            .nonce_commitment(&secret_nonce_2, &private_key_2)
+            .unwrap();
+        for session in [&mut session_1, &mut session_2].iter_mut() {
+            session
+                .add_nonce_commitment(&public_key_1, nonce_commitment_1)
+                .unwrap();
+            session
+                .add_nonce_commitment(&public_key_2, nonce_commitment_2)
+                .unwrap();
+        }
+
+        let nonce_1 = session_1.nonce(&secret_nonce_1, &private_key_1).unwrap();
+        session_1
+            .add_partial_signature(&public_key_2, [2u8; 32])
+            .expect_err("Should not be able to add a partial signature before all nonces");
+
+        let nonce_2 = session_2.nonce(&secret_nonce_2, &private_key_2).unwrap();
+        session_1.add_nonce(&public_key_1, nonce_1).unwrap();
+        session_1.add_nonce(&public_key_2, nonce_2).unwrap();
+
+        let partial_signature_1 = session_1
+            .partial_signature(&secret_nonce_1, &private_key_1)
+            .unwrap();
+
+        let error = session_1
+            .add_partial_signature(&public_key_2, partial_signature_1)
+            .expect_err("Should not accept an invalid partial signature");
+        assert_eq!(ErrorKind::MultiSigError, error.kind());
+        assert!(error.to_string().contains(&public_key_2.to_string()));
+        assert!(!session_1.has_partial_signature(&public_key_2).unwrap());
+    }
+
+    #[test]
+    fn check_session_does_not_contain_secrets() {
+        let private_key_1 = PrivateKey::new().unwrap();
+        let private_key_2 = PrivateKey::new().unwrap();
+
+        let public_key_1 = PublicKey::from(&private_key_1);
+        let public_key_2 = PublicKey::from(&private_key_2);
+
+        let (session, secret_nonce) =
+            new_session(&[public_key_1.clone(), public_key_2], &public_key_1);
+
+        let encoded = session.encode();
+        let contains = |secret: &[u8]| encoded.windows(secret.len()).any(|window| window == secret);
+        assert!(!contains(&private_key_1.serialize()));
+        assert!(!contains(&secret_nonce.0));
+
+        let decoded = MultiSigSession::decode(&mut encoded.as_slice()).unwrap();
+        assert_eq!(session.id, decoded.id);
+        assert_eq!(EXPIRES_AT, decoded.expires_at);
+    }
+
+    #[test]
+    fn check_session_expiry() {
+        let public_key_1 = PublicKey::from(&PrivateKey::new().unwrap());
+        let public_key_2 = PublicKey::from(&PrivateKey::new().unwrap());
+
+        let (session, _) = new_session(&[public_key_1.clone(), public_key_2], &public_key_1);
+
+        assert!(session.check_expiry(EXPIRES_AT - 1).is_ok());
+        assert_eq!(
+            ErrorKind::MultiSigError,
+            session.check_expiry(EXPIRES_AT).unwrap_err().kind()
+        );
+    }
 }
@@ -1,32 +1,53 @@
 //! Management services
+mod backup_service;
 mod hd_key_service;
 mod hw_key_service;
 mod key_service;
+mod label_service;
 mod ledger_hw_key_service;
+mod migration_service;
 #[cfg(feature = "mock-hardware-wallet")]
 mod mock_hw_key_service;
 #[cfg(feature = "experimental")]
 mod multi_sig_session_service;
+mod remote_signer_service;
 mod root_hash_service;
+mod staking_history_service;
 mod sync_state_service;
+mod threshold_key_service;
 mod wallet_service;
 mod wallet_state_service;
 
 #[doc(hidden)]
 pub use self::wallet_state_service::WalletStateMemento;
 
-pub use self::hd_key_service::{HDAccountType, HdKey, HdKeyService};
+pub use self::backup_service::{BackupEntry, BackupHeader, BackupService, BACKUP_VERSION};
+pub use self::hd_key_service::{
+    account_wallet_name, AccountXpubs, HDAccountType, HdKey, HdKeyService, XpubKey,
+    ACCOUNT_SEPARATOR,
+};
 pub use self::hw_key_service::{HwKeyService, UnauthorizedHwKeyService};
 pub use self::key_service::KeyService;
+pub use self::label_service::{LabelMap, LabelService};
 pub use self::ledger_hw_key_service::{LedgerService, LedgerSignKey};
+pub use self::migration_service::{wallet_migrations, MigrationService};
 #[cfg(feature = "mock-hardware-wallet")]
 pub use self::mock_hw_key_service::{MockHardwareKey, MockHardwareService, MockHardwareWallet};
 #[cfg(feature = "experimental")]
-pub use self::multi_sig_session_service::MultiSigSessionService;
+pub use self::multi_sig_session_service::{MultiSigSessionService, DEFAULT_SESSION_TTL};
+pub use self::remote_signer_service::{
+    RemoteSignKey, RemoteSigner, RemoteSignerEndpoint, RemoteSignerService, SignatureAlgorithm,
+    SignerPolicy, SignerRequest, SignerResponse,
+};
 pub use self::root_hash_service::RootHashService;
+pub use self::staking_history_service::{
+    delete_staking_history, load_staking_history, save_staking_history, StakingHistory,
+    StakingHistoryService,
+};
 pub use self::sync_state_service::{
     delete_sync_state, load_sync_state, save_sync_state, SyncState, SyncStateService,
 };
+pub use self::threshold_key_service::{ThresholdKeyService, DEFAULT_THRESHOLD_SESSION_TTL};
 pub use self::wallet_service::{load_wallet, Wallet, WalletInfo, WalletService, WalletStorageImpl};
 pub use self::wallet_state_service::{
     delete_wallet_state, load_wallet_state, modify_wallet_state, save_wallet_state, WalletState,
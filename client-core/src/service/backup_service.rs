@@ -0,0 +1,482 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use parity_scale_codec::{Decode, Encode};
+use zeroize::Zeroize;
+
+use client_common::storage::{decrypt_bytes, encrypt_bytes, value_record_key, SCHEMA_KEYSPACE};
+use client_common::{Error, ErrorKind, Result, ResultExt, SecKey, Storage};
+
+use super::hd_key_service::{ACCOUNT_KEYSPACE, KEYSPACE as HD_KEY_KEYSPACE, XPUB_KEYSPACE};
+use super::key_service::KEYSPACE as KEY_KEYSPACE;
+use super::label_service::KEYSPACE as LABEL_KEYSPACE;
+use super::migration_service::wallet_migrations;
+#[cfg(feature = "experimental")]
+use super::multi_sig_session_service::KEYSPACE as MULTI_SIG_SESSION_KEYSPACE;
+use super::staking_history_service::KEYSPACE as STAKING_HISTORY_KEYSPACE;
+use super::sync_state_service::KEYSPACE as SYNC_STATE_KEYSPACE;
+use super::wallet_service::{
+    get_wallet_keyspace, get_wallet_keyspaces, KEYSPACE as WALLET_KEYSPACE,
+};
+use super::wallet_state_service::KEYSPACE as WALLET_STATE_KEYSPACE;
+
+/// Current version of the wallet backup format
+pub const BACKUP_VERSION: u16 = 1;
+
+const BACKUP_MAGIC: &[u8; 8] = b"CROWALLT";
+const NONCE_SIZE: usize = 12;
+/// keyspace of the scheduled payments of the RPC server (`wallet name -> schedules`)
+const PAYMENT_SCHEDULE_KEYSPACE: &str = "rpc_payment_schedule";
+
+/// Header of a wallet backup (stored in plain text and authenticated with the content)
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct BackupHeader {
+    /// version of the backup format
+    pub version: u16,
+    /// name of the wallet
+    pub name: String,
+    /// storage names of the wallet and its accounts
+    pub wallet_names: Vec<String>,
+}
+
+/// Raw storage entry (values of secure keyspaces stay encrypted with the wallet's enckey)
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct BackupEntry {
+    /// keyspace of the entry
+    pub keyspace: Vec<u8>,
+    /// key of the entry
+    pub key: Vec<u8>,
+    /// stored value
+    pub value: Vec<u8>,
+}
+
+/// Creates and restores encrypted backups of all the storage entries of a wallet
+///
+/// # Format
+///
+/// `magic ("CROWALLT") || SCALE(BackupHeader) || nonce || AES-GCM-SIV(SCALE(Vec<BackupEntry>))`
+///
+/// The content is encrypted with the wallet's enckey (derived from its passphrase and name)
+/// and authenticated together with the magic bytes and the header.
+///
+/// Values of versioned keyspaces come with their schema version (`core_schema_version` entries),
+/// so that restoring an older backup upgrades them.
+#[derive(Debug, Default, Clone)]
+pub struct BackupService<T: Storage> {
+    storage: T,
+}
+
+impl<T> BackupService<T>
+where
+    T: Storage,
+{
+    /// Creates a new instance of backup service
+    #[inline]
+    pub fn new(storage: T) -> Self {
+        Self { storage }
+    }
+
+    /// Creates the encrypted backup of the wallet `name` stored under `wallet_names`
+    /// (the wallet and its accounts)
+    pub fn backup(&self, name: &str, wallet_names: &[String], enckey: &SecKey) -> Result<Vec<u8>> {
+        let header = BackupHeader {
+            version: BACKUP_VERSION,
+            name: name.to_owned(),
+            wallet_names: wallet_names.to_vec(),
+        };
+
+        let migrations = wallet_migrations();
+        let mut entries = Vec::new();
+        for wallet_name in wallet_names {
+            for keyspace in named_keyspaces() {
+                if let Some(value) = self.storage.get(&keyspace, wallet_name)? {
+                    if migrations.latest_version(&keyspace) > 0 {
+                        let version = migrations.value_version(
+                            &self.storage,
+                            &keyspace,
+                            wallet_name.as_bytes(),
+                        )?;
+                        entries.push(BackupEntry {
+                            keyspace: SCHEMA_KEYSPACE.as_bytes().to_vec(),
+                            key: value_record_key(&keyspace, wallet_name.as_bytes()),
+                            value: version.encode(),
+                        });
+                    }
+                    entries.push(BackupEntry {
+                        keyspace: keyspace.into_bytes(),
+                        key: wallet_name.as_bytes().to_vec(),
+                        value,
+                    });
+                }
+            }
+            for keyspace in get_wallet_keyspaces(wallet_name) {
+                for key in self.storage.keys(&keyspace)? {
+                    let value = self
+                        .storage
+                        .get(&keyspace, &key)?
+                        .chain(|| (ErrorKind::StorageError, "Storage entry disappeared"))?;
+                    entries.push(BackupEntry {
+                        keyspace: keyspace.clone().into_bytes(),
+                        key,
+                        value,
+                    });
+                }
+            }
+        }
+        let enckey = self.storage.resolve_enckey(enckey)?;
+        self.backup_sessions(&enckey, &mut entries)?;
+
+        let mut archive = BACKUP_MAGIC.to_vec();
+        header.encode_to(&mut archive);
+        let mut content = entries.encode();
+        let cipher = encrypt_bytes(&archive, &enckey, &content);
+        content.zeroize();
+        archive.extend_from_slice(&cipher?);
+        Ok(archive)
+    }
+
+    /// Returns the (validated) header of a backup without decrypting it
+    pub fn header(&self, archive: &[u8]) -> Result<BackupHeader> {
+        parse_header(archive).map(|(header, _)| header)
+    }
+
+    /// Validates a backup and writes its entries to the storage
+    ///
+    /// Fails if any of the wallets in the backup already exists.
+    pub fn restore(&self, archive: &[u8], enckey: &SecKey) -> Result<BackupHeader> {
+        let (header, header_length) = parse_header(archive)?;
+        if archive.len() < header_length + NONCE_SIZE {
+            return Err(Error::new(
+                ErrorKind::DeserializationError,
+                "Wallet backup is truncated",
+            ));
+        }
+        let enckey = self.storage.resolve_enckey(enckey)?;
+        let mut content =
+            decrypt_bytes(&archive[..header_length], &enckey, &archive[header_length..]).chain(
+                || {
+                    (
+                        ErrorKind::DecryptionError,
+                        "Unable to decrypt wallet backup: incorrect passphrase or corrupted backup",
+                    )
+                },
+            )?;
+        let entries = Vec::<BackupEntry>::decode(&mut content.as_slice());
+        content.zeroize();
+        let entries = entries.chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Unable to decode entries of wallet backup",
+            )
+        })?;
+
+        let allowed_keyspaces = allowed_keyspaces(&header.wallet_names);
+        let versioned_values = versioned_values(&header.wallet_names);
+        let migrations = wallet_migrations();
+        let mut versions = BTreeMap::new();
+        for entry in entries.iter() {
+            if entry.keyspace == SCHEMA_KEYSPACE.as_bytes() {
+                let (keyspace, key) = versioned_values.get(&entry.key).chain(|| {
+                    (
+                        ErrorKind::VerifyError,
+                        "Wallet backup contains an unexpected schema version record",
+                    )
+                })?;
+                let version = u32::decode(&mut entry.value.as_slice()).chain(|| {
+                    (
+                        ErrorKind::DeserializationError,
+                        "Unable to decode schema version in wallet backup",
+                    )
+                })?;
+                if version > migrations.version(&self.storage, keyspace)? {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        "Wallet backup was created by a newer version",
+                    ));
+                }
+                versions.insert((keyspace.as_bytes().to_vec(), key.clone()), version);
+            } else if !allowed_keyspaces.contains(&entry.keyspace) {
+                return Err(Error::new(
+                    ErrorKind::VerifyError,
+                    format!(
+                        "Wallet backup contains an entry of unexpected keyspace {}",
+                        String::from_utf8_lossy(&entry.keyspace)
+                    ),
+                ));
+            }
+        }
+        for wallet_name in header.wallet_names.iter() {
+            if self
+                .storage
+                .contains_key(get_wallet_keyspace(), wallet_name)?
+            {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Wallet {} already exists", wallet_name),
+                ));
+            }
+        }
+
+        for entry in entries {
+            if entry.keyspace == SCHEMA_KEYSPACE.as_bytes() {
+                continue;
+            }
+            match versions.remove(&(entry.keyspace.clone(), entry.key.clone())) {
+                Some(version) => migrations.import_value(
+                    &self.storage,
+                    &String::from_utf8_lossy(&entry.keyspace),
+                    &entry.key,
+                    entry.value,
+                    version,
+                )?,
+                None => self.storage.set(entry.keyspace, entry.key, entry.value)?,
+            }
+        }
+        self.storage.flush()?;
+        Ok(header)
+    }
+
+    /// adds the multi-sig sessions encrypted with the wallet's enckey
+    #[cfg(feature = "experimental")]
+    fn backup_sessions(&self, enckey: &SecKey, entries: &mut Vec<BackupEntry>) -> Result<()> {
+        for key in self.storage.keys(MULTI_SIG_SESSION_KEYSPACE)? {
+            if let Some(value) = self.storage.get(MULTI_SIG_SESSION_KEYSPACE, &key)? {
+                if value.len() > NONCE_SIZE && decrypt_bytes(&key, enckey, &value).is_ok() {
+                    entries.push(BackupEntry {
+                        keyspace: MULTI_SIG_SESSION_KEYSPACE.as_bytes().to_vec(),
+                        key,
+                        value,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "experimental"))]
+    fn backup_sessions(&self, _enckey: &SecKey, _entries: &mut Vec<BackupEntry>) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// keyspaces storing wallet data under the wallet name
+fn named_keyspaces() -> Vec<String> {
+    vec![
+        get_wallet_keyspace(),
+        WALLET_KEYSPACE.to_owned(),
+        KEY_KEYSPACE.to_owned(),
+        HD_KEY_KEYSPACE.to_owned(),
+        XPUB_KEYSPACE.to_owned(),
+        ACCOUNT_KEYSPACE.to_owned(),
+        SYNC_STATE_KEYSPACE.to_owned(),
+        WALLET_STATE_KEYSPACE.to_owned(),
+        LABEL_KEYSPACE.to_owned(),
+        STAKING_HISTORY_KEYSPACE.to_owned(),
+        PAYMENT_SCHEDULE_KEYSPACE.to_owned(),
+    ]
+}
+
+/// version record keys of the values of versioned keyspaces (`record key -> (keyspace, key)`)
+fn versioned_values(wallet_names: &[String]) -> BTreeMap<Vec<u8>, (String, Vec<u8>)> {
+    let migrations = wallet_migrations();
+    named_keyspaces()
+        .into_iter()
+        .filter(|keyspace| migrations.latest_version(keyspace) > 0)
+        .flat_map(|keyspace| {
+            wallet_names.iter().map(move |name| {
+                (
+                    value_record_key(&keyspace, name.as_bytes()),
+                    (keyspace.clone(), name.as_bytes().to_vec()),
+                )
+            })
+        })
+        .collect()
+}
+
+/// keyspaces storing data of several wallets under other keys
+#[cfg(feature = "experimental")]
+fn shared_keyspaces() -> Vec<String> {
+    vec![MULTI_SIG_SESSION_KEYSPACE.to_owned()]
+}
+
+#[cfg(not(feature = "experimental"))]
+fn shared_keyspaces() -> Vec<String> {
+    Vec::new()
+}
+
+fn allowed_keyspaces(wallet_names: &[String]) -> BTreeSet<Vec<u8>> {
+    named_keyspaces()
+        .into_iter()
+        .chain(shared_keyspaces())
+        .chain(
+            wallet_names
+                .iter()
+                .flat_map(|name| get_wallet_keyspaces(name)),
+        )
+        .map(String::into_bytes)
+        .collect()
+}
+
+/// returns the header and the length of the authenticated prefix (magic and header)
+fn parse_header(archive: &[u8]) -> Result<(BackupHeader, usize)> {
+    if !archive.starts_with(BACKUP_MAGIC) {
+        return Err(Error::new(
+            ErrorKind::DeserializationError,
+            "Not a wallet backup",
+        ));
+    }
+    let mut input = &archive[BACKUP_MAGIC.len()..];
+    let version = u16::decode(&mut input).chain(|| {
+        (
+            ErrorKind::DeserializationError,
+            "Unable to decode version of wallet backup",
+        )
+    })?;
+    if version != BACKUP_VERSION {
+        return Err(Error::new(
+            ErrorKind::DeserializationError,
+            format!(
+                "Unsupported wallet backup version {} (supported: {})",
+                version, BACKUP_VERSION
+            ),
+        ));
+    }
+    let mut input = &archive[BACKUP_MAGIC.len()..];
+    let header = BackupHeader::decode(&mut input).chain(|| {
+        (
+            ErrorKind::DeserializationError,
+            "Unable to decode header of wallet backup",
+        )
+    })?;
+    if !header.wallet_names.contains(&header.name) {
+        return Err(Error::new(
+            ErrorKind::VerifyError,
+            "Wallet backup does not contain the wallet",
+        ));
+    }
+    Ok((header, archive.len() - input.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::account_wallet_name;
+    use crate::types::WalletKind;
+    use crate::wallet::{DefaultWalletClient, WalletClient};
+    use client_common::storage::MemoryStorage;
+    use secstr::SecUtf8;
+
+    #[test]
+    fn check_backup_and_restore() {
+        let storage = MemoryStorage::default();
+        wallet_migrations().migrate(&storage, false).unwrap();
+        let wallet = DefaultWalletClient::new_read_only(storage.clone());
+        let name = "name";
+        let passphrase = SecUtf8::from("passphrase");
+        let (enckey, _) = wallet
+            .new_wallet(name, &passphrase, WalletKind::HD, None)
+            .unwrap();
+        wallet.new_transfer_address(name, &enckey).unwrap();
+        wallet.new_staking_address(name, &enckey).unwrap();
+        wallet.new_account(name, &enckey).unwrap();
+        let account_name = account_wallet_name(name, 1);
+        wallet.new_transfer_address(&account_name, &enckey).unwrap();
+
+        let wallet_names = vec![name.to_owned(), account_name.clone()];
+        let archive = BackupService::new(storage.clone())
+            .backup(name, &wallet_names, &enckey)
+            .unwrap();
+        let header = BackupService::new(storage.clone())
+            .header(&archive)
+            .unwrap();
+        assert_eq!(header.version, BACKUP_VERSION);
+        assert_eq!(header.wallet_names, wallet_names);
+
+        // restoring over an existing wallet fails
+        assert!(BackupService::new(storage)
+            .restore(&archive, &enckey)
+            .is_err());
+
+        let new_storage = MemoryStorage::default();
+        let service = BackupService::new(new_storage.clone());
+        let wrong_enckey =
+            client_common::seckey::derive_enckey(&SecUtf8::from("wrong"), name).unwrap();
+        assert!(service.restore(&archive, &wrong_enckey).is_err());
+        let mut tampered = archive.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(service.restore(&tampered, &enckey).is_err());
+
+        service.restore(&archive, &enckey).unwrap();
+        let restored = DefaultWalletClient::new_read_only(new_storage);
+        assert_eq!(
+            restored
+                .transfer_addresses(name, &enckey, 0, 0, false)
+                .unwrap(),
+            wallet
+                .transfer_addresses(name, &enckey, 0, 0, false)
+                .unwrap()
+        );
+        assert_eq!(
+            restored
+                .staking_addresses(name, &enckey, 0, 0, false)
+                .unwrap(),
+            wallet
+                .staking_addresses(name, &enckey, 0, 0, false)
+                .unwrap()
+        );
+        assert_eq!(restored.accounts(name, &enckey).unwrap(), vec![0, 1]);
+        assert_eq!(
+            restored.new_transfer_address(name, &enckey).unwrap(),
+            wallet.new_transfer_address(name, &enckey).unwrap()
+        );
+    }
+
+    #[test]
+    fn check_restore_keeps_outdated_values() {
+        let storage = MemoryStorage::default();
+        let migrations = wallet_migrations();
+        migrations.migrate(&storage, false).unwrap();
+        let wallet = DefaultWalletClient::new_read_only(storage.clone());
+        let name = "name";
+        let (enckey, _) = wallet
+            .new_wallet(name, &SecUtf8::from("passphrase"), WalletKind::HD, None)
+            .unwrap();
+        // HD key waiting for its upgrade
+        storage
+            .set(
+                SCHEMA_KEYSPACE,
+                value_record_key(HD_KEY_KEYSPACE, name.as_bytes()),
+                0u32.encode(),
+            )
+            .unwrap();
+        let archive = BackupService::new(storage)
+            .backup(name, &[name.to_owned()], &enckey)
+            .unwrap();
+
+        let new_storage = MemoryStorage::default();
+        migrations.migrate(&new_storage, false).unwrap();
+        BackupService::new(new_storage.clone())
+            .restore(&archive, &enckey)
+            .unwrap();
+        assert_eq!(
+            migrations
+                .value_version(&new_storage, HD_KEY_KEYSPACE, name.as_bytes())
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            migrations
+                .migrate_secure(&new_storage, name.as_bytes(), &enckey, false)
+                .unwrap()
+                .changes
+                .len(),
+            1
+        );
+        assert_eq!(
+            migrations
+                .value_version(&new_storage, HD_KEY_KEYSPACE, name.as_bytes())
+                .unwrap(),
+            1
+        );
+    }
+}
@@ -1,4 +1,5 @@
-use parity_scale_codec::{Decode, Encode};
+use parity_scale_codec::{Decode, Encode, Error as CodecError, Input};
+use serde::{Deserialize, Serialize};
 
 use chain_core::init::network::get_network;
 use client_common::storage::decrypt_bytes;
@@ -9,13 +10,32 @@ use client_common::{
 use crate::types::AddressType;
 use crate::{HDSeed, Mnemonic};
 
-use crate::hd_wallet::ChainPath;
+use crate::hd_wallet::{AccountXpub, ChainPath};
 use std::convert::From;
-
-const KEYSPACE: &str = "core_hd_key";
+use std::str::FromStr;
+
+pub(crate) const KEYSPACE: &str = "core_hd_key";
+pub(crate) const XPUB_KEYSPACE: &str = "core_hd_xpub_key";
+/// storage name of an account wallet -> (wallet name, account number)
+pub(crate) const ACCOUNT_KEYSPACE: &str = "core_hd_account";
+
+/// Separator between the wallet name and the account number in the names of account wallets
+pub const ACCOUNT_SEPARATOR: char = '#';
+
+/// Returns the storage name of the given account of a wallet (account `0` is the wallet itself)
+///
+/// Wallets created before multi-account support may have names of this form too, so account wallets
+/// are recognized by their entries in the account registry (see `HdKeyService::account_of`), not by names.
+pub fn account_wallet_name(name: &str, account: u32) -> String {
+    if account == 0 {
+        name.to_owned()
+    } else {
+        format!("{}{}{}", name, ACCOUNT_SEPARATOR, account)
+    }
+}
 
 /// HD key
-#[derive(Debug, Clone, PartialEq, Default, Encode, Decode)]
+#[derive(Debug, Clone, PartialEq, Default, Encode)]
 pub struct HdKey {
     /// staking index
     pub staking_index: u32,
@@ -26,6 +46,131 @@ pub struct HdKey {
     pub viewkey_index: u32,
     /// HDSeed
     pub seed: HDSeed,
+    /// logical account of the wallet (BIP-44 accounts `3 * account` to `3 * account + 2`)
+    pub account: u32,
+}
+
+impl Decode for HdKey {
+    fn decode<I: Input>(input: &mut I) -> std::result::Result<Self, CodecError> {
+        let staking_index = u32::decode(input)?;
+        let transfer_index = u32::decode(input)?;
+        let viewkey_index = u32::decode(input)?;
+        let seed = HDSeed::decode(input)?;
+        // keys stored before multi-account support only have the first account
+        let account = match input.remaining_len()? {
+            Some(0) => 0,
+            _ => u32::decode(input)?,
+        };
+        Ok(HdKey {
+            staking_index,
+            transfer_index,
+            viewkey_index,
+            seed,
+            account,
+        })
+    }
+}
+
+impl HdKey {
+    /// BIP-44 account index of the given account type in this key's logical account
+    #[inline]
+    pub fn account_index(&self, account_type: HDAccountType) -> u32 {
+        account_type.account_index(self.account)
+    }
+
+    /// current address index of the given account type
+    #[inline]
+    pub fn index(&self, account_type: HDAccountType) -> u32 {
+        match account_type {
+            HDAccountType::Transfer => self.transfer_index,
+            HDAccountType::Staking => self.staking_index,
+            HDAccountType::Viewkey => self.viewkey_index,
+        }
+    }
+}
+
+/// Serialized account-level extended public keys of one logical account of a HD wallet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct AccountXpubs {
+    /// extended public key of the transfer account
+    pub transfer: String,
+    /// extended public key of the staking account
+    pub staking: String,
+}
+
+impl AccountXpubs {
+    /// Derives the extended public keys of the given logical account from a HD key
+    pub fn from_hd_key(hd_key: &HdKey) -> Result<Self> {
+        if hd_key.seed.as_bytes().is_empty() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Extended public keys can only be exported from HD wallet",
+            ));
+        }
+        let xpub = |account_type| {
+            AccountXpub::from_seed(
+                &hd_key.seed,
+                get_network(),
+                hd_key.account_index(account_type),
+            )
+        };
+        Ok(AccountXpubs {
+            transfer: xpub(HDAccountType::Transfer)?.to_string(),
+            staking: xpub(HDAccountType::Staking)?.to_string(),
+        })
+    }
+
+    /// Returns the extended public key of given account type
+    pub fn xpub(&self, account_type: HDAccountType) -> Result<AccountXpub> {
+        match account_type {
+            HDAccountType::Transfer => AccountXpub::from_str(&self.transfer),
+            HDAccountType::Staking => AccountXpub::from_str(&self.staking),
+            HDAccountType::Viewkey => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "View key can not be derived from extended public keys",
+            )),
+        }
+    }
+
+    /// Checks that the keys are the transfer and staking keys of the same logical account
+    pub fn verify(&self) -> Result<()> {
+        let transfer = self.xpub(HDAccountType::Transfer)?;
+        let staking = self.xpub(HDAccountType::Staking)?;
+        let account = transfer.account_index / HDAccountType::COUNT;
+        if transfer.account_index != HDAccountType::Transfer.account_index(account)
+            || staking.account_index != HDAccountType::Staking.account_index(account)
+            || transfer.parent_fingerprint != staking.parent_fingerprint
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Extended public keys are not the transfer and staking keys of one account",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Extended public keys and address indexes of a watch-only (xpub) wallet
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct XpubKey {
+    /// staking index
+    pub staking_index: u32,
+    /// transfer index
+    pub transfer_index: u32,
+    /// extended public keys
+    pub xpubs: AccountXpubs,
+}
+
+impl XpubKey {
+    /// current address index of the given account type
+    #[inline]
+    pub fn index(&self, account_type: HDAccountType) -> u32 {
+        match account_type {
+            HDAccountType::Transfer => self.transfer_index,
+            HDAccountType::Staking => self.staking_index,
+            HDAccountType::Viewkey => 0,
+        }
+    }
 }
 
 /// Enum for specifying different types of accounts
@@ -40,11 +185,20 @@ pub enum HDAccountType {
 }
 
 impl HDAccountType {
+    /// number of BIP-44 accounts used by one logical account
+    pub const COUNT: u32 = 3;
+
     /// get account index for hd wallet
     #[inline]
     pub fn index(self) -> u32 {
         self as u32
     }
+
+    /// get BIP-44 account index for the given logical account of hd wallet
+    #[inline]
+    pub fn account_index(self, account: u32) -> u32 {
+        account * Self::COUNT + self.index()
+    }
 }
 
 // AddressType is subset of HDAccountType
@@ -94,19 +248,25 @@ where
         Ok(index)
     }
 
-    /// Returns true if wallet's HD key is present in storage
+    /// Returns true if wallet's HD key or extended public keys are present in storage
     pub fn has_wallet(&self, name: &str) -> Result<bool> {
-        self.storage.contains_key(KEYSPACE, name)
+        Ok(self.storage.contains_key(KEYSPACE, name)?
+            || self.storage.contains_key(XPUB_KEYSPACE, name)?)
     }
 
     /// Delete wallet
     pub fn delete_wallet(&self, name: &str, enckey: &SecKey) -> Result<()> {
+        let keyspace = if self.storage.contains_key(XPUB_KEYSPACE, name)? {
+            XPUB_KEYSPACE
+        } else {
+            KEYSPACE
+        };
         self.storage
-            .get_secure(KEYSPACE, name, enckey)?
+            .get_secure(keyspace, name, enckey)?
             .err_kind(ErrorKind::InvalidInput, || {
                 format!("Wallet with name {} not found in hd key service", name)
             })?;
-        self.storage.delete(KEYSPACE, name)?;
+        self.storage.delete(keyspace, name)?;
         Ok(())
     }
 
@@ -130,10 +290,73 @@ where
             transfer_index: 0,
             viewkey_index: 0,
             seed: hd_seed,
+            account: 0,
         };
         self.add_hdkey(name, enckey, hd_key)
     }
 
+    /// Adds the HD key of a new logical account which shares the seed of the given wallet
+    ///
+    /// The account's key is stored under `account_wallet_name(name, account)`, which is registered
+    /// as an account wallet.
+    pub fn add_account(&self, name: &str, enckey: &SecKey, account: u32) -> Result<()> {
+        let account_name = account_wallet_name(name, account);
+        if self.storage.get(KEYSPACE, &account_name)?.is_some()
+            || self.storage.contains_key(ACCOUNT_KEYSPACE, &account_name)?
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Account {} of wallet {} already exists", account, name),
+            ));
+        }
+        let hd_key = self.get_hdkey(name, enckey)?.chain(|| {
+            (
+                ErrorKind::InvalidInput,
+                format!("HD Key with name ({}) not found", name),
+            )
+        })?;
+        let account_key = HdKey {
+            staking_index: 0,
+            transfer_index: 0,
+            viewkey_index: 0,
+            seed: hd_key.seed,
+            account,
+        };
+        self.add_hdkey(&account_name, enckey, account_key)?;
+        self.storage
+            .save(ACCOUNT_KEYSPACE, &account_name, &(name.to_owned(), account))
+    }
+
+    /// Returns the wallet name and the account number if `name` is the storage name of an account wallet
+    pub fn account_of(&self, name: &str) -> Result<Option<(String, u32)>> {
+        self.storage.load(ACCOUNT_KEYSPACE, name)
+    }
+
+    /// Returns the numbers of the accounts added to the wallet (in ascending order, without account `0`)
+    pub fn accounts(&self, name: &str) -> Result<Vec<u32>> {
+        let mut accounts = Vec::new();
+        for key in self.storage.keys(ACCOUNT_KEYSPACE)? {
+            let account_name = String::from_utf8(key).chain(|| {
+                (
+                    ErrorKind::DeserializationError,
+                    "Unable to deserialize account wallet name",
+                )
+            })?;
+            match self.account_of(&account_name)? {
+                Some((wallet_name, account)) if wallet_name == name => accounts.push(account),
+                _ => {}
+            }
+        }
+        accounts.sort_unstable();
+        Ok(accounts)
+    }
+
+    /// Removes the account wallet from the account registry (if it's registered)
+    pub fn delete_account(&self, name: &str) -> Result<()> {
+        self.storage.delete(ACCOUNT_KEYSPACE, name)?;
+        Ok(())
+    }
+
     /// Adds a new encoded raw hdkey in storage
     #[inline]
     pub fn add_hdkey(&self, name: &str, enckey: &SecKey, hd_key: HdKey) -> Result<()> {
@@ -156,8 +379,14 @@ where
         }
     }
 
-    /// peek key pair by index
-    pub fn peek_pubkey(&self, name: &str, enckey: &SecKey, index: u32) -> Result<PublicKey> {
+    /// peek public key of given account type by index
+    pub fn peek_pubkey(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        account_type: HDAccountType,
+        index: u32,
+    ) -> Result<PublicKey> {
         let bytes: Vec<u8> = self.storage.get_secure(KEYSPACE, name, enckey)?.chain(|| {
             (
                 ErrorKind::InvalidInput,
@@ -175,7 +404,130 @@ where
 
         hd_key
             .seed
-            .get_pubkey(get_network(), HDAccountType::Transfer.index(), index)
+            .get_pubkey(get_network(), hd_key.account_index(account_type), index)
+    }
+
+    /// Adds the extended public keys of a watch-only wallet and sets its indexes to zero
+    pub fn add_xpubs(&self, name: &str, enckey: &SecKey, xpubs: &AccountXpubs) -> Result<()> {
+        if self.has_wallet(name)? {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "HD Key with given name already exists",
+            ));
+        }
+        xpubs.verify()?;
+
+        let xpub_key = XpubKey {
+            staking_index: 0,
+            transfer_index: 0,
+            xpubs: xpubs.clone(),
+        };
+        self.storage
+            .set_secure(XPUB_KEYSPACE, name, xpub_key.encode(), enckey)
+            .map(|_| ())
+    }
+
+    /// Get the extended public keys and indexes of a watch-only wallet from storage
+    pub fn get_xpub_key(&self, name: &str, enckey: &SecKey) -> Result<Option<XpubKey>> {
+        let raw = self.storage.get_secure(XPUB_KEYSPACE, name, enckey)?;
+        match raw {
+            None => Ok(None),
+            Some(r) => {
+                let xpub_key = XpubKey::decode(&mut r.as_slice())
+                    .chain(|| (ErrorKind::VerifyError, "parse xpub key failed"))?;
+                Ok(Some(xpub_key))
+            }
+        }
+    }
+
+    /// Returns the extended public keys of the transfer and staking accounts of given wallet
+    ///
+    /// They are derived from the seed of HD wallets and the stored ones of watch-only wallets.
+    pub fn export_xpubs(&self, name: &str, enckey: &SecKey) -> Result<AccountXpubs> {
+        if let Some(xpub_key) = self.get_xpub_key(name, enckey)? {
+            return Ok(xpub_key.xpubs);
+        }
+        let hd_key = self.get_hdkey(name, enckey)?.chain(|| {
+            (
+                ErrorKind::InvalidInput,
+                format!("HD Key with name ({}) not found", name),
+            )
+        })?;
+        AccountXpubs::from_hd_key(&hd_key)
+    }
+
+    /// peek public key of given account type by index in a watch-only wallet
+    pub fn peek_xpub_pubkey(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        account_type: HDAccountType,
+        index: u32,
+    ) -> Result<PublicKey> {
+        let xpub_key = self.get_xpub_key(name, enckey)?.chain(|| {
+            (
+                ErrorKind::InvalidInput,
+                format!("Extended public keys with name ({}) not found", name),
+            )
+        })?;
+        xpub_key.xpubs.xpub(account_type)?.derive_public_key(index)
+    }
+
+    /// Generates the next public key of given account type in a watch-only wallet
+    pub fn generate_xpub_pubkey(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        account_type: HDAccountType,
+    ) -> Result<PublicKey> {
+        let bytes = self
+            .storage
+            .fetch_and_update_secure(XPUB_KEYSPACE, name, enckey, |bytes| {
+                let mut xpub_key_bytes = bytes.chain(|| {
+                    (
+                        ErrorKind::InvalidInput,
+                        format!("Extended public keys with name ({}) not found", name),
+                    )
+                })?;
+
+                let mut xpub_key = XpubKey::decode(&mut xpub_key_bytes).chain(|| {
+                    (
+                        ErrorKind::DeserializationError,
+                        "Unable to deserialize xpub key from bytes",
+                    )
+                })?;
+
+                match account_type {
+                    HDAccountType::Staking => xpub_key.staking_index += 1,
+                    HDAccountType::Transfer => xpub_key.transfer_index += 1,
+                    HDAccountType::Viewkey => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidInput,
+                            "View key can not be derived from extended public keys",
+                        ))
+                    }
+                }
+
+                Ok(Some(xpub_key.encode()))
+            })?
+            .chain(|| {
+                (
+                    ErrorKind::InvalidInput,
+                    format!("Extended public keys with name ({}) not found", name),
+                )
+            })?;
+
+        let xpub_key_bytes = decrypt_bytes(name, &self.storage.resolve_enckey(enckey)?, &bytes)?;
+        let xpub_key = XpubKey::decode(&mut xpub_key_bytes.as_slice()).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Unable to decode xpub key bytes",
+            )
+        })?;
+        xpub_key
+            .xpubs
+            .xpub(account_type)?
+            .derive_public_key(xpub_key.index(account_type))
     }
 
     /// update the stored HDKey, return the updated one
@@ -186,7 +538,8 @@ where
     ///
     /// - `purpose`: `44`
     /// - `coin_type`: `394` for mainnet and `1` for others
-    /// - `account`: `0` for `AddressType::Transfer` and `1` for `AddressType::Staking`
+    /// - `account`: `3 * account` for `AddressType::Transfer`, `3 * account + 1` for `AddressType::Staking`
+    ///   and `3 * account + 2` for the view key, where `account` is the logical account of the wallet
     /// - `change`: `0`
     /// - `address_index`: Index of address as retrieved from storage
     pub fn update_hd_key(
@@ -227,7 +580,7 @@ where
                 )
             })?;
 
-        let hd_key_bytes = decrypt_bytes(name, enckey, &bytes)?;
+        let hd_key_bytes = decrypt_bytes(name, &self.storage.resolve_enckey(enckey)?, &bytes)?;
         HdKey::decode(&mut hd_key_bytes.as_slice()).chain(|| {
             (
                 ErrorKind::DeserializationError,
@@ -246,15 +599,12 @@ where
         account_type: HDAccountType,
     ) -> Result<(PublicKey, PrivateKey)> {
         let hd_key = self.update_hd_key(name, enckey, account_type)?;
-        let index = match account_type {
-            HDAccountType::Transfer => hd_key.transfer_index,
-            HDAccountType::Staking => hd_key.staking_index,
-            HDAccountType::Viewkey => hd_key.viewkey_index,
-        };
 
-        hd_key
-            .seed
-            .derive_key_pair(get_network(), account_type.index(), index)
+        hd_key.seed.derive_key_pair(
+            get_network(),
+            hd_key.account_index(account_type),
+            hd_key.index(account_type),
+        )
     }
 
     /// Generate ChainPath for given wallet and address type
@@ -267,26 +617,29 @@ where
         account_type: HDAccountType,
     ) -> Result<ChainPath> {
         let hd_key = self.update_hd_key(name, enckey, account_type)?;
-        let index = match account_type {
-            HDAccountType::Transfer => hd_key.transfer_index,
-            HDAccountType::Staking => hd_key.staking_index,
-            HDAccountType::Viewkey => hd_key.viewkey_index,
-        };
-        let chain_path = ChainPath::create_bip44(get_network(), account_type.index(), index);
+        let chain_path = ChainPath::create_bip44(
+            get_network(),
+            hd_key.account_index(account_type),
+            hd_key.index(account_type),
+        );
         Ok(chain_path)
     }
 
     /// Clears all storage
     #[inline]
     pub fn clear(&self) -> Result<()> {
-        self.storage.clear(KEYSPACE)
+        self.storage.clear(KEYSPACE)?;
+        self.storage.clear(XPUB_KEYSPACE)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::service::WalletService;
+    use crate::types::WalletKind;
     use crate::wallet::{DefaultWalletClient, WalletClient};
+    use client_common::seckey::derive_enckey;
     use client_common::storage::MemoryStorage;
     use secstr::SecUtf8;
 
@@ -302,22 +655,35 @@ mod tests {
                 115, 216, 81, 144, 7, 21, 109, 237, 40, 136, 91, 227, 27, 77, 94, 2, 39, 164, 114,
                 51, 145, 97, 19, 147, 4, 127, 154, 228,
             ]),
+            account: 0,
         };
 
+        let legacy_encoded = vec![
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 5, 60, 53, 84, 12, 242, 183, 58, 174, 139,
+            134, 77, 28, 50, 203, 135, 181, 100, 155, 234, 4, 110, 57, 243, 155, 154, 44, 159, 112,
+            255, 130, 44, 171, 107, 46, 195, 115, 216, 81, 144, 7, 21, 109, 237, 40, 136, 91, 227,
+            27, 77, 94, 2, 39, 164, 114, 51, 145, 97, 19, 147, 4, 127, 154, 228,
+        ];
         let encoded = hd_key.encode();
-        assert_eq!(
-            encoded,
-            vec![
-                0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 5, 60, 53, 84, 12, 242, 183, 58, 174,
-                139, 134, 77, 28, 50, 203, 135, 181, 100, 155, 234, 4, 110, 57, 243, 155, 154, 44,
-                159, 112, 255, 130, 44, 171, 107, 46, 195, 115, 216, 81, 144, 7, 21, 109, 237, 40,
-                136, 91, 227, 27, 77, 94, 2, 39, 164, 114, 51, 145, 97, 19, 147, 4, 127, 154, 228
-            ],
-            "encode should be backward-compatible"
-        );
+        assert_eq!(&encoded[..legacy_encoded.len()], legacy_encoded.as_slice());
+        assert_eq!(&encoded[legacy_encoded.len()..], &[0, 0, 0, 0]);
 
         let decoded_hd_key = HdKey::decode(&mut encoded.as_slice()).unwrap();
         assert_eq!(hd_key, decoded_hd_key);
+        let decoded_legacy_hd_key = HdKey::decode(&mut legacy_encoded.as_slice()).unwrap();
+        assert_eq!(
+            hd_key, decoded_legacy_hd_key,
+            "decode should be backward-compatible"
+        );
+    }
+
+    #[test]
+    fn check_account_wallet_name() {
+        assert_eq!(account_wallet_name("wallet", 0), "wallet");
+        assert_eq!(account_wallet_name("wallet", 2), "wallet#2");
+        assert_eq!(HDAccountType::Transfer.account_index(0), 0);
+        assert_eq!(HDAccountType::Viewkey.account_index(0), 2);
+        assert_eq!(HDAccountType::Staking.account_index(2), 7);
     }
 
     #[test]
@@ -398,6 +764,153 @@ mod tests {
         }
     }
 
+    #[test]
+    fn check_hd_key_accounts() {
+        let storage = MemoryStorage::default();
+        let name = "testhdwallet";
+        let passphrase = SecUtf8::from("passphrase");
+        let mnemonic =
+            Mnemonic::from_secstr(&SecUtf8::from("speed tortoise kiwi forward extend baby acoustic foil coach castle ship purchase unlock base hip erode tag keen present vibrant oyster cotton write fetch")).unwrap();
+
+        let wallet = DefaultWalletClient::new_read_only(storage.clone());
+        let enckey = wallet
+            .restore_wallet(&name, &passphrase, &mnemonic)
+            .expect("restore wallet");
+        assert_eq!(wallet.accounts(name, &enckey).unwrap(), vec![0]);
+        assert_eq!(wallet.new_account(name, &enckey).unwrap(), 1);
+        assert_eq!(wallet.new_account(name, &enckey).unwrap(), 2);
+        assert_eq!(wallet.accounts(name, &enckey).unwrap(), vec![0, 1, 2]);
+        assert_eq!(wallet.wallets().unwrap(), vec![name.to_owned()]);
+
+        let account_name = account_wallet_name(name, 2);
+        assert!(wallet.new_account(&account_name, &enckey).is_err());
+        assert_ne!(
+            wallet.view_key(name, &enckey).unwrap(),
+            wallet.view_key(&account_name, &enckey).unwrap()
+        );
+
+        // the first address of the account doesn't move the indexes of the other accounts
+        wallet
+            .new_transfer_address(&account_name, &enckey)
+            .expect("get new transfer address");
+        let seed = HDSeed::from(&mnemonic);
+        let (public_key, _) = seed
+            .derive_key_pair(get_network(), HDAccountType::Transfer.account_index(2), 1)
+            .unwrap();
+        assert!(wallet
+            .public_keys(&account_name, &enckey)
+            .unwrap()
+            .contains(&public_key));
+        assert!(wallet.public_keys(name, &enckey).unwrap().is_empty());
+        assert_eq!(
+            wallet
+                .new_transfer_address(&name, &enckey)
+                .expect("get new transfer address")
+                .to_string(),
+            "dcro1lgray2pkuqnkvd3hvhcvfta2ku5q0t3x8s03ehslu5xsauv4clfqv4yl40"
+        );
+
+        wallet.delete_wallet(name, &passphrase).unwrap();
+        assert!(wallet.wallets().unwrap().is_empty());
+        assert!(wallet.view_key(&account_name, &enckey).is_err());
+    }
+
+    #[test]
+    fn check_legacy_wallet_name_with_account_separator() {
+        let storage = MemoryStorage::default();
+        let name = "testhdwallet";
+        let legacy_name = format!("{}{}1", name, ACCOUNT_SEPARATOR);
+        let passphrase = SecUtf8::from("passphrase");
+        let mnemonic =
+            Mnemonic::from_secstr(&SecUtf8::from("speed tortoise kiwi forward extend baby acoustic foil coach castle ship purchase unlock base hip erode tag keen present vibrant oyster cotton write fetch")).unwrap();
+
+        // created before the account separator was reserved
+        let legacy_enckey = derive_enckey(&passphrase, &legacy_name).unwrap();
+        let view_key = PublicKey::from(&PrivateKey::new().unwrap());
+        WalletService::new(storage.clone())
+            .create(&legacy_name, &legacy_enckey, view_key, WalletKind::Basic)
+            .unwrap();
+
+        let wallet = DefaultWalletClient::new_read_only(storage.clone());
+        let enckey = wallet
+            .restore_wallet(&name, &passphrase, &mnemonic)
+            .expect("restore wallet");
+        assert!(wallet
+            .new_wallet("other#1", &passphrase, WalletKind::Basic, None)
+            .is_err());
+        assert_eq!(wallet.accounts(name, &enckey).unwrap(), vec![0]);
+        // the account number of the legacy wallet's name is skipped
+        assert_eq!(wallet.new_account(name, &enckey).unwrap(), 2);
+        assert_eq!(wallet.accounts(name, &enckey).unwrap(), vec![0, 2]);
+        let mut wallets = wallet.wallets().unwrap();
+        wallets.sort();
+        assert_eq!(wallets, vec![name.to_owned(), legacy_name.clone()]);
+
+        wallet.delete_wallet(&legacy_name, &passphrase).unwrap();
+        assert_eq!(wallet.wallets().unwrap(), vec![name.to_owned()]);
+        assert_eq!(wallet.accounts(name, &enckey).unwrap(), vec![0, 2]);
+        wallet.delete_wallet(name, &passphrase).unwrap();
+        assert!(wallet.wallets().unwrap().is_empty());
+    }
+
+    #[test]
+    fn check_xpub_wallet() {
+        let storage = MemoryStorage::default();
+        let name = "testhdwallet";
+        let watch_name = "testxpubwallet";
+        let passphrase = SecUtf8::from("passphrase");
+        let mnemonic =
+            Mnemonic::from_secstr(&SecUtf8::from("speed tortoise kiwi forward extend baby acoustic foil coach castle ship purchase unlock base hip erode tag keen present vibrant oyster cotton write fetch")).unwrap();
+
+        let wallet = DefaultWalletClient::new_read_only(storage.clone());
+        let enckey = wallet
+            .restore_wallet(&name, &passphrase, &mnemonic)
+            .expect("restore wallet");
+        let xpubs = wallet.export_xpubs(name, &enckey).unwrap();
+        let view_key = wallet.view_key_private(name, &enckey).unwrap();
+
+        let mut swapped = xpubs.clone();
+        std::mem::swap(&mut swapped.transfer, &mut swapped.staking);
+        assert!(wallet
+            .restore_xpub_wallet(watch_name, &passphrase, &swapped, &view_key)
+            .is_err());
+
+        let watch_enckey = wallet
+            .restore_xpub_wallet(watch_name, &passphrase, &xpubs, &view_key)
+            .expect("restore xpub wallet");
+        assert_eq!(
+            xpubs,
+            wallet.export_xpubs(watch_name, &watch_enckey).unwrap()
+        );
+        assert!(wallet.new_account(watch_name, &watch_enckey).is_err());
+
+        for _ in 0..3 {
+            assert_eq!(
+                wallet.new_transfer_address(name, &enckey).unwrap(),
+                wallet
+                    .new_transfer_address(watch_name, &watch_enckey)
+                    .unwrap()
+            );
+            assert_eq!(
+                wallet.new_staking_address(name, &enckey).unwrap(),
+                wallet
+                    .new_staking_address(watch_name, &watch_enckey)
+                    .unwrap()
+            );
+        }
+        assert_eq!(
+            wallet
+                .peek_public_key(name, &enckey, AddressType::Transfer, 7)
+                .unwrap(),
+            wallet
+                .peek_public_key(watch_name, &watch_enckey, AddressType::Transfer, 7)
+                .unwrap()
+        );
+
+        wallet.delete_wallet(watch_name, &passphrase).unwrap();
+        assert_eq!(wallet.wallets().unwrap(), vec![name.to_owned()]);
+    }
+
     #[test]
     fn check_peek_pubkey() {
         let storage = MemoryStorage::default();
@@ -412,7 +925,12 @@ mod tests {
             .restore_wallet(&name, &passphrase, &mnemonic)
             .expect("restore wallet");
 
-        assert_eq!(true, service.peek_pubkey("", &enckey, 0).is_err());
+        assert_eq!(
+            true,
+            service
+                .peek_pubkey("", &enckey, HDAccountType::Transfer, 0)
+                .is_err()
+        );
 
         let adddress_type = AddressType::Staking;
         let account_type: HDAccountType = adddress_type.into();
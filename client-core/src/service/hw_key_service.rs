@@ -2,6 +2,7 @@ use crate::hd_wallet::ChainPath;
 use crate::service::ledger_hw_key_service::LedgerService;
 #[cfg(feature = "mock-hardware-wallet")]
 use crate::service::MockHardwareService;
+use crate::service::RemoteSignerService;
 use client_common::{ErrorKind, PrivateKeyAction, PublicKey, Result};
 
 /// collection of hardware key interfaces
@@ -22,7 +23,6 @@ pub struct UnauthorizedHwKeyService;
 impl HardwareWalletAction for UnauthorizedHwKeyService {}
 
 /// Hardware Key Service collections
-/// TODO: add Ledger, Trezor Service
 #[derive(Clone, Debug)]
 pub enum HwKeyService {
     /// unauthorized hardware key service
@@ -32,6 +32,8 @@ pub enum HwKeyService {
     Mock(MockHardwareService),
     /// ledger service
     Ledger(LedgerService),
+    /// remote signer service
+    RemoteSigner(RemoteSignerService),
 }
 
 impl Default for HwKeyService {
@@ -48,6 +50,7 @@ impl HwKeyService {
             #[cfg(feature = "mock-hardware-wallet")]
             HwKeyService::Mock(hw_key_service) => hw_key_service.get_sign_key(hd_path),
             HwKeyService::Ledger(ledger_service) => ledger_service.get_sign_key(hd_path),
+            HwKeyService::RemoteSigner(remote_signer) => remote_signer.get_sign_key(hd_path),
         }
     }
 
@@ -58,6 +61,7 @@ impl HwKeyService {
             #[cfg(feature = "mock-hardware-wallet")]
             HwKeyService::Mock(hw_key_service) => hw_key_service.get_public_key(chain_path),
             HwKeyService::Ledger(ledger_service) => ledger_service.get_public_key(chain_path),
+            HwKeyService::RemoteSigner(remote_signer) => remote_signer.get_public_key(chain_path),
         }
     }
 }
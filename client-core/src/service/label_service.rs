@@ -0,0 +1,206 @@
+use std::collections::BTreeMap;
+
+use client_common::{Result, SecKey, SecureStorage, Storage};
+
+use crate::types::{Contact, Label, LabelType};
+
+/// key space of wallet labels
+pub(crate) const KEYSPACE: &str = "core_wallet_label";
+
+/// Labels of a wallet indexed by type and reference
+pub type LabelMap = BTreeMap<(LabelType, String), Label>;
+
+/// Maintains address book and labels of wallets
+///
+/// Stores `wallet-name -> labels` (encrypted with wallet's enckey)
+#[derive(Debug, Default, Clone)]
+pub struct LabelService<S>
+where
+    S: Storage,
+{
+    storage: S,
+}
+
+impl<S> LabelService<S>
+where
+    S: Storage,
+{
+    /// Creates new instance of label service
+    #[inline]
+    pub fn new(storage: S) -> Self {
+        Self { storage }
+    }
+
+    /// Returns labels of wallet indexed by type and reference
+    pub fn label_map(&self, name: &str, enckey: &SecKey) -> Result<LabelMap> {
+        let labels: Vec<Label> = self
+            .storage
+            .load_secure(KEYSPACE, name, enckey)?
+            .unwrap_or_default();
+        Ok(labels
+            .into_iter()
+            .map(|label| ((label.label_type, label.reference.clone()), label))
+            .collect())
+    }
+
+    /// Returns all labels of wallet (sorted by type and reference)
+    pub fn labels(&self, name: &str, enckey: &SecKey) -> Result<Vec<Label>> {
+        Ok(self
+            .label_map(name, enckey)?
+            .into_iter()
+            .map(|(_, label)| label)
+            .collect())
+    }
+
+    /// Returns label of given reference
+    pub fn get_label(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        label_type: LabelType,
+        reference: &str,
+    ) -> Result<Option<Label>> {
+        Ok(self
+            .label_map(name, enckey)?
+            .remove(&(label_type, reference.to_owned())))
+    }
+
+    /// Returns address book of wallet
+    pub fn contacts(&self, name: &str, enckey: &SecKey) -> Result<Vec<Contact>> {
+        Ok(self
+            .labels(name, enckey)?
+            .into_iter()
+            .filter(|label| label.label_type == LabelType::Addr)
+            .map(Contact::from)
+            .collect())
+    }
+
+    /// Sets labels of wallet (replacing existing labels of same references)
+    pub fn set_labels(&self, name: &str, enckey: &SecKey, labels: Vec<Label>) -> Result<()> {
+        let mut label_map = self.label_map(name, enckey)?;
+        for label in labels {
+            label_map.insert((label.label_type, label.reference.clone()), label);
+        }
+        self.save(name, enckey, label_map)
+    }
+
+    /// Removes label of given reference, returns `false` if there was none
+    pub fn remove_label(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        label_type: LabelType,
+        reference: &str,
+    ) -> Result<bool> {
+        let mut label_map = self.label_map(name, enckey)?;
+        if label_map
+            .remove(&(label_type, reference.to_owned()))
+            .is_none()
+        {
+            return Ok(false);
+        }
+        self.save(name, enckey, label_map)?;
+        Ok(true)
+    }
+
+    /// Deletes all labels of wallet
+    #[inline]
+    pub fn delete_labels(&self, name: &str) -> Result<()> {
+        self.storage.delete(KEYSPACE, name).map(|_| ())
+    }
+
+    /// Clears all storage
+    #[inline]
+    pub fn clear(&self) -> Result<()> {
+        self.storage.clear(KEYSPACE)
+    }
+
+    fn save(&self, name: &str, enckey: &SecKey, label_map: LabelMap) -> Result<()> {
+        let labels = label_map
+            .into_iter()
+            .map(|(_, label)| label)
+            .collect::<Vec<_>>();
+        self.storage.save_secure(KEYSPACE, name, enckey, &labels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use client_common::seckey::derive_enckey;
+    use client_common::storage::MemoryStorage;
+    use client_common::ErrorKind;
+    use secstr::SecUtf8;
+
+    const TRANSFER_ADDRESS: &str =
+        "dcro1zz30nheum6vnug3mjs0j4kw4w739tca8cuqae2kdjmt8suhv693qcs3qyn";
+    const TRANSACTION_ID: &str = "0f46e113c21f9eacb26d752f9523746cf8d47ecbea492736d176005911f973a5";
+
+    #[test]
+    fn check_flow() {
+        let label_service = LabelService::new(MemoryStorage::default());
+        let name = "name";
+        let enckey = derive_enckey(&SecUtf8::from("passphrase"), name).unwrap();
+
+        assert!(label_service.labels(name, &enckey).unwrap().is_empty());
+
+        let contact = Label::new(
+            LabelType::Addr,
+            TRANSFER_ADDRESS,
+            "alice".to_owned(),
+            Some("landlord".to_owned()),
+        )
+        .unwrap();
+        let tx_label = Label::new(LabelType::Tx, TRANSACTION_ID, "rent".to_owned(), None).unwrap();
+        label_service
+            .set_labels(name, &enckey, vec![tx_label.clone(), contact.clone()])
+            .unwrap();
+        assert_eq!(
+            vec![contact.clone(), tx_label.clone()],
+            label_service.labels(name, &enckey).unwrap()
+        );
+        assert_eq!(
+            vec![Contact {
+                address: TRANSFER_ADDRESS.to_owned(),
+                name: "alice".to_owned(),
+                notes: Some("landlord".to_owned()),
+            }],
+            label_service.contacts(name, &enckey).unwrap()
+        );
+
+        // same reference replaces the label
+        let renamed =
+            Label::new(LabelType::Tx, TRANSACTION_ID, "deposit".to_owned(), None).unwrap();
+        label_service
+            .set_labels(name, &enckey, vec![renamed.clone()])
+            .unwrap();
+        assert_eq!(
+            Some(renamed),
+            label_service
+                .get_label(name, &enckey, LabelType::Tx, TRANSACTION_ID)
+                .unwrap()
+        );
+
+        // labels are encrypted with wallet's enckey
+        let other_enckey = derive_enckey(&SecUtf8::from("other"), name).unwrap();
+        assert_eq!(
+            ErrorKind::DecryptionError,
+            label_service
+                .labels(name, &other_enckey)
+                .unwrap_err()
+                .kind()
+        );
+
+        assert!(label_service
+            .remove_label(name, &enckey, LabelType::Tx, TRANSACTION_ID)
+            .unwrap());
+        assert!(!label_service
+            .remove_label(name, &enckey, LabelType::Tx, TRANSACTION_ID)
+            .unwrap());
+        assert_eq!(vec![contact], label_service.labels(name, &enckey).unwrap());
+
+        label_service.delete_labels(name).unwrap();
+        assert!(label_service.labels(name, &enckey).unwrap().is_empty());
+    }
+}
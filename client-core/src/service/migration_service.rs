@@ -0,0 +1,328 @@
+use parity_scale_codec::{Decode, Encode};
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chain_core::common::H256;
+use chain_core::tx::data::{input::TxoPointer, output::TxOut, TxId};
+use client_common::storage::{Migration, MigrationRegistry, MigrationReport};
+use client_common::{Error, ErrorKind, Result, ResultExt, SecKey, Storage};
+
+use super::hd_key_service::{HdKey, KEYSPACE as HD_KEY_KEYSPACE};
+use super::sync_state_service::{SyncState, KEYSPACE as SYNC_STATE_KEYSPACE};
+use super::wallet_state_service::{WalletState, KEYSPACE as WALLET_STATE_KEYSPACE};
+use crate::types::{TransactionChange, TransactionPending};
+
+/// Migrations of the wallet storage, in order
+///
+/// | keyspace | version | change |
+/// |----------|---------|--------|
+/// | `core_wallet_sync` | 1 | `SyncState::last_filter_header` added (unknown unless at genesis) |
+/// | `core_hd_key` | 1 | `HdKey::account` appended (`0` for existing keys) |
+/// | `core_wallet_state` | 1 | `WalletState::frozen_outputs` appended (none for existing states) |
+pub fn wallet_migrations() -> MigrationRegistry {
+    MigrationRegistry::new()
+        .register(Migration {
+            keyspace: SYNC_STATE_KEYSPACE,
+            version: 1,
+            description: "add last verified block filter header to sync state",
+            encrypted: false,
+            upgrade: add_sync_state_filter_header,
+        })
+        .register(Migration {
+            keyspace: HD_KEY_KEYSPACE,
+            version: 1,
+            description: "add logical account to HD key",
+            encrypted: true,
+            upgrade: add_hd_key_account,
+        })
+        .register(Migration {
+            keyspace: WALLET_STATE_KEYSPACE,
+            version: 1,
+            description: "add frozen outputs to wallet state",
+            encrypted: true,
+            upgrade: add_wallet_state_frozen_outputs,
+        })
+}
+
+/// sync state before the block filter header chain
+#[derive(Decode)]
+struct SyncStateV0 {
+    last_block_height: u64,
+    last_app_hash: String,
+    last_block_hash: String,
+    staking_root: H256,
+    trusted: bool,
+}
+
+/// wallet state before coin control
+#[derive(Decode)]
+struct WalletStateV0 {
+    unspent_transactions: BTreeMap<TxoPointer, TxOut>,
+    pending_transactions: BTreeMap<TxId, TransactionPending>,
+    transaction_history: BTreeMap<TxId, TransactionChange>,
+    transaction_log: Vec<TxId>,
+}
+
+/// decodes a value of an old version (which must be entirely consumed)
+fn decode_old_version<T: Decode>(value: &[u8], name: &str, version: u32) -> Result<T> {
+    let mut input = value;
+    let decoded = T::decode(&mut input).chain(|| {
+        (
+            ErrorKind::DeserializationError,
+            format!("Unable to decode {} of version {}", name, version),
+        )
+    })?;
+    if !input.is_empty() {
+        return Err(Error::new(
+            ErrorKind::DeserializationError,
+            format!(
+                "Unable to decode {} of version {}: {} trailing bytes",
+                name,
+                version,
+                input.len()
+            ),
+        ));
+    }
+    Ok(decoded)
+}
+
+fn add_sync_state_filter_header(value: &[u8]) -> Result<Vec<u8>> {
+    let state: SyncStateV0 = decode_old_version(value, "sync state", 0)?;
+    // the filter header chain starts from zero at genesis, later headers are unknown
+    // and adopted from the next block (without verification)
+    let last_filter_header = if state.last_block_height == 0 {
+        Some([0u8; 32])
+    } else {
+        None
+    };
+    Ok(SyncState {
+        last_block_height: state.last_block_height,
+        last_app_hash: state.last_app_hash,
+        last_block_hash: state.last_block_hash,
+        staking_root: state.staking_root,
+        trusted: state.trusted,
+        last_filter_header,
+    }
+    .encode())
+}
+
+fn add_hd_key_account(value: &[u8]) -> Result<Vec<u8>> {
+    // keys without account are decoded with the first account
+    let hd_key = HdKey::decode(&mut &value[..]).chain(|| {
+        (
+            ErrorKind::DeserializationError,
+            "Unable to decode HD key of version 0",
+        )
+    })?;
+    Ok(hd_key.encode())
+}
+
+fn add_wallet_state_frozen_outputs(value: &[u8]) -> Result<Vec<u8>> {
+    let state: WalletStateV0 = decode_old_version(value, "wallet state", 0)?;
+    Ok(WalletState {
+        unspent_transactions: state.unspent_transactions,
+        pending_transactions: state.pending_transactions,
+        transaction_history: state.transaction_history,
+        transaction_log: state.transaction_log,
+        frozen_outputs: BTreeSet::new(),
+    }
+    .encode())
+}
+
+/// Upgrades the stored wallet data to the current schema
+#[derive(Default, Clone)]
+pub struct MigrationService<S: Storage> {
+    storage: S,
+    registry: MigrationRegistry,
+}
+
+impl<S> MigrationService<S>
+where
+    S: Storage,
+{
+    /// Creates a new instance of migration service with the wallet migrations
+    #[inline]
+    pub fn new(storage: S) -> Self {
+        Self {
+            storage,
+            registry: wallet_migrations(),
+        }
+    }
+
+    /// Migrates the unencrypted data and records the encrypted data to upgrade (only reports the
+    /// changes in a dry run)
+    #[inline]
+    pub fn migrate(&self, dry_run: bool) -> Result<MigrationReport> {
+        self.registry.migrate(&self.storage, dry_run)
+    }
+
+    /// Upgrades the encrypted data of a wallet (only reports the changes in a dry run)
+    #[inline]
+    pub fn migrate_wallet(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        dry_run: bool,
+    ) -> Result<MigrationReport> {
+        self.registry
+            .migrate_secure(&self.storage, name.as_bytes(), enckey, dry_run)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::{load_sync_state, HdKeyService, WalletStateService};
+    use crate::HDSeed;
+    use chain_core::init::coin::Coin;
+    use chain_core::tx::data::address::ExtendedAddr;
+    use client_common::seckey::derive_enckey;
+    use client_common::storage::{MemoryStorage, MigrationAction};
+    use client_common::SecureStorage;
+    use secstr::SecUtf8;
+
+    /// `SyncStateV0 { last_block_height: 5, last_app_hash: "AB", last_block_hash: "CD",
+    /// staking_root: [1; 32], trusted: true }`
+    const SYNC_STATE_V0: &str = "0500000000000000084142084344010101010101010101010101010101010101010101010101010101010101010101";
+
+    #[test]
+    fn check_sync_state_fixture_migration() {
+        let storage = MemoryStorage::default();
+        storage
+            .set(
+                SYNC_STATE_KEYSPACE,
+                "name",
+                hex::decode(SYNC_STATE_V0).unwrap(),
+            )
+            .unwrap();
+        assert!(load_sync_state(&storage, "name").is_err());
+
+        let service = MigrationService::new(storage.clone());
+        let report = service.migrate(true).unwrap();
+        assert_eq!(report.changes.len(), 1);
+        assert_eq!(report.changes[0].action, MigrationAction::Upgraded);
+        assert!(load_sync_state(&storage, "name").is_err());
+
+        service.migrate(false).unwrap();
+        let state = load_sync_state(&storage, "name").unwrap().unwrap();
+        assert_eq!(state.last_block_height, 5);
+        assert_eq!(state.last_app_hash, "AB");
+        assert_eq!(state.last_block_hash, "CD");
+        assert_eq!(state.staking_root, [1u8; 32]);
+        assert!(state.trusted);
+        assert_eq!(state.last_filter_header, None);
+        assert!(service.migrate(false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn check_sync_state_with_trailing_bytes_is_rejected() {
+        let mut value = hex::decode(SYNC_STATE_V0).unwrap();
+        value.push(0);
+        assert!(add_sync_state_filter_header(&value).is_err());
+    }
+
+    #[test]
+    fn check_sync_state_filter_header_is_known_at_genesis() {
+        // `SYNC_STATE_V0` at genesis
+        let mut value = hex::decode(SYNC_STATE_V0).unwrap();
+        value[0] = 0;
+        let state =
+            SyncState::decode(&mut add_sync_state_filter_header(&value).unwrap().as_slice())
+                .unwrap();
+        assert_eq!(state.last_block_height, 0);
+        assert_eq!(state.last_filter_header, Some([0u8; 32]));
+    }
+
+    #[test]
+    fn check_hd_key_fixture_migration() {
+        let storage = MemoryStorage::default();
+        let enckey = derive_enckey(&SecUtf8::from("passphrase"), "name").unwrap();
+        let seed = HDSeed {
+            bytes: vec![3u8; 64],
+        };
+        // HD key of version 0: staking, transfer and viewkey indexes followed by the seed
+        let legacy = (2u32, 3u32, 1u32, seed.clone()).encode();
+        storage
+            .set_secure(HD_KEY_KEYSPACE, "name", legacy.clone(), &enckey)
+            .unwrap();
+
+        let service = MigrationService::new(storage.clone());
+        let report = service.migrate(false).unwrap();
+        assert_eq!(report.changes[0].action, MigrationAction::Deferred);
+        assert_eq!(
+            service
+                .migrate_wallet("name", &enckey, true)
+                .unwrap()
+                .changes
+                .len(),
+            1
+        );
+        assert_eq!(
+            storage
+                .get_secure(HD_KEY_KEYSPACE, "name", &enckey)
+                .unwrap()
+                .unwrap(),
+            legacy
+        );
+
+        service.migrate_wallet("name", &enckey, false).unwrap();
+        let upgraded = storage
+            .get_secure(HD_KEY_KEYSPACE, "name", &enckey)
+            .unwrap()
+            .unwrap();
+        assert_eq!(upgraded.len(), legacy.len() + 4);
+        let hd_key = HdKeyService::new(storage)
+            .get_hdkey("name", &enckey)
+            .unwrap()
+            .unwrap();
+        assert_eq!(hd_key.staking_index, 2);
+        assert_eq!(hd_key.transfer_index, 3);
+        assert_eq!(hd_key.seed, seed);
+        assert_eq!(hd_key.account, 0);
+        assert!(service
+            .migrate_wallet("name", &enckey, false)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn check_wallet_state_migration() {
+        let storage = MemoryStorage::default();
+        let enckey = derive_enckey(&SecUtf8::from("passphrase"), "name").unwrap();
+        let pointer = TxoPointer::new([0; 32], 0);
+        let mut unspent_transactions = BTreeMap::new();
+        unspent_transactions.insert(
+            pointer.clone(),
+            TxOut::new(ExtendedAddr::OrTree([0; 32]), Coin::unit()),
+        );
+        // wallet state of version 0: no frozen outputs
+        let legacy = (
+            unspent_transactions,
+            BTreeMap::<TxId, TransactionPending>::new(),
+            BTreeMap::<TxId, TransactionChange>::new(),
+            Vec::<TxId>::new(),
+        )
+            .encode();
+        assert!(WalletState::decode(&mut legacy.as_slice()).is_err());
+        storage
+            .set_secure(WALLET_STATE_KEYSPACE, "name", legacy.clone(), &enckey)
+            .unwrap();
+
+        let service = MigrationService::new(storage.clone());
+        service.migrate(false).unwrap();
+        service.migrate_wallet("name", &enckey, false).unwrap();
+        let wallet_state_service = WalletStateService::new(storage);
+        assert!(wallet_state_service
+            .get_frozen_outputs("name", &enckey)
+            .unwrap()
+            .is_empty());
+        assert!(wallet_state_service
+            .get_unspent_transactions("name", &enckey, false)
+            .unwrap()
+            .contains_key(&pointer));
+
+        let mut with_trailing_bytes = legacy;
+        with_trailing_bytes.push(0);
+        assert!(add_wallet_state_frozen_outputs(&with_trailing_bytes).is_err());
+    }
+}
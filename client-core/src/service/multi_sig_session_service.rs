@@ -1,18 +1,31 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 use secp256k1::schnorrsig::SchnorrSignature;
 
 use chain_core::common::H256;
 use client_common::{
-    ErrorKind, PrivateKey, PublicKey, Result, ResultExt, SecKey, SecureStorage, Storage,
+    Error, ErrorKind, PrivateKey, PublicKey, Result, ResultExt, SecKey, SecureStorage, Storage,
 };
 
-use crate::multi_sig::MultiSigBuilder;
+use crate::multi_sig::{MultiSigBuilder, SecretNonce};
+
+pub(crate) const KEYSPACE: &str = "core_multi_sig_address";
 
-const KEYSPACE: &str = "core_multi_sig_address";
+/// Default time to live of a multi-sig session
+pub const DEFAULT_SESSION_TTL: Duration = Duration::from_secs(60 * 60);
 
 /// Maintains mapping `multi-sig session-id -> multi-sig session`
-#[derive(Debug, Default, Clone)]
+///
+/// Sessions are persisted without any secret. The secret nonce seeds of current signer are only kept in memory (by
+/// this service and its clones), so a session which lost its secret nonce (e.g. after a restart) can only be
+/// restarted from scratch and never reuses a nonce.
+#[derive(Debug, Clone)]
 pub struct MultiSigSessionService<T: Storage> {
     storage: T,
+    secret_nonces: Arc<Mutex<HashMap<H256, SecretNonce>>>,
+    session_ttl: Duration,
 }
 
 impl<T> MultiSigSessionService<T>
@@ -21,47 +34,70 @@ where
 {
     /// Creates a new instance of multi-sig session service
     pub fn new(storage: T) -> Self {
-        Self { storage }
+        Self {
+            storage,
+            secret_nonces: Default::default(),
+            session_ttl: DEFAULT_SESSION_TTL,
+        }
+    }
+
+    /// Sets the time to live of new sessions
+    pub fn with_session_ttl(mut self, session_ttl: Duration) -> Self {
+        self.session_ttl = session_ttl;
+        self
     }
 
     /// Creates a new session and returns session-id
     ///
     /// # Arguments
     ///
-    /// - `message`: Message to be signed,
+    /// - `message`: Message to be signed (transaction id),
     /// - `signer_public_keys`: Public keys of all the signers (including current signer)
     /// - `self_public_key`: Public key of current signer
-    /// - `self_private_key`: Private key of current signer
+    /// - `wallet_name`: Name of the wallet holding the private key of current signer
     /// - `enckey`: Passphrase for encryption
     pub fn new_session(
         &self,
         message: H256,
         signer_public_keys: Vec<PublicKey>,
         self_public_key: PublicKey,
-        self_private_key: PrivateKey,
+        wallet_name: &str,
         enckey: &SecKey,
     ) -> Result<H256> {
-        let session = MultiSigBuilder::new(
+        let expires_at = current_timestamp()?.saturating_add(self.session_ttl.as_secs());
+        let mut session = MultiSigBuilder::new(
             message,
             signer_public_keys,
             self_public_key,
-            self_private_key,
+            wallet_name.to_owned(),
+            expires_at,
         )?;
 
         let session_id = session.id();
+        let secret_nonce = session.take_secret_nonce().chain(|| {
+            (
+                ErrorKind::InternalError,
+                "Missing secret nonce of new session",
+            )
+        })?;
+
+        let mut secret_nonces = self.lock_secret_nonces()?;
         self.set_session(&session_id, session, enckey)?;
+        secret_nonces.insert(session_id, secret_nonce);
 
         Ok(session_id)
     }
 
     /// Returns nonce commitment of self
-    pub fn nonce_commitment(&self, session_id: &H256, enckey: &SecKey) -> Result<H256> {
-        let mut session = self.get_session(session_id, enckey)?;
-        let nonce_commitment = session.nonce_commitment()?;
-
-        self.set_session(session_id, session, enckey)?;
-
-        Ok(nonce_commitment)
+    pub fn nonce_commitment(
+        &self,
+        session_id: &H256,
+        private_key: &PrivateKey,
+        enckey: &SecKey,
+    ) -> Result<H256> {
+        self.update_session(session_id, enckey, |session| {
+            session.nonce_commitment(private_key)
+        })
     }
 
     /// Adds a nonce commitment from a public key to session with given id
@@ -72,30 +108,19 @@ where
         public_key: &PublicKey,
         enckey: &SecKey,
     ) -> Result<()> {
-        self.storage
-            .fetch_and_update_secure(KEYSPACE, session_id, enckey, |value| {
-                let session_bytes = value.chain(|| {
-                    (
-                        ErrorKind::InvalidInput,
-                        format!("Session with ID ({}) not found", hex::encode(session_id)),
-                    )
-                })?;
-                let mut session =
-                    MultiSigBuilder::from_incomplete_insecure(session_bytes.to_vec())?;
-                session.add_nonce_commitment(public_key, nonce_commitment)?;
-
-                Ok(Some(session.to_incomplete()))
-            })
-            .map(|_| ())
+        self.update_session(session_id, enckey, |session| {
+            session.add_nonce_commitment(public_key, nonce_commitment)
+        })
     }
 
     /// Returns nonce of self. This function will fail if nonce commitments from all co-signers are not received.
-    pub fn nonce(&self, session_id: &H256, enckey: &SecKey) -> Result<H256> {
-        let mut session = self.get_session(session_id, enckey)?;
-        let nonce = session.nonce()?;
-
-        self.set_session(session_id, session, enckey)?;
-        Ok(nonce)
+    pub fn nonce(
+        &self,
+        session_id: &H256,
+        private_key: &PrivateKey,
+        enckey: &SecKey,
+    ) -> Result<H256> {
+        self.update_session(session_id, enckey, |session| session.nonce(private_key))
     }
 
     /// Adds a nonce from a public key to session with given id
@@ -106,33 +131,25 @@ where
         public_key: &PublicKey,
         enckey: &SecKey,
     ) -> Result<()> {
-        self.storage
-            .fetch_and_update_secure(KEYSPACE, session_id, enckey, |value| {
-                let session_bytes = value.chain(|| {
-                    (
-                        ErrorKind::InvalidInput,
-                        format!("Session with ID ({}) not found", hex::encode(session_id)),
-                    )
-                })?;
-                let mut session =
-                    MultiSigBuilder::from_incomplete_insecure(session_bytes.to_vec())?;
-                session.add_nonce(public_key, nonce)?;
-
-                Ok(Some(session.to_incomplete()))
-            })
-            .map(|_| ())
+        self.update_session(session_id, enckey, |session| {
+            session.add_nonce(public_key, nonce)
+        })
     }
 
     /// Returns partial signature of self. This function will fail if nonces from all co-signers are not received.
-    pub fn partial_signature(&self, session_id: &H256, enckey: &SecKey) -> Result<H256> {
-        let mut session = self.get_session(session_id, enckey)?;
-        let partial_signature = session.partial_signature()?;
-
-        self.set_session(session_id, session, enckey)?;
-        Ok(partial_signature)
+    pub fn partial_signature(
+        &self,
+        session_id: &H256,
+        private_key: &PrivateKey,
+        enckey: &SecKey,
+    ) -> Result<H256> {
+        self.update_session(session_id, enckey, |session| {
+            session.partial_signature(private_key)
+        })
     }
 
-    /// Adds a partial signature from a public key to session with given id
+    /// Adds a partial signature from a public key to session with given id. The partial signature is verified on
+    /// arrival.
     pub fn add_partial_signature(
         &self,
         session_id: &H256,
@@ -140,21 +157,9 @@ where
         public_key: &PublicKey,
         enckey: &SecKey,
     ) -> Result<()> {
-        self.storage
-            .fetch_and_update_secure(KEYSPACE, session_id, enckey, |value| {
-                let session_bytes = value.chain(|| {
-                    (
-                        ErrorKind::InvalidInput,
-                        format!("Session with ID ({}) not found", hex::encode(session_id)),
-                    )
-                })?;
-                let mut session =
-                    MultiSigBuilder::from_incomplete_insecure(session_bytes.to_vec())?;
-                session.add_partial_signature(public_key, partial_signature)?;
-
-                Ok(Some(session.to_incomplete()))
-            })
-            .map(|_| ())
+        self.update_session(session_id, enckey, |session| {
+            session.add_partial_signature(public_key, partial_signature)
+        })
     }
 
     /// Returns final signature. This function will fail if partial signatures from all co-signers are not received.
@@ -169,8 +174,66 @@ where
         Ok(session.public_keys())
     }
 
-    /// Retrieves a session from storage
+    /// Returns the message (transaction id) the session with given id is bound to
+    pub fn message(&self, session_id: &H256, enckey: &SecKey) -> Result<H256> {
+        let session = self.get_session(session_id, enckey)?;
+        Ok(session.message())
+    }
+
+    /// Returns wallet name and public key of current signer in this session
+    pub fn signer(&self, session_id: &H256, enckey: &SecKey) -> Result<(String, PublicKey)> {
+        let session = self.get_session(session_id, enckey)?;
+        Ok((
+            session.wallet_name().to_owned(),
+            session.public_key().clone(),
+        ))
+    }
+
+    /// Removes the session with given id (along with its secret nonce)
+    pub fn remove_session(&self, session_id: &H256) -> Result<()> {
+        let mut secret_nonces = self.lock_secret_nonces()?;
+        secret_nonces.remove(session_id);
+        self.storage.delete(KEYSPACE, session_id).map(|_| ())
+    }
+
+    /// Updates a session in storage with given function. The secret nonce (if still available) is attached to the
+    /// session for the duration of the update and is dropped once consumed.
+    fn update_session<F, R>(&self, session_id: &H256, enckey: &SecKey, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut MultiSigBuilder) -> Result<R>,
+    {
+        let mut secret_nonces = self.lock_secret_nonces()?;
+        let mut session = self.load_session(session_id, enckey)?;
+        if let Err(err) = self.check_expiry(session_id, &session) {
+            secret_nonces.remove(session_id);
+            return Err(err);
+        }
+        if let Some(secret_nonce) = secret_nonces.remove(session_id) {
+            session = session.with_secret_nonce(secret_nonce);
+        }
+
+        let result = f(&mut session);
+        if let Some(secret_nonce) = session.take_secret_nonce() {
+            secret_nonces.insert(*session_id, secret_nonce);
+        }
+
+        let value = result?;
+        self.set_session(session_id, session, enckey)?;
+        Ok(value)
+    }
+
+    /// Retrieves a session from storage. Expired sessions are removed.
     fn get_session(&self, session_id: &H256, enckey: &SecKey) -> Result<MultiSigBuilder> {
+        let session = self.load_session(session_id, enckey)?;
+        if let Err(err) = self.check_expiry(session_id, &session) {
+            self.lock_secret_nonces()?.remove(session_id);
+            return Err(err);
+        }
+        Ok(session)
+    }
+
+    /// Retrieves a session from storage without checking its expiry
+    fn load_session(&self, session_id: &H256, enckey: &SecKey) -> Result<MultiSigBuilder> {
         let session_bytes = self
             .storage
             .get_secure(KEYSPACE, session_id, enckey)?
@@ -180,7 +243,16 @@ where
                     format!("Session with ID ({}) not found", hex::encode(session_id)),
                 )
             })?;
-        MultiSigBuilder::from_incomplete_insecure(session_bytes)
+        MultiSigBuilder::from_incomplete(session_bytes)
+    }
+
+    /// Removes given session from storage if it has expired
+    fn check_expiry(&self, session_id: &H256, session: &MultiSigBuilder) -> Result<()> {
+        let result = session.check_expiry(current_timestamp()?);
+        if result.is_err() {
+            self.storage.delete(KEYSPACE, session_id)?;
+        }
+        result
     }
 
     /// Persists a session in storage
@@ -194,6 +266,23 @@ where
             .set_secure(KEYSPACE, session_id, session.to_incomplete(), enckey)
             .map(|_| ())
     }
+
+    fn lock_secret_nonces(&self) -> Result<MutexGuard<'_, HashMap<H256, SecretNonce>>> {
+        self.secret_nonces.lock().map_err(|_| {
+            Error::new(
+                ErrorKind::InternalError,
+                "Unable to acquire lock on multi-sig secret nonces",
+            )
+        })
+    }
+}
+
+/// Returns current unix timestamp in seconds
+fn current_timestamp() -> Result<u64> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .chain(|| (ErrorKind::InternalError, "System time is before unix epoch"))
 }
 
 #[cfg(test)]
@@ -204,7 +293,7 @@ mod multi_sig_session_service_tests {
     use secp256k1::Message;
     use secstr::SecUtf8;
 
-    use client_common::{seckey::derive_enckey, storage::MemoryStorage, SECP};
+    use client_common::{seckey::derive_enckey, storage::MemoryStorage};
 
     #[test]
     fn check_multi_sig_transaction_flow() {
@@ -224,7 +313,7 @@ mod multi_sig_session_service_tests {
                 message,
                 vec![public_key_1.clone(), public_key_2.clone()],
                 public_key_1.clone(),
-                private_key_1.clone(),
+                "name_1",
                 &enckey,
             )
             .unwrap();
@@ -233,23 +322,32 @@ mod multi_sig_session_service_tests {
                 message,
                 vec![public_key_1.clone(), public_key_2.clone()],
                 public_key_2.clone(),
-                private_key_2.clone(),
+                "name_2",
                 &enckey,
             )
             .unwrap();
 
+        assert_eq!(
+            ("name_1".to_owned(), public_key_1.clone()),
+            multi_sig_service.signer(&session_id_1, &enckey).unwrap()
+        );
+        assert_eq!(
+            message,
+            multi_sig_service.message(&session_id_1, &enckey).unwrap()
+        );
+
         let nonce_commitment_1 = multi_sig_service
-            .nonce_commitment(&session_id_1, &enckey)
+            .nonce_commitment(&session_id_1, &private_key_1, &enckey)
             .unwrap();
         assert!(
             multi_sig_service
-                .nonce_commitment(&session_id_1, &enckey)
+                .nonce_commitment(&session_id_1, &private_key_1, &enckey)
                 .is_ok(),
             "Not able to retrieve nonce commitment multiple times"
         );
 
         let nonce_commitment_2 = multi_sig_service
-            .nonce_commitment(&session_id_2, &enckey)
+            .nonce_commitment(&session_id_2, &private_key_2, &enckey)
             .unwrap();
 
         multi_sig_service
@@ -259,13 +357,19 @@ mod multi_sig_session_service_tests {
             .add_nonce_commitment(&session_id_2, nonce_commitment_1, &public_key_1, &enckey)
             .expect("Unable to add nonce commitment to session 2");
 
-        let nonce_1 = multi_sig_service.nonce(&session_id_1, &enckey).unwrap();
+        let nonce_1 = multi_sig_service
+            .nonce(&session_id_1, &private_key_1, &enckey)
+            .unwrap();
         assert!(
-            multi_sig_service.nonce(&session_id_1, &enckey).is_ok(),
+            multi_sig_service
+                .nonce(&session_id_1, &private_key_1, &enckey)
+                .is_ok(),
             "Not able to retrieve nonce multiple times"
         );
 
-        let nonce_2 = multi_sig_service.nonce(&session_id_2, &enckey).unwrap();
+        let nonce_2 = multi_sig_service
+            .nonce(&session_id_2, &private_key_2, &enckey)
+            .unwrap();
 
         multi_sig_service
             .add_nonce(&session_id_1, &nonce_2, &public_key_2, &enckey)
@@ -278,19 +382,25 @@ mod multi_sig_session_service_tests {
             .expect("Unable to add nonce to session 2");
 
         let partial_signature_1 = multi_sig_service
-            .partial_signature(&session_id_1, &enckey)
+            .partial_signature(&session_id_1, &private_key_1, &enckey)
             .expect("Unable to generate partial signature for session 1");
-        assert!(
+        assert_eq!(
+            partial_signature_1,
             multi_sig_service
-                .partial_signature(&session_id_1, &enckey)
-                .is_ok(),
+                .partial_signature(&session_id_1, &private_key_1, &enckey)
+                .unwrap(),
             "Not able to retrieve partial signatures multiple times"
         );
 
         let partial_signature_2 = multi_sig_service
-            .partial_signature(&session_id_2, &enckey)
+            .partial_signature(&session_id_2, &private_key_2, &enckey)
             .expect("Unable to generate partial signature for session 2");
 
+        let error = multi_sig_service
+            .add_partial_signature(&session_id_1, partial_signature_1, &public_key_2, &enckey)
+            .expect_err("Can add an invalid partial signature");
+        assert!(error.to_string().contains(&public_key_2.to_string()));
+
         multi_sig_service
             .add_partial_signature(&session_id_1, partial_signature_2, &public_key_2, &enckey)
             .expect("Unable to add partial signature to session 1");
@@ -317,4 +427,67 @@ mod multi_sig_session_service_tests {
         )
         .expect("Invalid signature");
     }
+
+    #[test]
+    fn check_restarted_service_does_not_reuse_nonce() {
+        let storage = MemoryStorage::default();
+        let multi_sig_service = MultiSigSessionService::new(storage.clone());
+        let enckey = derive_enckey(&SecUtf8::from("passphrase"), "").unwrap();
+
+        let private_key_1 = PrivateKey::new().unwrap();
+        let private_key_2 = PrivateKey::new().unwrap();
+
+        let public_key_1 = PublicKey::from(&private_key_1);
+        let public_key_2 = PublicKey::from(&private_key_2);
+
+        let session_id = multi_sig_service
+            .new_session(
+                [1u8; 32],
+                vec![public_key_1.clone(), public_key_2],
+                public_key_1,
+                "name",
+                &enckey,
+            )
+            .unwrap();
+        multi_sig_service
+            .nonce_commitment(&session_id, &private_key_1, &enckey)
+            .unwrap();
+
+        let restarted_service = MultiSigSessionService::new(storage);
+        let error = restarted_service
+            .nonce(&session_id, &private_key_1, &enckey)
+            .expect_err("Restarted service should not have the secret nonce");
+        assert_eq!(ErrorKind::MultiSigError, error.kind());
+    }
+
+    #[test]
+    fn check_expired_session() {
+        let multi_sig_service = MultiSigSessionService::new(MemoryStorage::default())
+            .with_session_ttl(Duration::from_secs(0));
+        let enckey = derive_enckey(&SecUtf8::from("passphrase"), "").unwrap();
+
+        let private_key_1 = PrivateKey::new().unwrap();
+        let public_key_1 = PublicKey::from(&private_key_1);
+        let public_key_2 = PublicKey::from(&PrivateKey::new().unwrap());
+
+        let session_id = multi_sig_service
+            .new_session(
+                [1u8; 32],
+                vec![public_key_1.clone(), public_key_2],
+                public_key_1,
+                "name",
+                &enckey,
+            )
+            .unwrap();
+
+        let error = multi_sig_service
+            .nonce_commitment(&session_id, &private_key_1, &enckey)
+            .expect_err("Should not be able to use an expired session");
+        assert_eq!(ErrorKind::MultiSigError, error.kind());
+
+        let error = multi_sig_service
+            .public_keys(&session_id, &enckey)
+            .expect_err("Expired session should be removed");
+        assert_eq!(ErrorKind::InvalidInput, error.kind());
+    }
 }
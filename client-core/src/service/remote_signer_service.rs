@@ -0,0 +1,1039 @@
+//! Authenticated remote-signer protocol
+//!
+//! Keeps the keys of a wallet in a separate signer (e.g. an HSM or a signing service) which
+//! checks every request against its own policy before signing.
+//!
+//! # Transport
+//!
+//! The signer listens on a TCP socket (`tcp://127.0.0.1:9000`) or a Unix socket
+//! (`unix:///run/signer.sock`). Every connection carries one request. Messages are JSON objects,
+//! each one prefixed with its length (`u32`, big endian, at most 1 MiB).
+//!
+//! # Authentication
+//!
+//! Wallet and signer share a 32 bytes authentication key. On a new connection:
+//!
+//! 1. the signer sends `{"nonce":"<hex of 32 random bytes>"}`
+//! 2. the wallet sends `{"payload":"<request>","mac":"<hex>"}` where `payload` is the JSON
+//!    request as a string and `mac = HMAC-SHA256(key, "request" || nonce || payload)`
+//! 3. the signer checks the MAC and answers `{"payload":"<response>","mac":"<hex>"}` where
+//!    `mac = HMAC-SHA256(key, "response" || nonce || request mac || payload)`
+//!
+//! A request with an invalid MAC is answered with an `error` response without MAC.
+//!
+//! # Requests
+//!
+//! - `{"method":"get_public_key","params":{"path":"m/44'/394'/0'/0/0"}}`
+//! - `{"method":"sign","params":{"path":"...","transaction":<Transaction>,"algorithm":"ecdsa"}}`
+//!   (`algorithm` is `ecdsa` for recoverable ECDSA signatures, or `schnorr`)
+//!
+//! The signed message is the transaction id, computed by the signer from the transaction. The
+//! wallet checks every returned signature against the public key of the path.
+//!
+//! # Responses
+//!
+//! - `{"result":"public_key","value":"<hex of compressed public key>"}`
+//! - `{"result":"signature","value":"<hex>"}`: recovery id followed by the 64 bytes compact
+//!   signature for ECDSA, 64 bytes signature for Schnorr
+//! - `{"result":"rejected","value":"<reason>"}`: the request violates the policy of the signer
+//! - `{"result":"error","value":"<message>"}`
+use std::collections::BTreeSet;
+use std::env;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::thread;
+
+use rand::RngCore;
+use ring::hmac;
+use secp256k1::recovery::{RecoverableSignature, RecoveryId};
+use secp256k1::schnorrsig::{schnorr_verify, SchnorrSignature};
+use secp256k1::Message;
+use serde::{Deserialize, Serialize};
+
+use crate::hd_wallet::{ChainPath, DefaultKeyChain, ExtendedPrivKey, KeyChain};
+use crate::service::hw_key_service::HardwareWalletAction;
+use crate::service::{HDAccountType, RootHashService};
+use crate::HDSeed;
+use chain_core::init::address::RedeemAddress;
+use chain_core::init::coin::{sum_coins, Coin};
+use chain_core::init::network::Network;
+use chain_core::state::account::StakedStateAddress;
+use chain_core::tx::data::address::ExtendedAddr;
+use chain_core::tx::data::output::TxOut;
+use client_common::seckey::parse_hex_enckey;
+use client_common::storage::MemoryStorage;
+use client_common::{
+    Error, ErrorKind, PrivateKey, PrivateKeyAction, PublicKey, Result, ResultExt, SecKey,
+    Transaction,
+};
+
+/// maximum length of a message
+const MAX_MESSAGE_LENGTH: usize = 1024 * 1024;
+/// length of the nonce sent by the signer
+const NONCE_LENGTH: usize = 32;
+const TCP_SCHEME: &str = "tcp://";
+const UNIX_SCHEME: &str = "unix://";
+
+/// Endpoint of a remote signer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteSignerEndpoint {
+    /// TCP socket (`tcp://<address>`)
+    Tcp(SocketAddr),
+    /// Unix socket (`unix://<path>`)
+    Unix(PathBuf),
+}
+
+impl FromStr for RemoteSignerEndpoint {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if s.starts_with(TCP_SCHEME) {
+            let address = &s[TCP_SCHEME.len()..];
+            let address = address.parse().chain(|| {
+                (
+                    ErrorKind::InvalidInput,
+                    format!("Invalid remote signer address: {}", address),
+                )
+            })?;
+            Ok(RemoteSignerEndpoint::Tcp(address))
+        } else if s.starts_with(UNIX_SCHEME) {
+            Ok(RemoteSignerEndpoint::Unix(PathBuf::from(
+                &s[UNIX_SCHEME.len()..],
+            )))
+        } else {
+            Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Invalid remote signer endpoint: {} (tcp://<address> or unix://<path>)",
+                    s
+                ),
+            ))
+        }
+    }
+}
+
+/// Connection to a remote signer
+enum SignerStream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl SignerStream {
+    fn connect(endpoint: &RemoteSignerEndpoint) -> Result<Self> {
+        match endpoint {
+            RemoteSignerEndpoint::Tcp(address) => TcpStream::connect(address)
+                .map(SignerStream::Tcp)
+                .chain(|| {
+                    (
+                        ErrorKind::ConnectionError,
+                        format!("Unable to connect to remote signer at {}", address),
+                    )
+                }),
+            #[cfg(unix)]
+            RemoteSignerEndpoint::Unix(path) => {
+                UnixStream::connect(path).map(SignerStream::Unix).chain(|| {
+                    (
+                        ErrorKind::ConnectionError,
+                        format!("Unable to connect to remote signer at {}", path.display()),
+                    )
+                })
+            }
+            #[cfg(not(unix))]
+            RemoteSignerEndpoint::Unix(_) => Err(Error::new(
+                ErrorKind::ConnectionError,
+                "Unix sockets are only supported on Unix platforms",
+            )),
+        }
+    }
+}
+
+impl Read for SignerStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            SignerStream::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            SignerStream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for SignerStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            SignerStream::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            SignerStream::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            SignerStream::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            SignerStream::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+/// Signature algorithm of a sign request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureAlgorithm {
+    /// recoverable ECDSA signature (staking operations)
+    Ecdsa,
+    /// Schnorr signature (transfers)
+    Schnorr,
+}
+
+/// Request to a remote signer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum SignerRequest {
+    /// returns the public key of a path
+    GetPublicKey {
+        /// BIP-32 path
+        path: String,
+    },
+    /// signs a transaction with the key of a path
+    Sign {
+        /// BIP-32 path
+        path: String,
+        /// transaction to sign
+        transaction: Transaction,
+        /// signature algorithm
+        algorithm: SignatureAlgorithm,
+    },
+}
+
+/// Response of a remote signer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "result", content = "value", rename_all = "snake_case")]
+pub enum SignerResponse {
+    /// hex encoded compressed public key
+    PublicKey(String),
+    /// hex encoded signature
+    Signature(String),
+    /// request rejected by the policy of the signer
+    Rejected(String),
+    /// request failed
+    Error(String),
+}
+
+/// First message of the signer on a connection
+#[derive(Debug, Serialize, Deserialize)]
+struct SignerHello {
+    nonce: String,
+}
+
+/// Authenticated message
+#[derive(Debug, Serialize, Deserialize)]
+struct SignerEnvelope {
+    payload: String,
+    #[serde(default)]
+    mac: String,
+}
+
+fn request_mac_data(nonce: &[u8], payload: &str) -> Vec<u8> {
+    [b"request".as_ref(), nonce, payload.as_bytes()].concat()
+}
+
+fn response_mac_data(nonce: &[u8], request_mac: &[u8], payload: &str) -> Vec<u8> {
+    [b"response".as_ref(), nonce, request_mac, payload.as_bytes()].concat()
+}
+
+fn auth_key(key: &SecKey) -> hmac::Key {
+    hmac::Key::new(hmac::HMAC_SHA256, key.unsecure())
+}
+
+fn decode_hex(value: &str, what: &str) -> Result<Vec<u8>> {
+    hex::decode(value).chain(|| {
+        (
+            ErrorKind::DeserializationError,
+            format!("Invalid hex encoded {}", what),
+        )
+    })
+}
+
+fn write_message<W: Write, T: Serialize>(stream: &mut W, message: &T) -> Result<()> {
+    let data = serde_json::to_vec(message).chain(|| {
+        (
+            ErrorKind::SerializationError,
+            "Unable to serialize remote signer message",
+        )
+    })?;
+    stream
+        .write_all(&(data.len() as u32).to_be_bytes())
+        .and_then(|_| stream.write_all(&data))
+        .and_then(|_| stream.flush())
+        .chain(|| (ErrorKind::IoError, "Unable to send remote signer message"))
+}
+
+fn read_message<R: Read, T: for<'de> Deserialize<'de>>(stream: &mut R) -> Result<T> {
+    let mut length = [0u8; 4];
+    stream
+        .read_exact(&mut length)
+        .chain(|| (ErrorKind::IoError, "Unable to read remote signer message"))?;
+    let length = u32::from_be_bytes(length) as usize;
+    if length > MAX_MESSAGE_LENGTH {
+        return Err(Error::new(
+            ErrorKind::IoError,
+            "Remote signer message too long",
+        ));
+    }
+    let mut data = vec![0u8; length];
+    stream
+        .read_exact(&mut data)
+        .chain(|| (ErrorKind::IoError, "Unable to read remote signer message"))?;
+    serde_json::from_slice(&data).chain(|| {
+        (
+            ErrorKind::DeserializationError,
+            "Unable to deserialize remote signer message",
+        )
+    })
+}
+
+/// Remote signer service
+#[derive(Clone)]
+pub struct RemoteSignerService {
+    endpoint: RemoteSignerEndpoint,
+    auth_key: SecKey,
+}
+
+impl std::fmt::Debug for RemoteSignerService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteSignerService")
+            .field("endpoint", &self.endpoint)
+            .field("auth_key", &"<hidden>")
+            .finish()
+    }
+}
+
+impl RemoteSignerService {
+    /// create a new RemoteSignerService connecting to the signer at `CRYPTO_REMOTE_SIGNER` with
+    /// the hex encoded authentication key in `CRYPTO_REMOTE_SIGNER_KEY`
+    pub fn new() -> Result<Self> {
+        let endpoint = env::var("CRYPTO_REMOTE_SIGNER").chain(|| {
+            (
+                ErrorKind::InitializationError,
+                "CRYPTO_REMOTE_SIGNER is not set",
+            )
+        })?;
+        let auth_key = env::var("CRYPTO_REMOTE_SIGNER_KEY").chain(|| {
+            (
+                ErrorKind::InitializationError,
+                "CRYPTO_REMOTE_SIGNER_KEY is not set",
+            )
+        })?;
+        Ok(Self::with_endpoint(
+            endpoint.parse()?,
+            parse_hex_enckey(&auth_key)?,
+        ))
+    }
+
+    /// create a new RemoteSignerService connecting to given endpoint
+    pub fn with_endpoint(endpoint: RemoteSignerEndpoint, auth_key: SecKey) -> Self {
+        Self { endpoint, auth_key }
+    }
+
+    /// sends an authenticated request to the signer
+    pub fn request(&self, request: &SignerRequest) -> Result<SignerResponse> {
+        let mut stream = SignerStream::connect(&self.endpoint)?;
+        let hello: SignerHello = read_message(&mut stream)?;
+        let nonce = decode_hex(&hello.nonce, "nonce")?;
+        if nonce.len() != NONCE_LENGTH {
+            return Err(Error::new(
+                ErrorKind::VerifyError,
+                "Invalid nonce from remote signer",
+            ));
+        }
+
+        let key = auth_key(&self.auth_key);
+        let payload = serde_json::to_string(request).chain(|| {
+            (
+                ErrorKind::SerializationError,
+                "Unable to serialize remote signer request",
+            )
+        })?;
+        let request_mac = hmac::sign(&key, &request_mac_data(&nonce, &payload));
+        write_message(
+            &mut stream,
+            &SignerEnvelope {
+                payload,
+                mac: hex::encode(request_mac.as_ref()),
+            },
+        )?;
+
+        let envelope: SignerEnvelope = read_message(&mut stream)?;
+        let response_mac = decode_hex(&envelope.mac, "response mac")?;
+        if hmac::verify(
+            &key,
+            &response_mac_data(&nonce, request_mac.as_ref(), &envelope.payload),
+            &response_mac,
+        )
+        .is_err()
+        {
+            // unauthenticated errors are only reported
+            if let Ok(SignerResponse::Error(message)) = serde_json::from_str(&envelope.payload) {
+                return Err(Error::new(
+                    ErrorKind::PermissionDenied,
+                    format!("Remote signer error: {}", message),
+                ));
+            }
+            return Err(Error::new(
+                ErrorKind::VerifyError,
+                "Invalid authentication of remote signer response",
+            ));
+        }
+        serde_json::from_str(&envelope.payload).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Unable to deserialize remote signer response",
+            )
+        })
+    }
+
+    fn sign(
+        &self,
+        path: &ChainPath,
+        tx: &Transaction,
+        algorithm: SignatureAlgorithm,
+    ) -> Result<Vec<u8>> {
+        let request = SignerRequest::Sign {
+            path: path.to_string().to_owned(),
+            transaction: tx.clone(),
+            algorithm,
+        };
+        match self.request(&request)? {
+            SignerResponse::Signature(signature) => decode_hex(&signature, "signature"),
+            response => Err(unexpected_response(response)),
+        }
+    }
+}
+
+fn unexpected_response(response: SignerResponse) -> Error {
+    match response {
+        SignerResponse::Rejected(reason) => Error::new(
+            ErrorKind::PermissionDenied,
+            format!("Remote signer rejected the request: {}", reason),
+        ),
+        SignerResponse::Error(message) => Error::new(
+            ErrorKind::InternalError,
+            format!("Remote signer error: {}", message),
+        ),
+        _ => Error::new(
+            ErrorKind::InternalError,
+            "Unexpected response from remote signer",
+        ),
+    }
+}
+
+impl HardwareWalletAction for RemoteSignerService {
+    fn get_public_key(&self, chain_path: ChainPath) -> Result<PublicKey> {
+        let request = SignerRequest::GetPublicKey {
+            path: chain_path.into_string(),
+        };
+        match self.request(&request)? {
+            SignerResponse::PublicKey(public_key) => {
+                PublicKey::deserialize_from(&decode_hex(&public_key, "public key")?)
+            }
+            response => Err(unexpected_response(response)),
+        }
+    }
+
+    fn get_sign_key(&self, hd_path: &ChainPath) -> Result<Box<dyn PrivateKeyAction>> {
+        let key = RemoteSignKey {
+            path: hd_path.clone(),
+            service: self.clone(),
+        };
+        Ok(Box::new(key))
+    }
+}
+
+/// represent a private key, can sign msg in the remote signer with `path` using `service`
+pub struct RemoteSignKey {
+    path: ChainPath,
+    service: RemoteSignerService,
+}
+
+impl RemoteSignKey {
+    /// message signed for a transaction
+    fn message(tx: &Transaction) -> Result<Message> {
+        Message::from_slice(&tx.id()).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Unable to deserialize message to sign",
+            )
+        })
+    }
+
+    fn invalid_signature() -> Error {
+        Error::new(
+            ErrorKind::VerifyError,
+            "Remote signer returned a signature which doesn't match its public key",
+        )
+    }
+}
+
+impl PrivateKeyAction for RemoteSignKey {
+    fn sign(&self, tx: &Transaction) -> Result<RecoverableSignature> {
+        let signature = self
+            .service
+            .sign(&self.path, tx, SignatureAlgorithm::Ecdsa)?;
+        if signature.len() != 65 {
+            return Err(Error::new(
+                ErrorKind::DeserializationError,
+                "Invalid signature",
+            ));
+        }
+        let recover_id = RecoveryId::from_i32(i32::from(signature[0])).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                "Invalid signature, get recover id failed",
+            )
+        })?;
+        let signature = RecoverableSignature::from_compact(&signature[1..], recover_id)
+            .chain(|| (ErrorKind::DeserializationError, "Invalid signature"))?;
+
+        // the signer must not be trusted to sign what was asked with the expected key
+        let recovered = secp256k1::SECP256K1
+            .recover(&Self::message(tx)?, &signature)
+            .map_err(|_| Self::invalid_signature())?;
+        if PublicKey::from(recovered) != self.public_key()? {
+            return Err(Self::invalid_signature());
+        }
+        Ok(signature)
+    }
+
+    fn schnorr_sign(&self, tx: &Transaction) -> Result<SchnorrSignature> {
+        let signature = self
+            .service
+            .sign(&self.path, tx, SignatureAlgorithm::Schnorr)?;
+        let signature = SchnorrSignature::from_default(&signature)
+            .chain(|| (ErrorKind::DeserializationError, "Invalid schnorr signature"))?;
+
+        schnorr_verify(
+            secp256k1::SECP256K1,
+            &Self::message(tx)?,
+            &signature,
+            &self.public_key()?.into(),
+        )
+        .map_err(|_| Self::invalid_signature())?;
+        Ok(signature)
+    }
+
+    fn schnorr_sign_unsafe(
+        &self,
+        _tx: &Transaction,
+        _aux_payload: &[u8],
+    ) -> Result<SchnorrSignature> {
+        unreachable!()
+    }
+
+    fn public_key(&self) -> Result<PublicKey> {
+        self.service.get_public_key(self.path.clone())
+    }
+}
+
+/// Policy checked by the signer before signing a transaction
+///
+/// Every kind of transaction is checked:
+/// - transfers and withdrawals: the outputs to other addresses than the wallet's own ones
+///   (change) must go to allowed addresses, and their sum must not exceed the maximum amount
+///   (the staking address of a withdrawal is the one of the signing key)
+/// - deposits: the staking address must be allowed or owned; as a deposit doesn't carry the
+///   amount of its inputs, deposits are rejected when a maximum amount is set
+/// - unbonds: the staking address must be allowed or owned, and the unbonded amount must not
+///   exceed the maximum amount
+/// - unjails and node joins: the staking address must be allowed or owned
+#[derive(Debug, Clone, Default)]
+pub struct SignerPolicy {
+    /// maximum amount of a transaction (sum of the outputs, or unbonded amount)
+    pub max_amount: Option<Coin>,
+    /// addresses allowed to receive the outputs (transfer addresses) or to be operated
+    /// (staking addresses), any address if `None`
+    pub allowed_addresses: Option<BTreeSet<String>>,
+    /// own addresses of the wallet: outputs to them are change (not counted in the amount) and
+    /// they are always allowed
+    pub own_addresses: BTreeSet<String>,
+}
+
+impl SignerPolicy {
+    /// limits the amount of transactions
+    pub fn with_max_amount(mut self, max_amount: Coin) -> Self {
+        self.max_amount = Some(max_amount);
+        self
+    }
+
+    /// allows an address to receive outputs or deposits
+    pub fn with_allowed_address<A: ToString>(mut self, address: A) -> Self {
+        self.allowed_addresses
+            .get_or_insert_with(BTreeSet::new)
+            .insert(address.to_string());
+        self
+    }
+
+    /// marks an address as owned by the wallet (change or staking address)
+    pub fn with_own_address<A: ToString>(mut self, address: A) -> Self {
+        self.own_addresses.insert(address.to_string());
+        self
+    }
+
+    /// checks a transaction against the policy, returns the reason of the rejection
+    pub fn check(&self, tx: &Transaction) -> std::result::Result<(), String> {
+        match tx {
+            Transaction::TransferTransaction(tx) => self.check_outputs(&tx.outputs),
+            Transaction::WithdrawUnbondedStakeTransaction(tx) => self.check_outputs(&tx.outputs),
+            Transaction::DepositStakeTransaction(tx) => {
+                self.check_address(&tx.to_staked_account.to_string())?;
+                match self.max_amount {
+                    Some(_) => Err("amount of deposits can't be checked".to_owned()),
+                    None => Ok(()),
+                }
+            }
+            Transaction::UnbondStakeTransaction(tx) => {
+                self.check_address(&tx.from_staked_account.to_string())?;
+                self.check_amount(tx.value)
+            }
+            Transaction::UnjailTransaction(tx) => self.check_address(&tx.address.to_string()),
+            Transaction::NodejoinTransaction(tx) => self.check_address(&tx.address.to_string()),
+        }
+    }
+
+    fn check_outputs(&self, outputs: &[TxOut]) -> std::result::Result<(), String> {
+        let mut external = Vec::new();
+        for output in outputs {
+            let address = output.address.to_string();
+            if !self.own_addresses.contains(&address) {
+                self.check_address(&address)?;
+                external.push(output.value);
+            }
+        }
+        let amount =
+            sum_coins(external.into_iter()).map_err(|e| format!("invalid output amount: {}", e))?;
+        self.check_amount(amount)
+    }
+
+    fn check_address(&self, address: &str) -> std::result::Result<(), String> {
+        if self.own_addresses.contains(address) {
+            return Ok(());
+        }
+        match self.allowed_addresses {
+            Some(ref allowed_addresses) if !allowed_addresses.contains(address) => {
+                Err(format!("address {} is not allowed", address))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_amount(&self, amount: Coin) -> std::result::Result<(), String> {
+        match self.max_amount {
+            Some(max_amount) if amount > max_amount => Err(format!(
+                "amount {} exceeds the limit {}",
+                amount, max_amount
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// transfer address of a public key (1-of-1 multi-sig address, as the wallets create them)
+fn transfer_address(public_key: PublicKey) -> Result<ExtendedAddr> {
+    let (root_hash, _) = RootHashService::<MemoryStorage>::peek_new_root_hash(
+        vec![public_key.clone()],
+        public_key,
+        1,
+    )?;
+    Ok(ExtendedAddr::OrTree(root_hash))
+}
+
+/// Reference implementation of a remote signer, deriving its keys from a HD seed
+#[derive(Clone)]
+pub struct RemoteSigner {
+    hd_seed: HDSeed,
+    auth_key: SecKey,
+    policy: SignerPolicy,
+}
+
+impl RemoteSigner {
+    /// create a new RemoteSigner accepting any transaction
+    pub fn new(hd_seed: HDSeed, auth_key: SecKey) -> Self {
+        Self {
+            hd_seed,
+            auth_key,
+            policy: SignerPolicy::default(),
+        }
+    }
+
+    /// sets the policy checked before signing
+    pub fn with_policy(mut self, policy: SignerPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// adds the transfer and staking addresses (indexes up to `count`) of an account of the HD
+    /// wallet to the own addresses of the policy (set the policy first)
+    pub fn with_own_addresses(
+        mut self,
+        network: Network,
+        account: u32,
+        count: u32,
+    ) -> Result<Self> {
+        for index in 0..=count {
+            let transfer_key = self.private_key(
+                ChainPath::create_bip44(
+                    network,
+                    HDAccountType::Transfer.account_index(account),
+                    index,
+                )
+                .into_string(),
+            )?;
+            let address = transfer_address(PublicKey::from(&transfer_key))?;
+            self.policy.own_addresses.insert(address.to_string());
+
+            let staking_key = self.private_key(
+                ChainPath::create_bip44(
+                    network,
+                    HDAccountType::Staking.account_index(account),
+                    index,
+                )
+                .into_string(),
+            )?;
+            let address =
+                StakedStateAddress::from(RedeemAddress::from(&PublicKey::from(&staking_key)));
+            self.policy.own_addresses.insert(address.to_string());
+        }
+        Ok(self)
+    }
+
+    /// serves the requests of a TCP socket until the process is stopped
+    pub fn serve_tcp(&self, listener: TcpListener) -> Result<()> {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => self.spawn(stream),
+                Err(e) => log::warn!("remote signer connection failed: {}", e),
+            }
+        }
+        Ok(())
+    }
+
+    /// serves the requests of a Unix socket until the process is stopped
+    #[cfg(unix)]
+    pub fn serve_unix(&self, listener: UnixListener) -> Result<()> {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => self.spawn(stream),
+                Err(e) => log::warn!("remote signer connection failed: {}", e),
+            }
+        }
+        Ok(())
+    }
+
+    fn spawn<S: Read + Write + Send + 'static>(&self, mut stream: S) {
+        let signer = self.clone();
+        thread::spawn(move || {
+            if let Err(e) = signer.handle_connection(&mut stream) {
+                log::warn!("remote signer connection failed: {}", e);
+            }
+        });
+    }
+
+    /// answers the request of a connection
+    pub fn handle_connection<S: Read + Write>(&self, stream: &mut S) -> Result<()> {
+        let mut nonce = [0u8; NONCE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        write_message(
+            stream,
+            &SignerHello {
+                nonce: hex::encode(&nonce),
+            },
+        )?;
+
+        let key = auth_key(&self.auth_key);
+        let envelope: SignerEnvelope = read_message(stream)?;
+        let request_mac = hex::decode(&envelope.mac).unwrap_or_default();
+        if hmac::verify(
+            &key,
+            &request_mac_data(&nonce, &envelope.payload),
+            &request_mac,
+        )
+        .is_err()
+        {
+            log::warn!("remote signer request with invalid authentication");
+            let payload =
+                serde_json::to_string(&SignerResponse::Error("invalid authentication".to_owned()))
+                    .chain(|| {
+                        (
+                            ErrorKind::SerializationError,
+                            "Unable to serialize remote signer response",
+                        )
+                    })?;
+            return write_message(
+                stream,
+                &SignerEnvelope {
+                    payload,
+                    mac: String::new(),
+                },
+            );
+        }
+
+        let response = match serde_json::from_str(&envelope.payload) {
+            Ok(request) => self
+                .handle(request)
+                .unwrap_or_else(|e| SignerResponse::Error(e.to_string())),
+            Err(_) => SignerResponse::Error("invalid request".to_owned()),
+        };
+        let payload = serde_json::to_string(&response).chain(|| {
+            (
+                ErrorKind::SerializationError,
+                "Unable to serialize remote signer response",
+            )
+        })?;
+        let mac = hmac::sign(&key, &response_mac_data(&nonce, &request_mac, &payload));
+        write_message(
+            stream,
+            &SignerEnvelope {
+                payload,
+                mac: hex::encode(mac.as_ref()),
+            },
+        )
+    }
+
+    fn handle(&self, request: SignerRequest) -> Result<SignerResponse> {
+        match request {
+            SignerRequest::GetPublicKey { path } => {
+                let public_key = PublicKey::from(&self.private_key(path)?);
+                Ok(SignerResponse::PublicKey(hex::encode(
+                    public_key.serialize_compressed(),
+                )))
+            }
+            SignerRequest::Sign {
+                path,
+                transaction,
+                algorithm,
+            } => {
+                if let Err(reason) = self.policy.check(&transaction) {
+                    log::warn!("remote signer request rejected: {}", reason);
+                    return Ok(SignerResponse::Rejected(reason));
+                }
+                let private_key = self.private_key(path)?;
+                let signature = match algorithm {
+                    SignatureAlgorithm::Ecdsa => {
+                        let (recovery_id, compact) =
+                            private_key.sign(&transaction)?.serialize_compact();
+                        let mut signature = vec![recovery_id.to_i32() as u8];
+                        signature.extend_from_slice(&compact);
+                        signature
+                    }
+                    SignatureAlgorithm::Schnorr => private_key
+                        .schnorr_sign(&transaction)?
+                        .serialize_default()
+                        .to_vec(),
+                };
+                Ok(SignerResponse::Signature(hex::encode(signature)))
+            }
+        }
+    }
+
+    fn private_key(&self, path: String) -> Result<PrivateKey> {
+        let key_chain = DefaultKeyChain::new(
+            ExtendedPrivKey::with_seed(&self.hd_seed.bytes)
+                .chain(|| (ErrorKind::InternalError, "Invalid seed bytes"))?,
+        );
+        let (extended_private_key, _) =
+            key_chain
+                .derive_private_key(ChainPath::from(path))
+                .chain(|| {
+                    (
+                        ErrorKind::InvalidInput,
+                        "Failed to derive HD wallet private key",
+                    )
+                })?;
+        Ok(PrivateKey::from(extended_private_key.private_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chain_core::state::account::{DepositBondTx, StakedStateOpAttributes, UnbondTx, UnjailTx};
+    use chain_core::tx::data::attribute::TxAttributes;
+    use chain_core::tx::data::Tx;
+
+    fn test_auth_key(byte: u8) -> SecKey {
+        parse_hex_enckey(&hex::encode([byte; 32])).unwrap()
+    }
+
+    fn start_signer(policy: SignerPolicy) -> RemoteSignerEndpoint {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let signer = RemoteSigner::new(HDSeed::new(vec![7u8; 64]), test_auth_key(1))
+            .with_policy(policy)
+            .with_own_addresses(Network::Devnet, 0, 2)
+            .unwrap();
+        thread::spawn(move || signer.serve_tcp(listener));
+        RemoteSignerEndpoint::Tcp(address)
+    }
+
+    fn chain_path(account_type: HDAccountType, index: u32) -> ChainPath {
+        ChainPath::create_bip44(Network::Devnet, account_type.account_index(0), index)
+    }
+
+    fn transfer(outputs: &[(ExtendedAddr, u64)]) -> Transaction {
+        Transaction::TransferTransaction(Tx::new_with(
+            Vec::new(),
+            outputs
+                .iter()
+                .map(|(address, amount)| TxOut::new(address.clone(), Coin::new(*amount).unwrap()))
+                .collect(),
+            TxAttributes::default(),
+        ))
+    }
+
+    #[test]
+    fn check_endpoint_parsing() {
+        assert_eq!(
+            "tcp://127.0.0.1:9000"
+                .parse::<RemoteSignerEndpoint>()
+                .unwrap(),
+            RemoteSignerEndpoint::Tcp("127.0.0.1:9000".parse().unwrap())
+        );
+        assert_eq!(
+            "unix:///run/signer.sock"
+                .parse::<RemoteSignerEndpoint>()
+                .unwrap(),
+            RemoteSignerEndpoint::Unix(PathBuf::from("/run/signer.sock"))
+        );
+        assert!("127.0.0.1:9000".parse::<RemoteSignerEndpoint>().is_err());
+    }
+
+    #[test]
+    fn check_remote_signer() {
+        let allowed = ExtendedAddr::OrTree([1; 32]);
+        let policy = SignerPolicy::default()
+            .with_max_amount(Coin::new(1000).unwrap())
+            .with_allowed_address(&allowed);
+        let service = RemoteSignerService::with_endpoint(start_signer(policy), test_auth_key(1));
+        let denied = |error: Error| assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+
+        // ECDSA for staking operations
+        let staking_path = chain_path(HDAccountType::Staking, 1);
+        let public_key = service.get_public_key(staking_path.clone()).unwrap();
+        let sign_key = service.get_sign_key(&staking_path).unwrap();
+        let own_staking_address = StakedStateAddress::from(RedeemAddress::from(&public_key));
+        let unbond = |address: StakedStateAddress, amount: u64| {
+            Transaction::UnbondStakeTransaction(UnbondTx::new(
+                address,
+                0,
+                Coin::new(amount).unwrap(),
+                StakedStateOpAttributes::new(0),
+            ))
+        };
+        let tx = unbond(own_staking_address, 100);
+        let message = Message::from_slice(&tx.id()).unwrap();
+        let signature = sign_key.sign(&tx).unwrap();
+        let recovered = secp256k1::SECP256K1.recover(&message, &signature).unwrap();
+        assert_eq!(PublicKey::from(recovered), public_key);
+
+        let other_staking_address =
+            StakedStateAddress::from_str("0x0e7c045110b8dbf29765047380898919c5cb56f4").unwrap();
+        denied(
+            sign_key
+                .sign(&unbond(own_staking_address, 1001))
+                .unwrap_err(),
+        );
+        denied(
+            sign_key
+                .sign(&unbond(other_staking_address, 1))
+                .unwrap_err(),
+        );
+        let unjail = Transaction::UnjailTransaction(UnjailTx::new(
+            0,
+            other_staking_address,
+            StakedStateOpAttributes::new(0),
+        ));
+        denied(sign_key.sign(&unjail).unwrap_err());
+        let deposit = Transaction::DepositStakeTransaction(DepositBondTx::new(
+            Vec::new(),
+            own_staking_address,
+            StakedStateOpAttributes::new(0),
+        ));
+        denied(sign_key.sign(&deposit).unwrap_err());
+
+        // Schnorr for transfers, the change to own addresses isn't limited
+        let transfer_path = chain_path(HDAccountType::Transfer, 1);
+        let public_key = service.get_public_key(transfer_path.clone()).unwrap();
+        let sign_key = service.get_sign_key(&transfer_path).unwrap();
+        let change = transfer_address(
+            service
+                .get_public_key(chain_path(HDAccountType::Transfer, 2))
+                .unwrap(),
+        )
+        .unwrap();
+        let tx = transfer(&[(allowed.clone(), 1000), (change.clone(), 5000)]);
+        let message = Message::from_slice(&tx.id()).unwrap();
+        let signature = sign_key.schnorr_sign(&tx).unwrap();
+        schnorr_verify(
+            secp256k1::SECP256K1,
+            &message,
+            &signature,
+            &public_key.into(),
+        )
+        .expect("Invalid signature");
+
+        // policy violations
+        denied(
+            sign_key
+                .schnorr_sign(&transfer(&[(allowed, 1001), (change, 1)]))
+                .unwrap_err(),
+        );
+        denied(
+            sign_key
+                .schnorr_sign(&transfer(&[(ExtendedAddr::OrTree([2; 32]), 1)]))
+                .unwrap_err(),
+        );
+    }
+
+    #[test]
+    fn check_remote_signer_signature_verification() {
+        // the first connection (sign request) is answered by a signer with other keys, the
+        // next ones (public key requests) by the expected signer
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = RemoteSignerEndpoint::Tcp(listener.local_addr().unwrap());
+        let other_signer = RemoteSigner::new(HDSeed::new(vec![8u8; 64]), test_auth_key(1));
+        let signer = RemoteSigner::new(HDSeed::new(vec![7u8; 64]), test_auth_key(1));
+        thread::spawn(move || {
+            for (i, stream) in listener.incoming().enumerate() {
+                let mut stream = stream.unwrap();
+                let _ = if i == 0 {
+                    other_signer.handle_connection(&mut stream)
+                } else {
+                    signer.handle_connection(&mut stream)
+                };
+            }
+        });
+
+        let service = RemoteSignerService::with_endpoint(endpoint, test_auth_key(1));
+        let sign_key = service
+            .get_sign_key(&chain_path(HDAccountType::Transfer, 1))
+            .unwrap();
+        let error = sign_key
+            .schnorr_sign(&transfer(&[(ExtendedAddr::OrTree([1; 32]), 1)]))
+            .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::VerifyError);
+    }
+
+    #[test]
+    fn check_remote_signer_authentication() {
+        let endpoint = start_signer(SignerPolicy::default());
+        let service = RemoteSignerService::with_endpoint(endpoint, test_auth_key(2));
+        let error = service
+            .get_public_key(ChainPath::create_bip44(Network::Devnet, 0, 1))
+            .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::BTreeMap;
+
+use parity_scale_codec::{Decode, Encode};
+
+use chain_core::common::Timespec;
+use chain_core::state::account::StakedStateAddress;
+use client_common::tendermint::types::{StakingChange, StakingOpType, Time};
+use client_common::{Result, SecKey, SecureStorage, Storage};
+
+use crate::types::StakingHistoryEntry;
+
+/// key space of wallet staking history
+pub(crate) const KEYSPACE: &str = "core_wallet_staking_history";
+
+/// Staking history of a wallet (rewards, punishments and other changes of its staking addresses)
+#[derive(Debug, Default, Clone, PartialEq, Encode, Decode)]
+pub struct StakingHistory {
+    /// History entries (in order of blocks)
+    pub entries: Vec<StakingHistoryEntry>,
+    /// Unbonded stakes which are not withdrawable yet (time from which they can be withdrawn)
+    pub unbonding: BTreeMap<StakedStateAddress, Timespec>,
+    /// Height of last block recorded
+    pub last_block_height: u64,
+}
+
+impl StakingHistory {
+    /// Records staking changes of a block, and unbonded stakes which became withdrawable in it (blocks which were
+    /// already recorded are ignored)
+    pub fn add_block(&mut self, changes: &[StakingChange], block_height: u64, block_time: Time) {
+        if block_height <= self.last_block_height {
+            return;
+        }
+        self.last_block_height = block_height;
+
+        let timespec = block_time
+            .duration_since(Time::unix_epoch())
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let matured = self
+            .unbonding
+            .iter()
+            .filter(|(_, unbonded_from)| **unbonded_from <= timespec)
+            .map(|(staking_address, unbonded_from)| (*staking_address, *unbonded_from))
+            .collect::<Vec<_>>();
+        for (staking_address, unbonded_from) in matured {
+            self.unbonding.remove(&staking_address);
+            self.entries.push(StakingHistoryEntry::unbond_matured(
+                staking_address,
+                unbonded_from,
+                block_height,
+                block_time,
+            ));
+        }
+
+        for change in changes {
+            if let Some(unbonded_from) = change.unbonded_from {
+                self.unbonding.insert(change.staking_address, unbonded_from);
+            }
+            if change.op_type == StakingOpType::Withdraw {
+                self.unbonding.remove(&change.staking_address);
+            }
+            self.entries
+                .push(StakingHistoryEntry::new(change, block_height, block_time));
+        }
+    }
+}
+
+/// Load staking history from storage
+pub fn load_staking_history<S: SecureStorage>(
+    storage: &S,
+    name: &str,
+    enckey: &SecKey,
+) -> Result<Option<StakingHistory>> {
+    storage.load_secure(KEYSPACE, name, enckey)
+}
+
+/// Save staking history to storage
+pub fn save_staking_history<S: SecureStorage>(
+    storage: &S,
+    name: &str,
+    enckey: &SecKey,
+    history: &StakingHistory,
+) -> Result<()> {
+    storage.save_secure(KEYSPACE, name, enckey, history)
+}
+
+/// Delete staking history from storage
+pub fn delete_staking_history<S: Storage>(storage: &S, name: &str) -> Result<()> {
+    storage.delete(KEYSPACE, name)?;
+    Ok(())
+}
+
+/// Maintains staking history of wallets
+///
+/// Stores `wallet-name -> staking history` (encrypted with wallet's enckey)
+#[derive(Debug, Default, Clone)]
+pub struct StakingHistoryService<S>
+where
+    S: Storage,
+{
+    storage: S,
+}
+
+impl<S> StakingHistoryService<S>
+where
+    S: Storage,
+{
+    /// Creates new instance of staking history service
+    #[inline]
+    pub fn new(storage: S) -> Self {
+        Self { storage }
+    }
+
+    /// Returns staking history entries of wallet (of given staking address only, if any)
+    pub fn entries(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        staking_address: Option<&StakedStateAddress>,
+        offset: usize,
+        limit: usize,
+        reversed: bool,
+    ) -> Result<Vec<StakingHistoryEntry>> {
+        let history = load_staking_history(&self.storage, name, enckey)?.unwrap_or_default();
+        let entries = history.entries.into_iter().filter(|entry| {
+            staking_address.map_or(true, |address| entry.staking_address == *address)
+        });
+        if reversed {
+            Ok(entries.rev().skip(offset).take(limit).collect())
+        } else {
+            Ok(entries.skip(offset).take(limit).collect())
+        }
+    }
+
+    /// Deletes staking history of wallet
+    #[inline]
+    pub fn delete_history(&self, name: &str) -> Result<()> {
+        delete_staking_history(&self.storage, name)
+    }
+
+    /// Clears all storage
+    #[inline]
+    pub fn clear(&self) -> Result<()> {
+        self.storage.clear(KEYSPACE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::str::FromStr;
+
+    use chain_core::init::coin::Coin;
+    use client_common::seckey::derive_enckey;
+    use client_common::storage::MemoryStorage;
+    use client_common::tendermint::types::StakingCoinDiff;
+    use secstr::SecUtf8;
+
+    use crate::types::{BalanceChange, StakingHistoryKind};
+
+    fn change(
+        staking_address: StakedStateAddress,
+        op_type: StakingOpType,
+        bonded: Option<StakingCoinDiff>,
+        unbonded_from: Option<Timespec>,
+    ) -> StakingChange {
+        StakingChange {
+            staking_address,
+            op_type,
+            bonded,
+            unbonded: None,
+            unbonded_from,
+            jailed_until: None,
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn check_flow() {
+        let storage = MemoryStorage::default();
+        let service = StakingHistoryService::new(storage.clone());
+        let name = "name";
+        let enckey = derive_enckey(&SecUtf8::from("passphrase"), name).unwrap();
+
+        let alice =
+            StakedStateAddress::from_str("0x0e7c045110b8dbf29765047380898919c5cb56f4").unwrap();
+        let bob =
+            StakedStateAddress::from_str("0x33502ed39d0c4e2044fb37fdcd5161493f5900c3").unwrap();
+        let time = |seconds: u64| {
+            Time::from_str(&format!(
+                "2020-04-16T21:{:02}:{:02}Z",
+                seconds / 60,
+                seconds % 60
+            ))
+            .unwrap()
+        };
+        let unbonded_from = time(30)
+            .duration_since(Time::unix_epoch())
+            .unwrap()
+            .as_secs();
+
+        let mut history = StakingHistory::default();
+        history.add_block(
+            &[
+                change(
+                    alice,
+                    StakingOpType::Reward,
+                    Some(StakingCoinDiff::Increase(Coin::unit())),
+                    None,
+                ),
+                change(bob, StakingOpType::Unbond, None, Some(unbonded_from)),
+            ],
+            10,
+            time(10),
+        );
+        // blocks are recorded only once
+        history.add_block(
+            &[change(alice, StakingOpType::Unjail, None, None)],
+            10,
+            time(10),
+        );
+        history.add_block(&[], 11, time(20));
+        history.add_block(&[], 12, time(30));
+        save_staking_history(&storage, name, &enckey, &history).unwrap();
+
+        let entries = service.entries(name, &enckey, None, 0, 100, false).unwrap();
+        assert_eq!(
+            vec![
+                StakingHistoryKind::Reward,
+                StakingHistoryKind::Unbond,
+                StakingHistoryKind::UnbondMatured
+            ],
+            entries.iter().map(|entry| entry.kind).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            BalanceChange::Incoming {
+                value: Coin::unit()
+            },
+            entries[0].bonded_change
+        );
+        assert_eq!(12, entries[2].block_height);
+        assert!(history.unbonding.is_empty());
+
+        let entries = service
+            .entries(name, &enckey, Some(&bob), 0, 1, true)
+            .unwrap();
+        assert_eq!(1, entries.len());
+        assert_eq!(StakingHistoryKind::UnbondMatured, entries[0].kind);
+
+        service.delete_history(name).unwrap();
+        assert!(service
+            .entries(name, &enckey, None, 0, 100, false)
+            .unwrap()
+            .is_empty());
+    }
+}
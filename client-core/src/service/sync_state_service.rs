@@ -1,8 +1,9 @@
 use chain_core::common::H256;
+use chain_core::genesis_filter_header;
 use client_common::{ErrorKind, Result, ResultExt, Storage};
 use parity_scale_codec::{Decode, Encode};
 /// key space of wallet sync state
-const KEYSPACE: &str = "core_wallet_sync";
+pub(crate) const KEYSPACE: &str = "core_wallet_sync";
 
 /// Sync state for wallet
 #[derive(Debug, Encode, Decode)]
@@ -17,6 +18,9 @@ pub struct SyncState {
     pub staking_root: H256,
     /// Is current synced wallet state trusted
     pub trusted: bool,
+    /// last verified block filter header (unknown for states synchronized before the filter
+    /// header chain, or on chains without it)
+    pub last_filter_header: Option<H256>,
 }
 
 impl SyncState {
@@ -28,6 +32,7 @@ impl SyncState {
             last_block_hash: "".to_owned(),
             staking_root,
             trusted: true,
+            last_filter_header: genesis_filter_header(),
         }
     }
 }
@@ -137,6 +142,7 @@ mod tests {
                             .to_string(),
                     staking_root: [0u8; 32],
                     trusted: true,
+                    last_filter_header: None,
                 }
             )
             .is_ok());
@@ -0,0 +1,671 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use parity_scale_codec::{Decode, Encode};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use secp256k1::schnorrsig::SchnorrSignature;
+
+use super::wallet_service::get_threshold_key_keyspace;
+use chain_core::common::H256;
+use client_common::threshold::{
+    KeyGenParticipant, KeyGenRound1, KeyGenShare, SignatureShare, SigningCommitment, SigningNonces,
+    ThresholdKey, ThresholdParameters,
+};
+use client_common::{
+    Error, ErrorKind, PrivateKey, Result, ResultExt, SecKey, SecureStorage, Storage,
+};
+
+/// Default time to live of a threshold signing session
+pub const DEFAULT_THRESHOLD_SESSION_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Maintains threshold keys of wallets (`root-hash -> threshold key`) along with the distributed key generations and
+/// signing sessions of current process
+///
+/// Threshold keys are persisted (encrypted) in the keyspace of their wallet. Key generations and signing sessions
+/// hold secrets (polynomial coefficients and signing nonces) and are only kept in memory (by this service and its
+/// clones): when they are lost (e.g. after a restart), they have to be restarted from scratch.
+#[derive(Debug, Clone)]
+pub struct ThresholdKeyService<T: Storage> {
+    storage: T,
+    key_generations: Arc<Mutex<HashMap<H256, KeyGeneration>>>,
+    sessions: Arc<Mutex<HashMap<H256, SigningSession>>>,
+    session_ttl: Duration,
+}
+
+#[derive(Debug)]
+struct KeyGeneration {
+    wallet_name: String,
+    participant: KeyGenParticipant,
+    round1: Option<Vec<KeyGenRound1>>,
+}
+
+#[derive(Debug)]
+struct SigningSession {
+    wallet_name: String,
+    root_hash: H256,
+    message: H256,
+    nonces: Option<SigningNonces>,
+    commitments: Vec<SigningCommitment>,
+    shares: Vec<SignatureShare>,
+    expires_at: Instant,
+}
+
+impl SigningSession {
+    /// Signing set is fixed as soon as the first partial signature is generated or received
+    fn is_signing_set_fixed(&self) -> bool {
+        !self.shares.is_empty()
+    }
+}
+
+impl<T> ThresholdKeyService<T>
+where
+    T: Storage,
+{
+    /// Creates a new instance of threshold key service
+    pub fn new(storage: T) -> Self {
+        Self {
+            storage,
+            key_generations: Default::default(),
+            sessions: Default::default(),
+            session_ttl: DEFAULT_THRESHOLD_SESSION_TTL,
+        }
+    }
+
+    /// Sets the time to live of new signing sessions
+    pub fn with_session_ttl(mut self, session_ttl: Duration) -> Self {
+        self.session_ttl = session_ttl;
+        self
+    }
+
+    /// Starts a distributed key generation and returns the first round message to broadcast to all the participants
+    ///
+    /// # Arguments
+    ///
+    /// - `name`: Name of the wallet holding `identity_key`
+    /// - `context`: Unique identifier of the key generation (shared by all the participants)
+    /// - `parameters`: Threshold parameters
+    /// - `identity_key`: Private key of current participant
+    pub fn start_key_generation(
+        &self,
+        name: &str,
+        context: H256,
+        parameters: ThresholdParameters,
+        identity_key: PrivateKey,
+    ) -> Result<KeyGenRound1> {
+        let mut key_generations = lock(&self.key_generations)?;
+
+        if key_generations.contains_key(&context) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Key generation with context ({}) is already in progress",
+                    hex::encode(&context)
+                ),
+            ));
+        }
+
+        let (participant, round1) = KeyGenParticipant::new(context, parameters, identity_key)?;
+        key_generations.insert(
+            context,
+            KeyGeneration {
+                wallet_name: name.to_owned(),
+                participant,
+                round1: None,
+            },
+        );
+
+        Ok(round1)
+    }
+
+    /// Verifies first round messages of all the participants (including current participant) and returns the
+    /// encrypted shares to send to every other participant
+    pub fn key_generation_shares(
+        &self,
+        name: &str,
+        context: &H256,
+        round1: Vec<KeyGenRound1>,
+    ) -> Result<Vec<KeyGenShare>> {
+        let mut key_generations = lock(&self.key_generations)?;
+        let key_generation = get_key_generation(&mut key_generations, name, context)?;
+
+        if let Some(ref received) = key_generation.round1 {
+            if *received != round1 {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "First round messages differ from the ones used to generate shares",
+                ));
+            }
+        }
+
+        let shares = key_generation.participant.shares(&round1)?;
+        key_generation.round1 = Some(round1);
+
+        Ok(shares)
+    }
+
+    /// Verifies the shares received from all the other participants, persists the resulting threshold key and
+    /// returns it. The key generation is finished even if verification fails (it has to be restarted in that case).
+    pub fn finish_key_generation(
+        &self,
+        name: &str,
+        context: &H256,
+        shares: &[KeyGenShare],
+        enckey: &SecKey,
+    ) -> Result<ThresholdKey> {
+        let key_generation = {
+            let mut key_generations = lock(&self.key_generations)?;
+            get_key_generation(&mut key_generations, name, context)?;
+            key_generations
+                .remove(context)
+                .chain(|| (ErrorKind::InternalError, "Key generation not found"))?
+        };
+
+        let round1 = key_generation.round1.chain(|| {
+            (
+                ErrorKind::MultiSigError,
+                "Shares of current participant are not generated yet",
+            )
+        })?;
+
+        let threshold_key = key_generation.participant.finish(&round1, shares)?;
+        self.set_threshold_key(name, &threshold_key, enckey)?;
+
+        Ok(threshold_key)
+    }
+
+    /// Returns threshold key with given root hash (if any)
+    pub fn threshold_key(
+        &self,
+        name: &str,
+        root_hash: &H256,
+        enckey: &SecKey,
+    ) -> Result<Option<ThresholdKey>> {
+        self.storage
+            .get_secure(
+                get_threshold_key_keyspace(name),
+                hex::encode(root_hash),
+                enckey,
+            )?
+            .map(|bytes| {
+                ThresholdKey::decode(&mut bytes.as_slice()).chain(|| {
+                    (
+                        ErrorKind::DeserializationError,
+                        format!(
+                            "Unable to deserialize threshold key for root hash ({})",
+                            hex::encode(root_hash)
+                        ),
+                    )
+                })
+            })
+            .transpose()
+    }
+
+    /// Starts a new signing session with threshold key with given root hash and returns session-id along with the
+    /// nonce commitment of current signer
+    ///
+    /// # Arguments
+    ///
+    /// - `name`: Name of the wallet holding the threshold key
+    /// - `root_hash`: Root hash of the threshold key
+    /// - `message`: Message to be signed (transaction id)
+    /// - `enckey`: Passphrase for encryption
+    pub fn new_session(
+        &self,
+        name: &str,
+        root_hash: &H256,
+        message: H256,
+        enckey: &SecKey,
+    ) -> Result<(H256, SigningCommitment)> {
+        let threshold_key = self.get_threshold_key(name, root_hash, enckey)?;
+        let (nonces, commitment) = SigningNonces::new(threshold_key.index)?;
+
+        let mut session_id = [0u8; 32];
+        OsRng.fill_bytes(&mut session_id);
+
+        let session = SigningSession {
+            wallet_name: name.to_owned(),
+            root_hash: *root_hash,
+            message,
+            nonces: Some(nonces),
+            commitments: vec![commitment.clone()],
+            shares: Vec::new(),
+            expires_at: Instant::now() + self.session_ttl,
+        };
+        lock(&self.sessions)?.insert(session_id, session);
+
+        Ok((session_id, commitment))
+    }
+
+    /// Adds a nonce commitment of a co-signer to session with given id
+    pub fn add_commitment(
+        &self,
+        name: &str,
+        session_id: &H256,
+        commitment: SigningCommitment,
+        enckey: &SecKey,
+    ) -> Result<()> {
+        self.update_session(name, session_id, enckey, |session, threshold_key| {
+            let public_key = threshold_key.parameters.participant(commitment.signer)?;
+
+            if let Some(existing) = session
+                .commitments
+                .iter()
+                .find(|existing| existing.signer == commitment.signer)
+            {
+                if *existing == commitment {
+                    return Ok(());
+                }
+
+                return Err(Error::new(
+                    ErrorKind::MultiSigError,
+                    format!(
+                        "Conflicting nonce commitment from signer with public key ({})",
+                        public_key
+                    ),
+                ));
+            }
+
+            if session.is_signing_set_fixed() {
+                return Err(Error::new(
+                    ErrorKind::MultiSigError,
+                    "Signing set of session is already fixed; start a new session to add signers",
+                ));
+            }
+
+            session.commitments.push(commitment);
+            session
+                .commitments
+                .sort_by_key(|commitment| commitment.signer);
+            Ok(())
+        })
+    }
+
+    /// Returns partial signature of current signer. This function fails if nonce commitments from at least
+    /// `threshold` signers are not received. The signers whose commitments are received form the signing set.
+    pub fn partial_signature(
+        &self,
+        name: &str,
+        session_id: &H256,
+        enckey: &SecKey,
+    ) -> Result<SignatureShare> {
+        self.update_session(name, session_id, enckey, |session, threshold_key| {
+            if let Some(share) = session
+                .shares
+                .iter()
+                .find(|share| share.signer == threshold_key.index)
+            {
+                return Ok(share.clone());
+            }
+
+            let nonces = session.nonces.take().chain(|| {
+                (
+                    ErrorKind::MultiSigError,
+                    "Signing nonces of session are lost; start a new session",
+                )
+            })?;
+
+            let share = threshold_key.sign(nonces, &session.message, &session.commitments)?;
+            session.shares.push(share.clone());
+            Ok(share)
+        })
+    }
+
+    /// Verifies and adds a partial signature of a co-signer to session with given id
+    pub fn add_partial_signature(
+        &self,
+        name: &str,
+        session_id: &H256,
+        share: SignatureShare,
+        enckey: &SecKey,
+    ) -> Result<()> {
+        self.update_session(name, session_id, enckey, |session, threshold_key| {
+            if let Some(existing) = session
+                .shares
+                .iter()
+                .find(|existing| existing.signer == share.signer)
+            {
+                if *existing == share {
+                    return Ok(());
+                }
+
+                return Err(Error::new(
+                    ErrorKind::MultiSigError,
+                    format!(
+                        "Conflicting partial signature from signer with public key ({})",
+                        threshold_key.parameters.participant(share.signer)?
+                    ),
+                ));
+            }
+
+            threshold_key.verify_share(&session.message, &session.commitments, &share)?;
+            session.shares.push(share);
+            Ok(())
+        })
+    }
+
+    /// Returns final signature. This function fails if partial signatures from all the signers in signing set are
+    /// not received.
+    pub fn signature(
+        &self,
+        name: &str,
+        session_id: &H256,
+        enckey: &SecKey,
+    ) -> Result<SchnorrSignature> {
+        self.update_session(name, session_id, enckey, |session, threshold_key| {
+            threshold_key.aggregate(&session.message, &session.commitments, &session.shares)
+        })
+    }
+
+    /// Returns root hash of threshold key and message (transaction id) of session with given id
+    pub fn session_info(&self, name: &str, session_id: &H256) -> Result<(H256, H256)> {
+        let mut sessions = lock(&self.sessions)?;
+        let session = get_session(&mut sessions, name, session_id)?;
+        Ok((session.root_hash, session.message))
+    }
+
+    /// Removes session with given id
+    pub fn remove_session(&self, name: &str, session_id: &H256) -> Result<()> {
+        let mut sessions = lock(&self.sessions)?;
+        get_session(&mut sessions, name, session_id)?;
+        sessions.remove(session_id);
+        Ok(())
+    }
+
+    fn update_session<F, R>(
+        &self,
+        name: &str,
+        session_id: &H256,
+        enckey: &SecKey,
+        f: F,
+    ) -> Result<R>
+    where
+        F: FnOnce(&mut SigningSession, &ThresholdKey) -> Result<R>,
+    {
+        let mut sessions = lock(&self.sessions)?;
+        let session = get_session(&mut sessions, name, session_id)?;
+        let threshold_key = self.get_threshold_key(name, &session.root_hash, enckey)?;
+
+        f(session, &threshold_key)
+    }
+
+    fn get_threshold_key(
+        &self,
+        name: &str,
+        root_hash: &H256,
+        enckey: &SecKey,
+    ) -> Result<ThresholdKey> {
+        self.threshold_key(name, root_hash, enckey)?.chain(|| {
+            (
+                ErrorKind::InvalidInput,
+                format!(
+                    "Threshold key with root hash ({}) not found",
+                    hex::encode(root_hash)
+                ),
+            )
+        })
+    }
+
+    fn set_threshold_key(
+        &self,
+        name: &str,
+        threshold_key: &ThresholdKey,
+        enckey: &SecKey,
+    ) -> Result<()> {
+        self.storage
+            .set_secure(
+                get_threshold_key_keyspace(name),
+                hex::encode(&threshold_key.root_hash()),
+                threshold_key.encode(),
+                enckey,
+            )
+            .map(|_| ())
+    }
+}
+
+fn lock<V>(map: &Mutex<V>) -> Result<MutexGuard<'_, V>> {
+    map.lock().map_err(|_| {
+        Error::new(
+            ErrorKind::InternalError,
+            "Unable to acquire lock on threshold key state",
+        )
+    })
+}
+
+fn get_key_generation<'a>(
+    key_generations: &'a mut HashMap<H256, KeyGeneration>,
+    name: &str,
+    context: &H256,
+) -> Result<&'a mut KeyGeneration> {
+    key_generations
+        .get_mut(context)
+        .filter(|key_generation| key_generation.wallet_name == name)
+        .chain(|| {
+            (
+                ErrorKind::InvalidInput,
+                format!(
+                    "Key generation with context ({}) not found; start a new key generation",
+                    hex::encode(context)
+                ),
+            )
+        })
+}
+
+fn get_session<'a>(
+    sessions: &'a mut HashMap<H256, SigningSession>,
+    name: &str,
+    session_id: &H256,
+) -> Result<&'a mut SigningSession> {
+    let expired = match sessions.get(session_id) {
+        Some(session) if session.wallet_name == name => session.expires_at <= Instant::now(),
+        _ => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Session with ID ({}) not found; start a new session",
+                    hex::encode(session_id)
+                ),
+            ))
+        }
+    };
+
+    if expired {
+        sessions.remove(session_id);
+        return Err(Error::new(
+            ErrorKind::MultiSigError,
+            format!(
+                "Session with ID ({}) has expired; start a new session",
+                hex::encode(session_id)
+            ),
+        ));
+    }
+
+    sessions
+        .get_mut(session_id)
+        .chain(|| (ErrorKind::InternalError, "Session not found"))
+}
+
+#[cfg(test)]
+mod threshold_key_service_tests {
+    use super::*;
+
+    use secp256k1::key::XOnlyPublicKey;
+    use secp256k1::schnorrsig::schnorr_verify;
+    use secp256k1::Message;
+    use secstr::SecUtf8;
+
+    use client_common::storage::MemoryStorage;
+    use client_common::{seckey::derive_enckey, PublicKey};
+
+    fn generate_keys(
+        services: &[ThresholdKeyService<MemoryStorage>],
+        threshold: u16,
+        enckey: &SecKey,
+    ) -> Vec<ThresholdKey> {
+        let private_keys = services
+            .iter()
+            .map(|_| PrivateKey::new().unwrap())
+            .collect::<Vec<_>>();
+        let parameters = ThresholdParameters::new(
+            threshold,
+            private_keys.iter().map(PublicKey::from).collect(),
+        )
+        .unwrap();
+        let context = [7u8; 32];
+
+        let round1 = services
+            .iter()
+            .zip(private_keys.into_iter())
+            .map(|(service, private_key)| {
+                service
+                    .start_key_generation("name", context, parameters.clone(), private_key)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let mut sorted = round1.clone();
+        sorted.sort_by_key(|message| message.sender);
+
+        let shares = services
+            .iter()
+            .flat_map(|service| {
+                service
+                    .key_generation_shares("name", &context, sorted.clone())
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        services
+            .iter()
+            .map(|service| {
+                service
+                    .finish_key_generation("name", &context, &shares, enckey)
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn check_threshold_flow() {
+        let enckey = derive_enckey(&SecUtf8::from("passphrase"), "").unwrap();
+        let services = (0..4)
+            .map(|_| ThresholdKeyService::new(MemoryStorage::default()))
+            .collect::<Vec<_>>();
+        let keys = generate_keys(&services, 3, &enckey);
+        let root_hash = keys[0].root_hash();
+        assert!(keys.iter().all(|key| key.root_hash() == root_hash));
+
+        let message = [1u8; 32];
+        let signers = keys
+            .iter()
+            .zip(services.iter())
+            .filter(|(key, _)| key.index != 2)
+            .map(|(_, service)| {
+                service
+                    .new_session("name", &root_hash, message, &enckey)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let signing_services = services
+            .iter()
+            .zip(keys.iter())
+            .filter(|(_, key)| key.index != 2)
+            .map(|(service, _)| service)
+            .collect::<Vec<_>>();
+
+        for (service, (session_id, _)) in signing_services.iter().zip(signers.iter()) {
+            for (_, commitment) in signers.iter() {
+                service
+                    .add_commitment("name", session_id, commitment.clone(), &enckey)
+                    .unwrap();
+            }
+        }
+
+        let shares = signing_services
+            .iter()
+            .zip(signers.iter())
+            .map(|(service, (session_id, _))| {
+                service
+                    .partial_signature("name", session_id, &enckey)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let (session_id, _) = &signers[0];
+        assert_eq!(
+            ErrorKind::MultiSigError,
+            signing_services[0]
+                .add_commitment(
+                    "name",
+                    session_id,
+                    SigningNonces::new(2).unwrap().1,
+                    &enckey
+                )
+                .expect_err("Added signer after signing set is fixed")
+                .kind()
+        );
+
+        for share in shares.into_iter() {
+            signing_services[0]
+                .add_partial_signature("name", session_id, share, &enckey)
+                .unwrap();
+        }
+
+        let signature = signing_services[0]
+            .signature("name", session_id, &enckey)
+            .unwrap();
+        let public_key = XOnlyPublicKey::from_slice(&keys[0].raw_public_key().as_bytes()).unwrap();
+        schnorr_verify(
+            secp256k1::SECP256K1,
+            &Message::from_slice(&message).unwrap(),
+            &signature,
+            &public_key,
+        )
+        .expect("Invalid threshold signature");
+    }
+
+    #[test]
+    fn check_lost_key_generation() {
+        let enckey = derive_enckey(&SecUtf8::from("passphrase"), "").unwrap();
+        let service = ThresholdKeyService::new(MemoryStorage::default());
+
+        assert_eq!(
+            ErrorKind::InvalidInput,
+            service
+                .finish_key_generation("name", &[0u8; 32], &[], &enckey)
+                .expect_err("Finished unknown key generation")
+                .kind()
+        );
+    }
+
+    #[test]
+    fn check_session_expiry() {
+        let enckey = derive_enckey(&SecUtf8::from("passphrase"), "").unwrap();
+        let services = (0..2)
+            .map(|_| {
+                ThresholdKeyService::new(MemoryStorage::default())
+                    .with_session_ttl(Duration::from_secs(0))
+            })
+            .collect::<Vec<_>>();
+        let keys = generate_keys(&services, 2, &enckey);
+
+        let (session_id, _) = services[0]
+            .new_session("name", &keys[0].root_hash(), [1u8; 32], &enckey)
+            .unwrap();
+
+        assert_eq!(
+            ErrorKind::MultiSigError,
+            services[0]
+                .partial_signature("name", &session_id, &enckey)
+                .expect_err("Signed with expired session")
+                .kind()
+        );
+        assert_eq!(
+            ErrorKind::InvalidInput,
+            services[0]
+                .session_info("name", &session_id)
+                .expect_err("Expired session is not removed")
+                .kind()
+        );
+    }
+}
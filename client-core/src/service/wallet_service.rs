@@ -21,7 +21,7 @@ use std::fmt;
 use std::str;
 
 /// Key space of wallet
-const KEYSPACE: &str = "core_wallet";
+pub(crate) const KEYSPACE: &str = "core_wallet";
 
 fn get_public_keyspace(name: &str) -> String {
     format!("{}_{}_publickey", KEYSPACE, name)
@@ -55,11 +55,31 @@ pub fn get_multisig_keyspace(name: &str) -> String {
     format!("{}_{}_multisigaddress", KEYSPACE, name)
 }
 
+pub(crate) fn get_threshold_key_keyspace(name: &str) -> String {
+    format!("{}_{}_thresholdkey", KEYSPACE, name)
+}
+
 fn get_info_keyspace(name: &str) -> String {
     format!("{}_{}_info", KEYSPACE, name)
 }
 
-fn get_wallet_keyspace() -> String {
+/// Returns the keyspaces which only contain data of the given wallet
+pub(crate) fn get_wallet_keyspaces(name: &str) -> Vec<String> {
+    vec![
+        get_info_keyspace(name),
+        get_public_keyspace(name),
+        get_stakingkey_keyspace(name),
+        get_stakingkeyset_keyspace(name),
+        get_private_keyspace(name),
+        get_hdpath_keyspace(name),
+        get_roothash_keyspace(name),
+        get_roothashset_keyspace(name),
+        get_multisig_keyspace(name),
+        get_threshold_key_keyspace(name),
+    ]
+}
+
+pub(crate) fn get_wallet_keyspace() -> String {
     format!("{}_walletname", KEYSPACE)
 }
 
@@ -1078,6 +1098,7 @@ where
         let roothash_keyspace = get_roothash_keyspace(name);
         let roothashset_keyspace = get_roothashset_keyspace(name);
         let multisigaddress_keyspace = get_multisig_keyspace(name);
+        let thresholdkey_keyspace = get_threshold_key_keyspace(name);
         let wallet_keyspace = get_wallet_keyspace();
         self.storage.delete(wallet_keyspace, name)?;
         self.storage.clear(info_keyspace)?;
@@ -1088,6 +1109,7 @@ where
         self.storage.clear(public_keyspace)?;
         self.storage.clear(private_keyspace)?;
         self.storage.clear(multisigaddress_keyspace)?;
+        self.storage.clear(thresholdkey_keyspace)?;
         Ok(())
     }
     /// Delete the key
@@ -1,5 +1,5 @@
 use parity_scale_codec::{Decode, Encode};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use chain_core::{
     init::coin::{sum_coins, CoinError},
@@ -10,7 +10,7 @@ use client_common::{Error, ErrorKind, Result, ResultExt, SecKey, SecureStorage,
 use crate::types::{TransactionChange, TransactionPending, WalletBalance};
 
 /// key space of wallet state
-const KEYSPACE: &str = "core_wallet_state";
+pub(crate) const KEYSPACE: &str = "core_wallet_state";
 
 /// Maintains mapping `wallet-name -> wallet-state`
 #[derive(Debug, Default, Clone)]
@@ -37,18 +37,19 @@ where
         self.storage.clear(KEYSPACE)
     }
 
-    /// Returns `true` if given transaction inputs are present in the list of unspent transactions, `false` otherwise
-    pub fn has_unspent_transactions(
+    /// Returns `true` if given transaction inputs are present in the list of spendable transactions (i.e. unspent,
+    /// not pending and not frozen), `false` otherwise
+    pub fn has_spendable_transactions(
         &self,
         name: &str,
         enckey: &SecKey,
         inputs: &[TxoPointer],
     ) -> Result<bool> {
-        let unspent_transactions = self.get_unspent_transactions(name, enckey, false)?;
+        let spendable_transactions = self.get_spendable_transactions(name, enckey)?;
 
         Ok(inputs
             .iter()
-            .all(|input| unspent_transactions.contains_key(input)))
+            .all(|input| spendable_transactions.contains_key(input)))
     }
 
     /// Returns currently stored unspent transactions for given wallet
@@ -68,6 +69,53 @@ where
         }
     }
 
+    /// Returns unspent transactions which can be selected as inputs of new transactions (i.e. neither used by
+    /// pending transactions nor frozen)
+    pub fn get_spendable_transactions(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+    ) -> Result<BTreeMap<TxoPointer, TxOut>> {
+        Ok(self
+            .get_wallet_state(name, enckey)?
+            .get_spendable_transactions())
+    }
+
+    /// Returns frozen outputs of given wallet
+    #[inline]
+    pub fn get_frozen_outputs(&self, name: &str, enckey: &SecKey) -> Result<BTreeSet<TxoPointer>> {
+        Ok(self.get_wallet_state(name, enckey)?.frozen_outputs)
+    }
+
+    /// Freezes (or unfreezes) given unspent outputs so that input selection doesn't spend them
+    pub fn set_frozen(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        inputs: &[TxoPointer],
+        frozen: bool,
+    ) -> Result<()> {
+        self.modify_state(name, enckey, |state| {
+            for input in inputs {
+                if !frozen {
+                    state.frozen_outputs.remove(input);
+                } else if state.unspent_transactions.contains_key(input) {
+                    state.frozen_outputs.insert(input.clone());
+                } else {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!(
+                            "Output {}:{} is not an unspent output of wallet",
+                            hex::encode(&input.id),
+                            input.index
+                        ),
+                    ));
+                }
+            }
+            Ok(())
+        })
+    }
+
     /// Returns `true` or `false` depending if input is unspent or not. `true` if the input is unspent, `false`
     /// otherwise
     pub fn are_inputs_unspent(
@@ -251,6 +299,8 @@ pub struct WalletState {
     pub transaction_history: BTreeMap<TxId, TransactionChange>,
     /// Transaction ids ordered by insert order.
     pub transaction_log: Vec<TxId>,
+    /// UTxO excluded from input selection
+    pub frozen_outputs: BTreeSet<TxoPointer>,
 }
 
 impl Default for WalletState {
@@ -261,6 +311,7 @@ impl Default for WalletState {
             pending_transactions: Default::default(),
             transaction_history: Default::default(),
             transaction_log: vec![],
+            frozen_outputs: Default::default(),
         }
     }
 }
@@ -304,6 +355,14 @@ impl WalletState {
             .collect::<Vec<_>>();
         result
     }
+    /// get available transactions which are not frozen
+    pub fn get_spendable_transactions(&self) -> BTreeMap<TxoPointer, TxOut> {
+        let mut result = self.get_available_transactions();
+        for input in self.frozen_outputs.iter() {
+            result.remove(input);
+        }
+        result
+    }
     /// get the balance info
     pub fn get_balance(&self) -> std::result::Result<WalletBalance, CoinError> {
         // pending amount
@@ -360,6 +419,7 @@ impl WalletState {
             }
             MementoOperation::RemoveUnspentTransaction(ref input) => {
                 self.unspent_transactions.remove(input);
+                self.frozen_outputs.remove(input);
             }
             MementoOperation::AddPendingTransaction(ref transaction_id, ref pending_info) => {
                 if !self.pending_transactions.contains_key(transaction_id) {
@@ -761,4 +821,63 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_freeze_outputs() {
+        let name = "name";
+        let enckey = &derive_enckey(&SecUtf8::from("passphrase"), name).unwrap();
+        let storage = prepare_wallet_storage(name, enckey);
+        let wallet_state_service = WalletStateService::new(storage);
+        let tx_pointer = |n: u8, i: usize| TxoPointer::new([n; 32], i);
+
+        // only unspent outputs can be frozen
+        assert_eq!(
+            ErrorKind::InvalidInput,
+            wallet_state_service
+                .set_frozen(name, enckey, &[tx_pointer(2, 0)], true)
+                .unwrap_err()
+                .kind()
+        );
+
+        // frozen outputs are still unspent, but not spendable
+        wallet_state_service
+            .set_frozen(name, enckey, &[tx_pointer(0, 0), tx_pointer(0, 1)], true)
+            .unwrap();
+        assert_eq!(
+            1,
+            wallet_state_service
+                .get_unspent_transactions(name, enckey, false)
+                .unwrap()
+                .len()
+        );
+        assert!(wallet_state_service
+            .get_spendable_transactions(name, enckey)
+            .unwrap()
+            .is_empty());
+        assert!(!wallet_state_service
+            .has_spendable_transactions(name, enckey, &[tx_pointer(0, 1)])
+            .unwrap());
+
+        wallet_state_service
+            .set_frozen(name, enckey, &[tx_pointer(0, 1)], false)
+            .unwrap();
+        assert!(wallet_state_service
+            .get_spendable_transactions(name, enckey)
+            .unwrap()
+            .contains_key(&tx_pointer(0, 1)));
+        assert!(wallet_state_service
+            .has_spendable_transactions(name, enckey, &[tx_pointer(0, 1)])
+            .unwrap());
+
+        // spent outputs are not frozen anymore
+        let mut memento = WalletStateMemento::default();
+        memento.remove_unspent_transaction(tx_pointer(0, 0));
+        wallet_state_service
+            .apply_memento(name, enckey, &memento)
+            .unwrap();
+        assert!(wallet_state_service
+            .get_frozen_outputs(name, enckey)
+            .unwrap()
+            .is_empty());
+    }
 }
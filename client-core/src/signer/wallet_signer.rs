@@ -182,6 +182,12 @@ where
                     })?;
                 Box::new(private_key)
             }
+            WalletKind::Xpub => {
+                return Err(Error::new(
+                    ErrorKind::PermissionDenied,
+                    "Xpub wallet is watch-only and can not sign transactions",
+                ))
+            }
         };
 
         let proof = self.root_hash_service.generate_proof(
@@ -2,10 +2,16 @@
 mod address_type;
 mod wallet_type;
 
+pub mod label;
+pub mod staking_history;
 pub mod transaction_change;
 
 pub use self::address_type::AddressType;
 #[doc(inline)]
+pub use self::label::{Contact, Label, LabelType, LabeledTransactionChange};
+#[doc(inline)]
+pub use self::staking_history::{StakingHistoryEntry, StakingHistoryKind};
+#[doc(inline)]
 pub use self::transaction_change::{
     BalanceChange, TransactionChange, TransactionInput, TransactionPending, TransactionType,
     WalletBalance,
@@ -0,0 +1,369 @@
+//! Types for address book and labels of a wallet (exchangeable as BIP-329 JSON lines)
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use parity_scale_codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use chain_core::state::account::StakedStateAddress;
+use chain_core::tx::data::address::ExtendedAddr;
+use chain_core::tx::data::input::{str2txid, TxoPointer};
+use client_common::{Error, ErrorKind, Result, ResultExt};
+
+use crate::types::TransactionChange;
+
+/// Type of labelled reference (named as BIP-329 record types)
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Encode, Decode,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelType {
+    /// Transaction (reference: hex encoded transaction id)
+    Tx,
+    /// Address of address book (reference: transfer or staking address)
+    Addr,
+    /// Transaction output (reference: `<transaction id>:<output index>`)
+    Output,
+}
+
+impl LabelType {
+    fn as_str(self) -> &'static str {
+        match self {
+            LabelType::Tx => "tx",
+            LabelType::Addr => "addr",
+            LabelType::Output => "output",
+        }
+    }
+}
+
+impl fmt::Display for LabelType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for LabelType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "tx" => Ok(LabelType::Tx),
+            "addr" => Ok(LabelType::Addr),
+            "output" => Ok(LabelType::Output),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Unknown label type {} (expected tx, addr or output)", s),
+            )),
+        }
+    }
+}
+
+/// Label of a transaction, output or address (one record of BIP-329 export)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct Label {
+    /// type of labelled reference
+    #[serde(rename = "type")]
+    pub label_type: LabelType,
+    /// labelled reference
+    #[serde(rename = "ref")]
+    pub reference: String,
+    /// label (name of contact for addresses)
+    #[serde(default)]
+    pub label: String,
+    /// free form notes (not part of BIP-329, ignored by other wallets)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+}
+
+impl Label {
+    /// Creates a new label after validating its reference (which is normalized)
+    pub fn new(
+        label_type: LabelType,
+        reference: &str,
+        label: String,
+        notes: Option<String>,
+    ) -> Result<Self> {
+        if label.trim().is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "Label cannot be empty"));
+        }
+
+        Ok(Label {
+            label_type,
+            reference: normalize_reference(label_type, reference)?,
+            label,
+            notes: notes.filter(|notes| !notes.trim().is_empty()),
+        })
+    }
+
+    /// Validates reference and label of a deserialized record
+    pub fn validated(self) -> Result<Self> {
+        Label::new(self.label_type, &self.reference, self.label, self.notes)
+    }
+}
+
+/// Contact of address book of a wallet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contact {
+    /// transfer or staking address
+    pub address: String,
+    /// name of contact
+    pub name: String,
+    /// free form notes
+    pub notes: Option<String>,
+}
+
+impl From<Label> for Contact {
+    fn from(label: Label) -> Self {
+        Contact {
+            address: label.reference,
+            name: label.label,
+            notes: label.notes,
+        }
+    }
+}
+
+/// Transaction change with the labels of wallet attached
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabeledTransactionChange {
+    /// Transaction change
+    #[serde(flatten)]
+    pub change: TransactionChange,
+    /// Label of transaction
+    pub label: Option<String>,
+    /// Labels of transaction outputs (by output index)
+    pub output_labels: BTreeMap<usize, String>,
+    /// Names of address book contacts the transaction was sent to (or received from, if inputs are known)
+    pub contacts: Vec<String>,
+}
+
+impl LabeledTransactionChange {
+    /// Attaches labels of wallet to a transaction change
+    pub fn new(change: TransactionChange, labels: &BTreeMap<(LabelType, String), Label>) -> Self {
+        let transaction_id = hex::encode(&change.transaction_id);
+        let label = labels
+            .get(&(LabelType::Tx, transaction_id.clone()))
+            .map(|label| label.label.clone());
+        let output_labels = (0..change.outputs.len())
+            .filter_map(|index| {
+                labels
+                    .get(&(
+                        LabelType::Output,
+                        output_reference(&TxoPointer::new(change.transaction_id, index)),
+                    ))
+                    .map(|label| (index, label.label.clone()))
+            })
+            .collect();
+
+        let mut contacts = Vec::new();
+        let addresses = change
+            .outputs
+            .iter()
+            .chain(
+                change
+                    .inputs
+                    .iter()
+                    .filter_map(|input| input.output.as_ref()),
+            )
+            .map(|output| output.address.to_string());
+        for address in addresses {
+            if let Some(contact) = labels.get(&(LabelType::Addr, address)) {
+                if !contacts.contains(&contact.label) {
+                    contacts.push(contact.label.clone());
+                }
+            }
+        }
+
+        LabeledTransactionChange {
+            change,
+            label,
+            output_labels,
+            contacts,
+        }
+    }
+}
+
+/// Returns reference of a transaction output label
+pub fn output_reference(pointer: &TxoPointer) -> String {
+    format!("{}:{}", hex::encode(&pointer.id), pointer.index)
+}
+
+/// Parses an output reference (`<transaction id>:<output index>`)
+pub fn parse_output_reference(reference: &str) -> Result<TxoPointer> {
+    let mut parts = reference.trim().splitn(2, ':');
+    let transaction_id = str2txid(parts.next().unwrap_or_default())
+        .chain(|| (ErrorKind::InvalidInput, "Invalid transaction id of output"))?;
+    let index = parts
+        .next()
+        .and_then(|index| index.parse::<usize>().ok())
+        .chain(|| {
+            (
+                ErrorKind::InvalidInput,
+                "Output reference should be <transaction id>:<output index>",
+            )
+        })?;
+    Ok(TxoPointer::new(transaction_id, index))
+}
+
+/// Encodes labels as BIP-329 JSON lines
+pub fn labels_to_jsonl(labels: &[Label]) -> Result<String> {
+    let mut jsonl = String::new();
+    for label in labels {
+        jsonl.push_str(
+            &serde_json::to_string(label)
+                .chain(|| (ErrorKind::SerializationError, "Unable to serialize label"))?,
+        );
+        jsonl.push('\n');
+    }
+    Ok(jsonl)
+}
+
+/// Decodes labels from BIP-329 JSON lines
+///
+/// Records of types not used by this wallet (e.g. `pubkey`, `input` or `xpub`) and records without label are
+/// skipped.
+pub fn labels_from_jsonl(jsonl: &str) -> Result<Vec<Label>> {
+    let mut labels = Vec::new();
+    for (index, line) in jsonl.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record: Value = serde_json::from_str(line).chain(|| {
+            (
+                ErrorKind::DeserializationError,
+                format!("Unable to parse label record on line {}", index + 1),
+            )
+        })?;
+        let supported = record
+            .get("type")
+            .and_then(Value::as_str)
+            .map_or(false, |label_type| label_type.parse::<LabelType>().is_ok());
+        let has_label = record
+            .get("label")
+            .and_then(Value::as_str)
+            .map_or(false, |label| !label.trim().is_empty());
+        if !supported || !has_label {
+            continue;
+        }
+
+        let label = serde_json::from_value::<Label>(record)
+            .chain(|| {
+                (
+                    ErrorKind::DeserializationError,
+                    format!("Invalid label record on line {}", index + 1),
+                )
+            })?
+            .validated()
+            .chain(|| {
+                (
+                    ErrorKind::InvalidInput,
+                    format!("Invalid label reference on line {}", index + 1),
+                )
+            })?;
+        labels.push(label);
+    }
+    Ok(labels)
+}
+
+/// Validates a labelled reference and returns it in the form labels are stored with
+pub fn normalize_reference(label_type: LabelType, reference: &str) -> Result<String> {
+    let reference = reference.trim();
+    match label_type {
+        LabelType::Tx => {
+            let transaction_id = str2txid(reference)
+                .chain(|| (ErrorKind::InvalidInput, "Invalid transaction id"))?;
+            Ok(hex::encode(&transaction_id))
+        }
+        LabelType::Addr => {
+            if let Ok(address) = ExtendedAddr::from_str(reference) {
+                Ok(address.to_string())
+            } else if let Ok(address) = StakedStateAddress::from_str(reference) {
+                Ok(address.to_string())
+            } else {
+                Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Invalid transfer or staking address: {}", reference),
+                ))
+            }
+        }
+        LabelType::Output => Ok(output_reference(&parse_output_reference(reference)?)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSFER_ADDRESS: &str =
+        "dcro1zz30nheum6vnug3mjs0j4kw4w739tca8cuqae2kdjmt8suhv693qcs3qyn";
+    const TRANSACTION_ID: &str = "0f46e113c21f9eacb26d752f9523746cf8d47ecbea492736d176005911f973a5";
+
+    #[test]
+    fn check_label_validation() {
+        let label = Label::new(
+            LabelType::Output,
+            &format!(" {}:1", TRANSACTION_ID.to_uppercase()),
+            "salary".to_owned(),
+            Some("".to_owned()),
+        )
+        .unwrap();
+        assert_eq!(format!("{}:1", TRANSACTION_ID), label.reference);
+        assert_eq!(None, label.notes);
+
+        assert!(Label::new(LabelType::Tx, "0f46", "salary".to_owned(), None).is_err());
+        assert!(Label::new(LabelType::Output, TRANSACTION_ID, "salary".to_owned(), None).is_err());
+        assert!(Label::new(LabelType::Addr, "invalid", "alice".to_owned(), None).is_err());
+        assert!(Label::new(LabelType::Addr, TRANSFER_ADDRESS, " ".to_owned(), None).is_err());
+        assert!(Label::new(
+            LabelType::Addr,
+            "0x0e7c045110b8dbf29765047380898919c5cb56f4",
+            "validator".to_owned(),
+            None
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn check_jsonl_roundtrip() {
+        let labels = vec![
+            Label::new(LabelType::Tx, TRANSACTION_ID, "rent".to_owned(), None).unwrap(),
+            Label::new(
+                LabelType::Addr,
+                TRANSFER_ADDRESS,
+                "alice".to_owned(),
+                Some("landlord".to_owned()),
+            )
+            .unwrap(),
+        ];
+
+        let jsonl = labels_to_jsonl(&labels).unwrap();
+        assert_eq!(
+            format!(
+                "{{\"type\":\"tx\",\"ref\":\"{}\",\"label\":\"rent\"}}\n",
+                TRANSACTION_ID
+            ),
+            jsonl.lines().next().unwrap().to_owned() + "\n"
+        );
+        assert_eq!(labels, labels_from_jsonl(&jsonl).unwrap());
+
+        let foreign = format!(
+            "{}\n{{\"type\":\"xpub\",\"ref\":\"xpub661MyMwAqRbcF\",\"label\":\"cold\"}}\n\n{{\"type\":\"tx\",\"ref\":\"{}\"}}\n",
+            jsonl.lines().nth(1).unwrap(),
+            TRANSACTION_ID
+        );
+        assert_eq!(labels[1..].to_vec(), labels_from_jsonl(&foreign).unwrap());
+
+        assert_eq!(
+            ErrorKind::DeserializationError,
+            labels_from_jsonl("{\"type\":").unwrap_err().kind()
+        );
+        assert_eq!(
+            ErrorKind::InvalidInput,
+            labels_from_jsonl("{\"type\":\"tx\",\"ref\":\"0f46\",\"label\":\"rent\"}")
+                .unwrap_err()
+                .kind()
+        );
+    }
+}
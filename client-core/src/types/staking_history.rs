@@ -0,0 +1,305 @@
+//! Types for tracking staking rewards, punishments and other changes of staking addresses of a wallet
+use std::fmt;
+use std::str::FromStr;
+
+use parity_scale_codec::{Decode, Encode, Error, Input, Output};
+use serde::{Deserialize, Serialize};
+
+use chain_core::common::Timespec;
+use chain_core::init::coin::Coin;
+use chain_core::state::account::StakedStateAddress;
+use client_common::tendermint::types::{StakingChange, StakingCoinDiff, StakingOpType, Time};
+
+use crate::types::BalanceChange;
+
+/// Kind of staking history entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+#[serde(rename_all = "snake_case")]
+pub enum StakingHistoryKind {
+    /// Stake deposited to staking address
+    Deposit,
+    /// Stake unbonded
+    Unbond,
+    /// Unbonded stake withdrawn to transfer address
+    Withdraw,
+    /// Staking address joined as validator node
+    NodeJoin,
+    /// Validator rewards distributed
+    Reward,
+    /// Stake slashed
+    Slash,
+    /// Staking address jailed
+    Jail,
+    /// Staking address unjailed
+    Unjail,
+    /// Unbonded stake became withdrawable
+    UnbondMatured,
+}
+
+impl fmt::Display for StakingHistoryKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StakingHistoryKind::Deposit => write!(f, "deposit"),
+            StakingHistoryKind::Unbond => write!(f, "unbond"),
+            StakingHistoryKind::Withdraw => write!(f, "withdraw"),
+            StakingHistoryKind::NodeJoin => write!(f, "node_join"),
+            StakingHistoryKind::Reward => write!(f, "reward"),
+            StakingHistoryKind::Slash => write!(f, "slash"),
+            StakingHistoryKind::Jail => write!(f, "jail"),
+            StakingHistoryKind::Unjail => write!(f, "unjail"),
+            StakingHistoryKind::UnbondMatured => write!(f, "unbond_matured"),
+        }
+    }
+}
+
+impl From<StakingOpType> for StakingHistoryKind {
+    fn from(op_type: StakingOpType) -> Self {
+        match op_type {
+            StakingOpType::Deposit => StakingHistoryKind::Deposit,
+            StakingOpType::Unbond => StakingHistoryKind::Unbond,
+            StakingOpType::Withdraw => StakingHistoryKind::Withdraw,
+            StakingOpType::NodeJoin => StakingHistoryKind::NodeJoin,
+            StakingOpType::Reward => StakingHistoryKind::Reward,
+            StakingOpType::Slash => StakingHistoryKind::Slash,
+            StakingOpType::Jail => StakingHistoryKind::Jail,
+            StakingOpType::Unjail => StakingHistoryKind::Unjail,
+        }
+    }
+}
+
+/// Change of a staked state of wallet in a block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StakingHistoryEntry {
+    /// Staking address
+    pub staking_address: StakedStateAddress,
+    /// Kind of change
+    pub kind: StakingHistoryKind,
+    /// Change of bonded amount
+    pub bonded_change: BalanceChange,
+    /// Change of unbonded amount
+    pub unbonded_change: BalanceChange,
+    /// Time from which unbonded amount can be withdrawn (for unbonding and its maturity)
+    pub unbonded_from: Option<Timespec>,
+    /// Time until which the staking address is jailed
+    pub jailed_until: Option<Timespec>,
+    /// Reason of punishment (for slashing and jailing)
+    pub reason: Option<String>,
+    /// Height of block of the change
+    pub block_height: u64,
+    /// Time of block of the change
+    pub block_time: Time,
+}
+
+impl StakingHistoryEntry {
+    /// Creates a history entry from a staking change of given block
+    pub fn new(change: &StakingChange, block_height: u64, block_time: Time) -> Self {
+        StakingHistoryEntry {
+            staking_address: change.staking_address,
+            kind: change.op_type.into(),
+            bonded_change: balance_change(change.bonded),
+            unbonded_change: balance_change(change.unbonded),
+            unbonded_from: change.unbonded_from,
+            jailed_until: change.jailed_until,
+            reason: change.reason.clone(),
+            block_height,
+            block_time,
+        }
+    }
+
+    /// Creates a history entry of unbonded stake which became withdrawable in given block
+    pub fn unbond_matured(
+        staking_address: StakedStateAddress,
+        unbonded_from: Timespec,
+        block_height: u64,
+        block_time: Time,
+    ) -> Self {
+        StakingHistoryEntry {
+            staking_address,
+            kind: StakingHistoryKind::UnbondMatured,
+            bonded_change: BalanceChange::NoChange,
+            unbonded_change: BalanceChange::NoChange,
+            unbonded_from: Some(unbonded_from),
+            jailed_until: None,
+            reason: None,
+            block_height,
+            block_time,
+        }
+    }
+}
+
+impl Encode for StakingHistoryEntry {
+    fn encode_to<W: Output>(&self, dest: &mut W) {
+        self.staking_address.encode_to(dest);
+        self.kind.encode_to(dest);
+        self.bonded_change.encode_to(dest);
+        self.unbonded_change.encode_to(dest);
+        self.unbonded_from.encode_to(dest);
+        self.jailed_until.encode_to(dest);
+        self.reason.encode_to(dest);
+        self.block_height.encode_to(dest);
+        self.block_time.to_rfc3339().encode_to(dest);
+    }
+}
+
+impl Decode for StakingHistoryEntry {
+    fn decode<I: Input>(input: &mut I) -> std::result::Result<Self, Error> {
+        let staking_address = StakedStateAddress::decode(input)?;
+        let kind = StakingHistoryKind::decode(input)?;
+        let bonded_change = BalanceChange::decode(input)?;
+        let unbonded_change = BalanceChange::decode(input)?;
+        let unbonded_from = <Option<Timespec>>::decode(input)?;
+        let jailed_until = <Option<Timespec>>::decode(input)?;
+        let reason = <Option<String>>::decode(input)?;
+        let block_height = u64::decode(input)?;
+        let block_time = Time::from_str(&String::decode(input)?)
+            .map_err(|_| Error::from("Unable to parse block time"))?;
+        Ok(StakingHistoryEntry {
+            staking_address,
+            kind,
+            bonded_change,
+            unbonded_change,
+            unbonded_from,
+            jailed_until,
+            reason,
+            block_height,
+            block_time,
+        })
+    }
+}
+
+/// Encodes staking history as CSV (amounts in CRO, negative for decreases), e.g. for tax reporting
+pub fn staking_history_to_csv(entries: &[StakingHistoryEntry]) -> String {
+    let mut csv = String::from(
+        "block_time,block_height,staking_address,kind,bonded_change,unbonded_change,unbonded_from,jailed_until,reason\n",
+    );
+    for entry in entries {
+        let fields = [
+            entry.block_time.to_rfc3339(),
+            entry.block_height.to_string(),
+            entry.staking_address.to_string(),
+            entry.kind.to_string(),
+            signed_amount(entry.bonded_change),
+            signed_amount(entry.unbonded_change),
+            optional(entry.unbonded_from),
+            optional(entry.jailed_until),
+            optional(entry.reason.as_ref()),
+        ];
+        let fields = fields
+            .iter()
+            .map(|field| csv_field(field))
+            .collect::<Vec<_>>();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Quotes a CSV field (RFC 4180) if it contains a separator, a quote or a line break
+fn csv_field(value: &str) -> String {
+    if value.contains(|c: char| c == ',' || c == '"' || c == '\n' || c == '\r') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+fn balance_change(diff: Option<StakingCoinDiff>) -> BalanceChange {
+    match diff {
+        Some(StakingCoinDiff::Increase(value)) if value != Coin::zero() => {
+            BalanceChange::Incoming { value }
+        }
+        Some(StakingCoinDiff::Decrease(value)) if value != Coin::zero() => {
+            BalanceChange::Outgoing { value }
+        }
+        _ => BalanceChange::NoChange,
+    }
+}
+
+fn signed_amount(change: BalanceChange) -> String {
+    match change {
+        BalanceChange::Incoming { value } => value.to_string(),
+        BalanceChange::Outgoing { value } => format!("-{}", value),
+        BalanceChange::NoChange => "0".to_owned(),
+    }
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_entry_encoding_and_csv() {
+        let staking_address =
+            StakedStateAddress::from_str("0x0e7c045110b8dbf29765047380898919c5cb56f4").unwrap();
+        let block_time = Time::from_str("2020-04-16T21:03:34Z").unwrap();
+        let slash = StakingHistoryEntry::new(
+            &StakingChange {
+                staking_address,
+                op_type: StakingOpType::Slash,
+                bonded: Some(StakingCoinDiff::Decrease(Coin::new(150_000_000).unwrap())),
+                unbonded: Some(StakingCoinDiff::Decrease(Coin::zero())),
+                unbonded_from: None,
+                jailed_until: None,
+                reason: Some("NonLive".to_owned()),
+            },
+            10,
+            block_time,
+        );
+        let matured =
+            StakingHistoryEntry::unbond_matured(staking_address, 1_587_071_014, 11, block_time);
+
+        assert_eq!(
+            slash,
+            StakingHistoryEntry::decode(&mut slash.encode().as_slice()).unwrap()
+        );
+
+        let csv = staking_history_to_csv(&[slash, matured]);
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(3, lines.len());
+        assert_eq!(
+            format!(
+                "{},10,0x0e7c045110b8dbf29765047380898919c5cb56f4,slash,-1.50000000,0,,,NonLive",
+                block_time.to_rfc3339()
+            ),
+            lines[1]
+        );
+        assert_eq!(
+            format!(
+                "{},11,0x0e7c045110b8dbf29765047380898919c5cb56f4,unbond_matured,0,0,1587071014,,",
+                block_time.to_rfc3339()
+            ),
+            lines[2]
+        );
+    }
+
+    #[test]
+    fn check_csv_fields_are_quoted() {
+        assert_eq!("NonLive", csv_field("NonLive"));
+        assert_eq!("\"a,b\"", csv_field("a,b"));
+        assert_eq!("\"say \"\"hi\"\"\"", csv_field("say \"hi\""));
+        assert_eq!("\"a\nb\"", csv_field("a\nb"));
+
+        let staking_address =
+            StakedStateAddress::from_str("0x0e7c045110b8dbf29765047380898919c5cb56f4").unwrap();
+        let block_time = Time::from_str("2020-04-16T21:03:34Z").unwrap();
+        let entry = StakingHistoryEntry::new(
+            &StakingChange {
+                staking_address,
+                op_type: StakingOpType::Slash,
+                bonded: None,
+                unbonded: None,
+                unbonded_from: None,
+                jailed_until: None,
+                reason: Some("a,\"b\"\nc".to_owned()),
+            },
+            10,
+            block_time,
+        );
+        let csv = staking_history_to_csv(&[entry]);
+        assert!(csv.ends_with(",\"a,\"\"b\"\"\nc\"\n"));
+    }
+}
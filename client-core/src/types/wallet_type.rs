@@ -16,6 +16,8 @@ pub enum WalletKind {
     HD,
     /// HW Wallet
     HW,
+    /// Watch-only wallet deriving its addresses from account-level extended public keys
+    Xpub,
 }
 
 impl From<u64> for WalletKind {
@@ -23,6 +25,7 @@ impl From<u64> for WalletKind {
         match code {
             0 => WalletKind::Basic,
             1 => WalletKind::HD,
+            3 => WalletKind::Xpub,
             _ => WalletKind::HW,
         }
     }
@@ -38,10 +41,12 @@ impl FromStr for WalletKind {
             Ok(WalletKind::HW)
         } else if eq_ascii(s, "basic") {
             Ok(WalletKind::Basic)
+        } else if eq_ascii(s, "xpub") {
+            Ok(WalletKind::Xpub)
         } else {
             Err(Error::new(
                 ErrorKind::InvalidInput,
-                "Wallet type can either be `hd` or `hw` or `basic`",
+                "Wallet type can either be `hd` or `hw` or `basic` or `xpub`",
             ))
         }
     }
@@ -20,12 +20,14 @@ use chain_core::tx::data::address::ExtendedAddr;
 use chain_core::tx::data::attribute::TxAttributes;
 use chain_core::tx::data::input::TxoPointer;
 use chain_core::tx::data::output::TxOut;
-#[cfg(feature = "experimental")]
 use chain_core::tx::data::Tx;
 use chain_core::tx::data::TxId;
 use chain_core::tx::witness::tree::RawXOnlyPubkey;
 use chain_core::tx::TxAux;
 use client_common::tendermint::types::BroadcastTxResponse;
+use client_common::threshold::{
+    KeyGenRound1, KeyGenShare, SignatureShare, SigningCommitment, ThresholdKey,
+};
 use client_common::{
     MultiSigAddress, PrivateKey, PrivateKeyAction, PublicKey, Result, SecKey, Transaction,
     TransactionInfo,
@@ -33,9 +35,12 @@ use client_common::{
 use serde::{Deserialize, Serialize};
 
 use crate::hd_wallet::HardwareKind;
-use crate::service::{SyncState, WalletInfo};
+use crate::service::{account_wallet_name, AccountXpubs, SyncState, WalletInfo};
 use crate::transaction_builder::{SignedTransferTransaction, UnsignedTransferTransaction};
-use crate::types::{AddressType, TransactionChange, TransactionPending, WalletBalance, WalletKind};
+use crate::types::{
+    AddressType, Contact, Label, LabelType, LabeledTransactionChange, StakingHistoryEntry,
+    TransactionChange, TransactionPending, WalletBalance, WalletKind,
+};
 use crate::{InputSelectionStrategy, Mnemonic, UnspentTransactions};
 
 /// information needed when create/delete a wallet
@@ -55,6 +60,16 @@ pub struct WalletRequest {
     /// the auth token of the wallet
     #[serde(alias = "auth_token", alias = "enckey")]
     pub enckey: SecKey,
+    /// the logical account of the wallet (`0` by default)
+    #[serde(default)]
+    pub account: u32,
+}
+
+impl WalletRequest {
+    /// Name of the requested account wallet
+    pub fn wallet_name(&self) -> String {
+        account_wallet_name(&self.name, self.account)
+    }
 }
 
 /// Interface for a generic wallet
@@ -69,6 +84,9 @@ pub trait WalletClient: Send + Sync {
     fn get_wallet_kind(&self, name: &str, enckey: &SecKey) -> Result<WalletKind>;
 
     /// Send balance to a transfer address, return the transaction id directly
+    ///
+    /// Inputs are selected with given strategy (`InputSelectionStrategy::Manual` to spend given inputs only)
+    #[allow(clippy::too_many_arguments)]
     fn send_to_address(
         &self,
         name: &str,
@@ -77,6 +95,7 @@ pub trait WalletClient: Send + Sync {
         address: ExtendedAddr,
         view_keys: &mut BTreeSet<PublicKey>,
         network_id: u8,
+        input_selection_strategy: Option<InputSelectionStrategy>,
     ) -> Result<TxId>;
 
     /// send balance to a transfer address, waiting it transaction confirmed then return transaction id
@@ -132,12 +151,46 @@ pub trait WalletClient: Send + Sync {
         view_key: &PrivateKey,
     ) -> Result<SecKey>;
 
+    /// Restore a watch only wallet which derives its addresses from account-level extended
+    /// public keys (exported with `export_xpubs`) and tracks its transactions with the view key
+    fn restore_xpub_wallet(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        xpubs: &AccountXpubs,
+        view_key: &PrivateKey,
+    ) -> Result<SecKey>;
+
+    /// Retrieves the account-level extended public keys of a HD or xpub wallet
+    fn export_xpubs(&self, name: &str, enckey: &SecKey) -> Result<AccountXpubs>;
+
+    /// Creates a backup of all the stored data of a wallet and its accounts (keys, addresses,
+    /// wallet and sync states, HD indexes and multi-sig sessions), encrypted with its enckey
+    fn backup_wallet(&self, name: &str, enckey: &SecKey) -> Result<Vec<u8>>;
+
+    /// Validates a backup created by `backup_wallet` and restores all its stored data
+    fn restore_wallet_backup(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        backup: &[u8],
+    ) -> Result<SecKey>;
+
     /// Remove a wallet
     fn delete_wallet(&self, name: &str, passphrase: &SecUtf8) -> Result<()>;
 
     /// get auth token client
     fn auth_token(&self, name: &str, passphrase: &SecUtf8) -> Result<SecKey>;
 
+    /// Creates the next logical account of a HD or hardware wallet and returns its number
+    ///
+    /// The account has its own address indexes, view key and balance and it is accessed with
+    /// the wallet name `account_wallet_name(name, account)` and the wallet's auth token.
+    fn new_account(&self, name: &str, enckey: &SecKey) -> Result<u32>;
+
+    /// Retrieves the logical accounts of a wallet (account `0` is the wallet itself)
+    fn accounts(&self, name: &str, enckey: &SecKey) -> Result<Vec<u32>>;
+
     /// Retrieves view key corresponding to a given wallet
     fn view_key(&self, name: &str, enckey: &SecKey) -> Result<PublicKey>;
 
@@ -221,6 +274,16 @@ pub trait WalletClient: Send + Sync {
         address_type: Option<AddressType>,
     ) -> Result<PublicKey>;
 
+    /// Derives the public key at given index of the address chain of a HD or xpub wallet
+    /// (without storing it)
+    fn peek_public_key(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        address_type: AddressType,
+        index: u32,
+    ) -> Result<PublicKey>;
+
     /// Generates a new redeem address for given wallet
     fn new_staking_address(&self, name: &str, enckey: &SecKey) -> Result<StakedStateAddress>;
 
@@ -279,7 +342,7 @@ pub trait WalletClient: Send + Sync {
     /// Retrieves current balance of wallet
     fn balance(&self, name: &str, enckey: &SecKey) -> Result<WalletBalance>;
 
-    /// Retrieves transaction history of wallet
+    /// Retrieves transaction history of wallet (with labels of transactions, outputs and contacts attached)
     fn history(
         &self,
         name: &str,
@@ -287,7 +350,7 @@ pub trait WalletClient: Send + Sync {
         limit: usize,
         offset: usize,
         reversed: bool,
-    ) -> Result<Vec<TransactionChange>>;
+    ) -> Result<Vec<LabeledTransactionChange>>;
 
     /// Retrieves transaction change corresponding to given transaction ID
     fn get_transaction_change(
@@ -297,11 +360,56 @@ pub trait WalletClient: Send + Sync {
         transaction_id: &TxId,
     ) -> Result<Option<TransactionChange>>;
 
+    /// Sets labels of wallet (address book contacts, transaction and output labels), replacing existing labels of
+    /// same references
+    fn set_labels(&self, name: &str, enckey: &SecKey, labels: Vec<Label>) -> Result<()>;
+
+    /// Removes a label of wallet, returns `false` if there was none
+    fn remove_label(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        label_type: LabelType,
+        reference: &str,
+    ) -> Result<bool>;
+
+    /// Retrieves all labels of wallet
+    fn labels(&self, name: &str, enckey: &SecKey) -> Result<Vec<Label>>;
+
+    /// Retrieves address book of wallet
+    fn contacts(&self, name: &str, enckey: &SecKey) -> Result<Vec<Contact>>;
+
+    /// Retrieves staking history (rewards, punishments and other changes) of staking addresses of wallet (of given
+    /// staking address only, if any)
+    fn staking_history(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        staking_address: Option<&StakedStateAddress>,
+        offset: usize,
+        limit: usize,
+        reversed: bool,
+    ) -> Result<Vec<StakingHistoryEntry>>;
+
     /// Retrieves all unspent transactions of wallet
     fn unspent_transactions(&self, name: &str, enckey: &SecKey) -> Result<UnspentTransactions>;
 
-    /// Checks if all the provided transaction inputs are present in unspent transaction for given wallet
-    fn has_unspent_transactions(
+    /// Freezes (or unfreezes) unspent outputs of wallet, frozen outputs are never selected as inputs of new
+    /// transactions
+    fn set_frozen_outputs(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        inputs: &[TxoPointer],
+        frozen: bool,
+    ) -> Result<()>;
+
+    /// Retrieves frozen outputs of wallet
+    fn frozen_outputs(&self, name: &str, enckey: &SecKey) -> Result<Vec<TxoPointer>>;
+
+    /// Checks if all the provided transaction inputs are spendable (i.e. unspent, not pending and not frozen) for
+    /// given wallet
+    fn has_spendable_transactions(
         &self,
         name: &str,
         enckey: &SecKey,
@@ -420,13 +528,15 @@ pub trait MultiSigWalletClient: WalletClient {
         public_key: &PublicKey,
     ) -> Result<SchnorrSignature>;
 
-    /// Creates a new multi-sig session and returns session-id
+    /// Creates a new multi-sig session and returns session-id. The session is bound to given message (transaction id)
+    /// and expires after a while; its secret nonce is only kept in memory, so the session has to be restarted if it
+    /// is lost (e.g. after a restart).
     ///
     /// # Arguments
     ///
     /// `name`: Name of wallet
     /// `enckey`: enckey of wallet
-    /// `message`: Message to be signed,
+    /// `message`: Message to be signed (transaction id),
     /// `signer_public_keys`: Public keys of all co-signers (including current signer)
     /// `self_public_key`: Public key of current signer
     fn new_multi_sig_session(
@@ -467,7 +577,8 @@ pub trait MultiSigWalletClient: WalletClient {
     /// received.
     fn partial_signature(&self, session_id: &H256, enckey: &SecKey) -> Result<H256>;
 
-    /// Adds a partial signature from a public key to session with given id
+    /// Adds a partial signature from a public key to session with given id. The partial signature is verified on
+    /// arrival.
     fn add_partial_signature(
         &self,
         session_id: &H256,
@@ -488,3 +599,107 @@ pub trait MultiSigWalletClient: WalletClient {
         unsigned_transaction: Tx,
     ) -> Result<TxAux>;
 }
+
+/// Interface for a generic wallet for threshold (FROST-style) multi-signature keys
+///
+/// A threshold key is generated by all the participants together (no participant ever knows the whole private key)
+/// and its aggregated public key is the only leaf of an `OrTree` address, so that any `threshold` of the participants
+/// can spend from it, regardless of the number of participants. Key generations and signing sessions are only kept
+/// in memory and have to be restarted if they are lost (e.g. after a restart).
+pub trait ThresholdWalletClient: WalletClient {
+    /// Starts a distributed key generation and returns the first round message to broadcast to all the participants
+    ///
+    /// # Arguments
+    ///
+    /// `name`: Name of wallet
+    /// `enckey`: enckey of wallet
+    /// `context`: Unique identifier of the key generation (shared by all the participants)
+    /// `public_keys`: Public keys of all the participants (including current participant)
+    /// `self_public_key`: Public key of current participant
+    /// `threshold`: Number of participants required to sign
+    fn new_threshold_key_generation(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        context: H256,
+        public_keys: Vec<PublicKey>,
+        self_public_key: PublicKey,
+        threshold: u16,
+    ) -> Result<KeyGenRound1>;
+
+    /// Verifies first round messages of all the participants and returns the encrypted shares to send to every other
+    /// participant
+    fn threshold_key_generation_shares(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        context: &H256,
+        round1: Vec<KeyGenRound1>,
+    ) -> Result<Vec<KeyGenShare>>;
+
+    /// Verifies the shares received from all the other participants, stores the threshold key and returns its
+    /// transfer address
+    fn finish_threshold_key_generation(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        context: &H256,
+        shares: Vec<KeyGenShare>,
+    ) -> Result<ExtendedAddr>;
+
+    /// Returns threshold key of given address
+    fn threshold_key(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        address: &ExtendedAddr,
+    ) -> Result<ThresholdKey>;
+
+    /// Creates a new threshold signing session bound to given message (transaction id) and returns session-id along
+    /// with the nonce commitment of current signer
+    fn new_threshold_session(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        address: &ExtendedAddr,
+        message: H256,
+    ) -> Result<(H256, SigningCommitment)>;
+
+    /// Adds a nonce commitment of a co-signer to session with given id
+    fn add_threshold_commitment(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        session_id: &H256,
+        commitment: SigningCommitment,
+    ) -> Result<()>;
+
+    /// Returns partial signature of current signer. This function will fail if nonce commitments from at least
+    /// `threshold` signers are not received.
+    fn threshold_partial_signature(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        session_id: &H256,
+    ) -> Result<SignatureShare>;
+
+    /// Adds a partial signature of a co-signer to session with given id. The partial signature is verified on
+    /// arrival.
+    fn add_threshold_partial_signature(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        session_id: &H256,
+        share: SignatureShare,
+    ) -> Result<()>;
+
+    /// Returns obfuscated transaction by signing all the inputs of given transaction (which must belong to the
+    /// threshold address of session) with the final signature of session
+    fn threshold_transaction(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        session_id: &H256,
+        unsigned_transaction: Tx,
+    ) -> Result<TxAux>;
+}
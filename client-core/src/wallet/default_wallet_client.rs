@@ -2,13 +2,16 @@ use crate::hd_wallet::{ChainPath, HardwareKind};
 use crate::service::*;
 use crate::transaction_builder::UnauthorizedWalletTransactionBuilder;
 use crate::transaction_builder::{SignedTransferTransaction, UnsignedTransferTransaction};
+use crate::types::label::{normalize_reference, output_reference};
 use crate::types::{
-    AddressType, BalanceChange, TransactionChange, TransactionPending, WalletBalance, WalletKind,
+    AddressType, BalanceChange, Contact, Label, LabelType, LabeledTransactionChange,
+    StakingHistoryEntry, TransactionChange, TransactionPending, WalletBalance, WalletKind,
 };
 use crate::wallet::syncer::{get_genesis_sync_state, AddressRecovery};
 use crate::wallet::syncer_logic::create_transaction_change;
 #[cfg(feature = "experimental")]
 use crate::MultiSigWalletClient;
+use crate::ThresholdWalletClient;
 use crate::{
     InputSelectionStrategy, Mnemonic, UnspentTransactions, WalletClient, WalletTransactionBuilder,
 };
@@ -22,18 +25,19 @@ use chain_core::tx::data::address::ExtendedAddr;
 use chain_core::tx::data::attribute::TxAttributes;
 use chain_core::tx::data::input::{str2txid, TxoPointer};
 use chain_core::tx::data::output::TxOut;
-#[cfg(feature = "experimental")]
 use chain_core::tx::data::Tx;
 use chain_core::tx::data::TxId;
 use chain_core::tx::fee::Fee;
 use chain_core::tx::witness::tree::RawXOnlyPubkey;
-#[cfg(feature = "experimental")]
 use chain_core::tx::witness::{TxInWitness, TxWitness};
+use chain_core::tx::TransactionId;
 use chain_core::tx::{TxAux, TxEnclaveAux, TxObfuscated};
 use client_common::tendermint::types::Time;
 use client_common::tendermint::types::{AbciQueryExt, BlockResults, BroadcastTxResponse};
 use client_common::tendermint::{Client, UnauthorizedClient};
-#[cfg(feature = "experimental")]
+use client_common::threshold::{
+    KeyGenRound1, KeyGenShare, SignatureShare, SigningCommitment, ThresholdKey, ThresholdParameters,
+};
 use client_common::SignedTransaction;
 use client_common::{
     seckey::derive_enckey, Error, ErrorKind, MultiSigAddress, PrivateKey, PrivateKeyAction,
@@ -65,6 +69,10 @@ where
     wallet_state_service: WalletStateService<S>,
     sync_state_service: SyncStateService<S>,
     root_hash_service: RootHashService<S>,
+    backup_service: BackupService<S>,
+    threshold_key_service: ThresholdKeyService<S>,
+    label_service: LabelService<S>,
+    staking_history_service: StakingHistoryService<S>,
     #[cfg(feature = "experimental")]
     multi_sig_session_service: MultiSigSessionService<S>,
 
@@ -98,6 +106,10 @@ where
             #[cfg(feature = "experimental")]
             multi_sig_session_service: MultiSigSessionService::new(storage.clone()),
             root_hash_service: RootHashService::new(storage.clone()),
+            backup_service: BackupService::new(storage.clone()),
+            threshold_key_service: ThresholdKeyService::new(storage.clone()),
+            label_service: LabelService::new(storage.clone()),
+            staking_history_service: StakingHistoryService::new(storage.clone()),
             tendermint_client,
             transaction_builder,
             block_height_ensure,
@@ -119,6 +131,70 @@ where
             });
         Ok(tx_change.is_ok())
     }
+
+    /// Returns unspent transactions which may be selected as inputs (neither pending nor frozen), ordered by given
+    /// input selection strategy
+    fn spendable_transactions(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        input_selection_strategy: &InputSelectionStrategy,
+    ) -> Result<UnspentTransactions> {
+        // Check if wallet exists
+        self.wallet_service.view_key(name, enckey)?;
+
+        let mut spendable_transactions = self
+            .wallet_state_service
+            .get_spendable_transactions(name, enckey)?;
+
+        if let InputSelectionStrategy::Manual(inputs) = input_selection_strategy {
+            if inputs.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "No input is given for manual input selection",
+                ));
+            }
+            let selected = inputs
+                .iter()
+                .map(|input| {
+                    spendable_transactions
+                        .remove(input)
+                        .map(|output| (input.clone(), output))
+                        .chain(|| {
+                            (
+                                ErrorKind::InvalidInput,
+                                format!(
+                                    "Input {} is not spendable (spent, pending or frozen)",
+                                    output_reference(input)
+                                ),
+                            )
+                        })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            return Ok(UnspentTransactions::new(selected));
+        }
+
+        let mut unspent_transactions =
+            UnspentTransactions::new(spendable_transactions.into_iter().collect());
+        unspent_transactions.apply_all(input_selection_strategy.as_ref());
+        Ok(unspent_transactions)
+    }
+
+    /// Returns private key of current signer in given multi-sig session (derived from its wallet on every step, so
+    /// that it is never stored in the session)
+    #[cfg(feature = "experimental")]
+    fn multi_sig_private_key(&self, session_id: &H256, enckey: &SecKey) -> Result<PrivateKey> {
+        let (name, public_key) = self.multi_sig_session_service.signer(session_id, enckey)?;
+        self.private_key(&name, enckey, &public_key)?.chain(|| {
+            (
+                ErrorKind::InvalidInput,
+                format!(
+                    "Self public key ({}) is not owned by wallet {}",
+                    public_key, name
+                ),
+            )
+        })
+    }
 }
 
 impl<S> DefaultWalletClient<S, UnauthorizedClient, UnauthorizedWalletTransactionBuilder>
@@ -169,7 +245,9 @@ where
         let mut found = false;
         let count = 20;
         for i in index..(index + count) {
-            let publickey = self.hd_key_service.peek_pubkey(name, enckey, i)?;
+            let publickey =
+                self.hd_key_service
+                    .peek_pubkey(name, enckey, HDAccountType::Transfer, i)?;
             let (h256, _multisigaddr) = RootHashService::<S>::peek_new_root_hash(
                 vec![publickey.clone()],
                 publickey.clone(),
@@ -216,6 +294,10 @@ where
     fn update_hw_service(&mut self, hw_wallet_kind: HardwareKind) -> Result<()> {
         let hw_key_service = match hw_wallet_kind {
             HardwareKind::Trezor => HwKeyService::Unauthorized(UnauthorizedHwKeyService),
+            HardwareKind::Remote => {
+                let remote_signer = RemoteSignerService::new()?;
+                HwKeyService::RemoteSigner(remote_signer)
+            }
             #[cfg(feature = "mock-hardware-wallet")]
             HardwareKind::Mock => {
                 let mock = MockHardwareService::new();
@@ -243,6 +325,7 @@ where
         address: ExtendedAddr,
         view_keys: &mut BTreeSet<PublicKey>,
         network_id: u8,
+        input_selection_strategy: Option<InputSelectionStrategy>,
     ) -> Result<TxId> {
         let current_block_height = self.get_current_block_height()?;
         let tx_out = TxOut::new(address, amount);
@@ -263,8 +346,14 @@ where
             TxAttributes::new_with_access(network_id, access_policies.into_iter().collect());
 
         let return_address = self.new_transfer_address(name, enckey)?;
-        let (transaction, selected_inputs, return_amount) =
-            self.create_transaction(name, enckey, vec![tx_out], attributes, None, return_address)?;
+        let (transaction, selected_inputs, return_amount) = self.create_transaction(
+            name,
+            enckey,
+            vec![tx_out],
+            attributes,
+            input_selection_strategy,
+            return_address,
+        )?;
 
         self.broadcast_transaction(&transaction)?;
         //update the wallet state
@@ -300,7 +389,8 @@ where
         view_keys: &mut BTreeSet<PublicKey>,
         network_id: u8,
     ) -> Result<TxId> {
-        let tx_id = self.send_to_address(name, enckey, amount, address, view_keys, network_id)?;
+        let tx_id =
+            self.send_to_address(name, enckey, amount, address, view_keys, network_id, None)?;
         let block_height = self.get_current_block_height()?;
         loop {
             // query tx_id from tendermint
@@ -326,7 +416,13 @@ where
 
     #[inline]
     fn wallets(&self) -> Result<Vec<String>> {
-        self.wallet_service.names()
+        let mut wallets = Vec::new();
+        for name in self.wallet_service.names()? {
+            if self.hd_key_service.account_of(&name)?.is_none() {
+                wallets.push(name);
+            }
+        }
+        Ok(wallets)
     }
 
     fn export_wallet(&self, name: &str, enckey: &SecKey) -> Result<WalletInfo> {
@@ -408,6 +504,7 @@ where
                 format!("wallet {} already exist", name),
             ));
         }
+        check_wallet_name(name)?;
         check_passphrase_strength(name, passphrase)?;
         let enckey = derive_enckey(passphrase, name).err_kind(ErrorKind::InvalidInput, || {
             "unable to derive encryption key from passphrase"
@@ -486,6 +583,7 @@ where
         wallet_kind: WalletKind,
         mnemonics_word_count: Option<u32>,
     ) -> Result<(SecKey, Option<Mnemonic>)> {
+        check_wallet_name(name)?;
         check_passphrase_strength(name, passphrase)?;
 
         let enckey = derive_enckey(passphrase, name).err_kind(ErrorKind::InvalidInput, || {
@@ -536,6 +634,10 @@ where
 
                 Ok((enckey, None))
             }
+            WalletKind::Xpub => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Xpub wallets can only be restored from extended public keys",
+            )),
         }
     }
 
@@ -545,6 +647,7 @@ where
         passphrase: &SecUtf8,
         mnemonic: &Mnemonic,
     ) -> Result<SecKey> {
+        check_wallet_name(name)?;
         check_passphrase_strength(name, passphrase)?;
 
         let enckey = derive_enckey(passphrase, name).err_kind(ErrorKind::InvalidInput, || {
@@ -572,6 +675,7 @@ where
         passphrase: &SecUtf8,
         view_key_priv: &PrivateKey,
     ) -> Result<SecKey> {
+        check_wallet_name(name)?;
         check_passphrase_strength(name, passphrase)?;
 
         let enckey = derive_enckey(passphrase, name).err_kind(ErrorKind::InvalidInput, || {
@@ -586,6 +690,77 @@ where
         Ok(enckey)
     }
 
+    fn restore_xpub_wallet(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        xpubs: &AccountXpubs,
+        view_key_priv: &PrivateKey,
+    ) -> Result<SecKey> {
+        check_wallet_name(name)?;
+        check_passphrase_strength(name, passphrase)?;
+
+        let enckey = derive_enckey(passphrase, name).err_kind(ErrorKind::InvalidInput, || {
+            "unable to derive encryption key from passphrase"
+        })?;
+
+        self.hd_key_service.add_xpubs(name, &enckey, xpubs)?;
+
+        let view_key = PublicKey::from(view_key_priv);
+        self.key_service
+            .add_wallet_private_key(name, view_key_priv, &enckey)?;
+        self.wallet_service
+            .create(name, &enckey, view_key, WalletKind::Xpub)?;
+        Ok(enckey)
+    }
+
+    fn export_xpubs(&self, name: &str, enckey: &SecKey) -> Result<AccountXpubs> {
+        let wallet = self.wallet_service.get_wallet_info(name, enckey)?;
+        match wallet.wallet_kind {
+            WalletKind::HD | WalletKind::Xpub => self.hd_key_service.export_xpubs(name, enckey),
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Extended public keys can only be exported from HD and xpub wallets",
+            )),
+        }
+    }
+
+    fn backup_wallet(&self, name: &str, enckey: &SecKey) -> Result<Vec<u8>> {
+        if self.hd_key_service.account_of(name)?.is_some() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Backups can only be created for the first account of a wallet",
+            ));
+        }
+        // the enckey is verified here.
+        let wallet_names = self
+            .accounts(name, enckey)?
+            .into_iter()
+            .map(|account| account_wallet_name(name, account))
+            .collect::<Vec<_>>();
+        self.backup_service.backup(name, &wallet_names, enckey)
+    }
+
+    fn restore_wallet_backup(
+        &self,
+        name: &str,
+        passphrase: &SecUtf8,
+        backup: &[u8],
+    ) -> Result<SecKey> {
+        let header = self.backup_service.header(backup)?;
+        if header.name != name {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Backup is of wallet {}, not {}", header.name, name),
+            ));
+        }
+        let enckey = derive_enckey(passphrase, name).err_kind(ErrorKind::InvalidInput, || {
+            "unable to derive encryption key from passphrase"
+        })?;
+        self.backup_service.restore(backup, &enckey)?;
+        Ok(enckey)
+    }
+
     fn delete_wallet(&self, name: &str, passphrase: &SecUtf8) -> Result<()> {
         // remove from wallet/sync_state/wallet_state/key_service
 
@@ -594,14 +769,21 @@ where
         })?;
 
         // the passphrase is verified here.
-        self.wallet_service.delete(name, &enckey)?;
-        self.sync_state_service.delete_global_state(name)?;
-        self.wallet_state_service
-            .delete_wallet_state(name, &enckey)?;
-        if self.hd_key_service.has_wallet(name)? {
-            self.hd_key_service.delete_wallet(name, &enckey)?;
+        let accounts = self.accounts(name, &enckey)?;
+        for account in accounts.into_iter().rev() {
+            let name = account_wallet_name(name, account);
+            self.wallet_service.delete(&name, &enckey)?;
+            self.sync_state_service.delete_global_state(&name)?;
+            self.wallet_state_service
+                .delete_wallet_state(&name, &enckey)?;
+            if self.hd_key_service.has_wallet(&name)? {
+                self.hd_key_service.delete_wallet(&name, &enckey)?;
+            }
+            self.key_service.delete_wallet_private_key(&name, &enckey)?;
+            self.hd_key_service.delete_account(&name)?;
+            self.label_service.delete_labels(&name)?;
+            self.staking_history_service.delete_history(&name)?;
         }
-        self.key_service.delete_wallet_private_key(name, &enckey)?;
 
         Ok(())
     }
@@ -613,9 +795,72 @@ where
 
         // test validity of enckey
         self.view_key(name, &enckey)?;
+        // upgrades the encrypted data recorded as outdated
+        MigrationService::new(self.storage.clone()).migrate_wallet(name, &enckey, false)?;
         Ok(enckey)
     }
 
+    fn new_account(&self, name: &str, enckey: &SecKey) -> Result<u32> {
+        if self.hd_key_service.account_of(name)?.is_some() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Accounts can only be created in the first account of a wallet",
+            ));
+        }
+        let wallet = self.wallet_service.get_wallet_info(name, enckey)?;
+        let mut account = self
+            .accounts(name, enckey)?
+            .last()
+            .map_or(1, |last| last + 1);
+        // skip the numbers whose storage names are taken by wallets created before multi-account support
+        let wallet_names = self.wallet_service.names()?;
+        while wallet_names.contains(&account_wallet_name(name, account)) {
+            account += 1;
+        }
+        let account_name = account_wallet_name(name, account);
+
+        let view_key = match wallet.wallet_kind {
+            WalletKind::Basic | WalletKind::Xpub => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Accounts are only supported in HD and hardware wallets",
+                ))
+            }
+            WalletKind::HD => {
+                self.hd_key_service.add_account(name, enckey, account)?;
+                let (public_key, private_key) = self.hd_key_service.generate_keypair(
+                    &account_name,
+                    enckey,
+                    HDAccountType::Viewkey,
+                )?;
+                self.key_service
+                    .add_wallet_private_key(&account_name, &private_key, enckey)?;
+                public_key
+            }
+            WalletKind::HW => {
+                // the view-key pair is the local key pair, not come from the hardware wallet.
+                self.hd_key_service.add_account(name, enckey, account)?;
+                let private_key = PrivateKey::new()?;
+                self.key_service
+                    .add_wallet_private_key(&account_name, &private_key, enckey)?;
+                PublicKey::from(&private_key)
+            }
+        };
+
+        self.wallet_service
+            .create(&account_name, enckey, view_key, wallet.wallet_kind)?;
+        Ok(account)
+    }
+
+    fn accounts(&self, name: &str, enckey: &SecKey) -> Result<Vec<u32>> {
+        // Check if wallet exists
+        self.wallet_service.view_key(name, enckey)?;
+
+        let mut accounts = vec![0];
+        accounts.extend(self.hd_key_service.accounts(name)?);
+        Ok(accounts)
+    }
+
     #[inline]
     fn view_key(&self, name: &str, enckey: &SecKey) -> Result<PublicKey> {
         self.wallet_service.view_key(name, enckey)
@@ -808,6 +1053,46 @@ where
                     .add_key_path(name, enckey, &public_key, &hd_path)?;
                 Ok(public_key)
             }
+            WalletKind::Xpub => {
+                let account_type = address_type
+                    .chain(|| {
+                        (
+                            ErrorKind::InvalidInput,
+                            "Address type is needed when creating address for xpub wallet",
+                        )
+                    })?
+                    .into();
+                let public_key =
+                    self.hd_key_service
+                        .generate_xpub_pubkey(name, enckey, account_type)?;
+                self.wallet_service
+                    .add_public_key(name, enckey, &public_key)?;
+                Ok(public_key)
+            }
+        }
+    }
+
+    fn peek_public_key(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        address_type: AddressType,
+        index: u32,
+    ) -> Result<PublicKey> {
+        let wallet = self.wallet_service.get_wallet_info(name, enckey)?;
+        match wallet.wallet_kind {
+            WalletKind::HD => {
+                self.hd_key_service
+                    .peek_pubkey(name, enckey, address_type.into(), index)
+            }
+            WalletKind::Xpub => {
+                self.hd_key_service
+                    .peek_xpub_pubkey(name, enckey, address_type.into(), index)
+            }
+            _ => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Public keys can only be derived in HD and xpub wallets",
+            )),
         }
     }
 
@@ -846,6 +1131,10 @@ where
                     .add_key_path(name, enckey, &public_key, &hd_path)?;
                 public_key
             }
+            WalletKind::Xpub => {
+                self.hd_key_service
+                    .generate_xpub_pubkey(name, enckey, HDAccountType::Staking)?
+            }
         };
 
         self.wallet_service
@@ -885,6 +1174,10 @@ where
                     .add_key_path(name, enckey, &public_key, &hd_path)?;
                 public_key
             }
+            WalletKind::Xpub => {
+                self.hd_key_service
+                    .generate_xpub_pubkey(name, enckey, HDAccountType::Transfer)?
+            }
         };
         self.wallet_service
             .add_public_key(name, enckey, &public_key)?;
@@ -967,13 +1260,23 @@ where
 
     fn get_multisig_addresses(&self, name: &str, enckey: &SecKey) -> Result<Vec<MultiSigAddress>> {
         let root_hashes = self.wallet_service.root_hashes(name, enckey, 0, 0, false)?;
-        root_hashes
-            .iter()
-            .map(|hash| {
-                self.root_hash_service
-                    .get_multi_sig_address_from_root_hash(name, hash, enckey)
-            })
-            .collect::<Result<Vec<MultiSigAddress>>>()
+        let mut multi_sig_addresses = Vec::with_capacity(root_hashes.len());
+
+        for hash in root_hashes.iter() {
+            // Threshold keys are not combinations of public keys
+            if self
+                .threshold_key_service
+                .threshold_key(name, hash, enckey)?
+                .is_none()
+            {
+                multi_sig_addresses.push(
+                    self.root_hash_service
+                        .get_multi_sig_address_from_root_hash(name, hash, enckey)?,
+                );
+            }
+        }
+
+        Ok(multi_sig_addresses)
     }
 
     fn generate_proof(
@@ -988,8 +1291,17 @@ where
 
         match address {
             ExtendedAddr::OrTree(ref address) => {
-                self.root_hash_service
-                    .generate_proof(name, address, public_keys, enckey)
+                match self
+                    .threshold_key_service
+                    .threshold_key(name, address, enckey)?
+                {
+                    // Aggregated public key of a threshold key is the only leaf of its tree
+                    Some(threshold_key) => threshold_key.generate_proof(),
+                    None => {
+                        self.root_hash_service
+                            .generate_proof(name, address, public_keys, enckey)
+                    }
+                }
             }
         }
     }
@@ -998,8 +1310,15 @@ where
         // To verify if the enckey is correct or not
         self.wallet_service.view_key(name, enckey)?;
 
-        self.root_hash_service
-            .required_signers(name, root_hash, enckey)
+        match self
+            .threshold_key_service
+            .threshold_key(name, root_hash, enckey)?
+        {
+            Some(threshold_key) => Ok(threshold_key.parameters.threshold as usize),
+            None => self
+                .root_hash_service
+                .required_signers(name, root_hash, enckey),
+        }
     }
 
     #[inline]
@@ -1016,16 +1335,18 @@ where
         offset: usize,
         limit: usize,
         reversed: bool,
-    ) -> Result<Vec<TransactionChange>> {
+    ) -> Result<Vec<LabeledTransactionChange>> {
         // Check if wallet exists
         self.wallet_service.view_key(name, enckey)?;
 
+        let labels = self.label_service.label_map(name, enckey)?;
         let history = self
             .wallet_state_service
             .get_transaction_history(name, enckey, reversed)?
             .filter(|change| BalanceChange::NoChange != change.balance_change)
             .skip(offset)
             .take(limit)
+            .map(|change| LabeledTransactionChange::new(change, &labels))
             .collect::<Vec<_>>();
 
         Ok(history)
@@ -1042,6 +1363,62 @@ where
             .get_transaction_change(name, enckey, transaction_id)
     }
 
+    fn set_labels(&self, name: &str, enckey: &SecKey, labels: Vec<Label>) -> Result<()> {
+        // Check if wallet exists
+        self.wallet_service.view_key(name, enckey)?;
+
+        let labels = labels
+            .into_iter()
+            .map(Label::validated)
+            .collect::<Result<Vec<_>>>()?;
+        self.label_service.set_labels(name, enckey, labels)
+    }
+
+    fn remove_label(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        label_type: LabelType,
+        reference: &str,
+    ) -> Result<bool> {
+        // Check if wallet exists
+        self.wallet_service.view_key(name, enckey)?;
+
+        let reference = normalize_reference(label_type, reference)?;
+        self.label_service
+            .remove_label(name, enckey, label_type, &reference)
+    }
+
+    fn labels(&self, name: &str, enckey: &SecKey) -> Result<Vec<Label>> {
+        // Check if wallet exists
+        self.wallet_service.view_key(name, enckey)?;
+
+        self.label_service.labels(name, enckey)
+    }
+
+    fn contacts(&self, name: &str, enckey: &SecKey) -> Result<Vec<Contact>> {
+        // Check if wallet exists
+        self.wallet_service.view_key(name, enckey)?;
+
+        self.label_service.contacts(name, enckey)
+    }
+
+    fn staking_history(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        staking_address: Option<&StakedStateAddress>,
+        offset: usize,
+        limit: usize,
+        reversed: bool,
+    ) -> Result<Vec<StakingHistoryEntry>> {
+        // Check if wallet exists
+        self.wallet_service.view_key(name, enckey)?;
+
+        self.staking_history_service
+            .entries(name, enckey, staking_address, offset, limit, reversed)
+    }
+
     fn unspent_transactions(&self, name: &str, enckey: &SecKey) -> Result<UnspentTransactions> {
         // Check if wallet exists
         self.wallet_service.view_key(name, enckey)?;
@@ -1055,7 +1432,32 @@ where
         ))
     }
 
-    fn has_unspent_transactions(
+    fn set_frozen_outputs(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        inputs: &[TxoPointer],
+        frozen: bool,
+    ) -> Result<()> {
+        // Check if wallet exists
+        self.wallet_service.view_key(name, enckey)?;
+
+        self.wallet_state_service
+            .set_frozen(name, enckey, inputs, frozen)
+    }
+
+    fn frozen_outputs(&self, name: &str, enckey: &SecKey) -> Result<Vec<TxoPointer>> {
+        // Check if wallet exists
+        self.wallet_service.view_key(name, enckey)?;
+
+        Ok(self
+            .wallet_state_service
+            .get_frozen_outputs(name, enckey)?
+            .into_iter()
+            .collect())
+    }
+
+    fn has_spendable_transactions(
         &self,
         name: &str,
         enckey: &SecKey,
@@ -1065,7 +1467,7 @@ where
         self.wallet_service.view_key(name, enckey)?;
 
         self.wallet_state_service
-            .has_unspent_transactions(name, enckey, inputs)
+            .has_spendable_transactions(name, enckey, inputs)
     }
 
     #[inline]
@@ -1105,8 +1507,11 @@ where
         input_selection_strategy: Option<InputSelectionStrategy>,
         return_address: ExtendedAddr,
     ) -> Result<(TxAux, Vec<TxoPointer>, Coin)> {
-        let mut unspent_transactions = self.unspent_transactions(name, enckey)?;
-        unspent_transactions.apply_all(input_selection_strategy.unwrap_or_default().as_ref());
+        let unspent_transactions = self.spendable_transactions(
+            name,
+            enckey,
+            &input_selection_strategy.unwrap_or_default(),
+        )?;
 
         self.transaction_builder.build_transfer_tx(
             name,
@@ -1241,7 +1646,8 @@ where
         view_keys: Vec<PublicKey>,
         network_id: u8,
     ) -> Result<UnsignedTransferTransaction> {
-        let unspent_transactions = self.unspent_transactions(name, enckey)?;
+        let unspent_transactions =
+            self.spendable_transactions(name, enckey, &InputSelectionStrategy::default())?;
         let return_address = self.new_transfer_address(name, enckey)?;
         let unsigned = UnsignedTransferTransaction {
             unspent_transactions,
@@ -1388,32 +1794,30 @@ where
     ) -> Result<H256> {
         // To verify if the enckey is correct or not
         self.transfer_addresses(name, enckey)?;
-        //        let sign_key = self.sign_key(name, enckey, &self_public_key)?;
 
-        let self_private_key = self
-            .private_key(name, enckey, &self_public_key)?
-            .chain(|| {
-                (
-                    ErrorKind::InvalidInput,
-                    format!(
-                        "Self public key ({}) is not owned by current wallet",
-                        self_public_key
-                    ),
-                )
-            })?;
+        if self.private_key(name, enckey, &self_public_key)?.is_none() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Self public key ({}) is not owned by current wallet",
+                    self_public_key
+                ),
+            ));
+        }
 
         self.multi_sig_session_service.new_session(
             message,
             signer_public_keys,
             self_public_key,
-            self_private_key,
+            name,
             enckey,
         )
     }
 
     fn nonce_commitment(&self, session_id: &H256, enckey: &SecKey) -> Result<H256> {
+        let private_key = self.multi_sig_private_key(session_id, enckey)?;
         self.multi_sig_session_service
-            .nonce_commitment(session_id, enckey)
+            .nonce_commitment(session_id, &private_key, enckey)
     }
 
     fn add_nonce_commitment(
@@ -1432,7 +1836,9 @@ where
     }
 
     fn nonce(&self, session_id: &H256, enckey: &SecKey) -> Result<H256> {
-        self.multi_sig_session_service.nonce(session_id, enckey)
+        let private_key = self.multi_sig_private_key(session_id, enckey)?;
+        self.multi_sig_session_service
+            .nonce(session_id, &private_key, enckey)
     }
 
     fn add_nonce(
@@ -1447,8 +1853,9 @@ where
     }
 
     fn partial_signature(&self, session_id: &H256, enckey: &SecKey) -> Result<H256> {
+        let private_key = self.multi_sig_private_key(session_id, enckey)?;
         self.multi_sig_session_service
-            .partial_signature(session_id, enckey)
+            .partial_signature(session_id, &private_key, enckey)
     }
 
     fn add_partial_signature(
@@ -1484,6 +1891,14 @@ where
             ));
         }
 
+        if self.multi_sig_session_service.message(session_id, enckey)? != unsigned_transaction.id()
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Transaction id does not match the message signed in multi-sig session",
+            ));
+        }
+
         let output_to_spend = self.output(name, enckey, &unsigned_transaction.inputs[0])?;
         let root_hash = self
             .wallet_service
@@ -1511,6 +1926,204 @@ where
     }
 }
 
+impl<S, C, T> ThresholdWalletClient for DefaultWalletClient<S, C, T>
+where
+    S: Storage + 'static,
+    C: Client,
+    T: WalletTransactionBuilder,
+{
+    fn new_threshold_key_generation(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        context: H256,
+        public_keys: Vec<PublicKey>,
+        self_public_key: PublicKey,
+        threshold: u16,
+    ) -> Result<KeyGenRound1> {
+        let identity_key = self
+            .private_key(name, enckey, &self_public_key)?
+            .chain(|| {
+                (
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "Self public key ({}) is not owned by current wallet",
+                        self_public_key
+                    ),
+                )
+            })?;
+        let parameters = ThresholdParameters::new(threshold, public_keys)?;
+
+        self.threshold_key_service
+            .start_key_generation(name, context, parameters, identity_key)
+    }
+
+    fn threshold_key_generation_shares(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        context: &H256,
+        round1: Vec<KeyGenRound1>,
+    ) -> Result<Vec<KeyGenShare>> {
+        // To verify if the enckey is correct or not
+        self.wallet_service.view_key(name, enckey)?;
+
+        self.threshold_key_service
+            .key_generation_shares(name, context, round1)
+    }
+
+    fn finish_threshold_key_generation(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        context: &H256,
+        shares: Vec<KeyGenShare>,
+    ) -> Result<ExtendedAddr> {
+        let threshold_key = self
+            .threshold_key_service
+            .finish_key_generation(name, context, &shares, enckey)?;
+
+        self.wallet_service
+            .add_root_hash(name, enckey, threshold_key.root_hash())?;
+
+        self.storage
+            .flush()
+            .chain(|| (ErrorKind::IoError, "Unable to flush sled"))?;
+
+        Ok(threshold_key.to_extended_addr())
+    }
+
+    fn threshold_key(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        address: &ExtendedAddr,
+    ) -> Result<ThresholdKey> {
+        // To verify if the enckey is correct or not
+        self.wallet_service.view_key(name, enckey)?;
+
+        let ExtendedAddr::OrTree(ref root_hash) = address;
+        self.threshold_key_service
+            .threshold_key(name, root_hash, enckey)?
+            .chain(|| {
+                (
+                    ErrorKind::InvalidInput,
+                    format!("Address ({}) is not a threshold address", address),
+                )
+            })
+    }
+
+    fn new_threshold_session(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        address: &ExtendedAddr,
+        message: H256,
+    ) -> Result<(H256, SigningCommitment)> {
+        let threshold_key = self.threshold_key(name, enckey, address)?;
+
+        self.threshold_key_service
+            .new_session(name, &threshold_key.root_hash(), message, enckey)
+    }
+
+    fn add_threshold_commitment(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        session_id: &H256,
+        commitment: SigningCommitment,
+    ) -> Result<()> {
+        self.threshold_key_service
+            .add_commitment(name, session_id, commitment, enckey)
+    }
+
+    fn threshold_partial_signature(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        session_id: &H256,
+    ) -> Result<SignatureShare> {
+        self.threshold_key_service
+            .partial_signature(name, session_id, enckey)
+    }
+
+    fn add_threshold_partial_signature(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        session_id: &H256,
+        share: SignatureShare,
+    ) -> Result<()> {
+        self.threshold_key_service
+            .add_partial_signature(name, session_id, share, enckey)
+    }
+
+    fn threshold_transaction(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        session_id: &H256,
+        unsigned_transaction: Tx,
+    ) -> Result<TxAux> {
+        let (root_hash, message) = self.threshold_key_service.session_info(name, session_id)?;
+
+        if message != unsigned_transaction.id() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Transaction ID does not match the message of threshold signing session",
+            ));
+        }
+
+        let address = ExtendedAddr::OrTree(root_hash);
+        for input in unsigned_transaction.inputs.iter() {
+            if self.output(name, enckey, input)?.address != address {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!(
+                        "Input ({}:{}) does not belong to threshold address of session",
+                        hex::encode(&input.id),
+                        input.index
+                    ),
+                ));
+            }
+        }
+
+        let signature = self
+            .threshold_key_service
+            .signature(name, session_id, enckey)?;
+        let proof = self
+            .threshold_key(name, enckey, &address)?
+            .generate_proof()?;
+
+        // Every input is spent from the same address and signed over the same transaction id
+        let witness = TxWitness::from(vec![
+            TxInWitness::TreeSig(signature, proof);
+            unsigned_transaction.inputs.len()
+        ]);
+        let signed_transaction =
+            SignedTransaction::TransferTransaction(unsigned_transaction, witness);
+
+        let tx_aux = self.transaction_builder.obfuscate(signed_transaction)?;
+        self.threshold_key_service
+            .remove_session(name, session_id)?;
+
+        Ok(tx_aux)
+    }
+}
+
+fn check_wallet_name(name: &str) -> Result<()> {
+    if name.contains(ACCOUNT_SEPARATOR) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Wallet name can not contain `{}` (reserved for accounts)",
+                ACCOUNT_SEPARATOR
+            ),
+        ));
+    }
+    Ok(())
+}
+
 fn check_passphrase_strength(name: &str, passphrase: &SecUtf8) -> Result<()> {
     // `estimate_password_strength` returns a score between `0-4`. Any score less than 3 should be considered too
     // weak.
@@ -30,6 +30,7 @@ use tendermint_light_client::{
 };
 
 use chain_core::common::H256;
+use chain_core::compute_filter_header;
 use chain_core::state::account::StakedStateAddress;
 use chain_core::state::ChainState;
 use chain_core::tx::data::address::ExtendedAddr;
@@ -39,7 +40,7 @@ use chain_storage::jellyfish::compute_staking_root;
 use chain_tx_filter::BlockFilter;
 use chain_util::NonEmpty;
 use client_common::tendermint::types::{
-    Block, BlockExt, BlockResults, BlockResultsResponse, Genesis, Time,
+    Block, BlockExt, BlockResults, BlockResultsResponse, Genesis, StakingChange, Time,
 };
 use client_common::tendermint::Client;
 use client_common::{
@@ -49,7 +50,9 @@ use client_common::{
 
 use super::syncer_logic::handle_blocks;
 use crate::service;
-use crate::service::{KeyService, SyncState, Wallet, WalletState, WalletStateMemento};
+use crate::service::{
+    KeyService, StakingHistory, SyncState, Wallet, WalletState, WalletStateMemento,
+};
 use std::sync::Mutex;
 type BlockConfirmFunc = Arc<Mutex<Box<dyn Fn(u64, String) -> bool>>>; // height, blockhash
 
@@ -133,6 +136,8 @@ pub struct ObfuscationSyncerConfig<
     pub client: C,
     pub obfuscation: O,
     pub light_client: Option<L>,
+    /// additional nodes to fetch block filters from if `client` serves invalid ones
+    pub filter_peers: Vec<C>,
 
     // configs
     pub options: SyncerOptions,
@@ -155,8 +160,15 @@ impl<S: SecureStorage, C: Client, O: TransactionObfuscation, L: LightClientHandl
             obfuscation,
             options,
             light_client,
+            filter_peers: vec![],
         }
     }
+
+    /// Set additional nodes to fetch block filters from
+    pub fn with_filter_peers(mut self, filter_peers: Vec<C>) -> Self {
+        self.filter_peers = filter_peers;
+        self
+    }
 }
 
 /// Common configs for wallet syncer
@@ -166,6 +178,7 @@ pub struct SyncerConfig<S: SecureStorage, C: Client, L: LightClientHandle> {
     storage: S,
     client: C,
     light_client: Option<L>,
+    filter_peers: Vec<C>,
 
     // configs
     options: SyncerOptions,
@@ -183,6 +196,7 @@ pub struct WalletSyncer<
     // common
     storage: S,
     client: C,
+    filter_peers: Vec<C>,
     recover_address: T,
     options: SyncerOptions,
 
@@ -212,6 +226,7 @@ where
         Self {
             storage: config.storage,
             client: config.client,
+            filter_peers: config.filter_peers,
             decryptor,
             name,
             enckey,
@@ -221,10 +236,11 @@ where
         }
     }
 
-    /// Delete sync state and wallet state.
+    /// Delete sync state, wallet state and staking history.
     pub fn reset_state(&self) -> Result<()> {
         service::delete_sync_state(&self.storage, &self.name)?;
         service::delete_wallet_state(&self.storage, &self.name)?;
+        service::delete_staking_history(&self.storage, &self.name)?;
         Ok(())
     }
 
@@ -268,6 +284,7 @@ where
                 client: config.client,
                 options: config.options,
                 light_client: config.light_client,
+                filter_peers: config.filter_peers,
             },
             decryptor,
             name,
@@ -294,6 +311,7 @@ struct WalletSyncerImpl<
     wallet: Wallet,
     sync_state: SyncState,
     wallet_state: WalletState,
+    staking_history: StakingHistory,
 }
 
 impl<
@@ -311,6 +329,12 @@ impl<
             .err_kind(ErrorKind::InvalidInput, || {
                 format!("wallet not found: {}", env.name)
             })?;
+        // upgrades the encrypted data recorded as outdated before it is loaded
+        service::MigrationService::new(env.storage.clone()).migrate_wallet(
+            &env.name,
+            &env.enckey,
+            false,
+        )?;
 
         let mstate = service::load_sync_state(&env.storage, &env.name)?;
         let sync_state = if let Some(sync_state) = mstate {
@@ -323,6 +347,8 @@ impl<
 
         let wallet_state =
             service::load_wallet_state(&env.storage, &env.name, &env.enckey)?.unwrap_or_default();
+        let staking_history = service::load_staking_history(&env.storage, &env.name, &env.enckey)?
+            .unwrap_or_default();
 
         Ok(Self {
             env,
@@ -330,6 +356,7 @@ impl<
             wallet,
             sync_state,
             wallet_state,
+            staking_history,
         })
     }
 
@@ -361,6 +388,36 @@ impl<
         Ok(())
     }
 
+    fn update_staking_history(&mut self, blocks: &[FilteredBlock]) -> Result<()> {
+        let last_block_height = self.staking_history.last_block_height;
+        let mut changed = false;
+        for block in blocks {
+            if block.block_height <= last_block_height {
+                continue;
+            }
+            // unbonded stakes may become withdrawable in blocks without staking changes
+            if block.staking_changes.is_empty() && self.staking_history.unbonding.is_empty() {
+                continue;
+            }
+            let entries = self.staking_history.entries.len();
+            self.staking_history.add_block(
+                &block.staking_changes,
+                block.block_height,
+                block.block_time,
+            );
+            changed = changed || self.staking_history.entries.len() != entries;
+        }
+        if changed {
+            service::save_staking_history(
+                &self.env.storage,
+                &self.env.name,
+                &self.env.enckey,
+                &self.staking_history,
+            )?;
+        }
+        Ok(())
+    }
+
     fn save(&mut self, memento: &WalletStateMemento) -> Result<()> {
         service::save_sync_state(&self.env.storage, &self.env.name, &self.sync_state)?;
         self.update_state(memento)?;
@@ -445,6 +502,8 @@ impl<
         self.sync_state.last_app_hash = block.app_hash.clone();
         self.sync_state.last_block_hash = block.block_hash.clone();
         self.sync_state.staking_root = block.staking_root;
+        self.sync_state.last_filter_header = block.filter_header;
+        self.update_staking_history(&blocks)?;
         self.save(&memento)?;
 
         if !self.update_progress(block.block_height) {
@@ -574,6 +633,33 @@ impl<
                 block_results.into_iter(),
                 states.into_iter()
             ) {
+                // verify block filter against the filter header chain
+                // (the filter header is committed in the app hash verified below)
+                let block_result = match self.sync_state.last_filter_header {
+                    Some(ref last_filter_header)
+                        if !verify_block_filter(last_filter_header, &block_result, &state)? =>
+                    {
+                        self.fetch_verified_block_results(
+                            block.header.height.value(),
+                            last_filter_header,
+                            &state,
+                        )?
+                    }
+                    Some(_) => block_result,
+                    None => {
+                        // sync state migrated from before the filter header chain (or the chain
+                        // doesn't have it yet), the chain is resumed from the header of this block
+                        if state.filter_header.is_some() {
+                            log::warn!(
+                                "no filter header in sync state, block filter at height {} is not verified",
+                                block.header.height.value()
+                            );
+                        }
+                        block_result
+                    }
+                };
+                self.sync_state.last_filter_header = state.filter_header;
+
                 let block = FilteredBlock::from_block(
                     &self.wallet,
                     &self.wallet_state,
@@ -641,6 +727,48 @@ impl<
         }
     }
 
+    /// Fetches block results from the filter peers if the block filter served by the node
+    /// doesn't match the filter header
+    fn fetch_verified_block_results(
+        &self,
+        block_height: u64,
+        last_filter_header: &H256,
+        state: &ChainState,
+    ) -> Result<BlockResultsResponse> {
+        log::warn!(
+            "block filter at height {} doesn't match the filter header, trying other peers",
+            block_height
+        );
+        for (i, peer) in self.env.filter_peers.iter().enumerate() {
+            let block_result = match peer.block_results(block_height) {
+                Ok(block_result) => block_result,
+                Err(e) => {
+                    log::warn!(
+                        "failed to fetch block results from filter peer {}: {}",
+                        i,
+                        e
+                    );
+                    continue;
+                }
+            };
+            if verify_block_filter(last_filter_header, &block_result, state)? {
+                return Ok(block_result);
+            }
+            log::warn!(
+                "filter peer {} served an invalid block filter at height {}",
+                i,
+                block_height
+            );
+        }
+        Err(Error::new(
+            ErrorKind::VerifyError,
+            format!(
+                "block filter at height {} doesn't match the filter header (the node may be lying)",
+                block_height
+            ),
+        ))
+    }
+
     fn rollback_pending_tx(&mut self, current_block_height: u64) -> Result<()> {
         let mut memento = WalletStateMemento::default();
         let state =
@@ -703,6 +831,25 @@ impl<
     }
 }
 
+/// checks the block filter in block results chains the previous filter header
+/// to the one in the chain state (blocks without a filter don't change it);
+/// block filters of chains without the filter header chain can't be verified
+fn verify_block_filter(
+    last_filter_header: &H256,
+    block_result: &BlockResultsResponse,
+    state: &ChainState,
+) -> Result<bool> {
+    let state_filter_header = match state.filter_header {
+        Some(state_filter_header) => state_filter_header,
+        None => return Ok(true),
+    };
+    let filter_header = match block_result.raw_gcs_filter()? {
+        Some(gcs) => compute_filter_header(last_filter_header, &gcs),
+        None => *last_filter_header,
+    };
+    Ok(filter_header == state_filter_header)
+}
+
 /// testnet v0.5
 const CRYPTO_GENESIS_FINGERPRINT: &str =
     "DC05002AAEAB58DA40701073A76A018C9AB02C87BD89ADCB6EE7FE5B419526C8";
@@ -807,14 +954,18 @@ pub(crate) struct FilteredBlock {
     pub block_time: Time,
     /// List of successfully committed transaction ids in this block and their fees
     pub valid_transaction_fees: IndexMap<TxId, Fee>,
-    /// Bloom filter for view keys and staking addresses
+    /// Block filter for view keys (Golomb-coded set, or bloom filter in older blocks)
     pub block_filter: BlockFilter,
     /// List of successfully committed transaction of transactions that may need to be queried against
     pub enclave_transaction_ids: Vec<TxId>,
     /// List of un-encrypted transactions (only contains transactions of type `DepositStake` and `UnbondStake`)
     pub staking_transactions: Vec<Transaction>,
+    /// Changes of staked states of wallet in this block (rewards, punishments, etc.)
+    pub staking_changes: Vec<StakingChange>,
     /// staking root after this block
     pub staking_root: H256,
+    /// block filter header after this block (if the chain has one)
+    pub filter_header: Option<H256>,
 }
 
 impl FilteredBlock {
@@ -867,6 +1018,12 @@ impl FilteredBlock {
             staking_transactions = filter_staking_transactions(&block_result, block, wallet_state)?;
         }
 
+        let staking_changes = block_result.staking_changes(&|staked_state_address| {
+            wallet
+                .staking_addresses_contains(staked_state_address)
+                .expect("staking_addresses_contains")
+        })?;
+
         let valid_transaction_fees = block_result.fees()?;
 
         let enclave_transaction_ids =
@@ -887,7 +1044,9 @@ impl FilteredBlock {
             enclave_transaction_ids,
             block_filter,
             staking_transactions,
+            staking_changes,
             staking_root: state.account_root,
+            filter_header: state.filter_header,
         })
     }
 }
@@ -1053,6 +1212,7 @@ mod tests {
     use chain_core::init::coin::Coin;
     use chain_core::tx::data::{address::ExtendedAddr, output::TxOut};
     use chain_core::tx::data::{Tx, TxId};
+    use chain_tx_filter::GcsFilter;
     use client_common::PublicKey;
     use std::str::FromStr;
 
@@ -1082,6 +1242,7 @@ mod tests {
                 storage,
                 client,
                 light_client,
+                filter_peers: vec![],
                 options: SyncerOptions {
                     enable_fast_forward,
                     disable_light_client: enable_fast_forward,
@@ -1111,6 +1272,76 @@ mod tests {
         check_wallet_syncer_impl(true);
     }
 
+    fn sync_with_filter_peers(
+        client: GeneratorClient,
+        filter_peers: Vec<GeneratorClient>,
+    ) -> Result<()> {
+        let storage = MemoryStorage::default();
+        let name = "name";
+        let passphrase = SecUtf8::from("passphrase");
+        let wallet = DefaultWalletClient::new_read_only(storage.clone());
+        let (enckey, _) = wallet
+            .new_wallet(name, &passphrase, WalletKind::Basic, None)
+            .unwrap();
+        let light_client = Some(client.clone());
+
+        let mut syncer = WalletSyncer::with_config(
+            SyncerConfig {
+                storage,
+                client,
+                light_client,
+                filter_peers,
+                options: SyncerOptions {
+                    enable_fast_forward: false,
+                    disable_light_client: false,
+                    enable_address_recovery: false,
+                    batch_size: 20,
+                    block_height_ensure: 50,
+                    light_client_peers: "".into(),
+                    light_client_trusting_period_seconds: 36000000,
+                    light_client_trusting_height: 1,
+                    light_client_trusting_blockhash: "".into(),
+                },
+            },
+            |_txids: &[TxId]| -> Result<Vec<Transaction>> { Ok(vec![]) },
+            name.to_owned(),
+            enckey,
+            wallet,
+        );
+        let genesis = syncer.client.genesis().unwrap();
+        let hash = compute_genesis_fingerprint(&genesis).unwrap();
+        std::env::set_var("CRYPTO_GENESIS_FINGERPRINT", hash);
+        syncer.sync(|_| true)
+    }
+
+    #[test]
+    fn check_wallet_syncer_block_filter_peers() {
+        let gen_chain = |lying: bool| {
+            let mut gen = BlockGenerator::one_node();
+            for i in 0..10 {
+                if i == 4 {
+                    let view_key = vec![2u8; 33];
+                    gen.gen_block_with_filter(&[], GcsFilter::build(&[view_key]).to_bytes());
+                } else {
+                    gen.gen_block(&[]);
+                }
+            }
+            if lying {
+                // the lying node omits the view key from the filter
+                gen.blocks[4].block_filter = Some(GcsFilter::default().to_bytes());
+            }
+            GeneratorClient::new(gen)
+        };
+
+        let err = sync_with_filter_peers(gen_chain(true), vec![])
+            .expect_err("invalid block filter should be detected");
+        assert_eq!(err.kind(), ErrorKind::VerifyError);
+
+        sync_with_filter_peers(gen_chain(true), vec![gen_chain(true), gen_chain(false)])
+            .expect("valid block filter should be fetched from the honest peer");
+        sync_with_filter_peers(gen_chain(false), vec![]).expect("Unable to synchronize");
+    }
+
     #[test]
     #[ignore]
     fn check_wallet_syncer_app_hash_on_multiple_tx() {
@@ -1189,6 +1420,7 @@ mod tests {
                     .to_string(),
                 staking_root: [0u8; 32],
                 trusted: true,
+                last_filter_header: None,
             },
         )
         .expect("should save sync state");
@@ -1209,6 +1441,7 @@ mod tests {
                 storage,
                 client,
                 light_client,
+                filter_peers: vec![],
                 options: SyncerOptions {
                     enable_fast_forward,
                     disable_light_client: enable_fast_forward,
@@ -1266,6 +1499,7 @@ mod tests {
                 storage,
                 client,
                 light_client,
+                filter_peers: vec![],
                 options: SyncerOptions {
                     enable_fast_forward: false,
                     disable_light_client: false,
@@ -1330,6 +1564,7 @@ mod tests {
                 storage,
                 client,
                 light_client,
+                filter_peers: vec![],
                 options: SyncerOptions {
                     enable_fast_forward: false,
                     disable_light_client: false,
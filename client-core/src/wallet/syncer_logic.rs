@@ -357,7 +357,9 @@ mod tests {
             enclave_transaction_ids: enclave_txs.iter().map(|tx| tx.id()).collect(),
             block_filter,
             staking_transactions: other_txs.to_vec(),
+            staking_changes: vec![],
             staking_root,
+            filter_header: None,
         }
     }
 
@@ -0,0 +1,321 @@
+//! # Address discovery
+//! BIP-44 style gap-limit scan of the address chains of a (restored) HD wallet.
+//!
+//! - a staking address is used if it has a staked state on the chain
+//! - a transfer address is used if it received outputs of transactions in the blocks
+//!   whose filters match the wallet's view key
+//!
+//! The scan of a chain stops after `gap_limit` consecutive unused addresses and the wallet then
+//! generates all the addresses up to the last used one, so that the following synchronization
+//! (from genesis) picks up their funds.
+use std::collections::HashSet;
+
+use chain_core::init::address::RedeemAddress;
+use chain_core::state::account::StakedStateAddress;
+use chain_core::tx::data::address::ExtendedAddr;
+use client_common::tendermint::types::{BlockExt, BlockResults};
+use client_common::tendermint::Client;
+use client_common::{MultiSigAddress, Result, SecKey, TransactionObfuscation};
+use client_core::types::AddressType;
+use client_core::WalletClient;
+
+use crate::NetworkOpsClient;
+
+/// Default number of consecutive unused addresses which ends the scan of an address chain
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+/// Default number of requests per batch in RPC calls to tendermint
+pub const DEFAULT_BATCH_SIZE: usize = 20;
+
+/// Progress of the address discovery
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiscoveryProgress {
+    /// Staking address at `index` was checked
+    StakingAddress {
+        /// index of the address in the staking chain
+        index: u32,
+        /// if the address has a staked state
+        used: bool,
+    },
+    /// Block filters up to `current_block_height` were scanned
+    BlockFilters {
+        /// last scanned block height
+        current_block_height: u64,
+        /// latest block height
+        finish_block_height: u64,
+        /// number of blocks with filters matching the view key so far
+        matched_blocks: usize,
+    },
+    /// Transfer address at `index` was checked
+    TransferAddress {
+        /// index of the address in the transfer chain
+        index: u32,
+        /// if the address received any output
+        used: bool,
+    },
+}
+
+/// Result of the address discovery
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DiscoveryResult {
+    /// used staking addresses
+    pub staking_addresses: Vec<StakedStateAddress>,
+    /// used transfer addresses
+    pub transfer_addresses: Vec<ExtendedAddr>,
+    /// heights of the blocks with filters matching the wallet's view key
+    pub matched_blocks: Vec<u64>,
+}
+
+/// Gap-limit scan of the address chains of a HD wallet
+pub struct AddressDiscovery<'a, W, N, C, O>
+where
+    W: WalletClient,
+    N: NetworkOpsClient,
+    C: Client,
+    O: TransactionObfuscation,
+{
+    wallet_client: &'a W,
+    network_ops_client: &'a N,
+    client: &'a C,
+    transaction_obfuscation: &'a O,
+    gap_limit: u32,
+    batch_size: usize,
+}
+
+impl<'a, W, N, C, O> AddressDiscovery<'a, W, N, C, O>
+where
+    W: WalletClient,
+    N: NetworkOpsClient,
+    C: Client,
+    O: TransactionObfuscation,
+{
+    /// Creates a new instance of address discovery with default gap limit and batch size
+    pub fn new(
+        wallet_client: &'a W,
+        network_ops_client: &'a N,
+        client: &'a C,
+        transaction_obfuscation: &'a O,
+    ) -> Self {
+        Self {
+            wallet_client,
+            network_ops_client,
+            client,
+            transaction_obfuscation,
+            gap_limit: DEFAULT_GAP_LIMIT,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// Sets the number of consecutive unused addresses which ends the scan of an address chain
+    pub fn with_gap_limit(mut self, gap_limit: u32) -> Self {
+        self.gap_limit = gap_limit.max(1);
+        self
+    }
+
+    /// Sets the number of requests per batch in RPC calls to tendermint
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Scans the staking and transfer chains of the wallet and generates the addresses
+    /// up to the last used one in each chain
+    pub fn discover<F>(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        mut progress: F,
+    ) -> Result<DiscoveryResult>
+    where
+        F: FnMut(DiscoveryProgress),
+    {
+        let staking_indexes = scan_chain(self.gap_limit, |index| {
+            let address = self.staking_address(name, enckey, index)?;
+            let used = self
+                .network_ops_client
+                .get_staking(name, &address, false)?
+                .is_some();
+            progress(DiscoveryProgress::StakingAddress { index, used });
+            Ok(used)
+        })?;
+
+        let matched_blocks = self.scan_block_filters(name, enckey, &mut progress)?;
+        let outputs = self.transfer_outputs(name, enckey, &matched_blocks)?;
+        let transfer_indexes = scan_chain(self.gap_limit, |index| {
+            let used = outputs.contains(&self.transfer_address(name, enckey, index)?);
+            progress(DiscoveryProgress::TransferAddress { index, used });
+            Ok(used)
+        })?;
+
+        let result = DiscoveryResult {
+            staking_addresses: staking_indexes
+                .iter()
+                .map(|index| self.staking_address(name, enckey, *index))
+                .collect::<Result<_>>()?,
+            transfer_addresses: transfer_indexes
+                .iter()
+                .map(|index| self.transfer_address(name, enckey, *index))
+                .collect::<Result<_>>()?,
+            matched_blocks,
+        };
+
+        if let Some(last) = result.staking_addresses.last() {
+            let known = self
+                .wallet_client
+                .staking_addresses(name, enckey, 0, 0, false)?;
+            if !known.contains(last) {
+                for _ in 0..*staking_indexes.last().unwrap() {
+                    if &self.wallet_client.new_staking_address(name, enckey)? == last {
+                        break;
+                    }
+                }
+            }
+        }
+        if let Some(last) = result.transfer_addresses.last() {
+            let known = self
+                .wallet_client
+                .transfer_addresses(name, enckey, 0, 0, false)?;
+            if !known.contains(last) {
+                for _ in 0..*transfer_indexes.last().unwrap() {
+                    if &self.wallet_client.new_transfer_address(name, enckey)? == last {
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn staking_address(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        index: u32,
+    ) -> Result<StakedStateAddress> {
+        let public_key =
+            self.wallet_client
+                .peek_public_key(name, enckey, AddressType::Staking, index)?;
+        Ok(StakedStateAddress::BasicRedeem(RedeemAddress::from(
+            &public_key,
+        )))
+    }
+
+    fn transfer_address(&self, name: &str, enckey: &SecKey, index: u32) -> Result<ExtendedAddr> {
+        let public_key =
+            self.wallet_client
+                .peek_public_key(name, enckey, AddressType::Transfer, index)?;
+        let multi_sig_address = MultiSigAddress::new(vec![public_key.clone()], public_key, 1)?;
+        Ok(ExtendedAddr::OrTree(multi_sig_address.root_hash()))
+    }
+
+    /// returns the heights of the blocks with filters matching the wallet's view key
+    fn scan_block_filters<F>(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        progress: &mut F,
+    ) -> Result<Vec<u64>>
+    where
+        F: FnMut(DiscoveryProgress),
+    {
+        let view_key: secp256k1::PublicKey = self.wallet_client.view_key(name, enckey)?.into();
+        let finish_block_height = self.client.status()?.sync_info.latest_block_height.value();
+
+        let mut matched_blocks = Vec::new();
+        let mut start = 1;
+        while start <= finish_block_height {
+            let end = (start + self.batch_size as u64 - 1).min(finish_block_height);
+            let heights = (start..=end).collect::<Vec<_>>();
+            let block_results = self.client.block_results_batch(heights.iter())?;
+            for (height, block_result) in heights.into_iter().zip(block_results) {
+                if block_result.block_filter()?.check_view_key(&view_key) {
+                    matched_blocks.push(height);
+                }
+            }
+            progress(DiscoveryProgress::BlockFilters {
+                current_block_height: end,
+                finish_block_height,
+                matched_blocks: matched_blocks.len(),
+            });
+            start = end + 1;
+        }
+        Ok(matched_blocks)
+    }
+
+    /// returns the output addresses of the transactions (decryptable with the wallet's view key)
+    /// in the given blocks
+    fn transfer_outputs(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        heights: &[u64],
+    ) -> Result<HashSet<ExtendedAddr>> {
+        let private_key = self.wallet_client.view_key_private(name, enckey)?;
+
+        let mut outputs = HashSet::new();
+        for heights in heights.chunks(self.batch_size) {
+            let mut transaction_ids = Vec::new();
+            for block in self.client.block_batch(heights.iter())? {
+                transaction_ids.extend(block.enclave_transaction_ids()?);
+            }
+            if transaction_ids.is_empty() {
+                continue;
+            }
+            for transaction in self
+                .transaction_obfuscation
+                .decrypt(&transaction_ids, &private_key)?
+            {
+                outputs.extend(
+                    transaction
+                        .outputs()
+                        .iter()
+                        .map(|output| output.address.clone()),
+                );
+            }
+        }
+        Ok(outputs)
+    }
+}
+
+/// Checks the addresses of a chain (starting from index `1`) until `gap_limit` consecutive
+/// addresses are unused and returns the indexes of the used ones
+fn scan_chain<F>(gap_limit: u32, mut is_used: F) -> Result<Vec<u32>>
+where
+    F: FnMut(u32) -> Result<bool>,
+{
+    let mut used = Vec::new();
+    let mut gap = 0;
+    let mut index = 1;
+    while gap < gap_limit {
+        if is_used(index)? {
+            used.push(index);
+            gap = 0;
+        } else {
+            gap += 1;
+        }
+        index += 1;
+    }
+    Ok(used)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_scan_chain_stops_after_gap() {
+        let used = [1, 2, 5, 25, 46];
+        let mut checked = Vec::new();
+        let found = scan_chain(20, |index| {
+            checked.push(index);
+            Ok(used.contains(&index))
+        })
+        .unwrap();
+        assert_eq!(found, vec![1, 2, 5, 25]);
+        assert_eq!(checked.last(), Some(&45));
+
+        let found = scan_chain(3, |index| Ok(used.contains(&index))).unwrap();
+        assert_eq!(found, vec![1, 2, 5]);
+        assert!(scan_chain(5, |_| Ok(false)).unwrap().is_empty());
+    }
+}
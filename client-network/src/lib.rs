@@ -6,7 +6,10 @@
 //!
 //! This crate provides and easy to use client for performing network operations on Crypto.com Chain. Payments, on the
 //! other hand, are handled by `WalletClient` in `client-core` crate.
+pub mod address_discovery;
 pub mod network_ops;
 
+#[doc(inline)]
+pub use self::address_discovery::AddressDiscovery;
 #[doc(inline)]
 pub use self::network_ops::NetworkOpsClient;
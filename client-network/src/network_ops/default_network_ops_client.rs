@@ -48,7 +48,7 @@ where
 impl<W, S, C, F, E> DefaultNetworkOpsClient<W, S, C, F, E>
 where
     W: WalletClient,
-    S: Storage,
+    S: Storage + 'static,
     C: Client,
     F: FeeAlgorithm,
     E: TransactionObfuscation,
@@ -104,6 +104,42 @@ where
             },
         ))
     }
+
+    /// Signs a staking operation with the key controlling given staking address: a recoverable signature for
+    /// redeem addresses or a schnorr signature with merkle proof for multi-sig (1-of-n) tree addresses
+    fn sign_staking_operation(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        address: &StakedStateAddress,
+        tx: &Transaction,
+    ) -> Result<StakedStateOpWitness> {
+        match address {
+            StakedStateAddress::BasicRedeem(ref redeem_address) => {
+                let public_key = self
+                    .wallet_client
+                    .find_staking_key(name, enckey, redeem_address)?
+                    .chain(|| {
+                        (
+                            ErrorKind::InvalidInput,
+                            "Address not found in current wallet",
+                        )
+                    })?;
+                let sign_key = self.wallet_client.sign_key(name, enckey, &public_key)?;
+                sign_key.sign(tx).map(StakedStateOpWitness::new)
+            }
+            StakedStateAddress::OrTree(ref root_hash) => {
+                let signer = self.signer_manager.create_signer(
+                    name,
+                    enckey,
+                    &self.signer_manager.hw_key_service,
+                );
+                signer
+                    .schnorr_sign(tx, &ExtendedAddr::OrTree(*root_hash))
+                    .map(StakedStateOpWitness::from)
+            }
+        }
+    }
 }
 
 impl<W, S, C, F, E> NetworkOpsClient for DefaultNetworkOpsClient<W, S, C, F, E>
@@ -208,20 +244,7 @@ where
         let transaction = UnbondTx::new(address, nonce, value, attributes);
         let tx = Transaction::UnbondStakeTransaction(transaction.clone());
 
-        let public_key = match address {
-            StakedStateAddress::BasicRedeem(ref redeem_address) => self
-                .wallet_client
-                .find_staking_key(name, enckey, redeem_address)?
-                .chain(|| {
-                    (
-                        ErrorKind::InvalidInput,
-                        "Address not found in current wallet",
-                    )
-                })?,
-        };
-        let sign_key = self.wallet_client.sign_key(name, enckey, &public_key)?;
-
-        let signature = sign_key.sign(&tx).map(StakedStateOpWitness::new)?;
+        let signature = self.sign_staking_operation(name, enckey, &address, &tx)?;
 
         let txaux = TxAux::PublicTx(TxPublicAux::UnbondStakeTx(transaction, signature));
 
@@ -290,19 +313,7 @@ where
         let transaction = WithdrawUnbondedTx::new(nonce, outputs, attributes);
         let tx = Transaction::WithdrawUnbondedStakeTransaction(transaction.clone());
 
-        let public_key = match from_address {
-            StakedStateAddress::BasicRedeem(ref redeem_address) => self
-                .wallet_client
-                .find_staking_key(name, enckey, redeem_address)?
-                .chain(|| {
-                    (
-                        ErrorKind::InvalidInput,
-                        "Address not found in current wallet",
-                    )
-                })?,
-        };
-        let sign_key = self.wallet_client.sign_key(name, enckey, &public_key)?;
-        let signature = sign_key.sign(&tx).map(StakedStateOpWitness::new)?;
+        let signature = self.sign_staking_operation(name, enckey, from_address, &tx)?;
 
         let signed_transaction =
             SignedTransaction::WithdrawUnbondedStakeTransaction(transaction, signature);
@@ -346,19 +357,7 @@ where
         };
         let tx = Transaction::UnjailTransaction(transaction.clone());
 
-        let public_key = match address {
-            StakedStateAddress::BasicRedeem(ref redeem_address) => self
-                .wallet_client
-                .find_staking_key(name, enckey, redeem_address)?
-                .chain(|| {
-                    (
-                        ErrorKind::InvalidInput,
-                        "Address not found in current wallet",
-                    )
-                })?,
-        };
-        let sign_key = self.wallet_client.sign_key(name, enckey, &public_key)?;
-        let signature = sign_key.sign(&tx).map(StakedStateOpWitness::new)?;
+        let signature = self.sign_staking_operation(name, enckey, &address, &tx)?;
 
         Ok(TxAux::PublicTx(TxPublicAux::UnjailTx(
             transaction,
@@ -442,19 +441,7 @@ where
         };
         let tx = Transaction::NodejoinTransaction(transaction.clone());
 
-        let public_key = match staking_account_address {
-            StakedStateAddress::BasicRedeem(ref redeem_address) => self
-                .wallet_client
-                .find_staking_key(name, enckey, redeem_address)?
-                .chain(|| {
-                    (
-                        ErrorKind::InvalidInput,
-                        "Address not found in current wallet",
-                    )
-                })?,
-        };
-        let sign_key = self.wallet_client.sign_key(name, enckey, &public_key)?;
-        let signature = sign_key.sign(&tx).map(StakedStateOpWitness::new)?;
+        let signature = self.sign_staking_operation(name, enckey, &staking_account_address, &tx)?;
 
         Ok(TxAux::PublicTx(TxPublicAux::NodeJoinTx(
             transaction,
@@ -472,7 +459,7 @@ where
             let sync_state = self.wallet_client.get_sync_state(name)?;
             let rsp = self.client.query(
                 "staking",
-                address.as_ref(),
+                &address.to_query_data(),
                 Some(sync_state.last_block_height.into()),
                 true,
             )?;
@@ -511,7 +498,7 @@ where
         } else {
             let bytes = self
                 .client
-                .query("staking", address.as_ref(), None, false)?
+                .query("staking", &address.to_query_data(), None, false)?
                 .bytes();
             <Option<StakedState>>::decode(&mut bytes.as_slice())
                 .err_kind(ErrorKind::DeserializationError, || {
@@ -1121,6 +1108,60 @@ mod tests {
         }
     }
 
+    #[test]
+    fn check_unjail_transaction_with_tree_address() {
+        let name = "name";
+        let passphrase = SecUtf8::from("passphrase");
+
+        let storage = MemoryStorage::default();
+        let signer_manager = WalletSignerManager::new(storage.clone(), HwKeyService::default());
+
+        let fee_algorithm = UnitFeeAlgorithm::default();
+
+        let wallet_client = DefaultWalletClient::new_read_only(storage.clone());
+
+        let tendermint_client = MockJailedClient::default();
+        let network_ops_client = DefaultNetworkOpsClient::new(
+            wallet_client,
+            signer_manager,
+            tendermint_client,
+            fee_algorithm,
+            MockTransactionCipher,
+        );
+
+        let (enckey, _) = network_ops_client
+            .get_wallet_client()
+            .new_wallet(name, &passphrase, WalletKind::Basic, None)
+            .unwrap();
+
+        let from_address = match network_ops_client
+            .get_wallet_client()
+            .new_transfer_address(name, &enckey)
+            .unwrap()
+        {
+            ExtendedAddr::OrTree(root_hash) => StakedStateAddress::OrTree(root_hash),
+        };
+
+        let transaction = network_ops_client
+            .create_unjail_transaction(
+                name,
+                &enckey,
+                from_address,
+                StakedStateOpAttributes::new(171),
+                false,
+            )
+            .unwrap();
+        match transaction {
+            TxAux::PublicTx(TxPublicAux::UnjailTx(tx, witness)) => {
+                let txid = tx.id();
+                let account_address = verify_tx_recover_address(&witness, &txid)
+                    .expect("Unable to verify transaction");
+                assert_eq!(account_address, from_address);
+            }
+            _ => unreachable!("`unjail_tx()` created invalid transaction"),
+        }
+    }
+
     #[test]
     fn check_node_join_transaction() {
         let name = "name";
@@ -1,6 +1,8 @@
 use structopt::StructOpt;
 
 use crate::server::Server;
+use client_common::storage::StorageBackend;
+use client_common::Result;
 use std::env;
 
 #[derive(StructOpt, Debug)]
@@ -43,6 +45,21 @@ pub struct Options {
     )]
     pub storage_dir: String,
 
+    #[structopt(
+        name = "storage-backend",
+        long,
+        default_value = "sled",
+        help = "Backend of local data storage: sled|sqlite"
+    )]
+    pub storage_backend: StorageBackend,
+
+    #[structopt(
+        name = "secret-service-socket",
+        long,
+        help = "Unix socket of the secret service keeping the enckeys of unlocked wallets"
+    )]
+    pub secret_service_socket: Option<String>,
+
     #[structopt(
         name = "websocket-url",
         short,
@@ -137,7 +154,7 @@ pub fn find_string(args: &[String], target: &str) -> Option<usize> {
 }
 
 #[allow(dead_code)]
-pub fn run_electron() {
+pub fn run_electron() -> Result<()> {
     env_logger::init();
     // "~/Electron", ".", "--chain-id", "ab"]
     let args: Vec<String> = env::args().collect();
@@ -149,6 +166,9 @@ pub fn run_electron() {
     if let Some(a) = find_string(&args, "--storage-dir") {
         options.storage_dir = args[a + 1].clone()
     }
+    if let Some(a) = find_string(&args, "--storage-backend") {
+        options.storage_backend = args[a + 1].parse()?
+    }
 
     if let Some(a) = find_string(&args, "--websocket-url") {
         options.websocket_url = args[a + 1].clone()
@@ -160,5 +180,5 @@ pub fn run_electron() {
 
     log::info!("Options={:?}", options);
     log::info!("Storage={}", options.storage_dir);
-    Server::new(options).unwrap().start().unwrap();
+    Server::new(options)?.start()
 }
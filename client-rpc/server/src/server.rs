@@ -7,12 +7,13 @@ use chain_core::init::network::{get_network, get_network_id, init_chain_id};
 use client_common::Result;
 use client_common::{Error, ErrorKind};
 use client_core::wallet::syncer::SyncerOptions;
-use client_rpc_core::RpcHandler;
+use client_rpc_core::{RpcHandler, StorageOptions};
 pub(crate) struct Server {
     host: String,
     port: u16,
     network_id: u8,
     storage_dir: String,
+    storage_options: StorageOptions,
     websocket_url: String,
 
     sync_options: SyncerOptions,
@@ -42,6 +43,10 @@ impl Server {
             port: options.port,
             network_id,
             storage_dir: options.storage_dir,
+            storage_options: StorageOptions {
+                backend: options.storage_backend,
+                secret_service_socket: options.secret_service_socket,
+            },
             websocket_url: options.websocket_url,
             sync_options: SyncerOptions {
                 enable_fast_forward: options.enable_fast_forward,
@@ -63,6 +68,7 @@ impl Server {
         }
         RpcHandler::new(
             &self.storage_dir,
+            self.storage_options.clone(),
             &self.websocket_url,
             self.network_id,
             self.sync_options.clone(),
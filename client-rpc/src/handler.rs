@@ -4,11 +4,12 @@ use jsonrpc_core::IoHandler;
 use crate::rpc::multisig_rpc::{MultiSigRpc, MultiSigRpcImpl};
 use chain_core::tx::fee::FeeAlgorithm;
 use client_common::cipher::TransactionObfuscation;
-use client_common::storage::SledStorage;
+use client_common::secret_service::SecretServiceClient;
+use client_common::storage::{ClientStorage, SecretServiceStorage, StorageBackend};
 use client_common::tendermint::{types::GenesisExt, Client, WebsocketRpcClient};
 use client_common::Result;
 use client_common::Storage;
-use client_core::service::HwKeyService;
+use client_core::service::{HwKeyService, MigrationService};
 use client_core::signer::WalletSignerManager;
 use client_core::transaction_builder::DefaultWalletTransactionBuilder;
 use client_core::wallet::syncer::{
@@ -18,21 +19,34 @@ use client_core::wallet::DefaultWalletClient;
 use client_network::network_ops::DefaultNetworkOpsClient;
 
 use crate::rpc::{
+    discovery_rpc::{DiscoveryRpc, DiscoveryRpcImpl},
     info_rpc::{InfoRpc, InfoRpcImpl},
+    schedule_rpc::{ScheduleRpc, ScheduleRpcImpl},
+    schedule_worker::ScheduleWorker,
     staking_rpc::{StakingRpc, StakingRpcImpl},
     sync_rpc::{CBindingCore, SyncRpc, SyncRpcImpl},
     transaction_rpc::{TransactionRpc, TransactionRpcImpl},
     wallet_rpc::{WalletRpc, WalletRpcImpl},
 };
 
+type AppStorage = SecretServiceStorage<ClientStorage>;
 type AppWalletClient<O, F> = DefaultWalletClient<
-    SledStorage,
+    AppStorage,
     WebsocketRpcClient,
-    DefaultWalletTransactionBuilder<SledStorage, F, O>,
+    DefaultWalletTransactionBuilder<AppStorage, F, O>,
 >;
 type AppOpsClient<O, F> =
-    DefaultNetworkOpsClient<AppWalletClient<O, F>, SledStorage, WebsocketRpcClient, F, O>;
-type AppSyncerConfig<O, L> = ObfuscationSyncerConfig<SledStorage, WebsocketRpcClient, O, L>;
+    DefaultNetworkOpsClient<AppWalletClient<O, F>, AppStorage, WebsocketRpcClient, F, O>;
+type AppSyncerConfig<O, L> = ObfuscationSyncerConfig<AppStorage, WebsocketRpcClient, O, L>;
+
+/// Storage settings of the RPC handler
+#[derive(Debug, Clone, Default)]
+pub struct StorageOptions {
+    /// backend of the wallet storage
+    pub backend: StorageBackend,
+    /// socket of the secret service keeping the enckeys of unlocked wallets (if any)
+    pub secret_service_socket: Option<String>,
+}
 
 #[derive(Clone)]
 pub struct RpcHandler {
@@ -42,18 +56,39 @@ pub struct RpcHandler {
 impl RpcHandler {
     fn new_impl(
         storage_dir: &str,
+        storage_options: StorageOptions,
         websocket_url: &str,
         network_id: u8,
         sync_options: SyncerOptions,
         progress_callback: Option<CBindingCore>,
     ) -> Result<Self> {
         let mut io = IoHandler::new();
-        let storage = SledStorage::new(&storage_dir)?;
+        let secret_service = storage_options
+            .secret_service_socket
+            .as_ref()
+            .map(SecretServiceClient::new);
+        let mut storage =
+            SecretServiceStorage::new(ClientStorage::open(storage_options.backend, storage_dir)?);
+        if let Some(ref secret_service) = secret_service {
+            storage = storage.with_secret_service(secret_service.clone());
+        }
+        for change in MigrationService::new(storage.clone())
+            .migrate(false)?
+            .changes
+        {
+            log::info!(
+                "{:?} {} from version {} to {}",
+                change.action,
+                change.keyspace,
+                change.from_version,
+                change.to_version
+            );
+        }
 
         let polling_storage = storage.clone();
         std::thread::spawn(move || {
             loop {
-                polling_storage.flush().expect("sled storage flush");
+                polling_storage.flush().expect("storage flush");
                 // every 1 second
                 std::thread::sleep(std::time::Duration::from_secs(1));
             }
@@ -101,14 +136,32 @@ impl RpcHandler {
         let transaction_rpc = TransactionRpcImpl::new(network_id);
         let staking_rpc =
             StakingRpcImpl::new(wallet_client.clone(), ops_client.clone(), network_id);
+        let discovery_rpc = DiscoveryRpcImpl::new(
+            wallet_client.clone(),
+            ops_client.clone(),
+            tendermint_client.clone(),
+            obfuscation.clone(),
+        );
         let info_rpc = InfoRpcImpl::new(ops_client);
 
+        let schedule_worker = ScheduleWorker::new(
+            storage.clone(),
+            wallet_client.clone(),
+            tendermint_client.clone(),
+            network_id,
+        );
+        schedule_worker.clone().spawn();
+        let schedule_rpc = ScheduleRpcImpl::new(schedule_worker);
+
         let sync_wallet_client =
             make_wallet_client(storage, tendermint_client, fee_policy, obfuscation)?;
 
         let sync_rpc =
             SyncRpcImpl::new(syncer_config, progress_callback, sync_wallet_client, handle);
-        let wallet_rpc = WalletRpcImpl::new(wallet_client, network_id);
+        let mut wallet_rpc = WalletRpcImpl::new(wallet_client, network_id);
+        if let Some(secret_service) = secret_service {
+            wallet_rpc = wallet_rpc.with_secret_service(secret_service);
+        }
 
         #[cfg(feature = "experimental")]
         io.extend_with(multisig_rpc.to_delegate());
@@ -116,13 +169,16 @@ impl RpcHandler {
         io.extend_with(staking_rpc.to_delegate());
         io.extend_with(sync_rpc.to_delegate());
         io.extend_with(wallet_rpc.to_delegate());
+        io.extend_with(discovery_rpc.to_delegate());
         io.extend_with(info_rpc.to_delegate());
+        io.extend_with(schedule_rpc.to_delegate());
 
         Ok(RpcHandler { io })
     }
 
     pub fn new(
         storage_dir: &str,
+        storage_options: StorageOptions,
         websocket_url: &str,
         network_id: u8,
         sync_options: SyncerOptions,
@@ -130,6 +186,7 @@ impl RpcHandler {
     ) -> Result<Self> {
         Self::new_impl(
             storage_dir,
+            storage_options,
             websocket_url,
             network_id,
             sync_options,
@@ -143,7 +200,7 @@ impl RpcHandler {
 }
 
 fn make_wallet_client<O: TransactionObfuscation, F: FeeAlgorithm>(
-    storage: SledStorage,
+    storage: AppStorage,
     tendermint_client: WebsocketRpcClient,
     fee_policy: F,
     obfuscator: O,
@@ -160,7 +217,7 @@ fn make_wallet_client<O: TransactionObfuscation, F: FeeAlgorithm>(
 }
 
 fn make_ops_client<O: TransactionObfuscation, F: FeeAlgorithm>(
-    storage: SledStorage,
+    storage: AppStorage,
     tendermint_client: WebsocketRpcClient,
     fee_policy: F,
     obfuscator: O,
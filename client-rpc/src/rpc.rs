@@ -1,6 +1,9 @@
+pub mod discovery_rpc;
 pub mod info_rpc;
 #[cfg(feature = "experimental")]
 pub mod multisig_rpc;
+pub mod schedule_rpc;
+pub mod schedule_worker;
 pub mod staking_rpc;
 pub mod sync_rpc;
 pub mod sync_worker;
@@ -0,0 +1,157 @@
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+use serde::{Deserialize, Serialize};
+
+use client_common::tendermint::Client;
+use client_common::{SecKey, TransactionObfuscation};
+use client_core::wallet::{CreateWalletRequest, WalletRequest};
+use client_core::{Mnemonic, WalletClient};
+use client_network::address_discovery::{AddressDiscovery, DiscoveryResult};
+use client_network::NetworkOpsClient;
+
+use crate::to_rpc_error;
+
+/// Used addresses found by the address discovery
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DiscoveredAddresses {
+    /// used staking addresses (the wallet now has all the staking addresses up to the last one)
+    pub staking_addresses: Vec<String>,
+    /// used transfer addresses (the wallet now has all the transfer addresses up to the last one)
+    pub transfer_addresses: Vec<String>,
+    /// heights of the blocks with filters matching the wallet's view key
+    pub matched_blocks: Vec<u64>,
+}
+
+impl From<DiscoveryResult> for DiscoveredAddresses {
+    fn from(result: DiscoveryResult) -> Self {
+        DiscoveredAddresses {
+            staking_addresses: result
+                .staking_addresses
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            transfer_addresses: result
+                .transfer_addresses
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            matched_blocks: result.matched_blocks,
+        }
+    }
+}
+
+#[rpc(server)]
+pub trait DiscoveryRpc: Send + Sync {
+    /// Scans the address chains of a HD wallet (gap limit defaults to 20) and generates the addresses
+    /// up to the last used ones; the wallet should be synchronized again (with `reset`) afterwards
+    #[rpc(name = "wallet_discoverAddresses")]
+    fn discover_addresses(
+        &self,
+        request: WalletRequest,
+        gap_limit: Option<u32>,
+    ) -> Result<DiscoveredAddresses>;
+
+    /// Restores a HD wallet from the mnemonic and discovers its used addresses
+    #[rpc(name = "wallet_restoreAndDiscover")]
+    fn restore_and_discover(
+        &self,
+        request: CreateWalletRequest,
+        mnemonics: Mnemonic,
+        gap_limit: Option<u32>,
+    ) -> Result<(SecKey, DiscoveredAddresses)>;
+}
+
+pub struct DiscoveryRpcImpl<T, N, C, O>
+where
+    T: WalletClient,
+    N: NetworkOpsClient,
+    C: Client,
+    O: TransactionObfuscation,
+{
+    client: T,
+    ops_client: N,
+    tendermint_client: C,
+    transaction_obfuscation: O,
+}
+
+impl<T, N, C, O> DiscoveryRpcImpl<T, N, C, O>
+where
+    T: WalletClient,
+    N: NetworkOpsClient,
+    C: Client,
+    O: TransactionObfuscation,
+{
+    pub fn new(client: T, ops_client: N, tendermint_client: C, transaction_obfuscation: O) -> Self {
+        DiscoveryRpcImpl {
+            client,
+            ops_client,
+            tendermint_client,
+            transaction_obfuscation,
+        }
+    }
+
+    fn discover(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        gap_limit: Option<u32>,
+    ) -> Result<DiscoveredAddresses> {
+        let mut discovery = AddressDiscovery::new(
+            &self.client,
+            &self.ops_client,
+            &self.tendermint_client,
+            &self.transaction_obfuscation,
+        );
+        if let Some(gap_limit) = gap_limit {
+            discovery = discovery.with_gap_limit(gap_limit);
+        }
+        discovery
+            .discover(name, enckey, |_| {})
+            .map(Into::into)
+            .map_err(to_rpc_error)
+    }
+}
+
+impl<T, N, C, O> DiscoveryRpc for DiscoveryRpcImpl<T, N, C, O>
+where
+    T: WalletClient + 'static,
+    N: NetworkOpsClient + 'static,
+    C: Client + 'static,
+    O: TransactionObfuscation + 'static,
+{
+    fn discover_addresses(
+        &self,
+        request: WalletRequest,
+        gap_limit: Option<u32>,
+    ) -> Result<DiscoveredAddresses> {
+        self.discover(&request.wallet_name(), &request.enckey, gap_limit)
+    }
+
+    fn restore_and_discover(
+        &self,
+        request: CreateWalletRequest,
+        mnemonics: Mnemonic,
+        gap_limit: Option<u32>,
+    ) -> Result<(SecKey, DiscoveredAddresses)> {
+        let enckey = self
+            .client
+            .restore_wallet(&request.name, &request.passphrase, &mnemonics)
+            .map_err(to_rpc_error)?;
+        mnemonics.zeroize();
+
+        let discovered = self.discover(&request.name, &enckey, gap_limit)?;
+        // like `wallet_restore`, the wallet has at least one address of each kind
+        if discovered.staking_addresses.is_empty() {
+            self.client
+                .new_staking_address(&request.name, &enckey)
+                .map_err(to_rpc_error)?;
+        }
+        if discovered.transfer_addresses.is_empty() {
+            self.client
+                .new_transfer_address(&request.name, &enckey)
+                .map_err(to_rpc_error)?;
+        }
+        self.client.flush_database().map_err(to_rpc_error)?;
+        Ok((enckey, discovered))
+    }
+}
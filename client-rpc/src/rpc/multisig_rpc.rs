@@ -1,15 +1,78 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+
 use hex::{decode, encode};
 use jsonrpc_core::Result;
 use jsonrpc_derive::rpc;
+use parity_scale_codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
 
 use chain_core::common::{H256, HASH_SIZE_256};
+use chain_core::tx::data::address::ExtendedAddr;
 use chain_core::tx::data::Tx;
-use client_common::{Error, ErrorKind, PublicKey, Result as CommonResult, ResultExt, SecKey};
+use chain_core::tx::TransactionId;
+use client_common::{
+    Error, ErrorKind, PrivateKey, PublicKey, Result as CommonResult, ResultExt, SecKey,
+};
+use client_coordinator::{CoordinatorClient, CoordinatorRequest, SessionView};
 use client_core::types::AddressType;
 use client_core::wallet::WalletRequest;
-use client_core::{MultiSigWalletClient, WalletClient};
+use client_core::{MultiSigWalletClient, ThresholdWalletClient, WalletClient};
+
+use crate::{rpc_error_from_string, to_rpc_error};
+
+/// New threshold signing session (all the packages are hex encoded SCALE bytes)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ThresholdSession {
+    /// ID of session
+    pub session_id: String,
+    /// Nonce commitment of current signer (to send to all the co-signers)
+    pub commitment: String,
+}
+
+/// Stage of a coordinated session
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoordinatedSessionStage {
+    /// Waiting for nonce commitments of co-signers
+    NonceCommitments,
+    /// Waiting for nonces of co-signers
+    Nonces,
+    /// Waiting for partial signatures of co-signers
+    PartialSignatures,
+    /// Signed transaction is broadcasted
+    Broadcasted,
+}
 
-use crate::to_rpc_error;
+/// Status of a coordinated session
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CoordinatedSessionStatus {
+    /// ID of session on the coordinator
+    pub session_id: String,
+    /// Unsigned transaction to sign (to be reviewed before joining the session)
+    pub transaction: Tx,
+    /// Public key of the creator of session
+    pub creator: PublicKey,
+    /// Public keys of all the signers
+    pub signers: Vec<PublicKey>,
+    /// Current stage of session
+    pub stage: CoordinatedSessionStage,
+    /// ID of broadcasted transaction (if any)
+    pub transaction_id: Option<String>,
+}
+
+/// Coordinated session joined by a wallet of current process (it refers to a multi-sig session of the wallet, whose
+/// secret nonce is only kept in memory as well)
+#[derive(Debug)]
+struct CoordinatedSession {
+    wallet_name: String,
+    coordinator: CoordinatorClient,
+    multi_sig_session_id: H256,
+    self_public_key: PublicKey,
+    nonce_commitments: BTreeSet<PublicKey>,
+    nonces: BTreeSet<PublicKey>,
+    partial_signatures: BTreeSet<PublicKey>,
+}
 
 #[rpc(server)]
 pub trait MultiSigRpc: Send + Sync {
@@ -84,6 +147,106 @@ pub trait MultiSigRpc: Send + Sync {
         session_id: String,
         unsigned_transaction: Tx,
     ) -> Result<String>;
+
+    #[rpc(name = "multiSig_createCoordinatedSession")]
+    fn create_coordinated_session(
+        &self,
+        request: WalletRequest,
+        coordinator: String,
+        unsigned_transaction: Tx,
+        signer_public_keys: Vec<String>,
+        self_public_key: String,
+    ) -> Result<String>;
+
+    #[rpc(name = "multiSig_getCoordinatedSession")]
+    fn get_coordinated_session(
+        &self,
+        request: WalletRequest,
+        coordinator: String,
+        session_id: String,
+        self_public_key: String,
+    ) -> Result<CoordinatedSessionStatus>;
+
+    #[rpc(name = "multiSig_joinCoordinatedSession")]
+    fn join_coordinated_session(
+        &self,
+        request: WalletRequest,
+        coordinator: String,
+        session_id: String,
+        self_public_key: String,
+    ) -> Result<CoordinatedSessionStatus>;
+
+    #[rpc(name = "multiSig_progressCoordinatedSession")]
+    fn progress_coordinated_session(
+        &self,
+        request: WalletRequest,
+        session_id: String,
+    ) -> Result<CoordinatedSessionStatus>;
+
+    #[rpc(name = "multiSig_newThresholdKey")]
+    fn new_threshold_key(
+        &self,
+        request: WalletRequest,
+        context: String,
+        public_keys: Vec<String>,
+        self_public_key: String,
+        threshold: u16,
+    ) -> Result<String>;
+
+    #[rpc(name = "multiSig_thresholdKeyShares")]
+    fn threshold_key_shares(
+        &self,
+        request: WalletRequest,
+        context: String,
+        round1: Vec<String>,
+    ) -> Result<Vec<String>>;
+
+    #[rpc(name = "multiSig_finishThresholdKey")]
+    fn finish_threshold_key(
+        &self,
+        request: WalletRequest,
+        context: String,
+        shares: Vec<String>,
+    ) -> Result<String>;
+
+    #[rpc(name = "multiSig_newThresholdSession")]
+    fn new_threshold_session(
+        &self,
+        request: WalletRequest,
+        address: String,
+        message: String,
+    ) -> Result<ThresholdSession>;
+
+    #[rpc(name = "multiSig_addThresholdCommitment")]
+    fn add_threshold_commitment(
+        &self,
+        request: WalletRequest,
+        session_id: String,
+        commitment: String,
+    ) -> Result<()>;
+
+    #[rpc(name = "multiSig_thresholdPartialSign")]
+    fn threshold_partial_signature(
+        &self,
+        request: WalletRequest,
+        session_id: String,
+    ) -> Result<String>;
+
+    #[rpc(name = "multiSig_addThresholdPartialSignature")]
+    fn add_threshold_partial_signature(
+        &self,
+        request: WalletRequest,
+        session_id: String,
+        partial_signature: String,
+    ) -> Result<()>;
+
+    #[rpc(name = "multiSig_broadcastWithThresholdSignature")]
+    fn broadcast_with_threshold_signature(
+        &self,
+        request: WalletRequest,
+        session_id: String,
+        unsigned_transaction: Tx,
+    ) -> Result<String>;
 }
 
 pub struct MultiSigRpcImpl<T>
@@ -91,6 +254,9 @@ where
     T: WalletClient,
 {
     client: T,
+    /// joined coordinated sessions (`None` while a request exchanges messages with the coordinator, so that the lock
+    /// is never held during network I/O)
+    coordinated_sessions: Arc<Mutex<HashMap<H256, Option<CoordinatedSession>>>>,
 }
 
 impl<T> MultiSigRpcImpl<T>
@@ -98,24 +264,280 @@ where
     T: WalletClient,
 {
     pub fn new(client: T) -> Self {
-        MultiSigRpcImpl { client }
+        MultiSigRpcImpl {
+            client,
+            coordinated_sessions: Default::default(),
+        }
+    }
+}
+
+impl<T> MultiSigRpcImpl<T>
+where
+    T: WalletClient + MultiSigWalletClient,
+{
+    /// Returns the private key of given public key, used to sign the messages sent to the coordinator
+    fn coordinator_key(
+        &self,
+        request: &WalletRequest,
+        public_key: &PublicKey,
+    ) -> CommonResult<PrivateKey> {
+        self.client
+            .private_key(&request.wallet_name(), &request.enckey, public_key)?
+            .chain(|| {
+                (
+                    ErrorKind::InvalidInput,
+                    "Self public key does not belong to current wallet",
+                )
+            })
+    }
+
+    /// Starts a multi-sig session for the transaction of a coordinated session and submits its nonce commitment
+    fn start_coordinated_session(
+        &self,
+        request: &WalletRequest,
+        coordinator: CoordinatorClient,
+        view: &SessionView,
+        self_public_key: PublicKey,
+    ) -> CommonResult<()> {
+        {
+            let mut sessions = self.lock_coordinated_sessions()?;
+            if sessions.contains_key(&view.session_id) {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "Coordinated session is already joined",
+                ));
+            }
+            sessions.insert(view.session_id, None);
+        }
+
+        let result = self.submit_nonce_commitment(request, coordinator, view, self_public_key);
+        let mut sessions = self.lock_coordinated_sessions()?;
+        match result {
+            Ok(session) => {
+                sessions.insert(view.session_id, Some(session));
+                Ok(())
+            }
+            Err(e) => {
+                sessions.remove(&view.session_id);
+                Err(e)
+            }
+        }
+    }
+
+    /// Starts the local multi-sig session of a coordinated session and submits its nonce commitment
+    fn submit_nonce_commitment(
+        &self,
+        request: &WalletRequest,
+        coordinator: CoordinatorClient,
+        view: &SessionView,
+        self_public_key: PublicKey,
+    ) -> CommonResult<CoordinatedSession> {
+        let private_key = self.coordinator_key(request, &self_public_key)?;
+        // Message is computed from the reviewed transaction, so that nothing else can be signed
+        let multi_sig_session_id = self.client.new_multi_sig_session(
+            &request.wallet_name(),
+            &request.enckey,
+            view.transaction.id(),
+            view.signers.clone(),
+            self_public_key.clone(),
+        )?;
+        let nonce_commitment = self
+            .client
+            .nonce_commitment(&multi_sig_session_id, &request.enckey)?;
+        coordinator.submit(
+            &private_key,
+            CoordinatorRequest::SubmitNonceCommitment {
+                session_id: view.session_id,
+                nonce_commitment,
+            },
+        )?;
+
+        Ok(CoordinatedSession {
+            wallet_name: request.wallet_name(),
+            coordinator,
+            multi_sig_session_id,
+            self_public_key,
+            nonce_commitments: BTreeSet::new(),
+            nonces: BTreeSet::new(),
+            partial_signatures: BTreeSet::new(),
+        })
+    }
+
+    /// Exchanges the messages of every round which can be completed with the coordinator. Once all the partial
+    /// signatures are available, the final signature is assembled and the transaction is broadcasted.
+    fn progress(
+        &self,
+        request: &WalletRequest,
+        session_id: &H256,
+    ) -> CommonResult<CoordinatedSessionStatus> {
+        let name = request.wallet_name();
+        let mut session = {
+            let mut sessions = self.lock_coordinated_sessions()?;
+            let slot = sessions
+                .get_mut(session_id)
+                .filter(|slot| {
+                    slot.as_ref()
+                        .map_or(true, |session| session.wallet_name == name)
+                })
+                .chain(|| {
+                    (
+                        ErrorKind::InvalidInput,
+                        "Coordinated session not found; create or join it first",
+                    )
+                })?;
+            slot.take().chain(|| {
+                (
+                    ErrorKind::InvalidInput,
+                    "Coordinated session is being progressed by another request",
+                )
+            })?
+        };
+
+        let result = self.progress_session(request, session_id, &mut session);
+        let mut sessions = self.lock_coordinated_sessions()?;
+        match result {
+            Ok(ref view) if view.broadcast.is_some() => {
+                sessions.remove(session_id);
+            }
+            _ => {
+                sessions.insert(*session_id, Some(session));
+            }
+        }
+        result.map(coordinated_session_status)
+    }
+
+    /// Exchanges the messages of a coordinated session (taken out of the sessions, see `progress`) and returns the
+    /// latest view of the session
+    fn progress_session(
+        &self,
+        request: &WalletRequest,
+        session_id: &H256,
+        session: &mut CoordinatedSession,
+    ) -> CommonResult<SessionView> {
+        let name = request.wallet_name();
+        let enckey = &request.enckey;
+
+        let private_key = self.coordinator_key(request, &session.self_public_key)?;
+        let self_public_key = session.self_public_key.clone();
+        let local_id = session.multi_sig_session_id;
+        let mut view = session.coordinator.session(&private_key, session_id)?;
+
+        if view.broadcast.is_none() {
+            for (public_key, nonce_commitment) in view.nonce_commitments.iter() {
+                if *public_key != self_public_key && !session.nonce_commitments.contains(public_key)
+                {
+                    self.client.add_nonce_commitment(
+                        &local_id,
+                        enckey,
+                        *nonce_commitment,
+                        public_key,
+                    )?;
+                    session.nonce_commitments.insert(public_key.clone());
+                }
+            }
+        }
+
+        if view.broadcast.is_none() && view.is_complete(&view.nonce_commitments) {
+            if !view.nonces.contains_key(&self_public_key) {
+                let nonce = self.client.nonce(&local_id, enckey)?;
+                session.coordinator.submit(
+                    &private_key,
+                    CoordinatorRequest::SubmitNonce {
+                        session_id: *session_id,
+                        nonce,
+                    },
+                )?;
+                view.nonces.insert(self_public_key.clone(), nonce);
+            }
+
+            for (public_key, nonce) in view.nonces.iter() {
+                if *public_key != self_public_key && !session.nonces.contains(public_key) {
+                    self.client
+                        .add_nonce(&local_id, enckey, nonce, public_key)?;
+                    session.nonces.insert(public_key.clone());
+                }
+            }
+        }
+
+        if view.broadcast.is_none() && view.is_complete(&view.nonces) {
+            if !view.partial_signatures.contains_key(&self_public_key) {
+                let partial_signature = self.client.partial_signature(&local_id, enckey)?;
+                session.coordinator.submit(
+                    &private_key,
+                    CoordinatorRequest::SubmitPartialSignature {
+                        session_id: *session_id,
+                        partial_signature,
+                    },
+                )?;
+                view.partial_signatures
+                    .insert(self_public_key.clone(), partial_signature);
+            }
+
+            for (public_key, partial_signature) in view.partial_signatures.iter() {
+                if *public_key != self_public_key
+                    && !session.partial_signatures.contains(public_key)
+                {
+                    self.client.add_partial_signature(
+                        &local_id,
+                        enckey,
+                        *partial_signature,
+                        public_key,
+                    )?;
+                    session.partial_signatures.insert(public_key.clone());
+                }
+            }
+        }
+
+        if view.broadcast.is_none() && view.is_complete(&view.partial_signatures) {
+            let tx_aux =
+                self.client
+                    .transaction(&name, &local_id, enckey, view.transaction.clone())?;
+            self.client.broadcast_transaction(&tx_aux)?;
+
+            let transaction_id = tx_aux.tx_id();
+            session.coordinator.submit(
+                &private_key,
+                CoordinatorRequest::SubmitBroadcast {
+                    session_id: *session_id,
+                    transaction_id,
+                },
+            )?;
+            view.broadcast = Some(transaction_id);
+        }
+
+        Ok(view)
+    }
+
+    fn lock_coordinated_sessions(
+        &self,
+    ) -> CommonResult<MutexGuard<'_, HashMap<H256, Option<CoordinatedSession>>>> {
+        self.coordinated_sessions.lock().map_err(|_| {
+            Error::new(
+                ErrorKind::InternalError,
+                "Unable to acquire lock on coordinated sessions",
+            )
+        })
     }
 }
 
 impl<T> MultiSigRpc for MultiSigRpcImpl<T>
 where
-    T: WalletClient + MultiSigWalletClient + 'static,
+    T: WalletClient + MultiSigWalletClient + ThresholdWalletClient + 'static,
 {
     fn new_address_public_key(&self, request: WalletRequest) -> Result<String> {
         self.client
-            .new_public_key(&request.name, &request.enckey, Some(AddressType::Transfer))
+            .new_public_key(
+                &request.wallet_name(),
+                &request.enckey,
+                Some(AddressType::Transfer),
+            )
             .map(|public_key| public_key.to_string())
             .map_err(to_rpc_error)
     }
 
     fn list_address_public_keys(&self, request: WalletRequest) -> Result<Vec<PublicKey>> {
         self.client
-            .public_keys(&request.name, &request.enckey)
+            .public_keys(&request.wallet_name(), &request.enckey)
             .map(|keys| keys.into_iter().collect())
             .map_err(to_rpc_error)
     }
@@ -131,7 +553,7 @@ where
         let self_public_key = parse_public_key(self_public_key).map_err(to_rpc_error)?;
         // Check if self public key belongs to current wallet
         self.client
-            .private_key(&request.name, &request.enckey, &self_public_key)
+            .private_key(&request.wallet_name(), &request.enckey, &self_public_key)
             .chain(|| {
                 (
                     ErrorKind::InvalidInput,
@@ -142,7 +564,7 @@ where
         let extended_address = self
             .client
             .new_multisig_transfer_address(
-                &request.name,
+                &request.wallet_name(),
                 &request.enckey,
                 public_keys,
                 self_public_key,
@@ -170,7 +592,7 @@ where
 
         self.client
             .new_multi_sig_session(
-                &request.name,
+                &request.wallet_name(),
                 &request.enckey,
                 message,
                 signer_public_keys,
@@ -275,9 +697,248 @@ where
         let tx_aux = self
             .client
             .transaction(
-                &request.name,
+                &request.wallet_name(),
+                &session_id,
+                &request.enckey,
+                unsigned_transaction,
+            )
+            .map_err(to_rpc_error)?;
+
+        self.client
+            .broadcast_transaction(&tx_aux)
+            .map(|result| result.data.to_string())
+            .map_err(to_rpc_error)
+    }
+
+    fn create_coordinated_session(
+        &self,
+        request: WalletRequest,
+        coordinator: String,
+        unsigned_transaction: Tx,
+        signer_public_keys: Vec<String>,
+        self_public_key: String,
+    ) -> Result<String> {
+        let signer_public_keys = parse_public_keys(signer_public_keys).map_err(to_rpc_error)?;
+        let self_public_key = parse_public_key(self_public_key).map_err(to_rpc_error)?;
+        let private_key = self
+            .coordinator_key(&request, &self_public_key)
+            .map_err(to_rpc_error)?;
+        let coordinator = CoordinatorClient::new(&coordinator);
+
+        let session_id = coordinator
+            .create_session(&private_key, unsigned_transaction, signer_public_keys)
+            .map_err(to_rpc_error)?;
+        let view = coordinator
+            .session(&private_key, &session_id)
+            .map_err(to_rpc_error)?;
+        self.start_coordinated_session(&request, coordinator, &view, self_public_key)
+            .map_err(to_rpc_error)?;
+
+        Ok(serialize_hash_256(session_id))
+    }
+
+    fn get_coordinated_session(
+        &self,
+        request: WalletRequest,
+        coordinator: String,
+        session_id: String,
+        self_public_key: String,
+    ) -> Result<CoordinatedSessionStatus> {
+        let session_id = parse_hash_256(session_id).map_err(to_rpc_error)?;
+        let self_public_key = parse_public_key(self_public_key).map_err(to_rpc_error)?;
+        let private_key = self
+            .coordinator_key(&request, &self_public_key)
+            .map_err(to_rpc_error)?;
+
+        CoordinatorClient::new(&coordinator)
+            .session(&private_key, &session_id)
+            .map(coordinated_session_status)
+            .map_err(to_rpc_error)
+    }
+
+    fn join_coordinated_session(
+        &self,
+        request: WalletRequest,
+        coordinator: String,
+        session_id: String,
+        self_public_key: String,
+    ) -> Result<CoordinatedSessionStatus> {
+        let session_id = parse_hash_256(session_id).map_err(to_rpc_error)?;
+        let self_public_key = parse_public_key(self_public_key).map_err(to_rpc_error)?;
+        let private_key = self
+            .coordinator_key(&request, &self_public_key)
+            .map_err(to_rpc_error)?;
+        let coordinator = CoordinatorClient::new(&coordinator);
+
+        let view = coordinator
+            .session(&private_key, &session_id)
+            .map_err(to_rpc_error)?;
+        self.start_coordinated_session(&request, coordinator, &view, self_public_key)
+            .map_err(to_rpc_error)?;
+
+        self.progress(&request, &session_id).map_err(to_rpc_error)
+    }
+
+    fn progress_coordinated_session(
+        &self,
+        request: WalletRequest,
+        session_id: String,
+    ) -> Result<CoordinatedSessionStatus> {
+        let session_id = parse_hash_256(session_id).map_err(to_rpc_error)?;
+
+        self.progress(&request, &session_id).map_err(to_rpc_error)
+    }
+
+    fn new_threshold_key(
+        &self,
+        request: WalletRequest,
+        context: String,
+        public_keys: Vec<String>,
+        self_public_key: String,
+        threshold: u16,
+    ) -> Result<String> {
+        let context = parse_hash_256(context).map_err(to_rpc_error)?;
+        let public_keys = parse_public_keys(public_keys).map_err(to_rpc_error)?;
+        let self_public_key = parse_public_key(self_public_key).map_err(to_rpc_error)?;
+
+        self.client
+            .new_threshold_key_generation(
+                &request.wallet_name(),
+                &request.enckey,
+                context,
+                public_keys,
+                self_public_key,
+                threshold,
+            )
+            .map(|round1| serialize_package(&round1))
+            .map_err(to_rpc_error)
+    }
+
+    fn threshold_key_shares(
+        &self,
+        request: WalletRequest,
+        context: String,
+        round1: Vec<String>,
+    ) -> Result<Vec<String>> {
+        let context = parse_hash_256(context).map_err(to_rpc_error)?;
+        let round1 = parse_packages(round1).map_err(to_rpc_error)?;
+
+        self.client
+            .threshold_key_generation_shares(
+                &request.wallet_name(),
+                &request.enckey,
+                &context,
+                round1,
+            )
+            .map(|shares| shares.iter().map(serialize_package).collect())
+            .map_err(to_rpc_error)
+    }
+
+    fn finish_threshold_key(
+        &self,
+        request: WalletRequest,
+        context: String,
+        shares: Vec<String>,
+    ) -> Result<String> {
+        let context = parse_hash_256(context).map_err(to_rpc_error)?;
+        let shares = parse_packages(shares).map_err(to_rpc_error)?;
+
+        self.client
+            .finish_threshold_key_generation(
+                &request.wallet_name(),
+                &request.enckey,
+                &context,
+                shares,
+            )
+            .map(|address| address.to_string())
+            .map_err(to_rpc_error)
+    }
+
+    fn new_threshold_session(
+        &self,
+        request: WalletRequest,
+        address: String,
+        message: String,
+    ) -> Result<ThresholdSession> {
+        let address = address
+            .parse::<ExtendedAddr>()
+            .map_err(|err| rpc_error_from_string(format!("{}", err)))?;
+        let message = parse_hash_256(message).map_err(to_rpc_error)?;
+
+        self.client
+            .new_threshold_session(&request.wallet_name(), &request.enckey, &address, message)
+            .map(|(session_id, commitment)| ThresholdSession {
+                session_id: serialize_hash_256(session_id),
+                commitment: serialize_package(&commitment),
+            })
+            .map_err(to_rpc_error)
+    }
+
+    fn add_threshold_commitment(
+        &self,
+        request: WalletRequest,
+        session_id: String,
+        commitment: String,
+    ) -> Result<()> {
+        let session_id = parse_hash_256(session_id).map_err(to_rpc_error)?;
+        let commitment = parse_package(commitment).map_err(to_rpc_error)?;
+
+        self.client
+            .add_threshold_commitment(
+                &request.wallet_name(),
+                &request.enckey,
                 &session_id,
+                commitment,
+            )
+            .map_err(to_rpc_error)
+    }
+
+    fn threshold_partial_signature(
+        &self,
+        request: WalletRequest,
+        session_id: String,
+    ) -> Result<String> {
+        let session_id = parse_hash_256(session_id).map_err(to_rpc_error)?;
+
+        self.client
+            .threshold_partial_signature(&request.wallet_name(), &request.enckey, &session_id)
+            .map(|share| serialize_package(&share))
+            .map_err(to_rpc_error)
+    }
+
+    fn add_threshold_partial_signature(
+        &self,
+        request: WalletRequest,
+        session_id: String,
+        partial_signature: String,
+    ) -> Result<()> {
+        let session_id = parse_hash_256(session_id).map_err(to_rpc_error)?;
+        let partial_signature = parse_package(partial_signature).map_err(to_rpc_error)?;
+
+        self.client
+            .add_threshold_partial_signature(
+                &request.wallet_name(),
                 &request.enckey,
+                &session_id,
+                partial_signature,
+            )
+            .map_err(to_rpc_error)
+    }
+
+    fn broadcast_with_threshold_signature(
+        &self,
+        request: WalletRequest,
+        session_id: String,
+        unsigned_transaction: Tx,
+    ) -> Result<String> {
+        let session_id = parse_hash_256(session_id).map_err(to_rpc_error)?;
+
+        let tx_aux = self
+            .client
+            .threshold_transaction(
+                &request.wallet_name(),
+                &request.enckey,
+                &session_id,
                 unsigned_transaction,
             )
             .map_err(to_rpc_error)?;
@@ -289,6 +950,51 @@ where
     }
 }
 
+fn coordinated_session_status(view: SessionView) -> CoordinatedSessionStatus {
+    let stage = if view.broadcast.is_some() {
+        CoordinatedSessionStage::Broadcasted
+    } else if !view.is_complete(&view.nonce_commitments) {
+        CoordinatedSessionStage::NonceCommitments
+    } else if !view.is_complete(&view.nonces) {
+        CoordinatedSessionStage::Nonces
+    } else {
+        CoordinatedSessionStage::PartialSignatures
+    };
+
+    CoordinatedSessionStatus {
+        session_id: serialize_hash_256(view.session_id),
+        transaction: view.transaction,
+        creator: view.creator,
+        signers: view.signers,
+        stage,
+        transaction_id: view.broadcast.map(serialize_hash_256),
+    }
+}
+
+fn serialize_package<P: Encode>(package: &P) -> String {
+    encode(package.encode())
+}
+
+fn parse_package<P: Decode>(package: String) -> CommonResult<P> {
+    let bytes = decode(&package).chain(|| {
+        (
+            ErrorKind::DeserializationError,
+            format!("({}) is not a valid hex string", package),
+        )
+    })?;
+
+    P::decode(&mut bytes.as_slice()).chain(|| {
+        (
+            ErrorKind::DeserializationError,
+            format!("Unable to deserialize threshold package ({})", package),
+        )
+    })
+}
+
+fn parse_packages<P: Decode>(packages: Vec<String>) -> CommonResult<Vec<P>> {
+    packages.into_iter().map(parse_package).collect()
+}
+
 fn serialize_hash_256(hash: H256) -> String {
     encode(&hash)
 }
@@ -366,6 +1072,7 @@ mod test {
         let wallet_request = WalletRequest {
             name: "Default".to_owned(),
             enckey,
+            account: 0,
         };
 
         let wallet_public_key = multisig_rpc
@@ -397,6 +1104,77 @@ mod test {
         );
     }
 
+    #[test]
+    fn threshold_key_generation_should_return_same_address_to_all_participants() {
+        let participants = (0..3)
+            .map(|_| {
+                let multisig_rpc = setup_multisig_rpc();
+                let (enckey, _) = multisig_rpc
+                    .client
+                    .new_wallet(
+                        "Default",
+                        &SecUtf8::from("passphrase"),
+                        WalletKind::Basic,
+                        None,
+                    )
+                    .unwrap();
+                let public_key = multisig_rpc
+                    .client
+                    .new_public_key("Default", &enckey, None)
+                    .unwrap();
+                let wallet_request = WalletRequest {
+                    name: "Default".to_owned(),
+                    enckey,
+                    account: 0,
+                };
+
+                (multisig_rpc, wallet_request, public_key.to_string())
+            })
+            .collect::<Vec<_>>();
+        let public_keys = participants
+            .iter()
+            .map(|(_, _, public_key)| public_key.clone())
+            .collect::<Vec<String>>();
+        let context = encode([3u8; 32]);
+
+        let round1 = participants
+            .iter()
+            .map(|(multisig_rpc, wallet_request, public_key)| {
+                multisig_rpc
+                    .new_threshold_key(
+                        wallet_request.clone(),
+                        context.clone(),
+                        public_keys.clone(),
+                        public_key.clone(),
+                        2,
+                    )
+                    .unwrap()
+            })
+            .collect::<Vec<String>>();
+        let shares = participants
+            .iter()
+            .flat_map(|(multisig_rpc, wallet_request, _)| {
+                multisig_rpc
+                    .threshold_key_shares(wallet_request.clone(), context.clone(), round1.clone())
+                    .unwrap()
+            })
+            .collect::<Vec<String>>();
+        let addresses = participants
+            .iter()
+            .map(|(multisig_rpc, wallet_request, _)| {
+                multisig_rpc
+                    .finish_threshold_key(wallet_request.clone(), context.clone(), shares.clone())
+                    .unwrap()
+            })
+            .collect::<Vec<String>>();
+
+        assert!(
+            addresses[0].starts_with("dcro"),
+            "Return address should be bech32"
+        );
+        assert!(addresses.iter().all(|address| *address == addresses[0]));
+    }
+
     fn make_test_wallet_client(storage: MemoryStorage) -> TestWalletClient {
         let signer_manager = WalletSignerManager::new(storage.clone(), HwKeyService::default());
         let transaction_builder = DefaultWalletTransactionBuilder::new(
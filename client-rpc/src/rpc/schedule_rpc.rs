@@ -0,0 +1,102 @@
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+
+use client_common::tendermint::Client;
+use client_common::SecureStorage;
+use client_core::wallet::WalletRequest;
+use client_core::WalletClient;
+
+use crate::rpc::schedule_worker::{PaymentSchedule, PaymentScheduleRequest, ScheduleWorker};
+use crate::to_rpc_error;
+
+#[rpc(server)]
+pub trait ScheduleRpc: Send + Sync {
+    #[rpc(name = "wallet_schedulePayment")]
+    fn schedule_payment(
+        &self,
+        request: WalletRequest,
+        schedule: PaymentScheduleRequest,
+    ) -> Result<u64>;
+
+    #[rpc(name = "wallet_scheduleList")]
+    fn schedule_list(&self, request: WalletRequest) -> Result<Vec<PaymentSchedule>>;
+
+    #[rpc(name = "wallet_schedulePause")]
+    fn schedule_pause(&self, request: WalletRequest, id: u64) -> Result<()>;
+
+    #[rpc(name = "wallet_scheduleResume")]
+    fn schedule_resume(&self, request: WalletRequest, id: u64) -> Result<()>;
+
+    #[rpc(name = "wallet_scheduleCancel")]
+    fn schedule_cancel(&self, request: WalletRequest, id: u64) -> Result<()>;
+
+    #[rpc(name = "wallet_scheduleUnregister")]
+    fn schedule_unregister(&self, request: WalletRequest) -> Result<()>;
+}
+
+pub struct ScheduleRpcImpl<S, T, C>
+where
+    S: SecureStorage,
+    T: WalletClient,
+    C: Client,
+{
+    worker: ScheduleWorker<S, T, C>,
+}
+
+impl<S, T, C> ScheduleRpcImpl<S, T, C>
+where
+    S: SecureStorage,
+    T: WalletClient,
+    C: Client,
+{
+    pub fn new(worker: ScheduleWorker<S, T, C>) -> Self {
+        ScheduleRpcImpl { worker }
+    }
+}
+
+impl<S, T, C> ScheduleRpc for ScheduleRpcImpl<S, T, C>
+where
+    S: SecureStorage + 'static,
+    T: WalletClient + Clone + 'static,
+    C: Client + 'static,
+{
+    fn schedule_payment(
+        &self,
+        request: WalletRequest,
+        schedule: PaymentScheduleRequest,
+    ) -> Result<u64> {
+        self.worker
+            .add_schedule(&request.wallet_name(), &request.enckey, schedule)
+            .map_err(to_rpc_error)
+    }
+
+    fn schedule_list(&self, request: WalletRequest) -> Result<Vec<PaymentSchedule>> {
+        self.worker
+            .schedules(&request.wallet_name(), &request.enckey)
+            .map_err(to_rpc_error)
+    }
+
+    fn schedule_pause(&self, request: WalletRequest, id: u64) -> Result<()> {
+        self.worker
+            .pause(&request.wallet_name(), &request.enckey, id)
+            .map_err(to_rpc_error)
+    }
+
+    fn schedule_resume(&self, request: WalletRequest, id: u64) -> Result<()> {
+        self.worker
+            .resume(&request.wallet_name(), &request.enckey, id)
+            .map_err(to_rpc_error)
+    }
+
+    fn schedule_cancel(&self, request: WalletRequest, id: u64) -> Result<()> {
+        self.worker
+            .cancel(&request.wallet_name(), &request.enckey, id)
+            .map_err(to_rpc_error)
+    }
+
+    fn schedule_unregister(&self, request: WalletRequest) -> Result<()> {
+        self.worker
+            .unregister(&request.wallet_name(), &request.enckey)
+            .map_err(to_rpc_error)
+    }
+}
@@ -0,0 +1,891 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use parity_scale_codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+use chain_core::common::Timespec;
+use chain_core::init::coin::Coin;
+use chain_core::tx::data::access::{TxAccess, TxAccessPolicy};
+use chain_core::tx::data::address::ExtendedAddr;
+use chain_core::tx::data::attribute::TxAttributes;
+use chain_core::tx::data::input::str2txid;
+use chain_core::tx::data::output::TxOut;
+use chain_core::tx::TxAux;
+use client_common::tendermint::types::Time;
+use client_common::tendermint::Client;
+use client_common::{Error, ErrorKind, PublicKey, Result, ResultExt, SecKey, SecureStorage};
+use client_core::types::TransactionPending;
+use client_core::WalletClient;
+
+/// Keyspace of payment schedules (one encrypted list of schedules per wallet)
+const KEYSPACE: &str = "rpc_payment_schedule";
+
+/// Interval between two checks of due payments
+pub const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+/// Delay (in seconds of block time) before retrying a failed payment
+pub const SCHEDULE_RETRY_DELAY: Timespec = 60;
+/// Maximum number of blocks a wallet may lag behind the chain when executing a payment
+pub const MAX_SYNC_LAG: u64 = 10;
+/// Maximum number of overdue payments of a schedule executed late (when the RPC server was stopped or the enckey of
+/// the wallet was unknown at due time); older overdue payments are skipped
+pub const MAX_OVERDUE_PAYMENTS: usize = 3;
+/// Time (in seconds of block time) after which a pending payment not found in the wallet is considered unconfirmed
+pub const PAYMENT_CONFIRMATION_TIMEOUT: Timespec = 10 * 60;
+/// Maximum number of executed payments kept in the history of a schedule
+const MAX_PAYMENT_HISTORY: usize = 100;
+
+/// Cadence of a payment schedule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+#[serde(rename_all = "snake_case")]
+pub enum Cadence {
+    /// a single payment at start time
+    Once,
+    /// every day from start time
+    Daily,
+    /// every week from start time
+    Weekly,
+    /// every calendar month from start time (on the last day of shorter months)
+    Monthly,
+}
+
+/// Status of a payment schedule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleStatus {
+    /// payments are executed when due
+    Active,
+    /// payments due while paused are skipped
+    Paused,
+    /// all the payments until end time are executed
+    Completed,
+    /// cancelled by user
+    Cancelled,
+}
+
+/// Status of an executed payment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentStatus {
+    /// transaction is (or may have been) broadcast, but it's not yet in the synchronized blocks of the wallet
+    Pending,
+    /// transaction is in the synchronized blocks of the wallet
+    Confirmed,
+    /// transaction was not found `PAYMENT_CONFIRMATION_TIMEOUT` after execution (it's not retried, as it may still
+    /// be included in a block)
+    Unconfirmed,
+}
+
+/// An executed payment of a schedule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct ScheduledPayment {
+    /// time the payment was due
+    pub due: Timespec,
+    /// block time the payment was executed at
+    pub executed_at: Timespec,
+    /// id of the transfer transaction
+    pub transaction_id: String,
+    /// status of the transfer transaction
+    pub status: PaymentStatus,
+}
+
+/// Parameters of a new payment schedule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentScheduleRequest {
+    /// recipient transfer address
+    pub to_address: String,
+    /// amount of every payment
+    pub amount: Coin,
+    /// view keys allowed to see the payments (in addition to the wallet's one)
+    #[serde(default)]
+    pub view_keys: Vec<String>,
+    /// cadence of payments
+    pub cadence: Cadence,
+    /// time of first payment
+    pub start: Timespec,
+    /// no payment is due after this time
+    pub end: Option<Timespec>,
+}
+
+/// Recurring payment to a transfer address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Encode, Decode)]
+pub struct PaymentSchedule {
+    /// id of schedule (unique in a wallet)
+    pub id: u64,
+    /// recipient transfer address
+    pub to_address: String,
+    /// amount of every payment
+    pub amount: Coin,
+    /// view keys allowed to see the payments (in addition to the wallet's one)
+    pub view_keys: Vec<String>,
+    /// cadence of payments
+    pub cadence: Cadence,
+    /// time of first payment
+    pub start: Timespec,
+    /// no payment is due after this time
+    pub end: Option<Timespec>,
+    /// status of schedule
+    pub status: ScheduleStatus,
+    /// index of next payment (number of payments executed or skipped so far)
+    pub next_occurrence: u32,
+    /// number of overdue payments skipped (see `MAX_OVERDUE_PAYMENTS`)
+    pub skipped_payments: u32,
+    /// time next payment is due (`None` if there is no more payment)
+    pub next_payment: Option<Timespec>,
+    /// number of failed attempts of next payment
+    pub failed_attempts: u32,
+    /// error of last failed attempt
+    pub last_error: Option<String>,
+    /// block time after which a failed payment is retried
+    pub retry_at: Option<Timespec>,
+    /// last executed payments
+    pub payments: Vec<ScheduledPayment>,
+}
+
+impl PaymentSchedule {
+    /// Creates a new active schedule
+    pub fn new(id: u64, request: PaymentScheduleRequest) -> Result<Self> {
+        let PaymentScheduleRequest {
+            to_address,
+            amount,
+            view_keys,
+            cadence,
+            start,
+            end,
+        } = request;
+
+        to_address.parse::<ExtendedAddr>().chain(|| {
+            (
+                ErrorKind::InvalidInput,
+                "Invalid recipient transfer address",
+            )
+        })?;
+        parse_view_keys(&view_keys)?;
+
+        if amount == Coin::zero() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Amount of scheduled payment must be greater than zero",
+            ));
+        }
+
+        if let Some(end) = end {
+            if end < start {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "End time of schedule must not be before start time",
+                ));
+            }
+        }
+
+        Ok(Self {
+            id,
+            to_address,
+            amount,
+            view_keys,
+            cadence,
+            start,
+            end,
+            status: ScheduleStatus::Active,
+            next_occurrence: 0,
+            skipped_payments: 0,
+            next_payment: Some(start),
+            failed_attempts: 0,
+            last_error: None,
+            retry_at: None,
+            payments: Vec::new(),
+        })
+    }
+
+    /// Returns the time payment with given index is due (`None` if it is after end time)
+    pub fn occurrence(&self, index: u32) -> Option<Timespec> {
+        let due = match self.cadence {
+            Cadence::Once if index == 0 => Some(self.start),
+            Cadence::Once => None,
+            Cadence::Daily => Some(self.start + u64::from(index) * 24 * 60 * 60),
+            Cadence::Weekly => Some(self.start + u64::from(index) * 7 * 24 * 60 * 60),
+            Cadence::Monthly => add_months(self.start, index),
+        }?;
+
+        match self.end {
+            Some(end) if due > end => None,
+            _ => Some(due),
+        }
+    }
+
+    /// Returns `true` if next payment should be attempted at given block time
+    pub fn is_due(&self, block_time: Timespec) -> bool {
+        self.status == ScheduleStatus::Active
+            && self.next_payment.map_or(false, |due| due <= block_time)
+            && self
+                .retry_at
+                .map_or(true, |retry_at| retry_at <= block_time)
+    }
+
+    /// Records an executed (pending) payment and moves to next occurrence
+    fn record_payment(&mut self, transaction_id: String, block_time: Timespec) {
+        if let Some(due) = self.next_payment {
+            self.payments.push(ScheduledPayment {
+                due,
+                executed_at: block_time,
+                transaction_id,
+                status: PaymentStatus::Pending,
+            });
+            if self.payments.len() > MAX_PAYMENT_HISTORY {
+                self.payments.remove(0);
+            }
+        }
+
+        self.next_occurrence += 1;
+        self.update_next_payment();
+    }
+
+    /// Confirms the pending payments whose transaction is found (or marks them unconfirmed after
+    /// `PAYMENT_CONFIRMATION_TIMEOUT`) and returns `true` if any payment was updated
+    fn update_pending_payments<F>(&mut self, block_time: Timespec, mut is_found: F) -> Result<bool>
+    where
+        F: FnMut(&str) -> Result<bool>,
+    {
+        let mut updated = false;
+        for payment in self
+            .payments
+            .iter_mut()
+            .filter(|payment| payment.status == PaymentStatus::Pending)
+        {
+            if is_found(&payment.transaction_id)? {
+                payment.status = PaymentStatus::Confirmed;
+                updated = true;
+            } else if payment.executed_at + PAYMENT_CONFIRMATION_TIMEOUT <= block_time {
+                payment.status = PaymentStatus::Unconfirmed;
+                updated = true;
+            }
+        }
+        Ok(updated)
+    }
+
+    /// Records a failed payment (retried after `SCHEDULE_RETRY_DELAY`)
+    fn record_failure(&mut self, error: String, block_time: Timespec) {
+        self.failed_attempts += 1;
+        self.last_error = Some(error);
+        self.retry_at = Some(block_time + SCHEDULE_RETRY_DELAY);
+    }
+
+    /// Skips the oldest overdue payments, so that at most `MAX_OVERDUE_PAYMENTS` payments are due at given block time
+    fn limit_overdue_payments(&mut self, block_time: Timespec) {
+        if self.status != ScheduleStatus::Active {
+            return;
+        }
+
+        let overdue = (self.next_occurrence..)
+            .map(|index| self.occurrence(index))
+            .take_while(|due| due.map_or(false, |due| due <= block_time))
+            .count();
+        if overdue > MAX_OVERDUE_PAYMENTS {
+            let skipped = (overdue - MAX_OVERDUE_PAYMENTS) as u32;
+            self.next_occurrence += skipped;
+            self.skipped_payments += skipped;
+            self.update_next_payment();
+        }
+    }
+
+    /// Skips all the payments due before given block time
+    fn skip_until(&mut self, block_time: Timespec) {
+        while self.next_payment.map_or(false, |due| due < block_time) {
+            self.next_occurrence += 1;
+            self.update_next_payment();
+        }
+    }
+
+    fn update_next_payment(&mut self) {
+        self.next_payment = self.occurrence(self.next_occurrence);
+        self.failed_attempts = 0;
+        self.last_error = None;
+        self.retry_at = None;
+
+        if self.next_payment.is_none() {
+            self.status = ScheduleStatus::Completed;
+        }
+    }
+}
+
+/// Executes the due payments of the payment schedules of wallets
+///
+/// Schedules are stored encrypted with the enckey of their wallet, so the payments of a wallet are only executed
+/// while the worker knows its enckey, i.e., after any `wallet_schedule*` call for this wallet since the start of the
+/// RPC server (until `wallet_scheduleUnregister`). Payments due meanwhile are executed late, up to
+/// `MAX_OVERDUE_PAYMENTS` per schedule (older ones are skipped).
+///
+/// A payment is recorded (as pending) before its transaction is broadcast, so that it's never executed twice: if
+/// broadcasting fails, the transaction may still have reached the chain, so it's not retried.
+#[derive(Clone)]
+pub struct ScheduleWorker<S, T, C>
+where
+    S: SecureStorage,
+    T: WalletClient,
+    C: Client,
+{
+    storage: S,
+    wallet_client: T,
+    tendermint_client: C,
+    network_id: u8,
+    enckeys: Arc<Mutex<BTreeMap<String, SecKey>>>,
+    lock: Arc<Mutex<()>>,
+}
+
+impl<S, T, C> ScheduleWorker<S, T, C>
+where
+    S: SecureStorage + 'static,
+    T: WalletClient + Clone + 'static,
+    C: Client + 'static,
+{
+    /// Creates a new schedule worker
+    pub fn new(storage: S, wallet_client: T, tendermint_client: C, network_id: u8) -> Self {
+        Self {
+            storage,
+            wallet_client,
+            tendermint_client,
+            network_id,
+            enckeys: Default::default(),
+            lock: Default::default(),
+        }
+    }
+
+    /// Spawns a thread checking due payments every `SCHEDULE_CHECK_INTERVAL`
+    pub fn spawn(self) {
+        thread::spawn(move || loop {
+            self.execute_due_payments();
+            thread::sleep(SCHEDULE_CHECK_INTERVAL);
+        });
+    }
+
+    /// Returns all the schedules of a wallet (and lets the worker execute its payments)
+    pub fn schedules(&self, name: &str, enckey: &SecKey) -> Result<Vec<PaymentSchedule>> {
+        self.register(name, enckey)?;
+        let _guard = self.lock()?;
+        self.load(name, enckey)
+    }
+
+    /// Forgets the enckey of a wallet (its payments are not executed until next `wallet_schedule*` call)
+    pub fn unregister(&self, name: &str, enckey: &SecKey) -> Result<()> {
+        self.wallet_client.view_key(name, enckey)?;
+        self.enckeys()?.remove(name);
+        Ok(())
+    }
+
+    /// Adds a new schedule to a wallet and returns its id
+    pub fn add_schedule(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        request: PaymentScheduleRequest,
+    ) -> Result<u64> {
+        self.register(name, enckey)?;
+        let _guard = self.lock()?;
+
+        let mut schedules = self.load(name, enckey)?;
+        let id = schedules
+            .iter()
+            .map(|schedule| schedule.id + 1)
+            .max()
+            .unwrap_or_default();
+        schedules.push(PaymentSchedule::new(id, request)?);
+
+        self.save(name, enckey, &schedules)?;
+        Ok(id)
+    }
+
+    /// Pauses an active schedule
+    pub fn pause(&self, name: &str, enckey: &SecKey, id: u64) -> Result<()> {
+        self.update_status(name, enckey, id, |schedule| match schedule.status {
+            ScheduleStatus::Active => {
+                schedule.status = ScheduleStatus::Paused;
+                Ok(())
+            }
+            status => Err(invalid_status(id, status)),
+        })
+    }
+
+    /// Resumes a paused schedule (payments due while paused are skipped)
+    pub fn resume(&self, name: &str, enckey: &SecKey, id: u64) -> Result<()> {
+        let block_time = self.block_time()?;
+        self.update_status(name, enckey, id, |schedule| match schedule.status {
+            ScheduleStatus::Paused => {
+                schedule.status = ScheduleStatus::Active;
+                schedule.skip_until(block_time);
+                Ok(())
+            }
+            status => Err(invalid_status(id, status)),
+        })
+    }
+
+    /// Cancels an active or paused schedule
+    pub fn cancel(&self, name: &str, enckey: &SecKey, id: u64) -> Result<()> {
+        self.update_status(name, enckey, id, |schedule| match schedule.status {
+            ScheduleStatus::Active | ScheduleStatus::Paused => {
+                schedule.status = ScheduleStatus::Cancelled;
+                schedule.next_payment = None;
+                schedule.retry_at = None;
+                Ok(())
+            }
+            status => Err(invalid_status(id, status)),
+        })
+    }
+
+    /// Executes the due payments of all the known wallets
+    pub fn execute_due_payments(&self) {
+        let result = self.tendermint_client.status().map(|status| {
+            (
+                to_timespec(status.sync_info.latest_block_time),
+                status.sync_info.latest_block_height.value(),
+            )
+        });
+        let (block_time, block_height) = match result {
+            Ok(latest) => latest,
+            Err(e) => {
+                log::warn!("unable to check scheduled payments: {}", e);
+                return;
+            }
+        };
+
+        let enckeys = match self.enckeys.lock() {
+            Ok(enckeys) => enckeys.clone(),
+            Err(_) => return,
+        };
+        for (name, enckey) in enckeys.iter() {
+            if let Err(e) = self.execute_wallet_payments(name, enckey, block_time, block_height) {
+                log::warn!("unable to execute scheduled payments of {}: {}", name, e);
+            }
+        }
+    }
+
+    /// Executes the due payments of a wallet at given block time (failed payments are recorded in their schedule)
+    pub fn execute_wallet_payments(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        block_time: Timespec,
+        block_height: u64,
+    ) -> Result<()> {
+        let _guard = self.lock()?;
+        let mut schedules = self.load(name, enckey)?;
+
+        if self.update_pending_payments(name, enckey, &mut schedules, block_time, block_height)? {
+            self.save(name, enckey, &schedules)?;
+        }
+
+        for i in 0..schedules.len() {
+            schedules[i].limit_overdue_payments(block_time);
+            if !schedules[i].is_due(block_time) {
+                continue;
+            }
+
+            let (transaction, tx_pending) =
+                match self.prepare_payment(name, enckey, &schedules[i], block_height) {
+                    Ok(payment) => payment,
+                    Err(e) => {
+                        log::warn!(
+                            "scheduled payment {} of {} failed: {}",
+                            schedules[i].id,
+                            name,
+                            e
+                        );
+                        schedules[i].record_failure(e.to_string(), block_time);
+                        self.save(name, enckey, &schedules)?;
+                        continue;
+                    }
+                };
+
+            // saves before broadcasting so that a payment is never executed twice
+            let transaction_id = hex::encode(transaction.tx_id());
+            schedules[i].record_payment(transaction_id.clone(), block_time);
+            self.save(name, enckey, &schedules)?;
+
+            match self.broadcast_payment(name, enckey, &transaction, tx_pending) {
+                Ok(()) => log::info!(
+                    "scheduled payment {} of {} executed: {}",
+                    schedules[i].id,
+                    name,
+                    transaction_id
+                ),
+                Err(e) => {
+                    log::warn!(
+                        "scheduled payment {} of {} may not be broadcast ({}): {}",
+                        schedules[i].id,
+                        name,
+                        transaction_id,
+                        e
+                    );
+                    schedules[i].last_error = Some(e.to_string());
+                    self.save(name, enckey, &schedules)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Confirms the pending payments found in the wallet (or marks them unconfirmed after
+    /// `PAYMENT_CONFIRMATION_TIMEOUT`) and returns `true` if any payment was updated
+    fn update_pending_payments(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        schedules: &mut [PaymentSchedule],
+        block_time: Timespec,
+        block_height: u64,
+    ) -> Result<bool> {
+        // transactions can only be found in a synchronized wallet
+        if self.check_synchronized(name, block_height).is_err() {
+            return Ok(false);
+        }
+
+        let mut updated = false;
+        for schedule in schedules.iter_mut() {
+            updated |= schedule.update_pending_payments(block_time, |transaction_id| {
+                let transaction_id = str2txid(transaction_id)
+                    .chain(|| (ErrorKind::DeserializationError, "Invalid transaction id"))?;
+                Ok(self
+                    .wallet_client
+                    .get_transaction_change(name, enckey, &transaction_id)?
+                    .is_some())
+            })?;
+        }
+        Ok(updated)
+    }
+
+    /// Builds the transfer transaction of the next payment of a schedule
+    fn prepare_payment(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        schedule: &PaymentSchedule,
+        block_height: u64,
+    ) -> Result<(TxAux, TransactionPending)> {
+        self.check_synchronized(name, block_height)?;
+
+        let address = schedule.to_address.parse::<ExtendedAddr>().chain(|| {
+            (
+                ErrorKind::InvalidInput,
+                "Invalid recipient transfer address",
+            )
+        })?;
+        let mut view_keys = parse_view_keys(&schedule.view_keys)?;
+        view_keys.insert(self.wallet_client.view_key(name, enckey)?);
+        let access_policies = view_keys
+            .iter()
+            .map(|key| TxAccessPolicy {
+                view_key: key.into(),
+                access: TxAccess::AllData,
+            })
+            .collect();
+        let attributes = TxAttributes::new_with_access(self.network_id, access_policies);
+
+        let return_address = self.wallet_client.new_transfer_address(name, enckey)?;
+        let (transaction, used_inputs, return_amount) = self.wallet_client.create_transaction(
+            name,
+            enckey,
+            vec![TxOut::new(address, schedule.amount)],
+            attributes,
+            None,
+            return_address,
+        )?;
+
+        Ok((
+            transaction,
+            TransactionPending {
+                used_inputs,
+                block_height,
+                return_amount,
+            },
+        ))
+    }
+
+    /// Broadcasts the transaction of a payment and marks its inputs as pending in the wallet
+    fn broadcast_payment(
+        &self,
+        name: &str,
+        enckey: &SecKey,
+        transaction: &TxAux,
+        tx_pending: TransactionPending,
+    ) -> Result<()> {
+        self.wallet_client.broadcast_transaction(transaction)?;
+        self.wallet_client.update_tx_pending_state(
+            name,
+            enckey,
+            transaction.tx_id(),
+            tx_pending,
+        )?;
+        self.wallet_client.flush_database()
+    }
+
+    fn check_synchronized(&self, name: &str, block_height: u64) -> Result<()> {
+        let synced_height = self.wallet_client.get_sync_state(name)?.last_block_height;
+        if synced_height + MAX_SYNC_LAG < block_height {
+            return Err(Error::new(
+                ErrorKind::ValidationError,
+                format!(
+                    "Wallet is not synchronized (last synchronized block: {}, latest block: {})",
+                    synced_height, block_height
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    fn update_status<F>(&self, name: &str, enckey: &SecKey, id: u64, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut PaymentSchedule) -> Result<()>,
+    {
+        self.register(name, enckey)?;
+        let _guard = self.lock()?;
+
+        let mut schedules = self.load(name, enckey)?;
+        let schedule = schedules
+            .iter_mut()
+            .find(|schedule| schedule.id == id)
+            .chain(|| {
+                (
+                    ErrorKind::InvalidInput,
+                    format!("Payment schedule with id ({}) not found", id),
+                )
+            })?;
+        f(schedule)?;
+
+        self.save(name, enckey, &schedules)
+    }
+
+    /// Remembers the enckey of a wallet (after checking it) so that its payments can be executed
+    fn register(&self, name: &str, enckey: &SecKey) -> Result<()> {
+        self.wallet_client.view_key(name, enckey)?;
+        self.enckeys()?.insert(name.to_owned(), enckey.clone());
+        Ok(())
+    }
+
+    fn enckeys(&self) -> Result<MutexGuard<'_, BTreeMap<String, SecKey>>> {
+        self.enckeys.lock().map_err(|_| {
+            Error::new(
+                ErrorKind::InternalError,
+                "Unable to acquire lock on scheduled wallets",
+            )
+        })
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, ()>> {
+        self.lock.lock().map_err(|_| {
+            Error::new(
+                ErrorKind::InternalError,
+                "Unable to acquire lock on payment schedules",
+            )
+        })
+    }
+
+    fn block_time(&self) -> Result<Timespec> {
+        let status = self.tendermint_client.status()?;
+        Ok(to_timespec(status.sync_info.latest_block_time))
+    }
+
+    fn load(&self, name: &str, enckey: &SecKey) -> Result<Vec<PaymentSchedule>> {
+        Ok(self
+            .storage
+            .load_secure(KEYSPACE, name, enckey)?
+            .unwrap_or_default())
+    }
+
+    fn save(&self, name: &str, enckey: &SecKey, schedules: &[PaymentSchedule]) -> Result<()> {
+        self.storage
+            .save_secure(KEYSPACE, name, enckey, &schedules.to_vec())?;
+        self.storage.flush()
+    }
+}
+
+fn parse_view_keys(view_keys: &[String]) -> Result<BTreeSet<PublicKey>> {
+    view_keys
+        .iter()
+        .map(|view_key| PublicKey::from_str(view_key))
+        .collect()
+}
+
+fn invalid_status(id: u64, status: ScheduleStatus) -> Error {
+    Error::new(
+        ErrorKind::InvalidInput,
+        format!("Payment schedule with id ({}) is {:?}", id, status),
+    )
+}
+
+fn to_timespec(time: Time) -> Timespec {
+    time.duration_since(Time::unix_epoch())
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Adds calendar months to a time (clamping the day to the last day of shorter months)
+fn add_months(time: Timespec, months: u32) -> Option<Timespec> {
+    let datetime = NaiveDateTime::from_timestamp_opt(time as i64, 0)?;
+    let months = datetime.month0() + months;
+    let year = datetime.year() + (months / 12) as i32;
+    let month = months % 12 + 1;
+
+    let date = (1..=datetime.day())
+        .rev()
+        .filter_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+        .next()?;
+    Some(date.and_time(datetime.time()).timestamp() as Timespec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use secstr::SecUtf8;
+
+    use client_common::storage::MemoryStorage;
+    use client_core::types::WalletKind;
+    use client_core::wallet::DefaultWalletClient;
+
+    use crate::rpc::wallet_rpc::tests::MockRpcClient;
+
+    const TRANSFER_ADDRESS: &str =
+        "dcro1zz30nheum6vnug3mjs0j4kw4w739tca8cuqae2kdjmt8suhv693qcs3qyn";
+
+    fn request(cadence: Cadence, start: Timespec, end: Option<Timespec>) -> PaymentScheduleRequest {
+        PaymentScheduleRequest {
+            to_address: TRANSFER_ADDRESS.to_owned(),
+            amount: Coin::unit(),
+            view_keys: vec![],
+            cadence,
+            start,
+            end,
+        }
+    }
+
+    fn schedule(cadence: Cadence, start: Timespec, end: Option<Timespec>) -> PaymentSchedule {
+        PaymentSchedule::new(0, request(cadence, start, end)).unwrap()
+    }
+
+    #[test]
+    fn check_occurrences() {
+        // 2020-01-31T10:00:00Z
+        let start = 1_580_464_800;
+
+        let monthly = schedule(Cadence::Monthly, start, None);
+        // 2020-02-29T10:00:00Z, 2020-03-31T10:00:00Z, 2021-01-31T10:00:00Z
+        assert_eq!(Some(1_582_970_400), monthly.occurrence(1));
+        assert_eq!(Some(1_585_648_800), monthly.occurrence(2));
+        assert_eq!(Some(1_612_087_200), monthly.occurrence(12));
+
+        let weekly = schedule(Cadence::Weekly, start, Some(start + 14 * 24 * 60 * 60));
+        assert_eq!(Some(start + 7 * 24 * 60 * 60), weekly.occurrence(1));
+        assert_eq!(Some(start + 14 * 24 * 60 * 60), weekly.occurrence(2));
+        assert_eq!(None, weekly.occurrence(3));
+
+        let mut once = schedule(Cadence::Once, start, None);
+        assert_eq!(None, once.occurrence(1));
+        once.record_payment("00".to_owned(), start);
+        assert_eq!(ScheduleStatus::Completed, once.status);
+        assert_eq!(None, once.next_payment);
+
+        let mut daily = schedule(Cadence::Daily, start, None);
+        daily.skip_until(start + 3 * 24 * 60 * 60);
+        assert_eq!(3, daily.next_occurrence);
+        assert_eq!(Some(start + 3 * 24 * 60 * 60), daily.next_payment);
+
+        // payments of days 3 to 10 are overdue, only the last ones are executed
+        daily.limit_overdue_payments(start + 10 * 24 * 60 * 60);
+        assert_eq!(11 - MAX_OVERDUE_PAYMENTS as u32, daily.next_occurrence);
+        assert_eq!(8 - MAX_OVERDUE_PAYMENTS as u32, daily.skipped_payments);
+        daily.limit_overdue_payments(start + 10 * 24 * 60 * 60);
+        assert_eq!(8 - MAX_OVERDUE_PAYMENTS as u32, daily.skipped_payments);
+    }
+
+    #[test]
+    fn check_pending_payment_status() {
+        let mut daily = schedule(Cadence::Daily, 1000, None);
+        daily.record_payment("01".to_owned(), 1000);
+        daily.record_payment("02".to_owned(), 1000);
+        assert_eq!(2, daily.next_occurrence);
+
+        let found = |transaction_id: &str| Ok(transaction_id == "01");
+        assert!(daily.update_pending_payments(1001, found).unwrap());
+        assert_eq!(PaymentStatus::Confirmed, daily.payments[0].status);
+        assert_eq!(PaymentStatus::Pending, daily.payments[1].status);
+
+        let timeout = 1000 + PAYMENT_CONFIRMATION_TIMEOUT;
+        assert!(!daily.update_pending_payments(timeout - 1, found).unwrap());
+        assert!(daily.update_pending_payments(timeout, found).unwrap());
+        assert_eq!(PaymentStatus::Unconfirmed, daily.payments[1].status);
+        // unconfirmed payments are not retried
+        assert_eq!(2, daily.next_occurrence);
+    }
+
+    #[test]
+    fn check_failed_payment_is_retried() {
+        let storage = MemoryStorage::default();
+        let wallet_client = DefaultWalletClient::new_read_only(storage.clone());
+        let worker = ScheduleWorker::new(storage, wallet_client.clone(), MockRpcClient, 171);
+
+        let name = "name";
+        let passphrase = SecUtf8::from("passphrase");
+        let (enckey, _) = wallet_client
+            .new_wallet(name, &passphrase, WalletKind::Basic, None)
+            .unwrap();
+
+        let id = worker
+            .add_schedule(name, &enckey, request(Cadence::Daily, 1000, None))
+            .unwrap();
+        assert!(worker
+            .add_schedule(
+                name,
+                &enckey,
+                PaymentScheduleRequest {
+                    to_address: "invalid".to_owned(),
+                    ..request(Cadence::Daily, 1000, None)
+                },
+            )
+            .is_err());
+
+        // not yet due
+        worker
+            .execute_wallet_payments(name, &enckey, 999, 0)
+            .unwrap();
+        assert_eq!(
+            0,
+            worker.schedules(name, &enckey).unwrap()[0].failed_attempts
+        );
+
+        // read-only wallet client can not send any transaction
+        worker
+            .execute_wallet_payments(name, &enckey, 1000, 0)
+            .unwrap();
+        let schedule = worker.schedules(name, &enckey).unwrap().remove(0);
+        assert_eq!(1, schedule.failed_attempts);
+        assert!(schedule.last_error.is_some());
+        assert_eq!(Some(1000 + SCHEDULE_RETRY_DELAY), schedule.retry_at);
+        assert_eq!(Some(1000), schedule.next_payment);
+
+        // retried only after delay
+        worker
+            .execute_wallet_payments(name, &enckey, 1001, 0)
+            .unwrap();
+        assert_eq!(
+            1,
+            worker.schedules(name, &enckey).unwrap()[0].failed_attempts
+        );
+
+        worker.pause(name, &enckey, id).unwrap();
+        assert!(worker.pause(name, &enckey, id).is_err());
+        worker.cancel(name, &enckey, id).unwrap();
+        assert!(worker.resume(name, &enckey, id).is_err());
+        assert_eq!(
+            ScheduleStatus::Cancelled,
+            worker.schedules(name, &enckey).unwrap()[0].status
+        );
+
+        assert!(worker.enckeys().unwrap().contains_key(name));
+        worker.unregister(name, &enckey).unwrap();
+        assert!(!worker.enckeys().unwrap().contains_key(name));
+    }
+}
@@ -18,6 +18,8 @@ use chain_core::tx::data::input::TxoPointer;
 use chain_core::tx::data::output::TxOut;
 use client_common::temporary_mls_init;
 use client_common::{Error, ErrorKind, PublicKey, Result as CommonResult, ResultExt, Transaction};
+use client_core::types::staking_history::staking_history_to_csv;
+use client_core::types::StakingHistoryEntry;
 use client_core::wallet::WalletRequest;
 use client_core::WalletClient;
 use client_network::NetworkOpsClient;
@@ -72,6 +74,23 @@ pub trait StakingRpc: Send + Sync {
         staking_address: String,
         keypackage: String,
     ) -> Result<String>;
+
+    #[rpc(name = "staking_history")]
+    fn history(
+        &self,
+        request: WalletRequest,
+        staking_address: Option<StakedStateAddress>,
+        offset: usize,
+        limit: usize,
+        reversed: bool,
+    ) -> Result<Vec<StakingHistoryEntry>>;
+
+    #[rpc(name = "staking_exportHistory")]
+    fn export_history(
+        &self,
+        request: WalletRequest,
+        staking_address: Option<StakedStateAddress>,
+    ) -> Result<String>;
 }
 
 pub struct StakingRpcImpl<T, N>
@@ -121,10 +140,10 @@ where
 
         if !self
             .client
-            .has_unspent_transactions(&request.name, &request.enckey, &inputs)
+            .has_spendable_transactions(&request.wallet_name(), &request.enckey, &inputs)
             .map_err(to_rpc_error)?
         {
-            return Err( rpc_error_from_string("Given transaction inputs are not present in unspent transactions (synchronizing your wallet may help)".into()));
+            return Err( rpc_error_from_string("Given transaction inputs are not present in spendable transactions (they may be frozen, or synchronizing your wallet may help)".into()));
         }
 
         let transactions = inputs
@@ -132,7 +151,7 @@ where
             .map(|txo_pointer| {
                 let output = self
                     .client
-                    .output(&request.name, &request.enckey, &txo_pointer)
+                    .output(&request.wallet_name(), &request.enckey, &txo_pointer)
                     .map_err(to_rpc_error)?;
                 Ok((txo_pointer, output))
             })
@@ -142,7 +161,7 @@ where
         let (transaction, tx_pending) = self
             .ops_client
             .create_deposit_bonded_stake_transaction(
-                &request.name,
+                &request.wallet_name(),
                 &request.enckey,
                 transactions,
                 to_address,
@@ -158,7 +177,7 @@ where
         // update the wallet pending transaction state
         self.client
             .update_tx_pending_state(
-                &request.name,
+                &request.wallet_name(),
                 &request.enckey,
                 transaction.tx_id(),
                 tx_pending,
@@ -196,12 +215,12 @@ where
         // 1. build a transfer transaction to make a UTXO which amount is `deposit_amount + fee`
         let to_transfer_address = self
             .client
-            .new_transfer_address(&request.name, &request.enckey)
+            .new_transfer_address(&request.wallet_name(), &request.enckey)
             .map_err(to_rpc_error)?;
         let tx_id = self
             .client
             .send_to_address_commit(
-                &request.name,
+                &request.wallet_name(),
                 &request.enckey,
                 total_amount,
                 to_transfer_address,
@@ -213,7 +232,7 @@ where
         // 2. use the outputs[0] to deposit
         let transaction = self
             .client
-            .get_transaction(&request.name, &request.enckey, tx_id)
+            .get_transaction(&request.wallet_name(), &request.enckey, tx_id)
             .map_err(to_rpc_error)?;
         let output = match transaction {
             Transaction::TransferTransaction(tx) => {
@@ -229,7 +248,7 @@ where
         let (transaction, tx_pending) = self
             .ops_client
             .create_deposit_bonded_stake_transaction(
-                &request.name,
+                &request.wallet_name(),
                 &request.enckey,
                 transactions,
                 to_staking_address,
@@ -245,7 +264,7 @@ where
         // update the wallet pending transaction state
         self.client
             .update_tx_pending_state(
-                &request.name,
+                &request.wallet_name(),
                 &request.enckey,
                 transaction.tx_id(),
                 tx_pending,
@@ -282,7 +301,7 @@ where
         let transaction = self
             .ops_client
             .create_unbond_stake_transaction(
-                &request.name,
+                &request.wallet_name(),
                 &request.enckey,
                 addr,
                 amount,
@@ -329,7 +348,7 @@ where
 
         let view_key = self
             .client
-            .view_key(&request.name, &request.enckey)
+            .view_key(&request.wallet_name(), &request.enckey)
             .map_err(to_rpc_error)?;
 
         view_keys.insert(view_key);
@@ -348,7 +367,7 @@ where
         let (transaction, tx_pending) = self
             .ops_client
             .create_withdraw_all_unbonded_stake_transaction(
-                &request.name,
+                &request.wallet_name(),
                 &request.enckey,
                 &from_address,
                 to_address,
@@ -363,7 +382,7 @@ where
         // update the wallet pending transaction state
         self.client
             .update_tx_pending_state(
-                &request.name,
+                &request.wallet_name(),
                 &request.enckey,
                 transaction.tx_id(),
                 tx_pending,
@@ -387,7 +406,7 @@ where
         let transaction = self
             .ops_client
             .create_unjail_transaction(
-                &request.name,
+                &request.wallet_name(),
                 &request.enckey,
                 unjail_address,
                 attributes,
@@ -425,7 +444,7 @@ where
         let transaction = self
             .ops_client
             .create_node_join_transaction(
-                &request.name,
+                &request.wallet_name(),
                 &request.enckey,
                 staking_account_address,
                 attributes,
@@ -439,6 +458,45 @@ where
 
         Ok(hex::encode(transaction.tx_id()))
     }
+
+    fn history(
+        &self,
+        request: WalletRequest,
+        staking_address: Option<StakedStateAddress>,
+        offset: usize,
+        limit: usize,
+        reversed: bool,
+    ) -> Result<Vec<StakingHistoryEntry>> {
+        self.client
+            .staking_history(
+                &request.wallet_name(),
+                &request.enckey,
+                staking_address.as_ref(),
+                offset,
+                limit,
+                reversed,
+            )
+            .map_err(to_rpc_error)
+    }
+
+    fn export_history(
+        &self,
+        request: WalletRequest,
+        staking_address: Option<StakedStateAddress>,
+    ) -> Result<String> {
+        let entries = self
+            .client
+            .staking_history(
+                &request.wallet_name(),
+                &request.enckey,
+                staking_address.as_ref(),
+                0,
+                std::usize::MAX,
+                false,
+            )
+            .map_err(to_rpc_error)?;
+        Ok(staking_history_to_csv(&entries))
+    }
 }
 
 /// FIXME: take Add + Commit instead of keypackage
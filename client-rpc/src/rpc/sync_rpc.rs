@@ -134,7 +134,7 @@ where
 {
     let mut syncer = WalletSyncer::with_obfuscation_config(
         config,
-        request.name,
+        request.wallet_name(),
         request.enckey,
         recover_address,
     )
@@ -210,7 +210,7 @@ where
         let config = self.config.clone();
         let recover_address = self.recover_address.clone();
 
-        let name = request.name.clone();
+        let name = request.wallet_name();
         let worker = self.worker.clone();
         let userrequest = request.clone();
 
@@ -221,7 +221,7 @@ where
         if let Ok(value) = progress {
             return Ok(RunSyncResult {
                 message: "already syncing wallet".to_string(),
-                name: request.name,
+                name,
                 progress: value,
             });
         }
@@ -281,7 +281,7 @@ where
 
         Ok(RunSyncResult {
             message: "started sync wallet".to_string(),
-            name: request.name,
+            name: request.wallet_name(),
             progress: RunSyncProgressResult::default(),
         })
     }
@@ -318,7 +318,7 @@ where
         self.worker
             .lock()
             .expect("get sync worker lock")
-            .get_progress(&request.name)
+            .get_progress(&request.wallet_name())
     }
 
     #[inline]
@@ -326,7 +326,7 @@ where
         self.worker
             .lock()
             .expect("get sync worker lock")
-            .stop(&request.name)
+            .stop(&request.wallet_name())
     }
 }
 
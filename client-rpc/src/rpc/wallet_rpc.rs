@@ -7,14 +7,24 @@ use secstr::SecUtf8;
 
 use chain_core::init::coin::Coin;
 use chain_core::tx::data::address::ExtendedAddr;
+use chain_core::tx::data::input::TxoPointer;
+use client_common::secret_service::SecretServiceClient;
 use client_common::{PrivateKey, PublicKey, Result as CommonResult, SecKey};
-use client_core::service::WalletInfo;
+use client_core::mnemonic::shamir::{
+    recover_mnemonic, split_mnemonic, validate_shares, MnemonicShare, ShareSetInfo,
+};
+use client_core::service::{AccountXpubs, WalletInfo};
 use client_core::transaction_builder::SignedTransferTransaction;
-use client_core::types::{TransactionChange, WalletBalance, WalletKind};
+use client_core::types::label::{
+    labels_from_jsonl, labels_to_jsonl, output_reference, parse_output_reference,
+};
+use client_core::types::{
+    Contact, Label, LabelType, LabeledTransactionChange, WalletBalance, WalletKind,
+};
 use client_core::wallet::{CreateWalletRequest, WalletRequest};
 #[cfg(feature = "experimental")]
 use client_core::MultiSigWalletClient;
-use client_core::{Mnemonic, UnspentTransactions, WalletClient};
+use client_core::{InputSelectionStrategy, Mnemonic, UnspentTransactions, WalletClient};
 use parity_scale_codec::{Decode, Encode};
 
 use crate::{rpc_error_from_string, to_rpc_error};
@@ -35,12 +45,43 @@ pub trait WalletRpc: Send + Sync {
     #[rpc(name = "wallet_restore")]
     fn restore(&self, request: CreateWalletRequest, mnemonics: Mnemonic) -> Result<SecKey>;
 
+    #[rpc(name = "wallet_createShamirShares")]
+    fn create_shamir_shares(
+        &self,
+        mnemonics: Mnemonic,
+        threshold: u8,
+        count: u8,
+    ) -> Result<Vec<String>>;
+
+    #[rpc(name = "wallet_validateShamirShares")]
+    fn validate_shamir_shares(&self, shares: Vec<SecUtf8>) -> Result<ShareSetInfo>;
+
+    #[rpc(name = "wallet_restoreShamir")]
+    fn restore_shamir(&self, request: CreateWalletRequest, shares: Vec<SecUtf8>) -> Result<SecKey>;
+
     #[rpc(name = "wallet_restoreBasic")]
     fn restore_basic(&self, request: CreateWalletRequest, view_key: SecUtf8) -> Result<SecKey>;
 
+    #[rpc(name = "wallet_restoreXpub")]
+    fn restore_xpub(
+        &self,
+        request: CreateWalletRequest,
+        xpubs: AccountXpubs,
+        view_key: SecUtf8,
+    ) -> Result<SecKey>;
+
+    #[rpc(name = "wallet_exportXpub")]
+    fn export_xpub(&self, request: WalletRequest) -> Result<AccountXpubs>;
+
     #[rpc(name = "wallet_delete")]
     fn delete(&self, request: CreateWalletRequest) -> Result<()>;
 
+    #[rpc(name = "wallet_createAccount")]
+    fn create_account(&self, request: WalletRequest) -> Result<u32>;
+
+    #[rpc(name = "wallet_listAccounts")]
+    fn list_accounts(&self, request: WalletRequest) -> Result<Vec<u32>>;
+
     #[rpc(name = "wallet_createStakingAddress")]
     fn create_staking_address(&self, request: WalletRequest) -> Result<String>;
 
@@ -97,6 +138,15 @@ pub trait WalletRpc: Send + Sync {
     #[rpc(name = "wallet_listUTxO")]
     fn list_utxo(&self, request: WalletRequest) -> Result<UnspentTransactions>;
 
+    #[rpc(name = "wallet_freezeUTxO")]
+    fn freeze_utxo(&self, request: WalletRequest, inputs: Vec<String>) -> Result<()>;
+
+    #[rpc(name = "wallet_unfreezeUTxO")]
+    fn unfreeze_utxo(&self, request: WalletRequest, inputs: Vec<String>) -> Result<()>;
+
+    #[rpc(name = "wallet_listFrozenUTxO")]
+    fn list_frozen_utxo(&self, request: WalletRequest) -> Result<Vec<String>>;
+
     #[rpc(name = "wallet_sendToAddress")]
     fn send_to_address(
         &self,
@@ -104,6 +154,7 @@ pub trait WalletRpc: Send + Sync {
         to_address: String,
         amount: Coin,
         view_keys: Vec<String>,
+        inputs: Option<Vec<String>>,
     ) -> Result<String>;
 
     #[rpc(name = "wallet_buildRawTransferTx")]
@@ -129,7 +180,7 @@ pub trait WalletRpc: Send + Sync {
         offset: usize,
         limit: usize,
         reversed: bool,
-    ) -> Result<Vec<TransactionChange>>;
+    ) -> Result<Vec<LabeledTransactionChange>>;
 
     #[rpc(name = "wallet_exportTransaction")]
     fn export_plain_tx(&self, request: WalletRequest, txid: String) -> Result<String>;
@@ -137,6 +188,48 @@ pub trait WalletRpc: Send + Sync {
     #[rpc(name = "wallet_importTransaction")]
     fn import_plain_tx(&self, request: WalletRequest, tx: String) -> Result<Coin>;
 
+    #[rpc(name = "wallet_addContact")]
+    fn add_contact(
+        &self,
+        request: WalletRequest,
+        address: String,
+        name: String,
+        notes: Option<String>,
+    ) -> Result<()>;
+
+    #[rpc(name = "wallet_removeContact")]
+    fn remove_contact(&self, request: WalletRequest, address: String) -> Result<bool>;
+
+    #[rpc(name = "wallet_listContacts")]
+    fn list_contacts(&self, request: WalletRequest) -> Result<Vec<Contact>>;
+
+    #[rpc(name = "wallet_setLabel")]
+    fn set_label(
+        &self,
+        request: WalletRequest,
+        label_type: LabelType,
+        reference: String,
+        label: String,
+        notes: Option<String>,
+    ) -> Result<()>;
+
+    #[rpc(name = "wallet_removeLabel")]
+    fn remove_label(
+        &self,
+        request: WalletRequest,
+        label_type: LabelType,
+        reference: String,
+    ) -> Result<bool>;
+
+    #[rpc(name = "wallet_listLabels")]
+    fn list_labels(&self, request: WalletRequest) -> Result<Vec<Label>>;
+
+    #[rpc(name = "wallet_exportLabels")]
+    fn export_labels(&self, request: WalletRequest) -> Result<String>;
+
+    #[rpc(name = "wallet_importLabels")]
+    fn import_labels(&self, request: WalletRequest, labels: String) -> Result<usize>;
+
     #[rpc(name = "wallet_getEncKey")]
     fn get_enc_key(&self, request: CreateWalletRequest) -> Result<SecKey>;
 
@@ -145,6 +238,18 @@ pub trait WalletRpc: Send + Sync {
 
     #[rpc(name = "wallet_import")]
     fn import(&self, request: CreateWalletRequest, wallet_info: WalletInfo) -> Result<SecKey>;
+
+    #[rpc(name = "wallet_backup")]
+    fn backup(&self, request: WalletRequest) -> Result<String>;
+
+    #[rpc(name = "wallet_restoreBackup")]
+    fn restore_backup(&self, request: CreateWalletRequest, backup: String) -> Result<SecKey>;
+
+    #[rpc(name = "wallet_unlock")]
+    fn unlock(&self, request: CreateWalletRequest) -> Result<SecKey>;
+
+    #[rpc(name = "wallet_lock")]
+    fn lock(&self, request: WalletRequest) -> Result<bool>;
 }
 
 pub struct WalletRpcImpl<T>
@@ -153,6 +258,7 @@ where
 {
     client: T,
     network_id: u8,
+    secret_service: Option<SecretServiceClient>,
 }
 
 impl<T> WalletRpcImpl<T>
@@ -160,7 +266,36 @@ where
     T: WalletClient,
 {
     pub fn new(client: T, network_id: u8) -> Self {
-        WalletRpcImpl { client, network_id }
+        WalletRpcImpl {
+            client,
+            network_id,
+            secret_service: None,
+        }
+    }
+
+    /// Keeps the enckeys of unlocked wallets in given secret service
+    pub fn with_secret_service(mut self, secret_service: SecretServiceClient) -> Self {
+        self.secret_service = Some(secret_service);
+        self
+    }
+
+    fn secret_service(&self) -> Result<&SecretServiceClient> {
+        self.secret_service
+            .as_ref()
+            .ok_or_else(|| rpc_error_from_string("Secret service is not configured".to_owned()))
+    }
+
+    fn set_frozen_utxo(
+        &self,
+        request: WalletRequest,
+        inputs: Vec<String>,
+        frozen: bool,
+    ) -> Result<()> {
+        let inputs = parse_inputs(&inputs)?;
+        self.client
+            .set_frozen_outputs(&request.wallet_name(), &request.enckey, &inputs, frozen)
+            .map_err(to_rpc_error)?;
+        self.client.flush_database().map_err(to_rpc_error)
     }
 }
 
@@ -170,7 +305,7 @@ where
 {
     fn balance(&self, request: WalletRequest) -> Result<WalletBalance> {
         self.client
-            .balance(&request.name, &request.enckey)
+            .balance(&request.wallet_name(), &request.enckey)
             .map_err(to_rpc_error)
     }
 
@@ -228,6 +363,31 @@ where
         Ok(enckey)
     }
 
+    fn create_shamir_shares(
+        &self,
+        mnemonics: Mnemonic,
+        threshold: u8,
+        count: u8,
+    ) -> Result<Vec<String>> {
+        let shares = split_mnemonic(&mnemonics, threshold, count).map_err(to_rpc_error)?;
+        mnemonics.zeroize();
+        Ok(shares
+            .iter()
+            .map(|share| share.phrase().unsecure().to_owned())
+            .collect())
+    }
+
+    fn validate_shamir_shares(&self, shares: Vec<SecUtf8>) -> Result<ShareSetInfo> {
+        let shares = parse_shares(&shares)?;
+        validate_shares(&shares).map_err(to_rpc_error)
+    }
+
+    fn restore_shamir(&self, request: CreateWalletRequest, shares: Vec<SecUtf8>) -> Result<SecKey> {
+        let shares = parse_shares(&shares)?;
+        let mnemonic = recover_mnemonic(&shares).map_err(to_rpc_error)?;
+        self.restore(request, mnemonic)
+    }
+
     fn restore_basic(&self, request: CreateWalletRequest, view_key: SecUtf8) -> Result<SecKey> {
         let view_key =
             PrivateKey::deserialize_from(&hex::decode(view_key.unsecure()).map_err(to_rpc_error)?)
@@ -240,6 +400,36 @@ where
         Ok(enckey)
     }
 
+    fn restore_xpub(
+        &self,
+        request: CreateWalletRequest,
+        xpubs: AccountXpubs,
+        view_key: SecUtf8,
+    ) -> Result<SecKey> {
+        let view_key =
+            PrivateKey::deserialize_from(&hex::decode(view_key.unsecure()).map_err(to_rpc_error)?)
+                .map_err(to_rpc_error)?;
+        let enckey = self
+            .client
+            .restore_xpub_wallet(&request.name, &request.passphrase, &xpubs, &view_key)
+            .map_err(to_rpc_error)?;
+
+        self.client
+            .new_staking_address(&request.name, &enckey)
+            .map_err(to_rpc_error)?;
+        self.client
+            .new_transfer_address(&request.name, &enckey)
+            .map_err(to_rpc_error)?;
+        self.client.flush_database().map_err(to_rpc_error)?;
+        Ok(enckey)
+    }
+
+    fn export_xpub(&self, request: WalletRequest) -> Result<AccountXpubs> {
+        self.client
+            .export_xpubs(&request.wallet_name(), &request.enckey)
+            .map_err(to_rpc_error)
+    }
+
     fn delete(&self, request: CreateWalletRequest) -> Result<()> {
         let ret = self
             .client
@@ -249,10 +439,25 @@ where
         ret
     }
 
+    fn create_account(&self, request: WalletRequest) -> Result<u32> {
+        let ret = self
+            .client
+            .new_account(&request.name, &request.enckey)
+            .map_err(to_rpc_error);
+        self.client.flush_database().map_err(to_rpc_error)?;
+        ret
+    }
+
+    fn list_accounts(&self, request: WalletRequest) -> Result<Vec<u32>> {
+        self.client
+            .accounts(&request.name, &request.enckey)
+            .map_err(to_rpc_error)
+    }
+
     fn create_staking_address(&self, request: WalletRequest) -> Result<String> {
         let ret = self
             .client
-            .new_staking_address(&request.name, &request.enckey)
+            .new_staking_address(&request.wallet_name(), &request.enckey)
             .map(|staked_state_addr| staked_state_addr.to_string())
             .map_err(to_rpc_error);
         self.client.flush_database().map_err(to_rpc_error)?;
@@ -265,7 +470,7 @@ where
             let now = std::time::Instant::now();
             let progress = i as f64 / count as f64 * 100.0;
             self.client
-                .new_staking_address(&request.name, &request.enckey)
+                .new_staking_address(&request.wallet_name(), &request.enckey)
                 .map(|staked_state_addr| staked_state_addr.to_string())
                 .map_err(to_rpc_error)?;
             log::debug!(
@@ -288,7 +493,7 @@ where
     ) -> Result<String> {
         let ret = self
             .client
-            .new_watch_staking_address(&request.name, &request.enckey, &public_key)
+            .new_watch_staking_address(&request.wallet_name(), &request.enckey, &public_key)
             .map(|staked_state_addr| staked_state_addr.to_string())
             .map_err(to_rpc_error);
         self.client.flush_database().map_err(to_rpc_error)?;
@@ -298,7 +503,7 @@ where
     fn create_transfer_address(&self, request: WalletRequest) -> Result<String> {
         let extended_address = self
             .client
-            .new_transfer_address(&request.name, &request.enckey)
+            .new_transfer_address(&request.wallet_name(), &request.enckey)
             .map_err(to_rpc_error)?;
 
         self.client.flush_database().map_err(to_rpc_error)?;
@@ -311,7 +516,7 @@ where
             let now = std::time::Instant::now();
             let progress = i as f64 / count as f64 * 100.0;
             self.client
-                .new_transfer_address(&request.name, &request.enckey)
+                .new_transfer_address(&request.wallet_name(), &request.enckey)
                 .map_err(to_rpc_error)?;
             log::debug!(
                 "transfer-address progress {:.2}% created {}/{}  for each {} micro-seconds  total {} seconds",
@@ -333,7 +538,7 @@ where
     ) -> Result<String> {
         let extended_address = self
             .client
-            .new_watch_transfer_address(&request.name, &request.enckey, &public_key)
+            .new_watch_transfer_address(&request.wallet_name(), &request.enckey, &public_key)
             .map_err(to_rpc_error)?;
 
         self.client.flush_database().map_err(to_rpc_error)?;
@@ -345,13 +550,13 @@ where
             hex::encode(
                 &self
                     .client
-                    .view_key_private(&request.name, &request.enckey)
+                    .view_key_private(&request.wallet_name(), &request.enckey)
                     .map_err(to_rpc_error)?
                     .serialize(),
             )
         } else {
             self.client
-                .view_key(&request.name, &request.enckey)
+                .view_key(&request.wallet_name(), &request.enckey)
                 .map_err(to_rpc_error)?
                 .to_string()
         };
@@ -364,7 +569,7 @@ where
 
     fn list_public_keys(&self, request: WalletRequest) -> Result<Vec<PublicKey>> {
         self.client
-            .public_keys(&request.name, &request.enckey)
+            .public_keys(&request.wallet_name(), &request.enckey)
             .map(|keys| keys.into_iter().collect())
             .map_err(to_rpc_error)
     }
@@ -378,7 +583,7 @@ where
     ) -> Result<Vec<String>> {
         self.client
             .staking_addresses(
-                &request.name,
+                &request.wallet_name(),
                 &request.enckey,
                 offset.unwrap_or(0),
                 std::cmp::max(1, std::cmp::min(limit.unwrap_or(1000), 10000)),
@@ -397,7 +602,7 @@ where
     ) -> Result<Vec<String>> {
         self.client
             .transfer_addresses(
-                &request.name,
+                &request.wallet_name(),
                 &request.enckey,
                 offset.unwrap_or(0),
                 std::cmp::max(1, std::cmp::min(limit.unwrap_or(1000), 10000)),
@@ -409,7 +614,22 @@ where
 
     fn list_utxo(&self, request: WalletRequest) -> Result<UnspentTransactions> {
         self.client
-            .unspent_transactions(&request.name, &request.enckey)
+            .unspent_transactions(&request.wallet_name(), &request.enckey)
+            .map_err(to_rpc_error)
+    }
+
+    fn freeze_utxo(&self, request: WalletRequest, inputs: Vec<String>) -> Result<()> {
+        self.set_frozen_utxo(request, inputs, true)
+    }
+
+    fn unfreeze_utxo(&self, request: WalletRequest, inputs: Vec<String>) -> Result<()> {
+        self.set_frozen_utxo(request, inputs, false)
+    }
+
+    fn list_frozen_utxo(&self, request: WalletRequest) -> Result<Vec<String>> {
+        self.client
+            .frozen_outputs(&request.wallet_name(), &request.enckey)
+            .map(|inputs| inputs.iter().map(output_reference).collect())
             .map_err(to_rpc_error)
     }
 
@@ -419,6 +639,7 @@ where
         to_address: String,
         amount: Coin,
         view_keys: Vec<String>,
+        inputs: Option<Vec<String>>,
     ) -> Result<String> {
         let address = to_address
             .parse::<ExtendedAddr>()
@@ -428,15 +649,20 @@ where
             .map(|view_key| PublicKey::from_str(view_key))
             .collect::<CommonResult<BTreeSet<PublicKey>>>()
             .map_err(to_rpc_error)?;
+        let input_selection_strategy = match inputs {
+            Some(inputs) => Some(InputSelectionStrategy::Manual(parse_inputs(&inputs)?)),
+            None => None,
+        };
         let tx_id = self
             .client
             .send_to_address(
-                &request.name,
+                &request.wallet_name(),
                 &request.enckey,
                 amount,
                 address,
                 &mut view_keys,
                 self.network_id,
+                input_selection_strategy,
             )
             .map_err(to_rpc_error)?;
         self.client.flush_database().map_err(to_rpc_error)?;
@@ -461,7 +687,7 @@ where
         let unsigned_transfer_tx = self
             .client
             .build_raw_transfer_tx(
-                &request.name,
+                &request.wallet_name(),
                 &request.enckey,
                 to_address,
                 amount,
@@ -485,7 +711,7 @@ where
             SignedTransferTransaction::decode(&mut raw_data.as_slice()).map_err(to_rpc_error)?;
         let tx_id = self
             .client
-            .broadcast_signed_transfer_tx(&request.name, &request.enckey, signed_tx)
+            .broadcast_signed_transfer_tx(&request.wallet_name(), &request.enckey, signed_tx)
             .map_err(to_rpc_error)?;
         self.client.flush_database().map_err(to_rpc_error)?;
         Ok(hex::encode(tx_id))
@@ -494,7 +720,7 @@ where
     fn export_plain_tx(&self, request: WalletRequest, txid: String) -> Result<String> {
         let tx_info = self
             .client
-            .export_plain_tx(&request.name, &request.enckey, &txid)
+            .export_plain_tx(&request.wallet_name(), &request.enckey, &txid)
             .map_err(to_rpc_error)?;
         tx_info.encode().map_err(to_rpc_error)
     }
@@ -502,7 +728,7 @@ where
     fn import_plain_tx(&self, request: WalletRequest, tx: String) -> Result<Coin> {
         let ret = self
             .client
-            .import_plain_tx(&request.name, &request.enckey, &tx)
+            .import_plain_tx(&request.wallet_name(), &request.enckey, &tx)
             .map_err(to_rpc_error);
 
         self.client.flush_database().map_err(to_rpc_error)?;
@@ -515,12 +741,91 @@ where
         offset: usize,
         limit: usize,
         reversed: bool,
-    ) -> Result<Vec<TransactionChange>> {
+    ) -> Result<Vec<LabeledTransactionChange>> {
+        self.client
+            .history(
+                &request.wallet_name(),
+                &request.enckey,
+                offset,
+                limit,
+                reversed,
+            )
+            .map_err(to_rpc_error)
+    }
+
+    fn add_contact(
+        &self,
+        request: WalletRequest,
+        address: String,
+        name: String,
+        notes: Option<String>,
+    ) -> Result<()> {
+        let label = Label::new(LabelType::Addr, &address, name, notes).map_err(to_rpc_error)?;
         self.client
-            .history(&request.name, &request.enckey, offset, limit, reversed)
+            .set_labels(&request.wallet_name(), &request.enckey, vec![label])
             .map_err(to_rpc_error)
     }
 
+    fn remove_contact(&self, request: WalletRequest, address: String) -> Result<bool> {
+        self.remove_label(request, LabelType::Addr, address)
+    }
+
+    fn list_contacts(&self, request: WalletRequest) -> Result<Vec<Contact>> {
+        self.client
+            .contacts(&request.wallet_name(), &request.enckey)
+            .map_err(to_rpc_error)
+    }
+
+    fn set_label(
+        &self,
+        request: WalletRequest,
+        label_type: LabelType,
+        reference: String,
+        label: String,
+        notes: Option<String>,
+    ) -> Result<()> {
+        let label = Label::new(label_type, &reference, label, notes).map_err(to_rpc_error)?;
+        self.client
+            .set_labels(&request.wallet_name(), &request.enckey, vec![label])
+            .map_err(to_rpc_error)
+    }
+
+    fn remove_label(
+        &self,
+        request: WalletRequest,
+        label_type: LabelType,
+        reference: String,
+    ) -> Result<bool> {
+        self.client
+            .remove_label(
+                &request.wallet_name(),
+                &request.enckey,
+                label_type,
+                &reference,
+            )
+            .map_err(to_rpc_error)
+    }
+
+    fn list_labels(&self, request: WalletRequest) -> Result<Vec<Label>> {
+        self.client
+            .labels(&request.wallet_name(), &request.enckey)
+            .map_err(to_rpc_error)
+    }
+
+    fn export_labels(&self, request: WalletRequest) -> Result<String> {
+        let labels = self.list_labels(request)?;
+        labels_to_jsonl(&labels).map_err(to_rpc_error)
+    }
+
+    fn import_labels(&self, request: WalletRequest, labels: String) -> Result<usize> {
+        let labels = labels_from_jsonl(&labels).map_err(to_rpc_error)?;
+        let count = labels.len();
+        self.client
+            .set_labels(&request.wallet_name(), &request.enckey, labels)
+            .map_err(to_rpc_error)?;
+        Ok(count)
+    }
+
     fn get_enc_key(&self, request: CreateWalletRequest) -> Result<SecKey> {
         self.client
             .auth_token(&request.name, &request.passphrase)
@@ -530,7 +835,7 @@ where
     fn export(&self, request: WalletRequest) -> Result<WalletInfo> {
         let wallet_info = self
             .client
-            .export_wallet(&request.name, &request.enckey)
+            .export_wallet(&request.wallet_name(), &request.enckey)
             .map_err(to_rpc_error)?;
         Ok(wallet_info)
     }
@@ -545,6 +850,54 @@ where
         self.client.flush_database().map_err(to_rpc_error)?;
         ret
     }
+
+    fn backup(&self, request: WalletRequest) -> Result<String> {
+        self.client
+            .backup_wallet(&request.name, &request.enckey)
+            .map(base64::encode)
+            .map_err(to_rpc_error)
+    }
+
+    fn restore_backup(&self, request: CreateWalletRequest, backup: String) -> Result<SecKey> {
+        let backup = base64::decode(&backup).map_err(to_rpc_error)?;
+        let ret = self
+            .client
+            .restore_wallet_backup(&request.name, &request.passphrase, &backup)
+            .map_err(to_rpc_error);
+        self.client.flush_database().map_err(to_rpc_error)?;
+        ret
+    }
+
+    fn unlock(&self, request: CreateWalletRequest) -> Result<SecKey> {
+        let secret_service = self.secret_service()?;
+        let enckey = self
+            .client
+            .auth_token(&request.name, &request.passphrase)
+            .map_err(to_rpc_error)?;
+        secret_service.store(&enckey).map_err(to_rpc_error)
+    }
+
+    fn lock(&self, request: WalletRequest) -> Result<bool> {
+        self.secret_service()?
+            .remove(&request.enckey)
+            .map_err(to_rpc_error)
+    }
+}
+
+fn parse_inputs(inputs: &[String]) -> Result<Vec<TxoPointer>> {
+    inputs
+        .iter()
+        .map(|input| parse_output_reference(input))
+        .collect::<CommonResult<Vec<_>>>()
+        .map_err(to_rpc_error)
+}
+
+fn parse_shares(shares: &[SecUtf8]) -> Result<Vec<MnemonicShare>> {
+    shares
+        .iter()
+        .map(MnemonicShare::from_secstr)
+        .collect::<CommonResult<Vec<_>>>()
+        .map_err(to_rpc_error)
 }
 
 #[cfg(test)]
@@ -894,6 +1247,168 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn account_addresses_should_be_separated() {
+        let wallet_rpc = setup_wallet_rpc();
+        let (create_request, wallet_request) = create_wallet_request("Default", "123456");
+
+        wallet_rpc
+            .create(create_request, WalletKind::HD, Some(24))
+            .unwrap();
+        assert_eq!(
+            1,
+            wallet_rpc.create_account(wallet_request.clone()).unwrap()
+        );
+        assert_eq!(
+            vec![0, 1],
+            wallet_rpc.list_accounts(wallet_request.clone()).unwrap()
+        );
+
+        let account_request = WalletRequest {
+            account: 1,
+            ..wallet_request.clone()
+        };
+        assert!(wallet_rpc
+            .list_transfer_addresses(account_request.clone(), None, None, None)
+            .unwrap()
+            .is_empty());
+        wallet_rpc
+            .create_transfer_address(account_request.clone())
+            .unwrap();
+        assert_eq!(
+            1,
+            wallet_rpc
+                .list_transfer_addresses(account_request.clone(), None, None, None)
+                .unwrap()
+                .len()
+        );
+        assert_eq!(
+            1,
+            wallet_rpc
+                .list_transfer_addresses(wallet_request.clone(), None, None, None)
+                .unwrap()
+                .len()
+        );
+        assert_ne!(
+            wallet_rpc.get_view_key(wallet_request, false).unwrap(),
+            wallet_rpc.get_view_key(account_request, false).unwrap()
+        );
+    }
+
+    #[test]
+    fn xpub_wallet_should_derive_same_addresses() {
+        let wallet_rpc = setup_wallet_rpc();
+        let (create_request, wallet_request) = create_wallet_request("Default", "123456");
+        let (watch_create_request, watch_request) = create_wallet_request("Watch", "123456");
+
+        wallet_rpc
+            .create(create_request, WalletKind::HD, Some(24))
+            .unwrap();
+        let xpubs = wallet_rpc.export_xpub(wallet_request.clone()).unwrap();
+        let view_key = wallet_rpc
+            .get_view_key(wallet_request.clone(), true)
+            .unwrap();
+        wallet_rpc
+            .restore_xpub(watch_create_request, xpubs.clone(), SecUtf8::from(view_key))
+            .unwrap();
+
+        assert_eq!(
+            xpubs,
+            wallet_rpc.export_xpub(watch_request.clone()).unwrap()
+        );
+        assert_eq!(
+            wallet_rpc
+                .list_transfer_addresses(wallet_request.clone(), None, None, None)
+                .unwrap(),
+            wallet_rpc
+                .list_transfer_addresses(watch_request.clone(), None, None, None)
+                .unwrap()
+        );
+        assert_eq!(
+            wallet_rpc
+                .list_staking_addresses(wallet_request, None, None, None)
+                .unwrap(),
+            wallet_rpc
+                .list_staking_addresses(watch_request.clone(), None, None, None)
+                .unwrap()
+        );
+        assert!(wallet_rpc.create_account(watch_request).is_err());
+    }
+
+    #[test]
+    fn shamir_shares_should_restore_wallet() {
+        let wallet_rpc = setup_wallet_rpc();
+        let (create_request, wallet_request) = create_wallet_request("Default", "123456");
+        let (restore_request, restored_request) = create_wallet_request("Restored", "123456");
+
+        let (_, mnemonic) = wallet_rpc
+            .create(create_request, WalletKind::HD, Some(24))
+            .unwrap();
+        let mnemonic = Mnemonic::from_secstr(&SecUtf8::from(mnemonic.unwrap())).unwrap();
+        let shares = wallet_rpc
+            .create_shamir_shares(mnemonic, 3, 5)
+            .unwrap()
+            .into_iter()
+            .map(SecUtf8::from)
+            .collect::<Vec<_>>();
+        assert_eq!(5, shares.len());
+
+        let info = wallet_rpc
+            .validate_shamir_shares(shares[..2].to_vec())
+            .unwrap();
+        assert!(!info.recoverable);
+        assert!(wallet_rpc
+            .restore_shamir(restore_request.clone(), shares[..2].to_vec())
+            .is_err());
+
+        wallet_rpc
+            .restore_shamir(restore_request, shares[2..].to_vec())
+            .unwrap();
+        assert_eq!(
+            wallet_rpc
+                .list_transfer_addresses(wallet_request, None, None, None)
+                .unwrap(),
+            wallet_rpc
+                .list_transfer_addresses(restored_request, None, None, None)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn backup_should_restore_wallet() {
+        let wallet_rpc = setup_wallet_rpc();
+        let (create_request, wallet_request) = create_wallet_request("Default", "123456");
+
+        wallet_rpc
+            .create(create_request.clone(), WalletKind::HD, Some(24))
+            .unwrap();
+        let backup = wallet_rpc.backup(wallet_request.clone()).unwrap();
+        let addresses = wallet_rpc
+            .list_transfer_addresses(wallet_request.clone(), None, None, None)
+            .unwrap();
+
+        assert!(wallet_rpc
+            .restore_backup(create_request.clone(), backup.clone())
+            .is_err());
+        wallet_rpc.delete(create_request.clone()).unwrap();
+        assert!(wallet_rpc
+            .restore_backup(
+                CreateWalletRequest {
+                    passphrase: SecUtf8::from("654321"),
+                    ..create_request.clone()
+                },
+                backup.clone()
+            )
+            .is_err());
+        wallet_rpc.restore_backup(create_request, backup).unwrap();
+        assert_eq!(
+            addresses,
+            wallet_rpc
+                .list_transfer_addresses(wallet_request, None, None, None)
+                .unwrap()
+        );
+    }
+
     #[test]
     fn get_view_key_should_return_public_key() {
         let wallet_rpc = setup_wallet_rpc();
@@ -997,6 +1512,72 @@ pub mod tests {
         )
     }
 
+    #[test]
+    fn labels_should_be_exported_and_imported() {
+        let wallet_rpc = setup_wallet_rpc();
+        let (create_request, wallet_request) = create_wallet_request("Default", "123456");
+        wallet_rpc
+            .create(create_request, WalletKind::Basic, None)
+            .unwrap();
+
+        let address = "dcro1zz30nheum6vnug3mjs0j4kw4w739tca8cuqae2kdjmt8suhv693qcs3qyn";
+        wallet_rpc
+            .add_contact(
+                wallet_request.clone(),
+                address.to_owned(),
+                "alice".to_owned(),
+                None,
+            )
+            .unwrap();
+        assert!(wallet_rpc
+            .add_contact(
+                wallet_request.clone(),
+                "invalid".to_owned(),
+                "bob".to_owned(),
+                None,
+            )
+            .is_err());
+        wallet_rpc
+            .set_label(
+                wallet_request.clone(),
+                LabelType::Tx,
+                hex::encode([1u8; 32]),
+                "rent".to_owned(),
+                Some("march".to_owned()),
+            )
+            .unwrap();
+        assert_eq!(
+            1,
+            wallet_rpc
+                .list_contacts(wallet_request.clone())
+                .unwrap()
+                .len()
+        );
+
+        let exported = wallet_rpc.export_labels(wallet_request.clone()).unwrap();
+        assert_eq!(2, exported.lines().count());
+
+        let (create_request, other_request) = create_wallet_request("Other", "123456");
+        wallet_rpc
+            .create(create_request, WalletKind::Basic, None)
+            .unwrap();
+        assert_eq!(
+            2,
+            wallet_rpc
+                .import_labels(other_request.clone(), exported)
+                .unwrap()
+        );
+        assert_eq!(
+            wallet_rpc.list_labels(wallet_request).unwrap(),
+            wallet_rpc.list_labels(other_request.clone()).unwrap()
+        );
+
+        assert!(wallet_rpc
+            .remove_contact(other_request.clone(), address.to_owned())
+            .unwrap());
+        assert!(wallet_rpc.list_contacts(other_request).unwrap().is_empty());
+    }
+
     fn make_test_wallet_client(storage: MemoryStorage) -> TestWalletClient {
         let signer_manager = WalletSignerManager::new(storage.clone(), HwKeyService::default());
         let transaction_builder = DefaultWalletTransactionBuilder::new(
@@ -1032,6 +1613,7 @@ pub mod tests {
             WalletRequest {
                 name: name.to_owned(),
                 enckey: derive_enckey(&passphrase, name).unwrap(),
+                account: 0,
             },
         )
     }
@@ -1101,6 +1683,7 @@ pub mod tests {
             addrs[0].clone(),
             Coin::from(1_0000u32),
             vec![viewkey],
+            None,
         );
         assert!(send_result.is_err());
     }
@@ -148,6 +148,26 @@ CroResult cro_create_jsonrpc(CroJsonRpcPtr *rpc_out,
                              uint8_t network_id,
                              CroProgressPtr progress_callback);
 
+/**
+ * create json-rpc context with given storage settings
+ * rpc_out: null pointer which will be written
+ * example c-code)
+ *  CroJsonRpcPtr rpc= NULL;
+ *  cro_create_jsonrpc_with_storage(&rpc, ".storage", "sqlite", NULL, "ws://localhost:26657/websocket", 0xab, &progress);
+ * storage_backend: "sled" or "sqlite"
+ * secret_service_socket: unix socket of the secret service keeping the enckeys of unlocked wallets
+ * (null if none)
+ * other arguments are the same as in `cro_create_jsonrpc`
+ * # Safety
+ */
+CroResult cro_create_jsonrpc_with_storage(CroJsonRpcPtr *rpc_out,
+                                          const char *storage_dir_user,
+                                          const char *storage_backend_user,
+                                          const char *secret_service_socket_user,
+                                          const char *websocket_url_user,
+                                          uint8_t network_id,
+                                          CroProgressPtr progress_callback);
+
 /**
  * create staking address from bip44 hdwallet
  * # Safety
@@ -274,6 +294,20 @@ CroResult cro_extract_raw_address(CroAddressPtr address_ptr,
                                   uint8_t *address_output,
                                   uint32_t *address_output_length);
 
+/**
+ * freeze outputs of a wallet (which are then never selected as inputs automatically)
+ * inputs, input_count: outputs to freeze (`<transaction id>:<index>`)
+ * buf: error message (if failed)
+ * # Safety
+ */
+CroResult cro_freeze_utxo(CroJsonRpcPtr rpc_ptr,
+                          const char *name,
+                          const char *enckey,
+                          const char *const *inputs,
+                          uintptr_t input_count,
+                          char *buf,
+                          uintptr_t buf_size);
+
 /**
  * get address as string
  * minimum byte length 100 is necessary
@@ -349,6 +383,56 @@ CroResult cro_jsonrpc_call(const char *storage_dir,
 
 void cro_jsonrpc_call_dummy(ProgressCallback _progress_callback, ProgressWrapper _wrapper);
 
+/**
+ * list the transactions of a wallet with their labels, output labels and contacts
+ * buf: json array of transactions (same as `wallet_transactions`), error message if failed
+ * # Safety
+ */
+CroResult cro_labeled_history(CroJsonRpcPtr rpc_ptr,
+                              const char *name,
+                              const char *enckey,
+                              uint64_t offset,
+                              uint64_t limit,
+                              bool reversed,
+                              char *buf,
+                              uintptr_t buf_size);
+
+/**
+ * list the frozen outputs of a wallet
+ * buf: json array of outputs (`<transaction id>:<index>`), error message if failed
+ * # Safety
+ */
+CroResult cro_list_frozen_utxo(CroJsonRpcPtr rpc_ptr,
+                               const char *name,
+                               const char *enckey,
+                               char *buf,
+                               uintptr_t buf_size);
+
+/**
+ * list the labels of a wallet
+ * buf: json array of labels (`{"type", "ref", "label", "notes"}`), error message if failed
+ * # Safety
+ */
+CroResult cro_list_labels(CroJsonRpcPtr rpc_ptr,
+                          const char *name,
+                          const char *enckey,
+                          char *buf,
+                          uintptr_t buf_size);
+
+/**
+ * remove the label of a transaction, output or address of a wallet
+ * (arguments are the same as in `cro_set_label`)
+ * buf: `true` or `false` if there was no label (error message if failed)
+ * # Safety
+ */
+CroResult cro_remove_label(CroJsonRpcPtr rpc_ptr,
+                           const char *name,
+                           const char *enckey,
+                           const char *label_type,
+                           const char *reference,
+                           char *buf,
+                           uintptr_t buf_size);
+
 /**
  * # Safety
  */
@@ -367,6 +451,49 @@ CroResult cro_run_jsonrpc(CroJsonRpcPtr rpc_ptr,
                           uintptr_t buf_size,
                           const void *user_data);
 
+/**
+ * send amount to a transfer address from a wallet
+ * rpc_ptr: json-rpc context (see `cro_create_jsonrpc`)
+ * name, enckey: wallet name and enckey (hex, see `wallet_getEncKey`)
+ * to_address: transfer address   ex) dcro1xwjryuh85xejtc20qkwtesk0yjhfrwxjmwy6mmxjn0aevjhrw7dszselj5
+ * amount: carson unit   ex) 1 cro = 100000000 carson
+ * viewkeys, viewkey_count: hex encoded view keys of the receivers (besides the wallet's own)
+ * inputs, input_count: outputs to spend (`<transaction id>:<index>`), null to select automatically
+ * buf: transaction id (error message if failed)
+ * # Safety
+ */
+CroResult cro_send_to_address(CroJsonRpcPtr rpc_ptr,
+                              const char *name,
+                              const char *enckey,
+                              const char *to_address,
+                              uint64_t amount,
+                              const char *const *viewkeys,
+                              uintptr_t viewkey_count,
+                              const char *const *inputs,
+                              uintptr_t input_count,
+                              char *buf,
+                              uintptr_t buf_size);
+
+/**
+ * set the label of a transaction, output or address of a wallet
+ * rpc_ptr: json-rpc context (see `cro_create_jsonrpc`)
+ * name, enckey: wallet name and enckey (hex, see `wallet_getEncKey`)
+ * label_type: "tx" (reference: transaction id), "output" (reference: `<transaction id>:<index>`)
+ * or "addr" (reference: transfer or staking address)
+ * notes: null if none
+ * buf: error message (if failed)
+ * # Safety
+ */
+CroResult cro_set_label(CroJsonRpcPtr rpc_ptr,
+                        const char *name,
+                        const char *enckey,
+                        const char *label_type,
+                        const char *reference,
+                        const char *label,
+                        const char *notes,
+                        char *buf,
+                        uintptr_t buf_size);
+
 /**
  * add txin
  * txid_string: null terminated string, 64 length hex-char , 32 bytes
@@ -493,6 +620,19 @@ CroResult cro_unbond(uint8_t network,
                      uint8_t *output,
                      uint32_t *output_length);
 
+/**
+ * unfreeze outputs of a wallet
+ * (arguments are the same as in `cro_freeze_utxo`)
+ * # Safety
+ */
+CroResult cro_unfreeze_utxo(CroJsonRpcPtr rpc_ptr,
+                            const char *name,
+                            const char *enckey,
+                            const char *const *inputs,
+                            uintptr_t input_count,
+                            char *buf,
+                            uintptr_t buf_size);
+
 /**
  * staked -> staked
  * network: networkid   ex) 0xab
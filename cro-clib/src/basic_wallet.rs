@@ -10,20 +10,17 @@ use std::ptr;
 /// # Safety
 fn do_cro_basic_create_staking_address(private: PrivateKey) -> Box<CroAddress> {
     let public: PublicKey = PublicKey::from(&private);
-    let address: StakedStateAddress = StakedStateAddress::BasicRedeem(RedeemAddress::from(&public));
-    match address {
-        StakedStateAddress::BasicRedeem(redeem) => {
-            // redeem is 20 bytes
-            let raw = redeem.to_vec();
-            let ret = CroAddress {
-                privatekey: private,
-                publickey: public,
-                raw,
-                address: address.to_string(),
-            };
-            Box::new(ret)
-        }
-    }
+    let redeem = RedeemAddress::from(&public);
+    let address: StakedStateAddress = StakedStateAddress::BasicRedeem(redeem);
+    // redeem is 20 bytes
+    let raw = redeem.to_vec();
+    let ret = CroAddress {
+        privatekey: private,
+        publickey: public,
+        raw,
+        address: address.to_string(),
+    };
+    Box::new(ret)
 }
 
 /// create staking address
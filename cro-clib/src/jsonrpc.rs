@@ -8,8 +8,9 @@ use client_common::Result;
 use client_core::wallet::syncer::SyncerOptions;
 use client_rpc_core::{
     rpc::sync_rpc::{CBindingCallback, CBindingCore},
-    RpcHandler,
+    RpcHandler, StorageOptions,
 };
+use serde_json::{json, Value};
 
 use crate::types::get_string;
 use crate::types::CroResult;
@@ -74,6 +75,7 @@ pub unsafe extern "C" fn cro_jsonrpc_call(
 ) -> CroResult {
     let res = create_rpc(
         storage_dir,
+        StorageOptions::default(),
         websocket_url,
         network_id,
         progress_callback,
@@ -134,6 +136,7 @@ pub unsafe extern "C" fn cro_create_jsonrpc(
 ) -> CroResult {
     let mrpc = create_rpc(
         storage_dir_user,
+        StorageOptions::default(),
         websocket_url_user,
         network_id,
         progress_callback,
@@ -149,6 +152,56 @@ pub unsafe extern "C" fn cro_create_jsonrpc(
     }
 }
 
+/// create json-rpc context with given storage settings
+/// rpc_out: null pointer which will be written
+/// example c-code)
+///  CroJsonRpcPtr rpc= NULL;
+///  cro_create_jsonrpc_with_storage(&rpc, ".storage", "sqlite", NULL, "ws://localhost:26657/websocket", 0xab, &progress);
+/// storage_backend: "sled" or "sqlite"
+/// secret_service_socket: unix socket of the secret service keeping the enckeys of unlocked wallets
+/// (null if none)
+/// other arguments are the same as in `cro_create_jsonrpc`
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn cro_create_jsonrpc_with_storage(
+    rpc_out: *mut CroJsonRpcPtr,
+    storage_dir_user: *const c_char,
+    storage_backend_user: *const c_char,
+    secret_service_socket_user: *const c_char,
+    websocket_url_user: *const c_char,
+    network_id: u8,
+    progress_callback: CroProgressPtr,
+) -> CroResult {
+    let storage_options = get_string(storage_backend_user)
+        .parse()
+        .map(|backend| StorageOptions {
+            backend,
+            secret_service_socket: if secret_service_socket_user.is_null() {
+                None
+            } else {
+                Some(get_string(secret_service_socket_user))
+            },
+        });
+    let mrpc = storage_options.and_then(|storage_options| {
+        create_rpc(
+            storage_dir_user,
+            storage_options,
+            websocket_url_user,
+            network_id,
+            progress_callback,
+            ptr::null(),
+        )
+    });
+    match mrpc {
+        Ok(rpc) => {
+            let rpc_box = Box::new(rpc);
+            ptr::write(rpc_out, Box::into_raw(rpc_box));
+            CroResult::success()
+        }
+        _ => CroResult::fail(),
+    }
+}
+
 /// request: json rpc request
 /// example c code) const char* req = "{\"jsonrpc\": \"2.0\", \"method\": \"wallet_list\", \"params\": [], \"id\": 1}";
 /// buf: minimum 500 bytes
@@ -187,8 +240,55 @@ pub unsafe extern "C" fn cro_destroy_jsonrpc(rpc: CroJsonRpcPtr) -> CroResult {
     CroResult::success()
 }
 
+/// calls a method of json-rpc context and writes its json result (or error message) in buf
+/// (fails if the method returns an error or if the result doesn't fit in buf)
+pub(crate) unsafe fn call_jsonrpc_method(
+    rpc_ptr: CroJsonRpcPtr,
+    method: &str,
+    params: Value,
+    buf: *mut c_char,
+    buf_size: usize,
+) -> CroResult {
+    if rpc_ptr.is_null() || buf.is_null() {
+        return CroResult::fail();
+    }
+    let rpc = rpc_ptr.as_mut().expect("get json-rpc context");
+    let request = json!({"jsonrpc": "2.0", "method": method, "params": params, "id": 1});
+    let response = rpc
+        .handler
+        .handle(&request.to_string())
+        .and_then(|response| serde_json::from_str::<Value>(&response).ok())
+        .unwrap_or_default();
+
+    let (output, success) = match (response.get("result"), response.get("error")) {
+        (Some(result), None) => (result.to_string(), true),
+        (_, Some(error)) => (
+            error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown error")
+                .to_owned(),
+            false,
+        ),
+        _ => ("invalid json-rpc response".to_owned(), false),
+    };
+    let output = CString::new(output).unwrap_or_default();
+    let bytes = output.as_bytes_with_nul();
+    if bytes.len() > buf_size {
+        return CroResult::fail();
+    }
+    ptr::copy_nonoverlapping(bytes.as_ptr() as *const c_char, buf, bytes.len());
+
+    if success {
+        CroResult::success()
+    } else {
+        CroResult::fail()
+    }
+}
+
 unsafe fn create_rpc(
     storage_dir: *const c_char,
+    storage_options: StorageOptions,
     websocket_url: *const c_char,
     network_id: u8,
     progress_callback_user: CroProgressPtr,
@@ -228,6 +328,7 @@ unsafe fn create_rpc(
     };
     let handler = RpcHandler::new(
         &storage_dir,
+        storage_options,
         &websocket_url,
         network_id,
         options,
@@ -0,0 +1,121 @@
+use std::os::raw::c_char;
+
+use serde_json::{json, Value};
+
+use crate::jsonrpc::call_jsonrpc_method;
+use crate::types::get_string;
+use crate::types::{CroJsonRpcPtr, CroResult};
+
+/// set the label of a transaction, output or address of a wallet
+/// rpc_ptr: json-rpc context (see `cro_create_jsonrpc`)
+/// name, enckey: wallet name and enckey (hex, see `wallet_getEncKey`)
+/// label_type: "tx" (reference: transaction id), "output" (reference: `<transaction id>:<index>`)
+/// or "addr" (reference: transfer or staking address)
+/// notes: null if none
+/// buf: error message (if failed)
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn cro_set_label(
+    rpc_ptr: CroJsonRpcPtr,
+    name: *const c_char,
+    enckey: *const c_char,
+    label_type: *const c_char,
+    reference: *const c_char,
+    label: *const c_char,
+    notes: *const c_char,
+    buf: *mut c_char,
+    buf_size: usize,
+) -> CroResult {
+    let notes = if notes.is_null() {
+        Value::Null
+    } else {
+        Value::String(get_string(notes))
+    };
+    call_jsonrpc_method(
+        rpc_ptr,
+        "wallet_setLabel",
+        json!([
+            wallet_request(name, enckey),
+            get_string(label_type),
+            get_string(reference),
+            get_string(label),
+            notes
+        ]),
+        buf,
+        buf_size,
+    )
+}
+
+/// remove the label of a transaction, output or address of a wallet
+/// (arguments are the same as in `cro_set_label`)
+/// buf: `true` or `false` if there was no label (error message if failed)
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn cro_remove_label(
+    rpc_ptr: CroJsonRpcPtr,
+    name: *const c_char,
+    enckey: *const c_char,
+    label_type: *const c_char,
+    reference: *const c_char,
+    buf: *mut c_char,
+    buf_size: usize,
+) -> CroResult {
+    call_jsonrpc_method(
+        rpc_ptr,
+        "wallet_removeLabel",
+        json!([
+            wallet_request(name, enckey),
+            get_string(label_type),
+            get_string(reference)
+        ]),
+        buf,
+        buf_size,
+    )
+}
+
+/// list the labels of a wallet
+/// buf: json array of labels (`{"type", "ref", "label", "notes"}`), error message if failed
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn cro_list_labels(
+    rpc_ptr: CroJsonRpcPtr,
+    name: *const c_char,
+    enckey: *const c_char,
+    buf: *mut c_char,
+    buf_size: usize,
+) -> CroResult {
+    call_jsonrpc_method(
+        rpc_ptr,
+        "wallet_listLabels",
+        json!([wallet_request(name, enckey)]),
+        buf,
+        buf_size,
+    )
+}
+
+/// list the transactions of a wallet with their labels, output labels and contacts
+/// buf: json array of transactions (same as `wallet_transactions`), error message if failed
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn cro_labeled_history(
+    rpc_ptr: CroJsonRpcPtr,
+    name: *const c_char,
+    enckey: *const c_char,
+    offset: u64,
+    limit: u64,
+    reversed: bool,
+    buf: *mut c_char,
+    buf_size: usize,
+) -> CroResult {
+    call_jsonrpc_method(
+        rpc_ptr,
+        "wallet_transactions",
+        json!([wallet_request(name, enckey), offset, limit, reversed]),
+        buf,
+        buf_size,
+    )
+}
+
+pub(crate) unsafe fn wallet_request(name: *const c_char, enckey: *const c_char) -> Value {
+    json!({"name": get_string(name), "enckey": get_string(enckey)})
+}
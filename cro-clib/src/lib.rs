@@ -6,6 +6,8 @@ pub mod wallet;
 pub use chain_core::init::network::Network;
 pub mod fee;
 pub mod jsonrpc;
+pub mod label;
 pub mod transaction;
 pub mod transaction_deposit;
 pub mod transaction_staking;
+pub mod utxo;
@@ -0,0 +1,132 @@
+use std::os::raw::c_char;
+
+use serde_json::{json, Value};
+
+use crate::jsonrpc::call_jsonrpc_method;
+use crate::label::wallet_request;
+use crate::types::get_string;
+use crate::types::{CroJsonRpcPtr, CroResult};
+
+/// send amount to a transfer address from a wallet
+/// rpc_ptr: json-rpc context (see `cro_create_jsonrpc`)
+/// name, enckey: wallet name and enckey (hex, see `wallet_getEncKey`)
+/// to_address: transfer address   ex) dcro1xwjryuh85xejtc20qkwtesk0yjhfrwxjmwy6mmxjn0aevjhrw7dszselj5
+/// amount: carson unit   ex) 1 cro = 100000000 carson
+/// viewkeys, viewkey_count: hex encoded view keys of the receivers (besides the wallet's own)
+/// inputs, input_count: outputs to spend (`<transaction id>:<index>`), null to select automatically
+/// buf: transaction id (error message if failed)
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn cro_send_to_address(
+    rpc_ptr: CroJsonRpcPtr,
+    name: *const c_char,
+    enckey: *const c_char,
+    to_address: *const c_char,
+    amount: u64,
+    viewkeys: *const *const c_char,
+    viewkey_count: usize,
+    inputs: *const *const c_char,
+    input_count: usize,
+    buf: *mut c_char,
+    buf_size: usize,
+) -> CroResult {
+    let inputs = if inputs.is_null() {
+        Value::Null
+    } else {
+        json!(get_strings(inputs, input_count))
+    };
+    call_jsonrpc_method(
+        rpc_ptr,
+        "wallet_sendToAddress",
+        json!([
+            wallet_request(name, enckey),
+            get_string(to_address),
+            amount.to_string(),
+            get_strings(viewkeys, viewkey_count),
+            inputs
+        ]),
+        buf,
+        buf_size,
+    )
+}
+
+/// freeze outputs of a wallet (which are then never selected as inputs automatically)
+/// inputs, input_count: outputs to freeze (`<transaction id>:<index>`)
+/// buf: error message (if failed)
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn cro_freeze_utxo(
+    rpc_ptr: CroJsonRpcPtr,
+    name: *const c_char,
+    enckey: *const c_char,
+    inputs: *const *const c_char,
+    input_count: usize,
+    buf: *mut c_char,
+    buf_size: usize,
+) -> CroResult {
+    call_jsonrpc_method(
+        rpc_ptr,
+        "wallet_freezeUTxO",
+        json!([
+            wallet_request(name, enckey),
+            get_strings(inputs, input_count)
+        ]),
+        buf,
+        buf_size,
+    )
+}
+
+/// unfreeze outputs of a wallet
+/// (arguments are the same as in `cro_freeze_utxo`)
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn cro_unfreeze_utxo(
+    rpc_ptr: CroJsonRpcPtr,
+    name: *const c_char,
+    enckey: *const c_char,
+    inputs: *const *const c_char,
+    input_count: usize,
+    buf: *mut c_char,
+    buf_size: usize,
+) -> CroResult {
+    call_jsonrpc_method(
+        rpc_ptr,
+        "wallet_unfreezeUTxO",
+        json!([
+            wallet_request(name, enckey),
+            get_strings(inputs, input_count)
+        ]),
+        buf,
+        buf_size,
+    )
+}
+
+/// list the frozen outputs of a wallet
+/// buf: json array of outputs (`<transaction id>:<index>`), error message if failed
+/// # Safety
+#[no_mangle]
+pub unsafe extern "C" fn cro_list_frozen_utxo(
+    rpc_ptr: CroJsonRpcPtr,
+    name: *const c_char,
+    enckey: *const c_char,
+    buf: *mut c_char,
+    buf_size: usize,
+) -> CroResult {
+    call_jsonrpc_method(
+        rpc_ptr,
+        "wallet_listFrozenUTxO",
+        json!([wallet_request(name, enckey)]),
+        buf,
+        buf_size,
+    )
+}
+
+unsafe fn get_strings(src: *const *const c_char, count: usize) -> Vec<String> {
+    if count == 0 {
+        return vec![];
+    }
+    std::slice::from_raw_parts(src, count)
+        .iter()
+        .map(|item| get_string(*item).trim().to_owned())
+        .collect()
+}
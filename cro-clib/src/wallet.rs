@@ -72,24 +72,20 @@ pub unsafe extern "C" fn cro_create_staking_address(
         .seed
         .derive_key_pair(network, CroAccount::Staking as u32, index)
         .expect("derive key pair");
-    let address = StakedStateAddress::BasicRedeem(RedeemAddress::from(&public));
+    let redeem = RedeemAddress::from(&public);
+    let address = StakedStateAddress::BasicRedeem(redeem);
+    assert!(20 == redeem.0.len());
+    let raw = redeem.to_vec();
 
-    match address {
-        StakedStateAddress::BasicRedeem(redeem) => {
-            assert!(20 == redeem.0.len());
-            let raw = redeem.to_vec();
-
-            let ret = CroAddress {
-                privatekey: private,
-                publickey: public,
-                raw,
-                address: address.to_string(),
-            };
-            let address_box = Box::new(ret);
-            ptr::write(address_out, Box::into_raw(address_box));
-            CroResult::success()
-        }
-    }
+    let ret = CroAddress {
+        privatekey: private,
+        publickey: public,
+        raw,
+        address: address.to_string(),
+    };
+    let address_box = Box::new(ret);
+    ptr::write(address_out, Box::into_raw(address_box));
+    CroResult::success()
 }
 
 /// create utxo address from bip44 wallet, which is for withdrawal, transfer amount
@@ -186,6 +186,7 @@ impl VectorFactory {
             staking_index: 0,
             transfer_index: 0,
             viewkey_index: 0,
+            account: 0,
         };
         let (view_key, priv_key) = hd_key
             .seed
@@ -15,6 +15,10 @@ pub trait StreamWrite {
 /// Trait for reading values written using `StreamWrite`
 pub trait StreamRead: Decode {
     fn read_from<R: Read>(reader: R) -> io::Result<Self>;
+
+    /// Same as `read_from`, but values longer than `max_size` are skipped (so that the stream can
+    /// still be read) and an error is returned instead of allocating a buffer for them
+    fn read_from_bounded<R: Read>(reader: R, max_size: usize) -> io::Result<Self>;
 }
 
 impl<T: Encode> StreamWrite for T {
@@ -42,7 +46,8 @@ impl<T: Encode> StreamWrite for T {
         to_send.extend(&size.to_le_bytes());
         to_send.append(&mut bytes);
 
-        writer.write(&to_send)
+        writer.write_all(&to_send)?;
+        Ok(to_send.len())
     }
 }
 
@@ -59,4 +64,43 @@ impl<T: Decode> StreamRead for T {
 
         Self::decode(&mut buffer.as_slice()).map_err(|_| io::ErrorKind::Other.into())
     }
+
+    fn read_from_bounded<R: Read>(mut reader: R, max_size: usize) -> io::Result<Self> {
+        let mut size = [0u8; 4];
+        reader.read_exact(&mut size)?;
+        let size = u32::from_le_bytes(size);
+
+        if size as usize > max_size {
+            io::copy(&mut reader.by_ref().take(size.into()), &mut io::sink())?;
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "value is longer than the maximum size",
+            ));
+        }
+
+        let mut buffer = vec![0; size as usize];
+        reader.read_exact(&mut buffer)?;
+
+        Self::decode(&mut buffer.as_slice()).map_err(|_| io::ErrorKind::Other.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_bounded_read_skips_long_values() {
+        let mut bytes = Vec::new();
+        vec![1u8; 100].write_to(&mut bytes).unwrap();
+        vec![2u8; 10].write_to(&mut bytes).unwrap();
+        let mut reader = bytes.as_slice();
+
+        assert!(Vec::<u8>::read_from_bounded(&mut reader, 50).is_err());
+        assert_eq!(
+            vec![2u8; 10],
+            Vec::<u8>::read_from_bounded(&mut reader, 50).unwrap()
+        );
+        assert!(reader.is_empty());
+    }
 }
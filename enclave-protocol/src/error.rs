@@ -4,6 +4,7 @@ pub enum Error {
     HexIdMisMatch,
     EmptySealedLog,
     EmptyRequestAccount,
+    StaleObfuscationKey,
 }
 
 impl fmt::Display for Error {
@@ -13,6 +14,7 @@ impl fmt::Display for Error {
             HexIdMisMatch => write!(f, "hex id mismatch"),
             EmptySealedLog => write!(f, "sealed log is empty"),
             EmptyRequestAccount => write!(f, "request account is empty"),
+            StaleObfuscationKey => write!(f, "obfuscation key is not newer than the current one"),
         }
     }
 }
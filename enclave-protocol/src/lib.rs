@@ -3,6 +3,7 @@
 
 pub mod codec;
 pub mod error;
+pub mod obfuscation_keys;
 #[cfg(feature = "edp")]
 pub mod tdbe_protocol;
 
@@ -16,6 +17,7 @@ use chain_core::state::account::DepositBondTx;
 use chain_core::state::account::StakedState;
 use chain_core::state::account::StakedStateOpWitness;
 use chain_core::state::account::WithdrawUnbondedTx;
+use chain_core::state::tendermint::BlockHeight;
 use chain_core::tx::data::input::TxoPointer;
 use chain_core::tx::data::{Tx, TxId};
 use chain_core::tx::witness::TxWitness;
@@ -61,6 +63,16 @@ pub enum IntraEnclaveRequest {
     },
     EndBlock,
     Encrypt(Box<IntraEncryptRequest>),
+    /// obfuscation key of a newly committed TDBE epoch.
+    /// chain-abci has to pass it to all the validators at the same block height (known from
+    /// the committed MLS handshakes) -- that's not supported yet, as MLS handshake transactions
+    /// are still rejected (`TxError::WIPMLSData`), so payloads only use the initial key for now.
+    NewObfuscationKey {
+        /// the height from which the key is used (`TxObfuscated::key_from`)
+        key_from: BlockHeight,
+        /// the key sealed by TDBE (with `obfuscation_key_seal_id(key_from)` as the additional data)
+        sealed_key: SealedLog,
+    },
 }
 
 impl IntraEnclaveRequest {
@@ -131,6 +143,15 @@ pub fn is_basic_valid_tx_request(
     }
 }
 
+/// view key filters of the block
+#[derive(Encode, Decode)]
+pub struct RawBlockFilter {
+    /// legacy bloom filter
+    pub bloom: TxFilter,
+    /// serialized Golomb-coded set
+    pub gcs: Vec<u8>,
+}
+
 /// positive response from the enclave
 #[derive(Encode, Decode)]
 pub enum IntraEnclaveResponseOk {
@@ -140,10 +161,12 @@ pub enum IntraEnclaveResponseOk {
     TxWithOutputs { paid_fee: Fee, sealed_tx: SealedLog },
     /// deposit stake pays minimal fee, so this returns the sum of input amounts -- staked stake's bonded balance is added `input_coins-min_fee`
     DepositStakeTx { input_coins: Coin },
-    /// transaction filter
-    EndBlock(Option<Box<TxFilter>>),
+    /// transaction filters
+    EndBlock(Option<Box<RawBlockFilter>>),
     /// encryption response
     Encrypt(TxObfuscated),
+    /// the new obfuscation key was installed
+    NewObfuscationKey,
 }
 
 /// variable length response returned from the tx-validation enclave
@@ -173,6 +196,9 @@ pub struct QueryEncryptRequest {
     pub op_sig: Option<StakedStateOpWitness>,
 }
 
+/// maximum number of blocks that can be requested in one `EnclaveRequest::GetBlockTxIds`
+pub const MAX_BLOCK_TXIDS_RANGE: u64 = 100;
+
 /// requests sent from tx-query to chain-abci tx validation enclave app wrapper
 #[derive(Encode, Decode)]
 pub enum EnclaveRequest {
@@ -180,6 +206,37 @@ pub enum EnclaveRequest {
     GetSealedTxData { txids: Vec<TxId> },
     /// request to encrypt tx by the current key (requested by TQE -- they should be on the same machine)
     EncryptTx(Box<QueryEncryptRequest>),
+    /// request to get the committed transaction IDs of blocks in the inclusive height range
+    /// (requested by TDBE -- at most `MAX_BLOCK_TXIDS_RANGE` blocks)
+    GetBlockTxIds {
+        start_height: BlockHeight,
+        end_height: BlockHeight,
+    },
+    /// request to store transaction data fetched from other TDBE servers (sealed to "mrsigner") and
+    /// the sync cursor (the next height to fetch; requested by TDBE after the sealed logs of
+    /// a whole page are stored). The whole request is rejected unless all the transactions
+    /// were committed with outputs and don't have their sealed logs stored yet.
+    StoreSealedTxData {
+        sealed_logs: Vec<(TxId, SealedLog)>,
+        sync_cursor: Option<BlockHeight>,
+    },
+    /// request to get the last stored TDBE sync cursor (requested by TDBE for resuming
+    /// an interrupted sync)
+    GetTdbeSyncCursor,
+}
+
+/// transaction IDs committed in a block, as stored in the block's transaction merkle tree
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+pub struct BlockTxIds {
+    /// height of the block
+    pub block_height: BlockHeight,
+    /// root of the transaction merkle tree (the one used in the app hash computation)
+    pub txs_root: H256,
+    /// all transaction IDs in the block (leaves of the merkle tree, in order)
+    pub txids: Vec<TxId>,
+    /// IDs of the transactions in the block which created transaction outputs
+    /// (i.e. the ones whose payloads TDBE servers need to hold)
+    pub output_txids: Vec<TxId>,
 }
 
 pub type VerifyOk = (Fee, Option<StakedState>, Option<Box<SealedLog>>);
@@ -192,6 +249,12 @@ pub enum EnclaveResponse {
     GetSealedTxData(Option<Vec<SealedLog>>),
     /// returns Ok(encrypted tx payload) if Tx was valid
     EncryptTx(Result<TxObfuscated, chain_tx_validation::Error>),
+    /// returns Some(transaction IDs of every requested block) or None (if any block was not found / range is invalid)
+    GetBlockTxIds(Option<Vec<BlockTxIds>>),
+    /// returns true if the sealed logs (and the sync cursor) were stored
+    StoreSealedTxData(bool),
+    /// returns the last stored TDBE sync cursor (if any)
+    GetTdbeSyncCursor(Option<BlockHeight>),
     /// response if the enclave failed to parse the request
     UnknownRequest,
 }
@@ -0,0 +1,175 @@
+//! Transaction obfuscation keys rotated by TDBE
+//!
+//! Each committed MLS epoch in TDBE exports a new obfuscation key which is used
+//! for obfuscating new transaction payloads from a given block height
+//! (`TxObfuscated::key_from`). Keys of the previous epochs are kept,
+//! so that historical payloads can still be deobfuscated.
+//!
+//! Whether a payload is valid must not depend on the node, so a new payload validated
+//! at some block height has to be obfuscated with the key active at that height
+//! (the latest one with `key_from` not after it): keys which aren't active yet are never used
+//! and the older ones are retired for new payloads.
+use std::collections::BTreeMap;
+
+use chain_core::common::H256;
+use chain_core::state::tendermint::BlockHeight;
+use parity_scale_codec::Encode;
+use zeroize::Zeroize;
+
+use crate::error::Error as PError;
+
+/// length of the obfuscation key (AES-128-GCM-SIV)
+pub const OBFUSCATION_KEY_LEN: usize = 16;
+
+/// obfuscation key
+pub type ObfuscationKey = [u8; OBFUSCATION_KEY_LEN];
+
+/// the additional data the obfuscation key is sealed with when it's passed
+/// from TDBE to the tx-validation enclave (binds the sealed key to its `key_from` height)
+pub fn obfuscation_key_seal_id(key_from: BlockHeight) -> H256 {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(b"obfuscationkey");
+    hasher.update(&key_from.encode());
+    hasher.finalize().into()
+}
+
+/// All the known obfuscation keys indexed by the height from which they are used
+#[derive(Default)]
+pub struct ObfuscationKeyring {
+    keys: BTreeMap<BlockHeight, ObfuscationKey>,
+}
+
+impl ObfuscationKeyring {
+    /// keyring with the initial key (used until the first rotation)
+    pub fn new(key_from: BlockHeight, key: ObfuscationKey) -> Self {
+        let mut keys = BTreeMap::new();
+        keys.insert(key_from, key);
+        ObfuscationKeyring { keys }
+    }
+
+    /// adds a key of a newly committed epoch
+    /// (it has to be used from a height after all the existing keys)
+    pub fn insert(&mut self, key_from: BlockHeight, key: ObfuscationKey) -> Result<(), PError> {
+        match self.current() {
+            Some((latest, _)) if latest >= key_from => Err(PError::StaleObfuscationKey),
+            _ => {
+                self.keys.insert(key_from, key);
+                Ok(())
+            }
+        }
+    }
+
+    /// the latest key (used for obfuscating new payloads) with its `key_from` height
+    pub fn current(&self) -> Option<(BlockHeight, &ObfuscationKey)> {
+        self.keys
+            .iter()
+            .next_back()
+            .map(|(key_from, key)| (*key_from, key))
+    }
+
+    /// the key active at given height (used for obfuscating new payloads) with its `key_from` height
+    pub fn active(&self, block_height: BlockHeight) -> Option<(BlockHeight, &ObfuscationKey)> {
+        self.keys
+            .range(..=block_height)
+            .next_back()
+            .map(|(key_from, key)| (*key_from, key))
+    }
+
+    /// the key a new payload validated at given height was obfuscated with
+    /// (none if `key_from` doesn't select the key active at that height)
+    pub fn get_for_new_payload(
+        &self,
+        key_from: BlockHeight,
+        block_height: BlockHeight,
+    ) -> Option<&ObfuscationKey> {
+        if key_from > block_height {
+            return None;
+        }
+        match self.active(block_height) {
+            Some((active_from, key)) if active_from == key_from => Some(key),
+            _ => None,
+        }
+    }
+
+    /// the key a payload with given `key_from` was obfuscated with
+    pub fn get(&self, key_from: BlockHeight) -> Option<&ObfuscationKey> {
+        self.keys.get(&key_from)
+    }
+
+    /// number of known keys
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// if there are no keys
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl Drop for ObfuscationKeyring {
+    fn drop(&mut self) {
+        for key in self.keys.values_mut() {
+            key.zeroize();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_keyring_rotation() {
+        let mut keyring = ObfuscationKeyring::new(BlockHeight::genesis(), [0; 16]);
+        assert_eq!(keyring.current(), Some((BlockHeight::genesis(), &[0; 16])));
+
+        assert!(keyring.insert(10.into(), [1; 16]).is_ok());
+        assert!(keyring.insert(20.into(), [2; 16]).is_ok());
+        assert!(keyring.insert(20.into(), [3; 16]).is_err());
+        assert!(keyring.insert(15.into(), [3; 16]).is_err());
+
+        assert_eq!(keyring.len(), 3);
+        assert_eq!(keyring.current(), Some((20.into(), &[2; 16])));
+        assert_eq!(keyring.get(BlockHeight::genesis()), Some(&[0; 16]));
+        assert_eq!(keyring.get(10.into()), Some(&[1; 16]));
+        // only the exact `key_from` heights are valid
+        assert_eq!(keyring.get(15.into()), None);
+    }
+
+    #[test]
+    fn check_new_payloads_use_active_key() {
+        let mut keyring = ObfuscationKeyring::new(BlockHeight::genesis(), [0; 16]);
+        keyring.insert(10.into(), [1; 16]).unwrap();
+
+        assert_eq!(
+            keyring.active(9.into()),
+            Some((BlockHeight::genesis(), &[0; 16]))
+        );
+        assert_eq!(keyring.active(10.into()), Some((10.into(), &[1; 16])));
+        assert_eq!(
+            keyring.get_for_new_payload(BlockHeight::genesis(), 9.into()),
+            Some(&[0; 16])
+        );
+        // the key isn't active yet
+        assert_eq!(keyring.get_for_new_payload(10.into(), 9.into()), None);
+        assert_eq!(
+            keyring.get_for_new_payload(10.into(), 11.into()),
+            Some(&[1; 16])
+        );
+        // the key is retired for new payloads
+        assert_eq!(
+            keyring.get_for_new_payload(BlockHeight::genesis(), 10.into()),
+            None
+        );
+        assert_eq!(keyring.get(BlockHeight::genesis()), Some(&[0; 16]));
+    }
+
+    #[test]
+    fn check_seal_id_binds_height() {
+        assert_ne!(
+            obfuscation_key_seal_id(BlockHeight::genesis()),
+            obfuscation_key_seal_id(1.into())
+        );
+    }
+}
@@ -1,8 +1,14 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::BTreeSet};
 
 use parity_scale_codec::{Decode, Encode};
 
-use chain_core::tx::{data::TxId, TxWithOutputs};
+use chain_core::{
+    common::MerkleTree,
+    state::tendermint::BlockHeight,
+    tx::{data::TxId, TxWithOutputs},
+};
+
+use crate::BlockTxIds;
 
 /// TDBE request initialized from other TDBE servers (enclave-to-enclave communication)
 #[derive(Encode, Decode)]
@@ -12,6 +18,15 @@ pub enum TrustedTdbeRequest<'a> {
         /// Transaction IDs for which to fetch all the transactions
         transaction_ids: Cow<'a, [TxId]>,
     },
+    /// Fetch one page of transactions with outputs committed in a range of blocks
+    GetTransactionsInBlockRange {
+        /// Height of the first block in the page (i.e. the sync cursor)
+        start_height: BlockHeight,
+        /// Height of the last block in the whole range (inclusive)
+        end_height: BlockHeight,
+        /// Soft limit on number of transactions in the page (the page always contains whole blocks)
+        page_size: u32,
+    },
 }
 
 /// Response for `TrustedTdbeRequest`
@@ -22,6 +37,8 @@ pub enum TrustedTdbeResponse<'a> {
         /// Requested transactions
         transactions: Vec<TxWithOutputs>,
     },
+    /// Contains one page of transactions in the requested block range
+    GetTransactionsInBlockRange(TransactionsPage),
     /// Error response from TDBE
     Error {
         /// Error message
@@ -29,6 +46,107 @@ pub enum TrustedTdbeResponse<'a> {
     },
 }
 
+/// Transaction data committed in one block
+#[derive(Encode, Decode)]
+pub struct BlockTransactions {
+    /// Height of the block
+    pub block_height: BlockHeight,
+    /// All transaction IDs in the block (leaves of the block's transaction merkle tree, in order)
+    pub transaction_ids: Vec<TxId>,
+    /// Transactions with outputs in the block
+    pub transactions: Vec<TxWithOutputs>,
+}
+
+/// One page of transaction data returned for `TrustedTdbeRequest::GetTransactionsInBlockRange`
+#[derive(Encode, Decode)]
+pub struct TransactionsPage {
+    /// Consecutive blocks starting at the requested height
+    pub blocks: Vec<BlockTransactions>,
+    /// Height to continue from in the next request (`None` if the requested range was exhausted)
+    pub next_height: Option<BlockHeight>,
+}
+
+impl TransactionsPage {
+    /// Checks the page returned for the given range against transaction IDs committed on chain
+    /// (`local_blocks` are looked up by block height from the local node): the transaction IDs
+    /// need to match the block's merkle root and the page needs to contain every transaction
+    /// with outputs of the block (and nothing else)
+    pub fn verify<'a, F>(
+        &self,
+        start_height: BlockHeight,
+        end_height: BlockHeight,
+        local_blocks: F,
+    ) -> Result<(), String>
+    where
+        F: Fn(BlockHeight) -> Option<&'a BlockTxIds>,
+    {
+        if self.blocks.is_empty() {
+            return Err("Page does not contain any block".to_owned());
+        }
+
+        let mut expected_height = start_height;
+
+        for block in self.blocks.iter() {
+            if block.block_height != expected_height || block.block_height > end_height {
+                return Err(format!(
+                    "Unexpected block height in page: {}",
+                    block.block_height
+                ));
+            }
+
+            let local_block = local_blocks(block.block_height).ok_or_else(|| {
+                format!(
+                    "Transaction IDs not found for block: {}",
+                    block.block_height
+                )
+            })?;
+
+            if MerkleTree::new(block.transaction_ids.clone()).root_hash() != local_block.txs_root {
+                return Err(format!(
+                    "Transaction IDs do not match merkle root of block: {}",
+                    block.block_height
+                ));
+            }
+
+            let mut received_txids = BTreeSet::new();
+
+            for transaction in block.transactions.iter() {
+                if !received_txids.insert(transaction.id()) {
+                    return Err(format!(
+                        "Duplicate transaction in block: {}",
+                        block.block_height
+                    ));
+                }
+            }
+
+            let expected_txids = local_block.output_txids.iter().copied().collect();
+
+            if received_txids != expected_txids {
+                return Err(format!(
+                    "Transactions do not match the ones with outputs in block: {}",
+                    block.block_height
+                ));
+            }
+
+            expected_height = expected_height
+                .checked_add(1)
+                .ok_or_else(|| "Block height overflow".to_owned())?;
+        }
+
+        let expected_next_height = if expected_height > end_height {
+            None
+        } else {
+            Some(expected_height)
+        };
+
+        if self.next_height != expected_next_height {
+            return Err("Unexpected next height in page".to_owned());
+        }
+
+        Ok(())
+    }
+}
+
 /// TDBE request initialized by untrusted sources
 #[derive(Encode, Decode)]
 pub enum UntrustedTdbeRequest {
@@ -50,3 +168,122 @@ pub enum UntrustedTdbeResponse<'a> {
         message: Cow<'a, str>,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chain_core::tx::data::{attribute::TxAttributes, input::TxoPointer, Tx};
+
+    fn transaction(n: u8) -> TxWithOutputs {
+        TxWithOutputs::Transfer(Tx::new_with(
+            vec![TxoPointer::new([n; 32], 0)],
+            vec![],
+            TxAttributes::new(0),
+        ))
+    }
+
+    fn block(
+        height: u64,
+        transactions: Vec<TxWithOutputs>,
+        public_txids: &[TxId],
+    ) -> BlockTransactions {
+        let mut transaction_ids = transactions
+            .iter()
+            .map(TxWithOutputs::id)
+            .collect::<Vec<_>>();
+        transaction_ids.extend_from_slice(public_txids);
+
+        BlockTransactions {
+            block_height: height.into(),
+            transaction_ids,
+            transactions,
+        }
+    }
+
+    fn local_block(block: &BlockTransactions) -> BlockTxIds {
+        BlockTxIds {
+            block_height: block.block_height,
+            txs_root: MerkleTree::new(block.transaction_ids.clone()).root_hash(),
+            txids: block.transaction_ids.clone(),
+            output_txids: block.transactions.iter().map(TxWithOutputs::id).collect(),
+        }
+    }
+
+    fn page() -> TransactionsPage {
+        TransactionsPage {
+            blocks: vec![
+                block(3, vec![transaction(0), transaction(1)], &[[9; 32]]),
+                block(4, vec![], &[]),
+            ],
+            next_height: Some(5.into()),
+        }
+    }
+
+    fn verify(
+        page: &TransactionsPage,
+        local_blocks: &[BlockTxIds],
+        start_height: u64,
+        end_height: u64,
+    ) -> Result<(), String> {
+        page.verify(start_height.into(), end_height.into(), |height| {
+            local_blocks
+                .iter()
+                .find(|block| block.block_height == height)
+        })
+    }
+
+    #[test]
+    fn check_valid_page() {
+        let page = page();
+        let local_blocks = page.blocks.iter().map(local_block).collect::<Vec<_>>();
+
+        assert!(verify(&page, &local_blocks, 3, 10).is_ok());
+        assert!(verify(&page, &local_blocks, 3, 4).is_err());
+    }
+
+    #[test]
+    fn check_omitted_transaction_id() {
+        let mut page = page();
+        let local_blocks = page.blocks.iter().map(local_block).collect::<Vec<_>>();
+        page.blocks[0].transaction_ids.pop();
+
+        assert!(verify(&page, &local_blocks, 3, 10).is_err());
+    }
+
+    #[test]
+    fn check_omitted_transaction() {
+        let mut page = page();
+        let local_blocks = page.blocks.iter().map(local_block).collect::<Vec<_>>();
+        page.blocks[0].transactions.pop();
+
+        assert!(verify(&page, &local_blocks, 3, 10).is_err());
+    }
+
+    #[test]
+    fn check_duplicate_transaction() {
+        let mut page = page();
+        let local_blocks = page.blocks.iter().map(local_block).collect::<Vec<_>>();
+        page.blocks[0].transactions.pop();
+        page.blocks[0].transactions.push(transaction(0));
+
+        assert!(verify(&page, &local_blocks, 3, 10).is_err());
+    }
+
+    #[test]
+    fn check_transaction_not_in_block() {
+        let mut page = page();
+        let local_blocks = page.blocks.iter().map(local_block).collect::<Vec<_>>();
+        page.blocks[1].transactions.push(transaction(2));
+
+        assert!(verify(&page, &local_blocks, 3, 10).is_err());
+    }
+
+    #[test]
+    fn check_skipped_block() {
+        let page = page();
+        let local_blocks = page.blocks.iter().map(local_block).collect::<Vec<_>>();
+
+        assert!(verify(&page, &local_blocks, 2, 10).is_err());
+    }
+}
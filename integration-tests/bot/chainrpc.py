@@ -167,6 +167,19 @@ class Wallet:
         :param name: Name of the wallet. [default: Default]'''
         return self.client.call('wallet_listUTxO', [name, enckey or get_enckey()])
 
+    def freeze_utxo(self, inputs, name=DEFAULT_WALLET, enckey=None):
+        '''freeze unspent outputs, so that they are never selected as inputs
+        :param inputs: list of <transaction id>:<output index>'''
+        return self.client.call('wallet_freezeUTxO', [name, enckey or get_enckey()], inputs)
+
+    def unfreeze_utxo(self, inputs, name=DEFAULT_WALLET, enckey=None):
+        '''unfreeze unspent outputs
+        :param inputs: list of <transaction id>:<output index>'''
+        return self.client.call('wallet_unfreezeUTxO', [name, enckey or get_enckey()], inputs)
+
+    def frozen_utxo(self, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('wallet_listFrozenUTxO', [name, enckey or get_enckey()])
+
     def create(self, name=DEFAULT_WALLET, type='Basic', passphrase=None):
         '''create wallet
         :param name: Name of the wallet. [defualt: Default]
@@ -203,11 +216,76 @@ class Wallet:
     def transactions(self, name=DEFAULT_WALLET, offset=0, limit=100, reversed=False, enckey=None):
         return self.client.call('wallet_transactions', [name, enckey or get_enckey()], offset, limit, reversed)
 
-    def send(self, to_address, amount, name=DEFAULT_WALLET, view_keys=None, enckey=None):
+    def add_contact(self, address, contact_name, notes=None, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('wallet_addContact', [name, enckey or get_enckey()], address, contact_name, notes)
+
+    def remove_contact(self, address, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('wallet_removeContact', [name, enckey or get_enckey()], address)
+
+    def contacts(self, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('wallet_listContacts', [name, enckey or get_enckey()])
+
+    def set_label(self, type, ref, label, notes=None, name=DEFAULT_WALLET, enckey=None):
+        '''label transaction, output or address
+        :param type: tx|output|addr
+        :param ref: transaction id, <transaction id>:<output index> or address'''
+        return self.client.call('wallet_setLabel', [name, enckey or get_enckey()], type, ref, label, notes)
+
+    def remove_label(self, type, ref, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('wallet_removeLabel', [name, enckey or get_enckey()], type, ref)
+
+    def labels(self, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('wallet_listLabels', [name, enckey or get_enckey()])
+
+    def export_labels(self, name=DEFAULT_WALLET, enckey=None):
+        '''export labels as BIP-329 JSON lines'''
+        return self.client.call('wallet_exportLabels', [name, enckey or get_enckey()])
+
+    def import_labels(self, labels, name=DEFAULT_WALLET, enckey=None):
+        '''import labels from BIP-329 JSON lines'''
+        return self.client.call('wallet_importLabels', [name, enckey or get_enckey()], labels)
+
+    def send(self, to_address, amount, name=DEFAULT_WALLET, view_keys=None, enckey=None, inputs=None):
+        '''send amount to a transfer address
+        :param inputs: list of <transaction id>:<output index> to spend [default: selected automatically]'''
         return self.client.call(
             'wallet_sendToAddress',
             [name, enckey or get_enckey()],
-            to_address, str(amount), view_keys or [])
+            to_address, str(amount), view_keys or [], inputs)
+
+    def schedule_payment(self, to_address, amount, cadence, start, end=None,
+                         name=DEFAULT_WALLET, view_keys=None, enckey=None):
+        '''schedule recurring payment
+        :param cadence: once|daily|weekly|monthly
+        :param start: unix timestamp of the first payment
+        :param end: no payment is due after this unix timestamp'''
+        return self.client.call(
+            'wallet_schedulePayment',
+            [name, enckey or get_enckey()],
+            {
+                'to_address': to_address,
+                'amount': str(amount),
+                'view_keys': view_keys or [],
+                'cadence': cadence,
+                'start': start,
+                'end': end,
+            })
+
+    def schedule_list(self, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('wallet_scheduleList', [name, enckey or get_enckey()])
+
+    def schedule_pause(self, id, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('wallet_schedulePause', [name, enckey or get_enckey()], id)
+
+    def schedule_resume(self, id, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('wallet_scheduleResume', [name, enckey or get_enckey()], id)
+
+    def schedule_cancel(self, id, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('wallet_scheduleCancel', [name, enckey or get_enckey()], id)
+
+    def schedule_unregister(self, name=DEFAULT_WALLET, enckey=None):
+        '''stop executing the scheduled payments of the wallet (until next schedule call)'''
+        return self.client.call('wallet_scheduleUnregister', [name, enckey or get_enckey()])
 
     def sync(self, name=DEFAULT_WALLET, enckey=None):
         return self.client.call('sync', [name, enckey or get_enckey()],{"blocking":True, "reset":False, "do_loop":False})
@@ -272,6 +350,21 @@ class Staking:
             keypackage
         )
 
+    def history(self, address=None, offset=0, limit=100, reversed=False, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call(
+            'staking_history',
+            [name, enckey or get_enckey()],
+            fix_address(address) if address else None,
+            offset, limit, reversed
+        )
+
+    def export_history(self, address=None, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call(
+            'staking_exportHistory',
+            [name, enckey or get_enckey()],
+            fix_address(address) if address else None
+        )
+
     def build_raw_transfer_tx(self, to_address, amount, name=DEFAULT_WALLET,  enckey=None, viewkeys=[]):
         return self.client.call('wallet_buildRawTransferTx', [name, enckey or get_enckey()], to_address, amount, viewkeys)
 
@@ -335,6 +428,63 @@ class MultiSig:
             session_id,
             unsigned_transaction)
 
+    def create_coordinated_session(self, coordinator, unsigned_transaction, signer_public_keys, self_public_key,
+                                   name=DEFAULT_WALLET, enckey=None):
+        return self.client.call(
+            'multiSig_createCoordinatedSession',
+            [name, enckey or get_enckey()],
+            coordinator,
+            unsigned_transaction,
+            signer_public_keys,
+            self_public_key)
+
+    def get_coordinated_session(self, coordinator, session_id, self_public_key, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call(
+            'multiSig_getCoordinatedSession', [name, enckey or get_enckey()], coordinator, session_id, self_public_key)
+
+    def join_coordinated_session(self, coordinator, session_id, self_public_key, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call(
+            'multiSig_joinCoordinatedSession', [name, enckey or get_enckey()], coordinator, session_id, self_public_key)
+
+    def progress_coordinated_session(self, session_id, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('multiSig_progressCoordinatedSession', [name, enckey or get_enckey()], session_id)
+
+    def new_threshold_key(self, context, public_keys, self_public_key, threshold, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call(
+            'multiSig_newThresholdKey',
+            [name, enckey or get_enckey()],
+            context,
+            public_keys,
+            self_public_key,
+            threshold)
+
+    def threshold_key_shares(self, context, round1, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('multiSig_thresholdKeyShares', [name, enckey or get_enckey()], context, round1)
+
+    def finish_threshold_key(self, context, shares, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('multiSig_finishThresholdKey', [name, enckey or get_enckey()], context, shares)
+
+    def new_threshold_session(self, address, message, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('multiSig_newThresholdSession', [name, enckey or get_enckey()], address, message)
+
+    def add_threshold_commitment(self, session_id, commitment, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call(
+            'multiSig_addThresholdCommitment', [name, enckey or get_enckey()], session_id, commitment)
+
+    def threshold_partial_sign(self, session_id, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call('multiSig_thresholdPartialSign', [name, enckey or get_enckey()], session_id)
+
+    def add_threshold_partial_signature(self, session_id, partial_signature, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call(
+            'multiSig_addThresholdPartialSignature', [name, enckey or get_enckey()], session_id, partial_signature)
+
+    def broadcast_with_threshold_signature(self, session_id, unsigned_transaction, name=DEFAULT_WALLET, enckey=None):
+        return self.client.call(
+            'multiSig_broadcastWithThresholdSignature',
+            [name, enckey or get_enckey()],
+            session_id,
+            unsigned_transaction)
+
 
 class Blockchain:
     def __init__(self, tendermint_port):
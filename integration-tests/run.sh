@@ -116,7 +116,12 @@ mkdir data
 
 if [ $BUILD_MODE == "sgx" ]; then
     echo "Starting ra-sp-server..."
-    (set +e; while true; do ra-sp-server --ias-key $IAS_API_KEY --quote-type Unlinkable --spid $SPID; done) &
+    # RA_SP_SERVER_ARGS can be used to record (`--record-dir`) or replay (`--replay-dir`) attestation responses
+    if [ -z "$IAS_API_KEY" ]; then
+        # Offline: replay responses recorded in RA_SP_REPLAY_DIR, quotes without a recording get mock reports
+        RA_SP_SERVER_ARGS="--replay-dir ${RA_SP_REPLAY_DIR:-data/ra-sp-replay} --attestation-provider mock $RA_SP_SERVER_ARGS"
+    fi
+    (set +e; while true; do ra-sp-server --ias-key ${IAS_API_KEY:-offline} --quote-type Unlinkable --spid $SPID $RA_SP_SERVER_ARGS; done) &
     echo $! > data/ra-sp-server.pid
     wait_port 8989
 fi
@@ -23,8 +23,7 @@ use tendermint_rpc::endpoint::status;
 
 use chain_abci::app::ChainNodeState;
 use chain_abci::staking::StakingTable;
-use chain_core::common::{MerkleTree, Timespec};
-use chain_core::compute_app_hash;
+use chain_core::common::{MerkleTree, TendermintEventKey, TendermintEventType, Timespec, H256};
 use chain_core::init::config::NetworkParameters;
 use chain_core::init::{
     address::RedeemAddress, coin::Coin, config::InitConfig, network::Network, params,
@@ -36,6 +35,7 @@ use chain_core::state::tendermint::{
 use chain_core::state::ChainState;
 use chain_core::tx::fee::{LinearFee, Milli};
 use chain_core::tx::TxAux;
+use chain_core::{compute_app_hash, compute_filter_header};
 use chain_storage::buffer::MemStore;
 use chain_storage::jellyfish::{put_stakings, StakingGetter};
 use client_common::tendermint::types::{AbciQuery, BroadcastTxResponse, Genesis};
@@ -289,11 +289,14 @@ impl TestnetSpec {
             .expect("distribution validation error");
         let account_root = put_stakings(&mut store, 0, genesis_state.accounts.iter()).unwrap();
         let network_params = NetworkParameters::Genesis(config.network_params.clone());
+        // generated chains always commit the filter header chain (as if started at `FILTER_HEADER_APP_VERSION`)
+        let filter_header = H256::default();
         let app_hash = compute_app_hash(
             &MerkleTree::empty(),
             &account_root,
             &genesis_state.rewards_pool,
             &network_params,
+            Some(&filter_header),
         );
 
         let share = self.share();
@@ -340,7 +343,7 @@ impl TestnetSpec {
                 .collect::<Vec<_>>(),
         );
 
-        let state = ChainNodeState::genesis(
+        let mut state = ChainNodeState::genesis(
             app_hash,
             genesis_seconds,
             self.max_evidence_age,
@@ -350,6 +353,7 @@ impl TestnetSpec {
             staking_table,
             genesis_state.isv_svn,
         );
+        state.top_level.filter_header = Some(filter_header);
 
         (genesis, state)
     }
@@ -369,6 +373,8 @@ pub struct BlockState {
     pub block: block::Block,
     pub commit: block::Commit,
     pub state: ChainNodeState,
+    /// serialized Golomb-coded set emitted in end block events
+    pub block_filter: Option<Vec<u8>>,
 }
 
 impl BlockState {
@@ -504,10 +510,23 @@ impl BlockGenerator {
             block,
             commit,
             state,
+            block_filter: None,
         });
         self.current_height = Some(height);
     }
 
+    /// generates a block with the block filter, which updates the filter header (and the app hash)
+    pub fn gen_block_with_filter(&mut self, txs: &[TxAux], block_filter: Vec<u8>) {
+        self.gen_block(txs);
+        let block = self.blocks.last_mut().unwrap();
+        let top_level = &mut block.state.top_level;
+        top_level.filter_header = top_level
+            .filter_header
+            .map(|filter_header| compute_filter_header(&filter_header, &block_filter));
+        block.state.last_apphash = top_level.compute_app_hash(vec![]);
+        block.block_filter = Some(block_filter);
+    }
+
     pub fn signed_header(&self, height: Height) -> SignedHeader {
         self.blocks[(height.value() - 1) as usize].signed_header()
     }
@@ -552,11 +571,24 @@ impl Client for GeneratorClient {
     }
 
     fn block_results(&self, height: u64) -> Result<BlockResultsResponse> {
+        let end_block_events = self.gen.read().unwrap().blocks[height as usize - 1]
+            .block_filter
+            .as_ref()
+            .map(|block_filter| {
+                serde_json::from_value(serde_json::json!([{
+                    "type": TendermintEventType::BlockFilter.to_string(),
+                    "attributes": [{
+                        "key": TendermintEventKey::GolombCodedSet.to_base64_string(),
+                        "value": String::from_utf8(base64::encode(block_filter)).unwrap(),
+                    }],
+                }]))
+                .expect("block filter event")
+            });
         Ok(BlockResultsResponse {
             height: Height::from(height),
             txs_results: None,
             begin_block_events: None,
-            end_block_events: None,
+            end_block_events,
             validator_updates: vec![],
             consensus_param_updates: None,
         })
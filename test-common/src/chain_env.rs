@@ -14,7 +14,6 @@ use secp256k1::{
 use chain_abci::app::{BufferType, ChainNodeApp};
 use chain_abci::enclave_bridge::mock::MockClient;
 use chain_core::common::{MerkleTree, Timespec, H256};
-use chain_core::compute_app_hash;
 use chain_core::init::address::RedeemAddress;
 use chain_core::init::coin::Coin;
 use chain_core::init::config::{
@@ -33,6 +32,7 @@ use chain_core::state::validator::NodeJoinRequestTx;
 use chain_core::tx::fee::{LinearFee, Milli};
 use chain_core::tx::witness::EcdsaSignature;
 use chain_core::tx::{data::TxId, TransactionId, TxAux, TxPublicAux};
+use chain_core::{compute_app_hash, genesis_filter_header};
 use chain_storage::buffer::Get;
 use chain_storage::{Storage, NUM_COLUMNS};
 
@@ -313,6 +313,7 @@ impl ChainEnv {
             &new_account_root,
             &genesis_state.rewards_pool,
             &NetworkParameters::Genesis(init_network_params),
+            genesis_filter_header().as_ref(),
         );
         (
             ChainEnv {